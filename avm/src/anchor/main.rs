@@ -16,7 +16,7 @@ fn main() -> anyhow::Result<()> {
     }
 
     let exit = Command::new(binary_path)
-        .args(args)
+        .args(&args)
         .spawn()?
         .wait_with_output()
         .expect("Failed to run anchor-cli");
@@ -25,5 +25,13 @@ fn main() -> anyhow::Result<()> {
         std::process::exit(exit.status.code().unwrap_or(1));
     }
 
+    // Versions built from a commit also print where they come from
+    let is_version = matches!(args.as_slice(), [arg] if arg == "--version" || arg == "-V");
+    if is_version && !version.pre.is_empty() {
+        if let Some(source) = avm::read_install_source(&version) {
+            println!("Installed from {} at {}", source.url, source.rev);
+        }
+    }
+
     Ok(())
 }
@@ -4,13 +4,16 @@ use chrono::{TimeZone, Utc};
 use once_cell::sync::Lazy;
 use reqwest::header::USER_AGENT;
 use reqwest::StatusCode;
-use semver::{Prerelease, Version};
+use semver::{BuildMetadata, Prerelease, Version};
 use serde::{de, Deserialize};
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
+/// Repository Anchor is installed from by default.
+pub const ANCHOR_REPO: &str = "https://github.com/coral-xyz/anchor";
+
 /// Storage directory for AVM, customizable by setting the $AVM_HOME, defaults to ~/.avm
 pub static AVM_HOME: Lazy<PathBuf> = Lazy::new(|| {
     cfg_if::cfg_if! {
@@ -44,6 +47,11 @@ pub fn version_binary_path(version: &Version) -> PathBuf {
     get_bin_dir_path().join(format!("anchor-{version}"))
 }
 
+/// Path to the file recording where the given version was installed from
+fn install_source_path(version: &Version) -> PathBuf {
+    AVM_HOME.join("sources").join(version.to_string())
+}
+
 /// Ensure the users home directory is setup with the paths required by AVM.
 pub fn ensure_paths() {
     let home_dir = AVM_HOME.to_path_buf();
@@ -102,6 +110,35 @@ pub fn use_version(opt_version: Option<Version>) -> Result<()> {
 pub enum InstallTarget {
     Version(Version),
     Commit(String),
+    /// A revision of a git remote other than the Anchor repository, e.g. a fork
+    Remote {
+        url: String,
+        rev: String,
+    },
+}
+
+/// Git remote and revision an installed version was built from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstallSource {
+    pub url: String,
+    pub rev: String,
+}
+
+/// Read where the given version was installed from, if it was recorded
+pub fn read_install_source(version: &Version) -> Option<InstallSource> {
+    let source = fs::read_to_string(install_source_path(version)).ok()?;
+    let (url, rev) = source.trim().split_once(' ')?;
+    Some(InstallSource {
+        url: url.to_owned(),
+        rev: rev.to_owned(),
+    })
+}
+
+fn write_install_source(version: &Version, source: &InstallSource) -> Result<()> {
+    let path = install_source_path(version);
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, format!("{} {}", source.url, source.rev))?;
+    Ok(())
 }
 
 /// Update to the latest version
@@ -164,27 +201,103 @@ fn get_anchor_version_from_commit(commit: &str) -> Result<Version> {
     Ok(version)
 }
 
+/// Resolves a revision of a git remote to its full commit sha, and reads the anchor-cli version at
+/// that commit
+fn get_anchor_version_from_remote(url: &str, rev: &str) -> Result<(String, Version)> {
+    let repo = tempfile::tempdir()?;
+    let git = |args: &[&str], dir: &Path| -> Result<String> {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|e| anyhow!("Failed to run git: {e}"))?;
+        if !output.status.success() {
+            return Err(anyhow!("`git {}` failed", args.join(" ")));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    };
+
+    // Only the history is fetched, the files are read from it
+    let repo_path = repo.path().to_str().unwrap();
+    git(
+        &["clone", "--bare", "--filter=blob:none", url, repo_path],
+        Path::new("."),
+    )?;
+    let commit = git(&["rev-parse", &format!("{rev}^{{commit}}")], repo.path())
+        .map_err(|_| anyhow!("Revision {rev} not found in {url}"))?;
+    let anchor_cli_cargo_toml = git(&["show", &format!("{commit}:cli/Cargo.toml")], repo.path())
+        .map_err(|_| anyhow!("anchor-cli not found in {url} at {commit}"))?;
+
+    let anchor_cli_manifest = Manifest::from_str(&anchor_cli_cargo_toml)?;
+    let mut version = anchor_cli_manifest.package().version().parse::<Version>()?;
+    version.pre = Prerelease::new(&commit)?;
+    version.build = BuildMetadata::new(&remote_key(url))?;
+
+    Ok((commit, version))
+}
+
+/// Identifies a git remote in the build metadata of the versions installed from it, e.g. `my-org.anchor`
+/// for `https://github.com/my-org/anchor`, so that they don't clash with the versions of the Anchor
+/// repository
+fn remote_key(url: &str) -> String {
+    url.trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .take(2)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .map(|segment| {
+            segment
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect::<String>()
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
 /// Install a version of anchor-cli
 pub fn install_version(install_target: InstallTarget, force: bool) -> Result<()> {
-    let mut args: Vec<String> = vec![
+    // Releases are installed from their tag, even prereleases, the others from their commit
+    let (source, version, rev_flag) = match install_target {
+        InstallTarget::Version(version) => (
+            InstallSource {
+                url: ANCHOR_REPO.into(),
+                rev: format!("v{version}"),
+            },
+            version,
+            "--tag",
+        ),
+        InstallTarget::Commit(commit) => {
+            let version = get_anchor_version_from_commit(&commit)?;
+            (
+                InstallSource {
+                    url: ANCHOR_REPO.into(),
+                    rev: commit,
+                },
+                version,
+                "--rev",
+            )
+        }
+        InstallTarget::Remote { url, rev } => {
+            let (commit, version) = get_anchor_version_from_remote(&url, &rev)?;
+            (InstallSource { url, rev: commit }, version, "--rev")
+        }
+    };
+    let args: Vec<String> = vec![
         "install".into(),
         "--git".into(),
-        "https://github.com/coral-xyz/anchor".into(),
+        source.url.clone(),
+        rev_flag.into(),
+        source.rev.clone(),
         "anchor-cli".into(),
         "--locked".into(),
         "--root".into(),
         AVM_HOME.to_str().unwrap().into(),
     ];
-    let version = match install_target {
-        InstallTarget::Version(version) => {
-            args.extend(["--tag".into(), format!("v{}", version), "anchor-cli".into()]);
-            version
-        }
-        InstallTarget::Commit(commit) => {
-            args.extend(["--rev".into(), commit.clone()]);
-            get_anchor_version_from_commit(&commit)?
-        }
-    };
 
     // If version is already installed we ignore the request.
     let installed_versions = read_installed_versions()?;
@@ -211,6 +324,7 @@ pub fn install_version(install_target: InstallTarget, force: bool) -> Result<()>
         bin_dir.join("anchor"),
         bin_dir.join(format!("anchor-{version}")),
     )?;
+    write_install_source(&version, &source)?;
 
     // If .version file is empty or not parseable, write the newly installed version to it
     if current_version().is_err() {
@@ -231,6 +345,9 @@ pub fn uninstall_version(version: &Version) -> Result<()> {
         return Err(anyhow!("anchor-cli {} is currently in use", version));
     }
     fs::remove_file(version_path)?;
+    if install_source_path(version).exists() {
+        fs::remove_file(install_source_path(version))?;
+    }
 
     Ok(())
 }
@@ -435,6 +552,37 @@ mod tests {
         assert_eq!(read_installed_versions().unwrap(), expected);
     }
 
+    #[test]
+    fn test_install_source() {
+        ensure_paths();
+        let version = Version::parse("0.30.1-e1afcbf71e0f2e10fae14525934a6a68479167b9").unwrap();
+        assert_eq!(read_install_source(&version), None);
+
+        let source = InstallSource {
+            url: "https://github.com/fork/anchor".into(),
+            rev: "e1afcbf71e0f2e10fae14525934a6a68479167b9".into(),
+        };
+        write_install_source(&version, &source).unwrap();
+        assert_eq!(read_install_source(&version), Some(source));
+    }
+
+    #[test]
+    fn test_remote_key() {
+        assert_eq!(
+            remote_key("https://github.com/my-org/anchor"),
+            "my-org.anchor"
+        );
+        assert_eq!(
+            remote_key("https://github.com/my_org/anchor.git/"),
+            "my-org.anchor"
+        );
+        assert_eq!(
+            remote_key("git@github.com:my-org/anchor.git"),
+            "my-org.anchor"
+        );
+        assert!(BuildMetadata::new(&remote_key("https://github.com/my-org/anchor")).is_ok());
+    }
+
     #[test]
     fn test_get_anchor_version_from_commit() {
         let version =
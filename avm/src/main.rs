@@ -21,9 +21,11 @@ pub enum Commands {
     },
     #[clap(about = "Install a version of Anchor")]
     Install {
-        /// Anchor version or commit
-        #[clap(value_parser = parse_install_target)]
-        version_or_commit: InstallTarget,
+        /// Anchor version or commit, or the revision of the `--from` remote
+        version_or_commit: String,
+        /// Git remote to install Anchor from, e.g. a fork
+        #[clap(long)]
+        from: Option<String>,
         #[clap(long)]
         /// Flag to force installation even if the version
         /// is already installed
@@ -52,11 +54,14 @@ fn parse_version(version: &str) -> Result<Version, Error> {
 fn parse_install_target(version_or_commit: &str) -> Result<InstallTarget, Error> {
     parse_version(version_or_commit)
         .map(|version| {
-            if version.pre.is_empty() {
-                InstallTarget::Version(version)
-            } else {
-                // Allow `avm install 0.28.0-6cf200493a307c01487c7b492b4893e0d6f6cb23`
+            // Allow `avm install 0.28.0-6cf200493a307c01487c7b492b4893e0d6f6cb23`, other
+            // prereleases such as `0.31.0-rc.1` being releases
+            let is_commit =
+                version.pre.len() >= 7 && version.pre.chars().all(|c| c.is_ascii_hexdigit());
+            if is_commit {
                 InstallTarget::Commit(version.pre.to_string())
+            } else {
+                InstallTarget::Version(version)
             }
         })
         .or_else(|version_error| {
@@ -73,8 +78,18 @@ pub fn entry(opts: Cli) -> Result<()> {
         Commands::Use { version } => avm::use_version(version),
         Commands::Install {
             version_or_commit,
+            from,
             force,
-        } => avm::install_version(version_or_commit, force),
+        } => {
+            let install_target = match from {
+                Some(url) => InstallTarget::Remote {
+                    url,
+                    rev: version_or_commit,
+                },
+                None => parse_install_target(&version_or_commit)?,
+            };
+            avm::install_version(install_target, force)
+        }
         Commands::Uninstall { version } => avm::uninstall_version(&version),
         Commands::List {} => avm::list_versions(),
         Commands::Update {} => avm::update(),
@@ -0,0 +1,157 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{clock::Clock, sysvar};
+
+/// Method of the admin RPC that warps the test validator to a slot.
+pub const WARP_TO_SLOT: &str = "warpToSlot";
+/// Method of the admin RPC that warps the test validator to a unix timestamp.
+pub const WARP_TO_TIMESTAMP: &str = "warpToTimestamp";
+
+/// Environment variable of the tests with the URL of the admin RPC.
+pub const ADMIN_URL_ENV: &str = "ANCHOR_ADMIN_URL";
+
+/// Offset of the port of the admin RPC from the RPC port of the validator.
+pub const ADMIN_PORT_OFFSET: u16 = 2;
+
+/// Serves the admin RPC of the test validator whose RPC is at `rpc_url` until
+/// `stop` is set.
+///
+/// The validator can only warp when it starts, `warp` restarts it from its
+/// ledger at the given slot.
+pub fn serve(
+    listener: TcpListener,
+    rpc_url: &str,
+    stop: &AtomicBool,
+    mut warp: impl FnMut(u64) -> Result<()>,
+) -> Result<()> {
+    listener.set_nonblocking(true)?;
+    while !stop.load(Ordering::Relaxed) {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        stream.set_nonblocking(false)?;
+        if let Err(e) = handle(stream, rpc_url, &mut warp) {
+            eprintln!("Admin RPC request failed: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(
+    mut stream: TcpStream,
+    rpc_url: &str,
+    warp: &mut impl FnMut(u64) -> Result<()>,
+) -> Result<()> {
+    let request = read_request(&mut stream)?;
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let response = match warp_slot(&request, rpc_url).and_then(|slot| {
+        println!("Warping the test validator to slot {slot}");
+        warp(slot).map(|_| slot)
+    }) {
+        Ok(slot) => json!({ "jsonrpc": "2.0", "id": id, "result": slot }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32000, "message": e.to_string() },
+        }),
+    };
+
+    let body = response.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+        Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    Ok(())
+}
+
+/// Reads the JSON body of an HTTP request.
+fn read_request(stream: &mut TcpStream) -> Result<Value> {
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Returns the slot to warp to, which must be after the current slot.
+fn warp_slot(request: &Value, rpc_url: &str) -> Result<u64> {
+    let clock = current_clock(rpc_url)?;
+    let param = &request["params"][0];
+    let slot = match request["method"].as_str() {
+        Some(WARP_TO_SLOT) => param
+            .as_u64()
+            .ok_or_else(|| anyhow!("Expected the slot to warp to"))?,
+        Some(WARP_TO_TIMESTAMP) => {
+            let timestamp = param
+                .as_i64()
+                .ok_or_else(|| anyhow!("Expected the unix timestamp to warp to"))?;
+            timestamp_slot(&clock, timestamp)
+        }
+        method => return Err(anyhow!("Unknown method {method:?}")),
+    };
+
+    if slot <= clock.slot {
+        return Err(anyhow!(
+            "Unable to warp back to slot {slot}, the current slot is {}",
+            clock.slot
+        ));
+    }
+    Ok(slot)
+}
+
+fn current_clock(rpc_url: &str) -> Result<Clock> {
+    let account = RpcClient::new(rpc_url.to_owned()).get_account(&sysvar::clock::ID)?;
+    Ok(bincode::deserialize(&account.data)?)
+}
+
+/// Returns the slot at which the clock reaches the timestamp, with the default
+/// slot duration.
+fn timestamp_slot(clock: &Clock, timestamp: i64) -> u64 {
+    let ms = timestamp.saturating_sub(clock.unix_timestamp).max(0) as u64 * 1000;
+    clock.slot + ms / solana_sdk::clock::DEFAULT_MS_PER_SLOT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_slot() {
+        let clock = Clock {
+            slot: 100,
+            unix_timestamp: 1_700_000_000,
+            ..Default::default()
+        };
+        assert_eq!(timestamp_slot(&clock, 1_700_000_004), 110);
+        assert_eq!(timestamp_slot(&clock, 1_600_000_000), 100);
+    }
+}
@@ -37,6 +37,9 @@ pub struct ConfigOverride {
     /// Wallet override.
     #[clap(global = true, long = "provider.wallet")]
     pub wallet: Option<WalletPath>,
+    /// Profile of Anchor.toml to use.
+    #[clap(global = true, long)]
+    pub profile: Option<String>,
 }
 
 #[derive(Debug)]
@@ -359,6 +362,7 @@ pub struct Config {
     pub features: FeaturesConfig,
     pub registry: RegistryConfig,
     pub provider: ProviderConfig,
+    pub deploy: DeployConfig,
     pub programs: ProgramsConfig,
     pub scripts: ScriptsConfig,
     pub workspace: WorkspaceConfig,
@@ -367,6 +371,10 @@ pub struct Config {
     // not the Test.toml files
     pub test_validator: Option<TestValidator>,
     pub test_config: Option<TestConfig>,
+    pub profiles: ProfilesConfig,
+    /// Name of the selected profile.
+    pub profile: Option<String>,
+    pub program: ProgramConfig,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
@@ -415,6 +423,16 @@ impl Default for RegistryConfig {
     }
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeployConfig {
+    /// Compute unit price, in micro-lamports, of the deploy transactions.
+    pub priority_fee: Option<u64>,
+    /// Number of times a failed deploy is retried, resuming from the
+    /// partially written buffer.
+    pub max_retries: Option<u32>,
+}
+
 #[derive(Debug, Default)]
 pub struct ProviderConfig {
     pub cluster: Cluster,
@@ -423,8 +441,86 @@ pub struct ProviderConfig {
 
 pub type ScriptsConfig = BTreeMap<String, String>;
 
+pub type ProfilesConfig = BTreeMap<String, ProfileConfig>;
+
+/// Settings of an environment, e.g. `[profile.staging]`, selected with
+/// `--profile`.
+#[derive(Debug, Default, Clone)]
+pub struct ProfileConfig {
+    pub cluster: Option<Cluster>,
+    pub wallet: Option<WalletPath>,
+    /// Program ids on the cluster of the profile.
+    pub programs: BTreeMap<String, ProgramDeployment>,
+    /// Cargo features enabled when building the programs.
+    pub features: Vec<String>,
+    /// Environment variables exported to the scripts.
+    pub env: BTreeMap<String, String>,
+}
+
 pub type ProgramsConfig = BTreeMap<Cluster, BTreeMap<String, ProgramDeployment>>;
 
+/// Settings shared by the programs of the workspace.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ProgramConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<SecurityConfig>,
+}
+
+/// Security contact info embedded in the program binaries, following the
+/// `solana-security-txt` convention.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SecurityConfig {
+    pub project_url: String,
+    /// Contacts, e.g. `email:security@example.com` or `discord:handle`.
+    pub contacts: Vec<String>,
+    /// URL or text of the security policy.
+    pub policy: String,
+    /// Names of the auditors of the programs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audits: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preferred_languages: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledgements: Option<String>,
+    /// Date after which the info is outdated, e.g. `2030-01-01`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<String>,
+}
+
+impl SecurityConfig {
+    /// Returns the fields of the security.txt, keyed as in the convention.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("project_url", self.project_url.clone()),
+            ("contacts", self.contacts.join(",")),
+            ("policy", self.policy.clone()),
+        ];
+        if !self.preferred_languages.is_empty() {
+            fields.push(("preferred_languages", self.preferred_languages.join(",")));
+        }
+        let optional = [
+            ("encryption", &self.encryption),
+            ("source_code", &self.source_code),
+            ("acknowledgements", &self.acknowledgements),
+            ("expiry", &self.expiry),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                fields.push((name, value.clone()));
+            }
+        }
+        if !self.audits.is_empty() {
+            fields.push(("auditors", self.audits.join(",")));
+        }
+        fields
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct WorkspaceConfig {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -455,12 +551,42 @@ impl ProgramArch {
     }
 }
 
+/// Multisig holding the upgrade authority of a program, parsed from
+/// `<kind>:<address>`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Multisig {
+    /// Squads vault, e.g. `squads:<vault>`.
+    Squads(Pubkey),
+}
+
+impl Multisig {
+    /// Returns the address signing for the multisig.
+    pub fn authority(&self) -> Pubkey {
+        match self {
+            Self::Squads(vault) => *vault,
+        }
+    }
+}
+
+impl FromStr for Multisig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("squads", vault)) => Ok(Self::Squads(vault.parse()?)),
+            _ => Err(anyhow!("Invalid multisig `{s}`, expected `squads:<vault>`")),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BuildConfig {
     pub verifiable: bool,
     pub solana_version: Option<String>,
     pub docker_image: String,
     pub bootstrap: BootstrapMode,
+    /// Print the size report of the binaries.
+    pub size_report: bool,
 }
 
 impl Config {
@@ -483,17 +609,72 @@ impl Config {
     }
 
     pub fn discover(cfg_override: &ConfigOverride) -> Result<Option<WithPath<Config>>> {
-        Config::_discover().map(|opt| {
-            opt.map(|mut cfg| {
+        match Config::_discover()? {
+            Some(mut cfg) => {
+                if let Some(profile) = &cfg_override.profile {
+                    cfg.apply_profile(profile)?;
+                }
                 if let Some(cluster) = cfg_override.cluster.clone() {
                     cfg.provider.cluster = cluster;
                 }
                 if let Some(wallet) = cfg_override.wallet.clone() {
                     cfg.provider.wallet = wallet;
                 }
-                cfg
-            })
-        })
+                Ok(Some(cfg))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Overrides the provider and the program ids with the ones of the profile.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| anyhow!("Profile `{name}` not found in Anchor.toml"))?
+            .clone();
+        if let Some(cluster) = profile.cluster {
+            self.provider.cluster = cluster;
+        }
+        if let Some(wallet) = profile.wallet {
+            self.provider.wallet = wallet;
+        }
+        if !profile.programs.is_empty() {
+            self.programs
+                .entry(self.provider.cluster.clone())
+                .or_default()
+                .extend(profile.programs);
+        }
+        self.profile = Some(name.to_owned());
+        Ok(())
+    }
+
+    /// Returns the selected profile.
+    pub fn active_profile(&self) -> Option<&ProfileConfig> {
+        self.profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+
+    /// Returns the environment variables of the selected profile.
+    pub fn profile_env(&self) -> BTreeMap<String, String> {
+        let mut env = BTreeMap::new();
+        if let (Some(name), Some(profile)) = (&self.profile, self.active_profile()) {
+            env.insert("ANCHOR_PROFILE".to_owned(), name.clone());
+            env.extend(profile.env.clone());
+        }
+        env
+    }
+
+    /// Appends the features of the selected profile to the cargo arguments.
+    pub fn profile_cargo_args(&self, mut cargo_args: Vec<String>) -> Vec<String> {
+        if let Some(profile) = self.active_profile() {
+            if !profile.features.is_empty() {
+                cargo_args.push("--features".to_owned());
+                cargo_args.push(profile.features.join(","));
+            }
+        }
+        cargo_args
     }
 
     // Climbs each parent directory until we find an Anchor.toml.
@@ -551,9 +732,70 @@ struct _Config {
     programs: Option<BTreeMap<String, BTreeMap<String, serde_json::Value>>>,
     registry: Option<RegistryConfig>,
     provider: Provider,
+    deploy: Option<DeployConfig>,
     workspace: Option<WorkspaceConfig>,
     scripts: Option<ScriptsConfig>,
     test: Option<_TestValidator>,
+    profile: Option<BTreeMap<String, _Profile>>,
+    program: Option<ProgramConfig>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct _Profile {
+    #[serde(
+        default,
+        deserialize_with = "des_cluster_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    cluster: Option<Cluster>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wallet: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    programs: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    features: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+}
+
+impl TryFrom<_Profile> for ProfileConfig {
+    type Error = Error;
+
+    fn try_from(profile: _Profile) -> Result<Self> {
+        Ok(Self {
+            cluster: profile.cluster,
+            wallet: profile
+                .wallet
+                .map(|wallet| shellexpand::tilde(&wallet).parse())
+                .transpose()?,
+            programs: profile
+                .programs
+                .iter()
+                .map(|(name, program)| Ok((name.clone(), deser_program(program)?)))
+                .collect::<Result<_>>()?,
+            features: profile.features,
+            env: profile.env,
+        })
+    }
+}
+
+impl From<&ProfileConfig> for _Profile {
+    fn from(profile: &ProfileConfig) -> Self {
+        Self {
+            cluster: profile.cluster.clone(),
+            wallet: profile
+                .wallet
+                .as_ref()
+                .map(|wallet| wallet.stringify_with_tilde()),
+            programs: profile
+                .programs
+                .iter()
+                .map(|(name, program)| (name.clone(), to_value(&_ProgramDeployment::from(program))))
+                .collect(),
+            features: profile.features.clone(),
+            env: profile.env.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -610,6 +852,16 @@ where
     deserializer.deserialize_any(StringOrCustomCluster(PhantomData))
 }
 
+fn des_cluster_opt<'de, D>(deserializer: D) -> Result<Option<Cluster>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct ClusterEntry(#[serde(deserialize_with = "des_cluster")] Cluster);
+
+    Option::<ClusterEntry>::deserialize(deserializer).map(|entry| entry.map(|entry| entry.0))
+}
+
 impl fmt::Display for Config {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let programs = {
@@ -628,6 +880,8 @@ impl fmt::Display for Config {
                 cluster: self.provider.cluster.clone(),
                 wallet: self.provider.wallet.stringify_with_tilde(),
             },
+            deploy: (self.deploy.priority_fee.is_some() || self.deploy.max_retries.is_some())
+                .then(|| self.deploy.clone()),
             test: self.test_validator.clone().map(Into::into),
             scripts: match self.scripts.is_empty() {
                 true => None,
@@ -636,6 +890,17 @@ impl fmt::Display for Config {
             programs,
             workspace: (!self.workspace.members.is_empty() || !self.workspace.exclude.is_empty())
                 .then(|| self.workspace.clone()),
+            profile: (!self.profiles.is_empty()).then(|| {
+                self.profiles
+                    .iter()
+                    .map(|(name, profile)| (name.clone(), profile.into()))
+                    .collect()
+            }),
+            program: self
+                .program
+                .security
+                .is_some()
+                .then(|| self.program.clone()),
         };
 
         let cfg = toml::to_string(&cfg).expect("Must be well formed");
@@ -657,11 +922,20 @@ impl FromStr for Config {
                 cluster: cfg.provider.cluster,
                 wallet: shellexpand::tilde(&cfg.provider.wallet).parse()?,
             },
+            deploy: cfg.deploy.unwrap_or_default(),
             scripts: cfg.scripts.unwrap_or_default(),
             test_validator: cfg.test.map(Into::into),
             test_config: None,
             programs: cfg.programs.map_or(Ok(BTreeMap::new()), deser_programs)?,
             workspace: cfg.workspace.unwrap_or_default(),
+            profiles: cfg
+                .profile
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, profile)| Ok((name, profile.try_into()?)))
+                .collect::<Result<_>>()?,
+            profile: None,
+            program: cfg.program.unwrap_or_default(),
         })
     }
 }
@@ -676,6 +950,16 @@ pub fn get_solana_cfg_url() -> Result<String, io::Error> {
     SolanaConfig::load(config_file).map(|config| config.json_rpc_url)
 }
 
+pub fn get_solana_cfg_keypair_path() -> Result<String, io::Error> {
+    let config_file = CONFIG_FILE.as_ref().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Default Solana config was not found",
+        )
+    })?;
+    SolanaConfig::load(config_file).map(|config| config.keypair_path)
+}
+
 fn ser_programs(
     programs: &BTreeMap<Cluster, BTreeMap<String, ProgramDeployment>>,
 ) -> BTreeMap<String, BTreeMap<String, serde_json::Value>> {
@@ -713,33 +997,32 @@ fn deser_programs(
             let cluster: Cluster = cluster.parse()?;
             let programs = programs
                 .iter()
-                .map(|(name, program_id)| {
-                    Ok((
-                        name.clone(),
-                        ProgramDeployment::try_from(match &program_id {
-                            serde_json::Value::String(address) => _ProgramDeployment {
-                                address: address.parse()?,
-                                path: None,
-                                idl: None,
-                            },
-
-                            serde_json::Value::Object(_) => {
-                                serde_json::from_value(program_id.clone())
-                                    .map_err(|_| anyhow!("Unable to read toml"))?
-                            }
-                            _ => return Err(anyhow!("Invalid toml type")),
-                        })?,
-                    ))
-                })
+                .map(|(name, program_id)| Ok((name.clone(), deser_program(program_id)?)))
                 .collect::<Result<BTreeMap<String, ProgramDeployment>>>()?;
             Ok((cluster, programs))
         })
         .collect::<Result<BTreeMap<Cluster, BTreeMap<String, ProgramDeployment>>>>()
 }
 
+fn deser_program(program_id: &serde_json::Value) -> Result<ProgramDeployment> {
+    ProgramDeployment::try_from(match program_id {
+        serde_json::Value::String(address) => _ProgramDeployment {
+            address: address.parse()?,
+            path: None,
+            idl: None,
+        },
+
+        serde_json::Value::Object(_) => serde_json::from_value(program_id.clone())
+            .map_err(|_| anyhow!("Unable to read toml"))?,
+        _ => return Err(anyhow!("Invalid toml type")),
+    })
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct TestValidator {
     pub genesis: Option<Vec<GenesisEntry>>,
+    pub fixtures: Option<Vec<FixtureEntry>>,
+    pub wallets: Option<TestWallets>,
     pub validator: Option<Validator>,
     pub startup_wait: i32,
     pub shutdown_wait: i32,
@@ -751,6 +1034,10 @@ pub struct _TestValidator {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub genesis: Option<Vec<GenesisEntry>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixtures: Option<Vec<FixtureEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallets: Option<TestWallets>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub validator: Option<_Validator>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub startup_wait: Option<i32>,
@@ -769,6 +1056,8 @@ impl From<_TestValidator> for TestValidator {
             shutdown_wait: _test_validator.shutdown_wait.unwrap_or(SHUTDOWN_WAIT),
             startup_wait: _test_validator.startup_wait.unwrap_or(STARTUP_WAIT),
             genesis: _test_validator.genesis,
+            fixtures: _test_validator.fixtures,
+            wallets: _test_validator.wallets,
             validator: _test_validator.validator.map(Into::into),
             upgradeable: _test_validator.upgradeable.unwrap_or(false),
         }
@@ -781,6 +1070,8 @@ impl From<TestValidator> for _TestValidator {
             shutdown_wait: Some(test_validator.shutdown_wait),
             startup_wait: Some(test_validator.startup_wait),
             genesis: test_validator.genesis,
+            fixtures: test_validator.fixtures,
+            wallets: test_validator.wallets,
             validator: test_validator.validator.map(Into::into),
             upgradeable: Some(test_validator.upgradeable),
         }
@@ -859,6 +1150,10 @@ impl _TestToml {
                 if let Some(ledger_dir) = &mut validator.ledger {
                     *ledger_dir = canonicalize_filepath_from_origin(&ledger_dir, &path)?;
                 }
+                if let Some(geyser_plugin_config) = &mut validator.geyser_plugin_config {
+                    *geyser_plugin_config =
+                        canonicalize_filepath_from_origin(&geyser_plugin_config, &path)?;
+                }
                 if let Some(accounts) = &mut validator.account {
                     for entry in accounts {
                         entry.filename = canonicalize_filepath_from_origin(&entry.filename, &path)?;
@@ -944,6 +1239,15 @@ impl Merge for _TestToml {
                             None => my_test.genesis = Some(other_genesis),
                         }
                     }
+                    if let Some(other_fixtures) = other_test.fixtures {
+                        my_test
+                            .fixtures
+                            .get_or_insert_with(Vec::new)
+                            .extend(other_fixtures);
+                    }
+                    if let Some(wallets) = other_test.wallets {
+                        my_test.wallets = Some(wallets);
+                    }
                     let mut my_validator = my_test.validator.take();
                     match &mut my_validator {
                         None => my_validator = other_test.validator,
@@ -995,10 +1299,101 @@ pub struct GenesisEntry {
     pub upgradeable: Option<bool>,
 }
 
+/// Accounts created when the test validator starts, declared either inline or
+/// in a JSON or YAML file listing them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FixtureEntry {
+    File { file: String },
+    Account(Fixture),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    // Base58 pubkey string.
+    pub address: String,
+    // Balance of the account, rent exempt by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lamports: Option<u64>,
+    #[serde(flatten)]
+    pub kind: FixtureKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureKind {
+    /// Mint of the token program.
+    Mint {
+        decimals: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mint_authority: Option<String>,
+        #[serde(default)]
+        supply: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        freeze_authority: Option<String>,
+    },
+    /// Token account of the token program.
+    TokenAccount {
+        mint: String,
+        owner: String,
+        #[serde(default)]
+        amount: u64,
+    },
+    /// Account of a program, encoded with the program's IDL.
+    Account {
+        // Name of the program in the workspace or path to the IDL of the
+        // program.
+        program: String,
+        // Name of the account in the IDL.
+        #[serde(rename = "type")]
+        ty: String,
+        // Values of the fields of the account.
+        #[serde(default)]
+        data: serde_json::Value,
+    },
+}
+
+/// Wallets generated and funded before the tests run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestWallets {
+    // Number of wallets.
+    pub count: usize,
+    // Balance each wallet is funded with, 10 SOL by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lamports: Option<u64>,
+    // Base58 pubkey strings of the mints of which each wallet gets an
+    // associated token account.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mints: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloneEntry {
     // Base58 pubkey string.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub address: String,
+    // Whether to also clone the accounts owned by the program at `address`.
+    #[serde(default)]
+    pub program_accounts: bool,
+    // Filters of the cloned program accounts, as with `getProgramAccounts`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Vec<CloneFilter>>,
+    // File listing the addresses of the accounts to clone, one per line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    // Whether to cache the accounts in `.anchor/clone-cache` instead of
+    // fetching them every time the validator starts.
+    #[serde(default)]
+    pub cache: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloneFilter {
+    // Size of the account data.
+    DataSize(u64),
+    // Base58 encoded bytes of the account data at the given offset.
+    Memcmp { offset: usize, bytes: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1218,10 +1613,10 @@ impl Merge for _Validator {
                     None => Some(entries),
                     Some(other_entries) => {
                         for other_entry in other_entries {
-                            match entries
-                                .iter()
-                                .position(|my_entry| *my_entry.address == other_entry.address)
-                            {
+                            match entries.iter().position(|my_entry| {
+                                my_entry.address == other_entry.address
+                                    && my_entry.file == other_entry.file
+                            }) {
                                 None => entries.push(other_entry),
                                 Some(i) => entries[i] = other_entry,
                             };
@@ -1312,7 +1707,7 @@ impl Program {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct ProgramDeployment {
     pub address: Pubkey,
     pub path: Option<String>,
@@ -1431,6 +1826,58 @@ mod tests {
         wallet = \"id.json\"
     ";
 
+    #[test]
+    fn parse_profile() {
+        let string = BASE_CONFIG.to_owned()
+            + "
+            [profile.staging]
+            cluster = \"devnet\"
+            wallet = \"staging.json\"
+            features = [\"staging\"]
+
+            [profile.staging.programs]
+            counter = \"Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS\"
+
+            [profile.staging.env]
+            RPC_TIMEOUT = \"30\"
+        ";
+        let mut config = Config::from_str(&string).unwrap();
+        config.apply_profile("staging").unwrap();
+        assert_eq!(config.provider.cluster, Cluster::Devnet);
+        assert_eq!(config.provider.wallet.to_string(), "staging.json");
+        assert!(config.programs[&Cluster::Devnet].contains_key("counter"));
+        assert_eq!(config.profile_cargo_args(vec![]), ["--features", "staging"]);
+        assert_eq!(config.profile_env()["RPC_TIMEOUT"], "30");
+        assert_eq!(config.profile_env()["ANCHOR_PROFILE"], "staging");
+        assert!(config.apply_profile("prod").is_err());
+    }
+
+    #[test]
+    fn parse_program_security() {
+        let string = BASE_CONFIG.to_owned()
+            + "
+            [program.security]
+            project-url = \"https://example.com\"
+            contacts = [\"email:security@example.com\", \"discord:example\"]
+            policy = \"https://example.com/security\"
+            audits = [\"Neodyme\"]
+        ";
+        let config = Config::from_str(&string).unwrap();
+        let security = config.program.security.unwrap();
+        assert_eq!(
+            security.fields(),
+            [
+                ("project_url", "https://example.com".to_owned()),
+                (
+                    "contacts",
+                    "email:security@example.com,discord:example".to_owned()
+                ),
+                ("policy", "https://example.com/security".to_owned()),
+                ("auditors", "Neodyme".to_owned()),
+            ]
+        );
+    }
+
     #[test]
     fn parse_custom_cluster() {
         let config = Config::from_str(CUSTOM_CONFIG).unwrap();
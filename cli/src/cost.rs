@@ -0,0 +1,184 @@
+use std::{collections::BTreeMap, fmt, fmt::Write};
+
+use anchor_lang_idl::types::{Idl, IdlInstructionAccountItem};
+use heck::ToUpperCamelCase;
+use serde::Serialize;
+
+use crate::fuzz;
+
+/// Directory of the cost estimation crate, relative to the target directory.
+pub const COST_DIR: &str = "cost";
+
+/// Returns the manifest of the cost estimation crate, with a binary per program.
+pub fn manifest(programs: &[String]) -> String {
+    let mut manifest = r#"[package]
+name = "cost"
+version = "0.0.0"
+publish = false
+edition = "2021"
+
+[dependencies]
+arbitrary = { version = "1", features = ["derive"] }
+borsh = "0.10"
+litesvm = "0.1"
+solana-sdk = "1.18"
+
+# Not part of the workspace of the programs
+[workspace]
+members = ["."]
+"#
+    .to_owned();
+    for program in programs {
+        let _ = write!(
+            manifest,
+            r#"
+[[bin]]
+name = "{program}"
+path = "src/{program}.rs"
+"#
+        );
+    }
+    manifest
+}
+
+/// Returns the binary executing every instruction of a program once, in the
+/// order of the IDL, and printing the compute units it consumed.
+///
+/// Arguments are zeroed and accounts are distinct, so that instructions
+/// initializing accounts usually come first and set up the next ones.
+pub fn scenarios(program: &str, idl: &Idl) -> String {
+    let mut scenarios = String::new();
+    for ix in &idl.instructions {
+        let mut fields = vec![];
+        let accounts = count_accounts(&ix.accounts);
+        if accounts > 0 {
+            let indexes = (0..accounts).map(|i| i.to_string()).collect::<Vec<_>>();
+            fields.push(format!("accounts: [{}]", indexes.join(", ")));
+        }
+        for arg in &ix.args {
+            fields.push(format!("{}: zeroed()", arg.name));
+        }
+        let _ = writeln!(
+            scenarios,
+            "        (\"{}\", HarnessInstruction::{} {{ {} }}),",
+            ix.name,
+            ix.name.to_upper_camel_case(),
+            fields.join(", ")
+        );
+    }
+
+    format!(
+        r#"//! Compute units of the `{program}` program, generated by `anchor cost`.
+{harness}
+use solana_sdk::transaction::Transaction;
+
+fn zeroed<T: for<'a> Arbitrary<'a>>() -> T {{
+    T::arbitrary(&mut arbitrary::Unstructured::new(&[])).unwrap()
+}}
+
+fn main() {{
+    let mut harness = Harness::new();
+    let scenarios = vec![
+{scenarios}    ];
+    for (name, ix) in scenarios {{
+        let (instruction, signers) = harness.instruction(&ix);
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&signers[0].pubkey()),
+            &signers,
+            harness.svm.latest_blockhash(),
+        );
+        match harness.svm.send_transaction(tx) {{
+            Ok(meta) => println!("{{name}}\t{{}}\t", meta.compute_units_consumed),
+            Err(failed) => println!(
+                "{{name}}\t{{}}\t{{:?}}",
+                failed.meta.compute_units_consumed, failed.err
+            ),
+        }}
+        harness.svm.expire_blockhash();
+    }}
+}}
+"#,
+        harness = fuzz::harness(program, idl, "../../deploy"),
+    )
+}
+
+/// Compute units consumed by the instructions of a program.
+#[derive(Debug, Default, Serialize)]
+pub struct CostReport {
+    pub instructions: BTreeMap<String, InstructionCost>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstructionCost {
+    pub units: u64,
+    /// Error of the transaction, the units being the ones consumed until then.
+    pub error: Option<String>,
+}
+
+impl CostReport {
+    /// Parses the output of the binary of [`scenarios`].
+    pub fn from_output(output: &str) -> Self {
+        let mut report = Self::default();
+        for line in output.lines() {
+            let mut columns = line.splitn(3, '\t');
+            let (name, units, error) = match (columns.next(), columns.next(), columns.next()) {
+                (Some(name), Some(units), Some(error)) => (name, units, error),
+                _ => continue,
+            };
+            let units = match units.parse() {
+                Ok(units) => units,
+                Err(_) => continue,
+            };
+            report.instructions.insert(
+                name.to_owned(),
+                InstructionCost {
+                    units,
+                    error: (!error.is_empty()).then(|| error.to_owned()),
+                },
+            );
+        }
+        report
+    }
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<32}{:>14}  Result", "Instruction", "Compute units")?;
+        for (name, cost) in &self.instructions {
+            let result = match &cost.error {
+                Some(error) => format!("failed: {error}"),
+                None => "ok".to_owned(),
+            };
+            writeln!(f, "{name:<32}{:>14}  {result}", cost.units)?;
+        }
+        Ok(())
+    }
+}
+
+fn count_accounts(accounts: &[IdlInstructionAccountItem]) -> usize {
+    accounts
+        .iter()
+        .map(|acc| match acc {
+            IdlInstructionAccountItem::Single(_) => 1,
+            IdlInstructionAccountItem::Composite(accs) => count_accounts(&accs.accounts),
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_report() {
+        let output = "initialize\t4521\t\ndeposit\t1200\tInstructionError(0, Custom(3012))\n";
+        let report = CostReport::from_output(output);
+        assert_eq!(report.instructions["initialize"].units, 4521);
+        assert!(report.instructions["initialize"].error.is_none());
+        assert_eq!(
+            report.instructions["deposit"].error.as_deref(),
+            Some("InstructionError(0, Custom(3012))")
+        );
+    }
+}
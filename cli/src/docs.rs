@@ -0,0 +1,354 @@
+use std::fmt::Write;
+
+use anchor_lang_idl::types::{
+    Idl, IdlArrayLen, IdlDefinedFields, IdlGenericArg, IdlInstructionAccountItem, IdlSeed, IdlType,
+    IdlTypeDef, IdlTypeDefGeneric, IdlTypeDefTy,
+};
+
+/// Renders the reference documentation of a program from its IDL as markdown.
+pub fn markdown(idl: &Idl) -> String {
+    let mut md = String::new();
+    let _ = write_program(&mut md, idl);
+    md
+}
+
+/// Renders markdown as a standalone HTML page.
+pub fn html(title: &str, md: &str) -> String {
+    let mut body = String::new();
+    let parser = pulldown_cmark::Parser::new_ext(md, pulldown_cmark::Options::ENABLE_TABLES);
+    pulldown_cmark::html::push_html(&mut body, parser);
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 960px; margin: auto; padding: 0 16px; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; }}
+code {{ background: #f4f4f4; }}
+</style>
+</head>
+<body>
+{body}</body>
+</html>
+"#
+    )
+}
+
+fn write_program(md: &mut String, idl: &Idl) -> std::fmt::Result {
+    writeln!(md, "# {}\n", idl.metadata.name)?;
+    if let Some(description) = &idl.metadata.description {
+        writeln!(md, "{description}\n")?;
+    }
+    write_docs(md, &idl.docs)?;
+    writeln!(md, "- Program ID: `{}`", idl.address)?;
+    writeln!(md, "- Version: `{}`\n", idl.metadata.version)?;
+
+    if !idl.instructions.is_empty() {
+        writeln!(md, "## Instructions\n")?;
+    }
+    for ix in &idl.instructions {
+        writeln!(md, "### `{}`\n", ix.name)?;
+        write_docs(md, &ix.docs)?;
+
+        if !ix.args.is_empty() {
+            writeln!(md, "Arguments:\n")?;
+            writeln!(md, "| Name | Type | Description |")?;
+            writeln!(md, "| --- | --- | --- |")?;
+            for arg in &ix.args {
+                writeln!(
+                    md,
+                    "| `{}` | `{}` | {} |",
+                    arg.name,
+                    type_name(&arg.ty),
+                    cell(&arg.docs.join(" "))
+                )?;
+            }
+            writeln!(md)?;
+        }
+
+        if !ix.accounts.is_empty() {
+            writeln!(md, "Accounts:\n")?;
+            writeln!(md, "| Name | Constraints | Description |")?;
+            writeln!(md, "| --- | --- | --- |")?;
+            write_accounts(md, &ix.accounts, "")?;
+            writeln!(md)?;
+        }
+
+        if let Some(returns) = &ix.returns {
+            writeln!(md, "Returns `{}`.\n", type_name(returns))?;
+        }
+    }
+
+    let type_def = |name: &str| idl.types.iter().find(|ty| ty.name == name);
+    if !idl.accounts.is_empty() {
+        writeln!(md, "## Accounts\n")?;
+    }
+    for acc in &idl.accounts {
+        writeln!(md, "### `{}`\n", acc.name)?;
+        writeln!(md, "Discriminator: `{:?}`\n", acc.discriminator)?;
+        if let Some(ty) = type_def(&acc.name) {
+            write_type_def(md, ty)?;
+        }
+    }
+
+    if !idl.events.is_empty() {
+        writeln!(md, "## Events\n")?;
+    }
+    for event in &idl.events {
+        writeln!(md, "### `{}`\n", event.name)?;
+        if let Some(ty) = type_def(&event.name) {
+            write_type_def(md, ty)?;
+        }
+    }
+
+    let types = idl
+        .types
+        .iter()
+        .filter(|ty| {
+            !idl.accounts.iter().any(|acc| acc.name == ty.name)
+                && !idl.events.iter().any(|event| event.name == ty.name)
+        })
+        .collect::<Vec<_>>();
+    if !types.is_empty() {
+        writeln!(md, "## Types\n")?;
+    }
+    for ty in types {
+        writeln!(md, "### `{}`\n", type_def_name(ty))?;
+        write_type_def(md, ty)?;
+    }
+
+    if !idl.errors.is_empty() {
+        writeln!(md, "## Errors\n")?;
+        writeln!(md, "| Code | Name | Message |")?;
+        writeln!(md, "| --- | --- | --- |")?;
+        for error in &idl.errors {
+            writeln!(
+                md,
+                "| {} | `{}` | {} |",
+                error.code,
+                error.name,
+                cell(error.msg.as_deref().unwrap_or_default())
+            )?;
+        }
+        writeln!(md)?;
+    }
+
+    if !idl.constants.is_empty() {
+        writeln!(md, "## Constants\n")?;
+        writeln!(md, "| Name | Type | Value | Description |")?;
+        writeln!(md, "| --- | --- | --- | --- |")?;
+        for constant in &idl.constants {
+            writeln!(
+                md,
+                "| `{}` | `{}` | `{}` | {} |",
+                constant.name,
+                type_name(&constant.ty),
+                cell(&constant.value),
+                cell(&constant.docs.join(" "))
+            )?;
+        }
+        writeln!(md)?;
+    }
+
+    Ok(())
+}
+
+fn write_accounts(
+    md: &mut String,
+    accounts: &[IdlInstructionAccountItem],
+    prefix: &str,
+) -> std::fmt::Result {
+    for acc in accounts {
+        match acc {
+            IdlInstructionAccountItem::Single(acc) => {
+                let mut constraints = vec![];
+                if acc.writable {
+                    constraints.push("mut".to_owned());
+                }
+                if acc.signer {
+                    constraints.push("signer".to_owned());
+                }
+                if acc.optional {
+                    constraints.push("optional".to_owned());
+                }
+                if let Some(address) = &acc.address {
+                    constraints.push(format!("address = `{address}`"));
+                }
+                if let Some(pda) = &acc.pda {
+                    let seeds = pda.seeds.iter().map(seed).collect::<Vec<_>>().join(", ");
+                    constraints.push(format!("seeds = [{seeds}]"));
+                    if let Some(program) = &pda.program {
+                        constraints.push(format!("seeds::program = {}", seed(program)));
+                    }
+                }
+                for relation in &acc.relations {
+                    constraints.push(format!("has_one = `{relation}`"));
+                }
+
+                writeln!(
+                    md,
+                    "| `{prefix}{}` | {} | {} |",
+                    acc.name,
+                    cell(&constraints.join(", ")),
+                    cell(&acc.docs.join(" "))
+                )?;
+            }
+            IdlInstructionAccountItem::Composite(accs) => {
+                write_accounts(md, &accs.accounts, &format!("{prefix}{}.", accs.name))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_type_def(md: &mut String, ty: &IdlTypeDef) -> std::fmt::Result {
+    write_docs(md, &ty.docs)?;
+    match &ty.ty {
+        IdlTypeDefTy::Struct { fields } => write_fields(md, fields.as_ref())?,
+        IdlTypeDefTy::Enum { variants } => {
+            writeln!(md, "| Variant | Fields |")?;
+            writeln!(md, "| --- | --- |")?;
+            for variant in variants {
+                let fields = match &variant.fields {
+                    Some(IdlDefinedFields::Named(fields)) => fields
+                        .iter()
+                        .map(|field| format!("`{}: {}`", field.name, type_name(&field.ty)))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    Some(IdlDefinedFields::Tuple(fields)) => fields
+                        .iter()
+                        .map(|ty| format!("`{}`", type_name(ty)))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    None => String::new(),
+                };
+                writeln!(md, "| `{}` | {} |", variant.name, cell(&fields))?;
+            }
+            writeln!(md)?;
+        }
+        IdlTypeDefTy::Type { alias } => writeln!(md, "Alias of `{}`.\n", type_name(alias))?,
+    }
+
+    Ok(())
+}
+
+fn write_fields(md: &mut String, fields: Option<&IdlDefinedFields>) -> std::fmt::Result {
+    match fields {
+        Some(IdlDefinedFields::Named(fields)) => {
+            writeln!(md, "| Field | Type | Description |")?;
+            writeln!(md, "| --- | --- | --- |")?;
+            for field in fields {
+                writeln!(
+                    md,
+                    "| `{}` | `{}` | {} |",
+                    field.name,
+                    type_name(&field.ty),
+                    cell(&field.docs.join(" "))
+                )?;
+            }
+            writeln!(md)
+        }
+        Some(IdlDefinedFields::Tuple(fields)) => {
+            writeln!(md, "| Index | Type |")?;
+            writeln!(md, "| --- | --- |")?;
+            for (i, ty) in fields.iter().enumerate() {
+                writeln!(md, "| {i} | `{}` |", type_name(ty))?;
+            }
+            writeln!(md)
+        }
+        None => Ok(()),
+    }
+}
+
+fn write_docs(md: &mut String, docs: &[String]) -> std::fmt::Result {
+    if docs.is_empty() {
+        return Ok(());
+    }
+    writeln!(md, "{}\n", docs.join("\n"))
+}
+
+/// Escapes text for a table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn seed(seed: &IdlSeed) -> String {
+    match seed {
+        IdlSeed::Const(seed) => match std::str::from_utf8(&seed.value) {
+            Ok(value) if value.chars().all(|c| c.is_ascii_graphic() || c == ' ') => {
+                format!("`b\"{value}\"`")
+            }
+            _ => format!("`{:?}`", seed.value),
+        },
+        IdlSeed::Arg(seed) => format!("`{}`", seed.path),
+        IdlSeed::Account(seed) => format!("`{}`", seed.path),
+    }
+}
+
+fn type_def_name(ty: &IdlTypeDef) -> String {
+    if ty.generics.is_empty() {
+        return ty.name.clone();
+    }
+
+    let generics = ty
+        .generics
+        .iter()
+        .map(|generic| match generic {
+            IdlTypeDefGeneric::Type { name } => name.clone(),
+            IdlTypeDefGeneric::Const { name, ty } => format!("const {name}: {ty}"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{}<{generics}>", ty.name)
+}
+
+/// Returns the Rust representation of an IDL type.
+fn type_name(ty: &IdlType) -> String {
+    match ty {
+        IdlType::Bool => "bool".into(),
+        IdlType::U8 => "u8".into(),
+        IdlType::I8 => "i8".into(),
+        IdlType::U16 => "u16".into(),
+        IdlType::I16 => "i16".into(),
+        IdlType::U32 => "u32".into(),
+        IdlType::I32 => "i32".into(),
+        IdlType::F32 => "f32".into(),
+        IdlType::U64 => "u64".into(),
+        IdlType::I64 => "i64".into(),
+        IdlType::F64 => "f64".into(),
+        IdlType::U128 => "u128".into(),
+        IdlType::I128 => "i128".into(),
+        IdlType::U256 => "u256".into(),
+        IdlType::I256 => "i256".into(),
+        IdlType::Bytes => "bytes".into(),
+        IdlType::String => "String".into(),
+        IdlType::Pubkey => "Pubkey".into(),
+        IdlType::Option(ty) => format!("Option<{}>", type_name(ty)),
+        IdlType::Vec(ty) => format!("Vec<{}>", type_name(ty)),
+        IdlType::Array(ty, len) => {
+            let len = match len {
+                IdlArrayLen::Generic(len) => len.clone(),
+                IdlArrayLen::Value(len) => len.to_string(),
+            };
+            format!("[{}; {len}]", type_name(ty))
+        }
+        IdlType::Defined { name, generics } if generics.is_empty() => name.clone(),
+        IdlType::Defined { name, generics } => {
+            let generics = generics
+                .iter()
+                .map(|generic| match generic {
+                    IdlGenericArg::Type { ty } => type_name(ty),
+                    IdlGenericArg::Const { value } => value.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{name}<{generics}>")
+        }
+        IdlType::Generic(ty) => ty.clone(),
+        _ => format!("{ty:?}"),
+    }
+}
@@ -0,0 +1,166 @@
+use std::str::FromStr;
+
+use anchor_lang_idl::{
+    codec,
+    types::{Idl, IdlType},
+};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent};
+
+use crate::config::{Fixture, FixtureKind};
+
+/// Size of a mint of the token program.
+const MINT_LEN: usize = 82;
+/// Size of a token account of the token program.
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Builds the account of a fixture, `program_idl` returning the address and
+/// the IDL of a program from its name or from the path to its IDL.
+pub fn account(
+    fixture: &Fixture,
+    program_idl: impl Fn(&str) -> Result<(Pubkey, Idl)>,
+) -> Result<Account> {
+    let (owner, data) = match &fixture.kind {
+        FixtureKind::Mint {
+            decimals,
+            mint_authority,
+            supply,
+            freeze_authority,
+        } => {
+            let mut data = Vec::with_capacity(MINT_LEN);
+            write_coption_pubkey(&mut data, mint_authority.as_deref())?;
+            data.extend_from_slice(&supply.to_le_bytes());
+            data.push(*decimals);
+            data.push(1);
+            write_coption_pubkey(&mut data, freeze_authority.as_deref())?;
+            (crate::TOKEN_PROGRAM_ID, data)
+        }
+        FixtureKind::TokenAccount {
+            mint,
+            owner,
+            amount,
+        } => {
+            let mut data = Vec::with_capacity(TOKEN_ACCOUNT_LEN);
+            data.extend_from_slice(pubkey(mint)?.as_ref());
+            data.extend_from_slice(pubkey(owner)?.as_ref());
+            data.extend_from_slice(&amount.to_le_bytes());
+            write_coption_pubkey(&mut data, None)?;
+            // Initialized, not native, nothing delegated and no close authority
+            data.push(1);
+            data.extend_from_slice(&[0; 12]);
+            data.extend_from_slice(&0u64.to_le_bytes());
+            write_coption_pubkey(&mut data, None)?;
+            (crate::TOKEN_PROGRAM_ID, data)
+        }
+        FixtureKind::Account { program, ty, data } => {
+            let (program_id, idl) = program_idl(program)?;
+            (program_id, account_data(&idl, ty, data)?)
+        }
+    };
+
+    Ok(Account {
+        lamports: fixture
+            .lamports
+            .unwrap_or_else(|| Rent::default().minimum_balance(data.len())),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    })
+}
+
+/// Encodes the data of an account of the IDL, discriminator included.
+fn account_data(idl: &Idl, name: &str, value: &Value) -> Result<Vec<u8>> {
+    let account = idl
+        .accounts
+        .iter()
+        .find(|acc| acc.name == name)
+        .ok_or_else(|| {
+            anyhow!(
+                "Account `{name}` not found in the IDL of {}",
+                idl.metadata.name
+            )
+        })?;
+    let mut data = account.discriminator.clone();
+    data.extend(codec::encode_type(
+        idl,
+        &IdlType::Defined {
+            name: name.to_owned(),
+            generics: vec![],
+        },
+        value,
+    )?);
+    Ok(data)
+}
+
+fn pubkey(address: &str) -> Result<Pubkey> {
+    Pubkey::from_str(address).map_err(|_| anyhow!("Invalid address: {address}"))
+}
+
+fn write_coption_pubkey(data: &mut Vec<u8>, address: Option<&str>) -> Result<()> {
+    match address {
+        Some(address) => {
+            data.extend_from_slice(&1u32.to_le_bytes());
+            data.extend_from_slice(pubkey(address)?.as_ref());
+        }
+        None => data.extend_from_slice(&[0; 36]),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_accounts() {
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "Vault", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+            "types": [
+                {
+                    "name": "Vault",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "amount", "type": "u64" },
+                            { "name": "label", "type": { "option": "string" } },
+                            { "name": "state", "type": { "defined": { "name": "State" } } }
+                        ]
+                    }
+                },
+                {
+                    "name": "State",
+                    "type": { "kind": "enum", "variants": [{ "name": "Open" }, { "name": "Closed" }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let fixture: Fixture = serde_json::from_value(serde_json::json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "account": {
+                "program": "vault",
+                "type": "Vault",
+                "data": { "amount": "5", "label": null, "state": "Closed" }
+            }
+        }))
+        .unwrap();
+        let vault = account(&fixture, |_| Ok((Pubkey::default(), idl.clone()))).unwrap();
+        assert_eq!(
+            vault.data,
+            [1, 2, 3, 4, 5, 6, 7, 8, 5, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+
+        let fixture: Fixture = serde_json::from_value(serde_json::json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "mint": { "decimals": 6, "supply": 1000 }
+        }))
+        .unwrap();
+        let mint = account(&fixture, |_| unreachable!()).unwrap();
+        assert_eq!(mint.data.len(), MINT_LEN);
+        assert_eq!(mint.owner, crate::TOKEN_PROGRAM_ID);
+    }
+}
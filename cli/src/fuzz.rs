@@ -0,0 +1,461 @@
+use std::fmt::Write;
+
+use anchor_lang_idl::types::{
+    Idl, IdlArrayLen, IdlInstruction, IdlInstructionAccount, IdlInstructionAccountItem, IdlSeed,
+    IdlType,
+};
+use heck::ToUpperCamelCase;
+
+/// Directory of the fuzzing crate, relative to the workspace root.
+pub const FUZZ_DIR: &str = "fuzz";
+
+/// Returns the manifest of the fuzzing crate, with a target per program.
+pub fn manifest(programs: &[String]) -> String {
+    let mut manifest = r#"[package]
+name = "fuzz"
+version = "0.0.0"
+publish = false
+edition = "2021"
+
+[package.metadata]
+cargo-fuzz = true
+
+[dependencies]
+arbitrary = { version = "1", features = ["derive"] }
+borsh = "0.10"
+libfuzzer-sys = "0.4"
+litesvm = "0.1"
+solana-sdk = "1.18"
+
+# Not part of the workspace of the programs
+[workspace]
+members = ["."]
+"#
+    .to_owned();
+    for program in programs {
+        let _ = write!(
+            manifest,
+            r#"
+[[bin]]
+name = "{program}"
+path = "fuzz_targets/{program}.rs"
+test = false
+doc = false
+"#
+        );
+    }
+    manifest
+}
+
+/// Returns the invariants checked by the fuzz target of a program, to be
+/// completed by the user.
+pub fn invariants(program: &str) -> String {
+    format!(
+        r#"//! Invariants of the `{program}` program, checked after every fuzzed instruction.
+
+use litesvm::LiteSVM;
+
+/// Returns the violated invariant, if any.
+///
+/// The accounts of the program can be read with `svm.get_account(&address)`.
+pub fn check(_svm: &LiteSVM) -> Result<(), String> {{
+    Ok(())
+}}
+"#
+    )
+}
+
+/// Returns the fuzz target of a program.
+///
+/// Every input is decoded as a sequence of the program's instructions, each
+/// with arbitrary arguments and accounts picked from a pool of known accounts.
+pub fn target(program: &str, idl: &Idl) -> String {
+    format!(
+        r#"//! Fuzz target of the `{program}` program, generated by `anchor fuzz init`.
+//!
+//! Invariants are defined in `invariants/{program}.rs`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solana_sdk::{{
+    instruction::InstructionError,
+    transaction::{{Transaction, TransactionError}},
+}};
+
+#[path = "../invariants/{program}.rs"]
+mod invariants;
+{harness}
+impl Harness {{
+    fn execute(&mut self, ix: &HarnessInstruction) {{
+        let (instruction, signers) = self.instruction(ix);
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&signers[0].pubkey()),
+            &signers,
+            self.svm.latest_blockhash(),
+        );
+        if let Err(failed) = self.svm.send_transaction(tx) {{
+            // Errors returned by the program are expected, panics are not
+            if let TransactionError::InstructionError(_, InstructionError::ProgramFailedToComplete) =
+                failed.err
+            {{
+                panic!(
+                    "the program crashed on {{ix:?}}\n{{}}",
+                    failed.meta.logs.join("\n")
+                );
+            }}
+        }}
+        self.svm.expire_blockhash();
+    }}
+}}
+
+fuzz_target!(|instructions: Vec<HarnessInstruction>| {{
+    let mut harness = Harness::new();
+    for ix in &instructions {{
+        harness.execute(ix);
+        if let Err(err) = invariants::check(&harness.svm) {{
+            panic!("invariant violated after {{ix:?}}: {{err}}");
+        }}
+    }}
+}});
+"#,
+        harness = harness(program, idl, "../../target/deploy"),
+    )
+}
+
+/// Returns the instructions of a program and the harness building them on
+/// top of LiteSVM, `deploy_dir` being the directory of the program binary
+/// relative to the source file.
+///
+/// The accounts of an instruction are indexes in a pool of known accounts,
+/// to which the accounts of every built instruction are added. Accounts with a
+/// fixed address or with seeds are derived as the program expects them,
+/// signers are picked from the funded users.
+pub(crate) fn harness(program: &str, idl: &Idl, deploy_dir: &str) -> String {
+    let mut variants = String::new();
+    let mut arms = String::new();
+    for ix in &idl.instructions {
+        let _ = write_instruction(&mut variants, &mut arms, ix);
+    }
+
+    format!(
+        r#"
+use arbitrary::Arbitrary;
+use borsh::BorshSerialize;
+use litesvm::LiteSVM;
+use solana_sdk::{{
+    instruction::{{AccountMeta, Instruction}},
+    pubkey::Pubkey,
+    signature::{{Keypair, Signer}},
+}};
+
+const PROGRAM_ID: Pubkey = solana_sdk::pubkey!("{address}");
+
+/// Number of funded users signing the instructions.
+const USERS: usize = 4;
+
+#[derive(Arbitrary, Debug)]
+enum HarnessInstruction {{
+{variants}}}
+
+struct Harness {{
+    svm: LiteSVM,
+    users: Vec<Keypair>,
+    /// Accounts that can be passed to the instructions.
+    accounts: Vec<Pubkey>,
+}}
+
+impl Harness {{
+    fn new() -> Self {{
+        let mut svm = LiteSVM::new();
+        svm.add_program(
+            PROGRAM_ID,
+            include_bytes!("{deploy_dir}/{program}.so"),
+        );
+        let users = (0..USERS).map(|_| Keypair::new()).collect::<Vec<_>>();
+        for user in &users {{
+            svm.airdrop(&user.pubkey(), 1_000_000_000_000).unwrap();
+        }}
+        let accounts = users.iter().map(|user| user.pubkey()).collect();
+        Self {{
+            svm,
+            users,
+            accounts,
+        }}
+    }}
+
+    fn account(&self, index: u8) -> Pubkey {{
+        self.accounts[index as usize % self.accounts.len()]
+    }}
+
+    fn user(&self, index: u8) -> Keypair {{
+        self.users[index as usize % self.users.len()].insecure_clone()
+    }}
+
+    #[allow(unused_mut, unused_variables)]
+    fn instruction(&mut self, ix: &HarnessInstruction) -> (Instruction, Vec<Keypair>) {{
+        let mut signers = vec![self.users[0].insecure_clone()];
+        let mut data = vec![];
+        let accounts = match ix {{
+{arms}        }};
+        for meta in &accounts {{
+            if !self.accounts.contains(&meta.pubkey) {{
+                self.accounts.push(meta.pubkey);
+            }}
+        }}
+        (
+            Instruction {{
+                program_id: PROGRAM_ID,
+                accounts,
+                data,
+            }},
+            signers,
+        )
+    }}
+}}
+"#,
+        address = idl.address,
+    )
+}
+
+fn write_instruction(
+    variants: &mut String,
+    arms: &mut String,
+    ix: &IdlInstruction,
+) -> std::fmt::Result {
+    let mut accounts = vec![];
+    flatten_accounts(&ix.accounts, "", &mut accounts);
+    let variant = ix.name.to_upper_camel_case();
+
+    let mut fields = vec![];
+    writeln!(variants, "    {variant} {{")?;
+    if !accounts.is_empty() {
+        writeln!(variants, "        accounts: [u8; {}],", accounts.len())?;
+        fields.push("accounts".to_owned());
+    }
+    for arg in &ix.args {
+        match rust_type(&arg.ty) {
+            Some(ty) => writeln!(variants, "        {}: {ty},", arg.name)?,
+            None => {
+                writeln!(variants, "        /// Borsh encoding of the argument.")?;
+                writeln!(variants, "        {}: Vec<u8>,", arg.name)?;
+            }
+        }
+        fields.push(arg.name.clone());
+    }
+    writeln!(variants, "    }},")?;
+
+    writeln!(
+        arms,
+        "            HarnessInstruction::{variant} {{ {} }} => {{",
+        fields.join(", ")
+    )?;
+    writeln!(arms, "                data.extend({:?});", ix.discriminator)?;
+    for arg in &ix.args {
+        match rust_type(&arg.ty) {
+            Some(_) => writeln!(
+                arms,
+                "                {}.serialize(&mut data).unwrap();",
+                arg.name
+            )?,
+            None => writeln!(arms, "                data.extend({});", arg.name)?,
+        }
+    }
+
+    // Accounts without seeds are resolved first as seeds can refer to them
+    let mut resolved: Vec<&str> = vec![];
+    for (i, (var, acc)) in accounts.iter().enumerate() {
+        if acc.pda.is_some() && acc.address.is_none() && !acc.signer {
+            continue;
+        }
+        if let Some(address) = &acc.address {
+            writeln!(
+                arms,
+                "                let {var} = solana_sdk::pubkey!(\"{address}\");"
+            )?;
+        } else if acc.signer {
+            writeln!(
+                arms,
+                "                let {var}_signer = self.user(accounts[{i}]);"
+            )?;
+            writeln!(arms, "                let {var} = {var}_signer.pubkey();")?;
+            writeln!(arms, "                signers.push({var}_signer);")?;
+        } else {
+            writeln!(
+                arms,
+                "                let {var} = self.account(accounts[{i}]);"
+            )?;
+        }
+        resolved.push(var);
+    }
+    for (i, (var, acc)) in accounts.iter().enumerate() {
+        if resolved.contains(&var.as_str()) {
+            continue;
+        }
+        let pda = acc.pda.as_ref().unwrap();
+        let seeds = pda
+            .seeds
+            .iter()
+            .map(|s| seed(s, ix, &resolved))
+            .collect::<Option<Vec<_>>>();
+        let program = match &pda.program {
+            None => Some("PROGRAM_ID".to_owned()),
+            Some(IdlSeed::Const(program)) => <[u8; 32]>::try_from(program.value.as_slice())
+                .ok()
+                .map(|program| format!("Pubkey::new_from_array({program:?})")),
+            Some(_) => None,
+        };
+        match (seeds, program) {
+            (Some(seeds), Some(program)) => writeln!(
+                arms,
+                "                let {var} = Pubkey::find_program_address(&[{}], &{program}).0;",
+                seeds.join(", ")
+            )?,
+            // Seeds that can't be derived are fuzzed like any other account
+            _ => writeln!(
+                arms,
+                "                let {var} = self.account(accounts[{i}]);"
+            )?,
+        }
+        resolved.push(var);
+    }
+
+    writeln!(arms, "                vec![")?;
+    for (var, acc) in &accounts {
+        let meta = if acc.writable { "new" } else { "new_readonly" };
+        writeln!(
+            arms,
+            "                    AccountMeta::{meta}({var}, {}),",
+            acc.signer
+        )?;
+    }
+    writeln!(arms, "                ]")?;
+    writeln!(arms, "            }}")
+}
+
+/// Flattens composite accounts, naming nested accounts after their path.
+fn flatten_accounts<'a>(
+    accounts: &'a [IdlInstructionAccountItem],
+    prefix: &str,
+    flat: &mut Vec<(String, &'a IdlInstructionAccount)>,
+) {
+    for acc in accounts {
+        match acc {
+            IdlInstructionAccountItem::Single(acc) => {
+                flat.push((format!("{prefix}{}", acc.name), acc));
+            }
+            IdlInstructionAccountItem::Composite(accs) => {
+                flatten_accounts(&accs.accounts, &format!("{prefix}{}_", accs.name), flat);
+            }
+        }
+    }
+}
+
+/// Returns the expression of a seed, if it can be derived from the arguments
+/// and the already resolved accounts.
+fn seed(seed: &IdlSeed, ix: &IdlInstruction, resolved: &[&str]) -> Option<String> {
+    match seed {
+        IdlSeed::Const(seed) => match std::str::from_utf8(&seed.value) {
+            Ok(value) if value.chars().all(|c| c.is_ascii_graphic() || c == ' ') => {
+                Some(format!("b{value:?}.as_ref()"))
+            }
+            _ => Some(format!("{:?}.as_ref()", seed.value)),
+        },
+        IdlSeed::Arg(seed) => {
+            let arg = ix.args.iter().find(|arg| arg.name == seed.path)?;
+            let name = &arg.name;
+            match arg.ty {
+                IdlType::Bool => Some(format!("[*{name} as u8].as_ref()")),
+                IdlType::U8
+                | IdlType::I8
+                | IdlType::U16
+                | IdlType::I16
+                | IdlType::U32
+                | IdlType::I32
+                | IdlType::U64
+                | IdlType::I64
+                | IdlType::U128
+                | IdlType::I128 => Some(format!("{name}.to_le_bytes().as_ref()")),
+                IdlType::String => Some(format!("{name}.as_bytes()")),
+                IdlType::Bytes | IdlType::Pubkey => Some(format!("{name}.as_ref()")),
+                _ => None,
+            }
+        }
+        IdlSeed::Account(seed) => resolved
+            .iter()
+            .find(|var| **var == seed.path)
+            .map(|var| format!("{var}.as_ref()")),
+    }
+}
+
+/// Returns the arbitrary Rust type whose borsh encoding is the one of the IDL
+/// type, if there is one.
+///
+/// Floats are left out as borsh refuses to encode NaN.
+fn rust_type(ty: &IdlType) -> Option<String> {
+    Some(match ty {
+        IdlType::Bool => "bool".into(),
+        IdlType::U8 => "u8".into(),
+        IdlType::I8 => "i8".into(),
+        IdlType::U16 => "u16".into(),
+        IdlType::I16 => "i16".into(),
+        IdlType::U32 => "u32".into(),
+        IdlType::I32 => "i32".into(),
+        IdlType::U64 => "u64".into(),
+        IdlType::I64 => "i64".into(),
+        IdlType::U128 => "u128".into(),
+        IdlType::I128 => "i128".into(),
+        IdlType::Bytes => "Vec<u8>".into(),
+        IdlType::String => "String".into(),
+        IdlType::Pubkey => "[u8; 32]".into(),
+        IdlType::Option(ty) => format!("Option<{}>", rust_type(ty)?),
+        IdlType::Vec(ty) => format!("Vec<{}>", rust_type(ty)?),
+        IdlType::Array(ty, IdlArrayLen::Value(len)) => format!("[{}; {len}]", rust_type(ty)?),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        let idl: Idl = serde_json::from_str(
+            r#"{
+                "address": "Vau1t11111111111111111111111111111111111111",
+                "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+                "instructions": [{
+                    "name": "deposit",
+                    "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                    "accounts": [
+                        { "name": "user", "writable": true, "signer": true },
+                        {
+                            "name": "vault",
+                            "writable": true,
+                            "pda": { "seeds": [
+                                { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                                { "kind": "account", "path": "user" }
+                            ]}
+                        },
+                        { "name": "system_program", "address": "11111111111111111111111111111111" }
+                    ],
+                    "args": [{ "name": "amount", "type": "u64" }]
+                }]
+            }"#,
+        )
+        .unwrap();
+        let target = target("vault", &idl);
+
+        assert!(target.contains("    Deposit {\n        accounts: [u8; 3],\n        amount: u64,"));
+        assert!(target.contains("let user_signer = self.user(accounts[0]);"));
+        assert!(target.contains(
+            "let vault = Pubkey::find_program_address(&[b\"vault\".as_ref(), user.as_ref()], &PROGRAM_ID).0;"
+        ));
+        assert!(target.contains(
+            "let system_program = solana_sdk::pubkey!(\"11111111111111111111111111111111\");"
+        ));
+        assert!(target.contains("AccountMeta::new_readonly(system_program, false),"));
+    }
+}
@@ -0,0 +1,391 @@
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path};
+
+use anyhow::Result;
+use quote::ToTokens;
+use syn::{
+    visit::{self, Visit},
+    Fields, GenericArgument, ItemFn, ItemMod, ItemStruct, PathArguments, Type,
+};
+
+use crate::lint::constraints;
+
+/// Account wrappers whose type argument is an account or a program type.
+const TYPED_WRAPPERS: &[&str] = &[
+    "Account",
+    "AccountLoader",
+    "Interface",
+    "InterfaceAccount",
+    "Program",
+    "Sysvar",
+];
+
+/// Relationships between the instructions, the accounts and the account types
+/// of a program.
+#[derive(Debug, Default)]
+pub struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+#[derive(Debug)]
+struct Node {
+    id: String,
+    label: String,
+    kind: NodeKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum NodeKind {
+    Instruction,
+    Account,
+    Type,
+}
+
+#[derive(Debug)]
+struct Edge {
+    from: String,
+    to: String,
+    label: Option<&'static str>,
+}
+
+impl Graph {
+    /// Builds the graph of the program whose sources are in `src`.
+    pub fn from_sources(src: impl AsRef<Path>) -> Result<Self> {
+        let mut analyzer = Analyzer::default();
+        let sources = walkdir::WalkDir::new(src)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "rs"));
+        for source in sources {
+            let file = syn::parse_file(&fs::read_to_string(source.path())?)?;
+            analyzer.visit_file(&file);
+        }
+        Ok(analyzer.graph())
+    }
+
+    /// Renders the graph as a mermaid flowchart.
+    pub fn mermaid(&self) -> String {
+        let mut out = "flowchart LR\n".to_owned();
+        for node in &self.nodes {
+            let label = node
+                .label
+                .replace('"', "#quot;")
+                .replace('<', "#lt;")
+                .replace('>', "#gt;")
+                .replace("#lt;br/#gt;", "<br/>");
+            let _ = match node.kind {
+                NodeKind::Instruction => writeln!(out, "    {}([\"{label}\"])", node.id),
+                NodeKind::Account => writeln!(out, "    {}[\"{label}\"]", node.id),
+                NodeKind::Type => writeln!(out, "    {}{{{{\"{label}\"}}}}", node.id),
+            };
+        }
+        for edge in &self.edges {
+            let _ = match edge.label {
+                Some(label) => writeln!(out, "    {} -- {label} --> {}", edge.from, edge.to),
+                None => writeln!(out, "    {} --> {}", edge.from, edge.to),
+            };
+        }
+        out
+    }
+
+    /// Renders the graph in the graphviz DOT language.
+    pub fn dot(&self, name: &str) -> String {
+        let mut out = format!("digraph \"{name}\" {{\n    rankdir=LR;\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Instruction => "ellipse",
+                NodeKind::Account => "box",
+                NodeKind::Type => "hexagon",
+            };
+            let label = node.label.replace('"', "\\\"").replace("<br/>", "\\n");
+            let _ = writeln!(out, "    {} [label=\"{label}\", shape={shape}];", node.id);
+        }
+        for edge in &self.edges {
+            let _ = match edge.label {
+                Some(label) => {
+                    writeln!(out, "    {} -> {} [label=\"{label}\"];", edge.from, edge.to)
+                }
+                None => writeln!(out, "    {} -> {};", edge.from, edge.to),
+            };
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[derive(Debug, Default)]
+struct Analyzer {
+    /// Accounts structs by name.
+    accounts: BTreeMap<String, Vec<AccountsField>>,
+    /// Instructions and the name of their accounts struct.
+    instructions: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+struct AccountsField {
+    name: String,
+    /// Account wrapper, e.g. `Account` or the name of a nested accounts struct.
+    wrapper: String,
+    /// Type argument of the wrapper, e.g. the account type of `Account`.
+    ty: Option<String>,
+    optional: bool,
+    constraints: Vec<(String, String)>,
+}
+
+impl Analyzer {
+    fn graph(&self) -> Graph {
+        let mut graph = Graph::default();
+        let mut types = vec![];
+
+        for (name, fields) in &self.accounts {
+            for field in fields {
+                let mut flags = vec![];
+                for constraint in ["mut", "init", "init_if_needed", "close"] {
+                    if field.has_constraint(constraint) {
+                        flags.push(constraint);
+                    }
+                }
+                if field.wrapper == "Signer" || field.has_constraint("signer") {
+                    flags.push("signer");
+                }
+                if field.has_constraint("seeds") {
+                    flags.push("pda");
+                }
+                if field.optional {
+                    flags.push("optional");
+                }
+
+                let ty = match &field.ty {
+                    Some(ty) => format!("{}<{ty}>", field.wrapper),
+                    None => field.wrapper.clone(),
+                };
+                let mut label = format!("{}: {ty}", field.name);
+                if !flags.is_empty() {
+                    label.push_str(&format!("<br/>{}", flags.join(", ")));
+                }
+                graph.nodes.push(Node {
+                    id: field_id(name, &field.name),
+                    label,
+                    kind: NodeKind::Account,
+                });
+
+                if let Some(ty) = &field.ty {
+                    if !types.contains(ty) {
+                        types.push(ty.clone());
+                    }
+                    graph.edges.push(Edge {
+                        from: field_id(name, &field.name),
+                        to: type_id(ty),
+                        label: None,
+                    });
+                }
+
+                // Nested accounts structs point to their accounts.
+                if let Some(nested) = self.accounts.get(&field.wrapper) {
+                    for nested_field in nested {
+                        graph.edges.push(Edge {
+                            from: field_id(name, &field.name),
+                            to: field_id(&field.wrapper, &nested_field.name),
+                            label: None,
+                        });
+                    }
+                }
+
+                // Relationships with the other accounts of the struct.
+                for (constraint, tokens) in &field.constraints {
+                    let label = match constraint.as_str() {
+                        "seeds" => "seed",
+                        "has_one" => "has_one",
+                        "close" => "close",
+                        "payer" => "payer",
+                        _ => continue,
+                    };
+                    for other in fields {
+                        if other.name != field.name && references(tokens, &other.name) {
+                            graph.edges.push(Edge {
+                                from: field_id(name, &other.name),
+                                to: field_id(name, &field.name),
+                                label: Some(label),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        for ty in types {
+            graph.nodes.push(Node {
+                id: type_id(&ty),
+                label: ty,
+                kind: NodeKind::Type,
+            });
+        }
+
+        for (ix, accounts) in &self.instructions {
+            graph.nodes.push(Node {
+                id: format!("ix_{ix}"),
+                label: ix.clone(),
+                kind: NodeKind::Instruction,
+            });
+            for field in self.accounts.get(accounts).into_iter().flatten() {
+                graph.edges.push(Edge {
+                    from: format!("ix_{ix}"),
+                    to: field_id(accounts, &field.name),
+                    label: None,
+                });
+            }
+        }
+
+        graph
+    }
+}
+
+impl AccountsField {
+    fn has_constraint(&self, name: &str) -> bool {
+        self.constraints.iter().any(|(n, _)| n == name)
+    }
+}
+
+impl<'ast> Visit<'ast> for Analyzer {
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        let is_accounts = item.attrs.iter().any(|attr| {
+            attr.path.is_ident("derive") && attr.tokens.to_string().contains("Accounts")
+        });
+        if let (true, Fields::Named(fields)) = (is_accounts, &item.fields) {
+            let fields = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let (wrapper, ty, optional) = account_type(&field.ty);
+                    AccountsField {
+                        name: field.ident.as_ref().unwrap().to_string(),
+                        wrapper,
+                        ty,
+                        optional,
+                        constraints: field
+                            .attrs
+                            .iter()
+                            .filter(|attr| attr.path.is_ident("account"))
+                            .flat_map(|attr| constraints(attr.tokens.clone()))
+                            .collect(),
+                    }
+                })
+                .collect();
+            self.accounts.insert(item.ident.to_string(), fields);
+        }
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        let is_program = item.attrs.iter().any(|attr| attr.path.is_ident("program"));
+        if let (true, Some((_, items))) = (is_program, &item.content) {
+            for item in items {
+                if let syn::Item::Fn(f) = item {
+                    if let Some(accounts) = context_accounts(f) {
+                        self.instructions.push((f.sig.ident.to_string(), accounts));
+                    }
+                }
+            }
+        }
+        visit::visit_item_mod(self, item);
+    }
+}
+
+/// Returns the name of the accounts struct of the `Context` of an instruction.
+fn context_accounts(f: &ItemFn) -> Option<String> {
+    let ty = match f.sig.inputs.first()? {
+        syn::FnArg::Typed(arg) => &*arg.ty,
+        _ => return None,
+    };
+    let (wrapper, accounts, _) = account_type(ty);
+    match wrapper.as_str() {
+        "Context" => accounts,
+        _ => None,
+    }
+}
+
+/// Returns the wrapper, its type argument and whether the account is optional.
+fn account_type(ty: &Type) -> (String, Option<String>, bool) {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last(),
+        Type::Reference(reference) => return account_type(&reference.elem),
+        _ => None,
+    };
+    let segment = match segment {
+        Some(segment) => segment,
+        None => return (ty.to_token_stream().to_string(), None, false),
+    };
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    let wrapper = segment.ident.to_string();
+    match (wrapper.as_str(), args.last()) {
+        ("Box", Some(ty)) => account_type(ty),
+        ("Option", Some(ty)) => {
+            let (wrapper, ty, _) = account_type(ty);
+            (wrapper, ty, true)
+        }
+        ("Context", Some(ty)) => (wrapper, Some(account_type(ty).0), false),
+        (w, Some(ty)) if TYPED_WRAPPERS.contains(&w) => (wrapper, Some(account_type(ty).0), false),
+        _ => (wrapper, None, false),
+    }
+}
+
+/// Whether the tokens of a constraint refer to the given account.
+fn references(tokens: &str, account: &str) -> bool {
+    tokens
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|ident| ident == account)
+}
+
+fn field_id(accounts: &str, field: &str) -> String {
+    format!("{accounts}_{field}")
+}
+
+fn type_id(ty: &str) -> String {
+    format!("ty_{ty}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        let src = r#"
+            #[program]
+            pub mod vault {
+                pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+                    Ok(())
+                }
+            }
+
+            #[derive(Accounts)]
+            pub struct Deposit<'info> {
+                #[account(mut)]
+                pub user: Signer<'info>,
+                #[account(mut, seeds = [b"vault", user.key().as_ref()], bump, has_one = user)]
+                pub vault: Box<Account<'info, Vault>>,
+            }
+        "#;
+        let mut analyzer = Analyzer::default();
+        analyzer.visit_file(&syn::parse_file(src).unwrap());
+        let mermaid = analyzer.graph().mermaid();
+
+        assert!(mermaid.contains("ix_deposit([\"deposit\"])"));
+        assert!(mermaid.contains("Deposit_vault[\"vault: Account#lt;Vault#gt;<br/>mut, pda\"]"));
+        assert!(mermaid.contains("ix_deposit --> Deposit_user"));
+        assert!(mermaid.contains("Deposit_vault --> ty_Vault"));
+        assert!(mermaid.contains("Deposit_user -- seed --> Deposit_vault"));
+        assert!(mermaid.contains("Deposit_user -- has_one --> Deposit_vault"));
+    }
+}
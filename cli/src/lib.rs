@@ -1,18 +1,19 @@
 #![cfg_attr(nightly, feature(proc_macro_span))]
 
 use crate::config::{
-    AnchorPackage, BootstrapMode, BuildConfig, Config, ConfigOverride, Manifest, ProgramArch,
+    _TestValidator, _Validator, AnchorPackage, BootstrapMode, BuildConfig, CloneEntry, CloneFilter,
+    Config, ConfigOverride, Fixture, FixtureEntry, Manifest, Multisig, ProgramArch,
     ProgramDeployment, ProgramWorkspace, ScriptsConfig, TestValidator, WithPath,
     DEFAULT_LEDGER_PATH, SHUTDOWN_WAIT, STARTUP_WAIT,
 };
 use anchor_client::Cluster;
 use anchor_lang::idl::{IdlAccount, IdlInstruction, ERASED_AUTHORITY};
 use anchor_lang::{AccountDeserialize, AnchorDeserialize, AnchorSerialize};
-use anchor_lang_idl::convert::convert_idl;
-use anchor_lang_idl::types::{Idl, IdlArrayLen, IdlDefinedFields, IdlType, IdlTypeDefTy};
+use anchor_lang_idl::convert::{convert_idl, convert_idl_to_legacy, convert_idl_with_report};
+use anchor_lang_idl::types::{Idl, IdlInstructionAccountItem, IdlType};
 use anyhow::{anyhow, Context, Result};
 use checks::{check_anchor_version, check_overflow};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use dirs::home_dir;
 use flate2::read::GzDecoder;
 use flate2::read::ZlibDecoder;
@@ -22,11 +23,19 @@ use heck::{ToKebabCase, ToLowerCamelCase, ToPascalCase, ToSnakeCase};
 use regex::{Regex, RegexBuilder};
 use reqwest::blocking::multipart::{Form, Part};
 use reqwest::blocking::Client;
-use rust_template::{ProgramTemplate, TestTemplate};
+use rust_template::{InitTemplate, ProgramTemplate, TestTemplate};
 use semver::{Version, VersionReq};
-use serde::Deserialize;
-use serde_json::{json, Map, Value as JsonValue};
-use solana_client::rpc_client::RpcClient;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::pubsub_client::PubsubClient;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsConfig,
+    RpcTransactionLogsFilter,
+};
+use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_client::rpc_request::RpcRequest;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_sdk::account_utils::StateMut;
 use solana_sdk::bpf_loader;
@@ -34,12 +43,15 @@ use solana_sdk::bpf_loader_deprecated;
 use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
+use solana_sdk::signature::Signature;
 use solana_sdk::signature::Signer;
 use solana_sdk::sysvar;
 use solana_sdk::transaction::Transaction;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
@@ -51,15 +63,31 @@ use std::str::FromStr;
 use std::string::ToString;
 use tar::Archive;
 
+mod admin;
 mod checks;
 pub mod config;
+mod cost;
+mod docs;
+mod fixtures;
+mod fuzz;
+mod graph;
+mod lint;
 pub mod rust_template;
+mod size;
 pub mod solidity_template;
+mod wallets;
 
 // Version of the docker image.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const DOCKER_BUILDER_VERSION: &str = VERSION;
 
+const TOKEN_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const TOKEN_2022_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Number of times a failed deploy is retried if not configured.
+const DEFAULT_DEPLOY_RETRIES: u32 = 0;
+
 #[derive(Debug, Parser)]
 #[clap(version = VERSION)]
 pub struct Opts {
@@ -87,9 +115,11 @@ pub enum Command {
         /// Don't initialize git
         #[clap(long)]
         no_git: bool,
-        /// Rust program template to use
-        #[clap(value_enum, short, long, default_value = "single")]
-        template: ProgramTemplate,
+        /// Template to use: `single`, `multiple` or `token` for a Rust program
+        /// template, `multi-program`, `rust-test` or the URL of a git
+        /// repository
+        #[clap(short, long, default_value = "single")]
+        template: InitTemplate,
         /// Test template to use
         #[clap(value_enum, long, default_value = "mocha")]
         test_template: TestTemplate,
@@ -141,6 +171,10 @@ pub enum Command {
         /// Architecture to use when building the program
         #[clap(value_enum, long, default_value = "sbf")]
         arch: ProgramArch,
+        /// Print the size of the binaries by crate and by function, with
+        /// hints to make them smaller
+        #[clap(long, conflicts_with = "verifiable")]
+        size_report: bool,
     },
     /// Expands macros (wrapper around cargo expand)
     ///
@@ -188,6 +222,36 @@ pub enum Command {
         /// use this to save time when running verify and the program code is already built.
         #[clap(long, required = false)]
         skip_build: bool,
+        /// Submit an attestation of the verified build, signed by the
+        /// upgrade authority of the program as the wallet, to the given URL.
+        #[clap(long)]
+        attest: Option<String>,
+    },
+    /// Runs the benchmark scenarios and compares the compute units consumed
+    /// by each instruction against a baseline.
+    ///
+    /// Scenarios are run with the `bench` script of the Anchor.toml, or the
+    /// `test` script if there is none, against a local validator.
+    Bench {
+        /// Build and benchmark only this program
+        #[clap(short, long)]
+        program_name: Option<String>,
+        /// Baseline file of the compute units of each instruction
+        #[clap(long, default_value = "bench.json")]
+        baseline: String,
+        /// Allowed increase of compute units over the baseline, in percents
+        #[clap(long, default_value = "5")]
+        threshold: f64,
+        /// Save the results as the new baseline instead of comparing them
+        #[clap(long)]
+        update: bool,
+        /// Flag to skip building the program in the workspace
+        #[clap(long)]
+        skip_build: bool,
+        /// Test runner of the scenarios
+        #[clap(value_enum, long, default_value = "ts")]
+        runner: TestRunner,
+        args: Vec<String>,
     },
     #[clap(name = "test", alias = "t")]
     /// Runs integration tests.
@@ -221,6 +285,25 @@ pub enum Command {
         /// Run the test suites under the specified path
         #[clap(long)]
         run: Vec<String>,
+        /// Collect code coverage of the programs with `cargo llvm-cov`
+        /// instead of running the test suites against a validator.
+        ///
+        /// lcov and HTML reports are written to `target/coverage/<program>`.
+        #[clap(long)]
+        coverage: bool,
+        /// Shard the test files across test validators running side by side
+        #[clap(long)]
+        parallel: bool,
+        /// Number of shards of a parallel run, defaults to the number of CPUs
+        #[clap(long, requires = "parallel")]
+        shards: Option<usize>,
+        /// Run the tests against the test validator of each of these Solana
+        /// versions, e.g. `1.18,2.0`
+        #[clap(long, value_delimiter = ',')]
+        solana_versions: Vec<String>,
+        /// Test runner of the test suites
+        #[clap(value_enum, long, default_value = "ts")]
+        runner: TestRunner,
         args: Vec<String>,
         /// Environment variables to pass into the docker container
         #[clap(short, long, required = false)]
@@ -242,6 +325,10 @@ pub enum Command {
         /// Create new program even if there is already one
         #[clap(long, action)]
         force: bool,
+        /// IDL of a program to call through CPI, scaffolds its
+        /// `declare_program!` module and clones it in the test validator
+        #[clap(long, conflicts_with = "solidity")]
+        with_dep: Vec<String>,
     },
     /// Commands for interacting with interface definitions.
     Idl {
@@ -261,21 +348,96 @@ pub enum Command {
         /// If true, deploy from path target/verifiable
         #[clap(short, long)]
         verifiable: bool,
+        /// Compute unit price, in micro-lamports, of the deploy transactions
+        #[clap(long)]
+        priority_fee: Option<u64>,
+        /// Number of times to retry a failed deploy, resuming from the
+        /// partially written buffer
+        #[clap(long)]
+        max_retries: Option<u32>,
+        /// Print the cost of the deploy without sending any transaction
+        #[clap(long)]
+        simulate: bool,
         /// Arguments to pass to the underlying `solana program deploy` command.
         #[clap(required = false, last = true)]
         solana_args: Vec<String>,
     },
+    /// Generates the reference documentation of the programs from their IDL.
+    Docs {
+        /// Only document this program
+        #[clap(short, long)]
+        program_name: Option<String>,
+        /// Output directory of the documentation
+        #[clap(short, long, default_value = "target/docs")]
+        out: String,
+        /// Render HTML instead of markdown
+        #[clap(long)]
+        html: bool,
+        /// Use the IDLs in `target/idl` instead of generating them
+        #[clap(long)]
+        skip_build: bool,
+    },
+    /// Renders the graph of the instructions, the accounts and the account
+    /// types of the programs.
+    Graph {
+        /// Only render this program
+        #[clap(short, long)]
+        program_name: Option<String>,
+        /// Format of the graph
+        #[clap(value_enum, long, default_value = "mermaid")]
+        format: GraphFormat,
+        /// Output directory of the graphs, printed if not given
+        #[clap(short, long)]
+        out: Option<String>,
+    },
+    /// Estimates the compute units of every instruction of the programs by
+    /// executing them once on an in-process SVM.
+    Cost {
+        /// Only estimate this program
+        #[clap(short, long)]
+        program_name: Option<String>,
+        /// Skip building the programs
+        #[clap(long)]
+        skip_build: bool,
+        /// Print the estimations as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    /// Generates and runs fuzz targets of the programs from their IDL.
+    Fuzz {
+        #[clap(subcommand)]
+        subcmd: FuzzCommand,
+    },
     /// Runs the deploy migration script.
     Migrate,
     /// Deploys, initializes an IDL, and migrates all in one command.
     /// Upgrades a single program. The configured wallet must be the upgrade
     /// authority.
+    #[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Upgrade {
+        #[clap(subcommand)]
+        subcmd: Option<UpgradeCommand>,
         /// The program to upgrade.
-        #[clap(short, long)]
-        program_id: Pubkey,
+        #[clap(short, long, required = true)]
+        program_id: Option<Pubkey>,
         /// Filepath to the new program binary.
-        program_filepath: String,
+        #[clap(required = true)]
+        program_filepath: Option<String>,
+        /// Compute unit price, in micro-lamports, of the deploy transactions
+        #[clap(long)]
+        priority_fee: Option<u64>,
+        /// Number of times to retry a failed deploy, resuming from the
+        /// partially written buffer
+        #[clap(long)]
+        max_retries: Option<u32>,
+        /// Multisig holding the upgrade authority, e.g. `squads:<vault>`, to
+        /// set as the authority of the buffer.
+        ///
+        /// The program is written to a buffer whose authority is set to the
+        /// multisig, and the upgrade instruction to propose to the multisig is
+        /// printed instead of being executed. The proposal isn't created.
+        #[clap(long)]
+        buffer_authority: Option<Multisig>,
         /// Arguments to pass to the underlying `solana program deploy` command.
         #[clap(required = false, last = true)]
         solana_args: Vec<String>,
@@ -293,7 +455,15 @@ pub enum Command {
     },
     /// Starts a node shell with an Anchor client setup according to the local
     /// config.
-    Shell,
+    Shell {
+        /// Starts a shell to inspect and simulate the instructions of a single
+        /// deployed program, which doesn't require a workspace
+        #[clap(long)]
+        program: Option<Pubkey>,
+        /// IDL of the program, fetched from the chain by default
+        #[clap(long, requires = "program")]
+        idl: Option<String>,
+    },
     /// Runs the script defined by the current workspace's Anchor.toml.
     Run {
         /// The name of the script to run.
@@ -302,6 +472,16 @@ pub enum Command {
         #[clap(required = false, last = true)]
         script_args: Vec<String>,
     },
+    /// Pulls a program from the registry into the workspace, to call it with
+    /// `declare_program!`.
+    Add {
+        /// Program in the registry, as `<name>` or `<name>@<version>`
+        registry_path: String,
+        /// Workspace program to declare the pulled program in, required if the
+        /// workspace has several programs
+        #[clap(short, long)]
+        program_name: Option<String>,
+    },
     /// Saves an api token from the registry locally.
     Login {
         /// API access token.
@@ -330,8 +510,32 @@ pub enum Command {
         #[clap(subcommand)]
         subcmd: KeysCommand,
     },
+    /// Runs static security lints on the programs of the workspace.
+    Lint {
+        /// Lint only this program
+        #[clap(short, long)]
+        program_name: Option<String>,
+        /// Exit with an error if any lint is reported
+        #[clap(long)]
+        deny_warnings: bool,
+    },
+    /// Rebuilds the changed programs and upgrades them on the running localnet.
+    Watch {
+        /// Only watch this program
+        #[clap(short, long)]
+        program_name: Option<String>,
+        /// True if the build should not fail even if there are
+        /// no "CHECK" comments where normally required
+        #[clap(long)]
+        skip_lint: bool,
+    },
     /// Localnet commands.
     Localnet {
+        #[clap(subcommand)]
+        subcmd: Option<LocalnetCommand>,
+        /// Start from a snapshot taken with `anchor localnet snapshot`
+        #[clap(long)]
+        restore: Option<String>,
         /// Flag to skip building the program in the workspace,
         /// use this to save time when running test and the program code is not altered.
         #[clap(long)]
@@ -354,9 +558,15 @@ pub enum Command {
         #[clap(required = false, last = true)]
         cargo_args: Vec<String>,
     },
+    /// Client generation commands.
+    Client {
+        #[clap(subcommand)]
+        subcmd: ClientCommand,
+    },
     /// Fetch and deserialize an account using the IDL provided.
     Account {
-        /// Account struct to deserialize
+        /// Account struct to deserialize, as `<program>.<Account>`, or the
+        /// program only to identify the account type by its discriminator
         account_type: String,
         /// Address of the account to deserialize
         address: Pubkey,
@@ -364,6 +574,42 @@ pub enum Command {
         #[clap(long)]
         idl: Option<String>,
     },
+    /// Print the events emitted by a program, decoded with its IDL, as JSON
+    /// lines.
+    Events {
+        /// Name of the program
+        program_name: String,
+        /// Keep printing new events as they are emitted
+        #[clap(long)]
+        follow: bool,
+        /// Print the events emitted since this transaction signature or slot
+        #[clap(long)]
+        since: Option<String>,
+        /// IDL to use (defaults to workspace IDL)
+        #[clap(long)]
+        idl: Option<String>,
+    },
+    /// Decode on-chain data with the IDLs of the programs.
+    Decode {
+        #[clap(subcommand)]
+        subcmd: DecodeCommand,
+    },
+}
+
+#[derive(Debug, Parser)]
+pub enum DecodeCommand {
+    /// Print the instructions of a transaction, CPIs included, and the events
+    /// they emit, decoded with the IDLs of the workspace or on-chain IDLs.
+    Tx {
+        /// Signature of the transaction
+        signature: Signature,
+        /// Cluster of the transaction (defaults to the cluster of the workspace)
+        #[clap(long)]
+        cluster: Option<Cluster>,
+        /// IDL to use in addition to the workspace IDLs
+        #[clap(long = "idl")]
+        idls: Vec<String>,
+    },
 }
 
 #[derive(Debug, Parser)]
@@ -376,6 +622,15 @@ pub enum KeysCommand {
         #[clap(short, long)]
         program_name: Option<String>,
     },
+    /// Generate a new keypair for a program and replace its old id across the
+    /// workspace.
+    Rotate {
+        /// Name of the program to rotate the keypair of
+        program_name: String,
+        /// Flag to skip building the program with its new id
+        #[clap(long)]
+        skip_build: bool,
+    },
 }
 
 #[derive(Debug, Parser)]
@@ -397,6 +652,9 @@ pub enum IdlCommand {
         /// Useful for multisig execution when the local wallet keypair is not available.
         #[clap(long)]
         print_only: bool,
+        /// Account receiving the lamports of the closed account. Defaults to the wallet.
+        #[clap(long)]
+        refund_to: Option<Pubkey>,
         #[clap(long)]
         priority_fee: Option<u64>,
     },
@@ -406,6 +664,9 @@ pub enum IdlCommand {
         program_id: Pubkey,
         #[clap(short, long)]
         filepath: String,
+        /// Buffer of an interrupted write to resume, instead of a new buffer.
+        #[clap(long)]
+        buffer: Option<Pubkey>,
         #[clap(long)]
         priority_fee: Option<u64>,
     },
@@ -428,6 +689,9 @@ pub enum IdlCommand {
         program_id: Pubkey,
         #[clap(short, long)]
         filepath: String,
+        /// Buffer of an interrupted upgrade to resume, instead of a new buffer.
+        #[clap(long)]
+        buffer: Option<Pubkey>,
         #[clap(long)]
         priority_fee: Option<u64>,
     },
@@ -484,18 +748,27 @@ pub enum IdlCommand {
     /// Fetches an IDL for the given address from a cluster.
     /// The address can be a program, IDL account, or IDL buffer.
     Fetch {
-        address: Pubkey,
+        #[clap(required_unless_present = "all")]
+        address: Option<Pubkey>,
         /// Output file for the IDL (stdout if not specified).
-        #[clap(short, long)]
+        #[clap(short, long, conflicts_with = "all")]
         out: Option<String>,
+        /// Fetch the IDLs of the programs of the cluster under `[programs]`
+        /// and of the programs cloned by the test validator into `idls/`
+        #[clap(long, conflicts_with = "address")]
+        all: bool,
     },
-    /// Convert legacy IDLs (pre Anchor 0.30) to the new IDL spec
+    /// Convert legacy IDLs (pre Anchor 0.30) to the new IDL spec, or the other
+    /// way around with `--legacy`
     Convert {
         /// Path to the IDL file
         path: String,
         /// Output file for the IDL (stdout if not specified)
         #[clap(short, long)]
         out: Option<String>,
+        /// Convert to the legacy IDL spec instead
+        #[clap(long)]
+        legacy: bool,
     },
     /// Generate TypeScript type for the IDL
     Type {
@@ -505,6 +778,135 @@ pub enum IdlCommand {
         #[clap(short, long)]
         out: Option<String>,
     },
+    /// Generate the JSON Schemas of the accounts, events and instruction
+    /// arguments of the IDL
+    Schema {
+        /// Path to the IDL file
+        path: String,
+        /// Output directory for the schemas (stdout if not specified)
+        #[clap(short, long)]
+        out: Option<String>,
+    },
+    /// Prints the changes between two IDLs, exiting with an error if any of
+    /// them is breaking
+    Diff {
+        /// Path to the old IDL file
+        old: String,
+        /// Path to the new IDL file
+        new: String,
+    },
+    /// Compares the built IDLs of the workspace programs with their on-chain
+    /// IDLs, exiting with an error if any of them differ
+    Check {
+        /// Only check the given program instead of all programs
+        #[clap(short, long)]
+        program_name: Option<String>,
+    },
+}
+
+#[derive(Debug, Parser)]
+pub enum LocalnetCommand {
+    /// Saves the ledger of the last localnet run as a snapshot.
+    Snapshot {
+        /// Name of the snapshot
+        name: String,
+    },
+}
+
+#[derive(Debug, Parser)]
+pub enum FuzzCommand {
+    /// Generates the fuzzing crate in `fuzz`, with a target per program.
+    /// Targets are generated again, invariants are kept.
+    Init {
+        /// Only generate the target of this program
+        #[clap(short, long)]
+        program_name: Option<String>,
+    },
+    /// Runs the fuzz target of a program with `cargo fuzz`.
+    Run {
+        /// Name of the program to fuzz
+        program_name: String,
+        /// Fuzz the program binary in `target/deploy` without building it
+        #[clap(long)]
+        skip_build: bool,
+        /// Arguments to pass to libFuzzer, e.g. `-max_total_time=60`.
+        #[clap(required = false, last = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Debug, Parser)]
+pub enum ClientCommand {
+    /// Generates a standalone client crate for the given IDL.
+    Gen {
+        /// Language of the client
+        #[clap(value_enum, long, default_value = "rust")]
+        lang: ClientLang,
+        /// Path to the IDL file
+        idl: String,
+        /// Output directory of the client
+        #[clap(short, long)]
+        out: String,
+    },
+}
+
+/// Runner of the test suites of `anchor test`
+#[derive(Clone, Debug, Eq, PartialEq, Parser, ValueEnum)]
+pub enum TestRunner {
+    /// The `test` script of the Anchor.toml, usually mocha
+    Ts,
+    /// `cargo test`, without requiring Node
+    Rust,
+}
+
+/// Format of `anchor graph`
+#[derive(Clone, Debug, Eq, PartialEq, Parser, ValueEnum)]
+pub enum GraphFormat {
+    /// Mermaid flowchart
+    Mermaid,
+    /// Graphviz DOT
+    Dot,
+}
+
+/// Language of a generated client
+#[derive(Clone, Debug, Eq, PartialEq, Parser, ValueEnum)]
+pub enum ClientLang {
+    /// Rust crate built on `declare_program!`
+    Rust,
+}
+
+#[derive(Debug, Parser)]
+pub enum UpgradeCommand {
+    /// Writes the new program binary to a buffer, to be swapped in later with
+    /// `anchor upgrade commit`.
+    WriteBuffer {
+        /// The program to upgrade.
+        #[clap(short, long)]
+        program_id: Pubkey,
+        /// Filepath to the new program binary.
+        program_filepath: String,
+        /// Compute unit price, in micro-lamports, of the write transactions
+        #[clap(long)]
+        priority_fee: Option<u64>,
+        /// Number of times to retry a failed write, resuming from the
+        /// partially written buffer
+        #[clap(long)]
+        max_retries: Option<u32>,
+        /// Arguments to pass to the underlying `solana program write-buffer`
+        /// command.
+        #[clap(required = false, last = true)]
+        solana_args: Vec<String>,
+    },
+    /// Upgrades the program from the buffer written by
+    /// `anchor upgrade write-buffer`.
+    Commit {
+        /// The program to upgrade.
+        #[clap(short, long)]
+        program_id: Pubkey,
+        /// Compute unit price, in micro-lamports, of the upgrade transaction
+        #[clap(long)]
+        priority_fee: Option<u64>,
+    },
 }
 
 #[derive(Debug, Parser)]
@@ -726,7 +1128,15 @@ fn process_command(opts: Opts) -> Result<()> {
             name,
             template,
             force,
-        } => new(&opts.cfg_override, solidity, name, template, force),
+            with_dep,
+        } => new(
+            &opts.cfg_override,
+            solidity,
+            name,
+            template,
+            force,
+            with_dep,
+        ),
         Command::Build {
             no_idl,
             idl,
@@ -741,6 +1151,7 @@ fn process_command(opts: Opts) -> Result<()> {
             skip_lint,
             no_docs,
             arch,
+            size_report,
         } => build(
             &opts.cfg_override,
             no_idl,
@@ -758,6 +1169,7 @@ fn process_command(opts: Opts) -> Result<()> {
             cargo_args,
             no_docs,
             arch,
+            size_report,
         ),
         Command::Verify {
             program_id,
@@ -768,6 +1180,7 @@ fn process_command(opts: Opts) -> Result<()> {
             env,
             cargo_args,
             skip_build,
+            attest,
             arch,
         } => verify(
             &opts.cfg_override,
@@ -779,6 +1192,7 @@ fn process_command(opts: Opts) -> Result<()> {
             env,
             cargo_args,
             skip_build,
+            attest,
             arch,
         ),
         Command::Clean => clean(&opts.cfg_override),
@@ -786,29 +1200,63 @@ fn process_command(opts: Opts) -> Result<()> {
             program_name,
             program_keypair,
             verifiable,
+            priority_fee,
+            max_retries,
+            simulate,
             solana_args,
         } => deploy(
             &opts.cfg_override,
             program_name,
             program_keypair,
             verifiable,
+            priority_fee,
+            max_retries,
+            simulate,
             solana_args,
         ),
+        Command::Docs {
+            program_name,
+            out,
+            html,
+            skip_build,
+        } => docs(&opts.cfg_override, program_name, out, html, skip_build),
         Command::Expand {
             program_name,
             cargo_args,
         } => expand(&opts.cfg_override, program_name, &cargo_args),
         Command::Upgrade {
+            subcmd: Some(subcmd),
+            ..
+        } => upgrade_cmd(&opts.cfg_override, subcmd),
+        Command::Upgrade {
+            subcmd: None,
             program_id,
             program_filepath,
+            priority_fee,
+            max_retries,
+            buffer_authority,
             solana_args,
         } => upgrade(
             &opts.cfg_override,
-            program_id,
-            program_filepath,
+            program_id.expect("Required by clap"),
+            program_filepath.expect("Required by clap"),
+            priority_fee,
+            max_retries,
+            buffer_authority,
             solana_args,
         ),
         Command::Idl { subcmd } => idl(&opts.cfg_override, subcmd),
+        Command::Graph {
+            program_name,
+            format,
+            out,
+        } => graph(&opts.cfg_override, program_name, format, out),
+        Command::Cost {
+            program_name,
+            skip_build,
+            json,
+        } => cost(&opts.cfg_override, program_name, skip_build, json),
+        Command::Fuzz { subcmd } => fuzz_cmd(&opts.cfg_override, subcmd),
         Command::Migrate => migrate(&opts.cfg_override),
         Command::Test {
             program_name,
@@ -817,6 +1265,11 @@ fn process_command(opts: Opts) -> Result<()> {
             skip_build,
             detach,
             run,
+            coverage,
+            parallel,
+            shards,
+            solana_versions,
+            runner,
             args,
             env,
             cargo_args,
@@ -831,19 +1284,54 @@ fn process_command(opts: Opts) -> Result<()> {
             skip_lint,
             detach,
             run,
+            coverage,
+            parallel.then(|| {
+                shards.unwrap_or_else(|| {
+                    std::thread::available_parallelism()
+                        .map(usize::from)
+                        .unwrap_or(1)
+                })
+            }),
+            solana_versions,
+            runner,
             args,
             env,
             cargo_args,
             arch,
         ),
+        Command::Bench {
+            program_name,
+            baseline,
+            threshold,
+            update,
+            skip_build,
+            runner,
+            args,
+        } => bench(
+            &opts.cfg_override,
+            program_name,
+            baseline,
+            threshold,
+            update,
+            skip_build,
+            runner,
+            args,
+        ),
         #[cfg(feature = "dev")]
         Command::Airdrop { .. } => airdrop(&opts.cfg_override),
         Command::Cluster { subcmd } => cluster(subcmd),
-        Command::Shell => shell(&opts.cfg_override),
+        Command::Shell { program, idl } => match program {
+            Some(program_id) => program_shell(&opts.cfg_override, program_id, idl),
+            None => shell(&opts.cfg_override),
+        },
         Command::Run {
             script,
             script_args,
         } => run(&opts.cfg_override, script, script_args),
+        Command::Add {
+            registry_path,
+            program_name,
+        } => add(&opts.cfg_override, registry_path, program_name),
         Command::Login { token } => login(&opts.cfg_override, token),
         Command::Publish {
             program,
@@ -860,7 +1348,21 @@ fn process_command(opts: Opts) -> Result<()> {
             arch,
         ),
         Command::Keys { subcmd } => keys(&opts.cfg_override, subcmd),
+        Command::Lint {
+            program_name,
+            deny_warnings,
+        } => lint(&opts.cfg_override, program_name, deny_warnings),
+        Command::Watch {
+            program_name,
+            skip_lint,
+        } => watch(&opts.cfg_override, program_name, skip_lint),
+        Command::Localnet {
+            subcmd: Some(LocalnetCommand::Snapshot { name }),
+            ..
+        } => localnet_snapshot(&opts.cfg_override, name),
         Command::Localnet {
+            subcmd: None,
+            restore,
             skip_build,
             skip_deploy,
             skip_lint,
@@ -869,6 +1371,7 @@ fn process_command(opts: Opts) -> Result<()> {
             arch,
         } => localnet(
             &opts.cfg_override,
+            restore,
             skip_build,
             skip_deploy,
             skip_lint,
@@ -881,6 +1384,14 @@ fn process_command(opts: Opts) -> Result<()> {
             address,
             idl,
         } => account(&opts.cfg_override, account_type, address, idl),
+        Command::Events {
+            program_name,
+            follow,
+            since,
+            idl,
+        } => events(&opts.cfg_override, program_name, follow, since, idl),
+        Command::Decode { subcmd } => decode(&opts.cfg_override, subcmd),
+        Command::Client { subcmd } => client(subcmd),
     }
 }
 
@@ -892,13 +1403,18 @@ fn init(
     solidity: bool,
     no_install: bool,
     no_git: bool,
-    template: ProgramTemplate,
+    template: InitTemplate,
     test_template: TestTemplate,
     force: bool,
 ) -> Result<()> {
     if !force && Config::discover(cfg_override)?.is_some() {
         return Err(anyhow!("Workspace already initialized"));
     }
+    if solidity && !matches!(template, InitTemplate::Program(_)) {
+        return Err(anyhow!(
+            "Solidity workspaces only support program templates"
+        ));
+    }
 
     // We need to format different cases for the dir and the name
     let rust_name = name.to_snake_case();
@@ -926,7 +1442,22 @@ fn init(
         fs::create_dir(&project_name)?;
     }
     std::env::set_current_dir(&project_name)?;
-    fs::create_dir_all("app")?;
+
+    let (program_template, test_template) = match &template {
+        InitTemplate::Program(template) => (template.clone(), test_template),
+        InitTemplate::MultiProgram => (ProgramTemplate::Single, test_template),
+        InitTemplate::RustTest => (ProgramTemplate::Single, TestTemplate::Rust),
+        InitTemplate::Git(url) => {
+            init_from_git(url, &project_name, &rust_name)?;
+            let no_install = no_install || !Path::new("package.json").exists();
+            return finish_init(&project_name, no_install, no_git);
+        }
+    };
+    let no_js = template == InitTemplate::RustTest;
+
+    if !no_js {
+        fs::create_dir_all("app")?;
+    }
 
     let mut cfg = Config::default();
     let test_script = test_template.get_test_script(javascript);
@@ -936,13 +1467,28 @@ fn init(
     let mut localnet = BTreeMap::new();
     let program_id = rust_template::get_or_create_program_id(&rust_name);
     localnet.insert(
-        rust_name,
+        rust_name.clone(),
         ProgramDeployment {
             address: program_id,
             path: None,
             idl: None,
         },
     );
+    let caller_name = if project_name == rust_name {
+        format!("{project_name}_caller")
+    } else {
+        format!("{project_name}-caller")
+    };
+    if template == InitTemplate::MultiProgram {
+        localnet.insert(
+            caller_name.to_snake_case(),
+            ProgramDeployment {
+                address: rust_template::get_or_create_program_id(&caller_name),
+                path: None,
+                idl: None,
+            },
+        );
+    }
     cfg.programs.insert(Cluster::Localnet, localnet);
     let toml = cfg.to_string();
     fs::write("Anchor.toml", toml)?;
@@ -966,33 +1512,38 @@ fn init(
     if solidity {
         solidity_template::create_program(&project_name)?;
     } else {
-        rust_template::create_program(&project_name, template)?;
+        rust_template::create_program(&project_name, program_template)?;
+        if template == InitTemplate::MultiProgram {
+            rust_template::create_caller_program(&caller_name, &project_name)?;
+        }
     }
 
-    // Build the migrations directory.
-    fs::create_dir_all("migrations")?;
+    if !no_js {
+        // Build the migrations directory.
+        fs::create_dir_all("migrations")?;
 
-    let license = get_npm_init_license()?;
+        let license = get_npm_init_license()?;
 
-    let jest = TestTemplate::Jest == test_template;
-    if javascript {
-        // Build javascript config
-        let mut package_json = File::create("package.json")?;
-        package_json.write_all(rust_template::package_json(jest, license).as_bytes())?;
+        let jest = TestTemplate::Jest == test_template;
+        if javascript {
+            // Build javascript config
+            let mut package_json = File::create("package.json")?;
+            package_json.write_all(rust_template::package_json(jest, license).as_bytes())?;
 
-        let mut deploy = File::create("migrations/deploy.js")?;
+            let mut deploy = File::create("migrations/deploy.js")?;
 
-        deploy.write_all(rust_template::deploy_script().as_bytes())?;
-    } else {
-        // Build typescript config
-        let mut ts_config = File::create("tsconfig.json")?;
-        ts_config.write_all(rust_template::ts_config(jest).as_bytes())?;
+            deploy.write_all(rust_template::deploy_script().as_bytes())?;
+        } else {
+            // Build typescript config
+            let mut ts_config = File::create("tsconfig.json")?;
+            ts_config.write_all(rust_template::ts_config(jest).as_bytes())?;
 
-        let mut ts_package_json = File::create("package.json")?;
-        ts_package_json.write_all(rust_template::ts_package_json(jest, license).as_bytes())?;
+            let mut ts_package_json = File::create("package.json")?;
+            ts_package_json.write_all(rust_template::ts_package_json(jest, license).as_bytes())?;
 
-        let mut deploy = File::create("migrations/deploy.ts")?;
-        deploy.write_all(rust_template::ts_deploy_script().as_bytes())?;
+            let mut deploy = File::create("migrations/deploy.ts")?;
+            deploy.write_all(rust_template::ts_deploy_script().as_bytes())?;
+        }
     }
 
     test_template.create_test_files(
@@ -1002,6 +1553,38 @@ fn init(
         &program_id.to_string(),
     )?;
 
+    finish_init(&project_name, no_install || no_js, no_git)
+}
+
+// Clones the template repository into the current directory and substitutes
+// its variables.
+fn init_from_git(url: &str, project_name: &str, rust_name: &str) -> Result<()> {
+    let exit = std::process::Command::new("git")
+        .args(["clone", "--depth", "1", url, "."])
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| anyhow::format_err!("git clone failed: {}", e.to_string()))?;
+    if !exit.status.success() {
+        return Err(anyhow!("Failed to clone the template repository {url}"));
+    }
+    fs::remove_dir_all(".git")?;
+
+    let program_id = rust_template::get_or_create_program_id(rust_name);
+    rust_template::substitute_template_vars(
+        Path::new("."),
+        &[
+            ("project_name", project_name.to_owned()),
+            ("program_name", rust_name.to_owned()),
+            ("ProgramName", rust_name.to_pascal_case()),
+            ("program_id", program_id.to_string()),
+        ],
+    )
+}
+
+// Installs the JavaScript dependencies and initializes git in the new
+// workspace.
+fn finish_init(project_name: &str, no_install: bool, no_git: bool) -> Result<()> {
     if !no_install {
         let yarn_result = install_node_modules("yarn")?;
         if !yarn_result.status.success() {
@@ -1052,6 +1635,7 @@ fn new(
     name: String,
     template: ProgramTemplate,
     force: bool,
+    with_dep: Vec<String>,
 ) -> Result<()> {
     with_workspace(cfg_override, |cfg| {
         match cfg.path().parent() {
@@ -1091,6 +1675,10 @@ fn new(
                     },
                 );
 
+                for idl_path in &with_dep {
+                    add_dependency(cfg, &name, Path::new(idl_path))?;
+                }
+
                 let toml = cfg.to_string();
                 fs::write("Anchor.toml", toml)?;
 
@@ -1101,6 +1689,76 @@ fn new(
     })
 }
 
+// Scaffolds the CPI calls of the `program` program to the program of the IDL
+// and clones the latter in the test validator.
+fn add_dependency(cfg: &mut WithPath<Config>, program: &str, idl_path: &Path) -> Result<()> {
+    let idl = convert_idl(&fs::read(idl_path)?)
+        .map_err(|e| anyhow!("Unable to read the IDL {}: {e}", idl_path.display()))?;
+    let dep = idl.metadata.name.to_snake_case();
+
+    // `declare_program!` reads the IDL from the `idls` directory.
+    fs::create_dir_all("idls")?;
+    write_idl(
+        &idl,
+        OutFile::File(Path::new("idls").join(format!("{dep}.json"))),
+    )?;
+
+    let src_path = Path::new("programs").join(program).join("src");
+    let cpi_mod = format!("{dep}_cpi");
+    fs::write(
+        src_path.join(format!("{cpi_mod}.rs")),
+        rust_template::dependency_cpi_mod(&idl),
+    )?;
+    let lib_path = src_path.join("lib.rs");
+    let lib = fs::read_to_string(&lib_path)?;
+    let lib = insert_after_declare_id(&lib, &format!("declare_program!({dep});"));
+    let lib = format!("pub mod {cpi_mod};\n{lib}");
+    fs::write(&lib_path, lib)?;
+
+    // The program is cloned from the cluster of the validator, mainnet unless
+    // another one is configured
+    let test_validator = cfg
+        .test_validator
+        .get_or_insert_with(|| _TestValidator::default().into());
+    let validator = test_validator
+        .validator
+        .get_or_insert_with(|| _Validator::default().into());
+    validator
+        .url
+        .get_or_insert_with(|| Cluster::Mainnet.url().to_owned());
+    let clones = validator.clone.get_or_insert_with(Vec::new);
+    if !clones.iter().any(|entry| entry.address == idl.address) {
+        clones.push(CloneEntry {
+            address: idl.address.clone(),
+            program_accounts: false,
+            filters: None,
+            file: None,
+            cache: false,
+        });
+    }
+
+    println!(
+        "Added `{dep}` ({}), call it through `{cpi_mod}` in {}",
+        idl.address,
+        src_path.display()
+    );
+    Ok(())
+}
+
+// Inserts an item on the line after `declare_id!`, or at the top of the file.
+fn insert_after_declare_id(lib: &str, item: &str) -> String {
+    if lib.contains(item) {
+        return lib.to_owned();
+    }
+    match lib.find("declare_id!") {
+        Some(i) => {
+            let end = lib[i..].find('\n').map_or(lib.len(), |j| i + j + 1);
+            format!("{}\n{item}\n{}", &lib[..end], &lib[end..])
+        }
+        None => format!("{item}\n\n{lib}"),
+    }
+}
+
 /// Array of (path, content) tuple.
 pub type Files = Vec<(PathBuf, String)>;
 
@@ -1269,6 +1927,7 @@ pub fn build(
     cargo_args: Vec<String>,
     no_docs: bool,
     arch: ProgramArch,
+    size_report: bool,
 ) -> Result<()> {
     // Change to the workspace member directory, if needed.
     if let Some(program_name) = program_name.as_ref() {
@@ -1303,12 +1962,14 @@ pub fn build(
         fs::create_dir_all(cfg_parent.join(&cfg.workspace.types))?;
     };
 
+    let cargo_args = cfg.profile_cargo_args(cargo_args);
     let cargo = Manifest::discover()?;
     let build_config = BuildConfig {
         verifiable,
         solana_version: solana_version.or_else(|| cfg.toolchain.solana_version.clone()),
         docker_image: docker_image.unwrap_or_else(|| cfg.docker()),
         bootstrap,
+        size_report,
     };
     match cargo {
         // No Cargo.toml so build the entire workspace.
@@ -1446,7 +2107,15 @@ fn build_rust_cwd(
     };
     match build_config.verifiable {
         false => _build_rust_cwd(
-            cfg, no_idl, idl_out, idl_ts_out, skip_lint, no_docs, arch, cargo_args,
+            cfg,
+            no_idl,
+            idl_out,
+            idl_ts_out,
+            skip_lint,
+            no_docs,
+            arch,
+            cargo_args,
+            build_config.size_report,
         ),
         true => build_cwd_verifiable(
             cfg,
@@ -1513,6 +2182,12 @@ fn build_cwd_verifiable(
     }
 
     let container_name = "anchor-program";
+    let binary_name = Manifest::from_path(&cargo_toml)?.lib_name()?;
+    let env_vars = security_txt_env(cfg, cargo_toml.parent().unwrap())?
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .chain(env_vars)
+        .collect();
 
     // Build the binary in docker.
     let result = docker_build(
@@ -1523,7 +2198,7 @@ fn build_cwd_verifiable(
         stdout,
         stderr,
         env_vars,
-        cargo_args,
+        cargo_args.clone(),
         arch,
     );
 
@@ -1531,7 +2206,7 @@ fn build_cwd_verifiable(
         Err(e) => {
             eprintln!("Error during Docker build: {e:?}");
         }
-        Ok(_) => {
+        Ok(toolchain) => {
             // Build the idl.
             println!("Extracting the IDL");
             let idl = generate_idl(cfg, skip_lint, no_docs)?;
@@ -1551,22 +2226,226 @@ fn build_cwd_verifiable(
                     ts_file,
                     workspace_dir
                         .join(&cfg.workspace.types)
-                        .join(idl.metadata.name)
+                        .join(&idl.metadata.name)
                         .with_extension("ts"),
                 )?;
             }
 
+            // Write out the reproducibility record of the build.
+            println!("Writing the build manifest");
+            let bin = fs::read(workspace_dir.join(format!("target/verifiable/{binary_name}.so")))?;
+            let idl =
+                fs::read(workspace_dir.join(format!("target/idl/{}.json", idl.metadata.name)))?;
+            let manifest = VerifiableBuildManifest {
+                version: VerifiableBuildManifest::VERSION,
+                program: binary_name.clone(),
+                anchor_version: VERSION.into(),
+                docker_image: build_config.docker_image.clone(),
+                rust_version: toolchain.rust_version.clone(),
+                solana_version: toolchain.solana_version.clone(),
+                arch: arch.build_subcommand().trim_start_matches("build-").into(),
+                features: cargo_features(&cargo_args),
+                cargo_args,
+                git_commit: git_commit(&workspace_dir),
+                binary_hash: executable_hash(&bin).to_string(),
+                idl_hash: solana_sdk::hash::hash(&idl).to_string(),
+            };
+            fs::write(
+                VerifiableBuildManifest::path(&workspace_dir, &binary_name),
+                serde_json::to_string_pretty(&manifest)?,
+            )?;
+
             println!("Build success");
         }
     }
 
-    result
+    result.map(|_| ())
 }
 
-#[allow(clippy::too_many_arguments)]
-fn docker_build(
-    cfg: &WithPath<Config>,
-    container_name: &str,
+/// Reproducibility record of a verifiable build, written next to the binary as
+/// `target/verifiable/<program>.manifest.json`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiableBuildManifest {
+    /// Version of the manifest format
+    pub version: u32,
+    pub program: String,
+    pub anchor_version: String,
+    pub docker_image: String,
+    pub rust_version: Option<String>,
+    pub solana_version: Option<String>,
+    pub arch: String,
+    /// Cargo features enabled with the cargo arguments
+    pub features: Vec<String>,
+    pub cargo_args: Vec<String>,
+    /// Commit of the workspace, suffixed with `-dirty` if it has uncommitted
+    /// changes
+    pub git_commit: Option<String>,
+    /// Hash of the binary, see [`executable_hash`]
+    pub binary_hash: String,
+    /// Hash of the IDL file
+    pub idl_hash: String,
+}
+
+impl VerifiableBuildManifest {
+    pub const VERSION: u32 = 1;
+
+    pub fn path(workspace_dir: &Path, program: &str) -> PathBuf {
+        workspace_dir.join(format!("target/verifiable/{program}.manifest.json"))
+    }
+}
+
+/// Returns the cargo features enabled by the given cargo arguments.
+fn cargo_features(cargo_args: &[String]) -> Vec<String> {
+    let mut features = vec![];
+    let mut args = cargo_args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--features" | "-F" => args.next().map(String::as_str),
+            _ => arg.strip_prefix("--features="),
+        };
+        if let Some(value) = value {
+            features.extend(
+                value
+                    .split([',', ' '])
+                    .filter(|feature| !feature.is_empty())
+                    .map(String::from),
+            );
+        }
+    }
+    features
+}
+
+fn git_commit(dir: &Path) -> Option<String> {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    };
+    let commit = git(&["rev-parse", "HEAD"])?;
+    match git(&["status", "--porcelain"]) {
+        Some(status) if status.is_empty() => Some(commit),
+        _ => Some(format!("{commit}-dirty")),
+    }
+}
+
+// Returns the environment variables through which `#[program]` embeds the
+// security.txt of the Anchor.toml in the program at `program_dir`, keyed by
+// the package of the program.
+fn security_txt_env(cfg: &WithPath<Config>, program_dir: &Path) -> Result<Vec<(String, String)>> {
+    let security = match &cfg.program.security {
+        Some(security) => security,
+        None => return Ok(vec![]),
+    };
+    // Both security.txts would be exported as the same symbol, which fails to
+    // link with an obscure error.
+    if let Some(source) = find_security_txt_macro(program_dir) {
+        return Err(anyhow!(
+            "{} embeds a security.txt with the `security_txt!` macro, which conflicts with \
+            the `[program.security]` of the Anchor.toml. Remove one of them.",
+            source.display()
+        ));
+    }
+    let package = Manifest::from_path(program_dir.join("Cargo.toml"))?
+        .package
+        .as_ref()
+        .ok_or_else(|| anyhow!("package section not provided"))?
+        .name
+        .to_uppercase()
+        .replace('-', "_");
+    let mut fields = security.fields();
+    if security.source_code.is_some() {
+        if let Some(commit) = git_commit(cfg.path().parent().unwrap()) {
+            fields.push(("source_revision", commit));
+        }
+    }
+    Ok(fields
+        .into_iter()
+        .map(|(name, value)| {
+            (
+                format!("ANCHOR_SECURITY_TXT_{package}_{}", name.to_uppercase()),
+                value,
+            )
+        })
+        .collect())
+}
+
+// Returns the first source of the program that invokes the `security_txt!`
+// macro of `solana-security-txt`.
+fn find_security_txt_macro(program_dir: &Path) -> Option<PathBuf> {
+    walkdir::WalkDir::new(program_dir.join("src"))
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "rs"))
+        .find(|entry| {
+            fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|source| syn::parse_file(&source).ok())
+                .map_or(false, |file| invokes_security_txt_macro(&file))
+        })
+        .map(|entry| entry.into_path())
+}
+
+// Mentions of the macro in comments or strings aren't invocations.
+fn invokes_security_txt_macro(file: &syn::File) -> bool {
+    struct Finder(bool);
+
+    impl<'ast> syn::visit::Visit<'ast> for Finder {
+        fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+            if mac
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.ident == "security_txt")
+            {
+                self.0 = true;
+            }
+            syn::visit::visit_macro(self, mac);
+        }
+    }
+
+    let mut finder = Finder(false);
+    syn::visit::Visit::visit_file(&mut finder, file);
+    finder.0
+}
+
+const SECURITY_TXT_BEGIN: &[u8] = b"=======BEGIN SECURITY.TXT V1=======\0";
+const SECURITY_TXT_END: &[u8] = b"=======END SECURITY.TXT V1=======\0";
+
+/// Fields the `solana-security-txt` convention requires.
+const SECURITY_TXT_REQUIRED: &[&str] = &["name", "project_url", "contacts", "policy"];
+
+// Returns the fields of the security.txt embedded in a program binary.
+fn parse_security_txt(bin: &[u8]) -> Option<BTreeMap<String, String>> {
+    let find = |bin: &[u8], needle: &[u8]| bin.windows(needle.len()).position(|w| w == needle);
+    let start = find(bin, SECURITY_TXT_BEGIN)? + SECURITY_TXT_BEGIN.len();
+    let len = find(&bin[start..], SECURITY_TXT_END)?;
+    let parts = bin[start..start + len]
+        .split(|b| *b == 0)
+        .map(|part| String::from_utf8_lossy(part).into_owned())
+        .collect::<Vec<_>>();
+    Some(
+        parts
+            .chunks_exact(2)
+            .map(|field| (field[0].clone(), field[1].clone()))
+            .collect(),
+    )
+}
+
+/// Versions of the toolchain used by a docker build.
+struct DockerToolchain {
+    rust_version: Option<String>,
+    solana_version: Option<String>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn docker_build(
+    cfg: &WithPath<Config>,
+    container_name: &str,
     cargo_toml: PathBuf,
     build_config: &BuildConfig,
     stdout: Option<File>,
@@ -1574,7 +2453,7 @@ fn docker_build(
     env_vars: Vec<String>,
     cargo_args: Vec<String>,
     arch: &ProgramArch,
-) -> Result<()> {
+) -> Result<DockerToolchain> {
     let binary_name = Manifest::from_path(&cargo_toml)?.lib_name()?;
 
     // Docker vars.
@@ -1631,6 +2510,10 @@ fn docker_build(
             arch,
         )
     });
+    let result = result.map(|_| DockerToolchain {
+        rust_version: docker_version(container_name, "rustc"),
+        solana_version: docker_version(container_name, "solana"),
+    });
 
     // Cleanup regardless of errors
     docker_cleanup(container_name, target_dir.as_path())?;
@@ -1682,6 +2565,26 @@ fn docker_prep(container_name: &str, build_config: &BuildConfig) -> Result<()> {
     Ok(())
 }
 
+/// `PATH` of the build commands run in the docker image.
+const DOCKER_PATH: &str = "PATH=/root/.local/share/solana/install/active_release/bin:/root/.cargo/bin:/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Returns the output of `<program> --version` in the docker container.
+fn docker_version(container_name: &str, program: &str) -> Option<String> {
+    std::process::Command::new("docker")
+        .args([
+            "exec",
+            "--env",
+            DOCKER_PATH,
+            container_name,
+            program,
+            "--version",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn docker_build_bpf(
     container_name: &str,
@@ -1708,16 +2611,14 @@ fn docker_build_bpf(
 
     // Execute the build.
     let exit = std::process::Command::new("docker")
-        .args([
-            "exec",
-            "--env",
-            "PATH=/root/.local/share/solana/install/active_release/bin:/root/.cargo/bin:/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin",
-        ])
-        .args(env_vars
-            .iter()
-            .map(|x| ["--env", x.as_str()])
-            .collect::<Vec<[&str; 2]>>()
-            .concat())
+        .args(["exec", "--env", DOCKER_PATH])
+        .args(
+            env_vars
+                .iter()
+                .map(|x| ["--env", x.as_str()])
+                .collect::<Vec<[&str; 2]>>()
+                .concat(),
+        )
         .args([
             container_name,
             "cargo",
@@ -1816,18 +2717,45 @@ fn _build_rust_cwd(
     no_docs: bool,
     arch: &ProgramArch,
     cargo_args: Vec<String>,
+    size_report: bool,
 ) -> Result<()> {
     let subcommand = arch.build_subcommand();
-    let exit = std::process::Command::new("cargo")
-        .arg(subcommand)
+    let mut cmd = std::process::Command::new("cargo");
+    cmd.arg(subcommand)
         .args(cargo_args)
+        .envs(security_txt_env(cfg, Path::new("."))?)
         .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
+        .stderr(Stdio::inherit());
+
+    // The size report is read from the map of the binary written by the linker
+    let size_map = if size_report {
+        let lib_name = Manifest::from_path("Cargo.toml")?.lib_name()?;
+        let map = cfg
+            .path()
+            .parent()
+            .unwrap()
+            .join("target/size-report")
+            .join(format!("{lib_name}.map"));
+        fs::create_dir_all(map.parent().unwrap())?;
+        let rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+        cmd.env(
+            "RUSTFLAGS",
+            format!("{rustflags} -C link-arg=-Map={}", map.display()).trim_start(),
+        );
+        Some((lib_name, map))
+    } else {
+        None
+    };
+
+    let exit = cmd
         .output()
         .map_err(|e| anyhow::format_err!("{}", e.to_string()))?;
     if !exit.status.success() {
         std::process::exit(exit.status.code().unwrap_or(1));
     }
+    if let Some((lib_name, map)) = size_map {
+        print_size_report(cfg, &lib_name, &map)?;
+    }
 
     // Generate IDL
     if !no_idl {
@@ -1869,6 +2797,37 @@ fn _build_rust_cwd(
     Ok(())
 }
 
+// Prints the size of a program binary by crate and by function, and the
+// optimizations that would make it smaller.
+fn print_size_report(cfg: &WithPath<Config>, lib_name: &str, map: &Path) -> Result<()> {
+    let root = cfg.path().parent().unwrap();
+    let report = size::SizeReport::from_map(&fs::read_to_string(map)?);
+    let binary_len = fs::metadata(root.join("target/deploy").join(format!("{lib_name}.so")))?.len();
+    let rent = solana_sdk::rent::Rent::default().minimum_balance(
+        UpgradeableLoaderState::size_of_programdata(binary_len as usize),
+    );
+
+    println!(
+        "\nSize report of `{lib_name}`: {} deployed, {} SOL of program data rent\n",
+        size::kb(binary_len),
+        solana_sdk::native_token::lamports_to_sol(rent)
+    );
+    print!("{report}");
+
+    let release_profile = fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Value>().ok())
+        .and_then(|manifest| manifest.get("profile")?.get("release").cloned());
+    let hints = report.hints(release_profile.as_ref());
+    if !hints.is_empty() {
+        println!("\nHints:");
+        for hint in hints {
+            println!("  - {hint}");
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn _build_solidity_cwd(
     cfg: &WithPath<Config>,
@@ -1956,6 +2915,7 @@ fn verify(
     env_vars: Vec<String>,
     cargo_args: Vec<String>,
     skip_build: bool,
+    attest: Option<String>,
     arch: ProgramArch,
 ) -> Result<()> {
     // Change to the workspace member directory, if needed.
@@ -1967,6 +2927,14 @@ fn verify(
     let cfg = Config::discover(cfg_override)?.expect("Not in workspace.");
     let cargo = Manifest::discover()?.ok_or_else(|| anyhow!("Cargo.toml not found"))?;
 
+    // Images are only reproducible when pinned by digest, tags can be moved.
+    let image = docker_image.clone().unwrap_or_else(|| cfg.docker());
+    if !image.contains("@sha256:") {
+        return Err(anyhow!(
+            "Image {image:?} isn't pinned by digest, pass `--docker-image <image>@sha256:<digest>`"
+        ));
+    }
+
     // Build the program we want to verify.
     let cur_dir = std::env::current_dir()?;
     if !skip_build {
@@ -1987,6 +2955,7 @@ fn verify(
             cargo_args,
             false,
             arch,
+            false,
         )?;
     }
     std::env::set_current_dir(cur_dir)?;
@@ -2002,11 +2971,78 @@ fn verify(
 
     let url = cluster_url(&cfg, &cfg.test_validator);
     let bin_ver = verify_bin(program_id, &bin_path, &url)?;
+    println!("Local executable hash: {}", bin_ver.local_hash);
+    println!("Deployed executable hash: {}", bin_ver.deployed_hash);
     if !bin_ver.is_verified {
         println!("Error: Binaries don't match");
         std::process::exit(1);
     }
 
+    // Check the binary against the record of the build that produced it.
+    let workspace_dir = cfg.path().parent().unwrap();
+    let manifest_path = VerifiableBuildManifest::path(workspace_dir, &binary_name);
+    let manifest = match fs::read(&manifest_path) {
+        Ok(manifest) => Some(serde_json::from_slice::<VerifiableBuildManifest>(
+            &manifest,
+        )?),
+        Err(_) => {
+            println!(
+                "Warning: build manifest {} not found",
+                manifest_path.display()
+            );
+            None
+        }
+    };
+    if let Some(manifest) = &manifest {
+        if manifest.binary_hash != bin_ver.local_hash.to_string() {
+            println!("Error: The binary doesn't match its build manifest");
+            std::process::exit(1);
+        }
+        if manifest.docker_image != image {
+            println!(
+                "Warning: the binary was built with image {:?}",
+                manifest.docker_image
+            );
+        }
+        println!("Build manifest: {}", manifest_path.display());
+    }
+
+    // Check the security.txt against the convention and the Anchor.toml.
+    let security_txt = parse_security_txt(&fs::read(&bin_path)?);
+    if let Some(security_txt) = &security_txt {
+        for field in SECURITY_TXT_REQUIRED {
+            if !security_txt.contains_key(*field) {
+                println!("Error: The security.txt is missing the required `{field}` field");
+                std::process::exit(1);
+            }
+        }
+    }
+    match (&security_txt, &cfg.program.security) {
+        (Some(security_txt), Some(security)) => {
+            for (field, value) in security.fields() {
+                if security_txt.get(field) != Some(&value) {
+                    println!(
+                        "Error: The security.txt `{field}` doesn't match the `[program.security]` \
+                        of the Anchor.toml"
+                    );
+                    std::process::exit(1);
+                }
+            }
+            println!("Security contacts: {}", security_txt["contacts"]);
+        }
+        (Some(security_txt), None) => {
+            println!("Security contacts: {}", security_txt["contacts"]);
+        }
+        (None, Some(_)) => {
+            println!("Error: The binary doesn't embed the `[program.security]` of the Anchor.toml");
+            std::process::exit(1);
+        }
+        (None, None) => println!(
+            "Warning: The binary has no security.txt, add a `[program.security]` to the \
+            Anchor.toml"
+        ),
+    }
+
     // Verify IDL (only if it's not a buffer account).
     let local_idl = generate_idl(&cfg, true, false)?;
     if bin_ver.state != BinVerificationState::Buffer {
@@ -2019,6 +3055,57 @@ fn verify(
 
     println!("{program_id} is verified.");
 
+    if let Some(attest_url) = attest {
+        // The attestation is only trusted if it's signed by whoever can
+        // upgrade the program.
+        let signer = cfg.wallet_kp()?;
+        let slot = match bin_ver.state {
+            BinVerificationState::ProgramData {
+                upgrade_authority_address: Some(authority),
+                ..
+            } if authority != signer.pubkey() => {
+                return Err(anyhow!(
+                    "The attestation must be signed by the upgrade authority {authority}, not \
+                    the wallet {}",
+                    signer.pubkey()
+                ))
+            }
+            BinVerificationState::ProgramData { slot, .. } => Some(slot),
+            BinVerificationState::Buffer => None,
+        };
+        let attestation = serde_json::json!({
+            "programId": program_id.to_string(),
+            "cluster": url,
+            "slot": slot,
+            "executableHash": bin_ver.deployed_hash.to_string(),
+            "dockerImage": image,
+            "buildManifest": manifest,
+            "securityTxt": security_txt,
+        });
+
+        let attestation = attestation.to_string();
+        let signature = signer.sign_message(attestation.as_bytes());
+        let body = serde_json::json!({
+            "attestation": attestation,
+            "signer": signer.pubkey().to_string(),
+            "signature": signature.to_string(),
+        });
+
+        let resp = Client::new()
+            .post(&attest_url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(
+                "Failed to submit the attestation ({}): {}",
+                resp.status(),
+                resp.text().unwrap_or_else(|_| "Server error".to_string())
+            ));
+        }
+        println!("Attestation submitted to {attest_url}");
+    }
+
     Ok(())
 }
 
@@ -2127,13 +3214,30 @@ pub fn verify_bin(program_id: Pubkey, bin_path: &Path, cluster: &str) -> Result<
     // Finally, check the bytes.
     let is_verified = local_bin == deployed_bin;
 
-    Ok(BinVerification { state, is_verified })
+    Ok(BinVerification {
+        state,
+        is_verified,
+        local_hash: executable_hash(&local_bin),
+        deployed_hash: executable_hash(&deployed_bin),
+    })
+}
+
+/// Hash of a program binary without the zero bytes it's padded with on
+/// chain, so it can be compared with the hash of the built binary.
+fn executable_hash(bin: &[u8]) -> Hash {
+    let len = bin.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+    solana_sdk::hash::hash(&bin[..len])
 }
 
 #[derive(PartialEq, Eq)]
+#[non_exhaustive]
 pub struct BinVerification {
     pub state: BinVerificationState,
     pub is_verified: bool,
+    /// Hash of the local binary, see [`executable_hash`].
+    pub local_hash: Hash,
+    /// Hash of the deployed binary, see [`executable_hash`].
+    pub deployed_hash: Hash,
 }
 
 #[derive(PartialEq, Eq)]
@@ -2156,6 +3260,7 @@ fn idl(cfg_override: &ConfigOverride, subcmd: IdlCommand) -> Result<()> {
             program_id,
             idl_address,
             print_only,
+            refund_to,
             priority_fee,
         } => {
             let closed_address = idl_close(
@@ -2163,6 +3268,7 @@ fn idl(cfg_override: &ConfigOverride, subcmd: IdlCommand) -> Result<()> {
                 program_id,
                 idl_address,
                 print_only,
+                refund_to,
                 priority_fee,
             )?;
             if !print_only {
@@ -2173,9 +3279,11 @@ fn idl(cfg_override: &ConfigOverride, subcmd: IdlCommand) -> Result<()> {
         IdlCommand::WriteBuffer {
             program_id,
             filepath,
+            buffer,
             priority_fee,
         } => {
-            let idl_buffer = idl_write_buffer(cfg_override, program_id, filepath, priority_fee)?;
+            let idl_buffer =
+                idl_write_buffer(cfg_override, program_id, filepath, buffer, priority_fee)?;
             println!("Idl buffer created: {idl_buffer}");
             Ok(())
         }
@@ -2188,8 +3296,9 @@ fn idl(cfg_override: &ConfigOverride, subcmd: IdlCommand) -> Result<()> {
         IdlCommand::Upgrade {
             program_id,
             filepath,
+            buffer,
             priority_fee,
-        } => idl_upgrade(cfg_override, program_id, filepath, priority_fee),
+        } => idl_upgrade(cfg_override, program_id, filepath, buffer, priority_fee),
         IdlCommand::SetAuthority {
             program_id,
             address,
@@ -2216,9 +3325,15 @@ fn idl(cfg_override: &ConfigOverride, subcmd: IdlCommand) -> Result<()> {
             no_docs,
             skip_lint,
         } => idl_build(cfg_override, program_name, out, out_ts, no_docs, skip_lint),
-        IdlCommand::Fetch { address, out } => idl_fetch(cfg_override, address, out),
-        IdlCommand::Convert { path, out } => idl_convert(path, out),
+        IdlCommand::Fetch { address, out, all } => match address {
+            Some(address) if !all => idl_fetch(cfg_override, address, out),
+            _ => idl_fetch_all(cfg_override),
+        },
+        IdlCommand::Convert { path, out, legacy } => idl_convert(path, out, legacy),
         IdlCommand::Type { path, out } => idl_type(path, out),
+        IdlCommand::Schema { path, out } => idl_schema(path, out),
+        IdlCommand::Diff { old, new } => idl_diff(old, new),
+        IdlCommand::Check { program_name } => idl_check(cfg_override, program_name),
     }
 }
 
@@ -2239,8 +3354,11 @@ fn fetch_idl(cfg_override: &ConfigOverride, idl_addr: Pubkey) -> Result<Idl> {
         }
     };
 
-    let client = create_client(url);
+    fetch_idl_from(&create_client(url), idl_addr)
+}
 
+// Fetches the IDL of a program, or at the IDL account address, with the client.
+fn fetch_idl_from(client: &RpcClient, idl_addr: Pubkey) -> Result<Idl> {
     let mut account = client.get_account(&idl_addr)?;
     if account.executable {
         let idl_addr = IdlAccount::address(&idl_addr);
@@ -2289,11 +3407,19 @@ fn idl_close(
     program_id: Pubkey,
     idl_address: Option<Pubkey>,
     print_only: bool,
+    refund_to: Option<Pubkey>,
     priority_fee: Option<u64>,
 ) -> Result<Pubkey> {
     with_workspace(cfg_override, |cfg| {
         let idl_address = idl_address.unwrap_or_else(|| IdlAccount::address(&program_id));
-        idl_close_account(cfg, &program_id, idl_address, print_only, priority_fee)?;
+        idl_close_account(
+            cfg,
+            &program_id,
+            idl_address,
+            print_only,
+            refund_to,
+            priority_fee,
+        )?;
 
         Ok(idl_address)
     })
@@ -2303,6 +3429,7 @@ fn idl_write_buffer(
     cfg_override: &ConfigOverride,
     program_id: Pubkey,
     idl_filepath: String,
+    buffer: Option<Pubkey>,
     priority_fee: Option<u64>,
 ) -> Result<Pubkey> {
     with_workspace(cfg_override, |cfg| {
@@ -2311,8 +3438,16 @@ fn idl_write_buffer(
         let bytes = fs::read(idl_filepath)?;
         let idl: Idl = serde_json::from_reader(&*bytes)?;
 
-        let idl_buffer = create_idl_buffer(cfg, &keypair, &program_id, &idl, priority_fee)?;
-        idl_write(cfg, &program_id, &idl, idl_buffer, priority_fee)?;
+        let idl_buffer = match buffer {
+            Some(buffer) => buffer,
+            None => create_idl_buffer(cfg, &keypair, &program_id, &idl, priority_fee)?,
+        };
+        idl_write(cfg, &program_id, &idl, idl_buffer, priority_fee).with_context(|| {
+            format!(
+                "Failed to write the IDL to buffer {idl_buffer}, run the command again with \
+                `--buffer {idl_buffer}` to resume"
+            )
+        })?;
 
         Ok(idl_buffer)
     })
@@ -2391,9 +3526,11 @@ fn idl_upgrade(
     cfg_override: &ConfigOverride,
     program_id: Pubkey,
     idl_filepath: String,
+    buffer: Option<Pubkey>,
     priority_fee: Option<u64>,
 ) -> Result<()> {
-    let buffer_address = idl_write_buffer(cfg_override, program_id, idl_filepath, priority_fee)?;
+    let buffer_address =
+        idl_write_buffer(cfg_override, program_id, idl_filepath, buffer, priority_fee)?;
     let idl_address = idl_set_buffer(
         cfg_override,
         program_id,
@@ -2406,6 +3543,7 @@ fn idl_upgrade(
         program_id,
         Some(buffer_address),
         false,
+        None,
         priority_fee,
     )?;
     println!("Idl account {idl_address} successfully upgraded");
@@ -2528,6 +3666,7 @@ fn idl_close_account(
     program_id: &Pubkey,
     idl_address: Pubkey,
     print_only: bool,
+    refund_to: Option<Pubkey>,
     priority_fee: Option<u64>,
 ) -> Result<()> {
     let keypair = get_keypair(&cfg.provider.wallet.to_string())?;
@@ -2543,7 +3682,7 @@ fn idl_close_account(
     let accounts = vec![
         AccountMeta::new(idl_address, false),
         AccountMeta::new_readonly(idl_authority, true),
-        AccountMeta::new(keypair.pubkey(), false),
+        AccountMeta::new(refund_to.unwrap_or_else(|| keypair.pubkey()), false),
     ];
     // Instruction.
     let ix = Instruction {
@@ -2574,6 +3713,9 @@ fn idl_close_account(
 // Write the idl to the account buffer, chopping up the IDL into pieces
 // and sending multiple transactions in the event the IDL doesn't fit into
 // a single transaction.
+//
+// The write resumes from the data already in the account, which is read again
+// after each transaction so that a chunk is never written twice.
 fn idl_write(
     cfg: &Config,
     program_id: &Pubkey,
@@ -2586,26 +3728,27 @@ fn idl_write(
     let url = cluster_url(cfg, &cfg.test_validator);
     let client = create_client(url);
 
-    // Serialize and compress the idl.
-    let idl_data = {
-        let json_bytes = serde_json::to_vec(idl)?;
-        let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
-        e.write_all(&json_bytes)?;
-        e.finish()?
-    };
-
+    let idl_data = serialize_idl(idl)?;
     println!("Idl data length: {:?} bytes", idl_data.len());
 
     const MAX_WRITE_SIZE: usize = 600;
-    let mut offset = 0;
-    while offset < idl_data.len() {
-        println!("Step {offset}/{} ", idl_data.len());
+    const MAX_RETRIES: u32 = 20;
+    let mut retries = 0;
+    loop {
+        let offset = idl_written_len(&client, &idl_address, &idl_data)?;
+        if offset == idl_data.len() {
+            break;
+        }
+        println!(
+            "Written {offset}/{} bytes ({}%)",
+            idl_data.len(),
+            offset * 100 / idl_data.len()
+        );
         // Instruction data.
         let data = {
-            let start = offset;
             let end = std::cmp::min(offset + MAX_WRITE_SIZE, idl_data.len());
             serialize_idl_ix(anchor_lang::idl::IdlInstruction::Write {
-                data: idl_data[start..end].to_vec(),
+                data: idl_data[offset..end].to_vec(),
             })?
         };
         // Instruction accounts.
@@ -2621,35 +3764,55 @@ fn idl_write(
         };
         // Send transaction.
         let instructions = prepend_compute_unit_ix(vec![ix], &client, priority_fee)?;
-
-        let mut latest_hash = client.get_latest_blockhash()?;
-        for retries in 0..20 {
-            if !client.is_blockhash_valid(&latest_hash, client.commitment())? {
-                latest_hash = client.get_latest_blockhash()?;
-            }
-            let tx = Transaction::new_signed_with_payer(
-                &instructions,
-                Some(&keypair.pubkey()),
-                &[&keypair],
-                latest_hash,
-            );
-
-            match client.send_and_confirm_transaction_with_spinner(&tx) {
-                Ok(_) => break,
-                Err(e) => {
-                    if retries == 19 {
-                        return Err(anyhow!("Error: {e}. Failed to send transaction."));
-                    }
-                    println!("Error: {e}. Retrying transaction.");
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            client.get_latest_blockhash()?,
+        );
+        match client.send_and_confirm_transaction_with_spinner(&tx) {
+            Ok(_) => retries = 0,
+            Err(e) => {
+                retries += 1;
+                if retries == MAX_RETRIES {
+                    return Err(anyhow!("Error: {e}. Failed to send transaction."));
                 }
+                println!("Error: {e}. Retrying transaction.");
             }
         }
-
-        offset += MAX_WRITE_SIZE;
     }
+    println!("Written {0}/{0} bytes (100%)", idl_data.len());
+
     Ok(())
 }
 
+// Returns the length of the data already written to an IDL account, which
+// must be the start of the IDL data and leave room for the rest of it.
+fn idl_written_len(client: &RpcClient, idl_address: &Pubkey, idl_data: &[u8]) -> Result<usize> {
+    let account = client.get_account(idl_address)?;
+    let mut data: &[u8] = &account.data;
+    let idl_account: IdlAccount =
+        AccountDeserialize::try_deserialize(&mut data).map_err(|e| anyhow!("{:?}", e))?;
+
+    // The data follows the discriminator, the authority and its length
+    let written = idl_account.data_len as usize;
+    let data = account.data.get(44..).unwrap_or_default();
+    if data.len() < idl_data.len() {
+        return Err(anyhow!(
+            "The IDL account {idl_address} has room for {} bytes but the IDL takes {} bytes",
+            data.len(),
+            idl_data.len()
+        ));
+    }
+    if idl_data.get(..written) != data.get(..written) {
+        return Err(anyhow!(
+            "The IDL account {idl_address} already holds the data of another IDL"
+        ));
+    }
+
+    Ok(written)
+}
+
 fn idl_build(
     cfg_override: &ConfigOverride,
     program_name: Option<String>,
@@ -2733,14 +3896,100 @@ fn idl_fetch(cfg_override: &ConfigOverride, address: Pubkey, out: Option<String>
     write_idl(&idl, out)
 }
 
-fn idl_convert(path: String, out: Option<String>) -> Result<()> {
+// Fetches the IDLs of the programs of the workspace and of its dependencies
+// concurrently, so that the `declare_program!` inputs in `idls/` are up to date.
+fn idl_fetch_all(cfg_override: &ConfigOverride) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        // Programs of the workspace must have an IDL, cloned accounts may not
+        // be Anchor programs
+        let mut addresses = cfg
+            .programs
+            .get(&cfg.provider.cluster)
+            .into_iter()
+            .flatten()
+            .map(|(_, deployment)| (deployment.address, true))
+            .collect::<BTreeMap<_, _>>();
+        let clones = cfg
+            .test_validator
+            .as_ref()
+            .and_then(|test| test.validator.as_ref())
+            .and_then(|validator| validator.clone.as_ref())
+            .into_iter()
+            .flatten();
+        for entry in clones {
+            let address = Pubkey::from_str(&entry.address)
+                .map_err(|_| anyhow!("Invalid clone address: {}", entry.address))?;
+            addresses.entry(address).or_insert(false);
+        }
+
+        let results = std::thread::scope(|scope| {
+            let handles = addresses
+                .keys()
+                .map(|address| scope.spawn(move || fetch_idl(cfg_override, *address)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("IDL fetch panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let idl_dir = cfg.path().parent().unwrap().join("idls");
+        fs::create_dir_all(&idl_dir)?;
+        let mut missing = vec![];
+        for ((address, required), result) in addresses.into_iter().zip(results) {
+            match result {
+                Ok(idl) => {
+                    let idl_path = idl_dir.join(format!("{}.json", idl.metadata.name));
+                    write_idl(&idl, OutFile::File(idl_path.clone()))?;
+                    println!("Fetched the IDL of {address} to {}", idl_path.display());
+                }
+                Err(e) if required => {
+                    eprintln!("Failed to fetch the IDL of {address}: {e}");
+                    missing.push(address.to_string());
+                }
+                Err(e) => println!("Skipped {address}, no IDL found: {e}"),
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "Failed to fetch the IDLs of {}",
+                missing.join(", ")
+            ));
+        }
+        Ok(())
+    })
+}
+
+fn idl_convert(path: String, out: Option<String>, legacy: bool) -> Result<()> {
     let idl = fs::read(path)?;
-    let idl = convert_idl(&idl)?;
-    let out = match out {
-        None => OutFile::Stdout,
-        Some(out) => OutFile::File(PathBuf::from(out)),
+    let (idl, mut report) = convert_idl_with_report(&idl)?;
+
+    // Features that couldn't be represented are reported rather than failing
+    // the conversion, as partial IDLs are still useful to most tools.
+    let print_report = |report: &[String]| {
+        for item in report {
+            eprintln!("WARNING: {item}");
+        }
     };
-    write_idl(&idl, out)
+    if !legacy {
+        print_report(&report);
+        let out = match out {
+            None => OutFile::Stdout,
+            Some(out) => OutFile::File(PathBuf::from(out)),
+        };
+        return write_idl(&idl, out);
+    }
+
+    let (legacy_idl, legacy_report) = convert_idl_to_legacy(&idl)?;
+    report.extend(legacy_report);
+    print_report(&report);
+    let legacy_idl = serde_json::to_string_pretty(&legacy_idl)?;
+    match out {
+        Some(out) => fs::write(out, legacy_idl)?,
+        None => println!("{legacy_idl}"),
+    };
+    Ok(())
 }
 
 fn idl_type(path: String, out: Option<String>) -> Result<()> {
@@ -2754,28 +4003,128 @@ fn idl_type(path: String, out: Option<String>) -> Result<()> {
     Ok(())
 }
 
-fn idl_ts(idl: &Idl) -> Result<String> {
-    let idl_name = &idl.metadata.name;
-    let type_name = idl_name.to_pascal_case();
-    let idl = serde_json::to_string(idl)?;
-
-    // Convert every field of the IDL to camelCase
-    let camel_idl = Regex::new(r#""\w+":"([\w\d]+)""#)?
-        .captures_iter(&idl)
-        .fold(idl.clone(), |acc, cur| {
-            let name = cur.get(1).unwrap().as_str();
-
-            // Do not modify pubkeys
-            if Pubkey::from_str(name).is_ok() {
-                return acc;
+fn idl_schema(path: String, out: Option<String>) -> Result<()> {
+    let idl = fs::read(path)?;
+    let idl = convert_idl(&idl)?;
+    let schemas = anchor_lang_idl::schema::schemas(&idl)?;
+    match out {
+        // One file per schema, e.g. `accounts/Vault.json`
+        Some(out) => {
+            for (name, schema) in schemas {
+                let path = Path::new(&out).join(format!("{name}.json"));
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(path, serde_json::to_string_pretty(&schema)?)?;
             }
+        }
+        _ => println!("{}", serde_json::to_string_pretty(&schemas)?),
+    };
+    Ok(())
+}
 
-            let camel_name = name.to_lower_camel_case();
-            acc.replace(&format!(r#""{name}""#), &format!(r#""{camel_name}""#))
-        });
+fn idl_diff(old: String, new: String) -> Result<()> {
+    let old = convert_idl(&fs::read(old)?)?;
+    let new = convert_idl(&fs::read(new)?)?;
 
-    // Pretty format
-    let camel_idl = serde_json::to_string_pretty(&serde_json::from_str::<Idl>(&camel_idl)?)?;
+    let changes = anchor_lang_idl::diff::diff(&old, &new);
+    if changes.is_empty() {
+        println!("No changes");
+        return Ok(());
+    }
+    for change in &changes {
+        println!("{change}");
+    }
+
+    let breaking = changes.iter().filter(|change| change.is_breaking()).count();
+    if breaking != 0 {
+        return Err(anyhow!("Found {breaking} breaking change(s)"));
+    }
+
+    Ok(())
+}
+
+fn idl_check(cfg_override: &ConfigOverride, program_name: Option<String>) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let url = cluster_url(cfg, &cfg.test_validator);
+        let client = create_client(url);
+        let deployments = cfg.programs.get(&cfg.provider.cluster);
+
+        let mut outdated = 0;
+        for program in cfg.read_all_programs()? {
+            if program_name
+                .as_ref()
+                .map_or(false, |name| *name != program.lib_name)
+            {
+                continue;
+            }
+            let idl = program.idl.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "IDL of {} not found, build the program first",
+                    program.lib_name
+                )
+            })?;
+            let program_id = match deployments.and_then(|d| d.get(&program.lib_name)) {
+                Some(deployment) => deployment.address,
+                None => program.pubkey()?,
+            };
+
+            let on_chain_idl = match fetch_idl_from(&client, program_id) {
+                Ok(idl) => idl,
+                Err(e) => {
+                    println!(
+                        "{}: no IDL found on chain for {program_id}: {e}",
+                        program.lib_name
+                    );
+                    outdated += 1;
+                    continue;
+                }
+            };
+            let changes = anchor_lang_idl::diff::diff(&on_chain_idl, idl);
+            if changes.is_empty() {
+                println!("{}: the on-chain IDL is up to date", program.lib_name);
+                continue;
+            }
+            println!(
+                "{}: the on-chain IDL differs from the built IDL",
+                program.lib_name
+            );
+            for change in &changes {
+                println!("  {change}");
+            }
+            outdated += 1;
+        }
+
+        if outdated != 0 {
+            return Err(anyhow!(
+                "Found {outdated} program(s) whose on-chain IDL is outdated, \
+                publish them with `anchor idl upgrade`"
+            ));
+        }
+        Ok(())
+    })
+}
+
+fn idl_ts(idl: &Idl) -> Result<String> {
+    let idl_name = &idl.metadata.name;
+    let type_name = idl_name.to_pascal_case();
+    let idl = serde_json::to_string(idl)?;
+
+    // Convert every field of the IDL to camelCase
+    let camel_idl = Regex::new(r#""\w+":"([\w\d]+)""#)?
+        .captures_iter(&idl)
+        .fold(idl.clone(), |acc, cur| {
+            let name = cur.get(1).unwrap().as_str();
+
+            // Do not modify pubkeys
+            if Pubkey::from_str(name).is_ok() {
+                return acc;
+            }
+
+            let camel_name = name.to_lower_camel_case();
+            acc.replace(&format!(r#""{name}""#), &format!(r#""{camel_name}""#))
+        });
+
+    // Pretty format
+    let camel_idl = serde_json::to_string_pretty(&serde_json::from_str::<Idl>(&camel_idl)?)?;
 
     Ok(format!(
         r#"/**
@@ -2801,13 +4150,20 @@ fn write_idl(idl: &Idl, out: OutFile) -> Result<()> {
 
 /// Print `base64+borsh` encoded IDL instruction.
 fn print_idl_instruction(ix_name: &str, ix: &Instruction, idl_address: &Pubkey) -> Result<()> {
-    use base64::engine::general_purpose::STANDARD;
-    use base64::Engine;
-
     println!("Print only mode. No execution!");
     println!("Instruction: {ix_name}");
     println!("IDL address: {idl_address}");
     println!("Program: {}", ix.program_id);
+    println!("Base64 encoded instruction: {}", encode_instruction(ix)?);
+
+    Ok(())
+}
+
+/// Encode an instruction with `base64+borsh`, the format multisig programs
+/// expect when importing instructions.
+fn encode_instruction(ix: &Instruction) -> Result<String> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
 
     // Serialize with `bincode` because `Instruction` does not implement `BorshSerialize`
     let mut serialized_ix = bincode::serialize(ix)?;
@@ -2823,12 +4179,7 @@ fn print_idl_instruction(ix_name: &str, ix: &Instruction, idl_address: &Pubkey)
     let data_index = accounts_index + 4 + std::mem::size_of_val(&*ix.accounts);
     remove_extra_vec_bytes(data_index);
 
-    println!(
-        "Base64 encoded instruction: {}",
-        STANDARD.encode(serialized_ix)
-    );
-
-    Ok(())
+    Ok(STANDARD.encode(serialized_ix))
 }
 
 fn account(
@@ -2837,16 +4188,82 @@ fn account(
     address: Pubkey,
     idl_filepath: Option<String>,
 ) -> Result<()> {
-    let (program_name, account_type_name) = account_type
-        .split_once('.') // Split at first occurrence of dot
-        .and_then(|(x, y)| y.find('.').map_or_else(|| Some((x, y)), |_| None)) // ensures no dots in second substring
-        .ok_or_else(|| {
-            anyhow!(
-                "Please enter the account struct in the following format: <program_name>.<Account>",
-            )
-        })?;
+    let (program_name, account_type_name) = match account_type.split_once('.') {
+        Some((program_name, account_type_name)) => (program_name, Some(account_type_name)),
+        None => (account_type.as_str(), None),
+    };
+    if account_type_name.map_or(false, |name| name.contains('.')) {
+        return Err(anyhow!(
+            "Please enter the account struct in the following format: <program_name>.<Account>",
+        ));
+    }
+
+    let idl = program_idl(cfg_override, program_name, idl_filepath);
+    let cluster = command_cluster(cfg_override)?;
+
+    let client = create_client(cluster.url());
+    let account = client.get_account(&address)?;
+
+    // Token accounts and mints are parsed by the RPC node, which also
+    // humanizes the amounts with the decimals of the mint.
+    if account.owner == TOKEN_PROGRAM_ID || account.owner == TOKEN_2022_PROGRAM_ID {
+        let parsed: JsonValue = client.send(
+            RpcRequest::GetAccountInfo,
+            json!([address.to_string(), { "encoding": "jsonParsed" }]),
+        )?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&parsed["value"]["data"]["parsed"]).unwrap()
+        );
+        return Ok(());
+    }
+
+    let data = account.data;
+    let deserialized_json = match account_type_name {
+        Some(name) => {
+            let (len, generics) = idl
+                .accounts
+                .iter()
+                .find(|acc| acc.name == name)
+                .map_or((8, vec![]), |acc| {
+                    (acc.discriminator.len(), acc.generics.clone())
+                });
+            if data.len() < len {
+                return Err(anyhow!(
+                    "The account has less than {len} bytes and is not an Anchor account."
+                ));
+            }
+            let ty = IdlType::Defined {
+                name: name.to_owned(),
+                generics,
+            };
+            anchor_lang_idl::codec::decode_type(&idl, &ty, &data[len..])?
+        }
+        None => {
+            let (name, value) =
+                anchor_lang_idl::codec::decode_account(&idl, &data).map_err(|_| {
+                    anyhow!("The account doesn't match any account of program {program_name}.")
+                })?;
+            eprintln!("Account type: {name}");
+            value
+        }
+    };
 
-    let idl = idl_filepath.map_or_else(
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&deserialized_json).unwrap()
+    );
+
+    Ok(())
+}
+
+// Returns the IDL of a workspace program, or the IDL file given instead.
+fn program_idl(
+    cfg_override: &ConfigOverride,
+    program_name: &str,
+    idl_filepath: Option<String>,
+) -> Idl {
+    idl_filepath.map_or_else(
         || {
             Config::discover(cfg_override)
                 .expect("Error when detecting workspace.")
@@ -2871,218 +4288,443 @@ fn account(
 
             idl
         },
-    );
+    )
+}
 
-    let cluster = match &cfg_override.cluster {
+// Returns the cluster of commands that can run outside of a workspace.
+fn command_cluster(cfg_override: &ConfigOverride) -> Result<Cluster> {
+    Ok(match &cfg_override.cluster {
         Some(cluster) => cluster.clone(),
         None => Config::discover(cfg_override)?
             .map(|cfg| cfg.provider.cluster.clone())
             .unwrap_or(Cluster::Localnet),
-    };
+    })
+}
 
-    let data = create_client(cluster.url()).get_account_data(&address)?;
-    if data.len() < 8 {
-        return Err(anyhow!(
-            "The account has less than 8 bytes and is not an Anchor account."
-        ));
+fn events(
+    cfg_override: &ConfigOverride,
+    program_name: String,
+    follow: bool,
+    since: Option<String>,
+    idl_filepath: Option<String>,
+) -> Result<()> {
+    if !follow && since.is_none() {
+        return Err(anyhow!("Either `--follow` or `--since` must be provided."));
     }
-    let mut data_view = &data[8..];
-
-    let deserialized_json =
-        deserialize_idl_defined_type_to_json(&idl, account_type_name, &mut data_view)?;
 
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&deserialized_json).unwrap()
-    );
+    let idl = program_idl(cfg_override, &program_name, idl_filepath);
+    let program_id = Pubkey::from_str(&idl.address)?;
+    let cluster = command_cluster(cfg_override)?;
+
+    // Subscribe before backfilling so that no event is missed in between.
+    let subscription = follow
+        .then(|| {
+            PubsubClient::logs_subscribe(
+                cluster.ws_url(),
+                RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+        })
+        .transpose()?;
+
+    let mut backfilled = HashSet::new();
+    if let Some(since) = since {
+        let client = create_client(cluster.url());
+        let (until, since_slot) = match since.parse::<u64>() {
+            Ok(slot) => (None, Some(slot)),
+            Err(_) => (Some(Signature::from_str(&since)?), None),
+        };
 
-    Ok(())
-}
+        // Signatures are returned from the most recent one, page by page.
+        let mut signatures = Vec::new();
+        let mut before = None;
+        'pages: loop {
+            let page = client.get_signatures_for_address_with_config(
+                &program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: None,
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(Signature::from_str(&last.signature)?);
+            for status in page {
+                if since_slot.map_or(false, |slot| status.slot < slot) {
+                    break 'pages;
+                }
+                if status.err.is_none() {
+                    signatures.push((status.signature, status.slot));
+                }
+            }
+        }
 
-// Deserializes user defined IDL types by munching the account data(recursively).
-fn deserialize_idl_defined_type_to_json(
-    idl: &Idl,
-    defined_type_name: &str,
-    data: &mut &[u8],
-) -> Result<JsonValue, anyhow::Error> {
-    let defined_type = &idl
-        .accounts
-        .iter()
-        .find(|acc| acc.name == defined_type_name)
-        .and_then(|acc| idl.types.iter().find(|ty| ty.name == acc.name))
-        .or_else(|| idl.types.iter().find(|ty| ty.name == defined_type_name))
-        .ok_or_else(|| anyhow!("Type `{}` not found in IDL.", defined_type_name))?
-        .ty;
-
-    let mut deserialized_fields = Map::new();
-
-    match defined_type {
-        IdlTypeDefTy::Struct { fields } => {
-            if let Some(fields) = fields {
-                match fields {
-                    IdlDefinedFields::Named(fields) => {
-                        for field in fields {
-                            deserialized_fields.insert(
-                                field.name.clone(),
-                                deserialize_idl_type_to_json(&field.ty, data, idl)?,
-                            );
-                        }
-                    }
-                    IdlDefinedFields::Tuple(fields) => {
-                        let mut values = Vec::new();
-                        for field in fields {
-                            values.push(deserialize_idl_type_to_json(field, data, idl)?);
-                        }
-                        deserialized_fields
-                            .insert(defined_type_name.to_owned(), JsonValue::Array(values));
+        for (signature, slot) in signatures.into_iter().rev() {
+            let tx: JsonValue = client.send(
+                RpcRequest::GetTransaction,
+                json!([
+                    signature,
+                    {
+                        "encoding": "json",
+                        "commitment": "confirmed",
+                        "maxSupportedTransactionVersion": 0,
                     }
-                }
+                ]),
+            )?;
+            let logs: Vec<String> =
+                serde_json::from_value(tx["meta"]["logMessages"].clone()).unwrap_or_default();
+            print_events(&idl, &program_id, &signature, slot, &logs)?;
+            backfilled.insert(signature);
+        }
+    }
+
+    if let Some((_subscription, receiver)) = subscription {
+        for logs in receiver {
+            if logs.value.err.is_some() || backfilled.contains(&logs.value.signature) {
+                continue;
             }
+            print_events(
+                &idl,
+                &program_id,
+                &logs.value.signature,
+                logs.context.slot,
+                &logs.value.logs,
+            )?;
         }
-        IdlTypeDefTy::Enum { variants } => {
-            let repr = <u8 as AnchorDeserialize>::deserialize(data)?;
+    }
 
-            let variant = variants
-                .get(repr as usize)
-                .unwrap_or_else(|| panic!("Error while deserializing enum variant {repr}"));
+    Ok(())
+}
 
-            let mut value = json!({});
+// Prints the events emitted by the program in the logs of a transaction.
+fn print_events(
+    idl: &Idl,
+    program_id: &Pubkey,
+    signature: &str,
+    slot: u64,
+    logs: &[String],
+) -> Result<()> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
 
-            if let Some(enum_field) = &variant.fields {
-                match enum_field {
-                    IdlDefinedFields::Named(fields) => {
-                        let mut values = Map::new();
-                        for field in fields {
-                            values.insert(
-                                field.name.clone(),
-                                deserialize_idl_type_to_json(&field.ty, data, idl)?,
-                            );
-                        }
-                        value = JsonValue::Object(values);
-                    }
-                    IdlDefinedFields::Tuple(fields) => {
-                        let mut values = Vec::new();
-                        for field in fields {
-                            values.push(deserialize_idl_type_to_json(field, data, idl)?);
-                        }
-                        value = JsonValue::Array(values);
-                    }
-                }
+    let program_id = program_id.to_string();
+    let invoke = Regex::new(r"^Program (\S+) invoke \[\d+\]$").unwrap();
+    let exit = Regex::new(r"^Program (\S+) (success|failed)").unwrap();
+
+    // Events are only read from `Program data` logs of the program itself,
+    // not of the programs it invokes.
+    let mut stack = Vec::new();
+    for log in logs {
+        if let Some(captures) = invoke.captures(log) {
+            stack.push(captures[1].to_owned());
+        } else if exit.is_match(log) {
+            stack.pop();
+        } else if let Some(data) = log.strip_prefix("Program data: ") {
+            if stack.last() != Some(&program_id) {
+                continue;
             }
-
-            deserialized_fields.insert(variant.name.clone(), value);
-        }
-        IdlTypeDefTy::Type { alias } => {
-            return deserialize_idl_type_to_json(alias, data, idl);
+            let Ok(data) = STANDARD.decode(data) else {
+                eprintln!("Skipping invalid program data of {signature}: {data}");
+                continue;
+            };
+            // Skip the data that isn't an event of the program
+            if !idl
+                .events
+                .iter()
+                .any(|event| data.starts_with(&event.discriminator))
+            {
+                continue;
+            }
+            // A malformed event mustn't end the stream of the following ones
+            let (name, data) = match anchor_lang_idl::codec::decode_event(idl, &data) {
+                Ok(event) => event,
+                Err(e) => {
+                    eprintln!("Skipping malformed event of {signature}: {e}");
+                    continue;
+                }
+            };
+            println!(
+                "{}",
+                json!({
+                    "signature": signature,
+                    "slot": slot,
+                    "name": name,
+                    "data": data,
+                })
+            );
         }
     }
 
-    Ok(JsonValue::Object(deserialized_fields))
+    Ok(())
 }
 
-// Deserializes a primitive type using AnchorDeserialize
-fn deserialize_idl_type_to_json(
-    idl_type: &IdlType,
-    data: &mut &[u8],
-    parent_idl: &Idl,
-) -> Result<JsonValue, anyhow::Error> {
-    if data.is_empty() {
-        return Err(anyhow::anyhow!("Unable to parse from empty bytes"));
+fn decode(cfg_override: &ConfigOverride, subcmd: DecodeCommand) -> Result<()> {
+    match subcmd {
+        DecodeCommand::Tx {
+            signature,
+            cluster,
+            idls,
+        } => decode_tx(cfg_override, signature, cluster, idls),
     }
+}
 
-    Ok(match idl_type {
-        IdlType::Bool => json!(<bool as AnchorDeserialize>::deserialize(data)?),
-        IdlType::U8 => {
-            json!(<u8 as AnchorDeserialize>::deserialize(data)?)
-        }
-        IdlType::I8 => {
-            json!(<i8 as AnchorDeserialize>::deserialize(data)?)
-        }
-        IdlType::U16 => {
-            json!(<u16 as AnchorDeserialize>::deserialize(data)?)
-        }
-        IdlType::I16 => {
-            json!(<i16 as AnchorDeserialize>::deserialize(data)?)
-        }
-        IdlType::U32 => {
-            json!(<u32 as AnchorDeserialize>::deserialize(data)?)
-        }
-        IdlType::I32 => {
-            json!(<i32 as AnchorDeserialize>::deserialize(data)?)
-        }
-        IdlType::F32 => json!(<f32 as AnchorDeserialize>::deserialize(data)?),
-        IdlType::U64 => {
-            json!(<u64 as AnchorDeserialize>::deserialize(data)?)
-        }
-        IdlType::I64 => {
-            json!(<i64 as AnchorDeserialize>::deserialize(data)?)
+// Instruction of a transaction, instructions being in the order of execution.
+struct TxInstruction {
+    // Depth of the invocation, 0 for the instructions of the transaction
+    depth: usize,
+    program_id: String,
+    accounts: Vec<String>,
+    data: Vec<u8>,
+    // Events emitted by the instruction, decoded from the logs
+    events: Vec<(String, JsonValue)>,
+}
+
+fn decode_tx(
+    cfg_override: &ConfigOverride,
+    signature: Signature,
+    cluster: Option<Cluster>,
+    idl_filepaths: Vec<String>,
+) -> Result<()> {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    let cluster = match cluster {
+        Some(cluster) => cluster,
+        None => command_cluster(cfg_override)?,
+    };
+    let client = create_client(cluster.url());
+    let tx: JsonValue = client.send(
+        RpcRequest::GetTransaction,
+        json!([
+            signature.to_string(),
+            {
+                "encoding": "json",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0,
+            }
+        ]),
+    )?;
+    if tx.is_null() {
+        return Err(anyhow!(
+            "Transaction {signature} not found on {}",
+            cluster.url()
+        ));
+    }
+
+    // Addresses loaded from lookup tables come after the static ones
+    let message = &tx["transaction"]["message"];
+    let meta = &tx["meta"];
+    let account_keys = [
+        &message["accountKeys"],
+        &meta["loadedAddresses"]["writable"],
+        &meta["loadedAddresses"]["readonly"],
+    ]
+    .into_iter()
+    .filter_map(|keys| keys.as_array())
+    .flatten()
+    .filter_map(|key| key.as_str().map(ToOwned::to_owned))
+    .collect::<Vec<_>>();
+    let key = |index: &JsonValue| {
+        index
+            .as_u64()
+            .and_then(|index| account_keys.get(index as usize))
+            .cloned()
+            .ok_or_else(|| anyhow!("Invalid account index {index}"))
+    };
+    let instruction = |ix: &JsonValue, depth: usize| -> Result<TxInstruction> {
+        Ok(TxInstruction {
+            depth,
+            program_id: key(&ix["programIdIndex"])?,
+            accounts: ix["accounts"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(key)
+                .collect::<Result<_>>()?,
+            data: solana_sdk::bs58::decode(ix["data"].as_str().unwrap_or_default()).into_vec()?,
+            events: vec![],
+        })
+    };
+
+    // Inner instructions follow the instruction of the transaction invoking them
+    let mut instructions = Vec::new();
+    let inner_instructions = meta["innerInstructions"].as_array();
+    for (index, ix) in message["instructions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        instructions.push(instruction(ix, 0)?);
+        let inner = inner_instructions
+            .into_iter()
+            .flatten()
+            .filter(|inner| inner["index"].as_u64() == Some(index as u64))
+            .filter_map(|inner| inner["instructions"].as_array())
+            .flatten();
+        for ix in inner {
+            // Stack heights start at 1 with the instructions of the transaction
+            let depth = ix["stackHeight"]
+                .as_u64()
+                .map_or(1, |height| height.saturating_sub(1) as usize);
+            instructions.push(instruction(ix, depth)?);
         }
-        IdlType::F64 => json!(<f64 as AnchorDeserialize>::deserialize(data)?),
-        IdlType::U128 => {
-            // TODO: Remove to_string once serde_json supports u128 deserialization
-            json!(<u128 as AnchorDeserialize>::deserialize(data)?.to_string())
+    }
+
+    // IDLs given, then of the workspace, then on chain
+    let mut idls = HashMap::new();
+    for path in idl_filepaths {
+        let bytes = fs::read(&path).with_context(|| format!("Unable to read IDL {path}"))?;
+        let idl: Idl = serde_json::from_slice(&bytes)?;
+        idls.insert(idl.address.clone(), idl);
+    }
+    if let Some(cfg) = Config::discover(cfg_override)? {
+        for idl in cfg.read_all_programs()?.into_iter().filter_map(|p| p.idl) {
+            idls.entry(idl.address.clone()).or_insert(idl);
         }
-        IdlType::I128 => {
-            // TODO: Remove to_string once serde_json supports i128 deserialization
-            json!(<i128 as AnchorDeserialize>::deserialize(data)?.to_string())
+    }
+    let program_ids = instructions
+        .iter()
+        .map(|ix| ix.program_id.clone())
+        .collect::<BTreeSet<_>>();
+    for program_id in program_ids {
+        if idls.contains_key(&program_id) {
+            continue;
         }
-        IdlType::U256 => todo!("Upon completion of u256 IDL standard"),
-        IdlType::I256 => todo!("Upon completion of i256 IDL standard"),
-        IdlType::Bytes => JsonValue::Array(
-            <Vec<u8> as AnchorDeserialize>::deserialize(data)?
-                .iter()
-                .map(|i| json!(*i))
-                .collect(),
-        ),
-        IdlType::String => json!(<String as AnchorDeserialize>::deserialize(data)?),
-        IdlType::Pubkey => {
-            json!(<Pubkey as AnchorDeserialize>::deserialize(data)?.to_string())
+        let idl = Pubkey::from_str(&program_id)
+            .ok()
+            .and_then(|program_id| fetch_idl_from(&client, program_id).ok());
+        if let Some(idl) = idl {
+            idls.insert(program_id, idl);
         }
-        IdlType::Array(ty, size) => match size {
-            IdlArrayLen::Value(size) => {
-                let mut array_data: Vec<JsonValue> = Vec::with_capacity(*size);
+    }
 
-                for _ in 0..*size {
-                    array_data.push(deserialize_idl_type_to_json(ty, data, parent_idl)?);
+    // Programs log their invocations in the order of the instructions, which
+    // tells the instruction emitting each event.
+    let invoke = Regex::new(r"^Program (\S+) invoke \[\d+\]$").unwrap();
+    let exit = Regex::new(r"^Program (\S+) (success|failed)").unwrap();
+    let logs = meta["logMessages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|log| log.as_str());
+    let mut next = 0;
+    let mut stack = Vec::new();
+    for log in logs {
+        if let Some(captures) = invoke.captures(log) {
+            // Precompiled programs are invoked without logs
+            let position = instructions[next..]
+                .iter()
+                .position(|ix| ix.program_id == captures[1]);
+            match position {
+                Some(position) => {
+                    stack.push(next + position);
+                    next += position + 1;
                 }
-
-                JsonValue::Array(array_data)
+                None => break,
             }
-            // TODO:
-            IdlArrayLen::Generic(_) => unimplemented!("Generic array length is not yet supported"),
-        },
-        IdlType::Option(ty) => {
-            let is_present = <u8 as AnchorDeserialize>::deserialize(data)?;
-
-            if is_present == 0 {
-                JsonValue::String("None".to_string())
-            } else {
-                deserialize_idl_type_to_json(ty, data, parent_idl)?
+        } else if exit.is_match(log) {
+            stack.pop();
+        } else if let Some(data) = log.strip_prefix("Program data: ") {
+            let index = match stack.last() {
+                Some(index) => *index,
+                None => continue,
+            };
+            let event = STANDARD
+                .decode(data)
+                .ok()
+                .zip(idls.get(&instructions[index].program_id))
+                .and_then(|(data, idl)| anchor_lang_idl::codec::decode_event(idl, &data).ok());
+            if let Some(event) = event {
+                instructions[index].events.push(event);
             }
         }
-        IdlType::Vec(ty) => {
-            let size: usize = <u32 as AnchorDeserialize>::deserialize(data)?
-                .try_into()
-                .unwrap();
+    }
 
-            let mut vec_data: Vec<JsonValue> = Vec::with_capacity(size);
+    println!("Transaction {signature}");
+    println!("Slot: {}", tx["slot"]);
+    match &meta["err"] {
+        JsonValue::Null => println!("Status: success"),
+        err => println!("Status: failed: {err}"),
+    }
+    let mut path: Vec<usize> = Vec::new();
+    for ix in &instructions {
+        path.resize(ix.depth + 1, 0);
+        path[ix.depth] += 1;
+        let number = path
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(".");
+        print_tx_instruction(&number, ix, idls.get(&ix.program_id));
+    }
 
-            for _ in 0..size {
-                vec_data.push(deserialize_idl_type_to_json(ty, data, parent_idl)?);
-            }
+    Ok(())
+}
 
-            JsonValue::Array(vec_data)
+// Prints an instruction decoded with the IDL of its program, if any.
+fn print_tx_instruction(number: &str, ix: &TxInstruction, idl: Option<&Idl>) {
+    let indent = "  ".repeat(ix.depth);
+    let program = idl.map_or(ix.program_id.as_str(), |idl| idl.metadata.name.as_str());
+    let decoded =
+        idl.and_then(|idl| anchor_lang_idl::codec::decode_instruction(idl, &ix.data).ok());
+    let account_names = match decoded {
+        Some((name, args)) => {
+            println!("{indent}[{number}] {program}::{name} ({})", ix.program_id);
+            println!("{indent}    args: {args}");
+            idl.and_then(|idl| idl.instructions.iter().find(|i| i.name == name))
+                .map(|i| instruction_account_names(&i.accounts, ""))
+                .unwrap_or_default()
         }
-        IdlType::Defined {
-            name,
-            generics: _generics,
-        } => {
-            // TODO: Generics
-            deserialize_idl_defined_type_to_json(parent_idl, name, data)?
+        None => {
+            // Events emitted with `emit_cpi!` are instructions of the program
+            // to itself
+            let event = idl
+                .filter(|_| ix.data.starts_with(&anchor_lang::event::EVENT_IX_TAG_LE))
+                .and_then(|idl| anchor_lang_idl::codec::decode_event(idl, &ix.data[8..]).ok());
+            match event {
+                Some((name, data)) => {
+                    println!("{indent}[{number}] {program} event {name}: {data}");
+                    return;
+                }
+                None => println!(
+                    "{indent}[{number}] {} ({} bytes of instruction data)",
+                    ix.program_id,
+                    ix.data.len()
+                ),
+            }
+            vec![]
         }
-        IdlType::Generic(generic) => json!(generic),
-        _ => unimplemented!("{idl_type:?}"),
-    })
+    };
+
+    for (i, account) in ix.accounts.iter().enumerate() {
+        match account_names.get(i) {
+            Some(name) => println!("{indent}    {name}: {account}"),
+            None => println!("{indent}    {account}"),
+        }
+    }
+    for (name, data) in &ix.events {
+        println!("{indent}    event {name}: {data}");
+    }
+}
+
+// Returns the names of the accounts of an instruction, those of composite
+// accounts prefixed with the name of the composite.
+fn instruction_account_names(accounts: &[IdlInstructionAccountItem], prefix: &str) -> Vec<String> {
+    accounts
+        .iter()
+        .flat_map(|acc| match acc {
+            IdlInstructionAccountItem::Single(acc) => vec![format!("{prefix}{}", acc.name)],
+            IdlInstructionAccountItem::Composite(accs) => {
+                instruction_account_names(&accs.accounts, &format!("{prefix}{}.", accs.name))
+            }
+        })
+        .collect()
 }
 
 enum OutFile {
@@ -3101,11 +4743,60 @@ fn test(
     skip_lint: bool,
     detach: bool,
     tests_to_run: Vec<String>,
+    coverage: bool,
+    shards: Option<usize>,
+    solana_versions: Vec<String>,
+    runner: TestRunner,
     extra_args: Vec<String>,
     env_vars: Vec<String>,
     cargo_args: Vec<String>,
     arch: ProgramArch,
 ) -> Result<()> {
+    if coverage {
+        return test_coverage(cfg_override, program_name, cargo_args);
+    }
+    if !solana_versions.is_empty() {
+        if skip_local_validator || detach {
+            return Err(anyhow!(
+                "Tests against Solana versions start their own validators, they can't be used \
+                with `--skip-local-validator` or `--detach`"
+            ));
+        }
+        if !skip_build {
+            build(
+                cfg_override,
+                false,
+                None,
+                None,
+                false,
+                skip_lint,
+                program_name,
+                None,
+                None,
+                BootstrapMode::None,
+                None,
+                None,
+                env_vars,
+                cargo_args,
+                false,
+                arch,
+                false,
+            )?;
+        }
+        return test_solana_versions(cfg_override, &solana_versions);
+    }
+    if shards.is_some() && (skip_deploy || skip_local_validator || detach) {
+        return Err(anyhow!(
+            "Parallel tests start their own validators, they can't be used with \
+            `--skip-deploy`, `--skip-local-validator` or `--detach`"
+        ));
+    }
+    if shards.is_some() && runner != TestRunner::Ts {
+        return Err(anyhow!(
+            "Parallel tests are only supported by the `ts` runner"
+        ));
+    }
+
     let test_paths = tests_to_run
         .iter()
         .map(|path| {
@@ -3135,6 +4826,7 @@ fn test(
                 cargo_args,
                 false,
                 arch,
+                false,
             )?;
         }
 
@@ -3148,10 +4840,17 @@ fn test(
         //
         // In either case, skip the deploy if the user specifies.
         let is_localnet = cfg.provider.cluster == Cluster::Localnet;
+        if shards.is_some() && !is_localnet {
+            return Err(anyhow!("Parallel tests can only run against localnet"));
+        }
         if (!is_localnet || skip_local_validator) && !skip_deploy {
-            deploy(cfg_override, None, None, false, vec![])?;
+            deploy(cfg_override, None, None, false, None, None, false, vec![])?;
         }
         let mut is_first_suite = true;
+        if runner == TestRunner::Rust {
+            let cmd = cargo_test_script(cfg, program_name.as_deref())?;
+            cfg.scripts.insert("test".to_owned(), cmd);
+        }
         if let Some(test_script) = cfg.scripts.get_mut("test") {
             is_first_suite = false;
 
@@ -3179,51 +4878,471 @@ fn test(
                 ),
             }
 
-            run_test_suite(
-                cfg,
-                cfg.path(),
-                is_localnet,
-                skip_local_validator,
-                skip_deploy,
-                detach,
-                &cfg.test_validator,
-                &cfg.scripts,
-                &extra_args,
-            )?;
-        }
-        if let Some(test_config) = &cfg.test_config {
-            for test_suite in test_config.iter() {
-                if !is_first_suite {
-                    std::thread::sleep(std::time::Duration::from_millis(
-                        test_suite
-                            .1
-                            .test
-                            .as_ref()
-                            .map(|val| val.shutdown_wait)
-                            .unwrap_or(SHUTDOWN_WAIT) as u64,
-                    ));
-                } else {
-                    is_first_suite = false;
-                }
+            match shards {
+                Some(shards) => run_test_suite_parallel(
+                    cfg,
+                    cfg.path(),
+                    shards,
+                    &cfg.test_validator,
+                    &cfg.scripts,
+                    &extra_args,
+                )?,
+                None => run_test_suite(
+                    cfg,
+                    cfg.path(),
+                    is_localnet,
+                    skip_local_validator,
+                    skip_deploy,
+                    detach,
+                    &cfg.test_validator,
+                    &cfg.scripts,
+                    &runner,
+                    &extra_args,
+                )?,
+            }
+        }
+        if let Some(test_config) = &cfg.test_config {
+            for test_suite in test_config.iter() {
+                if !is_first_suite {
+                    std::thread::sleep(std::time::Duration::from_millis(
+                        test_suite
+                            .1
+                            .test
+                            .as_ref()
+                            .map(|val| val.shutdown_wait)
+                            .unwrap_or(SHUTDOWN_WAIT) as u64,
+                    ));
+                } else {
+                    is_first_suite = false;
+                }
+
+                match shards {
+                    Some(shards) => run_test_suite_parallel(
+                        cfg,
+                        test_suite.0,
+                        shards,
+                        &test_suite.1.test,
+                        &test_suite.1.scripts,
+                        &extra_args,
+                    )?,
+                    None => run_test_suite(
+                        cfg,
+                        test_suite.0,
+                        is_localnet,
+                        skip_local_validator,
+                        skip_deploy,
+                        detach,
+                        &test_suite.1.test,
+                        &test_suite.1.scripts,
+                        &runner,
+                        &extra_args,
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+// Returns the `cargo test` command of the Rust test runner.
+fn cargo_test_script(cfg: &WithPath<Config>, program_name: Option<&str>) -> Result<String> {
+    Ok(match program_name {
+        Some(program_name) => {
+            let program = cfg.get_program(program_name)?;
+            let manifest = Manifest::from_path(program.path.join("Cargo.toml"))?;
+            format!("cargo test -p {}", manifest.package().name())
+        }
+        None => "cargo test".to_owned(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bench(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    baseline_path: String,
+    threshold: f64,
+    update: bool,
+    skip_build: bool,
+    runner: TestRunner,
+    extra_args: Vec<String>,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        if cfg.provider.cluster != Cluster::Localnet {
+            return Err(anyhow!("Benchmarks can only run against localnet"));
+        }
+
+        if !skip_build {
+            build(
+                cfg_override,
+                false,
+                None,
+                None,
+                false,
+                false,
+                program_name.clone(),
+                None,
+                None,
+                BootstrapMode::None,
+                None,
+                None,
+                vec![],
+                vec![],
+                false,
+                ProgramArch::Sbf,
+                false,
+            )?;
+        }
+
+        let cmd = match (&runner, cfg.scripts.get("bench")) {
+            (_, Some(bench)) => bench.clone(),
+            (TestRunner::Rust, None) => cargo_test_script(cfg, program_name.as_deref())?,
+            (TestRunner::Ts, None) => cfg
+                .scripts
+                .get("test")
+                .cloned()
+                .ok_or_else(|| anyhow!("No `bench` or `test` script in the Anchor.toml"))?,
+        };
+        let mut scripts = cfg.scripts.clone();
+        scripts.insert("test".to_owned(), cmd);
+
+        run_test_suite(
+            cfg,
+            cfg.path(),
+            true,
+            false,
+            false,
+            false,
+            &cfg.test_validator,
+            &scripts,
+            &runner,
+            &extra_args,
+        )?;
+
+        // The program logs streamed during the run contain the compute units
+        // consumed by each invocation.
+        let mut results = BTreeMap::new();
+        for program in cfg.get_programs(program_name)? {
+            let idl: Idl = serde_json::from_str(&fs::read_to_string(format!(
+                "target/idl/{}.json",
+                program.lib_name
+            ))?)?;
+            let address = idl.address;
+            let logs = fs::read_to_string(format!(
+                ".anchor/program-logs/{address}.{}.log",
+                program.lib_name
+            ))?;
+            for (ix_name, units) in compute_units_per_instruction(&address, &logs) {
+                let units_max = results
+                    .entry(format!("{}.{ix_name}", program.lib_name))
+                    .or_insert(0);
+                *units_max = units.max(*units_max);
+            }
+        }
+
+        if update {
+            fs::write(&baseline_path, serde_json::to_string_pretty(&results)?)?;
+            println!("Baseline written to {baseline_path}");
+            return Ok(());
+        }
+
+        let baseline: BTreeMap<String, u64> = match fs::read_to_string(&baseline_path) {
+            Ok(baseline) => serde_json::from_str(&baseline)?,
+            Err(_) => BTreeMap::new(),
+        };
+        let mut regressions = 0;
+        for (name, units) in &results {
+            match baseline.get(name) {
+                Some(&base) => {
+                    let change = (*units as f64 - base as f64) / base.max(1) as f64 * 100.0;
+                    let regressed = change > threshold;
+                    if regressed {
+                        regressions += 1;
+                    }
+                    println!(
+                        "{name}: {units} CU ({change:+.2}% from {base}){}",
+                        if regressed { " REGRESSION" } else { "" }
+                    );
+                }
+                None => println!("{name}: {units} CU (new)"),
+            }
+        }
+
+        if regressions != 0 {
+            return Err(anyhow!(
+                "{regressions} instruction(s) regressed by more than {threshold}%"
+            ));
+        }
+        Ok(())
+    })
+}
+
+// Returns the compute units consumed by each invocation of the program in the
+// logs, keyed by the instruction name logged by Anchor's dispatcher.
+fn compute_units_per_instruction(program_id: &str, logs: &str) -> Vec<(String, u64)> {
+    let invoke = Regex::new(r"Program (\S+) invoke \[\d+\]").unwrap();
+    let consumed = Regex::new(r"Program (\S+) consumed (\d+) of").unwrap();
+    let exit = Regex::new(r"Program (\S+) (success|failed)").unwrap();
+
+    let mut results = Vec::new();
+    // Invoked programs and the instructions they're executing.
+    let mut stack: Vec<(String, Option<String>)> = Vec::new();
+    for log in logs.lines().map(str::trim) {
+        if let Some(captures) = invoke.captures(log) {
+            stack.push((captures[1].to_owned(), None));
+        } else if let Some(ix_name) = log.strip_prefix("Program log: Instruction: ") {
+            if let Some((_, name @ None)) = stack.last_mut() {
+                *name = Some(ix_name.to_owned());
+            }
+        } else if let Some(captures) = consumed.captures(log) {
+            if let Some((program, Some(ix_name))) = stack.last() {
+                if program == program_id && &captures[1] == program_id {
+                    results.push((ix_name.clone(), captures[2].parse().unwrap()));
+                }
+            }
+        } else if exit.is_match(log) {
+            stack.pop();
+        } else if log.starts_with("Transaction executed") {
+            stack.clear();
+        }
+    }
+    results
+}
+
+// Programs running inside a validator can't be instrumented, so coverage is
+// collected by running the Rust tests of each program natively, e.g. with
+// `solana-program-test` and the program's entrypoint as a builtin.
+fn test_coverage(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    cargo_args: Vec<String>,
+) -> Result<()> {
+    let installed = std::process::Command::new("cargo")
+        .args(["llvm-cov", "--version"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+    if !installed {
+        return Err(anyhow!(
+            "`cargo llvm-cov` is required for coverage, install it with \
+            `cargo install cargo-llvm-cov`"
+        ));
+    }
+
+    with_workspace(cfg_override, |cfg| {
+        let root = cfg.path().parent().unwrap().to_owned();
+        for program in cfg.get_programs(program_name)? {
+            if program.solidity {
+                println!(
+                    "Skipping coverage of Solidity program `{}`",
+                    program.lib_name
+                );
+                continue;
+            }
+
+            let out_dir = root.join("target").join("coverage").join(&program.lib_name);
+            fs::create_dir_all(&out_dir)?;
+            println!("\nCollecting coverage of program `{}`", program.lib_name);
 
-                run_test_suite(
-                    cfg,
-                    test_suite.0,
-                    is_localnet,
-                    skip_local_validator,
-                    skip_deploy,
-                    detach,
-                    &test_suite.1.test,
-                    &test_suite.1.scripts,
-                    &extra_args,
-                )?;
+            let lcov = out_dir.join("lcov.info");
+            let exit = std::process::Command::new("cargo")
+                .args(["llvm-cov", "--lcov", "--output-path"])
+                .arg(&lcov)
+                .args(&cargo_args)
+                .current_dir(&program.path)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .output()
+                .map_err(|e| anyhow!("Failed to run `cargo llvm-cov`: {e}"))?;
+            if !exit.status.success() {
+                std::process::exit(exit.status.code().unwrap_or(1));
+            }
+
+            // Render the HTML report from the profile data of the run above.
+            let exit = std::process::Command::new("cargo")
+                .args(["llvm-cov", "report", "--html", "--output-dir"])
+                .arg(&out_dir)
+                .current_dir(&program.path)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .output()
+                .map_err(|e| anyhow!("Failed to run `cargo llvm-cov report`: {e}"))?;
+            if !exit.status.success() {
+                std::process::exit(exit.status.code().unwrap_or(1));
             }
+
+            println!(
+                "Coverage report of `{}` written to {}",
+                program.lib_name,
+                out_dir.display()
+            );
         }
         Ok(())
     })
 }
 
 #[allow(clippy::too_many_arguments)]
+// Runs `anchor test` again against the test validator of each Solana version,
+// and prints whether the tests passed on each of them.
+fn test_solana_versions(cfg_override: &ConfigOverride, solana_versions: &[String]) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        if cfg.provider.cluster != Cluster::Localnet {
+            return Err(anyhow!(
+                "Tests against Solana versions can only run against localnet"
+            ));
+        }
+        Ok(())
+    })?;
+
+    // The runs get the arguments of this one, without the versions, and
+    // reuse the build.
+    let mut args = vec![];
+    let mut cli_args = std::env::args().skip(1);
+    while let Some(arg) = cli_args.next() {
+        match arg.as_str() {
+            "--" => {
+                args.push(arg);
+                args.extend(cli_args.by_ref());
+            }
+            "--solana-versions" => {
+                cli_args.next();
+            }
+            _ if arg.starts_with("--solana-versions=") => {}
+            _ => args.push(arg),
+        }
+    }
+    if !args.iter().any(|arg| arg == "--skip-build") {
+        let end = args
+            .iter()
+            .position(|arg| arg == "--")
+            .unwrap_or(args.len());
+        args.insert(end, "--skip-build".to_owned());
+    }
+
+    let anchor = std::env::current_exe()?;
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut results = vec![];
+    for version in solana_versions {
+        let version = resolve_solana_version(version)?;
+        let bin = solana_release_bin(&version)?;
+        println!("\nRunning the tests against Solana {version}");
+
+        let paths = std::iter::once(bin).chain(std::env::split_paths(&path));
+        let passed = std::process::Command::new(&anchor)
+            .args(&args)
+            .env("PATH", std::env::join_paths(paths)?)
+            .status()?
+            .success();
+        results.push((version, passed));
+    }
+
+    println!("\n{:<16}Result", "Solana version");
+    for (version, passed) in &results {
+        let result = if *passed { "passed" } else { "failed" };
+        println!("{:<16}{result}", version.to_string());
+    }
+    if results.iter().any(|(_, passed)| !passed) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// Returns the repository of the releases of a Solana version, which moved to
+// Agave from 1.18.
+fn solana_release_repo(version: &Version) -> &'static str {
+    if *version >= Version::new(1, 18, 0) {
+        "anza-xyz/agave"
+    } else {
+        "solana-labs/solana"
+    }
+}
+
+// Resolves a partial version, e.g. `1.18`, to its latest release.
+fn resolve_solana_version(version: &str) -> Result<Version> {
+    if let Ok(version) = Version::parse(version) {
+        return Ok(version);
+    }
+    let req = VersionReq::parse(&format!("~{version}"))
+        .map_err(|_| anyhow!("Invalid Solana version {version}"))?;
+    let base = Version::parse(&format!("{version}.0"))
+        .or_else(|_| Version::parse(&format!("{version}.0.0")))
+        .map_err(|_| anyhow!("Invalid Solana version {version}"))?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/releases?per_page=100",
+        solana_release_repo(&base)
+    );
+    let resp = Client::new()
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, "anchor-cli")
+        .send()?;
+    if !resp.status().is_success() {
+        return Err(anyhow!(
+            "Failed to list the Solana releases: {}",
+            resp.status()
+        ));
+    }
+    let releases: Vec<JsonValue> = serde_json::from_str(&resp.text()?)?;
+    releases
+        .iter()
+        .filter_map(|release| release["tag_name"].as_str())
+        .filter_map(|tag| Version::parse(tag.trim_start_matches('v')).ok())
+        .filter(|release| release.pre.is_empty() && req.matches(release))
+        .max()
+        .ok_or_else(|| anyhow!("No release of Solana {version} found"))
+}
+
+// Returns the directory of the binaries of a Solana release, downloading them
+// in the cache the first time.
+fn solana_release_bin(version: &Version) -> Result<PathBuf> {
+    let dir = home_dir()
+        .ok_or_else(|| anyhow!("Home directory not found"))?
+        .join(".cache/anchor/solana")
+        .join(version.to_string());
+    let bin = dir.join("solana-release/bin");
+    if bin.join("solana-test-validator").exists() {
+        return Ok(bin);
+    }
+
+    let target = match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => "x86_64-unknown-linux-gnu",
+        ("macos", "x86_64") => "x86_64-apple-darwin",
+        ("macos", "aarch64") => "aarch64-apple-darwin",
+        (os, arch) => return Err(anyhow!("Solana releases are not built for {arch} {os}")),
+    };
+    let url = format!(
+        "https://github.com/{}/releases/download/v{version}/solana-release-{target}.tar.bz2",
+        solana_release_repo(version)
+    );
+    println!("Downloading Solana {version} from {url}");
+    // Releases are too large for the default timeout
+    let resp = Client::builder().timeout(None).build()?.get(&url).send()?;
+    if !resp.status().is_success() {
+        return Err(anyhow!(
+            "Failed to download Solana {version}: {}",
+            resp.status()
+        ));
+    }
+
+    fs::create_dir_all(&dir)?;
+    let archive = dir.join("solana-release.tar.bz2");
+    fs::write(&archive, resp.bytes()?)?;
+    let extracted = std::process::Command::new("tar")
+        .arg("-xjf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&dir)
+        .status()?
+        .success();
+    fs::remove_file(&archive)?;
+    if !extracted {
+        return Err(anyhow!("Failed to extract {}", archive.display()));
+    }
+    Ok(bin)
+}
+
 fn run_test_suite(
     cfg: &WithPath<Config>,
     test_suite_path: impl AsRef<Path>,
@@ -3233,60 +5352,78 @@ fn run_test_suite(
     detach: bool,
     test_validator: &Option<TestValidator>,
     scripts: &ScriptsConfig,
+    runner: &TestRunner,
     extra_args: &[String],
 ) -> Result<()> {
     println!("\nRunning test suite: {:#?}\n", test_suite_path.as_ref());
     // Start local test validator, if needed.
     let mut validator_handle = None;
+    let mut flags = vec![];
     if is_localnet && (!skip_local_validator) {
-        let flags = match skip_deploy {
-            true => None,
-            false => Some(validator_flags(cfg, test_validator)?),
-        };
-        validator_handle = Some(start_test_validator(cfg, test_validator, flags, true)?);
+        if !skip_deploy {
+            flags = validator_flags(cfg, test_validator)?;
+        }
+        validator_handle = Some(start_test_validator(
+            cfg,
+            test_validator,
+            Some(flags.clone()),
+            true,
+        )?);
     }
 
     let url = cluster_url(cfg, test_validator);
 
-    let node_options = format!(
-        "{} {}",
-        match std::env::var_os("NODE_OPTIONS") {
-            Some(value) => value
-                .into_string()
-                .map_err(std::env::VarError::NotUnicode)?,
-            None => "".to_owned(),
-        },
-        get_node_dns_option()?,
-    );
-
     // Setup log reader.
     let log_streams = stream_logs(cfg, &url);
 
     // Run the tests.
-    let test_result = {
+    let run_tests = |admin_url: Option<&str>| -> Result<std::process::Output> {
+        let wallets = test_wallets_env(cfg, test_validator, &url)?;
         let cmd = scripts
             .get("test")
             .expect("Not able to find script for `test`")
             .clone();
         let script_args = format!("{cmd} {}", extra_args.join(" "));
-        std::process::Command::new("bash")
+        let mut command = std::process::Command::new("bash");
+        command
             .arg("-c")
             .arg(script_args)
-            .env("ANCHOR_PROVIDER_URL", url)
+            .env("ANCHOR_PROVIDER_URL", &url)
             .env("ANCHOR_WALLET", cfg.provider.wallet.to_string())
-            .env("NODE_OPTIONS", node_options)
+            .envs(cfg.profile_env())
+            .envs(wallets);
+        if let Some(admin_url) = admin_url {
+            command.env(admin::ADMIN_URL_ENV, admin_url);
+        }
+        match runner {
+            TestRunner::Ts => {
+                command.env("NODE_OPTIONS", node_options()?);
+            }
+            TestRunner::Rust => {
+                command.envs(program_envs(cfg)?);
+            }
+        }
+        let test_result = command
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .output()
             .map_err(anyhow::Error::from)
-            .context(cmd)
-    };
+            .context(cmd);
 
-    // Keep validator running if needed.
-    if test_result.is_ok() && detach {
-        println!("Local validator still running. Press Ctrl + C quit.");
-        std::io::stdin().lock().lines().next().unwrap().unwrap();
-    }
+        // Keep validator running if needed.
+        if test_result.is_ok() && detach {
+            println!("Local validator still running. Press Ctrl + C quit.");
+            std::io::stdin().lock().lines().next().unwrap().unwrap();
+        }
+        test_result
+    };
+    let test_result = match &mut validator_handle {
+        Some(handle) => with_admin_rpc(cfg, test_validator, &flags, true, handle, |admin_url| {
+            run_tests(Some(admin_url))
+        })
+        .and_then(|test_result| test_result),
+        None => run_tests(None),
+    };
 
     // Check all errors and shut down.
     if let Some(mut child) = validator_handle {
@@ -3316,6 +5453,253 @@ fn run_test_suite(
     Ok(())
 }
 
+// Funds the wallets of `[test.wallets]`, returning the environment of the
+// tests with the paths of their keypairs.
+fn test_wallets_env(
+    cfg: &WithPath<Config>,
+    test_validator: &Option<TestValidator>,
+    url: &str,
+) -> Result<Vec<(&'static str, String)>> {
+    let test_wallets = match test_validator.as_ref().and_then(|tv| tv.wallets.as_ref()) {
+        Some(test_wallets) => test_wallets,
+        None => return Ok(vec![]),
+    };
+    let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    let paths = wallets::fund(
+        &client,
+        &cfg.wallet_kp()?,
+        test_wallets,
+        cfg.path().parent().unwrap(),
+    )?;
+    let paths = paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>();
+    Ok(vec![(wallets::WALLETS_ENV, paths.join(","))])
+}
+
+/// Ports reserved for the validator of each shard of a parallel test run.
+const SHARD_PORTS: u16 = 50;
+
+// Runs a test suite with its test files split across shards, each testing
+// against its own validator.
+fn run_test_suite_parallel(
+    cfg: &WithPath<Config>,
+    test_suite_path: impl AsRef<Path>,
+    shards: usize,
+    test_validator: &Option<TestValidator>,
+    scripts: &ScriptsConfig,
+    extra_args: &[String],
+) -> Result<()> {
+    let cmd = scripts
+        .get("test")
+        .expect("Not able to find script for `test`");
+    let shard_scripts = shard_test_scripts(cmd, shards)?;
+    println!(
+        "\nRunning test suite: {:#?} in {} shards\n",
+        test_suite_path.as_ref(),
+        shard_scripts.len()
+    );
+
+    // Start the validators one after the other, each on its own ports.
+    let mut validators = vec![];
+    for shard in 0..shard_scripts.len() {
+        let test_validator = Some(shard_test_validator(test_validator, shard as u16));
+        let flags = validator_flags(cfg, &test_validator)?;
+        match start_test_validator(cfg, &test_validator, Some(flags), true) {
+            Ok(handle) => validators.push((handle, test_validator)),
+            Err(err) => {
+                for (mut handle, _) in validators {
+                    handle.kill().ok();
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    // Fund the test wallets on the validator of each shard.
+    let wallets = validators
+        .iter()
+        .map(|(_, test_validator)| {
+            test_wallets_env(cfg, test_validator, &cluster_url(cfg, test_validator))
+        })
+        .collect::<Result<Vec<_>>>();
+    let wallets = match wallets {
+        Ok(wallets) => wallets,
+        Err(err) => {
+            for (mut handle, _) in validators {
+                handle.kill().ok();
+            }
+            return Err(err);
+        }
+    };
+
+    let node_options = node_options()?;
+    let results = std::thread::scope(|scope| {
+        let handles = shard_scripts
+            .iter()
+            .zip(&validators)
+            .zip(wallets)
+            .enumerate()
+            .map(|(shard, ((script, (_, test_validator)), wallets))| {
+                let mut command = std::process::Command::new("bash");
+                command
+                    .arg("-c")
+                    .arg(format!("{script} {}", extra_args.join(" ")))
+                    .env("ANCHOR_PROVIDER_URL", cluster_url(cfg, test_validator))
+                    .env("ANCHOR_WALLET", cfg.provider.wallet.to_string())
+                    .env("ANCHOR_SHARD", shard.to_string())
+                    .env("ANCHOR_SHARD_COUNT", shard_scripts.len().to_string())
+                    .envs(cfg.profile_env())
+                    .envs(wallets)
+                    .env("NODE_OPTIONS", &node_options);
+                scope.spawn(move || command.output())
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Test shard panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    for (mut handle, _) in validators {
+        if let Err(err) = handle.kill() {
+            println!("Failed to kill subprocess {}: {}", handle.id(), err);
+        }
+    }
+
+    // Print the output of the shards one after the other.
+    let mut failed = 0;
+    for (shard, result) in results.into_iter().enumerate() {
+        println!("\nShard {shard}: {}", shard_scripts[shard]);
+        match result {
+            Ok(output) => {
+                std::io::stdout().write_all(&output.stdout)?;
+                std::io::stderr().write_all(&output.stderr)?;
+                if !output.status.success() {
+                    failed += 1;
+                }
+            }
+            Err(err) => {
+                println!("Failed to run test: {err}");
+                failed += 1;
+            }
+        }
+    }
+    if failed != 0 {
+        println!("\n{failed} of {} shards failed", shard_scripts.len());
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+// Returns the `test` scripts of each shard, which run a share of the test
+// files matched by the last `tests/...` path of the script.
+fn shard_test_scripts(cmd: &str, shards: usize) -> Result<Vec<String>> {
+    let pattern = Regex::new(r"\s(tests/\S*\.(js|ts))")
+        .unwrap()
+        .captures_iter(cmd)
+        .last()
+        .and_then(|c| c.get(1).zip(c.get(2)))
+        .ok_or_else(|| {
+            anyhow!(
+                "Parallel tests require the `test` script to run files of the `tests` directory"
+            )
+        })?;
+    let (path, ext) = (pattern.0.as_str(), pattern.1.as_str());
+
+    // Collect the files under the directory of the path before any wildcard.
+    let mut files = match path.find('*') {
+        Some(i) => {
+            let dir = path[..i].rsplit_once('/').map_or("tests", |(dir, _)| dir);
+            let depth = if path.contains("**") { usize::MAX } else { 1 };
+            walkdir::WalkDir::new(dir)
+                .max_depth(depth)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    entry.file_type().is_file()
+                        && entry.path().extension().and_then(|e| e.to_str()) == Some(ext)
+                })
+                .map(|entry| entry.path().display().to_string())
+                .collect::<Vec<_>>()
+        }
+        None => vec![path.to_owned()],
+    };
+    files.sort();
+    if files.is_empty() {
+        return Err(anyhow!("No test files found for {path}"));
+    }
+
+    let shards = shards.clamp(1, files.len());
+    let mut shard_files = vec![vec![]; shards];
+    for (i, file) in files.into_iter().enumerate() {
+        shard_files[i % shards].push(file);
+    }
+    Ok(shard_files
+        .into_iter()
+        .map(|files| cmd.replacen(path, &files.join(" "), 1))
+        .collect())
+}
+
+// Returns the configuration of the validator of a shard, on its own ports and
+// ledger so that the validators of the shards don't conflict.
+fn shard_test_validator(test_validator: &Option<TestValidator>, shard: u16) -> TestValidator {
+    let mut test_validator = test_validator
+        .clone()
+        .unwrap_or_else(|| _TestValidator::default().into());
+    let mut validator = test_validator
+        .validator
+        .take()
+        .unwrap_or_else(|| _Validator::default().into());
+    let base = validator.rpc_port + shard * SHARD_PORTS;
+    // The websocket is served on the port after the RPC port.
+    validator.rpc_port = base;
+    validator.faucet_port = Some(base + 2);
+    validator.gossip_port = Some(base + 3);
+    validator.dynamic_port_range = Some(format!("{}-{}", base + 4, base + SHARD_PORTS - 1));
+    validator.ledger = format!("{}/shard-{shard}", validator.ledger);
+    test_validator.validator = Some(validator);
+    test_validator
+}
+
+// Returns the `NODE_OPTIONS` of the TypeScript test runner.
+fn node_options() -> Result<String> {
+    Ok(format!(
+        "{} {}",
+        match std::env::var_os("NODE_OPTIONS") {
+            Some(value) => value
+                .into_string()
+                .map_err(std::env::VarError::NotUnicode)?,
+            None => "".to_owned(),
+        },
+        get_node_dns_option()?,
+    ))
+}
+
+// Returns the environment variables giving the address and the binary of
+// each workspace program to Rust tests, e.g. `ANCHOR_PROGRAM_MY_PROGRAM` and
+// `ANCHOR_PROGRAM_MY_PROGRAM_SO` for `my_program`, so that they can be loaded
+// in LiteSVM or `solana-program-test` as well.
+fn program_envs(cfg: &WithPath<Config>) -> Result<Vec<(String, String)>> {
+    let programs = cfg.programs.get(&cfg.provider.cluster);
+    let mut envs = Vec::new();
+    for program in cfg.read_all_programs()? {
+        let address = programs
+            .and_then(|m| m.get(&program.lib_name))
+            .map(|deployment| Ok(deployment.address.to_string()))
+            .unwrap_or_else(|| program.pubkey().map(|p| p.to_string()))?;
+        let name = format!("ANCHOR_PROGRAM_{}", program.lib_name.to_uppercase());
+        envs.push((
+            format!("{name}_SO"),
+            program.binary_path(false).display().to_string(),
+        ));
+        envs.push((name, address));
+    }
+    Ok(envs)
+}
+
 // Returns the solana-test-validator flags. This will embed the workspace
 // programs in the genesis block so we don't have to deploy every time. It also
 // allows control of other solana-test-validator features.
@@ -3387,6 +5771,9 @@ fn validator_flags(
                 }
             }
         }
+        if let Some(fixtures) = &test.fixtures {
+            flags.extend(fixture_flags(cfg, fixtures)?);
+        }
         if let Some(validator) = &test.validator {
             let entries = serde_json::to_value(validator)?;
             for (key, value) in entries.as_object().unwrap() {
@@ -3417,42 +5804,18 @@ fn validator_flags(
                         ));
                     };
 
-                    let pubkeys = value
-                        .as_array()
-                        .unwrap()
-                        .iter()
-                        .map(|entry| {
-                            let address = entry["address"].as_str().unwrap();
-                            Pubkey::from_str(address)
-                                .map_err(|_| anyhow!("Invalid pubkey {}", address))
-                        })
-                        .collect::<Result<HashSet<Pubkey>>>()?
-                        .into_iter()
-                        .collect::<Vec<_>>();
-                    let accounts = client.get_multiple_accounts(&pubkeys)?;
-
-                    for (pubkey, account) in pubkeys.into_iter().zip(accounts) {
-                        match account {
-                            Some(account) => {
-                                // Use a different flag for program accounts to fix the problem
-                                // described in https://github.com/anza-xyz/agave/issues/522
-                                if account.owner == bpf_loader_upgradeable::id()
-                                // Only programs are supported with `--clone-upgradeable-program`
-                                    && matches!(
-                                        account.deserialize_data::<UpgradeableLoaderState>()?,
-                                        UpgradeableLoaderState::Program { .. }
-                                    )
-                                {
-                                    flags.push("--clone-upgradeable-program".to_string());
-                                    flags.push(pubkey.to_string());
-                                } else {
-                                    flags.push("--clone".to_string());
-                                    flags.push(pubkey.to_string());
-                                }
-                            }
-                            _ => return Err(anyhow!("Account {} not found", pubkey)),
-                        }
+                    let clone_entries: Vec<CloneEntry> = serde_json::from_value(value.clone())?;
+                    flags.extend(clone_flags(&client, &clone_entries)?);
+                } else if key == "geyser_plugin_config" {
+                    // Fail early instead of the validator exiting at startup.
+                    let config = value.as_str().unwrap();
+                    if !Path::new(config).exists() {
+                        return Err(anyhow!(
+                            "Geyser plugin config does not exist at path: {config}"
+                        ));
                     }
+                    flags.push("--geyser-plugin-config".to_string());
+                    flags.push(config.to_string());
                 } else if key == "deactivate_feature" {
                     // Verify that the feature flags are valid pubkeys
                     let pubkeys_result: Result<Vec<Pubkey>, _> = value
@@ -3477,16 +5840,208 @@ fn validator_flags(
                     if let serde_json::Value::String(v) = value {
                         flags.push(v.to_string());
                     } else {
-                        flags.push(value.to_string());
+                        flags.push(value.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(flags)
+}
+
+// Returns the validator flags cloning the accounts of the given entries.
+fn clone_flags(client: &RpcClient, entries: &[CloneEntry]) -> Result<Vec<String>> {
+    let parse_pubkey = |address: &str| {
+        Pubkey::from_str(address).map_err(|_| anyhow!("Invalid pubkey {}", address))
+    };
+
+    // Addresses to clone, and whether to cache them.
+    let mut addresses = BTreeMap::new();
+    for entry in entries {
+        let mut pubkeys = Vec::new();
+        if !entry.address.is_empty() {
+            pubkeys.push(parse_pubkey(&entry.address)?);
+        }
+        if let Some(file) = &entry.file {
+            let list = fs::read_to_string(file)
+                .with_context(|| format!("Error reading clone address list: {file}"))?;
+            for line in list.lines().map(str::trim) {
+                if !line.is_empty() && !line.starts_with('#') {
+                    pubkeys.push(parse_pubkey(line)?);
+                }
+            }
+        }
+        if entry.program_accounts {
+            let program_id = parse_pubkey(&entry.address)?;
+            let filters = entry.filters.as_ref().map(|filters| {
+                filters
+                    .iter()
+                    .map(|filter| match filter {
+                        CloneFilter::DataSize(size) => RpcFilterType::DataSize(*size),
+                        CloneFilter::Memcmp { offset, bytes } => RpcFilterType::Memcmp(
+                            Memcmp::new(*offset, MemcmpEncodedBytes::Base58(bytes.clone())),
+                        ),
+                    })
+                    .collect()
+            });
+            let accounts = client.get_program_accounts_with_config(
+                &program_id,
+                RpcProgramAccountsConfig {
+                    filters,
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        // Only the addresses are needed.
+                        data_slice: Some(UiDataSliceConfig {
+                            offset: 0,
+                            length: 0,
+                        }),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )?;
+            pubkeys.extend(accounts.into_iter().map(|(pubkey, _)| pubkey));
+        }
+
+        for pubkey in pubkeys {
+            let cache = addresses.entry(pubkey).or_insert(entry.cache);
+            *cache &= entry.cache;
+        }
+    }
+
+    let cache_dir = Path::new(".anchor/clone-cache");
+    let mut flags = Vec::new();
+    let mut to_fetch = Vec::new();
+    for (pubkey, cache) in addresses {
+        let cache_path = cache_dir.join(format!("{pubkey}.json"));
+        if cache && cache_path.exists() {
+            flags.push("--account".to_string());
+            flags.push(pubkey.to_string());
+            flags.push(cache_path.display().to_string());
+        } else {
+            to_fetch.push((pubkey, cache));
+        }
+    }
+
+    // RPC nodes return at most 100 accounts per request.
+    for chunk in to_fetch.chunks(100) {
+        let pubkeys = chunk.iter().map(|(pubkey, _)| *pubkey).collect::<Vec<_>>();
+        let accounts = client.get_multiple_accounts(&pubkeys)?;
+
+        for ((pubkey, cache), account) in chunk.iter().zip(accounts) {
+            match account {
+                Some(account) => {
+                    // Use a different flag for program accounts to fix the problem
+                    // described in https://github.com/anza-xyz/agave/issues/522
+                    if account.owner == bpf_loader_upgradeable::id()
+                    // Only programs are supported with `--clone-upgradeable-program`
+                        && matches!(
+                            account.deserialize_data::<UpgradeableLoaderState>()?,
+                            UpgradeableLoaderState::Program { .. }
+                        )
+                    {
+                        flags.push("--clone-upgradeable-program".to_string());
+                        flags.push(pubkey.to_string());
+                    } else if *cache {
+                        let cache_path = cache_dir.join(format!("{pubkey}.json"));
+                        fs::create_dir_all(cache_dir)?;
+                        fs::write(&cache_path, account_json(pubkey, &account).to_string())?;
+                        flags.push("--account".to_string());
+                        flags.push(pubkey.to_string());
+                        flags.push(cache_path.display().to_string());
+                    } else {
+                        flags.push("--clone".to_string());
+                        flags.push(pubkey.to_string());
                     }
                 }
+                _ => return Err(anyhow!("Account {} not found", pubkey)),
+            }
+        }
+    }
+
+    Ok(flags)
+}
+
+// Creates the accounts of the fixtures in `.anchor/fixtures` and returns the
+// flags of the test validator to load them.
+fn fixture_flags(cfg: &WithPath<Config>, entries: &[FixtureEntry]) -> Result<Vec<String>> {
+    let mut fixtures = vec![];
+    for entry in entries {
+        match entry {
+            FixtureEntry::Account(fixture) => fixtures.push(fixture.clone()),
+            FixtureEntry::File { file } => {
+                let content = fs::read_to_string(file)
+                    .map_err(|e| anyhow!("Unable to read the fixtures {file}: {e}"))?;
+                let file_fixtures: Vec<Fixture> = match Path::new(file).extension() {
+                    Some(ext) if ext == "yaml" || ext == "yml" => serde_yaml::from_str(&content)?,
+                    _ => serde_json::from_str(&content)?,
+                };
+                fixtures.extend(file_fixtures);
             }
         }
     }
 
+    let programs = cfg.read_all_programs()?;
+    let program_idl = |program: &str| -> Result<(Pubkey, Idl)> {
+        if program.ends_with(".json") {
+            let idl = convert_idl(&fs::read(program)?)?;
+            let address = Pubkey::from_str(&idl.address)?;
+            return Ok((address, idl));
+        }
+        let program = programs
+            .iter()
+            .find(|p| p.lib_name == program)
+            .ok_or_else(|| anyhow!("Program `{program}` not found in the workspace"))?;
+        let idl = program
+            .idl
+            .clone()
+            .ok_or_else(|| anyhow!("IDL of `{}` not found, build it first", program.lib_name))?;
+        let address = cfg
+            .programs
+            .get(&Cluster::Localnet)
+            .and_then(|programs| programs.get(&program.lib_name))
+            .map(|deployment| Ok(deployment.address))
+            .unwrap_or_else(|| program.pubkey())?;
+        Ok((address, idl))
+    };
+
+    let fixtures_dir = Path::new(".anchor/fixtures");
+    fs::create_dir_all(fixtures_dir)?;
+    let mut flags = vec![];
+    for fixture in fixtures {
+        let address = Pubkey::from_str(&fixture.address)
+            .map_err(|_| anyhow!("Invalid fixture address: {}", fixture.address))?;
+        let account = fixtures::account(&fixture, program_idl)
+            .map_err(|e| anyhow!("Invalid fixture {address}: {e}"))?;
+        let path = fixtures_dir.join(format!("{address}.json"));
+        fs::write(&path, account_json(&address, &account).to_string())?;
+        flags.push("--account".to_string());
+        flags.push(address.to_string());
+        flags.push(path.display().to_string());
+    }
     Ok(flags)
 }
 
+// Returns the account in the JSON format of `solana account --output json`,
+// which `solana-test-validator --account` loads.
+fn account_json(pubkey: &Pubkey, account: &solana_sdk::account::Account) -> JsonValue {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    json!({
+        "pubkey": pubkey.to_string(),
+        "account": {
+            "lamports": account.lamports,
+            "data": [STANDARD.encode(&account.data), "base64"],
+            "owner": account.owner.to_string(),
+            "executable": account.executable,
+            "rentEpoch": account.rent_epoch,
+            "space": account.data.len(),
+        },
+    })
+}
+
 fn stream_logs(config: &WithPath<Config>, rpc_url: &str) -> Result<Vec<std::process::Child>> {
     let program_logs_dir = ".anchor/program-logs";
     if Path::new(program_logs_dir).exists() {
@@ -3539,9 +6094,24 @@ fn start_test_validator(
     test_validator: &Option<TestValidator>,
     flags: Option<Vec<String>>,
     test_log_stdout: bool,
+) -> Result<Child> {
+    start_test_validator_from(cfg, test_validator, flags, test_log_stdout, None)
+}
+
+// Starts the test validator, resuming from the ledger of the given snapshot
+// instead of a new one.
+fn start_test_validator_from(
+    cfg: &Config,
+    test_validator: &Option<TestValidator>,
+    flags: Option<Vec<String>>,
+    test_log_stdout: bool,
+    snapshot: Option<&Path>,
 ) -> Result<Child> {
     let (test_ledger_directory, test_ledger_log_filename) =
         test_validator_file_paths(test_validator)?;
+    if let Some(snapshot) = snapshot {
+        copy_dir(snapshot, &test_ledger_directory)?;
+    }
 
     // Start a validator for testing.
     let (test_validator_stdout, test_validator_stderr) = match test_log_stdout {
@@ -3558,8 +6128,7 @@ fn start_test_validator(
 
     let rpc_url = test_validator_rpc_url(test_validator);
 
-    let rpc_port = cfg
-        .test_validator
+    let rpc_port = test_validator
         .as_ref()
         .and_then(|test| test.validator.as_ref().map(|v| v.rpc_port))
         .unwrap_or(solana_sdk::rpc_port::DEFAULT_RPC_PORT);
@@ -3568,8 +6137,7 @@ fn start_test_validator(
             "Your configured rpc port: {rpc_port} is already in use"
         ));
     }
-    let faucet_port = cfg
-        .test_validator
+    let faucet_port = test_validator
         .as_ref()
         .and_then(|test| test.validator.as_ref().and_then(|v| v.faucet_port))
         .unwrap_or(solana_faucet::faucet::FAUCET_PORT);
@@ -3616,6 +6184,87 @@ fn start_test_validator(
     Ok(validator_handle)
 }
 
+// Runs `f` while serving the admin RPC of the test validator, passing it the
+// URL of the RPC. The validator can only warp its clock when it starts, so the
+// RPC restarts it from its ledger.
+fn with_admin_rpc<R>(
+    cfg: &Config,
+    test_validator: &Option<TestValidator>,
+    flags: &[String],
+    test_log_stdout: bool,
+    handle: &mut Child,
+    f: impl FnOnce(&str) -> R,
+) -> Result<R> {
+    let (bind_address, rpc_port) = match test_validator {
+        Some(TestValidator {
+            validator: Some(validator),
+            ..
+        }) => (validator.bind_address.as_str(), validator.rpc_port),
+        _ => ("127.0.0.1", solana_sdk::rpc_port::DEFAULT_RPC_PORT),
+    };
+    let admin_port = rpc_port + admin::ADMIN_PORT_OFFSET;
+    let listener = std::net::TcpListener::bind((bind_address, admin_port))
+        .map_err(|e| anyhow!("Unable to start the admin RPC on port {admin_port}: {e}"))?;
+    let admin_url = format!("http://{bind_address}:{admin_port}");
+    let rpc_url = test_validator_rpc_url(test_validator);
+
+    let stop = std::sync::atomic::AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let server = scope.spawn(|| {
+            admin::serve(listener, &rpc_url, &stop, |slot| {
+                warp_test_validator(cfg, test_validator, flags, test_log_stdout, handle, slot)
+            })
+        });
+        let result = f(&admin_url);
+        stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        server.join().expect("Admin RPC panicked")?;
+        Ok(result)
+    })
+}
+
+// Restarts the test validator from its ledger, warped to the given slot.
+fn warp_test_validator(
+    cfg: &Config,
+    test_validator: &Option<TestValidator>,
+    flags: &[String],
+    test_log_stdout: bool,
+    handle: &mut Child,
+    slot: u64,
+) -> Result<()> {
+    handle.kill()?;
+    handle.wait()?;
+
+    // The ledger is removed when the validator starts, resume from a copy
+    let ledger = Path::new(test_validator_ledger(test_validator));
+    let ledger_copy = ledger.with_extension("warp");
+    if ledger_copy.exists() {
+        fs::remove_dir_all(&ledger_copy)?;
+    }
+    copy_dir(ledger, &ledger_copy)?;
+
+    let mut warp_flags = vec![];
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--warp-slot" => {
+                flags.next();
+            }
+            _ => warp_flags.push(flag.clone()),
+        }
+    }
+    warp_flags.extend(["--warp-slot".to_owned(), slot.to_string()]);
+
+    *handle = start_test_validator_from(
+        cfg,
+        test_validator,
+        Some(warp_flags),
+        test_log_stdout,
+        Some(&ledger_copy),
+    )?;
+    fs::remove_dir_all(&ledger_copy)?;
+    Ok(())
+}
+
 // Return the URL that solana-test-validator should be running on given the
 // configuration
 fn test_validator_rpc_url(test_validator: &Option<TestValidator>) -> String {
@@ -3631,14 +6280,7 @@ fn test_validator_rpc_url(test_validator: &Option<TestValidator>) -> String {
 // Setup and return paths to the solana-test-validator ledger directory and log
 // files given the configuration
 fn test_validator_file_paths(test_validator: &Option<TestValidator>) -> Result<(PathBuf, PathBuf)> {
-    let ledger_path = match test_validator {
-        Some(TestValidator {
-            validator: Some(validator),
-            ..
-        }) => &validator.ledger,
-        _ => DEFAULT_LEDGER_PATH,
-    };
-    let ledger_path = Path::new(ledger_path);
+    let ledger_path = Path::new(test_validator_ledger(test_validator));
 
     if !ledger_path.is_relative() {
         // Prevent absolute paths to avoid someone using / or similar, as the
@@ -3658,6 +6300,17 @@ fn test_validator_file_paths(test_validator: &Option<TestValidator>) -> Result<(
     ))
 }
 
+// Return the solana-test-validator ledger directory given the configuration
+fn test_validator_ledger(test_validator: &Option<TestValidator>) -> &str {
+    match test_validator {
+        Some(TestValidator {
+            validator: Some(validator),
+            ..
+        }) => &validator.ledger,
+        _ => DEFAULT_LEDGER_PATH,
+    }
+}
+
 fn cluster_url(cfg: &Config, test_validator: &Option<TestValidator>) -> String {
     let is_localnet = cfg.provider.cluster == Cluster::Localnet;
     match is_localnet {
@@ -3704,11 +6357,15 @@ fn clean(cfg_override: &ConfigOverride) -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn deploy(
     cfg_override: &ConfigOverride,
     program_name: Option<String>,
     program_keypair: Option<String>,
     verifiable: bool,
+    priority_fee: Option<u64>,
+    max_retries: Option<u32>,
+    simulate: bool,
     solana_args: Vec<String>,
 ) -> Result<()> {
     // Execute the code within the workspace
@@ -3716,6 +6373,18 @@ fn deploy(
         let url = cluster_url(cfg, &cfg.test_validator);
         let keypair = cfg.provider.wallet.to_string();
 
+        if simulate {
+            return simulate_deploy(
+                cfg,
+                &url,
+                program_name,
+                program_keypair,
+                verifiable,
+                priority_fee,
+                &solana_args,
+            );
+        }
+
         // Deploy the programs.
         println!("Deploying cluster: {}", url);
         println!("Upgrade authority: {}", keypair);
@@ -3735,27 +6404,17 @@ fn deploy(
             };
 
             // Send deploy transactions using the Solana CLI
-            let exit = std::process::Command::new("solana")
-                .arg("program")
-                .arg("deploy")
-                .arg("--url")
-                .arg(&url)
-                .arg("--keypair")
-                .arg(&keypair)
-                .arg("--program-id")
-                .arg(strip_workspace_prefix(program_keypair_filepath))
-                .arg(strip_workspace_prefix(binary_path))
-                .args(&solana_args)
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .output()
-                .expect("Must deploy");
-
-            // Check if deployment was successful
-            if !exit.status.success() {
-                println!("There was a problem deploying: {exit:?}.");
-                std::process::exit(exit.status.code().unwrap_or(1));
-            }
+            program_deploy(
+                cfg,
+                &url,
+                &keypair,
+                Some(strip_workspace_prefix(program_keypair_filepath)),
+                strip_workspace_prefix(binary_path),
+                &program.lib_name,
+                priority_fee,
+                max_retries,
+                &solana_args,
+            )?;
 
             if let Some(idl) = program.idl.as_mut() {
                 // Add program address to the IDL.
@@ -3775,38 +6434,533 @@ fn deploy(
     })
 }
 
-fn upgrade(
-    cfg_override: &ConfigOverride,
-    program_id: Pubkey,
-    program_filepath: String,
-    solana_args: Vec<String>,
-) -> Result<()> {
-    let path: PathBuf = program_filepath.parse().unwrap();
-    let program_filepath = path.canonicalize()?.display().to_string();
+/// Compute unit limit of a transaction without a compute budget instruction,
+/// used as an upper bound of the priority fees.
+const DEFAULT_TX_COMPUTE_UNITS: u64 = 200_000;
+
+/// Base fee of a transaction signature.
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Cost of deploying a program, in lamports.
+#[derive(Debug, Default)]
+struct DeployCost {
+    transactions: u64,
+    signatures: u64,
+    rent: u64,
+    /// Rent refunded once the deploy succeeds
+    refunded_rent: u64,
+    priority_fees: u64,
+}
+
+impl DeployCost {
+    fn add(&mut self, other: &Self) {
+        self.transactions += other.transactions;
+        self.signatures += other.signatures;
+        self.rent += other.rent;
+        self.refunded_rent += other.refunded_rent;
+        self.priority_fees += other.priority_fees;
+    }
+
+    fn total(&self) -> u64 {
+        self.rent + self.signatures * LAMPORTS_PER_SIGNATURE + self.priority_fees
+    }
+}
+
+/// Prints what deploying the programs would cost without sending any
+/// transaction.
+fn simulate_deploy(
+    cfg: &WithPath<Config>,
+    url: &str,
+    program_name: Option<String>,
+    program_keypair: Option<String>,
+    verifiable: bool,
+    priority_fee: Option<u64>,
+    solana_args: &[String],
+) -> Result<()> {
+    let client = create_client(url);
+    let payer = cfg.wallet_kp()?.pubkey();
+    let priority_fee =
+        get_recommended_micro_lamport_fee(&client, priority_fee.or(cfg.deploy.priority_fee))?;
+    let max_len = solana_args
+        .iter()
+        .position(|arg| arg == "--max-len")
+        .and_then(|i| solana_args.get(i + 1))
+        .map(|len| len.parse::<usize>())
+        .transpose()?;
+
+    println!("Simulating deploy to cluster: {url}");
+    println!("Compute unit price: {priority_fee} micro-lamports");
+
+    let mut total = DeployCost::default();
+    for program in cfg.get_programs(program_name)? {
+        let binary_path = program.binary_path(verifiable);
+        let program_len = fs::metadata(&binary_path)
+            .with_context(|| format!("Program binary not found: {}", binary_path.display()))?
+            .len() as usize;
+        let program_id = match &program_keypair {
+            Some(path) => get_keypair(path)?.pubkey(),
+            None => program.pubkey()?,
+        };
+        let is_upgrade = client
+            .get_account_with_commitment(&program_id, CommitmentConfig::confirmed())?
+            .value
+            .is_some();
+
+        // Every write transaction is filled up to the packet size.
+        let buffer = Pubkey::new_unique();
+        let mut ixs = match priority_fee {
+            0 => vec![],
+            fee => vec![ComputeBudgetInstruction::set_compute_unit_price(fee)],
+        };
+        ixs.push(bpf_loader_upgradeable::write(&buffer, &payer, 0, vec![]));
+        let message = solana_sdk::message::Message::new(&ixs, Some(&payer));
+        let tx_size = bincode::serialized_size(&Transaction::new_unsigned(message))? as usize;
+        // Leave room for the length of the data growing by a byte.
+        let chunk_size = solana_sdk::packet::PACKET_DATA_SIZE - tx_size - 1;
+        let writes = program_len.div_ceil(chunk_size) as u64;
+
+        let buffer_rent = client.get_minimum_balance_for_rent_exemption(
+            UpgradeableLoaderState::size_of_buffer(program_len),
+        )?;
+        let mut cost = DeployCost {
+            // Create the buffer, write it and deploy it.
+            transactions: writes + 2,
+            // The buffer signs its creation, and a new program its deployment.
+            signatures: writes + if is_upgrade { 3 } else { 4 },
+            priority_fees: (writes + 2) * DEFAULT_TX_COMPUTE_UNITS * priority_fee / 1_000_000,
+            ..Default::default()
+        };
+        if is_upgrade {
+            // The buffer is closed by the upgrade.
+            cost.rent = buffer_rent;
+            cost.refunded_rent = buffer_rent;
+        } else {
+            // The lamports of the buffer move to the program data account.
+            let programdata_rent = client.get_minimum_balance_for_rent_exemption(
+                UpgradeableLoaderState::size_of_programdata(max_len.unwrap_or(program_len)),
+            )?;
+            let program_rent = client
+                .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program())?;
+            cost.rent = buffer_rent.max(programdata_rent) + program_rent;
+        }
+
+        println!();
+        println!(
+            "Program {:?} ({}):",
+            program.lib_name,
+            if is_upgrade { "upgrade" } else { "new deploy" }
+        );
+        println!("  Program id: {program_id}");
+        println!("  Binary size: {program_len} bytes");
+        println!(
+            "  Buffer size: {} bytes",
+            UpgradeableLoaderState::size_of_buffer(program_len)
+        );
+        println!("  Transactions: {} ({writes} writes)", cost.transactions);
+        print_deploy_cost(&cost);
+
+        total.add(&cost);
+    }
+
+    let balance = client.get_balance(&payer)?;
+    let required = total.total();
+    println!();
+    println!("Total:");
+    print_deploy_cost(&total);
+    println!(
+        "  Wallet balance: {} SOL",
+        solana_sdk::native_token::lamports_to_sol(balance)
+    );
+    if balance < required {
+        println!(
+            "Warning: the wallet is missing {} SOL",
+            solana_sdk::native_token::lamports_to_sol(required - balance)
+        );
+    }
+
+    Ok(())
+}
+
+fn print_deploy_cost(cost: &DeployCost) {
+    let sol = solana_sdk::native_token::lamports_to_sol;
+    println!("  Rent: {} SOL", sol(cost.rent));
+    if cost.refunded_rent > 0 {
+        println!("    of which refunded: {} SOL", sol(cost.refunded_rent));
+    }
+    println!(
+        "  Transaction fees: {} SOL",
+        sol(cost.signatures * LAMPORTS_PER_SIGNATURE)
+    );
+    println!("  Priority fees (at most): {} SOL", sol(cost.priority_fees));
+    println!("  Required: {} SOL", sol(cost.total()));
+}
+
+fn upgrade(
+    cfg_override: &ConfigOverride,
+    program_id: Pubkey,
+    program_filepath: String,
+    priority_fee: Option<u64>,
+    max_retries: Option<u32>,
+    buffer_authority: Option<Multisig>,
+    solana_args: Vec<String>,
+) -> Result<()> {
+    let path: PathBuf = program_filepath.parse().unwrap();
+    let program_filepath = path.canonicalize()?.display().to_string();
+
+    with_workspace(cfg_override, |cfg| {
+        let url = cluster_url(cfg, &cfg.test_validator);
+        let keypair = cfg.provider.wallet.to_string();
+        let Some(multisig) = buffer_authority else {
+            program_deploy(
+                cfg,
+                &url,
+                &keypair,
+                Some(strip_workspace_prefix(program_id.to_string())),
+                strip_workspace_prefix(program_filepath),
+                &program_id.to_string(),
+                priority_fee,
+                max_retries,
+                &solana_args,
+            )?;
+            return Ok(());
+        };
+
+        // The multisig must already be the upgrade authority of the program,
+        // otherwise the proposal would fail after being approved.
+        let authority = multisig.authority();
+        let client = create_client(&url);
+        let (programdata_address, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        let programdata = client.get_account(&programdata_address)?;
+        match programdata.state()? {
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address: Some(upgrade_authority),
+                ..
+            } if upgrade_authority == authority => {}
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            } => {
+                return Err(anyhow!(
+                    "The upgrade authority of the program is {}, not the multisig {authority}",
+                    upgrade_authority_address
+                        .map(|address| address.to_string())
+                        .unwrap_or_else(|| "none".into())
+                ))
+            }
+            _ => return Err(anyhow!("Expected program data")),
+        }
+
+        let buffer = program_deploy(
+            cfg,
+            &url,
+            &keypair,
+            None,
+            strip_workspace_prefix(program_filepath),
+            &program_id.to_string(),
+            priority_fee,
+            max_retries,
+            &solana_args,
+        )?;
+
+        let exit = std::process::Command::new("solana")
+            .arg("program")
+            .arg("set-buffer-authority")
+            .arg("--url")
+            .arg(&url)
+            .arg("--keypair")
+            .arg(&keypair)
+            .arg(buffer.to_string())
+            .arg("--new-buffer-authority")
+            .arg(authority.to_string())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .output()
+            .expect("Must set buffer authority");
+        if !exit.status.success() {
+            println!("There was a problem setting the buffer authority: {exit:?}.");
+            std::process::exit(exit.status.code().unwrap_or(1));
+        }
+        remove_buffer_keypair(&program_id.to_string())?;
+
+        // The rent of the buffer is refunded to the configured wallet once
+        // the upgrade is executed.
+        let spill = cfg.wallet_kp()?.pubkey();
+        let ix = bpf_loader_upgradeable::upgrade(&program_id, &buffer, &authority, &spill);
+        match multisig {
+            Multisig::Squads(vault) => {
+                println!(
+                    "Buffer {buffer} is ready, propose the upgrade from the Squads vault {vault}:"
+                );
+            }
+        }
+        println!("Program: {}", ix.program_id);
+        for account in &ix.accounts {
+            println!(
+                "  {}{}{}",
+                account.pubkey,
+                if account.is_writable {
+                    " (writable)"
+                } else {
+                    ""
+                },
+                if account.is_signer { " (signer)" } else { "" },
+            );
+        }
+        println!("Base64 encoded instruction: {}", encode_instruction(&ix)?);
+
+        Ok(())
+    })
+}
+
+fn upgrade_cmd(cfg_override: &ConfigOverride, cmd: UpgradeCommand) -> Result<()> {
+    match cmd {
+        UpgradeCommand::WriteBuffer {
+            program_id,
+            program_filepath,
+            priority_fee,
+            max_retries,
+            solana_args,
+        } => upgrade_write_buffer(
+            cfg_override,
+            program_id,
+            program_filepath,
+            priority_fee,
+            max_retries,
+            solana_args,
+        ),
+        UpgradeCommand::Commit {
+            program_id,
+            priority_fee,
+        } => upgrade_commit(cfg_override, program_id, priority_fee),
+    }
+}
+
+/// Upgrade staged by `anchor upgrade write-buffer`, persisted in
+/// `target/deploy/<program-id>-upgrade.json` until it's committed.
+#[derive(Debug, Serialize, Deserialize)]
+struct StagedUpgrade {
+    program_id: String,
+    buffer: String,
+}
+
+impl StagedUpgrade {
+    fn path(program_id: &Pubkey) -> PathBuf {
+        PathBuf::from("target/deploy").join(format!("{program_id}-upgrade.json"))
+    }
+}
+
+fn upgrade_write_buffer(
+    cfg_override: &ConfigOverride,
+    program_id: Pubkey,
+    program_filepath: String,
+    priority_fee: Option<u64>,
+    max_retries: Option<u32>,
+    solana_args: Vec<String>,
+) -> Result<()> {
+    let path: PathBuf = program_filepath.parse().unwrap();
+    let program_filepath = path.canonicalize()?.display().to_string();
+
+    with_workspace(cfg_override, |cfg| {
+        let url = cluster_url(cfg, &cfg.test_validator);
+        let buffer = program_deploy(
+            cfg,
+            &url,
+            &cfg.provider.wallet.to_string(),
+            None,
+            strip_workspace_prefix(program_filepath),
+            &program_id.to_string(),
+            priority_fee,
+            max_retries,
+            &solana_args,
+        )?;
+
+        let staged = StagedUpgrade {
+            program_id: program_id.to_string(),
+            buffer: buffer.to_string(),
+        };
+        fs::write(
+            StagedUpgrade::path(&program_id),
+            serde_json::to_string_pretty(&staged)?,
+        )?;
+        remove_buffer_keypair(&program_id.to_string())?;
+
+        println!(
+            "Buffer {buffer} is ready, upgrade the program with \
+            `anchor upgrade commit --program-id {program_id}`"
+        );
+        Ok(())
+    })
+}
+
+fn upgrade_commit(
+    cfg_override: &ConfigOverride,
+    program_id: Pubkey,
+    priority_fee: Option<u64>,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let path = StagedUpgrade::path(&program_id);
+        let staged: StagedUpgrade = match fs::read_to_string(&path) {
+            Ok(staged) => serde_json::from_str(&staged)?,
+            Err(_) => {
+                return Err(anyhow!(
+                    "No staged upgrade of {program_id}, run `anchor upgrade write-buffer` first"
+                ))
+            }
+        };
+        let buffer = Pubkey::from_str(&staged.buffer)?;
+
+        let url = cluster_url(cfg, &cfg.test_validator);
+        let client = create_client(url);
+        let keypair = cfg.wallet_kp()?;
+
+        // Make sure the buffer wasn't closed, or its authority changed, since
+        // it was written.
+        match client.get_account(&buffer)?.state()? {
+            UpgradeableLoaderState::Buffer {
+                authority_address: Some(authority),
+            } if authority == keypair.pubkey() => {}
+            _ => {
+                return Err(anyhow!(
+                    "Buffer {buffer} is not a buffer owned by the configured wallet"
+                ))
+            }
+        }
+
+        let ix = bpf_loader_upgradeable::upgrade(
+            &program_id,
+            &buffer,
+            &keypair.pubkey(),
+            &keypair.pubkey(),
+        );
+        let instructions = prepend_compute_unit_ix(vec![ix], &client, priority_fee)?;
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            client.get_latest_blockhash()?,
+        );
+        let signature = client.send_and_confirm_transaction_with_spinner(&tx)?;
+
+        fs::remove_file(&path)?;
+        println!("Program {program_id} upgraded from buffer {buffer}: {signature}");
+        Ok(())
+    })
+}
+
+/// Runs `solana program deploy`, or `solana program write-buffer` if no
+/// program id is given, retrying with an exponential backoff on failure.
+///
+/// The program is written to a buffer whose keypair is kept in
+/// `target/deploy/<name>-buffer-keypair.json` until the command succeeds, so
+/// retries, and later runs after running out of retries, resume writing the
+/// same buffer instead of starting over. Returns the address of the buffer.
+///
+/// A written buffer keeps its keypair, to be removed with
+/// [`remove_buffer_keypair`] once the buffer is used.
+#[allow(clippy::too_many_arguments)]
+fn program_deploy(
+    cfg: &Config,
+    url: &str,
+    keypair: &str,
+    program_id: Option<String>,
+    binary_path: String,
+    name: &str,
+    priority_fee: Option<u64>,
+    max_retries: Option<u32>,
+    solana_args: &[String],
+) -> Result<Pubkey> {
+    let priority_fee = priority_fee.or(cfg.deploy.priority_fee);
+    let max_retries = max_retries
+        .or(cfg.deploy.max_retries)
+        .unwrap_or(DEFAULT_DEPLOY_RETRIES);
 
-    with_workspace(cfg_override, |cfg| {
-        let url = cluster_url(cfg, &cfg.test_validator);
-        let exit = std::process::Command::new("solana")
-            .arg("program")
-            .arg("deploy")
+    if solana_args
+        .iter()
+        .any(|arg| arg == "--buffer" || arg.starts_with("--buffer="))
+    {
+        return Err(anyhow!(
+            "`--buffer` can't be passed to `solana program`, the buffer is kept in \
+            `target/deploy/{name}-buffer-keypair.json` to resume failed deploys"
+        ));
+    }
+
+    let buffer_path = buffer_keypair_path(name);
+    let buffer = if buffer_path.exists() {
+        println!("Resuming deploy from buffer: {}", buffer_path.display());
+        get_keypair(&buffer_path.display().to_string())?.pubkey()
+    } else {
+        fs::create_dir_all("target/deploy")?;
+        let buffer = Keypair::new();
+        solana_sdk::signature::write_keypair_file(&buffer, &buffer_path)
+            .map_err(|e| anyhow!("Failed to write the buffer keypair: {e}"))?;
+        buffer.pubkey()
+    };
+
+    let mut attempt = 0;
+    loop {
+        let mut command = std::process::Command::new("solana");
+        command.arg("program");
+        match &program_id {
+            Some(program_id) => command.arg("deploy").arg("--program-id").arg(program_id),
+            None => command.arg("write-buffer"),
+        };
+        command
             .arg("--url")
             .arg(url)
             .arg("--keypair")
-            .arg(&cfg.provider.wallet.to_string())
-            .arg("--program-id")
-            .arg(strip_workspace_prefix(program_id.to_string()))
-            .arg(strip_workspace_prefix(program_filepath))
-            .args(&solana_args)
+            .arg(keypair)
+            .arg("--buffer")
+            .arg(&buffer_path);
+        if let Some(priority_fee) = priority_fee {
+            command
+                .arg("--with-compute-unit-price")
+                .arg(priority_fee.to_string());
+        }
+        let exit = command
+            .arg(&binary_path)
+            .args(solana_args)
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .output()
             .expect("Must deploy");
-        if !exit.status.success() {
-            println!("There was a problem deploying: {exit:?}.");
+
+        if exit.status.success() {
+            // The buffer is closed by the deploy, a new one is used next time.
+            if program_id.is_some() {
+                remove_buffer_keypair(name)?;
+            }
+            return Ok(buffer);
+        }
+
+        println!("There was a problem deploying: {exit:?}.");
+        if attempt == max_retries {
+            println!(
+                "The buffer is kept in {}, run the command again to resume the deploy.",
+                buffer_path.display()
+            );
             std::process::exit(exit.status.code().unwrap_or(1));
         }
-        Ok(())
-    })
+
+        let backoff = std::time::Duration::from_secs(2u64.pow(attempt.min(5)));
+        attempt += 1;
+        println!(
+            "Retrying in {}s ({attempt}/{max_retries})...",
+            backoff.as_secs()
+        );
+        std::thread::sleep(backoff);
+    }
+}
+
+fn buffer_keypair_path(name: &str) -> PathBuf {
+    PathBuf::from("target/deploy").join(format!("{name}-buffer-keypair.json"))
+}
+
+/// Removes the buffer keypair of a buffer written by [`program_deploy`], so
+/// that the next deploy writes a new buffer.
+fn remove_buffer_keypair(name: &str) -> Result<()> {
+    fs::remove_file(buffer_keypair_path(name))?;
+    Ok(())
 }
 
 fn create_idl_account(
@@ -3823,8 +6977,14 @@ fn create_idl_account(
     let client = create_client(url);
     let idl_data = serialize_idl(idl)?;
 
-    // Run `Create instruction.
+    // Run `Create instruction, unless an interrupted init created the account.
+    if client
+        .get_account_with_commitment(&idl_address, client.commitment())?
+        .value
+        .is_some()
     {
+        println!("Idl account {idl_address} exists, resuming the write");
+    } else {
         let pda_max_growth = 60_000;
         let idl_header_size = 44;
         let idl_data_len = idl_data.len() as u64;
@@ -4006,12 +7166,53 @@ fn migrate(cfg_override: &ConfigOverride) -> Result<()> {
 
         let use_ts = Path::new("tsconfig.json").exists() && migrations_dir.join(deploy_ts).exists();
 
+        // Rust scripts are run in the order of their file names.
+        let mut rust_scripts = fs::read_dir(&migrations_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().map_or(false, |ext| ext == "rs"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        rust_scripts.sort();
+        let programs = cfg
+            .get_rust_program_list()?
+            .into_iter()
+            .map(|path| {
+                let manifest = Manifest::from_path(path.join("Cargo.toml"))?;
+                Ok((manifest.package().name().to_owned(), path))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
         if !Path::new(".anchor").exists() {
             fs::create_dir(".anchor")?;
         }
         std::env::set_current_dir(".anchor")?;
 
-        let exit = if use_ts {
+        let exit = if !rust_scripts.is_empty() {
+            let host_dir = Path::new("migrations");
+            fs::create_dir_all(host_dir.join("src"))?;
+            fs::write(
+                host_dir.join("Cargo.toml"),
+                rust_template::rust_migration_cargo_toml(&programs),
+            )?;
+            fs::write(
+                host_dir.join("src").join("main.rs"),
+                rust_template::rust_migration_main(&rust_scripts),
+            )?;
+
+            std::process::Command::new("cargo")
+                .args(["run", "--quiet", "--manifest-path"])
+                .arg(host_dir.join("Cargo.toml"))
+                .env("ANCHOR_PROVIDER_URL", &url)
+                .env("ANCHOR_WALLET", cfg.provider.wallet.to_string())
+                .envs(cfg.profile_env())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .output()?
+        } else if use_ts {
             let module_path = migrations_dir.join(deploy_ts);
             let deploy_script_host_str =
                 rust_template::deploy_ts_script_host(&url, &module_path.display().to_string());
@@ -4024,6 +7225,7 @@ fn migrate(cfg_override: &ConfigOverride) -> Result<()> {
                     &fs::canonicalize(deploy_ts)?.to_string_lossy(),
                 ])
                 .env("ANCHOR_WALLET", cfg.provider.wallet.to_string())
+                .envs(cfg.profile_env())
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit())
                 .output()?
@@ -4037,6 +7239,7 @@ fn migrate(cfg_override: &ConfigOverride) -> Result<()> {
             std::process::Command::new("node")
                 .arg(&deploy_js)
                 .env("ANCHOR_WALLET", cfg.provider.wallet.to_string())
+                .envs(cfg.profile_env())
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit())
                 .output()?
@@ -4114,6 +7317,46 @@ fn cluster(_cmd: ClusterCommand) -> Result<()> {
     Ok(())
 }
 
+fn program_shell(
+    cfg_override: &ConfigOverride,
+    program_id: Pubkey,
+    idl_filepath: Option<String>,
+) -> Result<()> {
+    let mut idl = match idl_filepath {
+        Some(path) => convert_idl(&fs::read(path)?)?,
+        None => fetch_idl(cfg_override, program_id)?,
+    };
+    idl.address = program_id.to_string();
+
+    let cfg = Config::discover(cfg_override)?;
+    let url = match &cfg {
+        Some(cfg) => cluster_url(cfg, &cfg.test_validator),
+        None => match &cfg_override.cluster {
+            Some(cluster) => cluster.url().to_string(),
+            None => config::get_solana_cfg_url()?,
+        },
+    };
+    let wallet = match (&cfg_override.wallet, &cfg) {
+        (Some(wallet), _) => wallet.to_string(),
+        (None, Some(cfg)) => cfg.provider.wallet.to_string(),
+        (None, None) => config::get_solana_cfg_keypair_path()?,
+    };
+
+    let js_code = rust_template::node_program_shell(&url, &wallet, &idl)?;
+    let exit = std::process::Command::new("node")
+        .args(["--experimental-repl-await", "-e", &js_code])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| anyhow::format_err!("{}", e.to_string()))?;
+    if !exit.status.success() {
+        std::process::exit(exit.status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
 fn shell(cfg_override: &ConfigOverride) -> Result<()> {
     with_workspace(cfg_override, |cfg| {
         let programs = {
@@ -4192,6 +7435,7 @@ fn run(cfg_override: &ConfigOverride, script: String, script_args: Vec<String>)
             .arg(&script_with_args)
             .env("ANCHOR_PROVIDER_URL", url)
             .env("ANCHOR_WALLET", cfg.provider.wallet.to_string())
+            .envs(cfg.profile_env())
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
             .output()
@@ -4203,6 +7447,94 @@ fn run(cfg_override: &ConfigOverride, script: String, script_args: Vec<String>)
     })
 }
 
+/// Program published to the registry.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryProgram {
+    name: String,
+    version: String,
+    address: String,
+    /// Whether the deployed program matches the verifiable build
+    verified: bool,
+    idl: JsonValue,
+}
+
+fn add(
+    cfg_override: &ConfigOverride,
+    registry_path: String,
+    program_name: Option<String>,
+) -> Result<()> {
+    let (name, version) = match registry_path.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (registry_path.as_str(), None),
+    };
+
+    with_workspace(cfg_override, |cfg| {
+        // Find the program to declare the pulled program in.
+        let programs = cfg
+            .get_programs(program_name)?
+            .into_iter()
+            .filter(|program| !program.solidity)
+            .collect::<Vec<_>>();
+        let program = match programs.as_slice() {
+            [program] => program,
+            [] => return Err(anyhow!("No Rust program found in the workspace")),
+            _ => {
+                return Err(anyhow!(
+                    "The workspace has several programs, choose one with `--program-name`"
+                ))
+            }
+        };
+
+        let url = match version {
+            Some(version) => format!("{}/api/v0/programs/{name}/{version}", cfg.registry.url),
+            None => format!("{}/api/v0/programs/{name}", cfg.registry.url),
+        };
+        let resp = Client::new().get(&url).send()?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(
+                "Unable to fetch `{registry_path}` from the registry: {}",
+                resp.text().unwrap_or_else(|_| "Server error".to_string())
+            ));
+        }
+        let registry_program: RegistryProgram = serde_json::from_str(&resp.text()?)?;
+        let mut idl = convert_idl(&serde_json::to_vec(&registry_program.idl)?)?;
+        idl.address = registry_program.address.clone();
+        if !registry_program.verified {
+            println!(
+                "Warning: `{}` isn't verified, its deployed binary may not match its source",
+                registry_program.name
+            );
+        }
+
+        // `declare_program!` reads the IDL from the `idls` directory.
+        let lib_name = idl.metadata.name.to_snake_case();
+        let idl_path = Path::new("idls").join(format!("{lib_name}.json"));
+        fs::create_dir_all("idls")?;
+        fs::write(&idl_path, serde_json::to_string_pretty(&idl)?)?;
+        println!(
+            "Added `{}` v{} ({}) to {}",
+            registry_program.name,
+            registry_program.version,
+            registry_program.address,
+            idl_path.display()
+        );
+
+        let lib_path = program.path.join("src").join("lib.rs");
+        let lib = fs::read_to_string(&lib_path)?;
+        let declaration = format!("declare_program!({lib_name});");
+        if !lib.contains(&declaration) {
+            fs::write(&lib_path, insert_after_declare_id(&lib, &declaration))?;
+            println!(
+                "Declared `{lib_name}` in {}, call it through `{lib_name}::cpi`",
+                lib_path.display()
+            );
+        }
+
+        Ok(())
+    })
+}
+
 fn login(_cfg_override: &ConfigOverride, token: String) -> Result<()> {
     let dir = shellexpand::tilde("~/.config/anchor");
     if !Path::new(&dir.to_string()).exists() {
@@ -4345,7 +7677,7 @@ fn publish(
             None,
             true,
             false,
-            Some(program_name),
+            Some(program_name.clone()),
             None,
             None,
             BootstrapMode::None,
@@ -4355,17 +7687,32 @@ fn publish(
             cargo_args,
             true,
             arch,
+            false,
         )?;
     }
 
-    // Upload the tarball to the server.
+    // Upload the tarball to the server, along with the IDL and the build
+    // manifest of the program, if it was built.
     let token = registry_api_token(cfg_override)?;
-    let form = Form::new()
+    let mut form = Form::new()
         .part("manifest", Part::bytes(anchor_package_bytes))
         .part("workspace", {
             let file = File::open(&tarball_filename)?;
             Part::reader(file)
         });
+    let idl_path = ws_dir
+        .join("target/idl")
+        .join(format!("{program_name}.json"));
+    if idl_path.exists() {
+        form = form.part("idl", Part::bytes(fs::read(idl_path)?));
+    }
+    let build_manifest_path = VerifiableBuildManifest::path(&ws_dir, &program_name);
+    if build_manifest_path.exists() {
+        form = form.part(
+            "build-manifest",
+            Part::bytes(fs::read(build_manifest_path)?),
+        );
+    }
     let client = Client::new();
     let resp = client
         .post(format!("{}/api/v0/build", cfg.registry.url))
@@ -4382,42 +7729,413 @@ fn publish(
         );
     }
 
-    Ok(())
+    Ok(())
+}
+
+// Unpacks the tarball into the current directory.
+fn unpack_archive(tar_path: impl AsRef<Path>) -> Result<()> {
+    let tar = GzDecoder::new(std::fs::File::open(tar_path)?);
+    let mut archive = Archive::new(tar);
+    archive.unpack(".")?;
+    archive.into_inner();
+
+    Ok(())
+}
+
+fn registry_api_token(_cfg_override: &ConfigOverride) -> Result<String> {
+    #[derive(Debug, Deserialize)]
+    struct Registry {
+        token: String,
+    }
+    #[derive(Debug, Deserialize)]
+    struct Credentials {
+        registry: Registry,
+    }
+    let filename = shellexpand::tilde("~/.config/anchor/credentials");
+    let mut file = File::open(filename.to_string())?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+
+    let credentials_toml: Credentials = toml::from_str(&contents)?;
+
+    Ok(credentials_toml.registry.token)
+}
+
+fn client(cmd: ClientCommand) -> Result<()> {
+    match cmd {
+        ClientCommand::Gen { lang, idl, out } => client_gen(lang, idl, out),
+    }
+}
+
+fn client_gen(lang: ClientLang, idl: String, out: String) -> Result<()> {
+    let idl = fs::read(idl)?;
+    let idl = convert_idl(&idl)?;
+    match lang {
+        ClientLang::Rust => rust_template::create_client_crate(&idl, Path::new(&out))?,
+    }
+    println!("Generated the client of {} in {out}", idl.metadata.name);
+    Ok(())
+}
+
+fn docs(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    out: String,
+    html: bool,
+    skip_build: bool,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let out = cfg.path().parent().unwrap().join(out);
+        fs::create_dir_all(&out)?;
+
+        let ext = if html { "html" } else { "md" };
+        let mut index = "# Programs\n\n".to_owned();
+        for program in cfg.get_programs(program_name)? {
+            // The IDL is generated again, with its docs, unless skipped
+            let idl = if skip_build || program.solidity {
+                program.idl.ok_or_else(|| {
+                    anyhow!(
+                        "IDL of {} not found, run `anchor build` first",
+                        program.lib_name
+                    )
+                })?
+            } else {
+                anchor_lang_idl::build::build_idl(
+                    &program.path,
+                    cfg.features.resolution,
+                    cfg.features.skip_lint,
+                    false,
+                )?
+            };
+
+            let md = docs::markdown(&idl);
+            let content = if html {
+                docs::html(&idl.metadata.name, &md)
+            } else {
+                md
+            };
+            let file = format!("{}.{ext}", program.lib_name);
+            fs::write(out.join(&file), content)?;
+            index.push_str(&format!("- [{}]({file})\n", idl.metadata.name));
+        }
+
+        let (index_file, index) = if html {
+            ("index.html", docs::html("Programs", &index))
+        } else {
+            ("README.md", index)
+        };
+        fs::write(out.join(index_file), index)?;
+        println!("Documentation written to {}", out.display());
+        Ok(())
+    })
+}
+
+fn graph(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    format: GraphFormat,
+    out: Option<String>,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let out = out.map(|out| cfg.path().parent().unwrap().join(out));
+        if let Some(out) = &out {
+            fs::create_dir_all(out)?;
+        }
+
+        for program in cfg.get_programs(program_name)? {
+            // The graph is built from the accounts structs of Rust programs.
+            if program.solidity {
+                continue;
+            }
+            let graph = graph::Graph::from_sources(program.path.join("src"))?;
+            let (graph, ext) = match format {
+                GraphFormat::Mermaid => (graph.mermaid(), "mmd"),
+                GraphFormat::Dot => (graph.dot(&program.lib_name), "dot"),
+            };
+            match &out {
+                Some(out) => {
+                    let path = out.join(format!("{}.{ext}", program.lib_name));
+                    fs::write(&path, graph)?;
+                    println!(
+                        "Graph of `{}` written to {}",
+                        program.lib_name,
+                        path.display()
+                    );
+                }
+                None => print!("{graph}"),
+            }
+        }
+        Ok(())
+    })
+}
+
+fn fuzz_cmd(cfg_override: &ConfigOverride, subcmd: FuzzCommand) -> Result<()> {
+    match subcmd {
+        FuzzCommand::Init { program_name } => fuzz_init(cfg_override, program_name),
+        FuzzCommand::Run {
+            program_name,
+            skip_build,
+            args,
+        } => fuzz_run(cfg_override, program_name, skip_build, args),
+    }
+}
+
+fn fuzz_init(cfg_override: &ConfigOverride, program_name: Option<String>) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let fuzz_dir = cfg.path().parent().unwrap().join(fuzz::FUZZ_DIR);
+        // The manifest lists the targets of all the programs
+        let mut targets = fs::read_dir(fuzz_dir.join("fuzz_targets"))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path().file_stem()?.to_str()?.to_owned()))
+            .collect::<BTreeSet<_>>();
+        let mut files = vec![];
+        for program in cfg.get_programs(program_name)? {
+            if program.solidity {
+                continue;
+            }
+            let idl = program.idl.ok_or_else(|| {
+                anyhow!(
+                    "IDL of {} not found, run `anchor build` first",
+                    program.lib_name
+                )
+            })?;
+            files.push((
+                fuzz_dir
+                    .join("fuzz_targets")
+                    .join(format!("{}.rs", program.lib_name)),
+                fuzz::target(&program.lib_name, &idl),
+            ));
+            targets.insert(program.lib_name.clone());
+
+            // Invariants are written by the user
+            let invariants = fuzz_dir
+                .join("invariants")
+                .join(format!("{}.rs", program.lib_name));
+            if !invariants.exists() {
+                files.push((invariants, fuzz::invariants(&program.lib_name)));
+            }
+        }
+
+        let targets = targets.into_iter().collect::<Vec<_>>();
+        files.push((fuzz_dir.join("Cargo.toml"), fuzz::manifest(&targets)));
+        files.push((
+            fuzz_dir.join(".gitignore"),
+            "target\ncorpus\nartifacts\ncoverage\n".to_owned(),
+        ));
+        override_or_create_files(&files)?;
+
+        println!("Fuzzing crate generated in {}", fuzz_dir.display());
+        println!("Run a target with `anchor fuzz run <PROGRAM_NAME>`");
+        Ok(())
+    })
+}
+
+fn fuzz_run(
+    cfg_override: &ConfigOverride,
+    program_name: String,
+    skip_build: bool,
+    args: Vec<String>,
+) -> Result<()> {
+    let installed = std::process::Command::new("cargo")
+        .args(["fuzz", "--version"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |status| status.success());
+    if !installed {
+        return Err(anyhow!(
+            "cargo-fuzz is not installed, install it with `cargo install cargo-fuzz`"
+        ));
+    }
+
+    if !skip_build {
+        build(
+            cfg_override,
+            false,
+            None,
+            None,
+            false,
+            false,
+            Some(program_name.clone()),
+            None,
+            None,
+            BootstrapMode::None,
+            None,
+            None,
+            vec![],
+            vec![],
+            false,
+            ProgramArch::Sbf,
+            false,
+        )?;
+    }
+
+    with_workspace(cfg_override, |cfg| {
+        let fuzz_dir = cfg.path().parent().unwrap().join(fuzz::FUZZ_DIR);
+        if !fuzz_dir
+            .join("fuzz_targets")
+            .join(format!("{program_name}.rs"))
+            .exists()
+        {
+            return Err(anyhow!(
+                "Fuzz target of {program_name} not found, run `anchor fuzz init` first"
+            ));
+        }
+
+        // libFuzzer requires a nightly toolchain
+        let status = std::process::Command::new("cargo")
+            .args(["+nightly", "fuzz", "run", &program_name, "--"])
+            .args(&args)
+            .current_dir(&fuzz_dir)
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()?;
+        if !status.success() {
+            println!(
+                "Crashing inputs of {program_name} are saved in {}",
+                fuzz_dir.join("artifacts").join(&program_name).display()
+            );
+            std::process::exit(status.code().unwrap_or(1));
+        }
+        Ok(())
+    })
 }
 
-// Unpacks the tarball into the current directory.
-fn unpack_archive(tar_path: impl AsRef<Path>) -> Result<()> {
-    let tar = GzDecoder::new(std::fs::File::open(tar_path)?);
-    let mut archive = Archive::new(tar);
-    archive.unpack(".")?;
-    archive.into_inner();
+fn cost(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    skip_build: bool,
+    json: bool,
+) -> Result<()> {
+    if !skip_build {
+        build(
+            cfg_override,
+            false,
+            None,
+            None,
+            false,
+            false,
+            program_name.clone(),
+            None,
+            None,
+            BootstrapMode::None,
+            None,
+            None,
+            vec![],
+            vec![],
+            false,
+            ProgramArch::Sbf,
+            false,
+        )?;
+    }
 
-    Ok(())
+    with_workspace(cfg_override, |cfg| {
+        let programs = cfg
+            .get_programs(program_name)?
+            .into_iter()
+            .filter(|program| !program.solidity)
+            .collect::<Vec<_>>();
+        let cost_dir = cfg
+            .path()
+            .parent()
+            .unwrap()
+            .join("target")
+            .join(cost::COST_DIR);
+        let mut files = vec![];
+        for program in &programs {
+            let idl = program.idl.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "IDL of {} not found, run `anchor build` first",
+                    program.lib_name
+                )
+            })?;
+            files.push((
+                cost_dir
+                    .join("src")
+                    .join(format!("{}.rs", program.lib_name)),
+                cost::scenarios(&program.lib_name, idl),
+            ));
+        }
+        let names = programs
+            .iter()
+            .map(|program| program.lib_name.clone())
+            .collect::<Vec<_>>();
+        files.push((cost_dir.join("Cargo.toml"), cost::manifest(&names)));
+        override_or_create_files(&files)?;
+
+        let mut reports = BTreeMap::new();
+        for name in names {
+            let output = std::process::Command::new("cargo")
+                .args(["run", "--release", "--quiet", "--bin", &name])
+                .current_dir(&cost_dir)
+                .stderr(Stdio::inherit())
+                .output()?;
+            if !output.status.success() {
+                return Err(anyhow!("Failed to estimate the compute units of {name}"));
+            }
+            let report = cost::CostReport::from_output(&String::from_utf8_lossy(&output.stdout));
+            if !json {
+                println!("Program: {name}\n{report}");
+            }
+            reports.insert(name, report);
+        }
+        if json {
+            println!("{}", serde_json::to_string_pretty(&reports)?);
+        }
+        Ok(())
+    })
 }
 
-fn registry_api_token(_cfg_override: &ConfigOverride) -> Result<String> {
-    #[derive(Debug, Deserialize)]
-    struct Registry {
-        token: String,
-    }
-    #[derive(Debug, Deserialize)]
-    struct Credentials {
-        registry: Registry,
-    }
-    let filename = shellexpand::tilde("~/.config/anchor/credentials");
-    let mut file = File::open(filename.to_string())?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-
-    let credentials_toml: Credentials = toml::from_str(&contents)?;
+fn lint(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    deny_warnings: bool,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let root = cfg.path().parent().unwrap().to_owned();
+        let mut count = 0;
+        for program in cfg.get_programs(program_name)? {
+            // Solidity programs are not linted.
+            if !program.path.is_dir() {
+                continue;
+            }
+            let sources = walkdir::WalkDir::new(program.path.join("src"))
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "rs"));
+            for source in sources {
+                let path = source.path();
+                let name = path
+                    .strip_prefix(&root)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string();
+                let lints = lint::lint_file(path, &name)
+                    .with_context(|| format!("Failed to parse {name}"))?;
+                for lint in &lints {
+                    println!("{lint}\n");
+                }
+                count += lints.len();
+            }
+        }
 
-    Ok(credentials_toml.registry.token)
+        if count != 0 && deny_warnings {
+            return Err(anyhow!("`anchor lint` reported {count} warning(s)"));
+        }
+        println!("`anchor lint` reported {count} warning(s)");
+        Ok(())
+    })
 }
 
 fn keys(cfg_override: &ConfigOverride, cmd: KeysCommand) -> Result<()> {
     match cmd {
         KeysCommand::List => keys_list(cfg_override),
         KeysCommand::Sync { program_name } => keys_sync(cfg_override, program_name),
+        KeysCommand::Rotate {
+            program_name,
+            skip_build,
+        } => keys_rotate(cfg_override, program_name, skip_build),
     }
 }
 
@@ -4491,14 +8209,188 @@ fn keys_sync(cfg_override: &ConfigOverride, program_name: Option<String>) -> Res
             }
         }
 
+        keys_sync_idls(cfg)?;
+
         println!("All program id declarations are synced.");
 
         Ok(())
     })
 }
 
+// Syncs the addresses of the IDLs in `idls/`, which `declare_program!` embeds,
+// with the workspace keypairs and the programs of the cluster in Anchor.toml.
+fn keys_sync_idls(cfg: &WithPath<Config>) -> Result<()> {
+    let root = cfg.path().parent().unwrap();
+    let mut addresses = cfg
+        .programs
+        .get(&cfg.provider.cluster)
+        .into_iter()
+        .flatten()
+        .map(|(name, deployment)| (name.clone(), deployment.address.to_string()))
+        .collect::<BTreeMap<_, _>>();
+    for program in cfg.read_all_programs()? {
+        if let Ok(program_id) = program.pubkey() {
+            addresses.insert(program.lib_name, program_id.to_string());
+        }
+    }
+
+    let idl_dir = root.join("idls");
+    let idls = match fs::read_dir(&idl_dir) {
+        Ok(idls) => idls,
+        Err(_) => return Ok(()),
+    };
+    let mut idl_names = BTreeSet::new();
+    for entry in idls {
+        let path = entry?.path();
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        let idl: JsonValue = match serde_json::from_str(&content) {
+            Ok(idl) => idl,
+            Err(_) => continue,
+        };
+        let name = match idl["metadata"]["name"].as_str() {
+            Some(name) => name.to_owned(),
+            None => path.file_stem().unwrap().to_string_lossy().into_owned(),
+        };
+        idl_names.insert(path.file_stem().unwrap().to_string_lossy().into_owned());
+
+        let (address, expected) = match (idl["address"].as_str(), addresses.get(&name)) {
+            (Some(address), Some(expected)) => (address, expected),
+            _ => continue,
+        };
+        if address != expected {
+            println!("Found incorrect program id in {path:?} for the program `{name}`");
+
+            // Replace the address in place to keep the formatting of the IDL
+            fs::write(&path, content.replace(address, expected))?;
+
+            println!("Updated to {expected}\n");
+        }
+    }
+
+    // `declare_program!` fails to compile without its IDL, make it obvious
+    let declare_program_regex = Regex::new(r"declare_program!\(\s*(\w+)\s*\)").unwrap();
+    for program in cfg.read_all_programs()? {
+        let sources = walkdir::WalkDir::new(program.path.join("src"))
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "rs"));
+        for source in sources {
+            let content = fs::read_to_string(source.path())?;
+            for captures in declare_program_regex.captures_iter(&content) {
+                let name = &captures[1];
+                if !idl_names.contains(name) {
+                    println!(
+                        "WARNING: `declare_program!({name})` in {:?} has no IDL in {idl_dir:?}, \
+                        fetch it with `anchor idl fetch --all`\n",
+                        source.path()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn keys_rotate(
+    cfg_override: &ConfigOverride,
+    program_name: String,
+    skip_build: bool,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let program = cfg.get_program(&program_name)?;
+        let old_program_id = program.pubkey()?;
+
+        // Keep the old keypair around, it's the only way to recover the old id.
+        let keypair_path = program.keypair_file()?.path().to_owned();
+        let backup_path = keypair_path.with_file_name(format!(
+            "{}-keypair-{old_program_id}.json",
+            program.lib_name
+        ));
+        fs::copy(&keypair_path, &backup_path)?;
+
+        let keypair = Keypair::new();
+        let program_id = keypair.pubkey();
+        solana_sdk::signature::write_keypair_file(&keypair, &keypair_path)
+            .map_err(|e| anyhow!("Failed to write the new keypair: {e}"))?;
+        println!(
+            "Rotated the id of `{}` from {old_program_id} to {program_id}\n",
+            program.lib_name
+        );
+
+        // The old id can be in `declare_id!`, the `Anchor.toml`, tests, clients
+        // or other programs of the workspace. Only source and config files are
+        // updated, `target` being skipped by `is_hidden` since it's rebuilt.
+        let root = cfg.path().parent().unwrap().to_owned();
+        let (old, new) = (old_program_id.to_string(), program_id.to_string());
+        let files = walkdir::WalkDir::new(&root)
+            .into_iter()
+            .filter_entry(|entry| !is_hidden(entry) && entry.file_name() != "node_modules")
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| {
+                entry.path().extension().map_or(false, |ext| {
+                    ["rs", "toml", "json", "ts", "js"].contains(&&*ext.to_string_lossy())
+                })
+            });
+        for file in files {
+            let path = file.path();
+            let content = match fs::read_to_string(path) {
+                Ok(content) if content.contains(&old) => content,
+                _ => continue,
+            };
+            fs::write(path, content.replace(&old, &new))?;
+            println!(
+                "Updated {}",
+                path.strip_prefix(&root).unwrap_or(path).display()
+            );
+        }
+
+        if !skip_build {
+            build(
+                cfg_override,
+                false,
+                None,
+                None,
+                false,
+                false,
+                Some(program_name.clone()),
+                None,
+                None,
+                BootstrapMode::None,
+                None,
+                None,
+                vec![],
+                vec![],
+                false,
+                ProgramArch::Sbf,
+                false,
+            )?;
+        }
+
+        println!(
+            "\nRedeploy plan of `{0}`:\n\n\
+            1. Deploy the program to its new address:\n\n\
+            \tanchor deploy --program-name {0} --provider.cluster <cluster>\n\n\
+            2. Initialize the IDL account of the new program:\n\n\
+            \tanchor idl init {new} --filepath target/idl/{0}.json\n\n\
+            3. Migrate the users of the old program, then close it to reclaim its rent:\n\n\
+            \tsolana program close {old} --bypass-warning\n\n\
+            The old keypair was saved to {1}",
+            program.lib_name,
+            backup_path.display(),
+        );
+
+        Ok(())
+    })
+}
+
 fn localnet(
     cfg_override: &ConfigOverride,
+    restore: Option<String>,
     skip_build: bool,
     skip_deploy: bool,
     skip_lint: bool,
@@ -4507,6 +8399,18 @@ fn localnet(
     arch: ProgramArch,
 ) -> Result<()> {
     with_workspace(cfg_override, |cfg| {
+        let snapshot = match &restore {
+            Some(name) => {
+                let snapshot = snapshot_path(name)?;
+                if !snapshot.exists() {
+                    return Err(anyhow!("Snapshot `{name}` not found"));
+                }
+                println!("Restoring snapshot `{name}`");
+                Some(snapshot)
+            }
+            None => None,
+        };
+
         // Build if needed.
         if !skip_build {
             build(
@@ -4526,6 +8430,7 @@ fn localnet(
                 cargo_args,
                 false,
                 arch,
+                false,
             )?;
         }
 
@@ -4534,13 +8439,30 @@ fn localnet(
             false => Some(validator_flags(cfg, &cfg.test_validator)?),
         };
 
-        let validator_handle = &mut start_test_validator(cfg, &cfg.test_validator, flags, false)?;
+        let flags = flags.unwrap_or_default();
+        let validator_handle = &mut start_test_validator_from(
+            cfg,
+            &cfg.test_validator,
+            Some(flags.clone()),
+            false,
+            snapshot.as_deref(),
+        )?;
 
         // Setup log reader.
         let url = test_validator_rpc_url(&cfg.test_validator);
         let log_streams = stream_logs(cfg, &url);
 
-        std::io::stdin().lock().lines().next().unwrap().unwrap();
+        with_admin_rpc(
+            cfg,
+            &cfg.test_validator,
+            &flags,
+            false,
+            validator_handle,
+            |admin_url| {
+                println!("Admin RPC listening on {admin_url}, warp the clock with `warpToSlot`");
+                std::io::stdin().lock().lines().next().unwrap().unwrap();
+            },
+        )?;
 
         // Check all errors and shut down.
         if let Err(err) = validator_handle.kill() {
@@ -4561,6 +8483,157 @@ fn localnet(
     })
 }
 
+// Rebuilds and upgrades the programs on the running localnet when their
+// sources change. Builds and deploys run as `anchor` subprocesses, so that a
+// failure doesn't stop watching.
+fn watch(
+    cfg_override: &ConfigOverride,
+    program_name: Option<String>,
+    skip_lint: bool,
+) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        if cfg.provider.cluster != Cluster::Localnet {
+            return Err(anyhow!("Programs can only be watched on localnet"));
+        }
+        let url = cluster_url(cfg, &cfg.test_validator);
+        if create_client(&url).get_version().is_err() {
+            return Err(anyhow!(
+                "No localnet running at {url}, start one with `anchor localnet`"
+            ));
+        }
+
+        let anchor = std::env::current_exe()?;
+        let mut override_args = vec![];
+        if let Some(profile) = &cfg_override.profile {
+            override_args.extend(["--profile".to_owned(), profile.clone()]);
+        }
+        override_args.extend([
+            "--provider.wallet".to_owned(),
+            cfg.provider.wallet.to_string(),
+        ]);
+
+        let programs = cfg
+            .get_programs(program_name)?
+            .into_iter()
+            .filter(|program| !program.solidity)
+            .collect::<Vec<_>>();
+        let mut modified = programs
+            .iter()
+            .map(|program| sources_modified(&program.path))
+            .collect::<Vec<_>>();
+        println!(
+            "Watching {} program(s) for changes. Press Ctrl + C to quit.",
+            programs.len()
+        );
+
+        loop {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            for (program, modified) in programs.iter().zip(&mut modified) {
+                let latest = sources_modified(&program.path);
+                if latest <= *modified {
+                    continue;
+                }
+                *modified = latest;
+
+                println!("\nRebuilding `{}`...", program.lib_name);
+                let mut build = std::process::Command::new(&anchor);
+                build
+                    .args(&override_args)
+                    .args(["build", "--program-name", &program.lib_name]);
+                if skip_lint {
+                    build.arg("--skip-lint");
+                }
+                if !build.status()?.success() {
+                    println!("Build of `{}` failed", program.lib_name);
+                    continue;
+                }
+
+                println!("Upgrading `{}`...", program.lib_name);
+                let deployed = std::process::Command::new(&anchor)
+                    .args(&override_args)
+                    .args(["deploy", "--program-name", &program.lib_name])
+                    .status()?
+                    .success();
+                if deployed {
+                    println!("Upgraded `{}`", program.lib_name);
+                } else {
+                    println!(
+                        "Upgrade of `{}` failed. Programs loaded at genesis can only be \
+                        upgraded with `[test] upgradeable = true`.",
+                        program.lib_name
+                    );
+                }
+            }
+        }
+    })
+}
+
+// Returns the last modification time of the sources of a program.
+fn sources_modified(program_path: &Path) -> Option<std::time::SystemTime> {
+    walkdir::WalkDir::new(program_path)
+        .into_iter()
+        .filter_entry(|entry| !is_hidden(entry))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+// Saves the ledger of the last localnet run, so that slow setups only run once
+// and later runs resume from their state with `anchor localnet --restore`.
+fn localnet_snapshot(cfg_override: &ConfigOverride, name: String) -> Result<()> {
+    with_workspace(cfg_override, |cfg| {
+        let rpc_port = cfg
+            .test_validator
+            .as_ref()
+            .and_then(|test| test.validator.as_ref().map(|v| v.rpc_port))
+            .unwrap_or(solana_sdk::rpc_port::DEFAULT_RPC_PORT);
+        if !portpicker::is_free(rpc_port) {
+            return Err(anyhow!(
+                "The localnet is still running, stop it before taking a snapshot of its ledger"
+            ));
+        }
+
+        let ledger = Path::new(test_validator_ledger(&cfg.test_validator));
+        if !ledger.join("genesis.bin").exists() {
+            return Err(anyhow!(
+                "No ledger found in {}, run `anchor localnet` first",
+                ledger.display()
+            ));
+        }
+
+        let snapshot = snapshot_path(&name)?;
+        if snapshot.exists() {
+            fs::remove_dir_all(&snapshot)?;
+        }
+        copy_dir(ledger, &snapshot)?;
+        println!("Snapshot `{name}` saved to {}", snapshot.display());
+
+        Ok(())
+    })
+}
+
+fn snapshot_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(anyhow!("Invalid snapshot name `{name}`"));
+    }
+    Ok(Path::new(".anchor/snapshots").join(name))
+}
+
+// Copies the files of a ledger, except the validator log.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        let path = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(path)?;
+        } else if entry.file_type().is_file() && entry.file_name() != "test-ledger-log.txt" {
+            fs::copy(entry.path(), path)?;
+        }
+    }
+    Ok(())
+}
+
 // with_workspace ensures the current working directory is always the top level
 // workspace directory, i.e., where the `Anchor.toml` file is located, before
 // and after the closure invocation.
@@ -4685,13 +8758,14 @@ mod tests {
             &ConfigOverride {
                 cluster: None,
                 wallet: None,
+                profile: None,
             },
             "await".to_string(),
             true,
             false,
             true,
             false,
-            ProgramTemplate::default(),
+            InitTemplate::default(),
             TestTemplate::default(),
             false,
         )
@@ -4705,13 +8779,14 @@ mod tests {
             &ConfigOverride {
                 cluster: None,
                 wallet: None,
+                profile: None,
             },
             "fn".to_string(),
             true,
             false,
             true,
             false,
-            ProgramTemplate::default(),
+            InitTemplate::default(),
             TestTemplate::default(),
             false,
         )
@@ -4725,16 +8800,137 @@ mod tests {
             &ConfigOverride {
                 cluster: None,
                 wallet: None,
+                profile: None,
             },
             "1project".to_string(),
             true,
             false,
             true,
             false,
-            ProgramTemplate::default(),
+            InitTemplate::default(),
             TestTemplate::default(),
             false,
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_shard_test_validator_ports() {
+        let shards = (0..2)
+            .map(|shard| shard_test_validator(&None, shard).validator.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(shards[0].rpc_port, solana_sdk::rpc_port::DEFAULT_RPC_PORT);
+        assert_eq!(shards[1].rpc_port, shards[0].rpc_port + SHARD_PORTS);
+        assert_ne!(shards[0].ledger, shards[1].ledger);
+        assert_eq!(shards[0].dynamic_port_range.as_deref(), Some("8903-8948"));
+    }
+
+    #[test]
+    fn test_parse_security_txt() {
+        let bin = b"\x7fELF\0=======BEGIN SECURITY.TXT V1=======\0name\0vault\0\
+            contacts\0email:security@example.com\0=======END SECURITY.TXT V1=======\0\0";
+        let security_txt = parse_security_txt(bin).unwrap();
+        assert_eq!(security_txt.len(), 2);
+        assert_eq!(security_txt["name"], "vault");
+        assert_eq!(security_txt["contacts"], "email:security@example.com");
+        assert!(parse_security_txt(b"\x7fELF\0").is_none());
+    }
+
+    #[test]
+    fn test_find_security_txt_macro() {
+        let dir = std::env::temp_dir().join(format!("anchor-security-txt-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src/instructions")).unwrap();
+        fs::write(
+            dir.join("src/lib.rs"),
+            "use anchor_lang::prelude::*;\n// No `security_txt!` here\nconst DOC: &str = \"security_txt!\";",
+        )
+        .unwrap();
+        assert!(find_security_txt_macro(&dir).is_none());
+
+        let source = dir.join("src/instructions/security.rs");
+        fs::write(
+            &source,
+            "solana_security_txt::security_txt! { name: \"vault\" }",
+        )
+        .unwrap();
+        assert_eq!(find_security_txt_macro(&dir), Some(source));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_client_gen() {
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "address": "Counter111111111111111111111111111111111111",
+            "metadata": { "name": "counter", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "init",
+                "discriminator": [220, 59, 207, 236, 108, 250, 47, 100],
+                "accounts": [
+                    { "name": "authority", "writable": true, "signer": true },
+                    {
+                        "name": "counter",
+                        "writable": true,
+                        "pda": { "seeds": [
+                            { "kind": "const", "value": [99, 111, 117, 110, 116, 101, 114] },
+                            { "kind": "account", "path": "authority" },
+                            { "kind": "arg", "path": "id" }
+                        ] }
+                    },
+                    {
+                        "name": "config",
+                        "pda": { "seeds": [{ "kind": "account", "path": "counter.authority" }] }
+                    },
+                    {
+                        "name": "metadata",
+                        "pda": { "seeds": [{ "kind": "arg", "path": "memo" }] }
+                    },
+                    {
+                        "name": "external",
+                        "pda": {
+                            "seeds": [{ "kind": "const", "value": [1] }],
+                            "program": { "kind": "account", "path": "authority" }
+                        }
+                    }
+                ],
+                "args": [
+                    { "name": "id", "type": "u64" },
+                    { "name": "memo", "type": { "vec": "u8" } }
+                ]
+            }],
+            "errors": [{ "code": 6000, "name": "Overflow", "msg": "The counter overflowed" }]
+        }))
+        .unwrap();
+        let dir = std::env::temp_dir().join(format!("anchor-client-gen-{}", std::process::id()));
+        rust_template::create_client_crate(&idl, &dir).unwrap();
+
+        let lib = fs::read_to_string(dir.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("pub fn find_counter_address(authority: &Pubkey, id: u64)"));
+        assert!(lib.contains("// Skipped `find_config_address`: the `counter.authority` seed"));
+        assert!(lib.contains("// Skipped `find_metadata_address`: unsupported type of the `memo`"));
+        assert!(lib.contains("// Skipped `find_external_address`: derived from another program"));
+
+        // Build the generated crate against the crates of this workspace
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap().replace(
+            &format!("version = \"{VERSION}\""),
+            &format!("version = \"{VERSION}\", path = {:?}", root.join("client")),
+        );
+        let manifest = manifest.replacen(
+            &format!("anchor-lang = \"{VERSION}\""),
+            &format!("anchor-lang = {{ path = {:?} }}", root.join("lang")),
+            1,
+        );
+        fs::write(dir.join("Cargo.toml"), format!("{manifest}\n[workspace]\n")).unwrap();
+        if root.join("Cargo.lock").exists() {
+            fs::copy(root.join("Cargo.lock"), dir.join("Cargo.lock")).unwrap();
+        }
+        let status = std::process::Command::new("cargo")
+            .args(["check", "--all-features", "--target-dir"])
+            .arg(root.join("target").join("client-gen"))
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,359 @@
+use std::{fmt, fs, path::Path};
+
+use anyhow::Result;
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{
+    spanned::Spanned,
+    visit::{self, Visit},
+    BinOp, Expr, ExprAssignOp, ExprBinary, Field, Fields, ItemStruct,
+};
+
+/// Field names of accounts that are usually expected to sign.
+const AUTHORITY_NAMES: &[&str] = &["admin", "authority", "owner", "signer"];
+
+/// A potential vulnerability found in a program's source.
+#[derive(Debug)]
+pub struct Lint {
+    pub name: &'static str,
+    pub message: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "warning: {}\n  --> {}:{}:{}\n   = note: `{}`",
+            self.message, self.file, self.line, self.column, self.name
+        )
+    }
+}
+
+/// Lints the Rust source file at `path`, reporting it as `name`.
+pub fn lint_file(path: impl AsRef<Path>, name: &str) -> Result<Vec<Lint>> {
+    let src = fs::read_to_string(path)?;
+    lint_source(&src, name)
+}
+
+fn lint_source(src: &str, name: &str) -> Result<Vec<Lint>> {
+    let file = syn::parse_file(src)?;
+    let mut linter = Linter {
+        file: name,
+        lints: vec![],
+    };
+    linter.visit_file(&file);
+    Ok(linter.lints)
+}
+
+struct Linter<'a> {
+    file: &'a str,
+    lints: Vec<Lint>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, name: &'static str, span: Span, message: String) {
+        let start = span.start();
+        self.lints.push(Lint {
+            name,
+            message,
+            file: self.file.to_owned(),
+            line: start.line,
+            column: start.column + 1,
+        });
+    }
+
+    fn lint_accounts(&mut self, accounts: &ItemStruct) {
+        let fields = match &accounts.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return,
+        };
+        let fields = fields.iter().map(AccountField::new).collect::<Vec<_>>();
+
+        for field in &fields {
+            let name = &field.name;
+            if !field.is_raw() {
+                continue;
+            }
+            if !field.has_doc("SAFETY") && !field.has_doc("CHECK") {
+                self.report(
+                    "unchecked_account_safety",
+                    field.span,
+                    format!(
+                        "`{name}` is not checked through its type, document why it is safe \
+                        with a `/// SAFETY:` comment"
+                    ),
+                );
+            }
+            if AUTHORITY_NAMES.contains(&name.as_str()) && !field.has_constraint("signer") {
+                self.report(
+                    "missing_signer_check",
+                    field.span,
+                    format!("`{name}` is not checked to be a signer, use `Signer<'info>`"),
+                );
+            }
+            if field.has_constraint("mut")
+                && !["owner", "address", "seeds", "init", "init_if_needed"]
+                    .iter()
+                    .any(|c| field.has_constraint(c))
+            {
+                self.report(
+                    "missing_owner_check",
+                    field.span,
+                    format!(
+                        "the owner of the mutable account `{name}` is not checked, \
+                        add an `owner` constraint"
+                    ),
+                );
+            }
+        }
+
+        let is_cancel = accounts.ident.to_string().to_lowercase().contains("cancel");
+        if is_cancel && !fields.iter().any(|field| field.has_constraint("close")) {
+            self.report(
+                "missing_close",
+                accounts.ident.span(),
+                format!(
+                    "`{}` cancels without closing any account, add a `close` constraint",
+                    accounts.ident
+                ),
+            );
+        }
+
+        // Mutable accounts of the same type could be passed the same account
+        // unless a constraint tells them apart.
+        let mutable = fields
+            .iter()
+            .filter(|field| field.has_constraint("mut") && !field.is_raw())
+            .collect::<Vec<_>>();
+        for (i, a) in mutable.iter().enumerate() {
+            for b in &mutable[i + 1..] {
+                if a.ty != b.ty || a.references(&b.name) || b.references(&a.name) {
+                    continue;
+                }
+                self.report(
+                    "duplicate_mutable_accounts",
+                    b.span,
+                    format!(
+                        "`{}` and `{}` could be the same account, add a constraint like \
+                        `{}.key() != {}.key()`",
+                        a.name, b.name, a.name, b.name
+                    ),
+                );
+            }
+        }
+    }
+
+    fn lint_arithmetic(&mut self, left: &Expr, op: &BinOp, right: &Expr) {
+        let checked = match op {
+            BinOp::Add(_) | BinOp::AddEq(_) => "checked_add",
+            BinOp::Sub(_) | BinOp::SubEq(_) => "checked_sub",
+            BinOp::Mul(_) | BinOp::MulEq(_) => "checked_mul",
+            _ => return,
+        };
+        if is_literal(left) && is_literal(right) {
+            return;
+        }
+        self.report(
+            "unchecked_arithmetic",
+            op.span(),
+            format!(
+                "`{}` can overflow, use `{checked}` instead",
+                op.to_token_stream()
+            ),
+        );
+    }
+}
+
+impl<'ast> Visit<'ast> for Linter<'_> {
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        let is_accounts = item.attrs.iter().any(|attr| {
+            attr.path.is_ident("derive") && attr.tokens.to_string().contains("Accounts")
+        });
+        if is_accounts {
+            self.lint_accounts(item);
+        }
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast ExprBinary) {
+        self.lint_arithmetic(&expr.left, &expr.op, &expr.right);
+        visit::visit_expr_binary(self, expr);
+    }
+
+    fn visit_expr_assign_op(&mut self, expr: &'ast ExprAssignOp) {
+        self.lint_arithmetic(&expr.left, &expr.op, &expr.right);
+        visit::visit_expr_assign_op(self, expr);
+    }
+
+    // Lengths and constants are evaluated at compile time.
+    fn visit_item_const(&mut self, _: &'ast syn::ItemConst) {}
+    fn visit_type(&mut self, _: &'ast syn::Type) {}
+}
+
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) => true,
+        Expr::Paren(expr) => is_literal(&expr.expr),
+        _ => false,
+    }
+}
+
+struct AccountField {
+    name: String,
+    ty: String,
+    span: Span,
+    docs: Vec<String>,
+    constraints: Vec<(String, String)>,
+}
+
+impl AccountField {
+    fn new(field: &Field) -> Self {
+        let docs = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("doc"))
+            .map(|attr| attr.tokens.to_string())
+            .collect();
+        let constraints = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("account"))
+            .flat_map(|attr| constraints(attr.tokens.clone()))
+            .collect();
+        Self {
+            name: field.ident.as_ref().unwrap().to_string(),
+            ty: field.ty.to_token_stream().to_string(),
+            span: field.ident.as_ref().unwrap().span(),
+            docs,
+            constraints,
+        }
+    }
+
+    /// Whether the account isn't checked through its type.
+    fn is_raw(&self) -> bool {
+        self.ty.starts_with("AccountInfo") || self.ty.starts_with("UncheckedAccount")
+    }
+
+    fn has_doc(&self, keyword: &str) -> bool {
+        self.docs.iter().any(|doc| doc.contains(keyword))
+    }
+
+    fn has_constraint(&self, name: &str) -> bool {
+        self.constraints.iter().any(|(n, _)| n == name)
+    }
+
+    fn references(&self, field: &str) -> bool {
+        self.constraints.iter().any(|(_, tokens)| {
+            tokens
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .any(|ident| ident == field)
+        })
+    }
+}
+
+/// Splits the arguments of an `#[account(...)]` attribute into the name and
+/// the tokens of each constraint.
+pub(crate) fn constraints(tokens: TokenStream) -> Vec<(String, String)> {
+    let group = match tokens.into_iter().next() {
+        Some(TokenTree::Group(group)) => group,
+        _ => return vec![],
+    };
+
+    let mut constraints = vec![];
+    let mut constraint: Vec<TokenTree> = vec![];
+    for token in group
+        .stream()
+        .into_iter()
+        .chain([TokenTree::Punct(proc_macro2::Punct::new(
+            ',',
+            proc_macro2::Spacing::Alone,
+        ))])
+    {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => {
+                if let Some(TokenTree::Ident(name)) = constraint.first() {
+                    let tokens = constraint.iter().cloned().collect::<TokenStream>();
+                    constraints.push((name.to_string(), tokens.to_string()));
+                }
+                constraint.clear();
+            }
+            _ => constraint.push(token),
+        }
+    }
+    constraints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_names(src: &str) -> Vec<&'static str> {
+        lint_source(src, "lib.rs")
+            .unwrap()
+            .into_iter()
+            .map(|lint| lint.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_accounts_lints() {
+        let src = r#"
+            #[derive(Accounts)]
+            pub struct CancelOrder<'info> {
+                pub authority: UncheckedAccount<'info>,
+                #[account(mut)]
+                pub from: Account<'info, Vault>,
+                #[account(mut)]
+                pub to: Account<'info, Vault>,
+            }
+        "#;
+        assert_eq!(
+            lint_names(src),
+            [
+                "unchecked_account_safety",
+                "missing_signer_check",
+                "missing_close",
+                "duplicate_mutable_accounts"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_accounts_lints_satisfied() {
+        let src = r#"
+            #[derive(Accounts)]
+            pub struct CancelOrder<'info> {
+                pub authority: Signer<'info>,
+                /// SAFETY: Only receives lamports.
+                #[account(mut, owner = system_program::ID)]
+                pub receiver: AccountInfo<'info>,
+                #[account(mut, close = receiver)]
+                pub from: Account<'info, Vault>,
+                #[account(mut, constraint = to.key() != from.key())]
+                pub to: Account<'info, Vault>,
+            }
+        "#;
+        assert!(lint_names(src).is_empty());
+    }
+
+    #[test]
+    fn test_unchecked_arithmetic() {
+        let src = r#"
+            const SPACE: usize = 8 + 32;
+
+            fn deposit(vault: &mut Vault, amount: u64) -> Result<()> {
+                let fee = 2 * 5;
+                vault.amount += amount;
+                vault.total = vault.amount.checked_add(fee).unwrap();
+                Ok(())
+            }
+        "#;
+        let lints = lint_source(src, "lib.rs").unwrap();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].name, "unchecked_arithmetic");
+        assert_eq!(lints[0].line, 6);
+    }
+}
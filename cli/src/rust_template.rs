@@ -2,20 +2,26 @@ use crate::{
     config::ProgramWorkspace, create_files, override_or_create_files, solidity_template, Files,
     VERSION,
 };
-use anyhow::Result;
+use anchor_lang_idl::types::{
+    Idl, IdlArrayLen, IdlInstructionAccount, IdlInstructionAccountItem, IdlSeed, IdlSeedAccount,
+    IdlSeedArg, IdlType,
+};
+use anyhow::{anyhow, Result};
 use clap::{Parser, ValueEnum};
-use heck::{ToPascalCase, ToSnakeCase};
+use heck::{ToKebabCase, ToPascalCase, ToSnakeCase};
 use solana_sdk::{
     pubkey::Pubkey,
     signature::{read_keypair_file, write_keypair_file, Keypair},
     signer::Signer,
 };
 use std::{
+    collections::HashSet,
     fmt::Write as _,
     fs::{self, File},
     io::Write as _,
-    path::Path,
+    path::{Path, PathBuf},
     process::Stdio,
+    str::FromStr,
 };
 
 /// Program initialization template
@@ -26,25 +32,124 @@ pub enum ProgramTemplate {
     Single,
     /// Program with multiple files for instructions, state...
     Multiple,
+    /// Program creating and minting a token with `anchor-spl`
+    Token,
+}
+
+/// Workspace initialization template
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InitTemplate {
+    /// Workspace with a single program created from the given template
+    Program(ProgramTemplate),
+    /// Workspace with a program and another program calling it through CPI
+    MultiProgram,
+    /// Workspace tested with Rust tests only, without JavaScript dependencies
+    RustTest,
+    /// Template repository cloned with git, see [`substitute_template_vars`]
+    Git(String),
+}
+
+impl Default for InitTemplate {
+    fn default() -> Self {
+        Self::Program(ProgramTemplate::default())
+    }
+}
+
+impl FromStr for InitTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.contains("://") || s.starts_with("git@") || s.ends_with(".git") {
+            return Ok(Self::Git(s.to_owned()));
+        }
+        match s {
+            "multi-program" => Ok(Self::MultiProgram),
+            "rust-test" => Ok(Self::RustTest),
+            _ => <ProgramTemplate as ValueEnum>::from_str(s, true)
+                .map(Self::Program)
+                .map_err(|_| {
+                    anyhow!(
+                        "Invalid template `{s}`, expected one of `single`, `multiple`, \
+                        `token`, `multi-program`, `rust-test` or a git repository URL"
+                    )
+                }),
+        }
+    }
 }
 
 /// Create a program from the given name and template.
 pub fn create_program(name: &str, template: ProgramTemplate) -> Result<()> {
     let program_path = Path::new("programs").join(name);
+    let dependencies = match template {
+        ProgramTemplate::Token => vec![("anchor-spl".to_owned(), format!("\"{VERSION}\""))],
+        _ => vec![],
+    };
     let common_files = vec![
         ("Cargo.toml".into(), workspace_manifest().into()),
-        (program_path.join("Cargo.toml"), cargo_toml(name)),
+        (
+            program_path.join("Cargo.toml"),
+            cargo_toml(name, &dependencies),
+        ),
         (program_path.join("Xargo.toml"), xargo_toml().into()),
     ];
 
     let template_files = match template {
         ProgramTemplate::Single => create_program_template_single(name, &program_path),
         ProgramTemplate::Multiple => create_program_template_multiple(name, &program_path),
+        ProgramTemplate::Token => create_program_template_token(name, &program_path),
     };
 
     create_files(&[common_files, template_files].concat())
 }
 
+/// Create a program named `caller` that calls the `initialize` instruction of
+/// the `callee` program of the workspace through CPI.
+pub fn create_caller_program(caller: &str, callee: &str) -> Result<()> {
+    let program_path = Path::new("programs").join(caller);
+    let dependencies = vec![(
+        callee.to_owned(),
+        format!("{{ path = \"../{callee}\", features = [\"cpi\"] }}"),
+    )];
+    create_files(&vec![
+        (
+            program_path.join("Cargo.toml"),
+            cargo_toml(caller, &dependencies),
+        ),
+        (program_path.join("Xargo.toml"), xargo_toml().into()),
+        (
+            program_path.join("src").join("lib.rs"),
+            format!(
+                r#"use anchor_lang::prelude::*;
+use {1}::program::{2};
+
+declare_id!("{0}");
+
+#[program]
+pub mod {3} {{
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {{
+        {1}::cpi::initialize(CpiContext::new(
+            ctx.accounts.{1}_program.to_account_info(),
+            {1}::cpi::accounts::Initialize {{}},
+        ))
+    }}
+}}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {{
+    pub {1}_program: Program<'info, {2}>,
+}}
+"#,
+                get_or_create_program_id(caller),
+                callee.to_snake_case(),
+                callee.to_pascal_case(),
+                caller.to_snake_case(),
+            ),
+        ),
+    ])
+}
+
 /// Create a program with a single `lib.rs` file.
 fn create_program_template_single(name: &str, program_path: &Path) -> Files {
     vec![(
@@ -153,6 +258,124 @@ pub fn handler(ctx: Context<Initialize>) -> Result<()> {
     ]
 }
 
+/// Substitute the variables of a template repository in the contents and the
+/// paths of its files.
+///
+/// Variables are written as `{{name}}`, e.g. `{{program_name}}`. Hidden
+/// files and the `target` directory are left untouched.
+pub fn substitute_template_vars(root: &Path, vars: &[(&str, String)]) -> Result<()> {
+    let substitute = |s: &str| {
+        vars.iter().fold(s.to_owned(), |s, (name, value)| {
+            s.replace(&format!("{{{{{name}}}}}"), value)
+        })
+    };
+
+    // Children come first so that they're renamed before their parents.
+    let entries = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .filter_entry(|entry| !crate::is_hidden(entry));
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type().is_file() {
+            // Binary files are copied as is.
+            if let Ok(contents) = fs::read_to_string(path) {
+                let substituted = substitute(&contents);
+                if substituted != contents {
+                    fs::write(path, substituted)?;
+                }
+            }
+        }
+
+        let name = entry.file_name().to_string_lossy();
+        let substituted = substitute(&name);
+        if substituted != name {
+            fs::rename(path, path.with_file_name(substituted))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a program creating a mint controlled by a PDA and minting tokens.
+fn create_program_template_token(name: &str, program_path: &Path) -> Files {
+    vec![(
+        program_path.join("src").join("lib.rs"),
+        format!(
+            r#"use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{{self, Mint, MintTo, TokenAccount, TokenInterface}};
+
+declare_id!("{}");
+
+#[program]
+pub mod {} {{
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {{
+        msg!("Greetings from: {{:?}}", ctx.program_id);
+        Ok(())
+    }}
+
+    pub fn create_mint(_ctx: Context<CreateMint>, _decimals: u8) -> Result<()> {{
+        Ok(())
+    }}
+
+    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {{
+        let signer_seeds: &[&[&[u8]]] = &[&[b"mint", &[ctx.bumps.mint]]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {{
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.to.to_account_info(),
+                    authority: ctx.accounts.mint.to_account_info(),
+                }},
+                signer_seeds,
+            ),
+            amount,
+        )
+    }}
+}}
+
+#[derive(Accounts)]
+pub struct Initialize {{}}
+
+#[derive(Accounts)]
+#[instruction(decimals: u8)]
+pub struct CreateMint<'info> {{
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"mint"],
+        bump,
+        mint::decimals = decimals,
+        mint::authority = mint,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}}
+
+#[derive(Accounts)]
+pub struct MintTokens<'info> {{
+    #[account(mut, seeds = [b"mint"], bump)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}}
+"#,
+            get_or_create_program_id(name),
+            name.to_snake_case(),
+        ),
+    )]
+}
+
 const fn workspace_manifest() -> &'static str {
     r#"[workspace]
 members = [
@@ -171,7 +394,17 @@ codegen-units = 1
 "#
 }
 
-fn cargo_toml(name: &str) -> String {
+/// Manifest of a program, with its dependencies given as the name and the
+/// TOML value of each dependency in addition to `anchor-lang`.
+fn cargo_toml(name: &str, dependencies: &[(String, String)]) -> String {
+    let idl_build = dependencies
+        .iter()
+        .map(|(dep, _)| format!(", \"{dep}/idl-build\""))
+        .collect::<String>();
+    let dependencies = dependencies
+        .iter()
+        .map(|(dep, value)| format!("{dep} = {value}\n"))
+        .collect::<String>();
     format!(
         r#"[package]
 name = "{0}"
@@ -189,14 +422,16 @@ cpi = ["no-entrypoint"]
 no-entrypoint = []
 no-idl = []
 no-log-ix-name = []
-idl-build = ["anchor-lang/idl-build"]
+idl-build = ["anchor-lang/idl-build"{3}]
 
 [dependencies]
 anchor-lang = "{2}"
-"#,
+{4}"#,
         name,
         name.to_snake_case(),
         VERSION,
+        idl_build,
+        dependencies,
     )
 }
 
@@ -282,6 +517,90 @@ main();
     )
 }
 
+/// Manifest of the crate running the Rust migration scripts, which depends on
+/// the given workspace programs as `(package name, path)`.
+pub fn rust_migration_cargo_toml(programs: &[(String, PathBuf)]) -> String {
+    let programs = programs
+        .iter()
+        .map(|(name, path)| format!("{name} = {{ path = {:?} }}\n", path.display().to_string()))
+        .collect::<String>();
+    format!(
+        r#"[package]
+name = "migrations"
+version = "0.1.0"
+edition = "2021"
+
+[workspace]
+
+[dependencies]
+anchor-client = "{VERSION}"
+anyhow = "1"
+{programs}"#
+    )
+}
+
+/// Entrypoint running each Rust migration script in order.
+pub fn rust_migration_main(scripts: &[PathBuf]) -> String {
+    let modules = scripts
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            format!(
+                "#[path = {:?}]\nmod migration_{i};\n",
+                path.display().to_string()
+            )
+        })
+        .collect::<String>();
+    let runs = scripts
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            format!(
+                "    println!(\"Running {}\");\n    migration_{i}::migrate(&migration)?;\n",
+                path.file_name().unwrap().to_string_lossy()
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"use std::rc::Rc;
+use std::str::FromStr;
+
+use anchor_client::{{
+    solana_sdk::{{pubkey::Pubkey, signature::read_keypair_file, signature::Keypair}},
+    Client, Cluster, Program,
+}};
+
+{modules}
+/// Context given to the migration scripts.
+pub struct Migration {{
+    pub client: Client<Rc<Keypair>>,
+    pub payer: Rc<Keypair>,
+}}
+
+impl Migration {{
+    /// Returns the client of the program with the given id.
+    pub fn program(&self, program_id: Pubkey) -> anyhow::Result<Program<Rc<Keypair>>> {{
+        Ok(self.client.program(program_id)?)
+    }}
+}}
+
+fn main() -> anyhow::Result<()> {{
+    let cluster = Cluster::from_str(&std::env::var("ANCHOR_PROVIDER_URL")?)?;
+    let payer = read_keypair_file(std::env::var("ANCHOR_WALLET")?)
+        .map_err(|e| anyhow::anyhow!("Failed to read the wallet: {{e}}"))?;
+    let payer = Rc::new(payer);
+    let migration = Migration {{
+        client: Client::new(cluster, payer.clone()),
+        payer,
+    }};
+
+{runs}
+    Ok(())
+}}
+"#
+    )
+}
+
 pub fn deploy_script() -> &'static str {
     r#"// Migrations are an early feature. Currently, they're nothing more than this
 // single deploy script that's invoked from the CLI, injecting a provider
@@ -601,6 +920,98 @@ anchor.workspace.{} = new anchor.Program({}, new PublicKey("{}"), provider);
     Ok(eval_string)
 }
 
+/// Node shell with helpers to inspect and simulate the instructions of a
+/// single deployed program.
+pub fn node_program_shell(cluster_url: &str, wallet_path: &str, idl: &Idl) -> Result<String> {
+    let mut eval_string = node_shell(cluster_url, wallet_path, vec![])?;
+    write!(
+        &mut eval_string,
+        r#"
+const idl = {idl};
+const program = new anchor.Program(idl, provider);
+const BN = anchor.BN;
+
+const repl = require("repl").start({{ prompt: "{name}> " }});
+const ask = (query) => new Promise((resolve) => repl.question(query, resolve));
+const camel = (name) => name.replace(/_(\w)/g, (_, c) => c.toUpperCase());
+
+// Fetches and decodes an account of the program.
+async function fetch(address) {{
+  const info = await provider.connection.getAccountInfo(new PublicKey(address));
+  if (!info) throw new Error(`Account ${{address}} not found`);
+  const account = idl.accounts?.find((acc) =>
+    info.data.subarray(0, acc.discriminator.length).equals(Buffer.from(acc.discriminator))
+  );
+  if (!account) throw new Error(`Account ${{address}} is not an account of the program`);
+  return {{ type: account.name, data: program.coder.accounts.decode(account.name, info.data) }};
+}}
+
+// Derives a PDA of the program from strings, public keys, numbers (as u64)
+// and byte arrays.
+function pda(...seeds) {{
+  const buffers = seeds.map((seed) => {{
+    if (typeof seed === "string") return Buffer.from(seed);
+    if (seed instanceof PublicKey) return seed.toBuffer();
+    if (typeof seed === "number" || BN.isBN(seed)) {{
+      return new BN(seed).toArrayLike(Buffer, "le", 8);
+    }}
+    return Buffer.from(seed);
+  }});
+  return PublicKey.findProgramAddressSync(buffers, program.programId)[0];
+}}
+
+function parseArg(type, value) {{
+  if (["u64", "i64", "u128", "i128", "u256", "i256"].includes(type)) return new BN(value);
+  if (typeof type === "string" && /^[ui](8|16|32)$|^f(32|64)$/.test(type)) return Number(value);
+  if (type === "bool") return value === "true";
+  if (type === "pubkey") return new PublicKey(value);
+  if (type === "string") return value;
+  return JSON.parse(value);
+}}
+
+async function askAccounts(accounts, prefix = "") {{
+  const resolved = {{}};
+  for (const acc of accounts) {{
+    if (acc.accounts) {{
+      resolved[camel(acc.name)] = await askAccounts(acc.accounts, `${{prefix}}${{acc.name}}.`);
+      continue;
+    }}
+    const value = await ask(`${{prefix}}${{acc.name}} (empty to resolve): `);
+    if (value) resolved[camel(acc.name)] = new PublicKey(value);
+  }}
+  return resolved;
+}}
+
+// Prompts the arguments and the accounts of an instruction.
+async function method(name) {{
+  const ix = idl.instructions.find((ix) => ix.name === name || camel(ix.name) === name);
+  if (!ix) throw new Error(`Instruction ${{name}} not found`);
+  const args = [];
+  for (const arg of ix.args) {{
+    const type = typeof arg.type === "string" ? arg.type : JSON.stringify(arg.type);
+    args.push(parseArg(arg.type, await ask(`${{arg.name}} (${{type}}): `)));
+  }}
+  const accounts = await askAccounts(ix.accounts);
+  return program.methods[camel(ix.name)](...args).accountsPartial(accounts);
+}}
+
+// Builds an instruction with prompted arguments.
+const build = async (name) => (await method(name)).instruction();
+
+// Simulates an instruction with prompted arguments.
+const simulate = async (name) => (await method(name)).simulate();
+
+Object.assign(repl.context, {{
+  anchor, web3, PublicKey, Keypair, BN, provider, idl, program, fetch, pda, build, simulate,
+}});
+"#,
+        idl = serde_json::to_string(idl)?,
+        name = idl.metadata.name,
+    )?;
+
+    Ok(eval_string)
+}
+
 /// Test initialization template
 #[derive(Clone, Debug, Default, Eq, PartialEq, Parser, ValueEnum)]
 pub enum TestTemplate {
@@ -773,3 +1184,344 @@ fn test_initialize() {{
         ),
     ]
 }
+
+/// Create a standalone Rust client crate of the program with the given IDL.
+///
+/// The crate declares the program with `declare_program!` from a copy of the
+/// IDL, and adds the errors and PDA helpers of the program on top of it.
+pub fn create_client_crate(idl: &Idl, out: &Path) -> Result<()> {
+    let name = idl.metadata.name.to_snake_case();
+    let files = vec![
+        (out.join("Cargo.toml"), client_cargo_toml(idl)),
+        (
+            out.join("idls").join(&name).with_extension("json"),
+            serde_json::to_string_pretty(idl)?,
+        ),
+        (out.join("src").join("lib.rs"), client_lib_rs(idl)),
+    ];
+    override_or_create_files(&files)
+}
+
+fn client_cargo_toml(idl: &Idl) -> String {
+    format!(
+        r#"[package]
+name = "{0}-client"
+version = "{1}"
+description = "Client of the {2} program generated by Anchor"
+edition = "2021"
+
+[features]
+default = []
+client = ["anchor-client"]
+
+[dependencies]
+anchor-lang = "{3}"
+anchor-client = {{ version = "{3}", optional = true }}
+"#,
+        idl.metadata.name.to_kebab_case(),
+        idl.metadata.version,
+        idl.metadata.name,
+        VERSION,
+    )
+}
+
+fn client_lib_rs(idl: &Idl) -> String {
+    let name = idl.metadata.name.to_snake_case();
+    let mut lib = format!(
+        r#"//! Client of the `{0}` program, generated with `anchor client gen`.
+
+anchor_lang::declare_program!({1});
+
+pub use {1}::*;
+
+/// Returns the client of the program.
+#[cfg(feature = "client")]
+pub fn program<C: Clone + std::ops::Deref<Target = impl anchor_client::solana_sdk::signer::Signer>>(
+    client: &anchor_client::Client<C>,
+) -> Result<anchor_client::Program<C>, anchor_client::ClientError> {{
+    client.program(ID)
+}}
+"#,
+        idl.metadata.name, name,
+    );
+    lib.push_str(&client_errors_mod(idl));
+    lib.push_str(&client_pda_mod(idl));
+    lib
+}
+
+fn client_errors_mod(idl: &Idl) -> String {
+    if idl.errors.is_empty() {
+        return String::new();
+    }
+
+    let mut variants = String::new();
+    let mut msgs = String::new();
+    let mut codes = String::new();
+    for err in &idl.errors {
+        let variant = err.name.to_pascal_case();
+        let msg = err.msg.as_deref().unwrap_or(&err.name);
+        writeln!(variants, "        {variant} = {},", err.code).unwrap();
+        writeln!(msgs, "                Self::{variant} => {msg:?},").unwrap();
+        writeln!(
+            codes,
+            "                {} => Some(Self::{variant}),",
+            err.code
+        )
+        .unwrap();
+    }
+
+    format!(
+        r#"
+/// Errors of the program.
+pub mod errors {{
+    /// Custom error codes of the program.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(u32)]
+    pub enum ErrorCode {{
+{variants}    }}
+
+    impl ErrorCode {{
+        /// Returns the error of the given custom error code.
+        pub fn from_code(code: u32) -> Option<Self> {{
+            match code {{
+{codes}                _ => None,
+            }}
+        }}
+
+        /// Returns the message of the error.
+        pub fn msg(self) -> &'static str {{
+            match self {{
+{msgs}            }}
+        }}
+    }}
+
+    impl std::fmt::Display for ErrorCode {{
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{
+            f.write_str(self.msg())
+        }}
+    }}
+}}
+"#
+    )
+}
+
+/// Generate a `find_<account>_address` function for every PDA of the
+/// instructions whose seeds are constants, accounts or primitive arguments.
+/// The other PDAs are listed in a comment with the reason they were skipped.
+fn client_pda_mod(idl: &Idl) -> String {
+    fn collect<'a>(
+        items: &'a [IdlInstructionAccountItem],
+        accounts: &mut Vec<&'a IdlInstructionAccount>,
+    ) {
+        for item in items {
+            match item {
+                IdlInstructionAccountItem::Single(acc) => accounts.push(acc),
+                IdlInstructionAccountItem::Composite(accs) => collect(&accs.accounts, accounts),
+            }
+        }
+    }
+
+    let mut names = HashSet::new();
+    let mut skipped = vec![];
+    let mut fns = String::new();
+    for ix in &idl.instructions {
+        let mut accounts = vec![];
+        collect(&ix.accounts, &mut accounts);
+
+        'accounts: for acc in accounts {
+            let Some(pda) = &acc.pda else {
+                continue;
+            };
+            let fn_name = format!("find_{}_address", acc.name.to_snake_case());
+            if names.contains(&fn_name) {
+                continue;
+            }
+            if pda.program.is_some() {
+                skipped.push((fn_name, "derived from another program".to_owned()));
+                continue;
+            }
+
+            let mut params = vec![];
+            let mut seeds = vec![];
+            for seed in &pda.seeds {
+                match seed {
+                    IdlSeed::Const(seed) => seeds.push(format!("&{:?}", seed.value)),
+                    IdlSeed::Account(seed) if !seed.path.contains('.') => {
+                        let param = seed.path.to_snake_case();
+                        seeds.push(format!("{param}.as_ref()"));
+                        params.push(format!("{param}: &Pubkey"));
+                    }
+                    IdlSeed::Arg(seed) if !seed.path.contains('.') => {
+                        let param = seed.path.to_snake_case();
+                        let ty = ix
+                            .args
+                            .iter()
+                            .find(|arg| arg.name == seed.path)
+                            .map(|arg| &arg.ty);
+                        let (ty, seed) = match ty {
+                            Some(IdlType::Pubkey) => ("&Pubkey", format!("{param}.as_ref()")),
+                            Some(IdlType::String) => ("&str", format!("{param}.as_bytes()")),
+                            Some(IdlType::Bytes) => ("&[u8]", param.clone()),
+                            Some(IdlType::U8) => ("u8", format!("&{param}.to_le_bytes()")),
+                            Some(IdlType::U16) => ("u16", format!("&{param}.to_le_bytes()")),
+                            Some(IdlType::U32) => ("u32", format!("&{param}.to_le_bytes()")),
+                            Some(IdlType::U64) => ("u64", format!("&{param}.to_le_bytes()")),
+                            Some(IdlType::I64) => ("i64", format!("&{param}.to_le_bytes()")),
+                            _ => {
+                                let reason =
+                                    format!("unsupported type of the `{}` seed", seed.path);
+                                skipped.push((fn_name, reason));
+                                continue 'accounts;
+                            }
+                        };
+                        seeds.push(seed);
+                        params.push(format!("{param}: {ty}"));
+                    }
+                    IdlSeed::Account(IdlSeedAccount { path, .. })
+                    | IdlSeed::Arg(IdlSeedArg { path, .. }) => {
+                        let reason = format!("the `{path}` seed is a field");
+                        skipped.push((fn_name, reason));
+                        continue 'accounts;
+                    }
+                }
+            }
+
+            // The same account or argument can be used more than once in the seeds
+            let params = params.into_iter().fold(vec![], |mut acc, param| {
+                if !acc.contains(&param) {
+                    acc.push(param);
+                }
+                acc
+            });
+            names.insert(fn_name.clone());
+            write!(
+                fns,
+                r#"
+    /// Returns the address and bump of the `{}` account.
+    pub fn {fn_name}({}) -> (Pubkey, u8) {{
+        Pubkey::find_program_address(&[{}], &crate::ID)
+    }}
+"#,
+                acc.name,
+                params.join(", "),
+                seeds.join(", "),
+            )
+            .unwrap();
+        }
+    }
+
+    let imports = if fns.is_empty() {
+        ""
+    } else {
+        "\n    use anchor_lang::prelude::Pubkey;"
+    };
+    let mut skipped_names = HashSet::new();
+    for (fn_name, reason) in skipped {
+        if !names.contains(&fn_name) && skipped_names.insert(fn_name.clone()) {
+            writeln!(fns, "\n    // Skipped `{fn_name}`: {reason}").unwrap();
+        }
+    }
+
+    if fns.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        r#"
+/// Program derived addresses of the program.
+pub mod pda {{{imports}
+{fns}}}
+"#
+    )
+}
+
+/// Generate the module of a program that calls the instructions of the `dep`
+/// program, declared with `declare_program!`, through CPI.
+pub fn dependency_cpi_mod(idl: &Idl) -> String {
+    let dep = idl.metadata.name.to_snake_case();
+    let mut fns = String::new();
+    for ix in &idl.instructions {
+        let mut params = vec![
+            format!("program: &Program<'info, {}>", dep.to_pascal_case()),
+            format!(
+                "accounts: {dep}::cpi::accounts::{}<'info>",
+                ix.name.to_pascal_case()
+            ),
+        ];
+        params.extend(
+            ix.args
+                .iter()
+                .map(|arg| format!("{}: {}", arg.name, dependency_type(&dep, &arg.ty))),
+        );
+        let args = ix.args.iter().map(|arg| format!(", {}", arg.name));
+        let ret = match &ix.returns {
+            Some(ty) => format!("{dep}::cpi::Return<{}>", dependency_type(&dep, ty)),
+            None => "()".into(),
+        };
+        write!(
+            fns,
+            r#"
+/// Calls the `{0}` instruction of `{dep}`.
+pub fn {0}<'info>(
+    {1},
+) -> Result<{ret}> {{
+    {dep}::cpi::{0}(
+        CpiContext::new(program.to_account_info(), accounts){2},
+    )
+}}
+"#,
+            ix.name,
+            params.join(",\n    "),
+            args.collect::<String>(),
+        )
+        .unwrap();
+    }
+
+    format!(
+        r#"//! CPI calls to the `{0}` program, generated with `anchor new --with-dep`.
+//!
+//! The `{0}` module is declared with `declare_program!({0})` from
+//! `idls/{0}.json`, keep it up to date with `anchor idl fetch --all`. Pass
+//! `CpiContext::new_with_signer` instead to sign with PDAs of this program.
+
+use anchor_lang::prelude::*;
+
+use crate::{0}::{{self, program::{1}}};
+{fns}"#,
+        dep,
+        dep.to_pascal_case(),
+    )
+}
+
+/// Returns the type generated by `declare_program!` for an IDL type.
+fn dependency_type(dep: &str, ty: &IdlType) -> String {
+    match ty {
+        IdlType::Bool => "bool".into(),
+        IdlType::U8 => "u8".into(),
+        IdlType::I8 => "i8".into(),
+        IdlType::U16 => "u16".into(),
+        IdlType::I16 => "i16".into(),
+        IdlType::U32 => "u32".into(),
+        IdlType::I32 => "i32".into(),
+        IdlType::F32 => "f32".into(),
+        IdlType::U64 => "u64".into(),
+        IdlType::I64 => "i64".into(),
+        IdlType::F64 => "f64".into(),
+        IdlType::U128 => "u128".into(),
+        IdlType::I128 => "i128".into(),
+        IdlType::Bytes => "Vec<u8>".into(),
+        IdlType::String => "String".into(),
+        IdlType::Pubkey => "Pubkey".into(),
+        IdlType::Option(ty) => format!("Option<{}>", dependency_type(dep, ty)),
+        IdlType::Vec(ty) => format!("Vec<{}>", dependency_type(dep, ty)),
+        IdlType::Array(ty, len) => {
+            let len = match len {
+                IdlArrayLen::Generic(len) => len.clone(),
+                IdlArrayLen::Value(len) => len.to_string(),
+            };
+            format!("[{}; {len}]", dependency_type(dep, ty))
+        }
+        IdlType::Defined { name, .. } => format!("{dep}::types::{name}"),
+        _ => format!("{ty:?}"),
+    }
+}
@@ -0,0 +1,226 @@
+use std::{collections::BTreeMap, fmt};
+
+/// Number of the largest functions listed in a report.
+const LARGEST_FUNCTIONS: usize = 10;
+
+/// Functions reached when a program panics.
+const PANIC_PATHS: &[&str] = &[
+    "core::panicking::",
+    "core::result::unwrap_failed",
+    "core::option::expect_failed",
+    "core::option::unwrap_failed",
+    "core::slice::index::",
+    "alloc::raw_vec::capacity_overflow",
+    "alloc::alloc::handle_alloc_error",
+];
+
+/// Size of the sections of a program binary, by crate and by function, read
+/// from the map file written by the linker.
+#[derive(Debug, Default)]
+pub struct SizeReport {
+    /// Size of the code and the data of each crate.
+    pub crates: BTreeMap<String, u64>,
+    /// Size of each function, demangled.
+    pub functions: BTreeMap<String, u64>,
+    /// Size of the sections that can't be attributed to a crate.
+    pub other: u64,
+}
+
+impl SizeReport {
+    /// Parses a map file written by `lld` with `-Map`.
+    pub fn from_map(map: &str) -> Self {
+        let mut report = Self::default();
+        for line in map.lines() {
+            // VMA, LMA, size and alignment are followed by the object and the
+            // section of input sections, e.g. `foo.o:(.text._ZN3foo3bar17h..E)`
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            let (size, input) = match columns.as_slice() {
+                [_, _, size, _, input] => (size, input),
+                _ => continue,
+            };
+            let input = input
+                .strip_suffix(')')
+                .and_then(|input| input.split_once(":("));
+            let (object, section) = match input {
+                Some(input) => input,
+                None => continue,
+            };
+            let size = match u64::from_str_radix(size, 16) {
+                Ok(size) if size > 0 => size,
+                _ => continue,
+            };
+
+            let symbol = [".text.", ".rodata.", ".data.rel.ro.", ".data.", ".bss."]
+                .iter()
+                .find_map(|prefix| section.strip_prefix(prefix))
+                .filter(|symbol| symbol.starts_with("_ZN"));
+            match symbol {
+                Some(symbol) => {
+                    // The alternate format leaves the hash out
+                    let name = format!("{:#}", rustc_demangle::demangle(symbol));
+                    *report.crates.entry(path_crate(&name)).or_default() += size;
+                    if section.starts_with(".text.") {
+                        *report.functions.entry(name).or_default() += size;
+                    }
+                }
+                None => match object_crate(object) {
+                    Some(krate) => *report.crates.entry(krate).or_default() += size,
+                    None => report.other += size,
+                },
+            }
+        }
+        report
+    }
+
+    /// Total size of the sections.
+    pub fn total(&self) -> u64 {
+        self.crates.values().sum::<u64>() + self.other
+    }
+
+    /// Returns the size optimizations that apply to the program.
+    pub fn hints(&self, release_profile: Option<&toml::Value>) -> Vec<String> {
+        let mut hints = vec![];
+
+        let formatting = self.functions_size(|name| {
+            name.starts_with("core::fmt::")
+                || name.starts_with("alloc::fmt::")
+                || (name.contains(" as core::fmt::") && name.ends_with(">::fmt"))
+        });
+        if formatting > 0 {
+            hints.push(format!(
+                "Formatting takes {}: `format!`, `msg!` with arguments, `{{:?}}` and \
+                `#[derive(Debug)]` pull in `core::fmt`, log constant strings instead",
+                kb(formatting)
+            ));
+        }
+
+        let panics = self
+            .functions
+            .iter()
+            .filter(|(name, _)| PANIC_PATHS.iter().any(|path| name.starts_with(path)))
+            .collect::<Vec<_>>();
+        if !panics.is_empty() {
+            hints.push(format!(
+                "{} panicking paths take {}: `unwrap`, `expect`, indexing and slicing panic, \
+                return errors with `ok_or` and `get` instead",
+                panics.len(),
+                kb(panics.iter().map(|(_, size)| **size).sum())
+            ));
+        }
+
+        let profile = |key: &str| release_profile.and_then(|profile| profile.get(key));
+        match profile("opt-level").and_then(|level| level.as_str()) {
+            Some("s" | "z") => {}
+            _ => hints.push(
+                "Set `opt-level = \"s\"` or `\"z\"` in `[profile.release]` to optimize for size, \
+                at the cost of compute units"
+                    .to_owned(),
+            ),
+        }
+        match profile("lto") {
+            Some(toml::Value::String(lto)) if lto == "fat" => {}
+            Some(toml::Value::Boolean(true)) => {}
+            _ => hints.push("Set `lto = \"fat\"` in `[profile.release]`".to_owned()),
+        }
+        if profile("codegen-units").and_then(|units| units.as_integer()) != Some(1) {
+            hints.push("Set `codegen-units = 1` in `[profile.release]`".to_owned());
+        }
+
+        hints
+    }
+
+    fn functions_size(&self, f: impl Fn(&str) -> bool) -> u64 {
+        self.functions
+            .iter()
+            .filter(|(name, _)| f(name))
+            .map(|(_, size)| size)
+            .sum()
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().max(1);
+        let mut crates = self.crates.iter().collect::<Vec<_>>();
+        crates.sort_by(|a, b| b.1.cmp(a.1));
+        writeln!(f, "{:<32}{:>12}{:>8}", "Crate", "Size", "Share")?;
+        for (krate, size) in crates {
+            writeln!(
+                f,
+                "{krate:<32}{:>12}{:>7.1}%",
+                kb(*size),
+                *size as f64 * 100.0 / total as f64
+            )?;
+        }
+        if self.other > 0 {
+            writeln!(
+                f,
+                "{:<32}{:>12}{:>7.1}%",
+                "(other)",
+                kb(self.other),
+                self.other as f64 * 100.0 / total as f64
+            )?;
+        }
+
+        let mut functions = self.functions.iter().collect::<Vec<_>>();
+        functions.sort_by(|a, b| b.1.cmp(a.1));
+        writeln!(f, "\nLargest functions:")?;
+        for (name, size) in functions.into_iter().take(LARGEST_FUNCTIONS) {
+            writeln!(f, "{:>12}  {name}", kb(*size))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a size in kilobytes.
+pub fn kb(bytes: u64) -> String {
+    format!("{:.1} KB", bytes as f64 / 1024.0)
+}
+
+/// Returns the crate of a demangled path, e.g. `alloc` for
+/// `<alloc::string::String as core::fmt::Display>::fmt`.
+fn path_crate(name: &str) -> String {
+    let name = name.trim_start_matches(['<', '&', '*']);
+    let name = name.strip_prefix("mut ").unwrap_or(name);
+    name.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// Returns the crate of an object file, e.g. `core` for
+/// `libcore-a1b2.rlib(core-a1b2.core.c3d4-cgu.0.rcgu.o)` or `foo` for
+/// `foo-a1b2.foo.c3d4-cgu.0.rcgu.o`.
+fn object_crate(object: &str) -> Option<String> {
+    let file = object.rsplit('/').next()?;
+    let file = file.strip_prefix("lib").unwrap_or(file);
+    let krate = file.split(['-', '.', '(']).next()?;
+    (!krate.is_empty()).then(|| krate.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_report() {
+        let map = "
+             VMA              LMA     Size Align Out     In      Symbol
+             120              120     1200    16 .text
+             120              120      800     8         /t/deps/vault-a1b2.vault.c3d4-cgu.0.rcgu.o:(.text._ZN5vault7deposit17h0123456789abcdefE)
+             120              120      800     1                 _ZN5vault7deposit17h0123456789abcdefE
+             920              920      200     8         /t/deps/libcore-a1b2.rlib(core-a1b2.core.c3d4-cgu.0.rcgu.o):(.text._ZN4core9panicking5panic17h0123456789abcdefE)
+             b20              b20      800     8         /t/deps/libcore-a1b2.rlib(core-a1b2.core.c3d4-cgu.0.rcgu.o):(.text._ZN4core3fmt5write17h0123456789abcdefE)
+            1320             1320       10     1         /t/deps/vault-a1b2.vault.c3d4-cgu.0.rcgu.o:(.rodata..L__unnamed_1)
+        ";
+        let report = SizeReport::from_map(map);
+        assert_eq!(report.functions["vault::deposit"], 0x800);
+        assert_eq!(report.crates["vault"], 0x810);
+        assert_eq!(report.crates["core"], 0xa00);
+        assert_eq!(report.total(), 0x1210);
+
+        let hints = report.hints(None);
+        assert!(hints[0].starts_with("Formatting takes 2.0 KB"));
+        assert!(hints[1].starts_with("1 panicking paths take 0.5 KB"));
+    }
+}
@@ -0,0 +1,164 @@
+use std::{fs, path::Path, path::PathBuf, str::FromStr, thread, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+    system_instruction, system_program,
+    transaction::Transaction,
+};
+
+use crate::config::TestWallets;
+
+/// Environment variable of the tests with the paths of the keypairs of the
+/// test wallets, separated by commas.
+pub const WALLETS_ENV: &str = "ANCHOR_TEST_WALLETS";
+
+/// Directory of the keypairs of the test wallets, relative to the workspace.
+const WALLETS_DIR: &str = ".anchor/wallets";
+
+/// Balance of the test wallets unless configured.
+const DEFAULT_LAMPORTS: u64 = 10 * LAMPORTS_PER_SOL;
+
+/// Number of airdrops requested before funding a wallet from the payer.
+const AIRDROP_ATTEMPTS: u32 = 5;
+
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xAF8TMGNQnXJDGUoQ");
+
+/// Funds the test wallets and creates their associated token accounts,
+/// returning the paths of their keypairs.
+///
+/// Keypairs are kept in the workspace so that wallets funded by a previous run
+/// are only topped up. Wallets are airdropped, or funded by `payer` when the
+/// faucet keeps refusing airdrops, e.g. because of its rate limits.
+pub fn fund(
+    client: &RpcClient,
+    payer: &Keypair,
+    wallets: &TestWallets,
+    workspace: &Path,
+) -> Result<Vec<PathBuf>> {
+    let dir = workspace.join(WALLETS_DIR);
+    fs::create_dir_all(&dir)?;
+
+    // Token accounts are owned by the token program of their mint
+    let mut mints = vec![];
+    for mint in &wallets.mints {
+        let mint = Pubkey::from_str(mint).map_err(|_| anyhow!("Invalid mint: {mint}"))?;
+        let token_program = client
+            .get_account(&mint)
+            .with_context(|| format!("Mint {mint} of the test wallets not found"))?
+            .owner;
+        mints.push((mint, token_program));
+    }
+
+    let lamports = wallets.lamports.unwrap_or(DEFAULT_LAMPORTS);
+    let mut paths = vec![];
+    for i in 0..wallets.count {
+        let path = dir.join(format!("wallet-{i}.json"));
+        let wallet = match path.exists() {
+            true => read_keypair_file(&path)
+                .map_err(|e| anyhow!("Unable to read keypair {}: {e}", path.display()))?,
+            false => {
+                let wallet = Keypair::new();
+                write_keypair_file(&wallet, &path)
+                    .map_err(|e| anyhow!("Unable to write keypair {}: {e}", path.display()))?;
+                wallet
+            }
+        };
+
+        let balance = client.get_balance(&wallet.pubkey())?;
+        if balance < lamports {
+            airdrop_or_transfer(client, payer, &wallet.pubkey(), lamports - balance)?;
+        }
+        if !mints.is_empty() {
+            let ixs = mints
+                .iter()
+                .map(|(mint, token_program)| {
+                    create_associated_token_account(
+                        &payer.pubkey(),
+                        &wallet.pubkey(),
+                        mint,
+                        token_program,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let tx = Transaction::new_signed_with_payer(
+                &ixs,
+                Some(&payer.pubkey()),
+                &[payer],
+                client.get_latest_blockhash()?,
+            );
+            client.send_and_confirm_transaction(&tx)?;
+        }
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+// Requests airdrops with an increasing delay between them, then transfers the
+// lamports from the payer if none succeeded.
+fn airdrop_or_transfer(
+    client: &RpcClient,
+    payer: &Keypair,
+    to: &Pubkey,
+    lamports: u64,
+) -> Result<()> {
+    let mut delay = Duration::from_millis(500);
+    for attempt in 1..=AIRDROP_ATTEMPTS {
+        let airdrop = client
+            .request_airdrop(to, lamports)
+            .and_then(|signature| client.poll_for_signature(&signature));
+        match airdrop {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < AIRDROP_ATTEMPTS => {
+                eprintln!("Airdrop to {to} failed, retrying in {delay:?}: {e}");
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            Err(e) => eprintln!("Airdrop to {to} failed: {e}"),
+        }
+    }
+
+    println!("Funding {to} from {}", payer.pubkey());
+    let tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&payer.pubkey(), to, lamports)],
+        Some(&payer.pubkey()),
+        &[payer],
+        client.get_latest_blockhash()?,
+    );
+    client
+        .send_and_confirm_transaction(&tx)
+        .with_context(|| format!("Unable to fund test wallet {to}"))?;
+    Ok(())
+}
+
+// Returns the `CreateIdempotent` instruction of the associated token program,
+// which leaves existing accounts as they are.
+fn create_associated_token_account(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let (address, _) = Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    );
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![1],
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use solana_sdk::hash::Hash;
+
+use crate::ClientError;
+
+/// Provider of the blockhashes requests are signed with, set with
+/// [`RequestBuilder::blockhash_provider`] to replace the latest blockhash of
+/// the cluster.
+///
+/// [`RequestBuilder::blockhash_provider`]: crate::RequestBuilder::blockhash_provider
+#[async_trait]
+pub trait BlockhashProvider: Send + Sync {
+    /// Returns the blockhash to sign a request with, and the last block height
+    /// at which the cluster accepts transactions signed with it.
+    async fn latest_blockhash(&self) -> Result<(Hash, u64), ClientError>;
+}
+
+/// [`BlockhashProvider`] returning a fixed blockhash, so that the transactions
+/// of unit tests are deterministic and can be asserted byte-for-byte.
+///
+/// The blockhash can be changed with [`set`], e.g. to test how a request is
+/// signed again after its blockhash expired.
+///
+/// ```ignore
+/// let blockhash = Arc::new(FixedBlockhash::new(Hash::new_unique(), u64::MAX));
+/// let tx = program
+///     .request()
+///     .blockhash_provider(blockhash.clone())
+///     .args(instruction::Deposit { amount: 10 })
+///     .signed_transaction()?;
+/// assert_eq!(tx.message.recent_blockhash, blockhash.get().0);
+/// ```
+///
+/// [`set`]: Self::set
+#[derive(Debug)]
+pub struct FixedBlockhash(Mutex<(Hash, u64)>);
+
+impl FixedBlockhash {
+    pub fn new(blockhash: Hash, last_valid_block_height: u64) -> Self {
+        Self(Mutex::new((blockhash, last_valid_block_height)))
+    }
+
+    /// Returns the blockhash and its last valid block height.
+    pub fn get(&self) -> (Hash, u64) {
+        *self.0.lock().unwrap()
+    }
+
+    /// Replaces the blockhash returned to the requests signed afterwards.
+    pub fn set(&self, blockhash: Hash, last_valid_block_height: u64) {
+        *self.0.lock().unwrap() = (blockhash, last_valid_block_height);
+    }
+}
+
+#[async_trait]
+impl BlockhashProvider for FixedBlockhash {
+    async fn latest_blockhash(&self) -> Result<(Hash, u64), ClientError> {
+        Ok(self.get())
+    }
+}
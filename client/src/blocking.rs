@@ -1,8 +1,12 @@
+#[cfg(feature = "metrics-prometheus")]
+use crate::PrometheusMetrics;
 use crate::{
-    ClientError, Config, EventContext, EventUnsubscriber, Program, ProgramAccountsIterator,
-    RequestBuilder,
+    health::{watch_lag, LagWatchdog},
+    AccountSchema, ClientError, Config, EventContext, EventUnsubscriber, Program,
+    ProgramAccountsIterator, RequestBuilder, SubscriptionHealth,
 };
 use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use anchor_lang_idl::types::Idl;
 #[cfg(feature = "rpc-client")]
 use solana_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient};
 use solana_client::{rpc_config::RpcSendTransactionConfig, rpc_filter::RpcFilterType};
@@ -11,7 +15,13 @@ use solana_sdk::{
     transaction::Transaction,
 };
 
-use std::{marker::PhantomData, ops::Deref, sync::Arc};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tokio::{
     runtime::{Builder, Handle},
     sync::RwLock,
@@ -33,6 +43,7 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             program_id,
             cfg,
             sub_client: Arc::new(RwLock::new(None)),
+            subscriptions: Mutex::new(Vec::new()),
             rt,
         });
 
@@ -44,9 +55,12 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
                 program_id,
                 cfg,
                 sub_client: Arc::new(RwLock::new(None)),
+                subscriptions: Mutex::new(Vec::new()),
                 rt,
                 rpc_client: RpcClient::new_with_commitment(cluster_url.clone(), comm_config),
                 async_rpc_client: AsyncRpcClient::new_with_commitment(cluster_url, comm_config),
+                #[cfg(feature = "metrics-prometheus")]
+                metrics: None,
             })
         }
     }
@@ -64,9 +78,12 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             program_id,
             cfg,
             sub_client: Arc::new(RwLock::new(None)),
+            subscriptions: Mutex::new(Vec::new()),
             rt,
             rpc_client,
             async_rpc_client,
+            #[cfg(feature = "metrics-prometheus")]
+            metrics: None,
         })
     }
 
@@ -92,15 +109,69 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         self.rt.block_on(self.accounts_lazy_internal(filters))
     }
 
+    /// Returns the IDL of the deployed program, fetched from its IDL account.
+    pub fn idl(&self) -> Result<Idl, ClientError> {
+        self.rt.block_on(self.idl_internal())
+    }
+
+    /// Checks that the account types of the client, e.g. `(Vault, Config)`,
+    /// are accounts of the IDL of the deployed program, returning a
+    /// [`ClientError::SchemaMismatch`] listing the differences otherwise. With
+    /// the `idl-build` feature, their layouts are checked against the type
+    /// definitions of the IDL too.
+    ///
+    /// Services can call it on startup to fail loudly instead of misdecoding
+    /// accounts after an upgrade of the program.
+    pub fn verify_schema<T: AccountSchema>(&self) -> Result<(), ClientError> {
+        self.rt.block_on(self.verify_schema_internal::<T>())
+    }
+
+    /// Checks that the IDL of the deployed program has no breaking changes
+    /// from the IDL the client was built with, returning a
+    /// [`ClientError::SchemaMismatch`] listing them otherwise.
+    pub fn verify_idl(&self, idl: &Idl) -> Result<(), ClientError> {
+        self.rt.block_on(self.verify_idl_internal(idl))
+    }
+
     pub fn on<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
         &self,
         f: impl Fn(&EventContext, T) + Send + 'static,
     ) -> Result<EventUnsubscriber, ClientError> {
-        let (handle, rx) = self.rt.block_on(self.on_internal(f))?;
+        let (handle, rx, health) = self.rt.block_on(self.on_internal(f))?;
 
         Ok(EventUnsubscriber {
             handle,
             rx,
+            health,
+            watchdog: None,
+            runtime_handle: self.rt.handle(),
+            _lifetime_marker: PhantomData,
+        })
+    }
+
+    /// Subscribe to program logs like [`on`](Self::on), calling `on_lag` with
+    /// the [`SubscriptionHealth`] when no message is received for more than
+    /// `max_lag`, so that stalled subscriptions can be alerted on.
+    ///
+    /// `on_lag` is called once per stall, and once when the subscription is
+    /// closed by the cluster.
+    pub fn on_with_lag_alert<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
+        &self,
+        f: impl Fn(&EventContext, T) + Send + 'static,
+        max_lag: Duration,
+        on_lag: impl Fn(&SubscriptionHealth) + Send + 'static,
+    ) -> Result<EventUnsubscriber, ClientError> {
+        let (handle, rx, health) = self.rt.block_on(self.on_internal(f))?;
+        let watchdog = LagWatchdog(
+            self.rt
+                .spawn(watch_lag(Arc::clone(&health), max_lag, on_lag)),
+        );
+
+        Ok(EventUnsubscriber {
+            handle,
+            rx,
+            health,
+            watchdog: Some(watchdog),
             runtime_handle: self.rt.handle(),
             _lifetime_marker: PhantomData,
         })
@@ -125,6 +196,11 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
             instructions: Vec::new(),
             instruction_data: None,
             signers: Vec::new(),
+            blockhash: Mutex::new(None),
+            blockhash_provider: None,
+            priority_fee: None,
+            prefetches: Vec::new(),
+            prefetched: Mutex::new(HashMap::new()),
             handle,
             _phantom: PhantomData,
         }
@@ -138,6 +214,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
         options: Option<CommitmentConfig>,
         handle: &'a Handle,
         async_rpc_client: &'a AsyncRpcClient,
+        #[cfg(feature = "metrics-prometheus")] metrics: Option<&'a PrometheusMetrics>,
     ) -> Self {
         Self {
             program_id,
@@ -148,9 +225,16 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
             instructions: Vec::new(),
             instruction_data: None,
             signers: Vec::new(),
+            blockhash: Mutex::new(None),
+            blockhash_provider: None,
+            priority_fee: None,
+            prefetches: Vec::new(),
+            prefetched: Mutex::new(HashMap::new()),
             handle,
             _phantom: PhantomData,
             async_rpc_client,
+            #[cfg(feature = "metrics-prometheus")]
+            metrics,
         }
     }
 
@@ -162,6 +246,22 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
         self.handle.block_on(self.send_internal())
     }
 
+    /// Returns whether the blockhash with which the request was last signed
+    /// expired, i.e. whether the cluster would refuse a transaction signed with
+    /// it. `false` until the request is signed.
+    ///
+    /// Sending the request signs it again with a new blockhash if it expires
+    /// before the transaction lands.
+    pub fn is_expired(&self) -> Result<bool, ClientError> {
+        self.handle.block_on(self.is_expired_internal())
+    }
+
+    /// Fetches the accounts registered with `prefetch` in batch, failing if
+    /// one of them isn't an account of its prefetched type.
+    pub fn fetch_prefetched(&self) -> Result<(), ClientError> {
+        self.handle.block_on(self.fetch_prefetched_internal())
+    }
+
     pub fn send_with_spinner_and_config(
         &self,
         config: RpcSendTransactionConfig,
@@ -0,0 +1,36 @@
+use serde_json::json;
+use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+
+use crate::ClientError;
+
+/// Environment variable set by `anchor test` with the URL of the admin RPC of
+/// the test validator.
+const ADMIN_URL_ENV: &str = "ANCHOR_ADMIN_URL";
+
+/// URL of the admin RPC of `anchor localnet` with the default RPC port.
+const DEFAULT_ADMIN_URL: &str = "http://127.0.0.1:8901";
+
+/// Warps the clock of the test validator started by `anchor test` or
+/// `anchor localnet` to `slot`, returning the slot it warped to.
+///
+/// The validator is restarted from its ledger at the new slot, so
+/// subscriptions and the pending transactions are dropped. It can only move
+/// forward.
+pub fn warp_to_slot(slot: u64) -> Result<u64, ClientError> {
+    warp("warpToSlot", json!([slot]))
+}
+
+/// Warps the clock of the test validator to the slot at which it reaches the
+/// `unix_timestamp`, estimated with the default slot duration, returning the
+/// slot it warped to.
+///
+/// See [`warp_to_slot`].
+pub fn warp_to_timestamp(unix_timestamp: i64) -> Result<u64, ClientError> {
+    warp("warpToTimestamp", json!([unix_timestamp]))
+}
+
+fn warp(method: &'static str, params: serde_json::Value) -> Result<u64, ClientError> {
+    let url = std::env::var(ADMIN_URL_ENV).unwrap_or_else(|_| DEFAULT_ADMIN_URL.to_owned());
+    let slot = RpcClient::new(url).send(RpcRequest::Custom { method }, params)?;
+    Ok(slot)
+}
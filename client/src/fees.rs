@@ -0,0 +1,47 @@
+use solana_sdk::instruction::Instruction;
+
+/// Strategy setting the priority fee of requests, set with
+/// [`RequestBuilder::priority_fee`].
+///
+/// The compute unit price it returns is set by a `SetComputeUnitPrice`
+/// instruction added before the instructions of the request. It's given the
+/// number of times the request was already sent, so that the fee of a request
+/// signed again after its blockhash expired can be escalated.
+///
+/// Closures taking the instructions and the attempt are strategies:
+///
+/// ```ignore
+/// program
+///     .request()
+///     .priority_fee(Arc::new(|_: &[Instruction], attempt: usize| {
+///         Some(1_000 << attempt)
+///     }))
+///     .args(instruction::Deposit { amount: 10 })
+///     .send()?;
+/// ```
+///
+/// [`RequestBuilder::priority_fee`]: crate::RequestBuilder::priority_fee
+pub trait PriorityFeeStrategy: Send + Sync {
+    /// Returns the compute unit price, in micro-lamports, of the transaction
+    /// with the given instructions sent for the `attempt`th time, starting at
+    /// 0, or `None` for no priority fee.
+    fn compute_unit_price(&self, instructions: &[Instruction], attempt: usize) -> Option<u64>;
+}
+
+impl<F: Fn(&[Instruction], usize) -> Option<u64> + Send + Sync> PriorityFeeStrategy for F {
+    fn compute_unit_price(&self, instructions: &[Instruction], attempt: usize) -> Option<u64> {
+        self(instructions, attempt)
+    }
+}
+
+/// [`PriorityFeeStrategy`] setting the same compute unit price, in
+/// micro-lamports, to every transaction, e.g. so that the transactions of unit
+/// tests are deterministic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPriorityFee(pub u64);
+
+impl PriorityFeeStrategy for FixedPriorityFee {
+    fn compute_unit_price(&self, _instructions: &[Instruction], _attempt: usize) -> Option<u64> {
+        Some(self.0)
+    }
+}
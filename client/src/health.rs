@@ -0,0 +1,135 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinHandle;
+
+/// Health of an event subscription, returned by [`EventUnsubscriber::health`]
+/// and [`Program::subscription_health`].
+///
+/// The time since the last message, or since the subscription when none was
+/// received, reveals silently stalled subscriptions, e.g. after the websocket
+/// stopped delivering logs without closing.
+///
+/// [`EventUnsubscriber::health`]: crate::EventUnsubscriber::health
+/// [`Program::subscription_health`]: crate::Program::subscription_health
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionHealth {
+    /// Slot of the last received message.
+    pub last_slot: Option<u64>,
+    /// Time at which the last message was received.
+    pub last_message_at: Option<Instant>,
+    /// Number of received messages.
+    pub messages: u64,
+    /// Time at which the subscription was created.
+    pub subscribed_at: Instant,
+    /// Whether the stream of messages ended, e.g. after the websocket closed.
+    pub closed: bool,
+}
+
+impl SubscriptionHealth {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_slot: None,
+            last_message_at: None,
+            messages: 0,
+            subscribed_at: Instant::now(),
+            closed: false,
+        }
+    }
+
+    /// Time since the last message, or since the subscription if no message
+    /// was received.
+    pub fn since_last_message(&self) -> Duration {
+        self.last_message_at.unwrap_or(self.subscribed_at).elapsed()
+    }
+
+    pub(crate) fn record(&mut self, slot: u64) {
+        self.last_slot = Some(slot);
+        self.last_message_at = Some(Instant::now());
+        self.messages += 1;
+    }
+}
+
+// Calls `on_lag` once each time the subscription goes without messages for
+// more than `max_lag`, and once when its stream ends.
+pub(crate) async fn watch_lag(
+    health: Arc<Mutex<SubscriptionHealth>>,
+    max_lag: Duration,
+    on_lag: impl Fn(&SubscriptionHealth) + Send + 'static,
+) {
+    let period = (max_lag / 4).max(Duration::from_millis(10));
+    let mut alerted = false;
+    loop {
+        tokio::time::sleep(period).await;
+        let health = health.lock().unwrap().clone();
+        if health.closed {
+            on_lag(&health);
+            return;
+        }
+        if health.since_last_message() <= max_lag {
+            alerted = false;
+        } else if !alerted {
+            on_lag(&health);
+            alerted = true;
+        }
+    }
+}
+
+/// Task running [`watch_lag`], aborted when dropped so that the lag callback
+/// isn't called after the subscription was unsubscribed or dropped.
+pub(crate) struct LagWatchdog(pub(crate) JoinHandle<()>);
+
+impl Drop for LagWatchdog {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_health() {
+        let mut health = SubscriptionHealth::new();
+        assert_eq!(health.last_slot, None);
+        assert!(health.since_last_message() <= health.subscribed_at.elapsed());
+
+        health.record(42);
+        health.record(43);
+        assert_eq!(health.last_slot, Some(43));
+        assert_eq!(health.messages, 2);
+        assert!(health.last_message_at.is_some());
+    }
+
+    #[test]
+    fn test_lag_watchdog_drop() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let health = Arc::new(Mutex::new(SubscriptionHealth::new()));
+        let alerts = Arc::new(Mutex::new(0));
+
+        rt.block_on(async {
+            let on_lag = {
+                let alerts = Arc::clone(&alerts);
+                move |_: &SubscriptionHealth| *alerts.lock().unwrap() += 1
+            };
+            let watchdog = LagWatchdog(tokio::spawn(watch_lag(
+                Arc::clone(&health),
+                Duration::from_millis(10),
+                on_lag,
+            )));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(*alerts.lock().unwrap(), 1);
+
+            drop(watchdog);
+            health.lock().unwrap().closed = true;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(*alerts.lock().unwrap(), 1);
+        });
+    }
+}
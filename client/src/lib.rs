@@ -59,6 +59,15 @@
 //! ```toml
 //! anchor-client = { version = "0.30.1 ", features = ["async"] }
 //! ````
+//!
+//! The `rpc-client` feature allows programs to use custom RPC clients, e.g. over a mock transport,
+//! and [`MockSysvars`] to override the sysvars seen by the clients of tests.
+//!
+//! Integration tests can start their own `solana-test-validator` with [`TestValidator`].
+//!
+//! The `metrics-prometheus` feature adds [`PrometheusMetrics`] to export the metrics of the RPC
+//! requests of the clients, of the outcomes of their requests and of their event subscriptions to
+//! Prometheus.
 
 use anchor_lang::solana_program::program_error::ProgramError;
 use anchor_lang::solana_program::pubkey::Pubkey;
@@ -66,6 +75,8 @@ use anchor_lang::{AccountDeserialize, Discriminator, InstructionData, ToAccountM
 use futures::{Future, StreamExt};
 use regex::Regex;
 use solana_account_decoder::UiAccountEncoding;
+#[cfg(feature = "rpc-client")]
+use solana_client::rpc_client::RpcClientConfig;
 use solana_client::rpc_config::{
     RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
     RpcTransactionLogsConfig, RpcTransactionLogsFilter,
@@ -80,17 +91,21 @@ use solana_client::{
     rpc_client::RpcClient,
     rpc_response::{Response as RpcResponse, RpcLogsResponse},
 };
+#[cfg(feature = "rpc-client")]
+use solana_rpc_client::http_sender::HttpSender;
 use solana_sdk::account::Account;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
 use solana_sdk::hash::Hash;
-use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::instruction::{AccountMeta, CompiledInstruction, Instruction};
 use solana_sdk::signature::{Signature, Signer};
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::collections::HashMap;
 use std::iter::Map;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, Weak};
 use std::vec::IntoIter;
 use thiserror::Error;
 use tokio::{
@@ -103,11 +118,32 @@ use tokio::{
 };
 
 pub use anchor_lang;
+pub use blockhash::{BlockhashProvider, FixedBlockhash};
+pub use clock::{warp_to_slot, warp_to_timestamp};
 pub use cluster::Cluster;
+pub use fees::{FixedPriorityFee, PriorityFeeStrategy};
+use health::LagWatchdog;
+pub use health::SubscriptionHealth;
+#[cfg(feature = "metrics-prometheus")]
+pub use metrics::{PrometheusMetrics, PrometheusSender};
+pub use schema::{AccountSchema, SchemaMismatch};
 pub use solana_client;
 pub use solana_sdk;
+#[cfg(feature = "rpc-client")]
+pub use sysvars::{MockSysvarSender, MockSysvars};
+pub use validator::{TestValidator, TestValidatorBuilder};
 
+mod blockhash;
+mod clock;
 mod cluster;
+mod fees;
+mod health;
+#[cfg(feature = "metrics-prometheus")]
+mod metrics;
+mod schema;
+#[cfg(feature = "rpc-client")]
+mod sysvars;
+mod validator;
 
 #[cfg(not(feature = "async"))]
 mod blocking;
@@ -117,6 +153,13 @@ mod nonblocking;
 const PROGRAM_LOG: &str = "Program log: ";
 const PROGRAM_DATA: &str = "Program data: ";
 
+/// Number of times a request is signed again with a new blockhash when sending
+/// it fails because its blockhash expired.
+const MAX_BLOCKHASH_REFRESHES: usize = 3;
+
+/// Maximum number of accounts of a `getMultipleAccounts` request.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
 type UnsubscribeFn = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
 /// Client defines the base configuration for building RPC clients to
 /// communicate with Anchor programs running on a Solana cluster. It's
@@ -171,6 +214,43 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
 
         Program::new_with_rpc(program_id, cfg, rpc_client, async_rpc_client)
     }
+
+    /// Creates a program whose RPC clients see the sysvars overridden by
+    /// `sysvars` instead of the ones of the cluster.
+    #[cfg(feature = "rpc-client")]
+    pub fn program_with_sysvars(
+        &self,
+        program_id: Pubkey,
+        sysvars: &MockSysvars,
+    ) -> Result<Program<C>, ClientError> {
+        let url = self.cfg.cluster.url();
+        let config = || RpcClientConfig::with_commitment(self.cfg.options.unwrap_or_default());
+        self.program_with_custom_rpc(
+            program_id,
+            RpcClient::new_sender(sysvars.sender(HttpSender::new(url)), config()),
+            AsyncRpcClient::new_sender(sysvars.sender(HttpSender::new(url)), config()),
+        )
+    }
+
+    /// Creates a program whose RPC clients record their requests in
+    /// `metrics`, along with the outcomes of its requests and the messages of
+    /// its event subscriptions.
+    #[cfg(feature = "metrics-prometheus")]
+    pub fn program_with_metrics(
+        &self,
+        program_id: Pubkey,
+        metrics: &PrometheusMetrics,
+    ) -> Result<Program<C>, ClientError> {
+        let url = self.cfg.cluster.url();
+        let config = || RpcClientConfig::with_commitment(self.cfg.options.unwrap_or_default());
+        let mut program = self.program_with_custom_rpc(
+            program_id,
+            RpcClient::new_sender(metrics.sender(HttpSender::new(url)), config()),
+            AsyncRpcClient::new_sender(metrics.sender(HttpSender::new(url)), config()),
+        )?;
+        program.metrics = Some(metrics.clone());
+        Ok(program)
+    }
 }
 
 /// Auxiliary data structure to align the types of the Solana CLI utils with Anchor client.
@@ -214,13 +294,23 @@ pub struct Config<C> {
 pub struct EventUnsubscriber<'a> {
     handle: JoinHandle<Result<(), ClientError>>,
     rx: UnboundedReceiver<UnsubscribeFn>,
+    health: Arc<Mutex<SubscriptionHealth>>,
+    // Task calling the lag callback, if any, aborted on drop.
+    watchdog: Option<LagWatchdog>,
     #[cfg(not(feature = "async"))]
     runtime_handle: &'a Handle,
     _lifetime_marker: PhantomData<&'a Handle>,
 }
 
 impl<'a> EventUnsubscriber<'a> {
+    /// Returns the health of the subscription, e.g. to export the time since
+    /// its last message to the monitoring of a service.
+    pub fn health(&self) -> SubscriptionHealth {
+        self.health.lock().unwrap().clone()
+    }
+
     async fn unsubscribe_internal(mut self) {
+        drop(self.watchdog.take());
         if let Some(unsubscribe) = self.rx.recv().await {
             unsubscribe().await;
         }
@@ -234,12 +324,17 @@ pub struct Program<C> {
     program_id: Pubkey,
     cfg: Config<C>,
     sub_client: Arc<RwLock<Option<PubsubClient>>>,
+    // Health of the event subscriptions, until they ended and their
+    // unsubscriber was dropped.
+    subscriptions: Mutex<Vec<Weak<Mutex<SubscriptionHealth>>>>,
     #[cfg(not(feature = "async"))]
     rt: tokio::runtime::Runtime,
     #[cfg(feature = "rpc-client")]
     rpc_client: RpcClient,
     #[cfg(feature = "rpc-client")]
     async_rpc_client: AsyncRpcClient,
+    #[cfg(feature = "metrics-prometheus")]
+    metrics: Option<PrometheusMetrics>,
 }
 
 impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
@@ -257,6 +352,8 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             self.rt.handle(),
             #[cfg(feature = "rpc-client")]
             &self.async_rpc_client,
+            #[cfg(feature = "metrics-prometheus")]
+            self.metrics.as_ref(),
         )
     }
 
@@ -270,6 +367,8 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             self.cfg.options,
             #[cfg(feature = "rpc-client")]
             &self.async_rpc_client,
+            #[cfg(feature = "metrics-prometheus")]
+            self.metrics.as_ref(),
         )
     }
 
@@ -277,6 +376,26 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         self.program_id
     }
 
+    /// Returns the health of the event subscriptions of the program, in the
+    /// order they were created. Subscriptions are forgotten once they ended
+    /// and their [`EventUnsubscriber`] was dropped, e.g. by unsubscribing.
+    pub fn subscription_health(&self) -> Vec<SubscriptionHealth> {
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|health| health.lock().unwrap().clone())
+            .collect()
+    }
+
+    // Tracks the health of a new subscription, forgetting the dropped ones.
+    fn track_subscription(&self, health: &Arc<Mutex<SubscriptionHealth>>) {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        subscriptions.retain(|health| health.strong_count() > 0);
+        subscriptions.push(Arc::downgrade(health));
+    }
+
     #[cfg(not(feature = "rpc-client"))]
     pub fn rpc(&self) -> RpcClient {
         RpcClient::new_with_commitment(
@@ -368,6 +487,7 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         (
             JoinHandle<Result<(), ClientError>>,
             UnboundedReceiver<UnsubscribeFn>,
+            Arc<Mutex<SubscriptionHealth>>,
         ),
         ClientError,
     > {
@@ -380,8 +500,24 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         let filter = RpcTransactionLogsFilter::Mentions(vec![program_id_str.clone()]);
 
         let lock = Arc::clone(&self.sub_client);
+        let health = Arc::new(Mutex::new(SubscriptionHealth::new()));
+        let task_health = Arc::clone(&health);
+        self.track_subscription(&health);
+        #[cfg(feature = "metrics-prometheus")]
+        let metrics = self.metrics.clone();
 
         let handle = tokio::spawn(async move {
+            // Closed when the task ends, whatever the reason
+            struct Closer(Arc<Mutex<SubscriptionHealth>>);
+            impl Drop for Closer {
+                fn drop(&mut self) {
+                    if let Ok(mut health) = self.0.lock() {
+                        health.closed = true;
+                    }
+                }
+            }
+            let closer = Closer(task_health);
+
             if let Some(ref client) = *lock.read().await {
                 let (mut notifications, unsubscribe) =
                     client.logs_subscribe(filter, config).await?;
@@ -394,6 +530,11 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
                 })?;
 
                 while let Some(logs) = notifications.next().await {
+                    closer.0.lock().unwrap().record(logs.context.slot);
+                    #[cfg(feature = "metrics-prometheus")]
+                    if let Some(metrics) = &metrics {
+                        metrics.record_event(&program_id_str);
+                    }
                     let ctx = EventContext {
                         signature: logs.value.signature.parse().unwrap(),
                         slot: logs.context.slot,
@@ -407,7 +548,7 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             Ok::<(), ClientError>(())
         });
 
-        Ok((handle, rx))
+        Ok((handle, rx, health))
     }
 }
 
@@ -490,6 +631,31 @@ pub fn handle_system_log(this_program_str: &str, log: &str) -> (Option<String>,
     }
 }
 
+/// Extracts the events of type `T` emitted through the noop program, e.g. with
+/// `anchor_spl::emit_compressed!`, from the inner instructions of a
+/// transaction.
+///
+/// `account_keys` are the account keys of the transaction message, which the
+/// compiled instructions' program id indexes refer to.
+pub fn parse_noop_events<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
+    noop_program_id: &Pubkey,
+    account_keys: &[Pubkey],
+    inner_instructions: &[CompiledInstruction],
+) -> Result<Vec<T>, ClientError> {
+    let mut events = Vec::new();
+    for ix in inner_instructions {
+        if account_keys.get(ix.program_id_index as usize) != Some(noop_program_id) {
+            continue;
+        }
+        if let Some(mut slice) = ix.data.strip_prefix(&T::DISCRIMINATOR[..]) {
+            let event: T = anchor_lang::AnchorDeserialize::deserialize(&mut slice)
+                .map_err(|e| ClientError::LogParseError(e.to_string()))?;
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
 pub struct Execution {
     stack: Vec<String>,
 }
@@ -550,6 +716,12 @@ pub enum ClientError {
     LogParseError(String),
     #[error(transparent)]
     IOError(#[from] std::io::Error),
+    #[error("Test validator error: {0}")]
+    TestValidatorError(String),
+    #[error("Invalid IDL: {0}")]
+    InvalidIdl(String),
+    #[error("{0}")]
+    SchemaMismatch(SchemaMismatch),
 }
 
 #[cfg(feature = "async")]
@@ -575,13 +747,31 @@ pub struct RequestBuilder<'a, C, S: 'a> {
     payer: C,
     instruction_data: Option<Vec<u8>>,
     signers: Vec<S>,
+    // Blockhash with which the request was last signed, with its last valid
+    // block height.
+    blockhash: Mutex<Option<(Hash, u64)>>,
+    blockhash_provider: Option<Arc<dyn BlockhashProvider>>,
+    priority_fee: Option<Arc<dyn PriorityFeeStrategy>>,
+    prefetches: Vec<Prefetch>,
+    // Prefetched accounts by name, the ones which don't exist being absent.
+    prefetched: Mutex<HashMap<String, Account>>,
     #[cfg(not(feature = "async"))]
     handle: &'a Handle,
     #[cfg(feature = "rpc-client")]
     async_rpc_client: &'a AsyncRpcClient,
+    #[cfg(feature = "metrics-prometheus")]
+    metrics: Option<&'a PrometheusMetrics>,
     _phantom: PhantomData<&'a ()>,
 }
 
+// Account of a request fetched by `RequestBuilder::fetch_prefetched`.
+struct Prefetch {
+    name: String,
+    address: Pubkey,
+    // Checks that the data of the account is an account of the prefetched type.
+    check: fn(&[u8]) -> Result<(), ClientError>,
+}
+
 // Shared implementation for all RequestBuilders
 impl<'a, C: Deref<Target = impl Signer> + Clone, S> RequestBuilder<'a, C, S> {
     #[must_use]
@@ -657,6 +847,22 @@ impl<'a, C: Deref<Target = impl Signer> + Clone, S> RequestBuilder<'a, C, S> {
         self
     }
 
+    /// Signs the request with the blockhashes of `provider` instead of the
+    /// latest blockhash of the cluster, e.g. a [`FixedBlockhash`] in tests.
+    #[must_use]
+    pub fn blockhash_provider(mut self, provider: Arc<dyn BlockhashProvider>) -> Self {
+        self.blockhash_provider = Some(provider);
+        self
+    }
+
+    /// Sets the priority fee of the request with `strategy`, which is asked
+    /// for the compute unit price each time the request is signed.
+    #[must_use]
+    pub fn priority_fee(mut self, strategy: Arc<dyn PriorityFeeStrategy>) -> Self {
+        self.priority_fee = Some(strategy);
+        self
+    }
+
     pub fn instructions(&self) -> Result<Vec<Instruction>, ClientError> {
         let mut instructions = self.instructions.clone();
         if let Some(ix_data) = &self.instruction_data {
@@ -670,11 +876,214 @@ impl<'a, C: Deref<Target = impl Signer> + Clone, S> RequestBuilder<'a, C, S> {
         Ok(instructions)
     }
 
+    // Returns the instructions of the transaction sent for the `attempt`th time,
+    // preceded by the compute unit price of the priority fee strategy, if any.
+    fn transaction_instructions(&self, attempt: usize) -> Result<Vec<Instruction>, ClientError> {
+        let mut instructions = self.instructions()?;
+        let price = self
+            .priority_fee
+            .as_ref()
+            .and_then(|strategy| strategy.compute_unit_price(&instructions, attempt));
+        if let Some(price) = price {
+            instructions.insert(0, ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        Ok(instructions)
+    }
+
     pub fn transaction(&self) -> Result<Transaction, ClientError> {
         let instructions = &self.instructions;
         let tx = Transaction::new_with_payer(instructions, Some(&self.payer.pubkey()));
         Ok(tx)
     }
+
+    /// Registers the account at `address` to be fetched as `name` by
+    /// `fetch_prefetched`, replacing the account registered with the same
+    /// name, if any.
+    ///
+    /// The accounts the instruction reads, e.g. mints or configs, are fetched
+    /// in batch before the instruction is built, so that the client can derive
+    /// its arguments and accounts from them, e.g. the decimals of a mint.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let request = program
+    ///     .request()
+    ///     .prefetch::<Mint>("mint", mint)
+    ///     .prefetch::<Config>("config", config);
+    /// request.fetch_prefetched()?;
+    ///
+    /// let decimals = request.prefetched::<Mint>("mint")?.decimals;
+    /// let fee_vault = request.prefetched::<Config>("config")?.fee_vault;
+    /// request
+    ///     .accounts(accounts::Deposit {
+    ///         mint,
+    ///         config,
+    ///         fee_vault,
+    ///     })
+    ///     .args(instruction::Deposit {
+    ///         amount: 10u64.pow(decimals.into()),
+    ///     })
+    ///     .send()?;
+    /// ```
+    #[must_use]
+    pub fn prefetch<T: AccountDeserialize>(mut self, name: &str, address: Pubkey) -> Self {
+        self.prefetches.retain(|prefetch| prefetch.name != name);
+        self.prefetches.push(Prefetch {
+            name: name.to_owned(),
+            address,
+            check: |data| {
+                T::try_deserialize(&mut &data[..])
+                    .map(|_| ())
+                    .map_err(Into::into)
+            },
+        });
+        self
+    }
+
+    /// Returns the account prefetched as `name`, failing with
+    /// [`ClientError::AccountNotFound`] if it wasn't fetched or doesn't exist.
+    pub fn prefetched<T: AccountDeserialize>(&self, name: &str) -> Result<T, ClientError> {
+        let prefetched = self.prefetched.lock().unwrap();
+        let account = prefetched.get(name).ok_or(ClientError::AccountNotFound)?;
+        T::try_deserialize(&mut &account.data[..]).map_err(Into::into)
+    }
+
+    async fn fetch_prefetched_internal(&self) -> Result<(), ClientError> {
+        #[cfg(not(feature = "rpc-client"))]
+        let rpc_client =
+            &AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
+        #[cfg(feature = "rpc-client")]
+        let rpc_client = self.async_rpc_client;
+
+        let mut prefetched = HashMap::new();
+        for prefetches in self.prefetches.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let addresses: Vec<Pubkey> = prefetches.iter().map(|p| p.address).collect();
+            let accounts = rpc_client
+                .get_multiple_accounts_with_commitment(&addresses, self.options)
+                .await?
+                .value;
+            for (prefetch, account) in prefetches.iter().zip(accounts) {
+                if let Some(account) = account {
+                    (prefetch.check)(&account.data)?;
+                    prefetched.insert(prefetch.name.clone(), account);
+                }
+            }
+        }
+        *self.prefetched.lock().unwrap() = prefetched;
+        Ok(())
+    }
+
+    /// Returns the blockhash with which the request was last signed, and the
+    /// last block height at which the cluster accepts transactions signed with
+    /// it.
+    pub fn blockhash(&self) -> Option<(Hash, u64)> {
+        *self.blockhash.lock().unwrap()
+    }
+
+    async fn is_expired_internal(&self) -> Result<bool, ClientError> {
+        #[cfg(not(feature = "rpc-client"))]
+        let rpc_client =
+            &AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
+        #[cfg(feature = "rpc-client")]
+        let rpc_client = self.async_rpc_client;
+
+        self.blockhash_expired(rpc_client).await
+    }
+
+    // Returns whether the block height of the cluster is past the last valid
+    // block height of the blockhash of the request.
+    async fn blockhash_expired(&self, rpc_client: &AsyncRpcClient) -> Result<bool, ClientError> {
+        match self.blockhash() {
+            Some((_, last_valid_block_height)) => Ok(rpc_client
+                .get_block_height_with_commitment(self.options)
+                .await?
+                > last_valid_block_height),
+            None => Ok(false),
+        }
+    }
+
+    // Fetches the latest blockhash from the blockhash provider, or from the
+    // cluster by default, tracked with its last valid block height.
+    async fn latest_blockhash(&self, rpc_client: &AsyncRpcClient) -> Result<Hash, ClientError> {
+        let blockhash = match &self.blockhash_provider {
+            Some(provider) => provider.latest_blockhash().await?,
+            None => {
+                rpc_client
+                    .get_latest_blockhash_with_commitment(self.options)
+                    .await?
+            }
+        };
+        *self.blockhash.lock().unwrap() = Some(blockhash);
+        Ok(blockhash.0)
+    }
+
+    // Records the outcome of sending the request, or of one of its attempts, in
+    // the metrics of the program.
+    fn record_send(&self, outcome: &str) {
+        #[cfg(feature = "metrics-prometheus")]
+        if let Some(metrics) = self.metrics {
+            metrics.record_send(outcome);
+        }
+        #[cfg(not(feature = "metrics-prometheus"))]
+        let _ = outcome;
+    }
+
+    // Signs the transaction with the blockhash the request was last signed
+    // with, or with a new one if there's none or it expired, and sends it. The
+    // transaction is signed again with a new blockhash if sending it failed
+    // because its blockhash expired, at most `MAX_BLOCKHASH_REFRESHES` times.
+    async fn send_with_blockhash_refresh<F>(
+        &self,
+        rpc_client: &AsyncRpcClient,
+        sign: impl Fn(Hash, usize) -> Result<Transaction, ClientError>,
+        send: impl Fn(Transaction) -> F,
+    ) -> Result<Signature, ClientError>
+    where
+        F: Future<Output = Result<Signature, SolanaClientError>>,
+    {
+        let mut blockhash = match self.blockhash() {
+            Some((blockhash, _)) if !self.blockhash_expired(rpc_client).await? => blockhash,
+            _ => self.latest_blockhash(rpc_client).await?,
+        };
+        let mut attempt = 0;
+        loop {
+            let tx = sign(blockhash, attempt)?;
+            let signature = tx.signatures[0];
+            let err = match send(tx).await {
+                Ok(signature) => {
+                    self.record_send("confirmed");
+                    return Ok(signature);
+                }
+                Err(err) => err,
+            };
+
+            let expired = err.get_transaction_error() == Some(TransactionError::BlockhashNotFound)
+                || self.blockhash_expired(rpc_client).await.unwrap_or(false);
+            if !expired || attempt == MAX_BLOCKHASH_REFRESHES {
+                self.record_send("failed");
+                return Err(err.into());
+            }
+            // Not signed again if it landed without its confirmation being seen
+            match rpc_client.get_signature_status(&signature).await {
+                Ok(Some(Ok(()))) => {
+                    self.record_send("confirmed");
+                    return Ok(signature);
+                }
+                Ok(Some(Err(e))) => {
+                    self.record_send("failed");
+                    return Err(SolanaClientError::from(e).into());
+                }
+                Ok(None) => self.record_send("blockhash_expired"),
+                Err(e) => {
+                    self.record_send("failed");
+                    return Err(e.into());
+                }
+            }
+            blockhash = self.latest_blockhash(rpc_client).await?;
+            attempt += 1;
+        }
+    }
 }
 
 impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn Signer + 'a>> {
@@ -684,11 +1093,19 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
         self
     }
 
+    /// Signs the transaction of the request with the given blockhash instead
+    /// of fetching the latest one, so that the transaction is deterministic,
+    /// e.g. to be asserted byte-for-byte in unit tests.
     pub fn signed_transaction_with_blockhash(
         &self,
         latest_hash: Hash,
     ) -> Result<Transaction, ClientError> {
-        let instructions = self.instructions()?;
+        self.sign(latest_hash, 0)
+    }
+
+    // Signs the transaction sent for the `attempt`th time with the blockhash.
+    fn sign(&self, latest_hash: Hash, attempt: usize) -> Result<Transaction, ClientError> {
+        let instructions = self.transaction_instructions(attempt)?;
         let signers: Vec<&dyn Signer> = self.signers.iter().map(|s| s.as_ref()).collect();
         let mut all_signers = signers;
         all_signers.push(&*self.payer);
@@ -705,11 +1122,12 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
 
     async fn signed_transaction_internal(&self) -> Result<Transaction, ClientError> {
         #[cfg(not(feature = "rpc-client"))]
-        let rpc_client = AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
+        let rpc_client =
+            &AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
         #[cfg(feature = "rpc-client")]
         let rpc_client = self.async_rpc_client;
 
-        let latest_hash = rpc_client.get_latest_blockhash().await?;
+        let latest_hash = self.latest_blockhash(rpc_client).await?;
         let tx = self.signed_transaction_with_blockhash(latest_hash)?;
 
         Ok(tx)
@@ -717,16 +1135,17 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
 
     async fn send_internal(&self) -> Result<Signature, ClientError> {
         #[cfg(not(feature = "rpc-client"))]
-        let rpc_client = AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
+        let rpc_client =
+            &AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
         #[cfg(feature = "rpc-client")]
         let rpc_client = self.async_rpc_client;
-        let latest_hash = rpc_client.get_latest_blockhash().await?;
-        let tx = self.signed_transaction_with_blockhash(latest_hash)?;
 
-        rpc_client
-            .send_and_confirm_transaction(&tx)
-            .await
-            .map_err(Into::into)
+        self.send_with_blockhash_refresh(
+            rpc_client,
+            |latest_hash, attempt| self.sign(latest_hash, attempt),
+            |tx| async move { rpc_client.send_and_confirm_transaction(&tx).await },
+        )
+        .await
     }
 
     async fn send_with_spinner_and_config_internal(
@@ -734,20 +1153,25 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
         config: RpcSendTransactionConfig,
     ) -> Result<Signature, ClientError> {
         #[cfg(not(feature = "rpc-client"))]
-        let rpc_client = AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
+        let rpc_client =
+            &AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
         #[cfg(feature = "rpc-client")]
         let rpc_client = self.async_rpc_client;
-        let latest_hash = rpc_client.get_latest_blockhash().await?;
-        let tx = self.signed_transaction_with_blockhash(latest_hash)?;
 
-        rpc_client
-            .send_and_confirm_transaction_with_spinner_and_config(
-                &tx,
-                rpc_client.commitment(),
-                config,
-            )
-            .await
-            .map_err(Into::into)
+        self.send_with_blockhash_refresh(
+            rpc_client,
+            |latest_hash, attempt| self.sign(latest_hash, attempt),
+            |tx| async move {
+                rpc_client
+                    .send_and_confirm_transaction_with_spinner_and_config(
+                        &tx,
+                        rpc_client.commitment(),
+                        config,
+                    )
+                    .await
+            },
+        )
+        .await
     }
 }
 
@@ -760,11 +1184,19 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
         self
     }
 
-    fn signed_transaction_with_blockhash(
+    /// Signs the transaction of the request with the given blockhash instead
+    /// of fetching the latest one, so that the transaction is deterministic,
+    /// e.g. to be asserted byte-for-byte in unit tests.
+    pub fn signed_transaction_with_blockhash(
         &self,
         latest_hash: Hash,
     ) -> Result<Transaction, ClientError> {
-        let instructions = self.instructions()?;
+        self.sign(latest_hash, 0)
+    }
+
+    // Signs the transaction sent for the `attempt`th time with the blockhash.
+    fn sign(&self, latest_hash: Hash, attempt: usize) -> Result<Transaction, ClientError> {
+        let instructions = self.transaction_instructions(attempt)?;
         let signers: Vec<&dyn Signer> = self.signers.iter().map(|s| s.as_signer()).collect();
         let mut all_signers = signers;
         all_signers.push(&*self.payer);
@@ -781,12 +1213,14 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
 
     async fn signed_transaction_internal(&self) -> Result<Transaction, ClientError> {
         #[cfg(not(feature = "rpc-client"))]
-        let latest_hash =
-            AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options)
-                .get_latest_blockhash()
-                .await?;
+        let latest_hash = self
+            .latest_blockhash(&AsyncRpcClient::new_with_commitment(
+                self.cluster.to_owned(),
+                self.options,
+            ))
+            .await?;
         #[cfg(feature = "rpc-client")]
-        let latest_hash = self.async_rpc_client.get_latest_blockhash().await?;
+        let latest_hash = self.latest_blockhash(self.async_rpc_client).await?;
 
         let tx = self.signed_transaction_with_blockhash(latest_hash)?;
 
@@ -795,16 +1229,17 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
 
     async fn send_internal(&self) -> Result<Signature, ClientError> {
         #[cfg(not(feature = "rpc-client"))]
-        let rpc_client = AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
+        let rpc_client =
+            &AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
         #[cfg(feature = "rpc-client")]
         let rpc_client = self.async_rpc_client;
-        let latest_hash = rpc_client.get_latest_blockhash().await?;
-        let tx = self.signed_transaction_with_blockhash(latest_hash)?;
 
-        rpc_client
-            .send_and_confirm_transaction(&tx)
-            .await
-            .map_err(Into::into)
+        self.send_with_blockhash_refresh(
+            rpc_client,
+            |latest_hash, attempt| self.sign(latest_hash, attempt),
+            |tx| async move { rpc_client.send_and_confirm_transaction(&tx).await },
+        )
+        .await
     }
 
     async fn send_with_spinner_and_config_internal(
@@ -812,20 +1247,25 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
         config: RpcSendTransactionConfig,
     ) -> Result<Signature, ClientError> {
         #[cfg(not(feature = "rpc-client"))]
-        let rpc_client = AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
+        let rpc_client =
+            &AsyncRpcClient::new_with_commitment(self.cluster.to_owned(), self.options);
         #[cfg(feature = "rpc-client")]
         let rpc_client = self.async_rpc_client;
-        let latest_hash = rpc_client.get_latest_blockhash().await?;
-        let tx = self.signed_transaction_with_blockhash(latest_hash)?;
 
-        rpc_client
-            .send_and_confirm_transaction_with_spinner_and_config(
-                &tx,
-                rpc_client.commitment(),
-                config,
-            )
-            .await
-            .map_err(Into::into)
+        self.send_with_blockhash_refresh(
+            rpc_client,
+            |latest_hash, attempt| self.sign(latest_hash, attempt),
+            |tx| async move {
+                rpc_client
+                    .send_and_confirm_transaction_with_spinner_and_config(
+                        &tx,
+                        rpc_client.commitment(),
+                        config,
+                    )
+                    .await
+            },
+        )
+        .await
     }
 }
 
@@ -902,6 +1342,375 @@ mod tests {
     pub struct MockEvent {}
 
     use super::*;
+    #[test]
+    fn test_prefetch() {
+        let payer = Arc::new(solana_sdk::signature::Keypair::new());
+        let client = Client::new(Cluster::Localnet, payer);
+        let program = client.program(Pubkey::new_unique()).unwrap();
+        let request = program
+            .request()
+            .prefetch::<anchor_lang::idl::IdlAccount>("idl", Pubkey::new_unique())
+            .prefetch::<anchor_lang::idl::IdlAccount>("idl", Pubkey::new_unique());
+        assert_eq!(request.prefetches.len(), 1);
+        assert!(matches!(
+            request.prefetched::<anchor_lang::idl::IdlAccount>("idl"),
+            Err(ClientError::AccountNotFound)
+        ));
+    }
+
+    // Transport serving `getMultipleAccounts` from `accounts`, recording the
+    // number of addresses of each request.
+    #[cfg(feature = "rpc-client")]
+    #[derive(Clone)]
+    struct MockAccountsSender {
+        accounts: Arc<HashMap<Pubkey, solana_sdk::account::Account>>,
+        batches: Arc<Mutex<Vec<usize>>>,
+    }
+
+    #[cfg(feature = "rpc-client")]
+    #[async_trait::async_trait]
+    impl solana_client::rpc_sender::RpcSender for MockAccountsSender {
+        async fn send(
+            &self,
+            request: solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> solana_client::client_error::Result<serde_json::Value> {
+            use solana_client::rpc_request::RpcRequest;
+
+            // The version is read to map the commitment of the requests
+            if request == RpcRequest::GetVersion {
+                return Ok(serde_json::json!({ "solana-core": "1.18.0" }));
+            }
+            assert_eq!(request, RpcRequest::GetMultipleAccounts);
+            let addresses = params[0].as_array().unwrap();
+            self.batches.lock().unwrap().push(addresses.len());
+            let accounts = addresses
+                .iter()
+                .map(|address| {
+                    let address = address.as_str().unwrap().parse().unwrap();
+                    self.accounts.get(&address).map(|account| {
+                        solana_account_decoder::UiAccount::encode(
+                            &address,
+                            account,
+                            UiAccountEncoding::Base64,
+                            None,
+                            None,
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(serde_json::json!({ "context": { "slot": 1 }, "value": accounts }))
+        }
+
+        fn get_transport_stats(&self) -> solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "mock".to_owned()
+        }
+    }
+
+    #[cfg(feature = "rpc-client")]
+    #[test]
+    fn test_fetch_prefetched() {
+        use anchor_lang::idl::IdlAccount;
+        use anchor_lang::AccountSerialize;
+
+        let idl_account = |data_len| {
+            let mut data = Vec::new();
+            IdlAccount {
+                authority: Pubkey::new_unique(),
+                data_len,
+            }
+            .try_serialize(&mut data)
+            .unwrap();
+            solana_sdk::account::Account {
+                lamports: 1,
+                data,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+            }
+        };
+
+        // More addresses than fit in one request, the last one missing
+        let addresses = (0..150).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let mut accounts = addresses[..149]
+            .iter()
+            .enumerate()
+            .map(|(i, address)| (*address, idl_account(i as u32)))
+            .collect::<HashMap<_, _>>();
+        let invalid = Pubkey::new_unique();
+        accounts.insert(
+            invalid,
+            solana_sdk::account::Account {
+                data: vec![0; 8],
+                ..idl_account(0)
+            },
+        );
+        let sender = MockAccountsSender {
+            accounts: Arc::new(accounts),
+            batches: Arc::new(Mutex::new(Vec::new())),
+        };
+
+        let payer = Arc::new(solana_sdk::signature::Keypair::new());
+        let client = Client::new(Cluster::Localnet, payer);
+        let program = client
+            .program_with_custom_rpc(
+                Pubkey::new_unique(),
+                RpcClient::new_sender(sender.clone(), RpcClientConfig::default()),
+                AsyncRpcClient::new_sender(sender.clone(), RpcClientConfig::default()),
+            )
+            .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let request = addresses
+            .iter()
+            .enumerate()
+            .fold(program.request(), |request, (i, address)| {
+                request.prefetch::<IdlAccount>(&i.to_string(), *address)
+            });
+        runtime
+            .block_on(request.fetch_prefetched_internal())
+            .unwrap();
+        assert_eq!(*sender.batches.lock().unwrap(), [100, 50]);
+        assert_eq!(request.prefetched::<IdlAccount>("0").unwrap().data_len, 0);
+        assert_eq!(
+            request.prefetched::<IdlAccount>("148").unwrap().data_len,
+            148
+        );
+        assert!(matches!(
+            request.prefetched::<IdlAccount>("149"),
+            Err(ClientError::AccountNotFound)
+        ));
+
+        // Accounts of another type fail the fetch
+        let request = program
+            .request()
+            .prefetch::<IdlAccount>("idl", addresses[0])
+            .prefetch::<IdlAccount>("invalid", invalid);
+        assert!(runtime
+            .block_on(request.fetch_prefetched_internal())
+            .is_err());
+        assert!(matches!(
+            request.prefetched::<IdlAccount>("idl"),
+            Err(ClientError::AccountNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_signed_transaction_with_blockhash() {
+        let payer = Arc::new(solana_sdk::signature::Keypair::new());
+        let client = Client::new(Cluster::Localnet, payer.clone());
+        let program = client.program(Pubkey::new_unique()).unwrap();
+        let blockhash = Hash::new_unique();
+        let build = || {
+            program
+                .request()
+                .instruction(Instruction::new_with_bytes(
+                    program.id(),
+                    &[1, 2, 3],
+                    vec![],
+                ))
+                .signed_transaction_with_blockhash(blockhash)
+                .unwrap()
+        };
+
+        let tx = build();
+        assert_eq!(tx, build());
+        assert_eq!(tx.message.recent_blockhash, blockhash);
+        assert_eq!(
+            tx.message.account_keys[0],
+            solana_sdk::signer::Signer::pubkey(payer.as_ref())
+        );
+    }
+
+    #[test]
+    fn test_blockhash_provider_and_priority_fee() {
+        let payer = Arc::new(solana_sdk::signature::Keypair::new());
+        let client = Client::new(Cluster::Localnet, payer);
+        let program = client.program(Pubkey::new_unique()).unwrap();
+        let blockhash = Arc::new(FixedBlockhash::new(Hash::new_unique(), u64::MAX));
+        let ix = Instruction::new_with_bytes(program.id(), &[1, 2, 3], vec![]);
+        let request = program
+            .request()
+            .instruction(ix.clone())
+            .blockhash_provider(blockhash.clone())
+            .priority_fee(Arc::new(FixedPriorityFee(5)));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let tx = runtime
+            .block_on(request.signed_transaction_internal())
+            .unwrap();
+        assert_eq!(tx.message.recent_blockhash, blockhash.get().0);
+        assert_eq!(request.blockhash(), Some(blockhash.get()));
+        assert_eq!(
+            tx,
+            Transaction::new_signed_with_payer(
+                &[ComputeBudgetInstruction::set_compute_unit_price(5), ix],
+                Some(&program.payer()),
+                &[&*client.cfg.payer],
+                blockhash.get().0,
+            )
+        );
+    }
+
+    #[test]
+    fn test_priority_fee_escalation() {
+        let payer = Arc::new(solana_sdk::signature::Keypair::new());
+        let client = Client::new(Cluster::Localnet, payer);
+        let program = client.program(Pubkey::new_unique()).unwrap();
+        let ix = Instruction::new_with_bytes(program.id(), &[1, 2, 3], vec![]);
+        let request = program
+            .request()
+            .instruction(ix.clone())
+            .priority_fee(Arc::new(|_: &[Instruction], attempt: usize| {
+                (attempt > 0).then_some(1_000 << attempt)
+            }));
+
+        assert_eq!(
+            request.transaction_instructions(0).unwrap(),
+            std::slice::from_ref(&ix)
+        );
+        assert_eq!(
+            request.transaction_instructions(2).unwrap(),
+            [ComputeBudgetInstruction::set_compute_unit_price(4_000), ix]
+        );
+    }
+
+    // Sends a request signed with the blockhashes of `provider` against an RPC
+    // mocking `url`. `send` returns the result of each attempt, and the
+    // blockhashes of the attempts are returned with the result.
+    fn send_with_refresh(
+        url: &str,
+        provider: Arc<FixedBlockhash>,
+        tracked: Option<(Hash, u64)>,
+        send: impl Fn(&Transaction, usize) -> std::result::Result<Signature, SolanaClientError>,
+    ) -> (std::result::Result<Signature, ClientError>, Vec<Hash>) {
+        let payer = Arc::new(solana_sdk::signature::Keypair::new());
+        let client = Client::new(Cluster::Localnet, payer);
+        let program = client.program(Pubkey::new_unique()).unwrap();
+        let request = program
+            .request()
+            .instruction(Instruction::new_with_bytes(program.id(), &[1], vec![]))
+            .blockhash_provider(provider);
+        *request.blockhash.lock().unwrap() = tracked;
+
+        let rpc_client = AsyncRpcClient::new_mock(url.to_owned());
+        let blockhashes = std::cell::RefCell::new(vec![]);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let result = runtime.block_on(request.send_with_blockhash_refresh(
+            &rpc_client,
+            |latest_hash, attempt| request.sign(latest_hash, attempt),
+            |tx| {
+                let attempt = blockhashes.borrow().len();
+                blockhashes.borrow_mut().push(tx.message.recent_blockhash);
+                futures::future::ready(send(&tx, attempt))
+            },
+        ));
+        (result, blockhashes.into_inner())
+    }
+
+    fn blockhash_not_found() -> SolanaClientError {
+        TransactionError::BlockhashNotFound.into()
+    }
+
+    #[test]
+    fn test_send_refreshes_expired_blockhash() {
+        let (first, second) = (Hash::new_unique(), Hash::new_unique());
+        let provider = Arc::new(FixedBlockhash::new(first, u64::MAX));
+        let (result, blockhashes) = send_with_refresh("sig_not_found", provider.clone(), None, {
+            let provider = provider.clone();
+            move |tx, attempt| {
+                if attempt == 0 {
+                    provider.set(second, u64::MAX);
+                    Err(blockhash_not_found())
+                } else {
+                    Ok(tx.signatures[0])
+                }
+            }
+        });
+        assert!(result.is_ok());
+        assert_eq!(blockhashes, [first, second]);
+    }
+
+    #[test]
+    fn test_send_reuses_blockhash() {
+        let (tracked, latest) = (Hash::new_unique(), Hash::new_unique());
+        let provider = Arc::new(FixedBlockhash::new(latest, u64::MAX));
+        let (result, blockhashes) =
+            send_with_refresh("succeeds", provider, Some((tracked, u64::MAX)), |tx, _| {
+                Ok(tx.signatures[0])
+            });
+        assert!(result.is_ok());
+        assert_eq!(blockhashes, [tracked]);
+    }
+
+    #[test]
+    fn test_send_bounds_blockhash_refreshes() {
+        let provider = Arc::new(FixedBlockhash::new(Hash::new_unique(), u64::MAX));
+        let (result, blockhashes) = send_with_refresh("sig_not_found", provider, None, |_, _| {
+            Err(blockhash_not_found())
+        });
+        assert!(result.is_err());
+        assert_eq!(blockhashes.len(), MAX_BLOCKHASH_REFRESHES + 1);
+    }
+
+    #[test]
+    fn test_send_other_errors_not_retried() {
+        let provider = Arc::new(FixedBlockhash::new(Hash::new_unique(), u64::MAX));
+        let (result, blockhashes) = send_with_refresh("sig_not_found", provider, None, |_, _| {
+            Err(TransactionError::AccountInUse.into())
+        });
+        assert!(result.is_err());
+        assert_eq!(blockhashes.len(), 1);
+    }
+
+    #[test]
+    fn test_send_landed_not_signed_again() {
+        // The transaction landed although its confirmation wasn't seen
+        let provider = Arc::new(FixedBlockhash::new(Hash::new_unique(), u64::MAX));
+        let (result, blockhashes) =
+            send_with_refresh(
+                "succeeds",
+                provider,
+                None,
+                |_, _| Err(blockhash_not_found()),
+            );
+        assert!(result.is_ok());
+        assert_eq!(blockhashes.len(), 1);
+    }
+
+    #[test]
+    fn test_subscription_health() {
+        let payer = Arc::new(solana_sdk::signature::Keypair::new());
+        let client = Client::new(Cluster::Localnet, payer);
+        let program = client.program(Pubkey::new_unique()).unwrap();
+        let first = Arc::new(Mutex::new(SubscriptionHealth::new()));
+        let second = Arc::new(Mutex::new(SubscriptionHealth::new()));
+        program.track_subscription(&first);
+        program.track_subscription(&second);
+
+        second.lock().unwrap().record(42);
+        let health = program.subscription_health();
+        assert_eq!(health.len(), 2);
+        assert_eq!(health[0].last_slot, None);
+        assert_eq!(health[1].last_slot, Some(42));
+
+        drop(first);
+        assert_eq!(
+            program.subscription_health(),
+            [second.lock().unwrap().clone()]
+        );
+    }
+
     #[test]
     fn new_execution() {
         let mut logs: &[String] =
@@ -929,6 +1738,37 @@ mod tests {
         assert!(!did_pop);
     }
 
+    #[test]
+    fn test_parse_noop_events() {
+        use anchor_lang::Event;
+
+        #[event]
+        #[derive(Debug, PartialEq)]
+        pub struct LeafUpdated {
+            index: u32,
+        }
+
+        let noop = Pubkey::new_unique();
+        let account_keys = [Pubkey::new_unique(), noop];
+        let ix = |program_id_index, data| {
+            CompiledInstruction::new_from_raw_parts(program_id_index, data, vec![])
+        };
+        let inner_instructions = [
+            ix(1, LeafUpdated { index: 1 }.data()),
+            // Events of other programs and other data of the noop program
+            ix(0, LeafUpdated { index: 2 }.data()),
+            ix(1, MockEvent {}.data()),
+            ix(1, vec![1, 2, 3]),
+            ix(1, LeafUpdated { index: 3 }.data()),
+        ];
+        let events =
+            parse_noop_events::<LeafUpdated>(&noop, &account_keys, &inner_instructions).unwrap();
+        assert_eq!(events, [LeafUpdated { index: 1 }, LeafUpdated { index: 3 }]);
+
+        let truncated = [ix(1, LeafUpdated::DISCRIMINATOR.to_vec())];
+        assert!(parse_noop_events::<LeafUpdated>(&noop, &account_keys, &truncated).is_err());
+    }
+
     #[test]
     fn test_parse_logs_response() -> Result<()> {
         // Mock logs received within an `RpcResponse`. These are based on a Jupiter transaction.
@@ -0,0 +1,251 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use prometheus::{GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use serde_json::Value;
+use solana_client::{
+    client_error::Result as SolanaClientResult,
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+
+/// Prometheus metrics of the RPC requests of clients, by method:
+///
+/// - `anchor_client_rpc_requests_total`, the number of requests
+/// - `anchor_client_rpc_errors_total`, the number of failed requests, e.g.
+///   the transactions the cluster refused with the `sendTransaction` method
+/// - `anchor_client_rpc_request_duration_seconds`, the latency of the requests
+///
+/// The metrics of the RPC requests are recorded by the senders created with
+/// [`sender`]. The programs created with `Client::program_with_metrics` also
+/// record:
+///
+/// - `anchor_client_send_outcomes_total`, the outcomes of sending requests by
+///   outcome, `confirmed`, `failed`, or `blockhash_expired` for the attempts
+///   signed again with a new blockhash
+/// - `anchor_client_subscription_messages_total`, the number of messages of
+///   the event subscriptions by program
+/// - `anchor_client_subscription_last_message_timestamp_seconds`, the time of
+///   the last message of the event subscriptions by program, so that the lag
+///   of events is `time() - anchor_client_subscription_last_message_timestamp_seconds`
+///
+/// The metrics are exported by the host with its [`registry`].
+///
+/// ```ignore
+/// let metrics = PrometheusMetrics::new();
+/// let program = client.program_with_metrics(my_program::ID, &metrics)?;
+///
+/// // e.g. in the handler of the `/metrics` endpoint of the host
+/// let body = prometheus::TextEncoder::new().encode_to_string(&metrics.registry().gather())?;
+/// ```
+///
+/// [`sender`]: Self::sender
+/// [`registry`]: Self::registry
+#[derive(Clone)]
+pub struct PrometheusMetrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    errors: IntCounterVec,
+    duration: HistogramVec,
+    send_outcomes: IntCounterVec,
+    subscription_messages: IntCounterVec,
+    subscription_last_message: GaugeVec,
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrometheusMetrics {
+    /// Creates the metrics in a new registry.
+    pub fn new() -> Self {
+        Self::with_registry(Registry::new()).expect("Metrics are registered once")
+    }
+
+    /// Creates the metrics in the registry of the host, failing if the
+    /// metrics are already registered in it.
+    pub fn with_registry(registry: Registry) -> prometheus::Result<Self> {
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "anchor_client_rpc_requests_total",
+                "Number of RPC requests by method",
+            ),
+            &["method"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new(
+                "anchor_client_rpc_errors_total",
+                "Number of failed RPC requests by method",
+            ),
+            &["method"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "anchor_client_rpc_request_duration_seconds",
+                "Latency of the RPC requests by method",
+            ),
+            &["method"],
+        )?;
+        let send_outcomes = IntCounterVec::new(
+            Opts::new(
+                "anchor_client_send_outcomes_total",
+                "Number of sent requests by outcome",
+            ),
+            &["outcome"],
+        )?;
+        let subscription_messages = IntCounterVec::new(
+            Opts::new(
+                "anchor_client_subscription_messages_total",
+                "Number of messages of the event subscriptions by program",
+            ),
+            &["program"],
+        )?;
+        let subscription_last_message = GaugeVec::new(
+            Opts::new(
+                "anchor_client_subscription_last_message_timestamp_seconds",
+                "Unix time of the last message of the event subscriptions by program",
+            ),
+            &["program"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        registry.register(Box::new(send_outcomes.clone()))?;
+        registry.register(Box::new(subscription_messages.clone()))?;
+        registry.register(Box::new(subscription_last_message.clone()))?;
+
+        Ok(Self {
+            registry,
+            requests,
+            errors,
+            duration,
+            send_outcomes,
+            subscription_messages,
+            subscription_last_message,
+        })
+    }
+
+    /// Registry of the metrics, to be scraped by the host.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Wraps the transport of an RPC client, e.g. an `HttpSender`, to record
+    /// the metrics of its requests.
+    pub fn sender<S: RpcSender>(&self, inner: S) -> PrometheusSender<S> {
+        PrometheusSender {
+            inner,
+            metrics: self.clone(),
+        }
+    }
+
+    pub(crate) fn record_send(&self, outcome: &str) {
+        self.send_outcomes.with_label_values(&[outcome]).inc();
+    }
+
+    pub(crate) fn record_event(&self, program: &str) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.subscription_messages
+            .with_label_values(&[program])
+            .inc();
+        self.subscription_last_message
+            .with_label_values(&[program])
+            .set(now.as_secs_f64());
+    }
+}
+
+/// RPC transport recording the metrics of a [`PrometheusMetrics`], created
+/// with [`PrometheusMetrics::sender`].
+pub struct PrometheusSender<S> {
+    inner: S,
+    metrics: PrometheusMetrics,
+}
+
+#[async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for PrometheusSender<S> {
+    async fn send(&self, request: RpcRequest, params: Value) -> SolanaClientResult<Value> {
+        let method = request.to_string();
+        let start = Instant::now();
+        let result = self.inner.send(request, params).await;
+
+        let labels = [method.as_str()];
+        self.metrics.requests.with_label_values(&labels).inc();
+        if result.is_err() {
+            self.metrics.errors.with_label_values(&labels).inc();
+        }
+        self.metrics
+            .duration
+            .with_label_values(&labels)
+            .observe(start.elapsed().as_secs_f64());
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_client::rpc_client::{RpcClient, RpcClientConfig};
+    use solana_rpc_client::mock_sender::MockSender;
+
+    use super::*;
+
+    #[test]
+    fn test_prometheus_metrics() {
+        let metrics = PrometheusMetrics::new();
+        let rpc = RpcClient::new_sender(
+            metrics.sender(MockSender::new("succeeds")),
+            RpcClientConfig::default(),
+        );
+        rpc.get_slot().unwrap();
+        rpc.get_slot().unwrap();
+
+        assert_eq!(metrics.requests.with_label_values(&["getSlot"]).get(), 2);
+        assert_eq!(metrics.errors.with_label_values(&["getSlot"]).get(), 0);
+        assert_eq!(
+            metrics
+                .duration
+                .with_label_values(&["getSlot"])
+                .get_sample_count(),
+            2
+        );
+        assert!(PrometheusMetrics::with_registry(metrics.registry().clone()).is_err());
+    }
+
+    #[test]
+    fn test_send_and_event_metrics() {
+        let metrics = PrometheusMetrics::new();
+        metrics.record_send("blockhash_expired");
+        metrics.record_send("confirmed");
+        metrics.record_event("program");
+
+        let outcomes = &metrics.send_outcomes;
+        assert_eq!(outcomes.with_label_values(&["blockhash_expired"]).get(), 1);
+        assert_eq!(outcomes.with_label_values(&["confirmed"]).get(), 1);
+        assert_eq!(outcomes.with_label_values(&["failed"]).get(), 0);
+        assert_eq!(
+            metrics
+                .subscription_messages
+                .with_label_values(&["program"])
+                .get(),
+            1
+        );
+        assert!(
+            metrics
+                .subscription_last_message
+                .with_label_values(&["program"])
+                .get()
+                > 0.0
+        );
+    }
+}
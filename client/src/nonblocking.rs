@@ -1,8 +1,12 @@
+#[cfg(feature = "metrics-prometheus")]
+use crate::PrometheusMetrics;
 use crate::{
-    ClientError, Config, EventContext, EventUnsubscriber, Program, ProgramAccountsIterator,
-    RequestBuilder, ThreadSafeSigner,
+    health::{watch_lag, LagWatchdog},
+    AccountSchema, ClientError, Config, EventContext, EventUnsubscriber, Program,
+    ProgramAccountsIterator, RequestBuilder, SubscriptionHealth, ThreadSafeSigner,
 };
 use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use anchor_lang_idl::types::Idl;
 #[cfg(feature = "rpc-client")]
 use solana_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient};
 use solana_client::{rpc_config::RpcSendTransactionConfig, rpc_filter::RpcFilterType};
@@ -10,7 +14,13 @@ use solana_sdk::{
     commitment_config::CommitmentConfig, signature::Signature, signer::Signer,
     transaction::Transaction,
 };
-use std::{marker::PhantomData, ops::Deref, sync::Arc};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tokio::sync::RwLock;
 
 impl<'a> EventUnsubscriber<'a> {
@@ -27,6 +37,7 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             program_id,
             cfg,
             sub_client: Arc::new(RwLock::new(None)),
+            subscriptions: Mutex::new(Vec::new()),
         });
 
         #[cfg(feature = "rpc-client")]
@@ -37,8 +48,11 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
                 program_id,
                 cfg,
                 sub_client: Arc::new(RwLock::new(None)),
+                subscriptions: Mutex::new(Vec::new()),
                 rpc_client: RpcClient::new_with_commitment(cluster_url.clone(), comm_config),
                 async_rpc_client: AsyncRpcClient::new_with_commitment(cluster_url, comm_config),
+                #[cfg(feature = "metrics-prometheus")]
+                metrics: None,
             })
         }
     }
@@ -54,8 +68,11 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             program_id,
             cfg,
             sub_client: Arc::new(RwLock::new(None)),
+            subscriptions: Mutex::new(Vec::new()),
             rpc_client,
             async_rpc_client,
+            #[cfg(feature = "metrics-prometheus")]
+            metrics: None,
         })
     }
 
@@ -81,6 +98,30 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         self.accounts_lazy_internal(filters).await
     }
 
+    /// Returns the IDL of the deployed program, fetched from its IDL account.
+    pub async fn idl(&self) -> Result<Idl, ClientError> {
+        self.idl_internal().await
+    }
+
+    /// Checks that the account types of the client, e.g. `(Vault, Config)`,
+    /// are accounts of the IDL of the deployed program, returning a
+    /// [`ClientError::SchemaMismatch`] listing the differences otherwise. With
+    /// the `idl-build` feature, their layouts are checked against the type
+    /// definitions of the IDL too.
+    ///
+    /// Services can call it on startup to fail loudly instead of misdecoding
+    /// accounts after an upgrade of the program.
+    pub async fn verify_schema<T: AccountSchema>(&self) -> Result<(), ClientError> {
+        self.verify_schema_internal::<T>().await
+    }
+
+    /// Checks that the IDL of the deployed program has no breaking changes
+    /// from the IDL the client was built with, returning a
+    /// [`ClientError::SchemaMismatch`] listing them otherwise.
+    pub async fn verify_idl(&self, idl: &Idl) -> Result<(), ClientError> {
+        self.verify_idl_internal(idl).await
+    }
+
     /// Subscribe to program logs.
     ///
     /// Returns an [`EventUnsubscriber`] to unsubscribe and close connection gracefully.
@@ -88,11 +129,41 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         &self,
         f: impl Fn(&EventContext, T) + Send + 'static,
     ) -> Result<EventUnsubscriber, ClientError> {
-        let (handle, rx) = self.on_internal(f).await?;
+        let (handle, rx, health) = self.on_internal(f).await?;
+
+        Ok(EventUnsubscriber {
+            handle,
+            rx,
+            health,
+            watchdog: None,
+            _lifetime_marker: PhantomData,
+        })
+    }
+
+    /// Subscribe to program logs like [`on`](Self::on), calling `on_lag` with
+    /// the [`SubscriptionHealth`] when no message is received for more than
+    /// `max_lag`, so that stalled subscriptions can be alerted on.
+    ///
+    /// `on_lag` is called once per stall, and once when the subscription is
+    /// closed by the cluster.
+    pub async fn on_with_lag_alert<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
+        &self,
+        f: impl Fn(&EventContext, T) + Send + 'static,
+        max_lag: Duration,
+        on_lag: impl Fn(&SubscriptionHealth) + Send + 'static,
+    ) -> Result<EventUnsubscriber, ClientError> {
+        let (handle, rx, health) = self.on_internal(f).await?;
+        let watchdog = LagWatchdog(tokio::spawn(watch_lag(
+            Arc::clone(&health),
+            max_lag,
+            on_lag,
+        )));
 
         Ok(EventUnsubscriber {
             handle,
             rx,
+            health,
+            watchdog: Some(watchdog),
             _lifetime_marker: PhantomData,
         })
     }
@@ -115,6 +186,11 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
             instructions: Vec::new(),
             instruction_data: None,
             signers: Vec::new(),
+            blockhash: Mutex::new(None),
+            blockhash_provider: None,
+            priority_fee: None,
+            prefetches: Vec::new(),
+            prefetched: Mutex::new(HashMap::new()),
             _phantom: PhantomData,
         }
     }
@@ -126,6 +202,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
         payer: C,
         options: Option<CommitmentConfig>,
         async_rpc_client: &'a AsyncRpcClient,
+        #[cfg(feature = "metrics-prometheus")] metrics: Option<&'a PrometheusMetrics>,
     ) -> Self {
         Self {
             program_id,
@@ -136,8 +213,15 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
             instructions: Vec::new(),
             instruction_data: None,
             signers: Vec::new(),
+            blockhash: Mutex::new(None),
+            blockhash_provider: None,
+            priority_fee: None,
+            prefetches: Vec::new(),
+            prefetched: Mutex::new(HashMap::new()),
             _phantom: PhantomData,
             async_rpc_client,
+            #[cfg(feature = "metrics-prometheus")]
+            metrics,
         }
     }
 
@@ -145,6 +229,22 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Box<dyn S
         self.signed_transaction_internal().await
     }
 
+    /// Returns whether the blockhash with which the request was last signed
+    /// expired, i.e. whether the cluster would refuse a transaction signed with
+    /// it. `false` until the request is signed.
+    ///
+    /// Sending the request signs it again with a new blockhash if it expires
+    /// before the transaction lands.
+    pub async fn is_expired(&self) -> Result<bool, ClientError> {
+        self.is_expired_internal().await
+    }
+
+    /// Fetches the accounts registered with `prefetch` in batch, failing if
+    /// one of them isn't an account of its prefetched type.
+    pub async fn fetch_prefetched(&self) -> Result<(), ClientError> {
+        self.fetch_prefetched_internal().await
+    }
+
     pub async fn send(self) -> Result<Signature, ClientError> {
         self.send_internal().await
     }
@@ -174,6 +274,11 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
             instructions: Vec::new(),
             instruction_data: None,
             signers: Vec::new(),
+            blockhash: Mutex::new(None),
+            blockhash_provider: None,
+            priority_fee: None,
+            prefetches: Vec::new(),
+            prefetched: Mutex::new(HashMap::new()),
             _phantom: PhantomData,
         }
     }
@@ -185,6 +290,7 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
         payer: C,
         options: Option<CommitmentConfig>,
         async_rpc_client: &'a AsyncRpcClient,
+        #[cfg(feature = "metrics-prometheus")] metrics: Option<&'a PrometheusMetrics>,
     ) -> Self {
         Self {
             program_id,
@@ -195,8 +301,15 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
             instructions: Vec::new(),
             instruction_data: None,
             signers: Vec::new(),
+            blockhash: Mutex::new(None),
+            blockhash_provider: None,
+            priority_fee: None,
+            prefetches: Vec::new(),
+            prefetched: Mutex::new(HashMap::new()),
             _phantom: PhantomData,
             async_rpc_client,
+            #[cfg(feature = "metrics-prometheus")]
+            metrics,
         }
     }
 
@@ -204,6 +317,22 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
         self.signed_transaction_internal().await
     }
 
+    /// Returns whether the blockhash with which the request was last signed
+    /// expired, i.e. whether the cluster would refuse a transaction signed with
+    /// it. `false` until the request is signed.
+    ///
+    /// Sending the request signs it again with a new blockhash if it expires
+    /// before the transaction lands.
+    pub async fn is_expired(&self) -> Result<bool, ClientError> {
+        self.is_expired_internal().await
+    }
+
+    /// Fetches the accounts registered with `prefetch` in batch, failing if
+    /// one of them isn't an account of its prefetched type.
+    pub async fn fetch_prefetched(&self) -> Result<(), ClientError> {
+        self.fetch_prefetched_internal().await
+    }
+
     pub async fn send(self) -> Result<Signature, ClientError> {
         self.send_internal().await
     }
@@ -0,0 +1,314 @@
+#[cfg(feature = "idl-build")]
+use std::collections::BTreeMap;
+use std::{fmt, io::Read, ops::Deref};
+
+use anchor_lang::{
+    idl::IdlAccount, solana_program::pubkey::Pubkey, AnchorDeserialize, Discriminator,
+};
+#[cfg(feature = "idl-build")]
+use anchor_lang_idl::{
+    build::{convert_module_paths, IdlBuild},
+    types::IdlTypeDef,
+};
+use anchor_lang_idl::{diff, types::Idl};
+use flate2::read::ZlibDecoder;
+use solana_sdk::{commitment_config::CommitmentConfig, signer::Signer};
+
+use crate::{ClientError, Program};
+
+/// Account types of the client, compared with the accounts of the IDL of the
+/// deployed program by [`Program::verify_schema`].
+///
+/// Implemented for tuples of account types, e.g. `(Vault, Config)`. With the
+/// `idl-build` feature, the account types must implement `IdlBuild`, i.e. the
+/// program crate must be built with its `idl-build` feature, and their layouts
+/// are compared with the type definitions of the IDL too.
+pub trait AccountSchema {
+    /// Returns the names and discriminators of the account types.
+    fn accounts() -> Vec<(&'static str, [u8; 8])>;
+
+    /// Returns the definitions of the account types and of the types they
+    /// contain, by name. Types are named as in the IDL built by the program,
+    /// i.e. by their full paths only if their names conflict.
+    #[cfg(feature = "idl-build")]
+    fn types() -> BTreeMap<String, IdlTypeDef>;
+}
+
+macro_rules! impl_account_schema {
+    ($($ty:ident),+) => {
+        #[cfg(not(feature = "idl-build"))]
+        impl<$($ty: Discriminator),+> AccountSchema for ($($ty,)+) {
+            fn accounts() -> Vec<(&'static str, [u8; 8])> {
+                vec![$((type_name::<$ty>(), $ty::DISCRIMINATOR)),+]
+            }
+        }
+
+        #[cfg(feature = "idl-build")]
+        impl<$($ty: Discriminator + IdlBuild),+> AccountSchema for ($($ty,)+) {
+            fn accounts() -> Vec<(&'static str, [u8; 8])> {
+                vec![$((type_name::<$ty>(), $ty::DISCRIMINATOR)),+]
+            }
+
+            fn types() -> BTreeMap<String, IdlTypeDef> {
+                let mut types = BTreeMap::new();
+                $(
+                    if let Some(ty) = $ty::create_type() {
+                        types.insert(ty.name.clone(), ty);
+                    }
+                    $ty::insert_types(&mut types);
+                )+
+                short_names(types)
+            }
+        }
+    };
+}
+
+// Names the types by their names instead of their full paths, e.g.
+// `my_program::state::Vault`, as the IDL build does.
+#[cfg(feature = "idl-build")]
+fn short_names(types: BTreeMap<String, IdlTypeDef>) -> BTreeMap<String, IdlTypeDef> {
+    convert_module_paths(types.into_values().collect::<Vec<_>>())
+        .into_iter()
+        .map(|ty| (ty.name.clone(), ty))
+        .collect()
+}
+
+impl_account_schema!(A);
+impl_account_schema!(A, B);
+impl_account_schema!(A, B, C);
+impl_account_schema!(A, B, C, D);
+impl_account_schema!(A, B, C, D, E);
+impl_account_schema!(A, B, C, D, E, F);
+impl_account_schema!(A, B, C, D, E, F, G);
+impl_account_schema!(A, B, C, D, E, F, G, H);
+impl_account_schema!(A, B, C, D, E, F, G, H, I);
+impl_account_schema!(A, B, C, D, E, F, G, H, I, J);
+impl_account_schema!(A, B, C, D, E, F, G, H, I, J, K);
+impl_account_schema!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Differences between the types of the client and the deployed program,
+/// returned by [`Program::verify_schema`] and [`Program::verify_idl`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    pub program_id: Pubkey,
+    pub mismatches: Vec<String>,
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The deployed program {} doesn't match the client:",
+            self.program_id
+        )?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n- {mismatch}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
+    pub(crate) async fn idl_internal(&self) -> Result<Idl, ClientError> {
+        let account = self
+            .async_rpc()
+            .get_account_with_commitment(
+                &IdlAccount::address(&self.program_id),
+                CommitmentConfig::processed(),
+            )
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound)?;
+        decode_idl(&account.data)
+    }
+
+    pub(crate) async fn verify_schema_internal<T: AccountSchema>(&self) -> Result<(), ClientError> {
+        let idl = self.idl_internal().await?;
+        let mismatches = check_accounts(&idl, &T::accounts());
+        #[cfg(feature = "idl-build")]
+        let mismatches = [mismatches, check_types(&idl, T::types())].concat();
+        self.mismatch(mismatches)
+    }
+
+    pub(crate) async fn verify_idl_internal(&self, idl: &Idl) -> Result<(), ClientError> {
+        let on_chain_idl = self.idl_internal().await?;
+        let mismatches = diff::diff(idl, &on_chain_idl)
+            .into_iter()
+            .filter(|change| change.is_breaking())
+            .map(|change| change.description)
+            .collect();
+        self.mismatch(mismatches)
+    }
+
+    fn mismatch(&self, mismatches: Vec<String>) -> Result<(), ClientError> {
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ClientError::SchemaMismatch(SchemaMismatch {
+                program_id: self.program_id,
+                mismatches,
+            }))
+        }
+    }
+}
+
+// Decodes the IDL of the data of an IDL account, whose zlib compressed IDL
+// follows its header.
+fn decode_idl(data: &[u8]) -> Result<Idl, ClientError> {
+    let invalid = |e: &dyn fmt::Display| ClientError::InvalidIdl(e.to_string());
+    let mut header = data.get(8..).ok_or_else(|| invalid(&"missing header"))?;
+    let idl_account = IdlAccount::deserialize(&mut header).map_err(|e| invalid(&e))?;
+    let compressed = data
+        .get(44..44 + idl_account.data_len as usize)
+        .ok_or_else(|| invalid(&"truncated data"))?;
+    let mut json = Vec::new();
+    ZlibDecoder::new(compressed)
+        .read_to_end(&mut json)
+        .map_err(|e| invalid(&e))?;
+    serde_json::from_slice(&json).map_err(|e| invalid(&e))
+}
+
+// Returns the mismatches between the account types of the client and the
+// accounts of the IDL, which are identified by their discriminators.
+fn check_accounts(idl: &Idl, accounts: &[(&str, [u8; 8])]) -> Vec<String> {
+    accounts
+        .iter()
+        .filter_map(|(name, discriminator)| {
+            if idl
+                .accounts
+                .iter()
+                .any(|acc| acc.discriminator == discriminator)
+            {
+                return None;
+            }
+            let mismatch = match idl.accounts.iter().find(|acc| acc.name == *name) {
+                Some(acc) => format!(
+                    "Account `{name}` has the discriminator {:?} on chain but {discriminator:?} \
+                    in the client",
+                    acc.discriminator
+                ),
+                None => format!(
+                    "Account `{name}` with the discriminator {discriminator:?} isn't an account \
+                    of the deployed program"
+                ),
+            };
+            Some(mismatch)
+        })
+        .collect()
+}
+
+// Returns the breaking changes from the type definitions of the client to the
+// ones of the IDL with the same names, i.e. the layouts the client would
+// misdecode.
+#[cfg(feature = "idl-build")]
+fn check_types(idl: &Idl, mut types: BTreeMap<String, IdlTypeDef>) -> Vec<String> {
+    let client_idl = Idl {
+        types: idl
+            .types
+            .iter()
+            .map(|ty| types.remove(&ty.name).unwrap_or_else(|| ty.clone()))
+            .collect(),
+        ..idl.clone()
+    };
+    diff::diff(&client_idl, idl)
+        .into_iter()
+        .filter(|change| change.is_breaking())
+        .map(|change| change.description)
+        .collect()
+}
+
+fn type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use anchor_lang::AnchorSerialize;
+    use flate2::{write::ZlibEncoder, Compression};
+
+    use super::*;
+
+    fn idl() -> Idl {
+        serde_json::from_value(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "vault", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "Vault", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_idl() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&serde_json::to_vec(&idl()).unwrap())
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut data = IdlAccount::DISCRIMINATOR.to_vec();
+        IdlAccount {
+            authority: Pubkey::new_unique(),
+            data_len: compressed.len() as u32,
+        }
+        .serialize(&mut data)
+        .unwrap();
+        data.extend(compressed);
+        assert_eq!(decode_idl(&data).unwrap(), idl());
+        assert!(decode_idl(&data[..50]).is_err());
+    }
+
+    #[test]
+    fn test_check_accounts() {
+        let idl = idl();
+        assert!(check_accounts(&idl, &[("Vault", [1, 2, 3, 4, 5, 6, 7, 8])]).is_empty());
+
+        let mismatches = check_accounts(&idl, &[("Vault", [0; 8]), ("Config", [1; 8])]);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("has the discriminator"));
+        assert!(mismatches[1].contains("isn't an account"));
+    }
+
+    #[cfg(feature = "idl-build")]
+    #[test]
+    fn test_check_types() {
+        use anchor_lang_idl::types::{IdlDefinedFields, IdlField, IdlType, IdlTypeDefTy};
+
+        let mut idl = idl();
+        let vault = |ty| IdlTypeDef {
+            name: "Vault".into(),
+            docs: vec![],
+            serialization: Default::default(),
+            repr: None,
+            generics: vec![],
+            layout: None,
+            ty: IdlTypeDefTy::Struct {
+                fields: Some(IdlDefinedFields::Named(vec![IdlField {
+                    name: "amount".into(),
+                    docs: vec![],
+                    ty,
+                }])),
+            },
+        };
+        idl.types = vec![vault(IdlType::U64)];
+
+        let types = |ty| BTreeMap::from([("Vault".to_owned(), vault(ty))]);
+        assert!(check_types(&idl, types(IdlType::U64)).is_empty());
+        // Types the IDL doesn't have are left to the check of the accounts
+        assert!(check_types(&idl, BTreeMap::new()).is_empty());
+
+        let mismatches = check_types(&idl, types(IdlType::U32));
+        assert!(!mismatches.is_empty());
+        assert!(mismatches.iter().any(|mismatch| mismatch.contains("Vault")));
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name::<Vec<u8>>(), "Vec");
+        assert_eq!(type_name::<SchemaMismatch>(), "SchemaMismatch");
+    }
+}
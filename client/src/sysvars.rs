@@ -0,0 +1,233 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::{
+    client_error::Result as SolanaClientResult,
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::{
+    account::{create_account_for_test, Account},
+    clock::Clock,
+    epoch_schedule::EpochSchedule,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar,
+};
+
+/// Sysvars seen by the clients of a test instead of the ones of the cluster,
+/// so that the code paths depending on time or rent can be tested
+/// deterministically against a mock or in-process transport.
+///
+/// The overrides are shared by the senders created with [`sender`], so that
+/// moving the clock of a `MockSysvars` moves the clock of all of its clients.
+///
+/// ```ignore
+/// let sysvars = MockSysvars::new();
+/// sysvars.set_unix_timestamp(1_700_000_000);
+/// let program = client.program_with_sysvars(my_program::ID, &sysvars)?;
+///
+/// // Client code reading the clock sees the mocked timestamp
+/// let clock: Clock = from_account(&program.rpc().get_account(&sysvar::clock::ID)?).unwrap();
+/// assert_eq!(clock.unix_timestamp, 1_700_000_000);
+/// ```
+///
+/// [`sender`]: Self::sender
+#[derive(Debug, Clone, Default)]
+pub struct MockSysvars(Arc<RwLock<Sysvars>>);
+
+#[derive(Debug, Clone, Default)]
+struct Sysvars {
+    clock: Option<Clock>,
+    rent: Option<Rent>,
+    epoch_schedule: Option<EpochSchedule>,
+}
+
+impl MockSysvars {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the `Clock` sysvar, and the slot of the responses.
+    pub fn set_clock(&self, clock: Clock) {
+        self.0.write().unwrap().clock = Some(clock);
+    }
+
+    /// Overrides the unix timestamp of the `Clock` sysvar, leaving the rest of
+    /// the overridden clock, if any, as it is.
+    pub fn set_unix_timestamp(&self, unix_timestamp: i64) {
+        let mut sysvars = self.0.write().unwrap();
+        sysvars
+            .clock
+            .get_or_insert_with(Clock::default)
+            .unix_timestamp = unix_timestamp;
+    }
+
+    /// Overrides the `Rent` sysvar, and the rent exemption minimums.
+    pub fn set_rent(&self, rent: Rent) {
+        self.0.write().unwrap().rent = Some(rent);
+    }
+
+    /// Overrides the `EpochSchedule` sysvar, and the epoch schedule.
+    pub fn set_epoch_schedule(&self, epoch_schedule: EpochSchedule) {
+        self.0.write().unwrap().epoch_schedule = Some(epoch_schedule);
+    }
+
+    /// Removes the overrides, leaving the sysvars of the transport.
+    pub fn reset(&self) {
+        *self.0.write().unwrap() = Sysvars::default();
+    }
+
+    /// Wraps the transport of an RPC client, e.g. a `MockSender` or an
+    /// `HttpSender`, to answer the requests of the overridden sysvars.
+    ///
+    /// The sender is passed to `RpcClient::new_sender`. Other requests are
+    /// forwarded to `inner`, including simulations, which see the sysvars of
+    /// the transport.
+    pub fn sender<S: RpcSender>(&self, inner: S) -> MockSysvarSender<S> {
+        MockSysvarSender {
+            inner,
+            sysvars: self.clone(),
+        }
+    }
+}
+
+impl Sysvars {
+    // Returns the account of the sysvar at `address`, if it's overridden.
+    fn account(&self, address: &str) -> Option<Value> {
+        let address = Pubkey::from_str(address).ok()?;
+        let account: Account = if address == sysvar::clock::ID {
+            create_account_for_test(self.clock.as_ref()?)
+        } else if address == sysvar::rent::ID {
+            create_account_for_test(self.rent.as_ref()?)
+        } else if address == sysvar::epoch_schedule::ID {
+            create_account_for_test(self.epoch_schedule.as_ref()?)
+        } else {
+            return None;
+        };
+        let account = UiAccount::encode(&address, &account, UiAccountEncoding::Base64, None, None);
+        serde_json::to_value(account).ok()
+    }
+
+    fn context(&self) -> Value {
+        json!({ "slot": self.clock.as_ref().map(|clock| clock.slot).unwrap_or_default() })
+    }
+}
+
+/// RPC transport answering the requests of the sysvars overridden by a
+/// [`MockSysvars`], created with [`MockSysvars::sender`].
+pub struct MockSysvarSender<S> {
+    inner: S,
+    sysvars: MockSysvars,
+}
+
+#[async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for MockSysvarSender<S> {
+    async fn send(&self, request: RpcRequest, params: Value) -> SolanaClientResult<Value> {
+        // Not held across the requests of the inner sender
+        let sysvars = self.sysvars.0.read().unwrap().clone();
+        match request {
+            RpcRequest::GetAccountInfo => {
+                if let Some(account) = params[0].as_str().and_then(|addr| sysvars.account(addr)) {
+                    return Ok(json!({ "context": sysvars.context(), "value": account }));
+                }
+            }
+            RpcRequest::GetMultipleAccounts => {
+                let accounts = params[0]
+                    .as_array()
+                    .map(|addresses| {
+                        addresses
+                            .iter()
+                            .map(|addr| addr.as_str().and_then(|addr| sysvars.account(addr)))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                if accounts.iter().any(Option::is_some) {
+                    let mut response = self.inner.send(request, params).await?;
+                    if let Some(values) = response["value"].as_array_mut() {
+                        for (value, account) in values.iter_mut().zip(accounts) {
+                            if let Some(account) = account {
+                                *value = account;
+                            }
+                        }
+                    }
+                    return Ok(response);
+                }
+            }
+            RpcRequest::GetSlot => {
+                if let Some(clock) = &sysvars.clock {
+                    return Ok(json!(clock.slot));
+                }
+            }
+            RpcRequest::GetMinimumBalanceForRentExemption => {
+                if let (Some(rent), Some(len)) = (&sysvars.rent, params[0].as_u64()) {
+                    return Ok(json!(rent.minimum_balance(len as usize)));
+                }
+            }
+            RpcRequest::GetEpochSchedule => {
+                if let Some(epoch_schedule) = &sysvars.epoch_schedule {
+                    return Ok(serde_json::to_value(epoch_schedule)?);
+                }
+            }
+            _ => {}
+        }
+        self.inner.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_client::rpc_client::{RpcClient, RpcClientConfig};
+    use solana_rpc_client::mock_sender::MockSender;
+    use solana_sdk::account::from_account;
+
+    use super::*;
+
+    #[test]
+    fn test_mock_sysvars() {
+        let sysvars = MockSysvars::new();
+        let rpc = RpcClient::new_sender(
+            sysvars.sender(MockSender::new("succeeds")),
+            RpcClientConfig::default(),
+        );
+
+        sysvars.set_clock(Clock {
+            slot: 42,
+            unix_timestamp: 1_700_000_000,
+            ..Default::default()
+        });
+        let clock: Clock = from_account(&rpc.get_account(&sysvar::clock::ID).unwrap()).unwrap();
+        assert_eq!(clock.unix_timestamp, 1_700_000_000);
+        assert_eq!(rpc.get_slot().unwrap(), 42);
+
+        sysvars.set_unix_timestamp(1_800_000_000);
+        let clock: Clock = from_account(&rpc.get_account(&sysvar::clock::ID).unwrap()).unwrap();
+        assert_eq!((clock.slot, clock.unix_timestamp), (42, 1_800_000_000));
+
+        sysvars.set_rent(Rent {
+            lamports_per_byte_year: 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            rpc.get_minimum_balance_for_rent_exemption(0).unwrap(),
+            Rent {
+                lamports_per_byte_year: 1,
+                ..Default::default()
+            }
+            .minimum_balance(0)
+        );
+    }
+}
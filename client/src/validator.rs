@@ -0,0 +1,286 @@
+use std::{
+    ffi::OsString,
+    fs,
+    net::TcpListener,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{ClientError, Cluster};
+
+/// Time to wait for the validator to become healthy unless configured.
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A `solana-test-validator` process owned by a test, killed when dropped.
+///
+/// Each validator listens on free ports and uses its own ledger, so that tests
+/// can start validators concurrently without any external orchestration.
+///
+/// ```ignore
+/// let validator = TestValidator::builder()
+///     .mint(payer.pubkey())
+///     .program(my_program::ID, "target/deploy/my_program.so")
+///     .spawn()?;
+/// let client = Client::new(Cluster::from(&validator), Rc::new(payer));
+/// let program = client.program(my_program::ID)?;
+/// ```
+pub struct TestValidator {
+    process: Child,
+    rpc_url: String,
+    ws_url: String,
+    ledger: PathBuf,
+    // Whether the ledger is a temporary directory removed on drop.
+    temporary_ledger: bool,
+}
+
+impl TestValidator {
+    pub fn builder() -> TestValidatorBuilder {
+        TestValidatorBuilder::default()
+    }
+
+    /// URL of the JSON RPC of the validator.
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// URL of the websocket of the validator, used by event subscriptions.
+    pub fn ws_url(&self) -> &str {
+        &self.ws_url
+    }
+
+    /// Directory of the ledger of the validator, with its logs in `validator.log`.
+    pub fn ledger(&self) -> &Path {
+        &self.ledger
+    }
+
+    /// Returns an RPC client of the validator.
+    pub fn rpc(&self) -> RpcClient {
+        RpcClient::new(self.rpc_url.clone())
+    }
+}
+
+impl From<&TestValidator> for Cluster {
+    fn from(validator: &TestValidator) -> Self {
+        Cluster::Custom(validator.rpc_url.clone(), validator.ws_url.clone())
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        if self.temporary_ledger {
+            let _ = fs::remove_dir_all(&self.ledger);
+        }
+    }
+}
+
+/// Configuration of a [`TestValidator`], created with
+/// [`TestValidator::builder`].
+#[derive(Debug, Clone)]
+pub struct TestValidatorBuilder {
+    ledger: Option<PathBuf>,
+    rpc_port: Option<u16>,
+    mint: Option<Pubkey>,
+    url: Option<String>,
+    args: Vec<OsString>,
+    startup_timeout: Duration,
+}
+
+impl Default for TestValidatorBuilder {
+    fn default() -> Self {
+        Self {
+            ledger: None,
+            rpc_port: None,
+            mint: None,
+            url: None,
+            args: vec![],
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+        }
+    }
+}
+
+impl TestValidatorBuilder {
+    /// Keeps the ledger in `ledger` instead of a temporary directory removed
+    /// when the validator is dropped.
+    pub fn ledger(mut self, ledger: impl Into<PathBuf>) -> Self {
+        self.ledger = Some(ledger.into());
+        self
+    }
+
+    /// Port of the JSON RPC, the websocket listening on the next one. A free
+    /// port is picked by default.
+    pub fn rpc_port(mut self, rpc_port: u16) -> Self {
+        self.rpc_port = Some(rpc_port);
+        self
+    }
+
+    /// Address receiving the lamports of the genesis, usually the payer of
+    /// the tests.
+    pub fn mint(mut self, mint: Pubkey) -> Self {
+        self.mint = Some(mint);
+        self
+    }
+
+    /// Cluster from which the accounts and programs are cloned.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Deploys the program at `path` to `address` in the genesis.
+    pub fn program(self, address: Pubkey, path: impl AsRef<Path>) -> Self {
+        self.arg("--bpf-program")
+            .arg(address.to_string())
+            .arg(path.as_ref())
+    }
+
+    /// Deploys the program at `path` to `address` in the genesis, upgradeable
+    /// by `upgrade_authority`.
+    pub fn upgradeable_program(
+        self,
+        address: Pubkey,
+        path: impl AsRef<Path>,
+        upgrade_authority: Pubkey,
+    ) -> Self {
+        self.arg("--upgradeable-program")
+            .arg(address.to_string())
+            .arg(path.as_ref())
+            .arg(upgrade_authority.to_string())
+    }
+
+    /// Loads the account at `address` from the JSON file at `path`, in the
+    /// format of `solana account --output json`.
+    pub fn account(self, address: Pubkey, path: impl AsRef<Path>) -> Self {
+        self.arg("--account")
+            .arg(address.to_string())
+            .arg(path.as_ref())
+    }
+
+    /// Clones the account at `address` from the cluster set with
+    /// [`url`](Self::url).
+    pub fn clone_account(self, address: Pubkey) -> Self {
+        self.arg("--clone").arg(address.to_string())
+    }
+
+    /// Clones the upgradeable program at `address`, with its program data,
+    /// from the cluster set with [`url`](Self::url).
+    pub fn clone_upgradeable_program(self, address: Pubkey) -> Self {
+        self.arg("--clone-upgradeable-program")
+            .arg(address.to_string())
+    }
+
+    /// Passes an argument to `solana-test-validator`, e.g. for the flags
+    /// without a method.
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Time after which [`spawn`](Self::spawn) gives up on the validator
+    /// becoming healthy, 30 seconds by default.
+    pub fn startup_timeout(mut self, startup_timeout: Duration) -> Self {
+        self.startup_timeout = startup_timeout;
+        self
+    }
+
+    /// Starts the validator and waits until it's healthy.
+    pub fn spawn(self) -> Result<TestValidator, ClientError> {
+        let rpc_port = match self.rpc_port {
+            Some(rpc_port) => rpc_port,
+            None => free_rpc_port()?,
+        };
+        let (ledger, temporary_ledger) = match self.ledger {
+            Some(ledger) => (ledger, false),
+            None => (
+                std::env::temp_dir().join(format!(
+                    "anchor-test-validator-{}-{rpc_port}",
+                    std::process::id()
+                )),
+                true,
+            ),
+        };
+        fs::create_dir_all(&ledger)?;
+
+        let mut command = Command::new("solana-test-validator");
+        command
+            .arg("--ledger")
+            .arg(&ledger)
+            .arg("--rpc-port")
+            .arg(rpc_port.to_string())
+            .arg("--faucet-port")
+            .arg(free_port()?.to_string())
+            .arg("--quiet");
+        if temporary_ledger {
+            command.arg("--reset");
+        }
+        if let Some(mint) = self.mint {
+            command.arg("--mint").arg(mint.to_string());
+        }
+        if let Some(url) = &self.url {
+            command.arg("--url").arg(url);
+        }
+        let process = command
+            .args(&self.args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        // Killed by `drop` if it doesn't become healthy
+        let mut validator = TestValidator {
+            process,
+            rpc_url: format!("http://127.0.0.1:{rpc_port}"),
+            ws_url: format!("ws://127.0.0.1:{}", rpc_port + 1),
+            ledger,
+            temporary_ledger,
+        };
+        if let Err(e) = validator.wait_until_healthy(self.startup_timeout) {
+            // Kept for its logs
+            validator.temporary_ledger = false;
+            return Err(e);
+        }
+        Ok(validator)
+    }
+}
+
+impl TestValidator {
+    fn wait_until_healthy(&self, timeout: Duration) -> Result<(), ClientError> {
+        let rpc = self.rpc();
+        let start = Instant::now();
+        loop {
+            if rpc.get_health().is_ok() {
+                return Ok(());
+            }
+            if start.elapsed() > timeout {
+                return Err(ClientError::TestValidatorError(format!(
+                    "not healthy after {timeout:?}, check {}",
+                    self.ledger.join("validator.log").display()
+                )));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+// Returns a free port of which the next one is free as well, for the websocket
+// of the validator.
+fn free_rpc_port() -> Result<u16, ClientError> {
+    for _ in 0..100 {
+        let port = free_port()?;
+        if port < u16::MAX && TcpListener::bind(("127.0.0.1", port + 1)).is_ok() {
+            return Ok(port);
+        }
+    }
+    Err(ClientError::TestValidatorError(
+        "no free port for the RPC".to_owned(),
+    ))
+}
+
+fn free_port() -> Result<u16, ClientError> {
+    Ok(TcpListener::bind(("127.0.0.1", 0))?.local_addr()?.port())
+}
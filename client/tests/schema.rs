@@ -0,0 +1,41 @@
+#![cfg(feature = "idl-build")]
+
+use anchor_client::AccountSchema;
+use anchor_lang::prelude::*;
+use anchor_lang_idl::types::{IdlDefinedFields, IdlType, IdlTypeDefTy};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+mod state {
+    use super::*;
+
+    #[account]
+    pub struct Vault {
+        pub amount: u64,
+        pub kind: Kind,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+    pub enum Kind {
+        Fixed,
+        Flexible,
+    }
+}
+
+#[test]
+fn test_types_named_as_in_idl() {
+    let types = <(state::Vault,)>::types();
+    assert_eq!(types.keys().collect::<Vec<_>>(), ["Kind", "Vault"]);
+
+    let vault = &types["Vault"];
+    assert_eq!(vault.name, "Vault");
+    let IdlTypeDefTy::Struct {
+        fields: Some(IdlDefinedFields::Named(fields)),
+    } = &vault.ty
+    else {
+        panic!("Expected the named fields of `Vault`");
+    };
+    assert_eq!(fields[0].ty, IdlType::U64);
+    // References to other types are named the same way
+    assert!(matches!(&fields[1].ty, IdlType::Defined { name, .. } if name == "Kind"));
+}
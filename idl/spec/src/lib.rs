@@ -88,6 +88,8 @@ pub struct IdlInstructionAccount {
     pub optional: bool,
     #[serde(skip_serializing_if = "is_default")]
     pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub addresses: Vec<String>,
     #[serde(skip_serializing_if = "is_default")]
     pub pda: Option<IdlPda>,
     #[serde(default, skip_serializing_if = "is_default")]
@@ -136,6 +138,9 @@ pub struct IdlSeedAccount {
 pub struct IdlAccount {
     pub name: String,
     pub discriminator: IdlDiscriminator,
+    /// Arguments of the instantiation used by the program if the account type is generic.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub generics: Vec<IdlGenericArg>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -182,6 +187,8 @@ pub struct IdlTypeDef {
     pub repr: Option<IdlRepr>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub generics: Vec<IdlTypeDefGeneric>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub layout: Option<IdlLayout>,
     #[serde(rename = "type")]
     pub ty: IdlTypeDefTy,
 }
@@ -214,6 +221,19 @@ pub struct IdlReprModifier {
     pub align: Option<usize>,
 }
 
+/// Memory layout of a `bytemuck` serialized type.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlLayout {
+    pub size: u64,
+    pub offsets: Vec<IdlFieldOffset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdlFieldOffset {
+    pub name: String,
+    pub offset: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "kind", rename_all = "lowercase")]
 pub enum IdlTypeDefGeneric {
@@ -1,16 +1,23 @@
 use std::{
     collections::BTreeMap,
-    env, mem,
-    path::Path,
+    env, fs, mem,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
 use anyhow::{anyhow, Result};
 use regex::Regex;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::types::{Idl, IdlEvent, IdlTypeDef};
 
+/// Environment variable which, when set, makes [`check_golden_idl`] write the
+/// golden IDL instead of comparing the generated IDL against it.
+pub const UPDATE_GOLDEN_IDL_ENV: &str = "ANCHOR_UPDATE_GOLDEN_IDL";
+
+/// Environment variable of the program path, set while the IDL is being built.
+const PROGRAM_PATH_ENV: &str = "ANCHOR_IDL_BUILD_PROGRAM_PATH";
+
 /// A trait that types must implement in order to include the type in the IDL definition.
 ///
 /// This trait is automatically implemented for Anchor all types that use the `AnchorSerialize`
@@ -50,7 +57,96 @@ pub fn build_idl(
     skip_lint: bool,
     no_docs: bool,
 ) -> Result<Idl> {
-    let idl = build(program_path.as_ref(), resolution, skip_lint, no_docs)?;
+    build_idl_with(program_path.as_ref(), resolution, skip_lint, no_docs, None)
+}
+
+/// Regenerates the IDL of the program at `program_path` and compares it with
+/// the committed golden IDL at `golden_path`, returning an error listing the
+/// changes if they differ, so that unintended interface changes fail the tests
+/// or the build.
+///
+/// A missing golden IDL is written, and the golden IDL is rewritten when
+/// [`UPDATE_GOLDEN_IDL_ENV`] is set.
+///
+/// It can be called from a test, or from the build script of the program with
+/// `anchor-lang-idl` as a build dependency with the `build` feature:
+///
+/// ```ignore
+/// fn main() {
+///     println!("cargo:rerun-if-changed=src");
+///     anchor_lang_idl::build::check_golden_idl(".", "idl.json").unwrap();
+/// }
+/// ```
+pub fn check_golden_idl(
+    program_path: impl AsRef<Path>,
+    golden_path: impl AsRef<Path>,
+) -> Result<()> {
+    // The build of the IDL runs the build script of the program again
+    if env::var_os(PROGRAM_PATH_ENV).is_some() {
+        return Ok(());
+    }
+
+    // Built in another target directory, as the one of the build running the
+    // check is locked
+    let target_dir = env::var_os("OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir)
+        .join("anchor-golden-idl");
+    let idl = build_idl_with(program_path.as_ref(), true, false, false, Some(&target_dir))?;
+
+    let update = env::var_os(UPDATE_GOLDEN_IDL_ENV).is_some();
+    compare_golden_idl(&idl, golden_path.as_ref(), update)
+}
+
+/// Compares the IDL with the golden IDL at `golden_path`, writing the golden
+/// IDL instead if it's missing or if `update` is set.
+fn compare_golden_idl(idl: &Idl, golden_path: &Path, update: bool) -> Result<()> {
+    let write = || -> Result<()> {
+        fs::write(golden_path, serde_json::to_string_pretty(idl)? + "\n")
+            .map_err(|e| anyhow!("Unable to write {}: {e}", golden_path.display()))
+    };
+    let golden = match fs::read_to_string(golden_path) {
+        Ok(golden) => golden,
+        Err(_) => return write(),
+    };
+    let golden = serde_json::from_str::<serde_json::Value>(&golden)
+        .map_err(|e| anyhow!("Invalid golden IDL {}: {e}", golden_path.display()))?;
+    let generated = serde_json::to_value(idl)?;
+    if golden == generated {
+        return Ok(());
+    }
+    if update {
+        return write();
+    }
+
+    // Changes the semantic diff doesn't cover, e.g. of docs, are listed by
+    // their path in the IDL
+    let mut changes = match serde_json::from_value::<Idl>(golden.clone()) {
+        Ok(golden) => crate::diff::diff(&golden, idl)
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        Err(_) => vec![],
+    };
+    if changes.is_empty() {
+        json_changes(&golden, &generated, "", &mut changes);
+    }
+    Err(anyhow!(
+        "The IDL doesn't match the golden IDL {}, rerun with {UPDATE_GOLDEN_IDL_ENV}=1 to update \
+        it:\n{}",
+        golden_path.display(),
+        changes.join("\n")
+    ))
+}
+
+fn build_idl_with(
+    program_path: &Path,
+    resolution: bool,
+    skip_lint: bool,
+    no_docs: bool,
+    target_dir: Option<&Path>,
+) -> Result<Idl> {
+    let idl = build(program_path, resolution, skip_lint, no_docs, target_dir)?;
     let idl = convert_module_paths(idl);
     let idl = sort(idl);
     verify(&idl)?;
@@ -59,7 +155,13 @@ pub fn build_idl(
 }
 
 /// Build IDL.
-fn build(program_path: &Path, resolution: bool, skip_lint: bool, no_docs: bool) -> Result<Idl> {
+fn build(
+    program_path: &Path,
+    resolution: bool,
+    skip_lint: bool,
+    no_docs: bool,
+    target_dir: Option<&Path>,
+) -> Result<Idl> {
     // `nightly` toolchain is currently required for building the IDL.
     let toolchain = std::env::var("RUSTUP_TOOLCHAIN")
         .map(|toolchain| format!("+{}", toolchain))
@@ -67,7 +169,11 @@ fn build(program_path: &Path, resolution: bool, skip_lint: bool, no_docs: bool)
 
     install_toolchain_if_needed(&toolchain)?;
 
-    let output = Command::new("cargo")
+    let mut command = Command::new("cargo");
+    if let Some(target_dir) = target_dir {
+        command.env("CARGO_TARGET_DIR", target_dir);
+    }
+    let output = command
         .args([
             &toolchain,
             "test",
@@ -90,7 +196,7 @@ fn build(program_path: &Path, resolution: bool, skip_lint: bool, no_docs: bool)
             "ANCHOR_IDL_BUILD_SKIP_LINT",
             if skip_lint { "TRUE" } else { "FALSE" },
         )
-        .env("ANCHOR_IDL_BUILD_PROGRAM_PATH", program_path)
+        .env(PROGRAM_PATH_ENV, program_path)
         .env("RUSTFLAGS", "--cfg procmacro2_semver_exempt")
         .current_dir(program_path)
         .stderr(Stdio::inherit())
@@ -220,7 +326,10 @@ fn install_toolchain_if_needed(toolchain: &str) -> Result<()> {
 }
 
 /// Convert paths to name if there are no conflicts.
-fn convert_module_paths(idl: Idl) -> Idl {
+///
+/// Applies to any part of an IDL, e.g. the type definitions created by
+/// [`IdlBuild::create_type`], whose names are the full paths of the types.
+pub fn convert_module_paths<T: Serialize + DeserializeOwned>(idl: T) -> T {
     let idl = serde_json::to_string(&idl).unwrap();
     let idl = Regex::new(r#""((\w+::)+)(\w+)""#)
         .unwrap()
@@ -255,6 +364,38 @@ fn sort(mut idl: Idl) -> Idl {
     idl
 }
 
+/// Lists the paths of the values that differ between two JSON values.
+fn json_changes(
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    path: &str,
+    changes: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let path = format!("{path}.{key}");
+                match new.get(key) {
+                    Some(new_value) => json_changes(old_value, new_value, &path, changes),
+                    None => changes.push(format!("{path}: removed")),
+                }
+            }
+            for key in new.keys().filter(|key| !old.contains_key(*key)) {
+                changes.push(format!("{path}.{key}: added"));
+            }
+        }
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (i, (old, new)) in old.iter().zip(new).enumerate() {
+                json_changes(old, new, &format!("{path}[{i}]"), changes);
+            }
+        }
+        _ if old != new => changes.push(format!("{path}: {old} -> {new}")),
+        _ => {}
+    }
+}
+
 /// Verify IDL is valid.
 fn verify(idl: &Idl) -> Result<()> {
     // Check full path accounts
@@ -272,3 +413,119 @@ fn verify(idl: &Idl) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::{IdlDefinedFields, IdlField, IdlMetadata, IdlType, IdlTypeDefTy};
+
+    fn idl(fields: Vec<IdlField>) -> Idl {
+        Idl {
+            address: "Test111111111111111111111111111111111111111".into(),
+            metadata: IdlMetadata {
+                name: "test".into(),
+                version: "0.1.0".into(),
+                spec: "0.1.0".into(),
+                description: None,
+                repository: None,
+                dependencies: vec![],
+                contact: None,
+                deployments: None,
+            },
+            docs: vec![],
+            instructions: vec![],
+            accounts: vec![],
+            events: vec![],
+            errors: vec![],
+            types: vec![IdlTypeDef {
+                name: "Data".into(),
+                docs: vec![],
+                serialization: Default::default(),
+                repr: None,
+                generics: vec![],
+                layout: None,
+                ty: IdlTypeDefTy::Struct {
+                    fields: Some(IdlDefinedFields::Named(fields)),
+                },
+            }],
+            constants: vec![],
+        }
+    }
+
+    fn field(ty: IdlType) -> IdlField {
+        IdlField {
+            name: "amount".into(),
+            docs: vec![],
+            ty,
+        }
+    }
+
+    fn golden_path(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("anchor-golden-idl-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_golden_idl_missing() {
+        let path = golden_path("missing.json");
+        let idl = idl(vec![field(IdlType::U64)]);
+
+        compare_golden_idl(&idl, &path, false).unwrap();
+        let golden = fs::read_to_string(&path).unwrap();
+        assert_eq!(golden, serde_json::to_string_pretty(&idl).unwrap() + "\n");
+    }
+
+    #[test]
+    fn test_golden_idl_equal() {
+        let path = golden_path("equal.json");
+        let idl = idl(vec![field(IdlType::U64)]);
+        compare_golden_idl(&idl, &path, false).unwrap();
+        let golden = fs::read_to_string(&path).unwrap();
+
+        compare_golden_idl(&idl, &path, false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), golden);
+    }
+
+    #[test]
+    fn test_golden_idl_changed() {
+        let path = golden_path("changed.json");
+        compare_golden_idl(&idl(vec![field(IdlType::U64)]), &path, false).unwrap();
+
+        // Changes of the interface are listed by the semantic diff
+        let changed = idl(vec![field(IdlType::U32)]);
+        let err = compare_golden_idl(&changed, &path, false).unwrap_err();
+        assert!(err.to_string().contains("doesn't match the golden IDL"));
+        assert!(err.to_string().lines().count() > 1);
+
+        // The others by their path in the IDL
+        let mut documented = idl(vec![field(IdlType::U64)]);
+        documented.docs = vec!["Test program".into()];
+        let err = compare_golden_idl(&documented, &path, false).unwrap_err();
+        assert!(err.to_string().ends_with(".docs: added"));
+
+        compare_golden_idl(&changed, &path, true).unwrap();
+        compare_golden_idl(&changed, &path, false).unwrap();
+    }
+
+    #[test]
+    fn test_json_changes() {
+        let old = json!({ "a": 1, "b": [1, 2], "c": true });
+        let new = json!({ "a": 2, "b": [1, 3], "d": null });
+
+        let mut changes = vec![];
+        json_changes(&old, &new, "", &mut changes);
+        assert_eq!(
+            changes,
+            [".a: 1 -> 2", ".b[1]: 2 -> 3", ".c: removed", ".d: added"]
+        );
+
+        let mut changes = vec![];
+        json_changes(&old, &old, "", &mut changes);
+        assert!(changes.is_empty());
+    }
+}
@@ -0,0 +1,716 @@
+//! Encoding and decoding of program data with the program's IDL.
+//!
+//! Values are represented as JSON:
+//!
+//! - Integers are numbers, except for 128-bit integers which are strings in order not to lose
+//!   precision (both are accepted when encoding)
+//! - Public keys are base58 strings, bytes are arrays of numbers
+//! - Options are either `null` or their value
+//! - Structs are objects if their fields are named, arrays otherwise
+//! - Enums are objects with the variant name as the only key, e.g. `{ "Confirmed": { "slot": 1 } }`,
+//!   unit variants having an empty object (their name is also accepted when encoding)
+//!
+//! Types with a memory layout, i.e. `zero_copy` accounts, are decoded with their field offsets.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+use crate::types::{
+    Idl, IdlArrayLen, IdlDefinedFields, IdlGenericArg, IdlLayout, IdlType, IdlTypeDef,
+    IdlTypeDefGeneric, IdlTypeDefTy,
+};
+
+/// Generic arguments by the name of their parameter.
+pub(crate) type Generics = BTreeMap<String, IdlGenericArg>;
+
+/// Decodes the data of an account, returning the name of the account and its value.
+pub fn decode_account(idl: &Idl, data: &[u8]) -> Result<(String, Value)> {
+    let acc = idl
+        .accounts
+        .iter()
+        .filter(|acc| data.starts_with(&acc.discriminator))
+        .max_by_key(|acc| acc.discriminator.len())
+        .ok_or_else(|| anyhow!("The data doesn't match any account of the IDL"))?;
+    let ty = IdlType::Defined {
+        name: acc.name.clone(),
+        generics: acc.generics.clone(),
+    };
+    let value = decode_type(idl, &ty, &data[acc.discriminator.len()..])?;
+    Ok((acc.name.clone(), value))
+}
+
+/// Decodes the data of an event, returning the name of the event and its value.
+pub fn decode_event(idl: &Idl, data: &[u8]) -> Result<(String, Value)> {
+    let event = idl
+        .events
+        .iter()
+        .filter(|event| data.starts_with(&event.discriminator))
+        .max_by_key(|event| event.discriminator.len())
+        .ok_or_else(|| anyhow!("The data doesn't match any event of the IDL"))?;
+    let ty = IdlType::Defined {
+        name: event.name.clone(),
+        generics: vec![],
+    };
+    let value = decode_type(idl, &ty, &data[event.discriminator.len()..])?;
+    Ok((event.name.clone(), value))
+}
+
+/// Decodes the data of an instruction, returning the name of the instruction and its arguments
+/// by name.
+pub fn decode_instruction(idl: &Idl, data: &[u8]) -> Result<(String, Value)> {
+    let ix = idl
+        .instructions
+        .iter()
+        .filter(|ix| data.starts_with(&ix.discriminator))
+        .max_by_key(|ix| ix.discriminator.len())
+        .ok_or_else(|| anyhow!("The data doesn't match any instruction of the IDL"))?;
+
+    let mut decoder = Decoder::new(idl, &data[ix.discriminator.len()..]);
+    let mut args = Map::new();
+    for arg in &ix.args {
+        let value = decoder
+            .decode(&arg.ty, &Generics::new())
+            .map_err(|e| anyhow!("Failed to decode argument `{}`: {e}", arg.name))?;
+        args.insert(arg.name.clone(), value);
+    }
+    Ok((ix.name.clone(), Value::Object(args)))
+}
+
+/// Encodes the data of the instruction `name` from its arguments by name.
+pub fn encode_instruction(idl: &Idl, name: &str, args: &Value) -> Result<Vec<u8>> {
+    let ix = idl
+        .instructions
+        .iter()
+        .find(|ix| ix.name == name)
+        .ok_or_else(|| anyhow!("Instruction `{name}` not found in the IDL"))?;
+
+    let mut encoder = Encoder::new(idl);
+    encoder.data.extend(&ix.discriminator);
+    for arg in &ix.args {
+        let value = args
+            .get(&arg.name)
+            .ok_or_else(|| anyhow!("Missing argument `{}`", arg.name))?;
+        encoder
+            .encode(&arg.ty, value, &Generics::new())
+            .map_err(|e| anyhow!("Failed to encode argument `{}`: {e}", arg.name))?;
+    }
+    Ok(encoder.data)
+}
+
+/// Decodes a value of type `ty`, ignoring the remaining data.
+pub fn decode_type(idl: &Idl, ty: &IdlType, data: &[u8]) -> Result<Value> {
+    Decoder::new(idl, data).decode(ty, &Generics::new())
+}
+
+/// Encodes a value of type `ty`.
+pub fn encode_type(idl: &Idl, ty: &IdlType, value: &Value) -> Result<Vec<u8>> {
+    let mut encoder = Encoder::new(idl);
+    encoder.encode(ty, value, &Generics::new())?;
+    Ok(encoder.data)
+}
+
+struct Decoder<'a> {
+    idl: &'a Idl,
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(idl: &'a Idl, data: &'a [u8]) -> Self {
+        Self {
+            idl,
+            data,
+            offset: 0,
+        }
+    }
+
+    fn read(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or_else(|| anyhow!("Unexpected end of data at offset {}", self.offset))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.read(N)?.try_into().unwrap())
+    }
+
+    fn read_len(&mut self) -> Result<usize> {
+        Ok(u32::from_le_bytes(self.read_array()?) as usize)
+    }
+
+    fn decode(&mut self, ty: &IdlType, generics: &Generics) -> Result<Value> {
+        Ok(match ty {
+            IdlType::Bool => match self.read(1)?[0] {
+                0 => Value::from(false),
+                1 => Value::from(true),
+                tag => return Err(anyhow!("Invalid bool value: {tag}")),
+            },
+            IdlType::U8 => Value::from(self.read(1)?[0]),
+            IdlType::I8 => Value::from(self.read(1)?[0] as i8),
+            IdlType::U16 => Value::from(u16::from_le_bytes(self.read_array()?)),
+            IdlType::I16 => Value::from(i16::from_le_bytes(self.read_array()?)),
+            IdlType::U32 => Value::from(u32::from_le_bytes(self.read_array()?)),
+            IdlType::I32 => Value::from(i32::from_le_bytes(self.read_array()?)),
+            IdlType::F32 => Value::from(f32::from_le_bytes(self.read_array()?)),
+            IdlType::U64 => Value::from(u64::from_le_bytes(self.read_array()?)),
+            IdlType::I64 => Value::from(i64::from_le_bytes(self.read_array()?)),
+            IdlType::F64 => Value::from(f64::from_le_bytes(self.read_array()?)),
+            IdlType::U128 => Value::from(u128::from_le_bytes(self.read_array()?).to_string()),
+            IdlType::I128 => Value::from(i128::from_le_bytes(self.read_array()?).to_string()),
+            IdlType::Bytes => {
+                let len = self.read_len()?;
+                Value::from(self.read(len)?)
+            }
+            IdlType::String => {
+                let len = self.read_len()?;
+                Value::from(String::from_utf8(self.read(len)?.to_vec())?)
+            }
+            IdlType::Pubkey => Value::from(bs58::encode(self.read(32)?).into_string()),
+            IdlType::Option(ty) => match self.read(1)?[0] {
+                0 => Value::Null,
+                1 => self.decode(ty, generics)?,
+                tag => return Err(anyhow!("Invalid option tag: {tag}")),
+            },
+            IdlType::Vec(ty) => {
+                let len = self.read_len()?;
+                let values = (0..len)
+                    .map(|_| self.decode(ty, generics))
+                    .collect::<Result<Vec<_>>>()?;
+                Value::Array(values)
+            }
+            IdlType::Array(ty, len) => {
+                let len = array_len(len, generics)?;
+                let values = (0..len)
+                    .map(|_| self.decode(ty, generics))
+                    .collect::<Result<Vec<_>>>()?;
+                Value::Array(values)
+            }
+            IdlType::Defined {
+                name,
+                generics: args,
+            } => self.decode_defined(name, args, generics)?,
+            IdlType::Generic(_) => self.decode(&substitute(ty, generics)?, &Generics::new())?,
+            _ => return Err(anyhow!("Type `{ty:?}` is not supported")),
+        })
+    }
+
+    fn decode_defined(
+        &mut self,
+        name: &str,
+        args: &[IdlGenericArg],
+        generics: &Generics,
+    ) -> Result<Value> {
+        let ty_def = find_type(self.idl, name)?;
+        let generics = type_generics(ty_def, args, generics)?;
+        match &ty_def.ty {
+            IdlTypeDefTy::Struct { fields } => {
+                self.decode_fields(fields.as_ref(), ty_def.layout.as_ref(), &generics)
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                let index = self.read(1)?[0];
+                let variant = variants
+                    .get(index as usize)
+                    .ok_or_else(|| anyhow!("Invalid variant {index} of enum `{name}`"))?;
+                let value = self.decode_fields(variant.fields.as_ref(), None, &generics)?;
+
+                let mut values = Map::new();
+                values.insert(variant.name.clone(), value);
+                Ok(Value::Object(values))
+            }
+            IdlTypeDefTy::Type { alias } => self.decode(alias, &generics),
+        }
+    }
+
+    fn decode_fields(
+        &mut self,
+        fields: Option<&IdlDefinedFields>,
+        layout: Option<&IdlLayout>,
+        generics: &Generics,
+    ) -> Result<Value> {
+        let start = self.offset;
+        let value = match fields {
+            None => Value::Object(Map::new()),
+            Some(IdlDefinedFields::Named(fields)) => {
+                let mut values = Map::new();
+                for field in fields {
+                    if let Some(offset) = field_offset(layout, &field.name) {
+                        self.offset = start + offset;
+                    }
+                    values.insert(field.name.clone(), self.decode(&field.ty, generics)?);
+                }
+                Value::Object(values)
+            }
+            Some(IdlDefinedFields::Tuple(fields)) => {
+                let values = fields
+                    .iter()
+                    .map(|ty| self.decode(ty, generics))
+                    .collect::<Result<Vec<_>>>()?;
+                Value::Array(values)
+            }
+        };
+        if let Some(layout) = layout {
+            self.offset = start + layout.size as usize;
+        }
+        Ok(value)
+    }
+}
+
+struct Encoder<'a> {
+    idl: &'a Idl,
+    data: Vec<u8>,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(idl: &'a Idl) -> Self {
+        Self { idl, data: vec![] }
+    }
+
+    fn encode_len(&mut self, len: usize) -> Result<()> {
+        let len = u32::try_from(len).map_err(|_| anyhow!("Length {len} is too large"))?;
+        self.data.extend(len.to_le_bytes());
+        Ok(())
+    }
+
+    fn encode(&mut self, ty: &IdlType, value: &Value, generics: &Generics) -> Result<()> {
+        match ty {
+            IdlType::Bool => {
+                let value = value
+                    .as_bool()
+                    .ok_or_else(|| anyhow!("Expected a boolean, found `{value}`"))?;
+                self.data.push(value as u8);
+            }
+            IdlType::U8 => self.data.extend(parse::<u8>(value)?.to_le_bytes()),
+            IdlType::I8 => self.data.extend(parse::<i8>(value)?.to_le_bytes()),
+            IdlType::U16 => self.data.extend(parse::<u16>(value)?.to_le_bytes()),
+            IdlType::I16 => self.data.extend(parse::<i16>(value)?.to_le_bytes()),
+            IdlType::U32 => self.data.extend(parse::<u32>(value)?.to_le_bytes()),
+            IdlType::I32 => self.data.extend(parse::<i32>(value)?.to_le_bytes()),
+            IdlType::F32 => self.data.extend(parse::<f32>(value)?.to_le_bytes()),
+            IdlType::U64 => self.data.extend(parse::<u64>(value)?.to_le_bytes()),
+            IdlType::I64 => self.data.extend(parse::<i64>(value)?.to_le_bytes()),
+            IdlType::F64 => self.data.extend(parse::<f64>(value)?.to_le_bytes()),
+            IdlType::U128 => self.data.extend(parse::<u128>(value)?.to_le_bytes()),
+            IdlType::I128 => self.data.extend(parse::<i128>(value)?.to_le_bytes()),
+            IdlType::Bytes => {
+                let bytes = array(value)?
+                    .iter()
+                    .map(parse::<u8>)
+                    .collect::<Result<Vec<_>>>()?;
+                self.encode_len(bytes.len())?;
+                self.data.extend(bytes);
+            }
+            IdlType::String => {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Expected a string, found `{value}`"))?;
+                self.encode_len(value.len())?;
+                self.data.extend(value.as_bytes());
+            }
+            IdlType::Pubkey => {
+                let pubkey = value
+                    .as_str()
+                    .and_then(|value| bs58::decode(value).into_vec().ok())
+                    .filter(|pubkey| pubkey.len() == 32)
+                    .ok_or_else(|| anyhow!("Expected a public key, found `{value}`"))?;
+                self.data.extend(pubkey);
+            }
+            IdlType::Option(ty) => match value {
+                Value::Null => self.data.push(0),
+                _ => {
+                    self.data.push(1);
+                    self.encode(ty, value, generics)?;
+                }
+            },
+            IdlType::Vec(ty) => {
+                let values = array(value)?;
+                self.encode_len(values.len())?;
+                for value in values {
+                    self.encode(ty, value, generics)?;
+                }
+            }
+            IdlType::Array(ty, len) => {
+                let len = array_len(len, generics)?;
+                let values = array(value)?;
+                if values.len() != len {
+                    return Err(anyhow!(
+                        "Expected an array of length {len}, found length {}",
+                        values.len()
+                    ));
+                }
+                for value in values {
+                    self.encode(ty, value, generics)?;
+                }
+            }
+            IdlType::Defined {
+                name,
+                generics: args,
+            } => self.encode_defined(name, args, value, generics)?,
+            IdlType::Generic(_) => {
+                self.encode(&substitute(ty, generics)?, value, &Generics::new())?
+            }
+            _ => return Err(anyhow!("Type `{ty:?}` is not supported")),
+        }
+
+        Ok(())
+    }
+
+    fn encode_defined(
+        &mut self,
+        name: &str,
+        args: &[IdlGenericArg],
+        value: &Value,
+        generics: &Generics,
+    ) -> Result<()> {
+        let ty_def = find_type(self.idl, name)?;
+        let generics = type_generics(ty_def, args, generics)?;
+        match &ty_def.ty {
+            IdlTypeDefTy::Struct { fields } => {
+                self.encode_fields(fields.as_ref(), ty_def.layout.as_ref(), value, &generics)
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                let unit = Value::Null;
+                let (variant_name, value) = match value {
+                    Value::String(variant) => (variant.as_str(), &unit),
+                    Value::Object(object) if object.len() == 1 => {
+                        let (variant, value) = object.iter().next().unwrap();
+                        (variant.as_str(), value)
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "Expected a variant of enum `{name}`, found `{value}`"
+                        ))
+                    }
+                };
+                let index = variants
+                    .iter()
+                    .position(|variant| variant.name == variant_name)
+                    .ok_or_else(|| anyhow!("Enum `{name}` has no variant `{variant_name}`"))?;
+                self.data.push(index as u8);
+                self.encode_fields(variants[index].fields.as_ref(), None, value, &generics)
+            }
+            IdlTypeDefTy::Type { alias } => self.encode(alias, value, &generics),
+        }
+    }
+
+    fn encode_fields(
+        &mut self,
+        fields: Option<&IdlDefinedFields>,
+        layout: Option<&IdlLayout>,
+        value: &Value,
+        generics: &Generics,
+    ) -> Result<()> {
+        let start = self.data.len();
+        match fields {
+            None => (),
+            Some(IdlDefinedFields::Named(fields)) => {
+                for field in fields {
+                    let value = value
+                        .get(&field.name)
+                        .ok_or_else(|| anyhow!("Missing field `{}`", field.name))?;
+                    // Fill the padding before the field
+                    if let Some(offset) = field_offset(layout, &field.name) {
+                        self.data.resize(start + offset, 0);
+                    }
+                    self.encode(&field.ty, value, generics)?;
+                }
+            }
+            Some(IdlDefinedFields::Tuple(fields)) => {
+                let values = array(value)?;
+                if values.len() != fields.len() {
+                    return Err(anyhow!(
+                        "Expected {} fields, found {}",
+                        fields.len(),
+                        values.len()
+                    ));
+                }
+                for (ty, value) in fields.iter().zip(values) {
+                    self.encode(ty, value, generics)?;
+                }
+            }
+        }
+        if let Some(layout) = layout {
+            self.data.resize(start + layout.size as usize, 0);
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn find_type<'a>(idl: &'a Idl, name: &str) -> Result<&'a IdlTypeDef> {
+    idl.types
+        .iter()
+        .find(|ty| ty.name == name)
+        .ok_or_else(|| anyhow!("Type `{name}` not found in the IDL"))
+}
+
+fn field_offset(layout: Option<&IdlLayout>, name: &str) -> Option<usize> {
+    layout?
+        .offsets
+        .iter()
+        .find(|offset| offset.name == name)
+        .map(|offset| offset.offset as usize)
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("Expected an array, found `{value}`"))
+}
+
+/// Parses a number from either a JSON number or a string.
+fn parse<T: std::str::FromStr>(value: &Value) -> Result<T> {
+    let number = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(number) => number.clone(),
+        _ => return Err(anyhow!("Expected a number, found `{value}`")),
+    };
+    number
+        .parse()
+        .map_err(|_| anyhow!("Invalid number `{number}`"))
+}
+
+/// Maps the generic parameters of a type definition to the given arguments, which can refer to
+/// the generic parameters of the type being encoded or decoded.
+pub(crate) fn type_generics(
+    ty_def: &IdlTypeDef,
+    args: &[IdlGenericArg],
+    generics: &Generics,
+) -> Result<Generics> {
+    if ty_def.generics.len() != args.len() {
+        return Err(anyhow!(
+            "Type `{}` has {} generic parameters but {} arguments were given",
+            ty_def.name,
+            ty_def.generics.len(),
+            args.len()
+        ));
+    }
+
+    ty_def
+        .generics
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| {
+            let name = match param {
+                IdlTypeDefGeneric::Type { name } => name,
+                IdlTypeDefGeneric::Const { name, .. } => name,
+            };
+            Ok((name.to_owned(), resolve_generic_arg(arg, generics)?))
+        })
+        .collect()
+}
+
+fn resolve_generic_arg(arg: &IdlGenericArg, generics: &Generics) -> Result<IdlGenericArg> {
+    match arg {
+        // Const generic parameters used as arguments are stored as types
+        IdlGenericArg::Type {
+            ty: IdlType::Generic(name),
+        } => generics
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Generic parameter `{name}` is not defined")),
+        IdlGenericArg::Type { ty } => Ok(IdlGenericArg::Type {
+            ty: substitute(ty, generics)?,
+        }),
+        IdlGenericArg::Const { .. } => Ok(arg.clone()),
+    }
+}
+
+/// Replaces the generic parameters of `ty` with their arguments.
+pub(crate) fn substitute(ty: &IdlType, generics: &Generics) -> Result<IdlType> {
+    Ok(match ty {
+        IdlType::Generic(name) => match generics.get(name) {
+            Some(IdlGenericArg::Type { ty }) => ty.clone(),
+            _ => return Err(anyhow!("Generic type `{name}` is not defined")),
+        },
+        IdlType::Option(ty) => IdlType::Option(Box::new(substitute(ty, generics)?)),
+        IdlType::Vec(ty) => IdlType::Vec(Box::new(substitute(ty, generics)?)),
+        IdlType::Array(ty, len) => IdlType::Array(
+            Box::new(substitute(ty, generics)?),
+            IdlArrayLen::Value(array_len(len, generics)?),
+        ),
+        IdlType::Defined {
+            name,
+            generics: args,
+        } => IdlType::Defined {
+            name: name.clone(),
+            generics: args
+                .iter()
+                .map(|arg| resolve_generic_arg(arg, generics))
+                .collect::<Result<_>>()?,
+        },
+        _ => ty.clone(),
+    })
+}
+
+pub(crate) fn array_len(len: &IdlArrayLen, generics: &Generics) -> Result<usize> {
+    match len {
+        IdlArrayLen::Value(len) => Ok(*len),
+        IdlArrayLen::Generic(name) => match generics.get(name) {
+            Some(IdlGenericArg::Const { value }) => value
+                .parse()
+                .map_err(|_| anyhow!("Invalid array length `{value}`")),
+            _ => Err(anyhow!("Array length `{name}` is not defined")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::{
+        IdlAccount, IdlEnumVariant, IdlField, IdlInstruction, IdlMetadata, IdlSerialization,
+    };
+
+    fn field(name: &str, ty: IdlType) -> IdlField {
+        IdlField {
+            name: name.into(),
+            docs: vec![],
+            ty,
+        }
+    }
+
+    fn ty_def(name: &str, generics: Vec<IdlTypeDefGeneric>, ty: IdlTypeDefTy) -> IdlTypeDef {
+        IdlTypeDef {
+            name: name.into(),
+            docs: vec![],
+            serialization: IdlSerialization::Borsh,
+            repr: None,
+            generics,
+            layout: None,
+            ty,
+        }
+    }
+
+    fn idl() -> Idl {
+        Idl {
+            address: "Test111111111111111111111111111111111111111".into(),
+            metadata: IdlMetadata {
+                name: "test".into(),
+                version: "0.1.0".into(),
+                spec: "0.1.0".into(),
+                description: None,
+                repository: None,
+                dependencies: vec![],
+                contact: None,
+                deployments: None,
+            },
+            docs: vec![],
+            instructions: vec![IdlInstruction {
+                name: "deposit".into(),
+                docs: vec![],
+                discriminator: vec![1, 2, 3, 4, 5, 6, 7, 8],
+                accounts: vec![],
+                args: vec![
+                    field("amount", IdlType::U64),
+                    field("memo", IdlType::Option(Box::new(IdlType::String))),
+                    field(
+                        "state",
+                        IdlType::Defined {
+                            name: "State".into(),
+                            generics: vec![],
+                        },
+                    ),
+                ],
+                returns: None,
+            }],
+            accounts: vec![IdlAccount {
+                name: "Vault".into(),
+                discriminator: vec![8, 7, 6, 5, 4, 3, 2, 1],
+                generics: vec![
+                    IdlGenericArg::Type { ty: IdlType::U16 },
+                    IdlGenericArg::Const { value: "2".into() },
+                ],
+            }],
+            events: vec![],
+            errors: vec![],
+            types: vec![
+                ty_def(
+                    "State",
+                    vec![],
+                    IdlTypeDefTy::Enum {
+                        variants: vec![
+                            IdlEnumVariant {
+                                name: "Pending".into(),
+                                fields: None,
+                            },
+                            IdlEnumVariant {
+                                name: "Confirmed".into(),
+                                fields: Some(IdlDefinedFields::Named(vec![field(
+                                    "slot",
+                                    IdlType::U128,
+                                )])),
+                            },
+                        ],
+                    },
+                ),
+                ty_def(
+                    "Vault",
+                    vec![
+                        IdlTypeDefGeneric::Type { name: "T".into() },
+                        IdlTypeDefGeneric::Const {
+                            name: "N".into(),
+                            ty: "usize".into(),
+                        },
+                    ],
+                    IdlTypeDefTy::Struct {
+                        fields: Some(IdlDefinedFields::Named(vec![field(
+                            "values",
+                            IdlType::Array(
+                                Box::new(IdlType::Generic("T".into())),
+                                IdlArrayLen::Generic("N".into()),
+                            ),
+                        )])),
+                    },
+                ),
+            ],
+            constants: vec![],
+        }
+    }
+
+    #[test]
+    fn instruction_roundtrip() {
+        let idl = idl();
+        let args = json!({
+            "amount": 5,
+            "memo": null,
+            "state": { "Confirmed": { "slot": "340282366920938463463374607431768211455" } },
+        });
+        let data = encode_instruction(&idl, "deposit", &args).unwrap();
+        assert_eq!(data.len(), 8 + 8 + 1 + 1 + 16);
+
+        let (name, decoded) = decode_instruction(&idl, &data).unwrap();
+        assert_eq!(name, "deposit");
+        assert_eq!(decoded, args);
+    }
+
+    #[test]
+    fn generic_account() {
+        let idl = idl();
+        let data = [8, 7, 6, 5, 4, 3, 2, 1, 1, 0, 2, 0];
+        let (name, decoded) = decode_account(&idl, &data).unwrap();
+        assert_eq!(name, "Vault");
+        assert_eq!(decoded, json!({ "values": [1, 2] }));
+    }
+
+    #[test]
+    fn invalid_option_tag() {
+        let idl = idl();
+        let args = json!({ "amount": 5, "memo": null, "state": "Pending" });
+        let mut data = encode_instruction(&idl, "deposit", &args).unwrap();
+        data[16] = 2;
+        assert!(decode_instruction(&idl, &data).is_err());
+    }
+
+    #[test]
+    fn missing_argument() {
+        let idl = idl();
+        assert!(encode_instruction(&idl, "deposit", &json!({ "amount": 5 })).is_err());
+    }
+}
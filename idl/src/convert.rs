@@ -11,6 +11,12 @@ use crate::types::Idl;
 /// **Note:** For legacy IDLs, `idl.metadata.address` field is required to be populated with
 /// program's address otherwise an error will be returned.
 pub fn convert_idl(idl: &[u8]) -> Result<Idl> {
+    convert_idl_with_report(idl).map(|(idl, _)| idl)
+}
+
+/// Same as [`convert_idl`], but also returns the description of each feature of a legacy
+/// IDL that couldn't be represented in the new spec.
+pub fn convert_idl_with_report(idl: &[u8]) -> Result<(Idl, Vec<String>)> {
     let value = serde_json::from_slice::<serde_json::Value>(idl)?;
     let spec = value
         .get("metadata")
@@ -19,19 +25,38 @@ pub fn convert_idl(idl: &[u8]) -> Result<Idl> {
     match spec {
         // New standard
         Some(spec) => match spec {
-            "0.1.0" => serde_json::from_value(value).map_err(Into::into),
+            "0.1.0" => Ok((serde_json::from_value(value)?, vec![])),
             _ => Err(anyhow!("IDL spec not supported: `{spec}`")),
         },
         // Legacy
-        None => serde_json::from_value::<legacy::Idl>(value).map(TryInto::try_into)?,
+        None => {
+            let mut report = vec![];
+            if value.get("state").is_some() {
+                report.push("`state` is not supported and was removed".into());
+            }
+            let idl = serde_json::from_value::<legacy::Idl>(value)?;
+            legacy::report_unsupported(&idl, &mut report);
+            Ok((idl.try_into()?, report))
+        }
     }
 }
 
+/// Convert an [`Idl`] to the legacy IDL spec (pre Anchor v0.30.1), for tools that only
+/// support the legacy spec.
+///
+/// Returns the legacy IDL along with the description of each feature that couldn't be
+/// represented in the legacy spec.
+pub fn convert_idl_to_legacy(idl: &Idl) -> Result<(serde_json::Value, Vec<String>)> {
+    let mut report = vec![];
+    let idl = legacy::Idl::from_idl(idl, &mut report)?;
+    Ok((serde_json::to_value(idl)?, report))
+}
+
 /// Legacy IDL spec (pre Anchor v0.30.1)
 mod legacy {
     use crate::types as t;
     use anyhow::{anyhow, Result};
-    use heck::SnakeCase;
+    use heck::{MixedCase, SnakeCase};
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -343,6 +368,7 @@ mod legacy {
             Self {
                 discriminator: get_disc("account", &value.name),
                 name: value.name,
+                generics: vec![],
             }
         }
     }
@@ -397,6 +423,7 @@ mod legacy {
                 serialization: Default::default(),
                 repr: Default::default(),
                 generics: Default::default(),
+                layout: Default::default(),
                 ty: value.ty.into(),
             }
         }
@@ -410,6 +437,7 @@ mod legacy {
                 serialization: Default::default(),
                 repr: Default::default(),
                 generics: Default::default(),
+                layout: Default::default(),
                 ty: t::IdlTypeDefTy::Struct {
                     fields: Some(t::IdlDefinedFields::Named(
                         value
@@ -511,6 +539,7 @@ mod legacy {
                     signer: acc.is_signer,
                     optional: acc.is_optional.unwrap_or_default(),
                     address: Default::default(),
+                    addresses: Default::default(),
                     pda: acc
                         .pda
                         .map(|pda| -> Result<t::IdlPda> {
@@ -555,4 +584,427 @@ mod legacy {
             Ok(seed)
         }
     }
+
+    /// Describe the features of the legacy IDL that are lost in the conversion to the new
+    /// spec.
+    pub fn report_unsupported(idl: &Idl, report: &mut Vec<String>) {
+        fn report_accounts(ix: &str, accounts: &[IdlAccountItem], report: &mut Vec<String>) {
+            for acc in accounts {
+                match acc {
+                    IdlAccountItem::IdlAccounts(accs) => {
+                        report_accounts(ix, &accs.accounts, report)
+                    }
+                    IdlAccountItem::IdlAccount(acc) => {
+                        let unsupported = acc.pda.iter().any(|pda| {
+                            pda.seeds.iter().chain(&pda.program_id).any(|seed| {
+                                matches!(seed, IdlSeed::Const(seed) if seed.ty != IdlType::String)
+                            })
+                        });
+                        if unsupported {
+                            report.push(format!(
+                                "the PDA of the `{ix}.{}` account was removed, only string \
+                                constant seeds are supported",
+                                acc.name
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        for ix in &idl.instructions {
+            report_accounts(&ix.name, &ix.accounts, report);
+        }
+        for event in idl.events.iter().flatten() {
+            for field in event.fields.iter().filter(|field| field.index) {
+                report.push(format!(
+                    "the `index` of the `{}.{}` event field was removed",
+                    event.name, field.name
+                ));
+            }
+        }
+    }
+
+    impl Idl {
+        /// Convert a new spec IDL to the legacy spec, describing the features that can't be
+        /// represented in `report`.
+        pub fn from_idl(idl: &t::Idl, report: &mut Vec<String>) -> Result<Self> {
+            let type_def = |name: &str| idl.types.iter().find(|ty| ty.name == name);
+
+            let mut metadata = serde_json::to_value(&idl.metadata)?;
+            if let Some(metadata) = metadata.as_object_mut() {
+                metadata.retain(|key, _| !["name", "version", "spec"].contains(&key.as_str()));
+                metadata.insert("address".into(), idl.address.clone().into());
+            }
+
+            let mut accounts = vec![];
+            for acc in &idl.accounts {
+                if acc.discriminator != get_disc("account", &acc.name) {
+                    report.push(format!(
+                        "the custom discriminator of the `{}` account was removed",
+                        acc.name
+                    ));
+                }
+                if !acc.generics.is_empty() {
+                    report.push(format!(
+                        "the generic arguments of the `{}` account were removed",
+                        acc.name
+                    ));
+                }
+                match type_def(&acc.name) {
+                    Some(ty) => accounts.push(IdlTypeDefinition::from_type_def(ty, report)),
+                    None => report.push(format!(
+                        "the `{}` account was removed, its type is missing",
+                        acc.name
+                    )),
+                }
+            }
+
+            let mut events = vec![];
+            for event in &idl.events {
+                if event.discriminator != get_disc("event", &event.name) {
+                    report.push(format!(
+                        "the custom discriminator of the `{}` event was removed",
+                        event.name
+                    ));
+                }
+                let ty = match type_def(&event.name) {
+                    Some(ty) => ty,
+                    None => {
+                        report.push(format!(
+                            "the `{}` event was removed, its type is missing",
+                            event.name
+                        ));
+                        continue;
+                    }
+                };
+                let fields = match IdlTypeDefinition::from_type_def(ty, report).ty {
+                    IdlTypeDefinitionTy::Struct { fields } => fields,
+                    _ => {
+                        report.push(format!(
+                            "the `{}` event was removed, only structs are supported",
+                            event.name
+                        ));
+                        continue;
+                    }
+                };
+                events.push(IdlEvent {
+                    name: event.name.clone(),
+                    fields: fields
+                        .into_iter()
+                        .map(|field| IdlEventField {
+                            name: field.name,
+                            ty: field.ty,
+                            index: false,
+                        })
+                        .collect(),
+                });
+            }
+
+            let is_type = |name: &str| {
+                !idl.accounts.iter().any(|acc| acc.name == name)
+                    && !idl.events.iter().any(|event| event.name == name)
+            };
+
+            Ok(Self {
+                version: idl.metadata.version.clone(),
+                name: idl.metadata.name.clone(),
+                docs: docs(&idl.docs),
+                constants: idl
+                    .constants
+                    .iter()
+                    .map(|constant| IdlConst {
+                        name: constant.name.clone(),
+                        ty: IdlType::from_type(&constant.ty, report),
+                        value: constant.value.clone(),
+                    })
+                    .collect(),
+                instructions: idl
+                    .instructions
+                    .iter()
+                    .map(|ix| IdlInstruction::from_instruction(ix, report))
+                    .collect(),
+                accounts,
+                types: idl
+                    .types
+                    .iter()
+                    .filter(|ty| is_type(&ty.name))
+                    .map(|ty| IdlTypeDefinition::from_type_def(ty, report))
+                    .collect(),
+                events: (!events.is_empty()).then_some(events),
+                errors: (!idl.errors.is_empty()).then(|| {
+                    idl.errors
+                        .iter()
+                        .map(|err| IdlErrorCode {
+                            code: err.code,
+                            name: err.name.clone(),
+                            msg: err.msg.clone(),
+                        })
+                        .collect()
+                }),
+                metadata: Some(metadata),
+            })
+        }
+    }
+
+    fn docs(docs: &[String]) -> Option<Vec<String>> {
+        (!docs.is_empty()).then(|| docs.to_vec())
+    }
+
+    impl IdlInstruction {
+        fn from_instruction(ix: &t::IdlInstruction, report: &mut Vec<String>) -> Self {
+            if ix.discriminator != get_disc("global", &ix.name) {
+                report.push(format!(
+                    "the custom discriminator of the `{}` instruction was removed",
+                    ix.name
+                ));
+            }
+            Self {
+                name: ix.name.to_mixed_case(),
+                docs: docs(&ix.docs),
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|acc| IdlAccountItem::from_account_item(acc, ix, report))
+                    .collect(),
+                args: ix
+                    .args
+                    .iter()
+                    .map(|arg| IdlField::from_field(arg, report))
+                    .collect(),
+                returns: ix.returns.as_ref().map(|ty| IdlType::from_type(ty, report)),
+            }
+        }
+    }
+
+    impl IdlAccountItem {
+        fn from_account_item(
+            acc: &t::IdlInstructionAccountItem,
+            ix: &t::IdlInstruction,
+            report: &mut Vec<String>,
+        ) -> Self {
+            let acc = match acc {
+                t::IdlInstructionAccountItem::Composite(accs) => {
+                    return Self::IdlAccounts(IdlAccounts {
+                        name: accs.name.to_mixed_case(),
+                        accounts: accs
+                            .accounts
+                            .iter()
+                            .map(|acc| Self::from_account_item(acc, ix, report))
+                            .collect(),
+                    })
+                }
+                t::IdlInstructionAccountItem::Single(acc) => acc,
+            };
+
+            if acc.address.is_some() {
+                report.push(format!(
+                    "the address of the `{}.{}` account was removed",
+                    ix.name, acc.name
+                ));
+            }
+
+            // Legacy seeds are typed, which is only known for instruction arguments and
+            // accounts themselves.
+            let seed = |seed: &t::IdlSeed| match seed {
+                t::IdlSeed::Const(seed) => Some(IdlSeed::Const(IdlSeedConst {
+                    ty: IdlType::Bytes,
+                    value: seed.value.clone().into(),
+                })),
+                t::IdlSeed::Arg(seed) => {
+                    ix.args.iter().find(|arg| arg.name == seed.path).map(|arg| {
+                        IdlSeed::Arg(IdlSeedArg {
+                            ty: IdlType::from_type(&arg.ty, &mut vec![]),
+                            path: seed.path.to_mixed_case(),
+                        })
+                    })
+                }
+                t::IdlSeed::Account(seed) if !seed.path.contains('.') => {
+                    Some(IdlSeed::Account(IdlSeedAccount {
+                        ty: IdlType::PublicKey,
+                        account: seed.account.clone(),
+                        path: seed.path.to_mixed_case(),
+                    }))
+                }
+                t::IdlSeed::Account(_) => None,
+            };
+            let pda = acc.pda.as_ref().and_then(|pda| {
+                let pda = (|| {
+                    Some(IdlPda {
+                        seeds: pda.seeds.iter().map(seed).collect::<Option<_>>()?,
+                        program_id: match &pda.program {
+                            Some(program) => Some(seed(program)?),
+                            None => None,
+                        },
+                    })
+                })();
+                if pda.is_none() {
+                    report.push(format!(
+                        "the PDA of the `{}.{}` account was removed, only constant, \
+                        argument and account seeds are supported",
+                        ix.name, acc.name
+                    ));
+                }
+                pda
+            });
+
+            Self::IdlAccount(IdlAccount {
+                name: acc.name.to_mixed_case(),
+                is_mut: acc.writable,
+                is_signer: acc.signer,
+                is_optional: acc.optional.then_some(true),
+                docs: docs(&acc.docs),
+                pda,
+                relations: acc.relations.clone(),
+            })
+        }
+    }
+
+    impl IdlTypeDefinition {
+        fn from_type_def(ty: &t::IdlTypeDef, report: &mut Vec<String>) -> Self {
+            let name = &ty.name;
+            if ty.serialization != t::IdlSerialization::Borsh {
+                report.push(format!(
+                    "the `{:?}` serialization of the `{name}` type was removed",
+                    ty.serialization
+                ));
+            }
+            if ty.repr.is_some() {
+                report.push(format!(
+                    "the representation of the `{name}` type was removed"
+                ));
+            }
+            if ty.layout.is_some() {
+                report.push(format!("the layout of the `{name}` type was removed"));
+            }
+
+            let generics = ty
+                .generics
+                .iter()
+                .map(|generic| match generic {
+                    t::IdlTypeDefGeneric::Type { name } => name.clone(),
+                    t::IdlTypeDefGeneric::Const { name: generic, .. } => {
+                        report.push(format!(
+                            "the type of the `{generic}` const generic of the `{name}` type \
+                            was removed"
+                        ));
+                        generic.clone()
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let fields = |fields: &t::IdlDefinedFields, report: &mut Vec<String>| match fields {
+                t::IdlDefinedFields::Named(fields) => EnumFields::Named(
+                    fields
+                        .iter()
+                        .map(|field| IdlField::from_field(field, report))
+                        .collect(),
+                ),
+                t::IdlDefinedFields::Tuple(tys) => EnumFields::Tuple(
+                    tys.iter()
+                        .map(|ty| IdlType::from_type(ty, report))
+                        .collect(),
+                ),
+            };
+
+            Self {
+                name: name.clone(),
+                docs: docs(&ty.docs),
+                generics: (!generics.is_empty()).then_some(generics),
+                ty: match &ty.ty {
+                    t::IdlTypeDefTy::Struct { fields: None } => {
+                        IdlTypeDefinitionTy::Struct { fields: vec![] }
+                    }
+                    t::IdlTypeDefTy::Struct {
+                        fields: Some(struct_fields),
+                    } => IdlTypeDefinitionTy::Struct {
+                        fields: match fields(struct_fields, report) {
+                            EnumFields::Named(fields) => fields,
+                            EnumFields::Tuple(tys) => {
+                                report.push(format!(
+                                    "the tuple fields of the `{name}` type are named by their \
+                                    index"
+                                ));
+                                tys.into_iter()
+                                    .enumerate()
+                                    .map(|(i, ty)| IdlField {
+                                        name: i.to_string(),
+                                        docs: None,
+                                        ty,
+                                    })
+                                    .collect()
+                            }
+                        },
+                    },
+                    t::IdlTypeDefTy::Enum { variants } => IdlTypeDefinitionTy::Enum {
+                        variants: variants
+                            .iter()
+                            .map(|variant| IdlEnumVariant {
+                                name: variant.name.clone(),
+                                fields: variant.fields.as_ref().map(|f| fields(f, report)),
+                            })
+                            .collect(),
+                    },
+                    t::IdlTypeDefTy::Type { alias } => IdlTypeDefinitionTy::Alias {
+                        value: IdlType::from_type(alias, report),
+                    },
+                },
+            }
+        }
+    }
+
+    impl IdlField {
+        fn from_field(field: &t::IdlField, report: &mut Vec<String>) -> Self {
+            Self {
+                name: field.name.to_mixed_case(),
+                docs: docs(&field.docs),
+                ty: IdlType::from_type(&field.ty, report),
+            }
+        }
+    }
+
+    impl IdlType {
+        fn from_type(ty: &t::IdlType, report: &mut Vec<String>) -> Self {
+            let boxed =
+                |ty: &t::IdlType, report: &mut Vec<String>| Box::new(Self::from_type(ty, report));
+            match ty {
+                t::IdlType::Pubkey => Self::PublicKey,
+                t::IdlType::Option(ty) => Self::Option(boxed(ty, report)),
+                t::IdlType::Vec(ty) => Self::Vec(boxed(ty, report)),
+                t::IdlType::Array(ty, t::IdlArrayLen::Value(len)) => {
+                    Self::Array(boxed(ty, report), *len)
+                }
+                t::IdlType::Array(ty, t::IdlArrayLen::Generic(generic)) => {
+                    Self::GenericLenArray(boxed(ty, report), generic.clone())
+                }
+                t::IdlType::Defined { name, generics } if generics.is_empty() => {
+                    Self::Defined(name.clone())
+                }
+                t::IdlType::Defined { name, generics } => Self::DefinedWithTypeArgs {
+                    name: name.clone(),
+                    args: generics
+                        .iter()
+                        .map(|arg| match arg {
+                            t::IdlGenericArg::Type {
+                                ty: t::IdlType::Generic(generic),
+                            } => IdlDefinedTypeArg::Generic(generic.clone()),
+                            t::IdlGenericArg::Type { ty } => {
+                                IdlDefinedTypeArg::Type(Self::from_type(ty, report))
+                            }
+                            t::IdlGenericArg::Const { value } => {
+                                IdlDefinedTypeArg::Value(value.clone())
+                            }
+                        })
+                        .collect(),
+                },
+                t::IdlType::Generic(generic) => Self::Generic(generic.clone()),
+                _ => serde_json::to_value(ty)
+                    .and_then(serde_json::from_value)
+                    .unwrap_or_else(|_| {
+                        report.push(format!("the `{ty:?}` type was replaced with `bytes`"));
+                        Self::Bytes
+                    }),
+            }
+        }
+    }
 }
@@ -0,0 +1,582 @@
+//! Compatibility check between two versions of an IDL.
+
+use std::fmt;
+
+use crate::types::{
+    Idl, IdlDefinedFields, IdlField, IdlInstruction, IdlInstructionAccount,
+    IdlInstructionAccountItem, IdlType, IdlTypeDef, IdlTypeDefTy,
+};
+
+/// Whether a change can break existing clients or on-chain data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    Compatible,
+    Breaking,
+}
+
+/// A change between two versions of an IDL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlChange {
+    pub compatibility: Compatibility,
+    pub description: String,
+}
+
+impl IdlChange {
+    fn compatible(description: impl Into<String>) -> Self {
+        Self {
+            compatibility: Compatibility::Compatible,
+            description: description.into(),
+        }
+    }
+
+    fn breaking(description: impl Into<String>) -> Self {
+        Self {
+            compatibility: Compatibility::Breaking,
+            description: description.into(),
+        }
+    }
+
+    /// Returns whether the change is breaking.
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility == Compatibility::Breaking
+    }
+}
+
+impl fmt::Display for IdlChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compatibility = match self.compatibility {
+            Compatibility::Compatible => "compatible",
+            Compatibility::Breaking => "breaking",
+        };
+        write!(f, "[{compatibility}] {}", self.description)
+    }
+}
+
+/// Returns the changes from the `old` IDL to the `new` one.
+///
+/// Changes to the serialized representation of instructions, accounts, events
+/// and their types are breaking. Renamed fields, arguments, instruction
+/// accounts and enum variants are compatible since their names aren't part of
+/// the serialized data, but instructions, accounts, events and types are
+/// matched by name, so renaming them is reported as a removal (breaking) and
+/// an addition.
+pub fn diff(old: &Idl, new: &Idl) -> Vec<IdlChange> {
+    let mut changes = Vec::new();
+
+    if old.address != new.address {
+        changes.push(IdlChange::breaking(format!(
+            "Program address changed from {} to {}",
+            old.address, new.address
+        )));
+    }
+
+    for old_ix in &old.instructions {
+        match new.instructions.iter().find(|ix| ix.name == old_ix.name) {
+            Some(new_ix) => diff_instruction(old_ix, new_ix, &mut changes),
+            None => changes.push(IdlChange::breaking(format!(
+                "Instruction `{}` removed",
+                old_ix.name
+            ))),
+        }
+    }
+    for new_ix in &new.instructions {
+        if !old.instructions.iter().any(|ix| ix.name == new_ix.name) {
+            changes.push(IdlChange::compatible(format!(
+                "Instruction `{}` added",
+                new_ix.name
+            )));
+        }
+    }
+
+    for old_acc in &old.accounts {
+        match new.accounts.iter().find(|acc| acc.name == old_acc.name) {
+            Some(new_acc) if new_acc.discriminator != old_acc.discriminator => {
+                changes.push(IdlChange::breaking(format!(
+                    "Discriminator of account `{}` changed",
+                    old_acc.name
+                )))
+            }
+            Some(_) => {}
+            None => changes.push(IdlChange::breaking(format!(
+                "Account `{}` removed",
+                old_acc.name
+            ))),
+        }
+    }
+    for new_acc in &new.accounts {
+        if !old.accounts.iter().any(|acc| acc.name == new_acc.name) {
+            changes.push(IdlChange::compatible(format!(
+                "Account `{}` added",
+                new_acc.name
+            )));
+        }
+    }
+
+    for old_event in &old.events {
+        match new.events.iter().find(|event| event.name == old_event.name) {
+            Some(new_event) if new_event.discriminator != old_event.discriminator => {
+                changes.push(IdlChange::breaking(format!(
+                    "Discriminator of event `{}` changed",
+                    old_event.name
+                )))
+            }
+            Some(_) => {}
+            None => changes.push(IdlChange::breaking(format!(
+                "Event `{}` removed",
+                old_event.name
+            ))),
+        }
+    }
+    for new_event in &new.events {
+        if !old.events.iter().any(|event| event.name == new_event.name) {
+            changes.push(IdlChange::compatible(format!(
+                "Event `{}` added",
+                new_event.name
+            )));
+        }
+    }
+
+    for old_ty in &old.types {
+        match new.types.iter().find(|ty| ty.name == old_ty.name) {
+            Some(new_ty) => diff_type(old_ty, new_ty, &mut changes),
+            None => changes.push(IdlChange::breaking(format!(
+                "Type `{}` removed",
+                old_ty.name
+            ))),
+        }
+    }
+    for new_ty in &new.types {
+        if !old.types.iter().any(|ty| ty.name == new_ty.name) {
+            changes.push(IdlChange::compatible(format!(
+                "Type `{}` added",
+                new_ty.name
+            )));
+        }
+    }
+
+    for old_err in &old.errors {
+        match new.errors.iter().find(|err| err.name == old_err.name) {
+            Some(new_err) if new_err.code != old_err.code => {
+                changes.push(IdlChange::breaking(format!(
+                    "Code of error `{}` changed from {} to {}",
+                    old_err.name, old_err.code, new_err.code
+                )))
+            }
+            Some(_) => {}
+            None => changes.push(IdlChange::compatible(format!(
+                "Error `{}` removed",
+                old_err.name
+            ))),
+        }
+    }
+    for new_err in &new.errors {
+        if !old.errors.iter().any(|err| err.name == new_err.name) {
+            changes.push(IdlChange::compatible(format!(
+                "Error `{}` added",
+                new_err.name
+            )));
+        }
+    }
+
+    changes
+}
+
+fn diff_instruction(old: &IdlInstruction, new: &IdlInstruction, changes: &mut Vec<IdlChange>) {
+    let name = &old.name;
+    if old.discriminator != new.discriminator {
+        changes.push(IdlChange::breaking(format!(
+            "Discriminator of instruction `{name}` changed"
+        )));
+    }
+
+    diff_fields(
+        &format!("instruction `{name}`"),
+        &old.args,
+        &new.args,
+        "argument",
+        changes,
+    );
+
+    let old_accounts = flatten_accounts(&old.accounts);
+    let new_accounts = flatten_accounts(&new.accounts);
+    if old_accounts.len() != new_accounts.len() {
+        changes.push(IdlChange::breaking(format!(
+            "Number of accounts of instruction `{name}` changed from {} to {}",
+            old_accounts.len(),
+            new_accounts.len()
+        )));
+    } else {
+        for (old_acc, new_acc) in old_accounts.iter().zip(&new_accounts) {
+            if old_acc.writable != new_acc.writable
+                || old_acc.signer != new_acc.signer
+                || old_acc.optional != new_acc.optional
+            {
+                changes.push(IdlChange::breaking(format!(
+                    "Account `{}` of instruction `{name}` changed",
+                    old_acc.name
+                )));
+            } else if old_acc.name != new_acc.name {
+                changes.push(IdlChange::compatible(format!(
+                    "Account `{}` of instruction `{name}` renamed to `{}`",
+                    old_acc.name, new_acc.name
+                )));
+            }
+        }
+    }
+
+    if old.returns != new.returns {
+        changes.push(IdlChange::breaking(format!(
+            "Return type of instruction `{name}` changed"
+        )));
+    }
+}
+
+fn flatten_accounts(items: &[IdlInstructionAccountItem]) -> Vec<&IdlInstructionAccount> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            IdlInstructionAccountItem::Single(acc) => vec![acc],
+            IdlInstructionAccountItem::Composite(accs) => flatten_accounts(&accs.accounts),
+        })
+        .collect()
+}
+
+fn diff_type(old: &IdlTypeDef, new: &IdlTypeDef, changes: &mut Vec<IdlChange>) {
+    let name = &old.name;
+    if old.serialization != new.serialization || old.repr != new.repr {
+        changes.push(IdlChange::breaking(format!(
+            "Serialization of type `{name}` changed"
+        )));
+    }
+
+    match (&old.ty, &new.ty) {
+        (
+            IdlTypeDefTy::Struct { fields: old_fields },
+            IdlTypeDefTy::Struct { fields: new_fields },
+        ) => diff_defined_fields(&format!("type `{name}`"), old_fields, new_fields, changes),
+        (
+            IdlTypeDefTy::Enum {
+                variants: old_variants,
+            },
+            IdlTypeDefTy::Enum {
+                variants: new_variants,
+            },
+        ) => {
+            // Variants are serialized by index, so new variants can only be
+            // appended.
+            for (index, old_variant) in old_variants.iter().enumerate() {
+                match new_variants.get(index) {
+                    Some(new_variant) => {
+                        let variant = format!("variant `{}` of type `{name}`", old_variant.name);
+                        if old_variant.name != new_variant.name {
+                            changes.push(IdlChange::compatible(format!(
+                                "Variant `{}` of type `{name}` renamed to `{}`",
+                                old_variant.name, new_variant.name
+                            )));
+                        }
+                        diff_defined_fields(
+                            &variant,
+                            &old_variant.fields,
+                            &new_variant.fields,
+                            changes,
+                        );
+                    }
+                    None => changes.push(IdlChange::breaking(format!(
+                        "Variant `{}` of type `{name}` removed",
+                        old_variant.name
+                    ))),
+                }
+            }
+            for new_variant in new_variants.iter().skip(old_variants.len()) {
+                changes.push(IdlChange::compatible(format!(
+                    "Variant `{}` added to type `{name}`",
+                    new_variant.name
+                )));
+            }
+        }
+        (IdlTypeDefTy::Type { alias: old_alias }, IdlTypeDefTy::Type { alias: new_alias }) => {
+            if old_alias != new_alias {
+                changes.push(IdlChange::breaking(format!(
+                    "Alias of type `{name}` changed"
+                )));
+            }
+        }
+        _ => changes.push(IdlChange::breaking(format!(
+            "Kind of type `{name}` changed"
+        ))),
+    }
+}
+
+fn diff_defined_fields(
+    parent: &str,
+    old: &Option<IdlDefinedFields>,
+    new: &Option<IdlDefinedFields>,
+    changes: &mut Vec<IdlChange>,
+) {
+    match (old, new) {
+        (Some(IdlDefinedFields::Named(old)), Some(IdlDefinedFields::Named(new))) => {
+            diff_fields(parent, old, new, "field", changes)
+        }
+        (old, new) if field_types(old) == field_types(new) => {}
+        _ => changes.push(IdlChange::breaking(format!("Fields of {parent} changed"))),
+    }
+}
+
+fn field_types(fields: &Option<IdlDefinedFields>) -> Vec<&IdlType> {
+    match fields {
+        Some(IdlDefinedFields::Named(fields)) => fields.iter().map(|field| &field.ty).collect(),
+        Some(IdlDefinedFields::Tuple(fields)) => fields.iter().collect(),
+        None => vec![],
+    }
+}
+
+// Fields are serialized in order without their names, so only renames are
+// compatible.
+fn diff_fields(
+    parent: &str,
+    old: &[IdlField],
+    new: &[IdlField],
+    kind: &str,
+    changes: &mut Vec<IdlChange>,
+) {
+    for (index, old_field) in old.iter().enumerate() {
+        match new.get(index) {
+            Some(new_field) if new_field.ty != old_field.ty => changes.push(IdlChange::breaking(
+                format!("Type of {kind} `{}` of {parent} changed", old_field.name),
+            )),
+            Some(new_field) if new_field.name != old_field.name => {
+                changes.push(IdlChange::compatible(format!(
+                    "{} `{}` of {parent} renamed to `{}`",
+                    capitalize(kind),
+                    old_field.name,
+                    new_field.name
+                )))
+            }
+            Some(_) => {}
+            None => changes.push(IdlChange::breaking(format!(
+                "{} `{}` of {parent} removed",
+                capitalize(kind),
+                old_field.name
+            ))),
+        }
+    }
+    for new_field in new.iter().skip(old.len()) {
+        changes.push(IdlChange::breaking(format!(
+            "{} `{}` added to {parent}",
+            capitalize(kind),
+            new_field.name
+        )));
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{IdlAccount, IdlErrorCode, IdlMetadata};
+
+    fn idl(types: Vec<IdlTypeDef>) -> Idl {
+        Idl {
+            address: "Test111111111111111111111111111111111111111".into(),
+            metadata: IdlMetadata {
+                name: "test".into(),
+                version: "0.1.0".into(),
+                spec: "0.1.0".into(),
+                description: None,
+                repository: None,
+                dependencies: vec![],
+                contact: None,
+                deployments: None,
+            },
+            docs: vec![],
+            instructions: vec![],
+            accounts: vec![],
+            events: vec![],
+            errors: vec![],
+            types,
+            constants: vec![],
+        }
+    }
+
+    fn ty(name: &str, ty: IdlTypeDefTy) -> IdlTypeDef {
+        IdlTypeDef {
+            name: name.into(),
+            docs: vec![],
+            serialization: Default::default(),
+            repr: None,
+            generics: vec![],
+            layout: None,
+            ty,
+        }
+    }
+
+    fn field(name: &str, ty: IdlType) -> IdlField {
+        IdlField {
+            name: name.into(),
+            docs: vec![],
+            ty,
+        }
+    }
+
+    fn strukt(fields: Vec<IdlField>) -> IdlTypeDefTy {
+        IdlTypeDefTy::Struct {
+            fields: Some(IdlDefinedFields::Named(fields)),
+        }
+    }
+
+    fn instruction(args: Vec<IdlField>, accounts: Vec<(&str, bool)>) -> IdlInstruction {
+        IdlInstruction {
+            name: "deposit".into(),
+            docs: vec![],
+            discriminator: vec![1; 8],
+            accounts: accounts
+                .into_iter()
+                .map(|(name, writable)| {
+                    IdlInstructionAccountItem::Single(IdlInstructionAccount {
+                        name: name.into(),
+                        docs: vec![],
+                        writable,
+                        signer: false,
+                        optional: false,
+                        address: None,
+                        addresses: vec![],
+                        pda: None,
+                        relations: vec![],
+                    })
+                })
+                .collect(),
+            args,
+            returns: None,
+        }
+    }
+
+    fn account(name: &str, discriminator: u8) -> IdlAccount {
+        IdlAccount {
+            name: name.into(),
+            discriminator: vec![discriminator; 8],
+            generics: vec![],
+        }
+    }
+
+    fn error(name: &str, code: u32) -> IdlErrorCode {
+        IdlErrorCode {
+            code,
+            name: name.into(),
+            msg: None,
+        }
+    }
+
+    #[test]
+    fn renamed_instruction_account_is_compatible() {
+        let mut old = idl(vec![]);
+        old.instructions = vec![instruction(vec![], vec![("vault", true)])];
+        let mut new = idl(vec![]);
+        new.instructions = vec![instruction(vec![], vec![("pool", true)])];
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].is_breaking());
+    }
+
+    #[test]
+    fn changed_instruction_accounts_are_breaking() {
+        let mut old = idl(vec![]);
+        old.instructions = vec![instruction(vec![], vec![("vault", true)])];
+
+        let mut readonly = idl(vec![]);
+        readonly.instructions = vec![instruction(vec![], vec![("vault", false)])];
+        assert!(diff(&old, &readonly).iter().any(IdlChange::is_breaking));
+
+        let mut added = idl(vec![]);
+        added.instructions = vec![instruction(vec![], vec![("vault", true), ("mint", false)])];
+        assert!(diff(&old, &added).iter().any(IdlChange::is_breaking));
+    }
+
+    #[test]
+    fn appended_instruction_argument_is_breaking() {
+        let mut old = idl(vec![]);
+        old.instructions = vec![instruction(vec![field("amount", IdlType::U64)], vec![])];
+        let mut new = idl(vec![]);
+        new.instructions = vec![instruction(
+            vec![
+                field("amount", IdlType::U64),
+                field("memo", IdlType::String),
+            ],
+            vec![],
+        )];
+        assert!(diff(&old, &new).iter().any(IdlChange::is_breaking));
+    }
+
+    #[test]
+    fn renamed_instruction_is_breaking() {
+        let mut old = idl(vec![]);
+        old.instructions = vec![instruction(vec![], vec![])];
+        let mut new = idl(vec![]);
+        new.instructions = vec![IdlInstruction {
+            name: "deposit_v2".into(),
+            ..instruction(vec![], vec![])
+        }];
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().any(IdlChange::is_breaking));
+    }
+
+    #[test]
+    fn changed_account_discriminator_is_breaking() {
+        let mut old = idl(vec![]);
+        old.accounts = vec![account("Vault", 1)];
+        let mut new = idl(vec![]);
+        new.accounts = vec![account("Vault", 2)];
+        assert!(diff(&old, &new).iter().any(IdlChange::is_breaking));
+
+        new.accounts.push(account("Pool", 3));
+        old.accounts = new.accounts[..1].to_vec();
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].is_breaking());
+    }
+
+    #[test]
+    fn changed_error_code_is_breaking() {
+        let mut old = idl(vec![]);
+        old.errors = vec![error("Overflow", 6000)];
+        let mut new = idl(vec![]);
+        new.errors = vec![error("Overflow", 6001)];
+        assert!(diff(&old, &new).iter().any(IdlChange::is_breaking));
+
+        new.errors = vec![error("Overflow", 6000), error("Underflow", 6001)];
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].is_breaking());
+    }
+
+    #[test]
+    fn renamed_field_is_compatible() {
+        let old = idl(vec![ty("Data", strukt(vec![field("a", IdlType::U64)]))]);
+        let new = idl(vec![ty("Data", strukt(vec![field("b", IdlType::U64)]))]);
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].is_breaking());
+    }
+
+    #[test]
+    fn changed_field_type_is_breaking() {
+        let old = idl(vec![ty("Data", strukt(vec![field("a", IdlType::U64)]))]);
+        let new = idl(vec![ty("Data", strukt(vec![field("a", IdlType::U32)]))]);
+        assert!(diff(&old, &new).iter().any(IdlChange::is_breaking));
+    }
+
+    #[test]
+    fn removed_type_is_breaking() {
+        let old = idl(vec![ty("Data", strukt(vec![]))]);
+        let new = idl(vec![]);
+        assert!(diff(&old, &new).iter().any(IdlChange::is_breaking));
+        assert!(!diff(&new, &old).iter().any(IdlChange::is_breaking));
+    }
+}
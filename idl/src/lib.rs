@@ -3,9 +3,17 @@
 #[cfg(feature = "build")]
 pub mod build;
 
+#[cfg(feature = "codec")]
+pub mod codec;
+
 #[cfg(feature = "convert")]
 pub mod convert;
 
+pub mod diff;
+
+#[cfg(feature = "codec")]
+pub mod schema;
+
 pub use anchor_lang_idl_spec as types;
 
 #[cfg(feature = "build")]
@@ -0,0 +1,296 @@
+//! JSON Schema of program data, as represented by the [`codec`](crate::codec).
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use crate::codec::{array_len, find_type, substitute, type_generics, Generics};
+use crate::types::{Idl, IdlDefinedFields, IdlType, IdlTypeDef, IdlTypeDefTy};
+
+/// JSON Schema version of the generated schemas.
+pub const JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Returns the JSON Schemas of the accounts, the events and the instruction arguments of the IDL,
+/// keyed by `accounts/<name>`, `events/<name>` and `instructions/<name>`.
+///
+/// Each schema is self-contained, the types it refers to being defined in its `$defs`.
+pub fn schemas(idl: &Idl) -> Result<BTreeMap<String, Value>> {
+    let mut schemas = BTreeMap::new();
+
+    for acc in &idl.accounts {
+        let schema = root_schema(idl, &acc.name, |generator| {
+            let ty_def = find_type(idl, &acc.name)?;
+            let generics = type_generics(ty_def, &acc.generics, &Generics::new())?;
+            generator.ty_def(ty_def, &generics)
+        })?;
+        schemas.insert(format!("accounts/{}", acc.name), schema);
+    }
+
+    for event in &idl.events {
+        let schema = root_schema(idl, &event.name, |generator| {
+            generator.ty_def(find_type(idl, &event.name)?, &Generics::new())
+        })?;
+        schemas.insert(format!("events/{}", event.name), schema);
+    }
+
+    for ix in &idl.instructions {
+        let schema = root_schema(idl, &ix.name, |generator| {
+            let args = IdlDefinedFields::Named(ix.args.clone());
+            let mut schema = generator.fields(Some(&args), &Generics::new())?;
+            add_docs(&mut schema, &ix.docs);
+            Ok(schema)
+        })?;
+        schemas.insert(format!("instructions/{}", ix.name), schema);
+    }
+
+    Ok(schemas)
+}
+
+fn root_schema(
+    idl: &Idl,
+    title: &str,
+    f: impl FnOnce(&mut SchemaGenerator) -> Result<Value>,
+) -> Result<Value> {
+    let mut generator = SchemaGenerator {
+        idl,
+        defs: Map::new(),
+    };
+
+    let mut schema = json!({
+        "$schema": JSON_SCHEMA_DRAFT,
+        "title": title,
+    });
+    if let (Some(root), Value::Object(ty)) = (schema.as_object_mut(), f(&mut generator)?) {
+        root.extend(ty);
+        if !generator.defs.is_empty() {
+            root.insert("$defs".into(), Value::Object(generator.defs));
+        }
+    }
+    Ok(schema)
+}
+
+struct SchemaGenerator<'a> {
+    idl: &'a Idl,
+    /// Schemas of the non-generic defined types, referred to by name.
+    defs: Map<String, Value>,
+}
+
+impl SchemaGenerator<'_> {
+    fn ty(&mut self, ty: &IdlType, generics: &Generics) -> Result<Value> {
+        Ok(match ty {
+            IdlType::Bool => json!({ "type": "boolean" }),
+            IdlType::U8 => integer(u8::MIN, u8::MAX),
+            IdlType::I8 => integer(i8::MIN, i8::MAX),
+            IdlType::U16 => integer(u16::MIN, u16::MAX),
+            IdlType::I16 => integer(i16::MIN, i16::MAX),
+            IdlType::U32 => integer(u32::MIN, u32::MAX),
+            IdlType::I32 => integer(i32::MIN, i32::MAX),
+            IdlType::U64 => integer(u64::MIN, u64::MAX),
+            IdlType::I64 => integer(i64::MIN, i64::MAX),
+            IdlType::F32 | IdlType::F64 => json!({ "type": "number" }),
+            // Decoded as strings in order not to lose precision
+            IdlType::U128 => json!({ "type": ["string", "integer"], "pattern": "^[0-9]+$" }),
+            IdlType::I128 => json!({ "type": ["string", "integer"], "pattern": "^-?[0-9]+$" }),
+            IdlType::Bytes => json!({ "type": "array", "items": integer(u8::MIN, u8::MAX) }),
+            IdlType::String => json!({ "type": "string" }),
+            IdlType::Pubkey => json!({
+                "type": "string",
+                "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$",
+            }),
+            IdlType::Option(ty) => json!({
+                "anyOf": [{ "type": "null" }, self.ty(ty, generics)?],
+            }),
+            IdlType::Vec(ty) => json!({ "type": "array", "items": self.ty(ty, generics)? }),
+            IdlType::Array(ty, len) => {
+                let len = array_len(len, generics)?;
+                json!({
+                    "type": "array",
+                    "items": self.ty(ty, generics)?,
+                    "minItems": len,
+                    "maxItems": len,
+                })
+            }
+            IdlType::Defined {
+                name,
+                generics: args,
+            } => {
+                let ty_def = find_type(self.idl, name)?;
+                if !args.is_empty() {
+                    // JSON Schema has no generics, inline the instantiation instead
+                    let generics = type_generics(ty_def, args, generics)?;
+                    return self.ty_def(ty_def, &generics);
+                }
+
+                if !self.defs.contains_key(name) {
+                    // Insert a placeholder first for recursive types
+                    self.defs.insert(name.clone(), Value::Null);
+                    let schema = self.ty_def(ty_def, &Generics::new())?;
+                    self.defs.insert(name.clone(), schema);
+                }
+                json!({ "$ref": format!("#/$defs/{name}") })
+            }
+            IdlType::Generic(_) => self.ty(&substitute(ty, generics)?, &Generics::new())?,
+            _ => json!({}),
+        })
+    }
+
+    fn ty_def(&mut self, ty_def: &IdlTypeDef, generics: &Generics) -> Result<Value> {
+        let mut schema = match &ty_def.ty {
+            IdlTypeDefTy::Struct { fields } => self.fields(fields.as_ref(), generics)?,
+            IdlTypeDefTy::Enum { variants } => {
+                let mut schemas = vec![];
+                for variant in variants {
+                    let fields = self.fields(variant.fields.as_ref(), generics)?;
+                    let mut properties = Map::new();
+                    properties.insert(variant.name.clone(), fields);
+                    let schema = json!({
+                        "type": "object",
+                        "properties": properties,
+                        "required": [variant.name],
+                        "additionalProperties": false,
+                    });
+
+                    // Unit variants can also be given by name
+                    match variant.fields {
+                        Some(_) => schemas.push(schema),
+                        None => schemas.push(json!({
+                            "anyOf": [{ "const": variant.name }, schema],
+                        })),
+                    }
+                }
+                json!({ "oneOf": schemas })
+            }
+            IdlTypeDefTy::Type { alias } => self.ty(alias, generics)?,
+        };
+        add_docs(&mut schema, &ty_def.docs);
+        Ok(schema)
+    }
+
+    fn fields(&mut self, fields: Option<&IdlDefinedFields>, generics: &Generics) -> Result<Value> {
+        Ok(match fields {
+            None => json!({ "type": "object" }),
+            Some(IdlDefinedFields::Named(fields)) => {
+                let mut properties = Map::new();
+                for field in fields {
+                    let mut schema = self.ty(&field.ty, generics)?;
+                    add_docs(&mut schema, &field.docs);
+                    properties.insert(field.name.clone(), schema);
+                }
+                let required = fields.iter().map(|field| &field.name).collect::<Vec<_>>();
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false,
+                })
+            }
+            Some(IdlDefinedFields::Tuple(fields)) => {
+                let mut items = vec![];
+                for ty in fields {
+                    items.push(self.ty(ty, generics)?);
+                }
+                json!({
+                    "type": "array",
+                    "prefixItems": items,
+                    "minItems": fields.len(),
+                    "maxItems": fields.len(),
+                })
+            }
+        })
+    }
+}
+
+fn integer(min: impl Into<Value>, max: impl Into<Value>) -> Value {
+    json!({ "type": "integer", "minimum": min.into(), "maximum": max.into() })
+}
+
+fn add_docs(schema: &mut Value, docs: &[String]) {
+    if docs.is_empty() {
+        return;
+    }
+    if let Some(schema) = schema.as_object_mut() {
+        schema.insert("description".into(), docs.join("\n").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{IdlAccount, IdlField, IdlMetadata, IdlSerialization};
+
+    fn idl() -> Idl {
+        let field = |name: &str, ty| IdlField {
+            name: name.into(),
+            docs: vec![],
+            ty,
+        };
+        let ty_def = |name: &str, fields| IdlTypeDef {
+            name: name.into(),
+            docs: vec![],
+            serialization: IdlSerialization::Borsh,
+            repr: None,
+            generics: vec![],
+            layout: None,
+            ty: IdlTypeDefTy::Struct {
+                fields: Some(IdlDefinedFields::Named(fields)),
+            },
+        };
+
+        Idl {
+            address: "Test111111111111111111111111111111111111111".into(),
+            metadata: IdlMetadata {
+                name: "test".into(),
+                version: "0.1.0".into(),
+                spec: "0.1.0".into(),
+                description: None,
+                repository: None,
+                dependencies: vec![],
+                contact: None,
+                deployments: None,
+            },
+            docs: vec![],
+            instructions: vec![],
+            accounts: vec![IdlAccount {
+                name: "Vault".into(),
+                discriminator: vec![0; 8],
+                generics: vec![],
+            }],
+            events: vec![],
+            errors: vec![],
+            types: vec![
+                ty_def(
+                    "Vault",
+                    vec![
+                        field("authority", IdlType::Pubkey),
+                        field(
+                            "config",
+                            IdlType::Defined {
+                                name: "Config".into(),
+                                generics: vec![],
+                            },
+                        ),
+                    ],
+                ),
+                ty_def("Config", vec![field("fee", IdlType::U16)]),
+            ],
+            constants: vec![],
+        }
+    }
+
+    #[test]
+    fn account_schema() {
+        let schemas = schemas(&idl()).unwrap();
+        let schema = &schemas["accounts/Vault"];
+        assert_eq!(schema["title"], "Vault");
+        assert_eq!(schema["required"], json!(["authority", "config"]));
+        assert_eq!(
+            schema["properties"]["config"],
+            json!({ "$ref": "#/$defs/Config" })
+        );
+        assert_eq!(
+            schema["$defs"]["Config"]["properties"]["fee"],
+            integer(u16::MIN, u16::MAX)
+        );
+    }
+}
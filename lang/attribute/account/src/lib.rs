@@ -25,6 +25,28 @@ mod id;
 /// check this discriminator. If it doesn't match, an invalid account was given,
 /// and the account deserialization will exit with an error.
 ///
+/// # Serialization
+///
+/// Accounts are Borsh serialized by default. The format can be selected per
+/// account with the `serde` argument:
+///
+/// - `#[account(serde = borsh)]`: the default.
+/// - `#[account(serde = bytemuck)]`: same as `#[account(zero_copy)]`, see
+///   [Zero Copy Deserialization](#zero-copy-deserialization).
+/// - `#[account(serde = bytemuck_unsafe)]`: same as
+///   `#[account(zero_copy(unsafe))]`.
+/// - `#[account(serde = MyCodec)]`: uses the given
+///   [`AccountCodec`](./trait.AccountCodec.html) implementation, which the
+///   IDL records as a custom serialization.
+///
+/// # Serde
+///
+/// With the `serde` feature of `anchor-lang` enabled, non zero-copy accounts
+/// also derive `serde::Serialize` and `serde::Deserialize`, with `Pubkey`
+/// fields (including `Option<Pubkey>` and `Vec<Pubkey>`) encoded as base58
+/// strings. Custom types used as fields must derive the `serde` traits
+/// themselves.
+///
 /// # Zero Copy Deserialization
 ///
 /// **WARNING**: Zero copy deserialization is an experimental feature. It's
@@ -70,30 +92,47 @@ pub fn account(
     let mut namespace = "".to_string();
     let mut is_zero_copy = false;
     let mut unsafe_bytemuck = false;
+    let mut codec: Option<syn::Path> = None;
     let args_str = args.to_string();
     let args: Vec<&str> = args_str.split(',').collect();
     if args.len() > 2 {
         panic!("Only two args are allowed to the account attribute.")
     }
     for arg in args {
-        let ns = arg
+        let ns: String = arg
             .to_string()
             .replace('\"', "")
             .chars()
             .filter(|c| !c.is_whitespace())
             .collect();
-        if ns == "zero_copy" {
+        if ns == "zero_copy" || ns == "serde=bytemuck" {
             is_zero_copy = true;
             unsafe_bytemuck = false;
-        } else if ns == "zero_copy(unsafe)" {
+        } else if ns == "zero_copy(unsafe)" || ns == "serde=bytemuck_unsafe" {
             is_zero_copy = true;
             unsafe_bytemuck = true;
+        } else if ns == "serde=borsh" {
+            codec = None;
+        } else if let Some(path) = ns.strip_prefix("serde=") {
+            let path: syn::Path =
+                syn::parse_str(path).unwrap_or_else(|_| panic!("Invalid account codec: `{path}`"));
+            codec = Some(path);
         } else {
             namespace = ns;
         }
     }
 
-    let account_strct = parse_macro_input!(input as syn::ItemStruct);
+    #[allow(unused_mut)]
+    let mut account_strct = parse_macro_input!(input as syn::ItemStruct);
+
+    // Zero-copy accounts are plain bytes, so they're excluded from `serde` support.
+    #[allow(unused_mut)]
+    let mut serde_derives = quote! {};
+    #[cfg(feature = "serde")]
+    if !is_zero_copy {
+        serde_derives = anchor_syn::codegen::serde_derives::gen_serde_derives(&mut account_strct);
+    }
+
     let account_name = &account_strct.ident;
     let account_name_str = account_name.to_string();
     let (impl_gen, type_gen, where_clause) = account_strct.generics.split_for_impl();
@@ -199,8 +238,49 @@ pub fn account(
                 #owner_impl
             }
         } else {
+            let (derives, serialize, deserialize) = match &codec {
+                Some(codec) => (
+                    quote! { #[derive(Clone)] },
+                    quote! {
+                        <#codec as anchor_lang::AccountCodec<Self>>::encode(self, writer)?;
+                    },
+                    quote! {
+                        <#codec as anchor_lang::AccountCodec<Self>>::decode(&buf[8..])
+                    },
+                ),
+                None => (
+                    quote! { #[derive(AnchorSerialize, AnchorDeserialize, Clone)] },
+                    quote! {
+                        if AnchorSerialize::serialize(self, writer).is_err() {
+                            return Err(anchor_lang::error::ErrorCode::AccountDidNotSerialize.into());
+                        }
+                    },
+                    quote! {
+                        let mut data: &[u8] = &buf[8..];
+                        AnchorDeserialize::deserialize(&mut data)
+                            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+                    },
+                ),
+            };
+
+            // Borsh accounts get their `IdlBuild` impl from the `AnchorSerialize` derive.
+            #[allow(unused_mut)]
+            let mut idl_build_impl = quote! {};
+            #[cfg(feature = "idl-build")]
+            if let Some(codec) = &codec {
+                let codec_str = anchor_syn::parser::tts_to_string(codec).replace(' ', "");
+                // Not a real attribute but exists in order to pass the serialization info
+                let codec_struct = syn::parse2(quote! {
+                    #[serialization(#codec_str)]
+                    #account_strct
+                })
+                .unwrap();
+                idl_build_impl = anchor_syn::idl::impl_idl_build_struct(&codec_struct);
+            }
+
             quote! {
-                #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+                #derives
+                #serde_derives
                 #account_strct
 
                 #[automatically_derived]
@@ -210,9 +290,7 @@ pub fn account(
                             return Err(anchor_lang::error::ErrorCode::AccountDidNotSerialize.into());
                         }
 
-                        if AnchorSerialize::serialize(self, writer).is_err() {
-                            return Err(anchor_lang::error::ErrorCode::AccountDidNotSerialize.into());
-                        }
+                        #serialize
                         Ok(())
                     }
                 }
@@ -231,9 +309,7 @@ pub fn account(
                     }
 
                     fn try_deserialize_unchecked(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
-                        let mut data: &[u8] = &buf[8..];
-                        AnchorDeserialize::deserialize(&mut data)
-                            .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+                        #deserialize
                     }
                 }
 
@@ -243,6 +319,8 @@ pub fn account(
                 }
 
                 #owner_impl
+
+                #idl_build_impl
             }
         }
     })
@@ -16,7 +16,13 @@ pub fn event(
     _args: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let event_strct = parse_macro_input!(input as syn::ItemStruct);
+    #[allow(unused_mut)]
+    let mut event_strct = parse_macro_input!(input as syn::ItemStruct);
+
+    #[cfg(feature = "serde")]
+    let serde_derives = anchor_syn::codegen::serde_derives::gen_serde_derives(&mut event_strct);
+    #[cfg(not(feature = "serde"))]
+    let serde_derives = quote! {};
 
     let event_name = &event_strct.ident;
 
@@ -28,6 +34,7 @@ pub fn event(
 
     let ret = quote! {
         #[derive(anchor_lang::__private::EventIndex, AnchorSerialize, AnchorDeserialize)]
+        #serde_derives
         #event_strct
 
         impl anchor_lang::Event for #event_name {
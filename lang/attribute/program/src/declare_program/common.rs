@@ -92,6 +92,34 @@ pub fn convert_idl_type_to_str(ty: &IdlType) -> String {
     }
 }
 
+/// Generate the impl and the type generics of a type definition, e.g. `<T: Bound, const N: usize>`
+/// and `<T, N>`, in order to implement traits for all of its instantiations.
+pub fn gen_impl_generics(
+    ty_def: &IdlTypeDef,
+    bound: proc_macro2::TokenStream,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if ty_def.generics.is_empty() {
+        return (quote!(), quote!());
+    }
+
+    let (params, args) = ty_def
+        .generics
+        .iter()
+        .map(|generic| match generic {
+            IdlTypeDefGeneric::Type { name } => {
+                let name = format_ident!("{}", name);
+                (quote! { #name: #bound }, quote! { #name })
+            }
+            IdlTypeDefGeneric::Const { name, ty } => {
+                let name = format_ident!("{}", name);
+                let ty = format_ident!("{}", ty);
+                (quote! { const #name: #ty }, quote! { #name })
+            }
+        })
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+    (quote!(<#(#params,)*>), quote!(<#(#args,)*>))
+}
+
 pub fn convert_idl_type_def_to_ts(
     ty_def: &IdlTypeDef,
     ty_defs: &[IdlTypeDef],
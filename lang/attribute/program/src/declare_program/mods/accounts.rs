@@ -1,7 +1,9 @@
 use anchor_lang_idl::types::{Idl, IdlSerialization};
 use quote::{format_ident, quote};
 
-use super::common::{convert_idl_type_def_to_ts, gen_discriminator, get_canonical_program_id};
+use super::common::{
+    convert_idl_type_def_to_ts, gen_discriminator, gen_impl_generics, get_canonical_program_id,
+};
 
 pub fn gen_accounts_mod(idl: &Idl) -> proc_macro2::TokenStream {
     let accounts = idl.accounts.iter().map(|acc| {
@@ -14,6 +16,14 @@ pub fn gen_accounts_mod(idl: &Idl) -> proc_macro2::TokenStream {
             .find(|ty| ty.name == acc.name)
             .expect("Type must exist");
 
+        let bound = match ty_def.serialization {
+            IdlSerialization::Borsh => quote!(AnchorSerialize + AnchorDeserialize),
+            _ => quote!(
+                anchor_lang::__private::bytemuck::Pod + anchor_lang::__private::bytemuck::Zeroable
+            ),
+        };
+        let (impl_generics, ty_generics) = gen_impl_generics(ty_def, bound);
+
         let impls = {
             let try_deserialize = quote! {
                 fn try_deserialize(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
@@ -34,7 +44,7 @@ pub fn gen_accounts_mod(idl: &Idl) -> proc_macro2::TokenStream {
             };
             match ty_def.serialization {
                 IdlSerialization::Borsh => quote! {
-                    impl anchor_lang::AccountSerialize for #name {
+                    impl #impl_generics anchor_lang::AccountSerialize for #name #ty_generics {
                         fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> anchor_lang::Result<()> {
                             if writer.write_all(&#discriminator).is_err() {
                                 return Err(anchor_lang::error::ErrorCode::AccountDidNotSerialize.into());
@@ -47,7 +57,7 @@ pub fn gen_accounts_mod(idl: &Idl) -> proc_macro2::TokenStream {
                         }
                     }
 
-                    impl anchor_lang::AccountDeserialize for #name {
+                    impl #impl_generics anchor_lang::AccountDeserialize for #name #ty_generics {
                         #try_deserialize
 
                         fn try_deserialize_unchecked(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
@@ -62,16 +72,16 @@ pub fn gen_accounts_mod(idl: &Idl) -> proc_macro2::TokenStream {
                         matches!(ty_def.serialization, IdlSerialization::BytemuckUnsafe)
                             .then(|| {
                                 quote! {
-                                    unsafe impl anchor_lang::__private::bytemuck::Pod for #name {}
-                                    unsafe impl anchor_lang::__private::bytemuck::Zeroable for #name {}
+                                    unsafe impl #impl_generics anchor_lang::__private::bytemuck::Pod for #name #ty_generics {}
+                                    unsafe impl #impl_generics anchor_lang::__private::bytemuck::Zeroable for #name #ty_generics {}
                                 }
                             })
                             .unwrap_or_default();
 
                     quote! {
-                        impl anchor_lang::ZeroCopy for #name {}
+                        impl #impl_generics anchor_lang::ZeroCopy for #name #ty_generics {}
 
-                        impl anchor_lang::AccountDeserialize for #name {
+                        impl #impl_generics anchor_lang::AccountDeserialize for #name #ty_generics {
                             #try_deserialize
 
                             fn try_deserialize_unchecked(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
@@ -95,11 +105,11 @@ pub fn gen_accounts_mod(idl: &Idl) -> proc_macro2::TokenStream {
 
             #impls
 
-            impl anchor_lang::Discriminator for #name {
+            impl #impl_generics anchor_lang::Discriminator for #name #ty_generics {
                 const DISCRIMINATOR: [u8; 8] = #discriminator;
             }
 
-            impl anchor_lang::Owner for #name {
+            impl #impl_generics anchor_lang::Owner for #name #ty_generics {
                 fn owner() -> Pubkey {
                     #program_id
                 }
@@ -8,6 +8,26 @@ use syn::parse_macro_input;
 
 /// The `#[program]` attribute defines the module containing all instruction
 /// handlers defining all entries into a Solana program.
+///
+/// With the `proptest` feature of `anchor-lang`, the structs of the generated
+/// `instruction` module implement `proptest::arbitrary::Arbitrary`, so that
+/// property-based tests can generate instructions with `any::<instruction::Ix>()`.
+/// Arguments are generated with the `Arbitrary` implementation of their type
+/// unless they have a `#[strategy(<expr>)]` attribute, e.g. to bound them.
+/// `Pubkey`s, which don't implement `Arbitrary`, are generated too, including
+/// in options, vectors, arrays and tuples. Other types that don't implement
+/// it, such as the types defined by the program, need a `#[strategy(..)]`:
+///
+/// ```ignore
+/// pub fn deposit(
+///     ctx: Context<Deposit>,
+///     #[strategy(1..=1_000_000u64)] amount: u64,
+///     #[strategy("[a-z]{1,32}")] memo: String,
+///     #[strategy(any::<u16>().prop_map(|fee| Config { fee }))] config: Config,
+/// ) -> Result<()> {
+///     // ...
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn program(
     _args: proc_macro::TokenStream,
@@ -103,3 +123,106 @@ pub fn interface(
     // discriminator.
     input
 }
+
+/// The `#[before_instruction]` attribute marks a function of the `#[program]`
+/// module to be run before every instruction handler, prior to the
+/// deserialization of the accounts.
+///
+/// The function must be public and receives the program id, the name of the
+/// instruction and the raw accounts. Returning an error aborts the instruction.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[program]
+/// pub mod my_program {
+///     use super::*;
+///
+///     #[before_instruction]
+///     pub fn check_paused(
+///         _program_id: &Pubkey,
+///         ix_name: &str,
+///         accounts: &[AccountInfo],
+///     ) -> Result<()> {
+///         // Reject every instruction but `unpause` while the program is paused
+///         ...
+///         Ok(())
+///     }
+///
+///     pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
+///         ...
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn before_instruction(
+    _args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    // This macro itself is a no-op, the `#[program]` macro detects this
+    // attribute and calls the function from every instruction handler.
+    input
+}
+
+/// The `#[after_instruction]` attribute marks a function of the `#[program]`
+/// module to be run after every instruction handler that succeeded, once the
+/// accounts have been persisted.
+///
+/// The function has the same signature as the one of
+/// [`#[before_instruction]`](macro@before_instruction). Returning an error
+/// aborts the instruction.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[program]
+/// pub mod my_program {
+///     use super::*;
+///
+///     #[after_instruction]
+///     pub fn count(_program_id: &Pubkey, _ix_name: &str, accounts: &[AccountInfo]) -> Result<()> {
+///         // Increment a metrics counter stored in the first account
+///         ...
+///         Ok(())
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn after_instruction(
+    _args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    // This macro itself is a no-op, the `#[program]` macro detects this
+    // attribute and calls the function from every instruction handler.
+    input
+}
+
+/// The `#[no_log_ix_name]` attribute disables the `Instruction: <name>` log
+/// of an instruction handler, which costs compute units and reveals which
+/// instruction is executed. Error logs are not affected.
+///
+/// The logs of every instruction can be disabled at once with the
+/// `no-log-ix-name` feature of the program crate.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[program]
+/// pub mod my_program {
+///     use super::*;
+///
+///     #[no_log_ix_name]
+///     pub fn crank(ctx: Context<Crank>) -> Result<()> {
+///         ...
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn no_log_ix_name(
+    _args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    // This macro itself is a no-op, the `#[program]` macro detects this
+    // attribute and skips the instruction name log.
+    input
+}
@@ -9,6 +9,7 @@ use syn::parse_macro_input;
 ///
 /// # Table of Contents
 /// - [Instruction Attribute](#instruction-attribute)
+/// - [Box Threshold](#box-threshold)
 /// - [Constraints](#constraints)
 ///
 /// # Instruction Attribute
@@ -34,6 +35,45 @@ use syn::parse_macro_input;
 /// }
 /// ```
 ///
+/// # Box Threshold
+///
+/// Large `Account` and `InterfaceAccount` fields are deserialized onto the
+/// stack, which is limited to 4KB per frame on Solana and usually shows up as
+/// a runtime access violation rather than a compile error. Setting
+/// `#[accounts(box_threshold = <bytes>)]` makes compilation fail for every
+/// unboxed account field whose type is larger than the given size, pointing
+/// at the fields that should be wrapped in a `Box`. The fields aren't boxed
+/// automatically, as derive macros can't change the types of fields.
+///
+/// The check only applies to account structs without type generics.
+///
+/// ```ignore
+/// #[derive(Accounts)]
+/// #[accounts(box_threshold = 1024)]
+/// pub struct Update<'info> {
+///     // Fails to compile if `Config` is larger than 1024 bytes
+///     pub config: Account<'info, Config>,
+///     pub large: Box<Account<'info, Large>>,
+/// }
+/// ```
+///
+/// # Signer Seeds
+///
+/// `#[accounts(signer_seeds)]` generates a `<field>_signer_seeds(bump)` method
+/// returning the [`SignerSeeds`](anchor_lang::SignerSeeds) of every PDA field
+/// of the struct derived from the current program. Fields whose seeds use
+/// instruction arguments, `?` or `load()` are skipped.
+///
+/// ```ignore
+/// #[derive(Accounts)]
+/// #[accounts(signer_seeds)]
+/// pub struct Withdraw<'info> {
+///     #[account(seeds = [b"vault", user.key().as_ref()], bump)]
+///     pub vault: SystemAccount<'info>,
+///     pub user: Signer<'info>,
+/// }
+/// ```
+///
 /// # Constraints
 ///
 /// There are different types of constraints that can be applied with the `#[account(..)]` attribute.
@@ -234,6 +274,29 @@ use syn::parse_macro_input;
 ///         </tr>
 ///         <tr>
 ///             <td>
+///                 <code>#[account(init_idempotent, payer = &lt;target_account&gt;, associated_token::mint = &lt;target_account&gt;, associated_token::authority = &lt;target_account&gt;)]</code>
+///             </td>
+///             <td>
+///                 Creates the associated token account with the associated token program's
+///                 <code>CreateIdempotent</code> instruction, which does nothing if the account
+///                 already exists. Since associated token accounts can't be re-initialized,
+///                 this doesn't require the <code>init-if-needed</code> feature. The account is
+///                 validated like with <code>init_if_needed</code>.
+///                 <br><br>
+///                 Example:
+///                 <pre>
+/// #[account(
+///     init_idempotent,
+///     payer = payer,
+///     associated_token::mint = mint,
+///     associated_token::authority = payer,
+/// )]
+/// pub token: InterfaceAccount<'info, TokenAccount>,
+///                 </pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
 ///                 <code>#[account(seeds = &lt;seeds&gt;, bump)]</code><br><br>
 ///                 <code>#[account(seeds = &lt;seeds&gt;, bump, seeds::program = &lt;expr&gt;)]<br><br>
 ///                 <code>#[account(seeds = &lt;seeds&gt;, bump = &lt;expr&gt;)]</code><br><br>
@@ -290,6 +353,15 @@ use syn::parse_macro_input;
 /// pub authority: Signer<'info>
 ///                 </code></pre>
 ///                 In this example <code>has_one</code> checks that <code>data.authority = authority.key()</code>
+///                 <br><br>
+///                 The target can also be a field of another account in the Accounts struct,
+///                 in which case the fields are compared directly:
+///                 <pre><code>
+/// #[account(has_one = market.authority)]
+/// pub vault: Account<'info, Vault>,
+/// pub market: Account<'info, Market>
+///                 </code></pre>
+///                 In this example <code>has_one</code> checks that <code>vault.authority = market.authority</code>
 ///             </td>
 ///         </tr>
 ///         <tr>
@@ -311,6 +383,33 @@ use syn::parse_macro_input;
 ///         </tr>
 ///         <tr>
 ///             <td>
+///                 <code>#[account(with_seed::base = &lt;target_account&gt;, with_seed::seed = &lt;expr&gt;)]</code><br><br>
+///                 <code>#[account(with_seed::base = &lt;target_account&gt;, with_seed::seed = &lt;expr&gt;, with_seed::owner = &lt;expr&gt;)]</code>
+///             </td>
+///             <td>
+///                 Checks the account key is the address derived with <code>Pubkey::create_with_seed</code>
+///                 from the base, the seed and the owner. The owner defaults to the one given with
+///                 <code>owner</code>, or the executing program.<br>
+///                 When used with <code>init</code>, the account is created with
+///                 <code>create_account_with_seed</code> and the base must sign instead of the account.
+///                 The account is created for the owner its address is derived with, so
+///                 <code>with_seed::owner</code> can't be used with <code>init</code>, use <code>owner</code> instead.<br><br>
+///                 Example:
+///                 <pre><code>
+/// #[account(
+///     init,
+///     payer = payer,
+///     space = 8 + Vault::INIT_SPACE,
+///     with_seed::base = authority,
+///     with_seed::seed = "vault",
+/// )]
+/// pub vault: Account<'info, Vault>,
+/// pub authority: Signer<'info>,
+///                 </code></pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
 ///                 <code>#[account(owner = &lt;expr&gt;)]</code><br><br>
 ///                 <code>#[account(owner = &lt;expr&gt; @ &lt;custom_error&gt;)]</code>
 ///             </td>
@@ -344,19 +443,29 @@ use syn::parse_macro_input;
 ///         <tr>
 ///             <td>
 ///                 <code>#[account(rent_exempt = skip)]</code><br><br>
-///                 <code>#[account(rent_exempt = enforce)]</code>
+///                 <code>#[account(rent_exempt = enforce)]</code><br><br>
+///                 <code>#[account(mut, rent_exempt = top_up(&lt;target_account&gt;))]</code>
 ///             </td>
 ///             <td>
 ///                 Enforces rent exemption with <code>= enforce</code>.<br>
 ///                 Skips rent exemption check that would normally be done
 ///                 through other constraints with <code>= skip</code>,
-///                 e.g. when used with the <code>zero</code> constraint<br><br>
+///                 e.g. when used with the <code>zero</code> constraint.<br>
+///                 Transfers the lamports missing for the account to be rent exempt
+///                 at its current size (after <code>realloc</code>, if any) from the given
+///                 payer with <code>= top_up(..)</code>, instead of erroring. Requires the
+///                 <code>system_program</code> to exist on the struct.<br><br>
 ///                 Example:
 ///                 <pre><code>
 /// #[account(zero, rent_exempt = skip)]
 /// pub skipped_account: Account<'info, MyData>,
 /// #[account(rent_exempt = enforce)]
-/// pub enforced_account: AccountInfo<'info>
+/// pub enforced_account: AccountInfo<'info>,
+/// #[account(mut, rent_exempt = top_up(payer))]
+/// pub topped_up_account: Account<'info, MyData>,
+/// #[account(mut)]
+/// pub payer: Signer<'info>,
+/// pub system_program: Program<'info, System>
 ///                 </code></pre>
 ///             </td>
 ///         </tr>
@@ -392,12 +501,85 @@ use syn::parse_macro_input;
 ///                 &nbsp;&nbsp;&nbsp;&nbsp;- Resetting the data of the account<br><br>
 ///                 Requires <code>mut</code> to exist on the account.
 ///                 <br><br>
+///                 The target can also be an expression evaluating to an account,
+///                 which allows choosing the receiver at runtime. The expression is
+///                 evaluated once, when the account is closed on exit. Use
+///                 <code>close_account</code> to close an account in the middle of
+///                 an instruction instead.
+///                 <br><br>
 ///                 Example:
 ///                 <pre><code>
 /// #[account(mut, close = receiver)]
 /// pub data_account: Account<'info, MyData>,
 /// #[account(mut)]
-/// pub receiver: SystemAccount<'info>
+/// pub receiver: SystemAccount<'info>,
+/// #[account(
+///     mut,
+///     close = if escrow.expired { maker.to_account_info() } else { taker.to_account_info() }
+/// )]
+/// pub escrow: Account<'info, Escrow>
+///                 </code></pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
+///                 <code>#[account(not_before = &lt;expr&gt;)]</code><br><br>
+///                 <code>#[account(not_after = &lt;expr&gt;)]</code><br><br>
+///                 <code>#[account(not_before_slot = &lt;expr&gt;)]</code><br><br>
+///                 <code>#[account(not_after_slot = &lt;expr&gt;)]</code>
+///             </td>
+///             <td>
+///                 Checks that the current unix timestamp (or slot for the <code>_slot</code>
+///                 variants) of the <code>Clock</code> sysvar is not before or not after
+///                 <code>expr</code>, e.g. for vesting or auction windows.<br>
+///                 Custom errors are supported via <code>@</code>.<br><br>
+///                 Example:
+///                 <pre><code>
+/// #[account(mut, not_before = auction.start_ts, not_after = auction.end_ts)]
+/// pub auction: Account<'info, Auction>,
+/// #[account(not_before_slot = vesting.cliff_slot @ MyError::Locked)]
+/// pub vesting: Account<'info, Vesting>
+///                 </code></pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
+///                 <code>#[account(authority_any_of = [&lt;expr&gt;, ...])]</code><br><br>
+///                 <code>#[account(authority_any_of = [&lt;expr&gt;, ...] @ &lt;custom_error&gt;)]</code>
+///             </td>
+///             <td>
+///                 Checks that the account is a signer and that its key is one of the given
+///                 keys, e.g. to migrate an authority from a wallet to a multisig without
+///                 breaking existing signers. Keys can be fields of the struct (compared with
+///                 their <code>.key()</code>) or expressions evaluating to a <code>Pubkey</code>.<br>
+///                 Custom errors are supported via <code>@</code>.<br><br>
+///                 Example:
+///                 <pre><code>
+/// #[account(authority_any_of = [config.admin, config.multisig, admin_pda])]
+/// pub authority: Signer<'info>,
+/// #[account(seeds = [b"admin"], bump)]
+/// pub admin_pda: UncheckedAccount<'info>
+///                 </code></pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
+///                 <code>#[account(reassign = &lt;expr&gt;)]</code>
+///             </td>
+///             <td>
+///                 Transfers the ownership of the account to the program <code>expr</code>
+///                 once the instruction has finished, e.g. to migrate it to another program, by:<br>
+///                 &nbsp;&nbsp;&nbsp;&nbsp;- Zeroing the data of the account, including the discriminator<br>
+///                 &nbsp;&nbsp;&nbsp;&nbsp;- Assigning the owner to <code>expr</code><br><br>
+///                 Requires <code>mut</code> to exist on the account and can't be used with
+///                 <code>close</code>. Combine it with <code>realloc</code> to resize the account
+///                 for the new owner. Use <code>reassign_account</code> to reassign an account in
+///                 the middle of an instruction instead.
+///                 <br><br>
+///                 Example:
+///                 <pre><code>
+/// #[account(mut, reassign = new_program::ID)]
+/// pub data_account: Account<'info, MyData>
 ///                 </code></pre>
 ///             </td>
 ///         </tr>
@@ -507,6 +689,34 @@ use syn::parse_macro_input;
 ///         </tr>
 ///         <tr>
 ///             <td>
+///                 <code>#[account(vault::mint = &lt;target_account&gt;, vault::authority = &lt;target_account&gt;)]</code>
+///             <br><br>
+///                 <code>#[account(vault::mint = &lt;target_account&gt;, vault::authority = &lt;target_account&gt;, vault::token_program = &lt;target_account&gt;)]</code>
+///             </td>
+///             <td>
+///                 Same as the <code>token</code> constraints for a token account owned by a PDA of
+///                 the program. When used as a check, the token account must additionally have no
+///                 delegate and no close authority. The authority must be provided and must be a
+///                 field of the struct with <code>seeds</code> and <code>bump</code>, without
+///                 <code>seeds::program</code>, so that it's a PDA of the executing program.
+///                 <br><br>
+///                 Example:
+///                 <pre>
+/// use anchor_spl::vault::Vault;
+/// ...&#10;
+/// #[account(seeds = [b"pool"], bump)]
+/// pub pool: Account<'info, Pool>,
+/// #[account(
+///     mut,
+///     vault::mint = mint,
+///     vault::authority = pool,
+/// )]
+/// pub vault: Vault<'info>,
+///                 </pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
 ///                 <code>#[account(mint::authority = &lt;target_account&gt;, mint::decimals = &lt;expr&gt;)]</code>
 ///                 <br><br>
 ///                 <code>#[account(mint::authority = &lt;target_account&gt;, mint::decimals = &lt;expr&gt;, mint::freeze_authority = &lt;target_account&gt;)]</code>
@@ -545,6 +755,70 @@ use syn::parse_macro_input;
 ///         </tr>
 ///         <tr>
 ///             <td>
+///                 <code>#[account(mint::close_authority = &lt;target_account&gt;)]</code><br><br>
+///                 <code>#[account(mint::close_authority = none)]</code><br><br>
+///                 <code>#[account(mint::permanent_delegate = &lt;target_account&gt;)]</code><br><br>
+///                 <code>#[account(mint::permanent_delegate = none)]</code>
+///             </td>
+///             <td>
+///                 Checks the close authority or permanent delegate of a Token-2022 mint.
+///                 With <code>none</code>, the mint must either not have the extension or have it
+///                 set to no authority, which allows rejecting mints that could be closed or
+///                 whose tokens could be moved by a third party. Can't be used with <code>init</code>.
+///                 <br><br>
+///                 Example:
+///                 <pre>
+/// #[account(
+///     mint::close_authority = none,
+///     mint::permanent_delegate = none,
+/// )]
+/// pub collateral_mint: InterfaceAccount<'info, Mint>,
+///                 </pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
+///                 <code>#[account(extensions::&lt;extension&gt;)]</code><br><br>
+///                 <code>#[account(extensions::&lt;extension&gt; = none)]</code>
+///             </td>
+///             <td>
+///                 Checks that the given Token-2022 extension is enabled on (or absent from)
+///                 a mint or token account. The extension is the snake case name of its
+///                 <code>ExtensionType</code> variant, with the shorthands <code>transfer_fee</code>,
+///                 <code>interest_bearing</code>, <code>close_authority</code>, <code>metadata</code>,
+///                 <code>group</code> and <code>group_member</code>.<br><br>
+///                 Example:
+///                 <pre>
+/// use anchor_spl::token_interface::Mint;
+/// ...&#10;
+/// #[account(
+///     extensions::transfer_fee,
+///     extensions::permanent_delegate = none,
+///     extensions::non_transferable = none,
+/// )]
+/// pub mint: InterfaceAccount<'info, Mint>,
+///                 </pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
+///                 <code>#[account(extensions::group_member::group = &lt;target_account&gt;)]</code>
+///             </td>
+///             <td>
+///                 Checks that the mint is a member of the given token group, i.e. that its
+///                 <code>TokenGroupMember</code> extension points to the group. Can't be used with
+///                 <code>init</code>, members must be added with <code>token_member_initialize</code>.
+///                 <br><br>
+///                 Example:
+///                 <pre>
+/// #[account(extensions::group_member::group = collection)]
+/// pub nft: InterfaceAccount<'info, Mint>,
+/// pub collection: InterfaceAccount<'info, Mint>,
+///                 </pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
 ///                 <code>#[account(associated_token::mint = &lt;target_account&gt;, associated_token::authority = &lt;target_account&gt;)]</code>
 ///                <br><br>
 ///                 <code>#[account(associated_token::mint = &lt;target_account&gt;, associated_token::authority = &lt;target_account&gt;, associated_token::token_program = &lt;target_account&gt;)]</code>
@@ -627,7 +901,7 @@ use syn::parse_macro_input;
 ///         </tr>
 ///     <tbody>
 /// </table>
-#[proc_macro_derive(Accounts, attributes(account, instruction))]
+#[proc_macro_derive(Accounts, attributes(account, accounts, instruction))]
 pub fn derive_accounts(item: TokenStream) -> TokenStream {
     parse_macro_input!(item as anchor_syn::AccountsStruct)
         .to_token_stream()
@@ -23,12 +23,39 @@ fn gen_borsh_serialize(input: TokenStream) -> TokenStream2 {
     }
 }
 
+/// Generates a `BorshSchema` implementation, which describes the Borsh layout
+/// of the type.
+///
+/// Unions don't have a Borsh schema, so nothing is generated for them.
+#[cfg(feature = "borsh-schema")]
+fn gen_borsh_schema(input: TokenStream) -> TokenStream2 {
+    let cratename = Ident::new("borsh", Span::call_site());
+
+    let item: Item = syn::parse(input).unwrap();
+    let res = match item {
+        Item::Struct(item) => borsh_schema_derive_internal::process_struct(&item, cratename),
+        Item::Enum(item) => borsh_schema_derive_internal::process_enum(&item, cratename),
+        Item::Union(_) => Ok(TokenStream2::new()),
+        // Derive macros can only be defined on structs, enums, and unions.
+        _ => unreachable!(),
+    };
+
+    match res {
+        Ok(res) => res,
+        Err(err) => err.to_compile_error(),
+    }
+}
+
 #[proc_macro_derive(AnchorSerialize, attributes(borsh_skip))]
 pub fn anchor_serialize(input: TokenStream) -> TokenStream {
-    #[cfg(not(feature = "idl-build"))]
+    #[cfg(not(any(feature = "idl-build", feature = "borsh-schema")))]
     let ret = gen_borsh_serialize(input);
-    #[cfg(feature = "idl-build")]
-    let ret = gen_borsh_serialize(input.clone());
+    #[cfg(any(feature = "idl-build", feature = "borsh-schema"))]
+    #[allow(unused_mut)]
+    let mut ret = gen_borsh_serialize(input.clone());
+
+    #[cfg(feature = "borsh-schema")]
+    ret.extend(gen_borsh_schema(input.clone()));
 
     #[cfg(feature = "idl-build")]
     {
@@ -255,8 +255,15 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Clone> Account<'a, T> {
         expected_owner: &Pubkey,
         program_id: &Pubkey,
     ) -> Result<()> {
-        // Only persist if the owner is the current program and the account is not closed.
-        if expected_owner == program_id && !crate::common::is_closed(self.info) {
+        // Only persist if the owner is the current program and the account is not closed
+        // or reassigned. Accounts whose lamports have been drained without closing them
+        // are zeroed instead.
+        if expected_owner != program_id || self.info.owner != program_id {
+            return Ok(());
+        }
+        if crate::common::is_drained(self.info) {
+            crate::common::mark_closed(self.info)?;
+        } else {
             let info = self.to_account_info();
             let mut data = info.try_borrow_mut_data()?;
             let dst: &mut [u8] = &mut data;
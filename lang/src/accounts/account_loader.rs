@@ -4,9 +4,10 @@ use crate::bpf_writer::BpfWriter;
 use crate::error::{Error, ErrorCode};
 use crate::{
     Accounts, AccountsClose, AccountsExit, Key, Owner, Result, ToAccountInfo, ToAccountInfos,
-    ToAccountMetas, ZeroCopy,
+    ToAccountMetas, ZeroCopy, ZeroCopySlice,
 };
 use arrayref::array_ref;
+use bytemuck::Pod;
 use solana_program::account_info::AccountInfo;
 use solana_program::instruction::AccountMeta;
 use solana_program::pubkey::Pubkey;
@@ -212,6 +213,77 @@ impl<'info, T: ZeroCopy + Owner> AccountLoader<'info, T> {
             bytemuck::from_bytes_mut(&mut data.deref_mut()[8..mem::size_of::<T>() + 8])
         }))
     }
+
+    /// Returns the account size required to hold the header and `len` items
+    /// of a [`ZeroCopySlice`], including the discriminator.
+    pub const fn slice_space<Item: Pod>(len: usize) -> usize
+    where
+        T: ZeroCopySlice<Item>,
+    {
+        8 + mem::size_of::<T>() + len * mem::size_of::<Item>()
+    }
+
+    /// Returns Refs to the header and the items that follow it for reading.
+    pub fn load_slice<Item: Pod>(&self) -> Result<(Ref<'_, T>, Ref<'_, [Item]>)>
+    where
+        T: ZeroCopySlice<Item>,
+    {
+        let data_len = self.acc_info.data_len();
+        let header = self.load()?;
+        let range = Self::slice_range::<Item>(&header, data_len)?;
+        drop(header);
+
+        let data = self.acc_info.try_borrow_data()?;
+        if bytemuck::try_cast_slice::<u8, Item>(&data[range.clone()]).is_err() {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        Ok(Ref::map_split(data, |data| {
+            let (header, items) = data.split_at(range.start);
+            (
+                bytemuck::from_bytes(&header[8..]),
+                bytemuck::cast_slice(&items[..range.len()]),
+            )
+        }))
+    }
+
+    /// Returns `RefMut`s to the header and the items that follow it for
+    /// reading or writing.
+    pub fn load_slice_mut<Item: Pod>(&self) -> Result<(RefMut<'_, T>, RefMut<'_, [Item]>)>
+    where
+        T: ZeroCopySlice<Item>,
+    {
+        let data_len = self.acc_info.data_len();
+        let header = self.load_mut()?;
+        let range = Self::slice_range::<Item>(&header, data_len)?;
+        drop(header);
+
+        let mut data = self.acc_info.try_borrow_mut_data()?;
+        if bytemuck::try_cast_slice_mut::<u8, Item>(&mut data[range.clone()]).is_err() {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        Ok(RefMut::map_split(data, |data| {
+            let (header, items) = data.deref_mut().split_at_mut(range.start);
+            (
+                bytemuck::from_bytes_mut(&mut header[8..]),
+                bytemuck::cast_slice_mut(&mut items[..range.len()]),
+            )
+        }))
+    }
+
+    /// Returns the range of the account data occupied by the slice items.
+    fn slice_range<Item: Pod>(header: &T, data_len: usize) -> Result<std::ops::Range<usize>>
+    where
+        T: ZeroCopySlice<Item>,
+    {
+        let start = 8 + mem::size_of::<T>();
+        let end = header
+            .slice_len()
+            .checked_mul(mem::size_of::<Item>())
+            .and_then(|len| len.checked_add(start))
+            .filter(|end| *end <= data_len)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Ok(start..end)
+    }
 }
 
 impl<'info, B, T: ZeroCopy + Owner> Accounts<'info, B> for AccountLoader<'info, T> {
@@ -236,8 +308,15 @@ impl<'info, B, T: ZeroCopy + Owner> Accounts<'info, B> for AccountLoader<'info,
 impl<'info, T: ZeroCopy + Owner> AccountsExit<'info> for AccountLoader<'info, T> {
     // The account *cannot* be loaded when this is called.
     fn exit(&self, program_id: &Pubkey) -> Result<()> {
-        // Only persist if the owner is the current program and the account is not closed.
-        if &T::owner() == program_id && !crate::common::is_closed(self.acc_info) {
+        // Only persist if the owner is the current program and the account is not closed
+        // or reassigned. Accounts whose lamports have been drained without closing them
+        // are zeroed instead.
+        if &T::owner() != program_id || self.acc_info.owner != program_id {
+            return Ok(());
+        }
+        if crate::common::is_drained(self.acc_info) {
+            crate::common::mark_closed(self.acc_info)?;
+        } else {
             let mut data = self.acc_info.try_borrow_mut_data()?;
             let dst: &mut [u8] = &mut data;
             let mut writer = BpfWriter::new(dst);
@@ -0,0 +1,374 @@
+//! Account container for a fixed header followed by variable-length data.
+
+use crate::bpf_writer::BpfWriter;
+use crate::context::CpiContext;
+use crate::error::{Error, ErrorCode};
+use crate::system_program::{self, Transfer};
+use crate::{
+    AccountDeserialize, AccountSerialize, Accounts, AccountsClose, AccountsExit, Key, Owner,
+    Result, Space, ToAccountInfo, ToAccountInfos, ToAccountMetas,
+};
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::AccountMeta;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use std::cell::{Ref, RefMut};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// Size of the tail's length prefix.
+const TAIL_LEN_PREFIX_SIZE: usize = 4;
+
+/// Account container for accounts that store a fixed size header `T` followed
+/// by a length-prefixed byte tail, e.g. for variable-length blobs that would
+/// be too expensive to deserialize as part of `T`.
+///
+/// The account data layout is:
+///
+/// ```text
+/// discriminator (8) | header (T::INIT_SPACE) | tail length (u32, 4) | tail
+/// ```
+///
+/// Only the header is deserialized. The tail is read and written in place
+/// through [`tail`](Self::tail) and [`tail_mut`](Self::tail_mut), and can be
+/// resized with [`append_tail`](Self::append_tail) and
+/// [`truncate_tail`](Self::truncate_tail), which reallocate the account and
+/// keep it rent exempt.
+///
+/// Checks:
+///
+/// - `AccountWithTail.info.owner == T::owner()`
+/// - `!(AccountWithTail.info.owner == SystemProgram && AccountWithTail.info.lamports() == 0)`
+/// - The tail length prefix is within the account data
+///
+/// # Example
+/// ```ignore
+/// #[account]
+/// #[derive(InitSpace)]
+/// pub struct Blob {
+///     pub authority: Pubkey,
+/// }
+///
+/// #[derive(Accounts)]
+/// pub struct Create<'info> {
+///     #[account(init, payer = payer, space = AccountWithTail::<Blob>::space(0))]
+///     pub blob: AccountWithTail<'info, Blob>,
+///     #[account(mut)]
+///     pub payer: Signer<'info>,
+///     pub system_program: Program<'info, System>,
+/// }
+///
+/// #[derive(Accounts)]
+/// pub struct Append<'info> {
+///     #[account(mut, has_one = authority)]
+///     pub blob: AccountWithTail<'info, Blob>,
+///     #[account(mut)]
+///     pub authority: Signer<'info>,
+///     pub system_program: Program<'info, System>,
+/// }
+///
+/// pub fn append(ctx: Context<Append>, bytes: Vec<u8>) -> Result<()> {
+///     let authority = ctx.accounts.authority.to_account_info();
+///     let system_program = ctx.accounts.system_program.to_account_info();
+///     ctx.accounts.blob.append_tail(&bytes, &authority, &system_program)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AccountWithTail<'info, T: AccountSerialize + AccountDeserialize + Space + Clone> {
+    account: T,
+    info: &'info AccountInfo<'info>,
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Space + Clone + fmt::Debug> fmt::Debug
+    for AccountWithTail<'info, T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountWithTail")
+            .field("account", &self.account)
+            .field("info", &self.info)
+            .finish()
+    }
+}
+
+impl<'a, T: AccountSerialize + AccountDeserialize + Space + Clone> AccountWithTail<'a, T> {
+    /// Offset of the tail's length prefix.
+    pub const TAIL_OFFSET: usize = 8 + T::INIT_SPACE;
+
+    /// Returns the account space required to store a tail of `tail_len` bytes.
+    pub const fn space(tail_len: usize) -> usize {
+        Self::TAIL_OFFSET + TAIL_LEN_PREFIX_SIZE + tail_len
+    }
+
+    fn new(info: &'a AccountInfo<'a>, account: T) -> Result<AccountWithTail<'a, T>> {
+        let account = Self { info, account };
+        if account.info.data_len() < Self::space(account.tail_len()?) {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        Ok(account)
+    }
+
+    /// Returns the length of the tail.
+    pub fn tail_len(&self) -> Result<usize> {
+        let data = self.info.try_borrow_data()?;
+        let prefix = data
+            .get(Self::TAIL_OFFSET..Self::TAIL_OFFSET + TAIL_LEN_PREFIX_SIZE)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Ok(u32::from_le_bytes(prefix.try_into().unwrap()) as usize)
+    }
+
+    /// Borrows the tail.
+    pub fn tail(&self) -> Result<Ref<'_, [u8]>> {
+        let start = Self::space(0);
+        let end = start + self.tail_len()?;
+        let data = self.info.try_borrow_data()?;
+        Ok(Ref::map(data, |data| &data[start..end]))
+    }
+
+    /// Mutably borrows the tail.
+    pub fn tail_mut(&self) -> Result<RefMut<'_, [u8]>> {
+        let start = Self::space(0);
+        let end = start + self.tail_len()?;
+        let data = self.info.try_borrow_mut_data()?;
+        Ok(RefMut::map(data, |data| &mut data[start..end]))
+    }
+
+    /// Appends `bytes` to the tail, reallocating the account and transferring
+    /// the additional rent from `payer`.
+    pub fn append_tail(
+        &mut self,
+        bytes: &[u8],
+        payer: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> Result<()> {
+        let tail_len = self.tail_len()?;
+        let new_tail_len = tail_len
+            .checked_add(bytes.len())
+            .filter(|len| *len <= u32::MAX as usize)
+            .ok_or(ErrorCode::AccountDidNotSerialize)?;
+        let new_space = Self::space(new_tail_len);
+
+        let required_lamports = Rent::get()?.minimum_balance(new_space);
+        let lamports = self.info.lamports();
+        if lamports < required_lamports {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: self.info.clone(),
+                    },
+                ),
+                required_lamports - lamports,
+            )?;
+        }
+        if self.info.data_len() < new_space {
+            self.info.realloc(new_space, false)?;
+        }
+
+        let start = Self::space(tail_len);
+        let mut data = self.info.try_borrow_mut_data()?;
+        data[start..start + bytes.len()].copy_from_slice(bytes);
+        Self::write_tail_len(&mut data, new_tail_len);
+        Ok(())
+    }
+
+    /// Truncates the tail to `len` bytes, reallocating the account and
+    /// transferring the rent that's no longer needed to `sol_destination`.
+    ///
+    /// Does nothing if the tail is already shorter than `len`.
+    pub fn truncate_tail(&mut self, len: usize, sol_destination: &AccountInfo<'a>) -> Result<()> {
+        if len >= self.tail_len()? {
+            return Ok(());
+        }
+        let new_space = Self::space(len);
+        Self::write_tail_len(&mut self.info.try_borrow_mut_data()?, len);
+        self.info.realloc(new_space, false)?;
+
+        let required_lamports = Rent::get()?.minimum_balance(new_space);
+        let excess_lamports = self.info.lamports().saturating_sub(required_lamports);
+        if excess_lamports > 0 {
+            **self.info.try_borrow_mut_lamports()? -= excess_lamports;
+            **sol_destination.try_borrow_mut_lamports()? += excess_lamports;
+        }
+        Ok(())
+    }
+
+    fn write_tail_len(data: &mut [u8], len: usize) {
+        data[Self::TAIL_OFFSET..Self::TAIL_OFFSET + TAIL_LEN_PREFIX_SIZE]
+            .copy_from_slice(&(len as u32).to_le_bytes());
+    }
+
+    /// Reloads the header from storage. This is useful, for example, when
+    /// observing side effects after CPI.
+    pub fn reload(&mut self) -> Result<()> {
+        let mut data: &[u8] = &self.info.try_borrow_data()?;
+        self.account = T::try_deserialize(&mut data)?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> T {
+        self.account
+    }
+
+    /// Sets the inner header.
+    pub fn set_inner(&mut self, inner: T) {
+        self.account = inner;
+    }
+}
+
+impl<'a, T: AccountSerialize + AccountDeserialize + Owner + Space + Clone> AccountWithTail<'a, T> {
+    /// Deserializes the header of the given `info` into an `AccountWithTail`.
+    #[inline(never)]
+    pub fn try_from(info: &'a AccountInfo<'a>) -> Result<AccountWithTail<'a, T>> {
+        Self::check_owner(info)?;
+        let account = {
+            let mut data: &[u8] = &info.try_borrow_data()?;
+            T::try_deserialize(&mut data)?
+        };
+        Self::new(info, account)
+    }
+
+    /// Deserializes the header of the given `info` into an `AccountWithTail`
+    /// without checking the account discriminator. Be careful when using this
+    /// and avoid it if possible.
+    #[inline(never)]
+    pub fn try_from_unchecked(info: &'a AccountInfo<'a>) -> Result<AccountWithTail<'a, T>> {
+        Self::check_owner(info)?;
+        let account = {
+            let mut data: &[u8] = &info.try_borrow_data()?;
+            T::try_deserialize_unchecked(&mut data)?
+        };
+        Self::new(info, account)
+    }
+
+    fn check_owner(info: &AccountInfo) -> Result<()> {
+        if info.owner == &solana_program::system_program::ID && info.lamports() == 0 {
+            return Err(ErrorCode::AccountNotInitialized.into());
+        }
+        if info.owner != &T::owner() {
+            return Err(Error::from(ErrorCode::AccountOwnedByWrongProgram)
+                .with_pubkeys((*info.owner, T::owner())));
+        }
+        Ok(())
+    }
+}
+
+impl<'info, B, T: AccountSerialize + AccountDeserialize + Owner + Space + Clone> Accounts<'info, B>
+    for AccountWithTail<'info, T>
+{
+    #[inline(never)]
+    fn try_accounts(
+        _program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        _ix_data: &[u8],
+        _bumps: &mut B,
+        _reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        if accounts.is_empty() {
+            return Err(ErrorCode::AccountNotEnoughKeys.into());
+        }
+        let account = &accounts[0];
+        *accounts = &accounts[1..];
+        AccountWithTail::try_from(account)
+    }
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Owner + Space + Clone> AccountsExit<'info>
+    for AccountWithTail<'info, T>
+{
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        // Only persist if the owner is the current program and the account is not closed
+        // or reassigned. Accounts whose lamports have been drained without closing them
+        // are zeroed instead.
+        if &T::owner() != program_id || self.info.owner != program_id {
+            return Ok(());
+        }
+        if crate::common::is_drained(self.info) {
+            crate::common::mark_closed(self.info)?;
+        } else {
+            let mut data = self.info.try_borrow_mut_data()?;
+            let dst: &mut [u8] = &mut data[..Self::TAIL_OFFSET];
+            let mut writer = BpfWriter::new(dst);
+            self.account.try_serialize(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Space + Clone> AccountsClose<'info>
+    for AccountWithTail<'info, T>
+{
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()> {
+        crate::common::close(self.to_account_info(), sol_destination)
+    }
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Space + Clone> ToAccountMetas
+    for AccountWithTail<'info, T>
+{
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let is_signer = is_signer.unwrap_or(self.info.is_signer);
+        let meta = match self.info.is_writable {
+            false => AccountMeta::new_readonly(*self.info.key, is_signer),
+            true => AccountMeta::new(*self.info.key, is_signer),
+        };
+        vec![meta]
+    }
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Space + Clone> ToAccountInfos<'info>
+    for AccountWithTail<'info, T>
+{
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.info.clone()]
+    }
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Space + Clone> AsRef<AccountInfo<'info>>
+    for AccountWithTail<'info, T>
+{
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        self.info
+    }
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Space + Clone> AsRef<T>
+    for AccountWithTail<'info, T>
+{
+    fn as_ref(&self) -> &T {
+        &self.account
+    }
+}
+
+impl<'a, T: AccountSerialize + AccountDeserialize + Space + Clone> Deref
+    for AccountWithTail<'a, T>
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.account
+    }
+}
+
+impl<'a, T: AccountSerialize + AccountDeserialize + Space + Clone> DerefMut
+    for AccountWithTail<'a, T>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        #[cfg(feature = "anchor-debug")]
+        if !self.info.is_writable {
+            solana_program::msg!("The given AccountWithTail is not mutable");
+            panic!();
+        }
+        &mut self.account
+    }
+}
+
+impl<'info, T: AccountSerialize + AccountDeserialize + Space + Clone> Key
+    for AccountWithTail<'info, T>
+{
+    fn key(&self) -> Pubkey {
+        *self.info.key
+    }
+}
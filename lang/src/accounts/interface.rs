@@ -72,6 +72,8 @@ use std::ops::Deref;
 ///
 /// - [`TokenInterface`](https://docs.rs/anchor-spl/latest/anchor_spl/token_interface/struct.TokenInterface.html)
 ///
+/// Other interfaces can be declared with [`declare_interface!`](crate::declare_interface).
+///
 #[derive(Clone)]
 pub struct Interface<'info, T>(Program<'info, T>);
 impl<'a, T> Interface<'a, T> {
@@ -3,6 +3,7 @@
 pub mod account;
 pub mod account_info;
 pub mod account_loader;
+pub mod account_with_tail;
 pub mod boxed;
 pub mod interface;
 pub mod interface_account;
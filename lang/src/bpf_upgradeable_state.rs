@@ -10,6 +10,21 @@ pub struct ProgramData {
     pub upgrade_authority_address: Option<Pubkey>,
 }
 
+impl ProgramData {
+    /// Offset of the program's ELF within the program data account.
+    pub const DATA_OFFSET: usize = UpgradeableLoaderState::size_of_programdata_metadata();
+
+    /// Whether the program can still be upgraded.
+    pub fn is_upgradeable(&self) -> bool {
+        self.upgrade_authority_address.is_some()
+    }
+
+    /// Whether `authority` is the upgrade authority of the program.
+    pub fn is_upgrade_authority(&self, authority: &Pubkey) -> bool {
+        self.upgrade_authority_address.as_ref() == Some(authority)
+    }
+}
+
 impl AccountDeserialize for ProgramData {
     fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
         ProgramData::try_deserialize_unchecked(buf)
@@ -50,6 +65,63 @@ impl Owner for ProgramData {
     }
 }
 
+/// The `Buffer` variant of [`UpgradeableLoaderState`], i.e. an account that
+/// holds a program's ELF before it's deployed or used for an upgrade.
+///
+/// # Example
+/// ```ignore
+/// #[derive(Accounts)]
+/// pub struct CheckBuffer<'info> {
+///     #[account(constraint = buffer.is_authority(&admin.key()))]
+///     pub buffer: Account<'info, Buffer>,
+///     pub admin: Signer<'info>,
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Buffer {
+    pub authority_address: Option<Pubkey>,
+}
+
+impl Buffer {
+    /// Offset of the program's ELF within the buffer account.
+    pub const DATA_OFFSET: usize = UpgradeableLoaderState::size_of_buffer_metadata();
+
+    /// Whether `authority` is the authority of the buffer.
+    pub fn is_authority(&self, authority: &Pubkey) -> bool {
+        self.authority_address.as_ref() == Some(authority)
+    }
+}
+
+impl AccountDeserialize for Buffer {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        Buffer::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let program_state = AccountDeserialize::try_deserialize_unchecked(buf)?;
+
+        match program_state {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                Ok(Buffer { authority_address })
+            }
+            _ => Err(ErrorCode::AccountNotBuffer.into()),
+        }
+    }
+}
+
+impl AccountSerialize for Buffer {
+    fn try_serialize<W: std::io::Write>(&self, _writer: &mut W) -> Result<()> {
+        // no-op
+        Ok(())
+    }
+}
+
+impl Owner for Buffer {
+    fn owner() -> Pubkey {
+        anchor_lang::solana_program::bpf_loader_upgradeable::ID
+    }
+}
+
 impl Owner for UpgradeableLoaderState {
     fn owner() -> Pubkey {
         anchor_lang::solana_program::bpf_loader_upgradeable::ID
@@ -81,4 +153,9 @@ mod idl_build {
     impl crate::Discriminator for ProgramData {
         const DISCRIMINATOR: [u8; 8] = [u8::MAX; 8];
     }
+
+    impl crate::IdlBuild for Buffer {}
+    impl crate::Discriminator for Buffer {
+        const DISCRIMINATOR: [u8; 8] = [u8::MAX; 8];
+    }
 }
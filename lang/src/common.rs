@@ -1,9 +1,19 @@
 use crate::prelude::{Id, System};
 use crate::Result;
 use solana_program::account_info::AccountInfo;
+use solana_program::pubkey::Pubkey;
 use solana_program::system_program;
 
+/// Discriminator written to accounts owned by the program whose lamports
+/// have been drained without closing them, so that they can't be
+/// deserialized again if they're refunded later in the same transaction.
+pub const CLOSED_ACCOUNT_DISCRIMINATOR: [u8; 8] = [255; 8];
+
 pub fn close<'info>(info: AccountInfo<'info>, sol_destination: AccountInfo<'info>) -> Result<()> {
+    // Zero the data so that it can't be recovered by reallocating the account
+    // later in the same transaction.
+    info.try_borrow_mut_data()?.fill(0);
+
     // Transfer tokens from the account to the sol_destination.
     let dest_starting_lamports = sol_destination.lamports();
     **sol_destination.lamports.borrow_mut() =
@@ -17,3 +27,71 @@ pub fn close<'info>(info: AccountInfo<'info>, sol_destination: AccountInfo<'info
 pub fn is_closed(info: &AccountInfo) -> bool {
     info.owner == &System::id() && info.data_is_empty()
 }
+
+/// Whether the lamports of the account have been drained, e.g. via CPI,
+/// without closing the account.
+pub fn is_drained(info: &AccountInfo) -> bool {
+    info.lamports() == 0 && !is_closed(info)
+}
+
+/// Zeroes the data of a drained account and sets the closed account
+/// discriminator.
+pub fn mark_closed(info: &AccountInfo) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    data.fill(0);
+    if let Some(discriminator) = data.get_mut(..CLOSED_ACCOUNT_DISCRIMINATOR.len()) {
+        discriminator.copy_from_slice(&CLOSED_ACCOUNT_DISCRIMINATOR);
+    }
+    Ok(())
+}
+
+/// Closes the account, transferring all of its lamports to `sol_destination`.
+///
+/// The account data is zeroed, and the account is reassigned to the system
+/// program and reallocated to zero bytes, so that it can't be revived later
+/// in the same transaction. Unlike the `close` constraint, which closes the
+/// account once the instruction has finished, this can be used in the middle
+/// of an instruction and with a receiver chosen at runtime.
+///
+/// # Example
+/// ```ignore
+/// pub fn settle(ctx: Context<Settle>) -> Result<()> {
+///     let receiver = if ctx.accounts.escrow.expired {
+///         ctx.accounts.maker.to_account_info()
+///     } else {
+///         ctx.accounts.taker.to_account_info()
+///     };
+///     close_account(&ctx.accounts.escrow.to_account_info(), &receiver)
+/// }
+/// ```
+pub fn close_account<'info>(
+    info: &AccountInfo<'info>,
+    sol_destination: &AccountInfo<'info>,
+) -> Result<()> {
+    close(info.clone(), sol_destination.clone())
+}
+
+/// Transfers the ownership of an account owned by the current program to
+/// `new_owner`, e.g. to migrate it to another program.
+///
+/// The runtime only allows reassigning accounts with zeroed data, so the data,
+/// including the discriminator, is zeroed first. The data length is kept, use
+/// [`AccountInfo::realloc`] beforehand to resize the account for the new
+/// owner. To migrate the account back, the other program has to reassign it
+/// with zeroed data, after which it can be initialized again with the `zero`
+/// constraint.
+///
+/// Anchor doesn't persist reassigned accounts on exit.
+///
+/// # Example
+/// ```ignore
+/// pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+///     let info = ctx.accounts.state.to_account_info();
+///     reassign_account(&info, &new_program::ID)
+/// }
+/// ```
+pub fn reassign_account(info: &AccountInfo, new_owner: &Pubkey) -> Result<()> {
+    info.try_borrow_mut_data()?.fill(0);
+    info.assign(new_owner);
+    Ok(())
+}
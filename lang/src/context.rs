@@ -3,6 +3,7 @@
 use crate::{Accounts, Bumps, ToAccountInfos, ToAccountMetas};
 use solana_program::account_info::AccountInfo;
 use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use std::fmt;
 
@@ -247,3 +248,74 @@ impl<'info, T: ToAccountInfos<'info> + ToAccountMetas> ToAccountMetas
         metas
     }
 }
+
+/// Owned seeds of a program derived address, including its bump, that can be
+/// used to sign cross-program-invocations.
+///
+/// `#[derive(Accounts)]` with `#[accounts(signer_seeds)]` generates a
+/// `<field>_signer_seeds(bump)` method returning this type for every PDA
+/// derived from the current program, so the nested seed slices don't have to
+/// be built by hand.
+///
+/// # Example
+/// ```ignore
+/// #[derive(Accounts)]
+/// #[accounts(signer_seeds)]
+/// pub struct Withdraw<'info> {
+///     #[account(seeds = [b"vault", user.key().as_ref()], bump)]
+///     pub vault: SystemAccount<'info>,
+///     pub user: Signer<'info>,
+///     // ...
+/// }
+///
+/// pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+///     let seeds = ctx.accounts.vault_signer_seeds(ctx.bumps.vault);
+///     seeds.with_signer(|signer| {
+///         let cpi_ctx = CpiContext::new_with_signer(program, accounts, signer);
+///         transfer(cpi_ctx, amount)
+///     })
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignerSeeds {
+    seeds: Vec<Vec<u8>>,
+}
+
+impl SignerSeeds {
+    /// Creates the signer seeds from the given seeds and bump.
+    pub fn new(mut seeds: Vec<Vec<u8>>, bump: u8) -> Self {
+        seeds.push(vec![bump]);
+        Self { seeds }
+    }
+
+    /// Returns the seeds, bump included, as slices.
+    pub fn seeds(&self) -> Vec<&[u8]> {
+        self.seeds.iter().map(Vec::as_slice).collect()
+    }
+
+    /// Returns the bump of the seeds.
+    pub fn bump(&self) -> u8 {
+        self.seeds.last().map(|bump| bump[0]).unwrap_or_default()
+    }
+
+    /// Derives the address of the PDA for `program_id`.
+    pub fn address(&self, program_id: &Pubkey) -> crate::Result<Pubkey> {
+        Pubkey::create_program_address(&self.seeds(), program_id)
+            .map_err(|e| ProgramError::from(e).into())
+    }
+
+    /// Calls `f` with the seeds in the form expected by
+    /// [`CpiContext::new_with_signer`] and `invoke_signed`.
+    pub fn with_signer<R>(&self, f: impl FnOnce(&[&[&[u8]]]) -> R) -> R {
+        let seeds = self.seeds();
+        f(&[&seeds[..]])
+    }
+
+    /// Calls `f` with the seeds of all of `signers`, which is required when a
+    /// cross-program-invocation needs signatures from multiple PDAs.
+    pub fn with_signers<R>(signers: &[&SignerSeeds], f: impl FnOnce(&[&[&[u8]]]) -> R) -> R {
+        let seeds: Vec<Vec<&[u8]>> = signers.iter().map(|signer| signer.seeds()).collect();
+        let seeds: Vec<&[&[u8]]> = seeds.iter().map(Vec::as_slice).collect();
+        f(&seeds)
+    }
+}
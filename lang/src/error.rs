@@ -177,6 +177,39 @@ pub enum ErrorCode {
     /// 2039 - A transfer hook extension transfer hook program id constraint was violated
     #[msg("A transfer hook extension transfer hook program id constraint was violated")]
     ConstraintMintTransferHookExtensionProgramId,
+    /// 2040 - A not_before constraint was violated
+    #[msg("A not_before constraint was violated")]
+    ConstraintNotBefore,
+    /// 2041 - A not_after constraint was violated
+    #[msg("A not_after constraint was violated")]
+    ConstraintNotAfter,
+    /// 2042 - An authority_any_of constraint was violated
+    #[msg("An authority_any_of constraint was violated")]
+    ConstraintAuthorityAnyOf,
+    /// 2043 - A required token extension is missing
+    #[msg("A required token extension is missing")]
+    ConstraintExtensionMissing,
+    /// 2044 - A forbidden token extension is present
+    #[msg("A forbidden token extension is present")]
+    ConstraintExtensionForbidden,
+    /// 2045 - A token group member extension constraint was violated
+    #[msg("A token group member extension constraint was violated")]
+    ConstraintMintTokenGroupMemberExtension,
+    /// 2046 - A token group member extension group constraint was violated
+    #[msg("A token group member extension group constraint was violated")]
+    ConstraintMintTokenGroupMemberExtensionGroup,
+    /// 2047 - A mint close authority constraint was violated
+    #[msg("A mint close authority constraint was violated")]
+    ConstraintMintCloseAuthority,
+    /// 2048 - A mint permanent delegate constraint was violated
+    #[msg("A mint permanent delegate constraint was violated")]
+    ConstraintMintPermanentDelegate,
+    /// 2049 - A with_seed constraint was violated
+    #[msg("A with_seed constraint was violated")]
+    ConstraintWithSeed,
+    /// 2050 - A vault constraint was violated
+    #[msg("A vault constraint was violated")]
+    ConstraintTokenVault,
 
     // Require
     /// 2500 - A require expression was violated
@@ -256,6 +289,9 @@ pub enum ErrorCode {
     /// 3017 - The account was duplicated for more than one reallocation
     #[msg("The account was duplicated for more than one reallocation")]
     AccountDuplicateReallocs,
+    /// 3018 - The given account is not a buffer account
+    #[msg("The given account is not a buffer account")]
+    AccountNotBuffer,
 
     // Miscellaneous
     /// 4100 - The declared program id does not match actual program id
@@ -267,6 +303,9 @@ pub enum ErrorCode {
     /// 4102 - Invalid numeric conversion error
     #[msg("Error during numeric conversion")]
     InvalidNumericConversion = 4102,
+    /// 4103 - Checked arithmetic operation overflowed
+    #[msg("Checked arithmetic operation overflowed")]
+    MathOverflow = 4103,
 
     // Deprecated
     /// 5000 - The API being used is deprecated and should no longer be used
@@ -43,16 +43,25 @@ pub mod error;
 pub mod event;
 #[doc(hidden)]
 pub mod idl;
+pub mod math;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_pubkey;
 pub mod system_program;
+pub mod sysvar;
 
 mod vec;
 pub use crate::bpf_upgradeable_state::*;
+pub use crate::common::{close_account, reassign_account, CLOSED_ACCOUNT_DISCRIMINATOR};
+pub use crate::context::SignerSeeds;
 pub use anchor_attribute_access_control::access_control;
 pub use anchor_attribute_account::{account, declare_id, pubkey, zero_copy};
 pub use anchor_attribute_constant::constant;
 pub use anchor_attribute_error::*;
 pub use anchor_attribute_event::{emit, event};
-pub use anchor_attribute_program::{declare_program, program};
+pub use anchor_attribute_program::{
+    after_instruction, before_instruction, declare_program, no_log_ix_name, program,
+};
 pub use anchor_derive_accounts::Accounts;
 pub use anchor_derive_serde::{AnchorDeserialize, AnchorSerialize};
 pub use anchor_derive_space::InitSpace;
@@ -60,6 +69,11 @@ pub use anchor_derive_space::InitSpace;
 /// Borsh is the default serialization format for instructions and accounts.
 pub use borsh::de::BorshDeserialize as AnchorDeserialize;
 pub use borsh::ser::BorshSerialize as AnchorSerialize;
+/// With the `borsh-schema` feature, every `AnchorSerialize` type (accounts,
+/// instruction arguments and events included) also implements `BorshSchema`,
+/// describing its layout for cross-language consumers and compatibility checks.
+#[cfg(feature = "borsh-schema")]
+pub use borsh::BorshSchema;
 pub use solana_program;
 
 #[cfg(feature = "event-cpi")]
@@ -269,9 +283,83 @@ pub trait AccountDeserialize: Sized {
     fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self>;
 }
 
+/// A custom serialization format for account data, used by accounts declared
+/// with `#[account(serde = <Codec>)]`.
+///
+/// The `#[account]` macro writes and checks the discriminator, so the codec
+/// only handles the bytes following it.
+///
+/// # Example
+///
+/// ```ignore
+/// pub struct Fixed;
+///
+/// impl AccountCodec<Config> for Fixed {
+///     fn encode<W: Write>(value: &Config, writer: &mut W) -> Result<()> {
+///         writer
+///             .write_all(&value.fee_bps.to_le_bytes())
+///             .map_err(|_| ErrorCode::AccountDidNotSerialize.into())
+///     }
+///
+///     fn decode(data: &[u8]) -> Result<Config> {
+///         let fee_bps = data
+///             .get(..2)
+///             .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+///         Ok(Config {
+///             fee_bps: u16::from_le_bytes(fee_bps.try_into().unwrap()),
+///         })
+///     }
+/// }
+///
+/// #[account(serde = Fixed)]
+/// pub struct Config {
+///     pub fee_bps: u16,
+/// }
+/// ```
+pub trait AccountCodec<T> {
+    /// Serializes the account data, excluding the discriminator.
+    fn encode<W: Write>(value: &T, writer: &mut W) -> Result<()>;
+
+    /// Deserializes the account data, excluding the discriminator.
+    fn decode(data: &[u8]) -> Result<T>;
+}
+
 /// An account data structure capable of zero copy deserialization.
 pub trait ZeroCopy: Discriminator + Copy + Clone + Zeroable + Pod {}
 
+/// A zero copy account header followed by a runtime-sized slice of `Item`s.
+///
+/// The number of items is stored in the header, so the account only has to be
+/// as large as the items it currently holds instead of reserving space for a
+/// fixed maximum. The slice can be accessed with
+/// [`AccountLoader::load_slice`](crate::accounts::account_loader::AccountLoader::load_slice)
+/// and grown with `realloc`.
+///
+/// # Example
+/// ```ignore
+/// #[account(zero_copy)]
+/// pub struct OrderBook {
+///     pub market: Pubkey,
+///     pub len: u64,
+/// }
+///
+/// #[zero_copy]
+/// pub struct Order {
+///     pub price: u64,
+///     pub amount: u64,
+/// }
+///
+/// impl ZeroCopySlice<Order> for OrderBook {
+///     fn slice_len(&self) -> usize {
+///         self.len as usize
+///     }
+/// }
+/// ```
+pub trait ZeroCopySlice<Item: Pod>: ZeroCopy {
+    /// Returns the number of items that follow the header.
+    fn slice_len(&self) -> usize;
+}
+
 /// Calculates the data for an instruction invocation, where the data is
 /// `Sha256(<namespace>:<method_name>)[..8] || BorshSerialize(args)`.
 /// `args` is a borsh serialized struct of named fields for each argument given
@@ -395,17 +483,21 @@ impl Key for Pubkey {
 pub mod prelude {
     pub use super::{
         access_control, account, accounts::account::Account,
-        accounts::account_loader::AccountLoader, accounts::interface::Interface,
-        accounts::interface_account::InterfaceAccount, accounts::program::Program,
-        accounts::signer::Signer, accounts::system_account::SystemAccount,
-        accounts::sysvar::Sysvar, accounts::unchecked_account::UncheckedAccount, constant,
-        context::Context, context::CpiContext, declare_id, declare_program, emit, err, error,
-        event, program, pubkey, require, require_eq, require_gt, require_gte, require_keys_eq,
-        require_keys_neq, require_neq,
+        accounts::account_loader::AccountLoader, accounts::account_with_tail::AccountWithTail,
+        accounts::interface::Interface, accounts::interface_account::InterfaceAccount,
+        accounts::program::Program, accounts::signer::Signer,
+        accounts::system_account::SystemAccount, accounts::sysvar::Sysvar,
+        accounts::unchecked_account::UncheckedAccount, after_instruction, before_instruction,
+        close_account, const_assert_layout, constant, context::Context, context::CpiContext,
+        context::SignerSeeds, declare_id, declare_interface, declare_program, emit, err, error,
+        event, math::Checked, no_log_ix_name, program, pubkey, reassign_account, require,
+        require_eq, require_gt, require_gte, require_keys_eq, require_keys_neq, require_neq,
         solana_program::bpf_loader_upgradeable::UpgradeableLoaderState, source,
-        system_program::System, zero_copy, AccountDeserialize, AccountSerialize, Accounts,
-        AccountsClose, AccountsExit, AnchorDeserialize, AnchorSerialize, Id, InitSpace, Key,
-        Lamports, Owner, ProgramData, Result, Space, ToAccountInfo, ToAccountInfos, ToAccountMetas,
+        system_program::System, sysvar::Introspection, sysvar::SlotHashesView,
+        sysvar::StakeHistoryView, zero_copy, AccountCodec, AccountDeserialize, AccountSerialize,
+        Accounts, AccountsClose, AccountsExit, AnchorDeserialize, AnchorSerialize, Buffer, Id,
+        InitSpace, Key, Lamports, Owner, ProgramData, Result, Space, ToAccountInfo, ToAccountInfos,
+        ToAccountMetas, ZeroCopySlice,
     };
     pub use anchor_attribute_error::*;
     pub use borsh;
@@ -416,8 +508,10 @@ pub mod prelude {
     pub use solana_program::program_error::ProgramError;
     pub use solana_program::pubkey::Pubkey;
     pub use solana_program::sysvar::clock::Clock;
+    pub use solana_program::sysvar::epoch_rewards::EpochRewards;
     pub use solana_program::sysvar::epoch_schedule::EpochSchedule;
     pub use solana_program::sysvar::instructions::Instructions;
+    pub use solana_program::sysvar::last_restart_slot::LastRestartSlot;
     pub use solana_program::sysvar::rent::Rent;
     pub use solana_program::sysvar::rewards::Rewards;
     pub use solana_program::sysvar::slot_hashes::SlotHashes;
@@ -447,8 +541,22 @@ pub mod __private {
 
     pub use bytemuck;
 
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+
+    #[cfg(feature = "serde")]
+    pub use crate::serde_pubkey;
+    #[cfg(feature = "serde")]
+    pub use serde;
+
     use solana_program::pubkey::Pubkey;
 
+    // Used by the generated signer seeds methods to own each seed.
+    #[doc(hidden)]
+    pub fn seed_to_vec(seed: &[u8]) -> Vec<u8> {
+        seed.to_vec()
+    }
+
     // Used to calculate the maximum between two expressions.
     // It is necessary for the calculation of the enum space.
     #[doc(hidden)]
@@ -727,6 +835,132 @@ macro_rules! err {
     };
 }
 
+/// Declares a program interface, i.e. a set of programs implementing the same
+/// instructions and account layouts, to be used with
+/// [`Interface`](crate::accounts::interface::Interface) and
+/// [`InterfaceAccount`](crate::accounts::interface_account::InterfaceAccount).
+///
+/// The interface type implements [`Ids`](crate::Ids) with the given program
+/// ids, and every listed account type implements [`Owners`](crate::Owners)
+/// with the same ids so it can only be deserialized from accounts owned by one
+/// of the programs.
+///
+/// In the IDL, `Interface` fields of the declared type list the program ids
+/// under `addresses`.
+///
+/// # Example
+/// ```ignore
+/// declare_interface! {
+///     /// Oracle programs sharing the price feed layout.
+///     pub struct Oracle = [pyth::ID, switchboard::ID];
+///     accounts = [PriceFeed];
+/// }
+///
+/// #[account]
+/// pub struct PriceFeed {
+///     pub price: i64,
+///     pub expo: i32,
+/// }
+///
+/// #[derive(Accounts)]
+/// pub struct ReadPrice<'info> {
+///     pub oracle: Interface<'info, Oracle>,
+///     #[account(owner = oracle.key())]
+///     pub price_feed: InterfaceAccount<'info, PriceFeed>,
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_interface {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident = [$($id:expr),+ $(,)?];
+        $(accounts = [$($account:ty),* $(,)?];)?
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone)]
+        $vis struct $name;
+
+        impl $name {
+            /// Ids of the programs implementing the interface.
+            pub const IDS: &'static [$crate::solana_program::pubkey::Pubkey] = &[$($id),+];
+        }
+
+        impl $crate::Ids for $name {
+            fn ids() -> &'static [$crate::solana_program::pubkey::Pubkey] {
+                Self::IDS
+            }
+        }
+
+        $($(
+            impl $crate::Owners for $account {
+                fn owners() -> &'static [$crate::solana_program::pubkey::Pubkey] {
+                    $name::IDS
+                }
+            }
+        )*)?
+    };
+}
+
+/// Asserts the memory layout of a type at compile time.
+///
+/// Zero copy accounts are read directly from the account data, so reordering
+/// their fields or changing their padding silently corrupts existing accounts.
+/// This macro checks the size of the type and optionally the offsets of its
+/// fields, making such changes break the build instead.
+///
+/// # Example
+/// ```ignore
+/// #[account(zero_copy)]
+/// pub struct Pool {
+///     pub authority: Pubkey,
+///     pub total_deposits: u64,
+///     pub bump: u8,
+///     pub _padding: [u8; 7],
+/// }
+///
+/// const_assert_layout!(Pool, size = 48, authority = 0, total_deposits = 32, bump = 40);
+/// ```
+#[macro_export]
+macro_rules! const_assert_layout {
+    ($ty:ty, size = $size:expr $(, $field:ident = $offset:expr)* $(,)?) => {
+        const _: () = {
+            assert!(
+                ::core::mem::size_of::<$ty>() == $size,
+                concat!("unexpected size of `", stringify!($ty), "`"),
+            );
+            $(
+                assert!(
+                    $crate::__field_offset!($ty, $field) == $offset,
+                    concat!(
+                        "unexpected offset of `",
+                        stringify!($ty),
+                        "::",
+                        stringify!($field),
+                        "`",
+                    ),
+                );
+            )*
+        };
+    };
+}
+
+/// Returns the offset of a field in bytes, usable in constant expressions.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __field_offset {
+    ($ty:ty, $field:ident) => {{
+        let uninit = ::core::mem::MaybeUninit::<$ty>::uninit();
+        let base = uninit.as_ptr();
+        // SAFETY: The field pointer is only computed, the uninitialized memory
+        // is never read.
+        #[allow(unused_unsafe)]
+        unsafe {
+            (::core::ptr::addr_of!((*base).$field) as *const u8).offset_from(base as *const u8)
+                as usize
+        }
+    }};
+}
+
 /// Creates a [`Source`](crate::error::Source)
 #[macro_export]
 macro_rules! source {
@@ -0,0 +1,115 @@
+//! Overflow-safe integer arithmetic.
+
+use crate::error::ErrorCode;
+use crate::Result;
+use std::ops::{Add, Deref, Div, Mul, Rem, Sub};
+
+/// Integer wrapper whose arithmetic operators are checked.
+///
+/// Every operation returns a [`Result`] that fails with
+/// [`ErrorCode::MathOverflow`] on overflow, underflow or division by zero, so
+/// financial logic can use plain operators instead of mixing them with ad hoc
+/// `checked_*().ok_or(...)?` chains. Operations can be chained before
+/// applying `?` once.
+///
+/// # Example
+/// ```ignore
+/// pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+///     let pool = &mut ctx.accounts.pool;
+///     let shares = Checked(amount).mul_div(pool.total_shares, pool.total_deposits)?;
+///     pool.total_deposits = (Checked(pool.total_deposits) + amount)?.get();
+///     pool.total_shares = (Checked(pool.total_shares) + shares)?.get();
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checked<T>(pub T);
+
+impl<T> Checked<T> {
+    /// Returns the inner value.
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Checked<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Checked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+macro_rules! impl_checked_op {
+    ($ty:ty, $trait:ident, $method:ident, $checked_method:ident) => {
+        impl $trait for Checked<$ty> {
+            type Output = Result<Checked<$ty>>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.0
+                    .$checked_method(rhs.0)
+                    .map(Checked)
+                    .ok_or_else(|| ErrorCode::MathOverflow.into())
+            }
+        }
+
+        impl $trait<$ty> for Checked<$ty> {
+            type Output = Result<Checked<$ty>>;
+
+            fn $method(self, rhs: $ty) -> Self::Output {
+                self.$method(Checked(rhs))
+            }
+        }
+
+        impl $trait<Checked<$ty>> for Result<Checked<$ty>> {
+            type Output = Result<Checked<$ty>>;
+
+            fn $method(self, rhs: Checked<$ty>) -> Self::Output {
+                self?.$method(rhs)
+            }
+        }
+    };
+}
+
+macro_rules! impl_checked {
+    ($($ty:ty),*) => {
+        $(
+            impl_checked_op!($ty, Add, add, checked_add);
+            impl_checked_op!($ty, Sub, sub, checked_sub);
+            impl_checked_op!($ty, Mul, mul, checked_mul);
+            impl_checked_op!($ty, Div, div, checked_div);
+            impl_checked_op!($ty, Rem, rem, checked_rem);
+
+            impl Checked<$ty> {
+                /// Raises the value to the power of `exp`.
+                pub fn pow(self, exp: u32) -> Result<Self> {
+                    self.0
+                        .checked_pow(exp)
+                        .map(Checked)
+                        .ok_or_else(|| ErrorCode::MathOverflow.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Checked<u64> {
+    /// Computes `self * numerator / denominator` using 128-bit intermediate
+    /// precision, which avoids overflowing on the multiplication for the
+    /// common "proportional share" calculation.
+    pub fn mul_div(self, numerator: u64, denominator: u64) -> Result<Self> {
+        let result =
+            ((Checked(self.0 as u128) * numerator as u128) / Checked(denominator as u128))?;
+        u64::try_from(result.0)
+            .map(Checked)
+            .map_err(|_| ErrorCode::MathOverflow.into())
+    }
+}
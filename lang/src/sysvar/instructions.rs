@@ -0,0 +1,207 @@
+use crate::error::ErrorCode;
+use crate::Result;
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::instructions;
+use std::ops::Range;
+
+/// Typed helper over the `Instructions` sysvar for inspecting the other
+/// instructions of the currently executing transaction.
+///
+/// This is the building block of the common "is instruction X from program Y
+/// present before me" checks, e.g. flash loan guards or signature
+/// verifications done by the `ed25519` native program.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Accounts)]
+/// pub struct Verify<'info> {
+///     /// CHECK: Validated by `Introspection::from_account_info`.
+///     pub instructions: UncheckedAccount<'info>,
+/// }
+///
+/// pub fn verify(ctx: Context<Verify>) -> Result<()> {
+///     let ixs = Introspection::from_account_info(&ctx.accounts.instructions)?;
+///     require!(
+///         ixs.is_present_before(&ed25519_program::ID)?,
+///         MyError::MissingSignatureVerification
+///     );
+///     Ok(())
+/// }
+/// ```
+pub struct Introspection<'a, 'info> {
+    info: &'a AccountInfo<'info>,
+    current_index: usize,
+    len: usize,
+}
+
+impl<'a, 'info> Introspection<'a, 'info> {
+    /// Creates the helper from the given account, checking that it's the
+    /// `Instructions` sysvar.
+    pub fn from_account_info(info: &'a AccountInfo<'info>) -> Result<Self> {
+        if !instructions::check_id(info.key) {
+            return Err(ErrorCode::AccountSysvarMismatch.into());
+        }
+        let current_index = instructions::load_current_index_checked(info)? as usize;
+        let len = {
+            let data = info.try_borrow_data()?;
+            if data.len() < 2 {
+                return Err(ErrorCode::AccountDidNotDeserialize.into());
+            }
+            u16::from_le_bytes([data[0], data[1]]) as usize
+        };
+        Ok(Self {
+            info,
+            current_index,
+            len,
+        })
+    }
+
+    /// Index of the currently executing instruction in the transaction.
+    pub fn current_index(&self) -> usize {
+        self.current_index
+    }
+
+    /// Number of top-level instructions in the transaction.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the transaction doesn't have any instructions.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the top-level instruction at `index`.
+    pub fn get(&self, index: usize) -> Result<Instruction> {
+        instructions::load_instruction_at_checked(index, self.info).map_err(Into::into)
+    }
+
+    /// Returns the currently executing top-level instruction.
+    pub fn current(&self) -> Result<Instruction> {
+        self.get(self.current_index)
+    }
+
+    /// Returns the instruction `offset` positions away from the current one,
+    /// e.g. `-1` for the previous instruction.
+    pub fn get_relative(&self, offset: i64) -> Result<Instruction> {
+        instructions::get_instruction_relative(offset, self.info).map_err(Into::into)
+    }
+
+    /// Iterates over all top-level instructions, including the current one.
+    pub fn iter(&self) -> InstructionsIter<'a, 'info> {
+        self.iter_range(0..self.len)
+    }
+
+    /// Iterates over the instructions that come before the current one.
+    pub fn before(&self) -> InstructionsIter<'a, 'info> {
+        self.iter_range(0..self.current_index)
+    }
+
+    /// Iterates over the instructions that come after the current one.
+    pub fn after(&self) -> InstructionsIter<'a, 'info> {
+        self.iter_range(self.current_index + 1..self.len)
+    }
+
+    fn iter_range(&self, indices: Range<usize>) -> InstructionsIter<'a, 'info> {
+        InstructionsIter {
+            info: self.info,
+            indices,
+        }
+    }
+
+    /// Returns the first instruction before the current one that matches
+    /// `predicate`.
+    pub fn find_before<P>(&self, mut predicate: P) -> Result<Option<(usize, Instruction)>>
+    where
+        P: FnMut(&Instruction) -> bool,
+    {
+        for ix in self.before() {
+            let (index, ix) = ix?;
+            if predicate(&ix) {
+                return Ok(Some((index, ix)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the first instruction after the current one that matches
+    /// `predicate`.
+    pub fn find_after<P>(&self, mut predicate: P) -> Result<Option<(usize, Instruction)>>
+    where
+        P: FnMut(&Instruction) -> bool,
+    {
+        for ix in self.after() {
+            let (index, ix) = ix?;
+            if predicate(&ix) {
+                return Ok(Some((index, ix)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether an instruction of `program_id` is present before the current
+    /// one.
+    pub fn is_present_before(&self, program_id: &Pubkey) -> Result<bool> {
+        self.find_before(|ix| ix.program_id == *program_id)
+            .map(|ix| ix.is_some())
+    }
+
+    /// Whether an instruction of `program_id` is present after the current
+    /// one.
+    pub fn is_present_after(&self, program_id: &Pubkey) -> Result<bool> {
+        self.find_after(|ix| ix.program_id == *program_id)
+            .map(|ix| ix.is_some())
+    }
+
+    /// Whether an instruction of `program_id` whose data starts with
+    /// `discriminator` is present before the current one.
+    pub fn is_instruction_present_before(
+        &self,
+        program_id: &Pubkey,
+        discriminator: &[u8],
+    ) -> Result<bool> {
+        self.find_before(|ix| ix.program_id == *program_id && ix.data.starts_with(discriminator))
+            .map(|ix| ix.is_some())
+    }
+
+    /// Whether the current instruction is the only instruction of
+    /// `program_id` in the transaction, i.e. the program isn't invoked more
+    /// than once at the top level.
+    pub fn is_only_invocation_of(&self, program_id: &Pubkey) -> Result<bool> {
+        for ix in self.iter() {
+            let (index, ix) = ix?;
+            if index != self.current_index && ix.program_id == *program_id {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Iterator over top-level instructions and their index, returned by
+/// [`Introspection::iter`], [`Introspection::before`] and
+/// [`Introspection::after`].
+pub struct InstructionsIter<'a, 'info> {
+    info: &'a AccountInfo<'info>,
+    indices: Range<usize>,
+}
+
+impl<'a, 'info> Iterator for InstructionsIter<'a, 'info> {
+    type Item = Result<(usize, Instruction)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.indices.next()?;
+        Some(
+            instructions::load_instruction_at_checked(index, self.info)
+                .map(|ix| (index, ix))
+                .map_err(Into::into),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
@@ -0,0 +1,119 @@
+//! Syscall accessors of sysvars, lightweight views over sysvars that are too
+//! large to deserialize cheaply, and typed helpers for instruction
+//! introspection.
+//!
+//! Sysvars such as [`Clock`] or [`EpochRewards`] are read with
+//! their `sol_get_*_sysvar` syscall by the accessors of this module, e.g.
+//! [`clock`], so that instructions don't need to take them as accounts.
+//!
+//! Sysvars such as [`SlotHashes`](solana_program::sysvar::slot_hashes::SlotHashes)
+//! and [`StakeHistory`](solana_program::sysvar::stake_history::StakeHistory)
+//! can't be fetched with the `get` syscall, and deserializing the whole
+//! account through [`Sysvar`](crate::accounts::sysvar::Sysvar) allocates every
+//! entry. The views in this module instead read entries directly from the
+//! account data on demand, which keeps the compute cost proportional to the
+//! number of entries actually accessed.
+//!
+//! # Example
+//!
+//! ```ignore
+//! #[derive(Accounts)]
+//! pub struct CheckSlot<'info> {
+//!     /// CHECK: Validated by `SlotHashesView::from_account_info`.
+//!     pub slot_hashes: UncheckedAccount<'info>,
+//! }
+//!
+//! pub fn check_slot(ctx: Context<CheckSlot>, slot: u64) -> Result<()> {
+//!     let slot_hashes = SlotHashesView::from_account_info(&ctx.accounts.slot_hashes)?;
+//!     let hash = slot_hashes.get_hash(slot).ok_or(MyError::SlotTooOld)?;
+//!     // ...
+//!     Ok(())
+//! }
+//! ```
+
+mod instructions;
+mod slot_hashes;
+mod stake_history;
+
+pub use instructions::{InstructionsIter, Introspection};
+pub use slot_hashes::SlotHashesView;
+pub use stake_history::StakeHistoryView;
+
+use crate::error::ErrorCode;
+use crate::Result;
+use solana_program::sysvar::clock::Clock;
+use solana_program::sysvar::epoch_rewards::EpochRewards;
+use solana_program::sysvar::epoch_schedule::EpochSchedule;
+use solana_program::sysvar::last_restart_slot::LastRestartSlot;
+use solana_program::sysvar::rent::Rent;
+use solana_program::sysvar::Sysvar;
+
+/// Returns the `Clock` sysvar, read with its syscall.
+pub fn clock() -> Result<Clock> {
+    Ok(Clock::get()?)
+}
+
+/// Returns the `Rent` sysvar, read with its syscall.
+pub fn rent() -> Result<Rent> {
+    Ok(Rent::get()?)
+}
+
+/// Returns the `EpochSchedule` sysvar, read with its syscall.
+pub fn epoch_schedule() -> Result<EpochSchedule> {
+    Ok(EpochSchedule::get()?)
+}
+
+/// Returns the `EpochRewards` sysvar, read with its syscall.
+pub fn epoch_rewards() -> Result<EpochRewards> {
+    Ok(EpochRewards::get()?)
+}
+
+/// Returns the `LastRestartSlot` sysvar, read with its syscall.
+pub fn last_restart_slot() -> Result<LastRestartSlot> {
+    Ok(LastRestartSlot::get()?)
+}
+
+/// Size of the `u64` length prefix of a bincode serialized `Vec`.
+const LEN_PREFIX_SIZE: usize = 8;
+
+/// Reads a little-endian `u64` at `offset`. The caller must ensure the slice
+/// is long enough.
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
+
+/// Validates the length prefix of a bincode serialized `Vec` whose elements
+/// are `entry_size` bytes each and returns the number of entries.
+fn read_entry_count(data: &[u8], entry_size: usize) -> Result<usize> {
+    if data.len() < LEN_PREFIX_SIZE {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    let len = usize::try_from(read_u64(data, 0))?;
+    let required = len
+        .checked_mul(entry_size)
+        .and_then(|size| size.checked_add(LEN_PREFIX_SIZE))
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    if data.len() < required {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    Ok(len)
+}
+
+/// Binary searches entries sorted in descending key order, where the key of
+/// each entry is the `u64` at its start. Returns the index of the entry whose
+/// key equals `key`.
+fn search_descending(data: &[u8], len: usize, entry_size: usize, key: u64) -> Option<usize> {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let mid_key = read_u64(data, LEN_PREFIX_SIZE + mid * entry_size);
+        match mid_key.cmp(&key) {
+            std::cmp::Ordering::Equal => return Some(mid),
+            std::cmp::Ordering::Greater => low = mid + 1,
+            std::cmp::Ordering::Less => high = mid,
+        }
+    }
+    None
+}
@@ -0,0 +1,71 @@
+use super::{read_entry_count, read_u64, search_descending, LEN_PREFIX_SIZE};
+use crate::error::ErrorCode;
+use crate::Result;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Slot;
+use solana_program::hash::{Hash, HASH_BYTES};
+use solana_program::sysvar::slot_hashes;
+use std::cell::Ref;
+
+/// Size of a single `(Slot, Hash)` entry.
+const ENTRY_SIZE: usize = 8 + HASH_BYTES;
+
+/// Read-only view over the `SlotHashes` sysvar account that doesn't
+/// deserialize the entire list of slot hashes.
+///
+/// Entries are ordered from the most recent slot to the oldest one.
+pub struct SlotHashesView<'a> {
+    data: Ref<'a, &'a mut [u8]>,
+    len: usize,
+}
+
+impl<'a> SlotHashesView<'a> {
+    /// Creates a view over the given account, checking that it's the
+    /// `SlotHashes` sysvar.
+    pub fn from_account_info(info: &'a AccountInfo) -> Result<Self> {
+        if *info.key != slot_hashes::ID {
+            return Err(ErrorCode::AccountSysvarMismatch.into());
+        }
+        let data = info.try_borrow_data()?;
+        let len = read_entry_count(&data, ENTRY_SIZE)?;
+        Ok(Self { data, len })
+    }
+
+    /// Number of slot hashes stored in the sysvar.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the sysvar doesn't hold any slot hashes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the entry at `index`, where `0` is the most recent slot.
+    pub fn get(&self, index: usize) -> Option<(Slot, Hash)> {
+        if index >= self.len {
+            return None;
+        }
+        let offset = LEN_PREFIX_SIZE + index * ENTRY_SIZE;
+        let slot = read_u64(&self.data, offset);
+        let hash = Hash::new(&self.data[offset + 8..offset + ENTRY_SIZE]);
+        Some((slot, hash))
+    }
+
+    /// Returns the most recent entry.
+    pub fn most_recent(&self) -> Option<(Slot, Hash)> {
+        self.get(0)
+    }
+
+    /// Returns the index of the entry for `slot`, if it's still stored.
+    pub fn position(&self, slot: Slot) -> Option<usize> {
+        search_descending(&self.data, self.len, ENTRY_SIZE, slot)
+    }
+
+    /// Returns the hash of `slot`, if it's still stored.
+    pub fn get_hash(&self, slot: Slot) -> Option<Hash> {
+        self.position(slot)
+            .and_then(|index| self.get(index))
+            .map(|(_, hash)| hash)
+    }
+}
@@ -0,0 +1,65 @@
+use super::{read_entry_count, read_u64, search_descending, LEN_PREFIX_SIZE};
+use crate::error::ErrorCode;
+use crate::Result;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Epoch;
+use solana_program::stake_history::StakeHistoryEntry;
+use solana_program::sysvar::stake_history;
+use std::cell::Ref;
+
+/// Size of a single `(Epoch, StakeHistoryEntry)` entry.
+const ENTRY_SIZE: usize = 8 + 3 * 8;
+
+/// Read-only view over the `StakeHistory` sysvar account that doesn't
+/// deserialize the entire stake history.
+///
+/// Entries are ordered from the most recent epoch to the oldest one.
+pub struct StakeHistoryView<'a> {
+    data: Ref<'a, &'a mut [u8]>,
+    len: usize,
+}
+
+impl<'a> StakeHistoryView<'a> {
+    /// Creates a view over the given account, checking that it's the
+    /// `StakeHistory` sysvar.
+    pub fn from_account_info(info: &'a AccountInfo) -> Result<Self> {
+        if *info.key != stake_history::ID {
+            return Err(ErrorCode::AccountSysvarMismatch.into());
+        }
+        let data = info.try_borrow_data()?;
+        let len = read_entry_count(&data, ENTRY_SIZE)?;
+        Ok(Self { data, len })
+    }
+
+    /// Number of epochs stored in the sysvar.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the sysvar doesn't hold any epochs.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the entry at `index`, where `0` is the most recent epoch.
+    pub fn get(&self, index: usize) -> Option<(Epoch, StakeHistoryEntry)> {
+        if index >= self.len {
+            return None;
+        }
+        let offset = LEN_PREFIX_SIZE + index * ENTRY_SIZE;
+        let epoch = read_u64(&self.data, offset);
+        let entry = StakeHistoryEntry {
+            effective: read_u64(&self.data, offset + 8),
+            activating: read_u64(&self.data, offset + 16),
+            deactivating: read_u64(&self.data, offset + 24),
+        };
+        Some((epoch, entry))
+    }
+
+    /// Returns the stake history entry of `epoch`, if it's still stored.
+    pub fn get_entry(&self, epoch: Epoch) -> Option<StakeHistoryEntry> {
+        search_descending(&self.data, self.len, ENTRY_SIZE, epoch)
+            .and_then(|index| self.get(index))
+            .map(|(_, entry)| entry)
+    }
+}
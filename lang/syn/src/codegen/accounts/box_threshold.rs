@@ -0,0 +1,45 @@
+use crate::{AccountField, AccountsStruct, Ty};
+use quote::quote;
+
+/// Generates compile time assertions that the unboxed `Account` and
+/// `InterfaceAccount` fields of the accounts struct don't exceed the stack
+/// size threshold given with `#[accounts(box_threshold = <bytes>)]`.
+///
+/// Large account types overflow the stack of the `try_accounts` function,
+/// which otherwise only fails at runtime. Derive macros can't change the
+/// types of the fields, so oversized fields are reported instead of boxed.
+pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let threshold = match &accs.box_threshold {
+        Some(threshold) => threshold,
+        None => return quote! {},
+    };
+    // The sizes of generic types can't be checked outside of the struct.
+    if accs.generics.type_params().next().is_some() {
+        return quote! {};
+    }
+
+    let assertions = accs.fields.iter().filter_map(|af| {
+        let f = match af {
+            AccountField::Field(f) => f,
+            AccountField::CompositeField(_) => return None,
+        };
+        let account_ty = match &f.ty {
+            Ty::Account(ty) if !ty.boxed => &ty.account_type_path,
+            Ty::InterfaceAccount(ty) if !ty.boxed => &ty.account_type_path,
+            _ => return None,
+        };
+        let msg = format!(
+            "`{}::{}` exceeds the box threshold of {} bytes, wrap it in a `Box`",
+            accs.ident, f.ident, threshold
+        );
+        Some(quote! {
+            assert!(::std::mem::size_of::<#account_ty>() <= #threshold, #msg);
+        })
+    });
+
+    quote! {
+        const _: () = {
+            #(#assertions)*
+        };
+    }
+}
@@ -81,7 +81,16 @@ pub fn linearize(c_group: &ConstraintGroup) -> Vec<Constraint> {
         seeds,
         executable,
         close,
+        // Applied on exit.
+        reassign: _,
+        not_before,
+        not_after,
+        not_before_slot,
+        not_after_slot,
+        authority_any_of,
+        extensions,
         address,
+        with_seed,
         associated_token,
         token_account,
         mint,
@@ -125,15 +134,39 @@ pub fn linearize(c_group: &ConstraintGroup) -> Vec<Constraint> {
     if let Some(c) = close {
         constraints.push(Constraint::Close(c));
     }
+    if let Some(c) = not_before {
+        constraints.push(Constraint::NotBefore(c));
+    }
+    if let Some(c) = not_after {
+        constraints.push(Constraint::NotAfter(c));
+    }
+    if let Some(c) = not_before_slot {
+        constraints.push(Constraint::NotBefore(c));
+    }
+    if let Some(c) = not_after_slot {
+        constraints.push(Constraint::NotAfter(c));
+    }
+    if let Some(c) = authority_any_of {
+        constraints.push(Constraint::AuthorityAnyOf(c));
+    }
     if let Some(c) = address {
         constraints.push(Constraint::Address(c));
     }
+    if let Some(c) = with_seed {
+        constraints.push(Constraint::WithSeed(c));
+    }
     if let Some(c) = token_account {
         constraints.push(Constraint::TokenAccount(c));
     }
     if let Some(c) = mint {
         constraints.push(Constraint::Mint(c));
     }
+    constraints.append(
+        &mut extensions
+            .into_iter()
+            .map(Constraint::ExtensionPresence)
+            .collect(),
+    );
     constraints
 }
 
@@ -150,15 +183,20 @@ fn generate_constraint(
         Constraint::Signer(c) => generate_constraint_signer(f, c),
         Constraint::Raw(c) => generate_constraint_raw(&f.ident, c),
         Constraint::Owner(c) => generate_constraint_owner(f, c),
-        Constraint::RentExempt(c) => generate_constraint_rent_exempt(f, c),
+        Constraint::RentExempt(c) => generate_constraint_rent_exempt(f, c, accs),
         Constraint::Seeds(c) => generate_constraint_seeds(f, c),
         Constraint::Executable(c) => generate_constraint_executable(f, c),
         Constraint::Close(c) => generate_constraint_close(f, c, accs),
+        Constraint::NotBefore(c) => generate_constraint_not_before(f, c),
+        Constraint::NotAfter(c) => generate_constraint_not_after(f, c),
+        Constraint::AuthorityAnyOf(c) => generate_constraint_authority_any_of(f, c, accs),
         Constraint::Address(c) => generate_constraint_address(f, c),
+        Constraint::WithSeed(c) => generate_constraint_with_seed(f, c),
         Constraint::AssociatedToken(c) => generate_constraint_associated_token(f, c, accs),
         Constraint::TokenAccount(c) => generate_constraint_token_account(f, c, accs),
         Constraint::Mint(c) => generate_constraint_mint(f, c, accs),
         Constraint::Realloc(c) => generate_constraint_realloc(f, c, accs),
+        Constraint::ExtensionPresence(c) => generate_constraint_extension_presence(f, c),
     }
 }
 
@@ -189,6 +227,30 @@ fn generate_constraint_address(f: &Field, c: &ConstraintAddress) -> proc_macro2:
     }
 }
 
+fn generate_constraint_with_seed(
+    f: &Field,
+    c: &ConstraintWithSeedGroup,
+) -> proc_macro2::TokenStream {
+    let field = &f.ident;
+    let name_str = field.to_string();
+    let base = &c.base;
+    let seed = &c.seed;
+    let owner = match &c.owner {
+        Some(o) => quote! { &#o },
+        None => quote! { __program_id },
+    };
+    quote! {
+        {
+            let __seed: &str = ::core::convert::AsRef::<str>::as_ref(&#seed);
+            let __with_seed_address = Pubkey::create_with_seed(&#base.key(), __seed, #owner)
+                .map_err(|_| anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintWithSeed).with_account_name(#name_str))?;
+            if #field.key() != __with_seed_address {
+                return Err(anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintWithSeed).with_account_name(#name_str).with_pubkeys((#field.key(), __with_seed_address)));
+            }
+        }
+    }
+}
+
 pub fn generate_constraint_init(
     f: &Field,
     c: &ConstraintInitGroup,
@@ -223,14 +285,111 @@ pub fn generate_constraint_close(
 ) -> proc_macro2::TokenStream {
     let field = &f.ident;
     let name_str = field.to_string();
-    let target = &c.sol_dest;
-    let target_optional_check =
-        OptionalCheckScope::new_with_field(accs, field).generate_check(target);
+    match c.sol_dest_field() {
+        Some(target) => {
+            let target_optional_check =
+                OptionalCheckScope::new_with_field(accs, field).generate_check(target);
+            quote! {
+                {
+                    #target_optional_check
+                    if #field.key() == #target.key() {
+                        return Err(anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintClose).with_account_name(#name_str));
+                    }
+                }
+            }
+        }
+        // Receivers chosen at runtime are checked on exit, where they're
+        // evaluated.
+        None => quote! {},
+    }
+}
+
+pub fn generate_constraint_not_before(
+    f: &Field,
+    c: &ConstraintClockBound,
+) -> proc_macro2::TokenStream {
+    let error = generate_custom_error(&f.ident, &c.error, quote! { ConstraintNotBefore }, &None);
+    generate_constraint_clock_bound(c, quote! { < }, error)
+}
+
+pub fn generate_constraint_not_after(
+    f: &Field,
+    c: &ConstraintClockBound,
+) -> proc_macro2::TokenStream {
+    let error = generate_custom_error(&f.ident, &c.error, quote! { ConstraintNotAfter }, &None);
+    generate_constraint_clock_bound(c, quote! { > }, error)
+}
+
+// Errors if the current unix timestamp or slot compares to the bound with `op`.
+fn generate_constraint_clock_bound(
+    c: &ConstraintClockBound,
+    op: proc_macro2::TokenStream,
+    error: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let bound = &c.bound;
+    let now = if c.slot {
+        quote! { __clock.slot }
+    } else {
+        quote! { __clock.unix_timestamp }
+    };
     quote! {
         {
-            #target_optional_check
-            if #field.key() == #target.key() {
-                return Err(anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintClose).with_account_name(#name_str));
+            let __clock = <anchor_lang::solana_program::clock::Clock as anchor_lang::solana_program::sysvar::Sysvar>::get()?;
+            if (#now as i128) #op ((#bound) as i128) {
+                return #error;
+            }
+        }
+    }
+}
+
+fn generate_constraint_authority_any_of(
+    f: &Field,
+    c: &ConstraintAuthorityAnyOf,
+    accs: &AccountsStruct,
+) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    let account_ref = generate_account_ref(f);
+    let error = generate_custom_error(ident, &c.error, quote! { ConstraintAuthorityAnyOf }, &None);
+    let keys = c.keys.iter().map(|key| match key {
+        Expr::Path(path)
+            if accs
+                .fields
+                .iter()
+                .any(|af| matches!(af, AccountField::Field(f) if path.path.is_ident(&f.ident))) =>
+        {
+            quote! { anchor_lang::Key::key(&#key) }
+        }
+        _ => quote! { #key },
+    });
+    quote! {
+        {
+            let __authority = #account_ref;
+            let __authorities: &[anchor_lang::solana_program::pubkey::Pubkey] = &[#(#keys),*];
+            if !__authority.is_signer || !__authorities.contains(__authority.key) {
+                return #error;
+            }
+        }
+    }
+}
+
+fn generate_constraint_extension_presence(
+    f: &Field,
+    c: &ConstraintExtensionPresence,
+) -> proc_macro2::TokenStream {
+    let ident = &f.ident;
+    let account_ref = generate_account_ref(f);
+    let extension = &c.extension;
+    let (check, error) = if c.present {
+        (quote! { ! }, quote! { ConstraintExtensionMissing })
+    } else {
+        (quote! {}, quote! { ConstraintExtensionForbidden })
+    };
+    let error = generate_custom_error(ident, &None, error, &None);
+    quote! {
+        {
+            let __extension_types = ::anchor_spl::token_interface::get_extension_types(#account_ref)?;
+            if #check __extension_types.contains(&::anchor_spl::token_interface::spl_token_2022::extension::ExtensionType::#extension) {
+                return #error;
             }
         }
     }
@@ -264,14 +423,49 @@ pub fn generate_constraint_has_one(
         quote! { ConstraintHasOne },
         &Some(&(quote! { my_key }, quote! { target_key })),
     );
-    let target_optional_check =
-        OptionalCheckScope::new_with_field(accs, &field).generate_check(target);
+    let mut optional_check_scope = OptionalCheckScope::new_with_field(accs, &field);
+
+    // `has_one = other.field` compares against a field of another account in
+    // the struct instead of its key.
+    let (target_optional_check, my_key, target_key) = match target {
+        Expr::Field(nested) => {
+            let member = &nested.member;
+            let base = &nested.base;
+            match base.as_ref() {
+                Expr::Path(path) if path.path.get_ident().is_some() => {
+                    let base_ident = path.path.get_ident().unwrap();
+                    let is_loader = accs.fields.iter().any(|af| match af {
+                        AccountField::Field(f) => {
+                            f.ident == *base_ident && matches!(f.ty, Ty::AccountLoader(_))
+                        }
+                        AccountField::CompositeField(_) => false,
+                    });
+                    let target_key = if is_loader {
+                        quote! { #base_ident.load()?.#member }
+                    } else {
+                        quote! { #base_ident.#member }
+                    };
+                    (
+                        optional_check_scope.generate_check(base_ident),
+                        quote! { #field.#member },
+                        target_key,
+                    )
+                }
+                _ => (quote! {}, quote! { #field.#member }, quote! { #target }),
+            }
+        }
+        _ => (
+            optional_check_scope.generate_check(target),
+            quote! { #field.#target },
+            quote! { #target.key() },
+        ),
+    };
 
     quote! {
         {
             #target_optional_check
-            let my_key = #field.#target;
-            let target_key = #target.key();
+            let my_key = #my_key;
+            let target_key = #target_key;
             if my_key != target_key {
                 return #error;
             }
@@ -324,6 +518,7 @@ pub fn generate_constraint_owner(f: &Field, c: &ConstraintOwner) -> proc_macro2:
 pub fn generate_constraint_rent_exempt(
     f: &Field,
     c: &ConstraintRentExempt,
+    accs: &AccountsStruct,
 ) -> proc_macro2::TokenStream {
     let ident = &f.ident;
     let name_str = ident.to_string();
@@ -337,6 +532,34 @@ pub fn generate_constraint_rent_exempt(
                 return Err(anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintRentExempt).with_account_name(#name_str));
             }
         },
+        ConstraintRentExempt::TopUp { payer } => {
+            let mut optional_check_scope = OptionalCheckScope::new_with_field(accs, ident);
+            let payer_optional_check = optional_check_scope.generate_check(payer);
+            let system_program_optional_check =
+                optional_check_scope.generate_check(quote! {system_program});
+
+            quote! {
+                {
+                    let __field_info = #info;
+                    let __rent_minimum = anchor_lang::prelude::Rent::get()?
+                        .minimum_balance(__field_info.try_data_len()?);
+                    if __rent_minimum > __field_info.lamports() {
+                        #payer_optional_check
+                        #system_program_optional_check
+                        anchor_lang::system_program::transfer(
+                            anchor_lang::context::CpiContext::new(
+                                system_program.to_account_info(),
+                                anchor_lang::system_program::Transfer {
+                                    from: #payer.to_account_info(),
+                                    to: __field_info.clone(),
+                                },
+                            ),
+                            __rent_minimum.checked_sub(__field_info.lamports()).unwrap(),
+                        )?;
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -612,6 +835,15 @@ fn generate_constraint_init_group(
 
             let payer_optional_check = check_scope.generate_check(payer);
 
+            // `CreateIdempotent` is a no-op if the account already exists, so
+            // it can always be invoked.
+            let idempotent = c.idempotent;
+            let create = if c.idempotent {
+                quote! { ::anchor_spl::associated_token::create_idempotent }
+            } else {
+                quote! { ::anchor_spl::associated_token::create }
+            };
+
             quote! {
                 // Define the bump and pda variable.
                 #find_pda
@@ -621,7 +853,7 @@ fn generate_constraint_init_group(
                     #optional_checks
 
                     let owner_program = #account_ref.owner;
-                    if !#if_needed || owner_program == &anchor_lang::solana_program::system_program::ID {
+                    if #idempotent || !#if_needed || owner_program == &anchor_lang::solana_program::system_program::ID {
                         #payer_optional_check
 
                         let cpi_program = associated_token_program.to_account_info();
@@ -634,7 +866,7 @@ fn generate_constraint_init_group(
                             token_program: #token_program.to_account_info(),
                         };
                         let cpi_ctx = anchor_lang::context::CpiContext::new(cpi_program, cpi_accounts);
-                        ::anchor_spl::associated_token::create(cpi_ctx)?;
+                        #create(cpi_ctx)?;
                     }
                     let pa: #ty_decl = #from_account_info_unchecked;
                     if #if_needed {
@@ -999,13 +1231,22 @@ fn generate_constraint_init_group(
             };
 
             // CPI to the system program to create the account.
-            let create_account = generate_create_account(
-                field,
-                quote! {space},
-                owner.clone(),
-                quote! {#payer},
-                seeds_with_bump,
-            );
+            let create_account = match &c.with_seed {
+                Some(with_seed) => generate_create_account_with_seed(
+                    field,
+                    quote! {space},
+                    owner.clone(),
+                    quote! {#payer},
+                    with_seed,
+                ),
+                None => generate_create_account(
+                    field,
+                    quote! {space},
+                    owner.clone(),
+                    quote! {#payer},
+                    seeds_with_bump,
+                ),
+            };
 
             // Put it all together.
             quote! {
@@ -1225,11 +1466,21 @@ fn generate_constraint_token_account(
         }
         None => quote! {},
     };
+    let vault_check = if c.vault {
+        quote! {
+            if #name.delegate.is_some() || #name.close_authority.is_some() {
+                return Err(anchor_lang::error::ErrorCode::ConstraintTokenVault.into());
+            }
+        }
+    } else {
+        quote! {}
+    };
     quote! {
         {
             #authority_check
             #mint_check
             #token_program_check
+            #vault_check
         }
     }
 }
@@ -1467,6 +1718,70 @@ fn generate_constraint_mint(
         None => quote! {},
     };
 
+    let group_member_group_check = match &c.group_member_group {
+        Some(group_member_group) => {
+            let group_member_group_optional_check =
+                optional_check_scope.generate_check(group_member_group);
+            quote! {
+                let group_member = ::anchor_spl::token_interface::get_mint_extension_data::<::anchor_spl::token_2022_extensions::spl_token_group_interface::state::TokenGroupMember>(#account_ref);
+                if group_member.is_err() {
+                    return Err(anchor_lang::error::ErrorCode::ConstraintMintTokenGroupMemberExtension.into());
+                }
+                #group_member_group_optional_check
+                if group_member.unwrap().group != #group_member_group.key() {
+                    return Err(anchor_lang::error::ErrorCode::ConstraintMintTokenGroupMemberExtensionGroup.into());
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    // Unlike the `extensions::*` checks, a missing extension is equivalent to
+    // the extension being set to `None`.
+    let mint_close_authority_check = match &c.mint_close_authority {
+        Some(c) => {
+            let (optional_check, expected) = match &c.close_authority {
+                Some(close_authority) => (
+                    optional_check_scope.generate_check(close_authority),
+                    quote! { Some(#close_authority.key()) },
+                ),
+                None => (quote! {}, quote! { None }),
+            };
+            quote! {
+                #optional_check
+                let close_authority = ::anchor_spl::token_interface::get_mint_extension_data::<::anchor_spl::token_interface::spl_token_2022::extension::mint_close_authority::MintCloseAuthority>(#account_ref)
+                    .ok()
+                    .and_then(|extension| Option::<anchor_lang::solana_program::pubkey::Pubkey>::from(extension.close_authority));
+                if close_authority != #expected {
+                    return Err(anchor_lang::error::ErrorCode::ConstraintMintCloseAuthority.into());
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    let mint_permanent_delegate_check = match &c.mint_permanent_delegate {
+        Some(c) => {
+            let (optional_check, expected) = match &c.permanent_delegate {
+                Some(permanent_delegate) => (
+                    optional_check_scope.generate_check(permanent_delegate),
+                    quote! { Some(#permanent_delegate.key()) },
+                ),
+                None => (quote! {}, quote! { None }),
+            };
+            quote! {
+                #optional_check
+                let permanent_delegate = ::anchor_spl::token_interface::get_mint_extension_data::<::anchor_spl::token_interface::spl_token_2022::extension::permanent_delegate::PermanentDelegate>(#account_ref)
+                    .ok()
+                    .and_then(|extension| Option::<anchor_lang::solana_program::pubkey::Pubkey>::from(extension.delegate));
+                if permanent_delegate != #expected {
+                    return Err(anchor_lang::error::ErrorCode::ConstraintMintPermanentDelegate.into());
+                }
+            }
+        }
+        None => quote! {},
+    };
+
     quote! {
         {
             #decimal_check
@@ -1483,6 +1798,9 @@ fn generate_constraint_mint(
             #permanent_delegate_check
             #transfer_hook_authority_check
             #transfer_hook_program_id_check
+            #group_member_group_check
+            #mint_close_authority_check
+            #mint_permanent_delegate_check
         }
     }
 }
@@ -1607,6 +1925,63 @@ fn generate_create_account(
     }
 }
 
+// Same as `generate_create_account` for addresses derived with a seed, which
+// are signed for by their base.
+fn generate_create_account_with_seed(
+    field: &Ident,
+    space: proc_macro2::TokenStream,
+    owner: proc_macro2::TokenStream,
+    payer: proc_macro2::TokenStream,
+    with_seed: &ConstraintWithSeedGroup,
+) -> proc_macro2::TokenStream {
+    let base = &with_seed.base;
+    let seed = &with_seed.seed;
+    quote! {
+        let __seed: &str = ::core::convert::AsRef::<str>::as_ref(&#seed);
+        let __current_lamports = #field.lamports();
+        if __current_lamports == 0 {
+            let space = #space;
+            let lamports = __anchor_rent.minimum_balance(space);
+            let cpi_accounts = anchor_lang::system_program::CreateAccountWithSeed {
+                from: #payer.to_account_info(),
+                to: #field.to_account_info(),
+                base: #base.to_account_info(),
+            };
+            let cpi_context = anchor_lang::context::CpiContext::new(system_program.to_account_info(), cpi_accounts);
+            anchor_lang::system_program::create_account_with_seed(cpi_context, __seed, lamports, space as u64, #owner)?;
+        } else {
+            require_keys_neq!(#payer.key(), #field.key(), anchor_lang::error::ErrorCode::TryingToInitPayerAsProgramAccount);
+            // Fund the account for rent exemption.
+            let required_lamports = __anchor_rent
+                .minimum_balance(#space)
+                .max(1)
+                .saturating_sub(__current_lamports);
+            if required_lamports > 0 {
+                let cpi_accounts = anchor_lang::system_program::Transfer {
+                    from: #payer.to_account_info(),
+                    to: #field.to_account_info(),
+                };
+                let cpi_context = anchor_lang::context::CpiContext::new(system_program.to_account_info(), cpi_accounts);
+                anchor_lang::system_program::transfer(cpi_context, required_lamports)?;
+            }
+            // Allocate space.
+            let cpi_accounts = anchor_lang::system_program::AllocateWithSeed {
+                account_to_allocate: #field.to_account_info(),
+                base: #base.to_account_info(),
+            };
+            let cpi_context = anchor_lang::context::CpiContext::new(system_program.to_account_info(), cpi_accounts);
+            anchor_lang::system_program::allocate_with_seed(cpi_context, __seed, #space as u64, #owner)?;
+            // Assign to the owner.
+            let cpi_accounts = anchor_lang::system_program::AssignWithSeed {
+                account_to_assign: #field.to_account_info(),
+                base: #base.to_account_info(),
+            };
+            let cpi_context = anchor_lang::context::CpiContext::new(system_program.to_account_info(), cpi_accounts);
+            anchor_lang::system_program::assign_with_seed(cpi_context, __seed, #owner)?;
+        }
+    }
+}
+
 pub fn generate_constraint_executable(
     f: &Field,
     _c: &ConstraintExecutable,
@@ -13,6 +13,7 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         where_clause,
     } = generics(accs);
 
+    let field_names: Vec<&syn::Ident> = accs.fields.iter().map(|af| af.ident()).collect();
     let on_save: Vec<proc_macro2::TokenStream> = accs
         .fields
         .iter()
@@ -29,17 +30,56 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                 let ident = &f.ident;
                 let name_str = ident.to_string();
                 if f.constraints.is_close() {
-                    let close_target = &f.constraints.close.as_ref().unwrap().sol_dest;
-                    let close_target_optional_check =
-                        OptionalCheckScope::new(accs).generate_check(close_target);
+                    let close = f.constraints.close.as_ref().unwrap();
+                    match close.sol_dest_field() {
+                        Some(close_target) => {
+                            let close_target_optional_check =
+                                OptionalCheckScope::new(accs).generate_check(close_target);
 
+                            quote! {
+                                {
+                                    let #close_target = &self.#close_target;
+                                    #close_target_optional_check
+                                    anchor_lang::AccountsClose::close(
+                                        &self.#ident,
+                                        #close_target.to_account_info(),
+                                    ).map_err(|e| e.with_account_name(#name_str))?;
+                                }
+                            }
+                        }
+                        None => {
+                            // The receiver is chosen at runtime, so the
+                            // expression is evaluated once with all fields in
+                            // scope, and checked here rather than on validation.
+                            let close_target = &close.sol_dest;
+                            quote! {
+                                {
+                                    #[allow(unused_variables)]
+                                    let Self { #(#field_names),* } = self;
+                                    let __close_target =
+                                        anchor_lang::ToAccountInfo::to_account_info(&(#close_target));
+                                    if self.#ident.key() == *__close_target.key {
+                                        return Err(anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintClose).with_account_name(#name_str));
+                                    }
+                                    anchor_lang::AccountsClose::close(
+                                        &self.#ident,
+                                        __close_target,
+                                    ).map_err(|e| e.with_account_name(#name_str))?;
+                                }
+                            }
+                        }
+                    }
+                } else if let Some(reassign) = &f.constraints.reassign {
+                    // The new owner is evaluated with all fields in scope.
+                    let new_owner = &reassign.new_owner;
                     quote! {
                         {
-                            let #close_target = &self.#close_target;
-                            #close_target_optional_check
-                            anchor_lang::AccountsClose::close(
-                                &self.#ident,
-                                #close_target.to_account_info(),
+                            #[allow(unused_variables)]
+                            let Self { #(#field_names),* } = self;
+                            let __new_owner: anchor_lang::solana_program::pubkey::Pubkey = #new_owner;
+                            anchor_lang::reassign_account(
+                                &anchor_lang::ToAccountInfo::to_account_info(&self.#ident),
+                                &__new_owner,
                             ).map_err(|e| e.with_account_name(#name_str))?;
                         }
                     }
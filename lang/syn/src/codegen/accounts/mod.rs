@@ -7,9 +7,11 @@ use syn::{GenericParam, PredicateLifetime, WhereClause, WherePredicate};
 
 pub mod __client_accounts;
 pub mod __cpi_client_accounts;
+mod box_threshold;
 mod bumps;
 mod constraints;
 mod exit;
+mod signer_seeds;
 mod to_account_infos;
 mod to_account_metas;
 mod try_accounts;
@@ -20,6 +22,8 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
     let impl_to_account_metas = to_account_metas::generate(accs);
     let impl_exit = exit::generate(accs);
     let bumps_struct = bumps::generate(accs);
+    let impl_signer_seeds = signer_seeds::generate(accs);
+    let box_threshold_assertions = box_threshold::generate(accs);
 
     let __client_accounts_mod = __client_accounts::generate(accs, quote!(crate::ID));
     let __cpi_client_accounts_mod = __cpi_client_accounts::generate(accs, quote!(crate::ID));
@@ -30,6 +34,8 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         #impl_to_account_metas
         #impl_exit
         #bumps_struct
+        #impl_signer_seeds
+        #box_threshold_assertions
 
         #__client_accounts_mod
         #__cpi_client_accounts_mod
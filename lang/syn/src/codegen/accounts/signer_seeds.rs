@@ -0,0 +1,125 @@
+use crate::codegen::accounts::{generics, ParsedGenerics};
+use crate::{AccountField, AccountsStruct};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+use std::collections::HashSet;
+use syn::{Expr, Ident};
+
+/// Generates a `<field>_signer_seeds(bump)` method for every PDA field of the
+/// accounts structs with `#[accounts(signer_seeds)]`, returning the seeds (bump
+/// included) that can be used to sign CPIs on behalf of the PDA.
+///
+/// Fields are skipped if the PDA isn't derived from the current program (as the
+/// program can't sign for it), if its seeds depend on instruction arguments,
+/// since those are not available outside of `try_accounts`, or if its seeds
+/// can't be evaluated outside of `try_accounts`, e.g. with `?` or `load()`.
+pub fn generate(accs: &AccountsStruct) -> TokenStream {
+    if !accs.signer_seeds {
+        return quote! {};
+    }
+
+    let name = &accs.ident;
+    let ParsedGenerics {
+        combined_generics,
+        trait_generics: _,
+        struct_generics,
+        where_clause,
+    } = generics(accs);
+
+    let ix_args = instruction_args(accs);
+    let field_names: Vec<&Ident> = accs.fields.iter().map(|af| af.ident()).collect();
+
+    let methods: Vec<TokenStream> = accs
+        .fields
+        .iter()
+        .filter_map(|af| match af {
+            AccountField::Field(f) => Some(f),
+            AccountField::CompositeField(_) => None,
+        })
+        .filter_map(|f| {
+            let c = f.constraints.seeds.as_ref()?;
+            if c.program_seed.is_some() {
+                return None;
+            }
+            if c.seeds
+                .iter()
+                .any(|seed| mentions_any(seed, &ix_args) || !is_plain(seed))
+            {
+                return None;
+            }
+
+            let field = &f.ident;
+            let method = Ident::new(&format!("{}_signer_seeds", field), Span::call_site());
+            let doc = format!(
+                " Returns the signer seeds of the `{}` PDA, including the given bump.",
+                field
+            );
+            let seeds = c.seeds.iter();
+            Some(quote! {
+                #[doc = #doc]
+                #[allow(unused_variables)]
+                pub fn #method(&self, __bump: u8) -> anchor_lang::SignerSeeds {
+                    let Self { #(#field_names),* } = self;
+                    anchor_lang::SignerSeeds::new(
+                        vec![#(anchor_lang::__private::seed_to_vec(#seeds)),*],
+                        __bump,
+                    )
+                }
+            })
+        })
+        .collect();
+
+    if methods.is_empty() {
+        return quote! {};
+    }
+
+    quote! {
+        #[automatically_derived]
+        impl<#combined_generics> #name<#struct_generics> #where_clause {
+            #(#methods)*
+        }
+    }
+}
+
+/// Returns the names of the arguments declared with `#[instruction(...)]`.
+fn instruction_args(accs: &AccountsStruct) -> HashSet<String> {
+    accs.instruction_api
+        .iter()
+        .flat_map(|api| api.iter())
+        .filter_map(|expr| match expr {
+            Expr::Type(expr_type) => Some(crate::parser::tts_to_string(&expr_type.expr)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the seed can be evaluated from the fields of the accounts struct,
+/// i.e. it doesn't propagate errors with `?` nor borrows zero-copy accounts
+/// with `load()`, whose guards can't outlive `try_accounts`.
+fn is_plain(expr: &Expr) -> bool {
+    fn visit(stream: TokenStream) -> bool {
+        stream.into_iter().all(|tt| match tt {
+            TokenTree::Punct(punct) => punct.as_char() != '?',
+            TokenTree::Ident(ident) => !["load", "load_mut", "load_init"]
+                .iter()
+                .any(|load| ident == load),
+            TokenTree::Group(group) => visit(group.stream()),
+            TokenTree::Literal(_) => true,
+        })
+    }
+
+    visit(quote!(#expr))
+}
+
+/// Whether the expression references any identifier in `idents`.
+fn mentions_any(expr: &Expr, idents: &HashSet<String>) -> bool {
+    fn visit(stream: TokenStream, idents: &HashSet<String>) -> bool {
+        stream.into_iter().any(|tt| match tt {
+            TokenTree::Ident(ident) => idents.contains(&ident.to_string()),
+            TokenTree::Group(group) => visit(group.stream(), idents),
+            _ => false,
+        })
+    }
+
+    !idents.is_empty() && visit(quote!(#expr), idents)
+}
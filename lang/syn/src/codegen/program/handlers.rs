@@ -100,9 +100,41 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             let ix_arg_names: Vec<&syn::Ident> = ix.args.iter().map(|arg| &arg.name).collect();
             let ix_name = generate_ix_variant_name(ix.raw_method.sig.ident.to_string());
             let ix_method_name = &ix.raw_method.sig.ident;
-            let anchor = &ix.anchor_ident;
+            let anchor = ix.anchor_ty();
             let variant_arm = generate_ix_variant(ix.raw_method.sig.ident.to_string(), &ix.args);
             let ix_name_log = format!("Instruction: {ix_name}");
+            let maybe_log_ix_name = if ix.no_log_ix_name {
+                quote! {}
+            } else {
+                quote! {
+                    #[cfg(not(feature = "no-log-ix-name"))]
+                    anchor_lang::prelude::msg!(#ix_name_log);
+                }
+            };
+            let ix_name_str = ix.raw_method.sig.ident.to_string();
+            let maybe_before_instruction = match &program.before_instruction {
+                Some(hook) => quote! {
+                    #program_name::#hook(__program_id, #ix_name_str, __accounts)?;
+                },
+                None => quote! {},
+            };
+            // Keep the raw accounts around since `__accounts` is shadowed by
+            // the deserialized accounts struct.
+            let maybe_raw_accounts = match &program.after_instruction {
+                Some(_) => quote! {
+                    let __ix_accounts: &'info [AccountInfo<'info>] = __accounts;
+                },
+                None => quote! {},
+            };
+            let maybe_exit_and_after_instruction = match &program.after_instruction {
+                Some(hook) => quote! {
+                    __accounts.exit(__program_id)?;
+                    #program_name::#hook(__program_id, #ix_name_str, __ix_accounts)
+                },
+                None => quote! {
+                    __accounts.exit(__program_id)
+                },
+            };
             let ret_type = &ix.returns.ty.to_token_stream();
             let maybe_set_return_data = match ret_type.to_string().as_str() {
                 "()" => quote! {},
@@ -119,8 +151,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                     __accounts: &'info[AccountInfo<'info>],
                     __ix_data: &[u8],
                 ) -> anchor_lang::Result<()> {
-                    #[cfg(not(feature = "no-log-ix-name"))]
-                    anchor_lang::prelude::msg!(#ix_name_log);
+                    #maybe_log_ix_name
+
+                    #maybe_before_instruction
+                    #maybe_raw_accounts
 
                     // Deserialize data.
                     let ix = instruction::#ix_name::deserialize(&mut &__ix_data[..])
@@ -134,7 +168,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
                     // Deserialize accounts.
                     let mut __remaining_accounts: &[AccountInfo] = __accounts;
-                    let mut __accounts = #anchor::try_accounts(
+                    let mut __accounts = <#anchor>::try_accounts(
                         __program_id,
                         &mut __remaining_accounts,
                         __ix_data,
@@ -157,7 +191,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                     #maybe_set_return_data
 
                     // Exit routine.
-                    __accounts.exit(__program_id)
+                    #maybe_exit_and_after_instruction
                 }
             }
         })
@@ -1,8 +1,9 @@
 use crate::codegen::program::common::*;
 use crate::parser;
-use crate::Program;
+use crate::{IxArg, Program};
 use heck::CamelCase;
-use quote::quote;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
 
 pub fn generate(program: &Program) -> proc_macro2::TokenStream {
     let variants: Vec<proc_macro2::TokenStream> = program
@@ -39,24 +40,38 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                     }
                 }
             };
+            // Property-based tests generate the instructions with proptest,
+            // which requires them to implement `Debug`.
+            let (derive_debug, arbitrary) = if cfg!(feature = "proptest") {
+                (
+                    quote! { #[derive(Debug)] },
+                    generate_arbitrary(&ix_name_camel, &ix.args),
+                )
+            } else {
+                (quote! {}, quote! {})
+            };
             // If no args, output a "unit" variant instead of a struct variant.
             if ix.args.is_empty() {
                 quote! {
                     /// Instruction.
                     #[derive(AnchorSerialize, AnchorDeserialize)]
+                    #derive_debug
                     pub struct #ix_name_camel;
 
                     #ix_data_trait
+                    #arbitrary
                 }
             } else {
                 quote! {
                     /// Instruction.
                     #[derive(AnchorSerialize, AnchorDeserialize)]
+                    #derive_debug
                     pub struct #ix_name_camel {
                         #(#raw_args),*
                     }
 
                     #ix_data_trait
+                    #arbitrary
                 }
             }
         })
@@ -77,3 +92,102 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
         }
     }
 }
+
+// Generate the `proptest::arbitrary::Arbitrary` implementation of an instruction,
+// generating each argument with its `#[strategy(..)]` or, by default, with the
+// strategy of its type.
+fn generate_arbitrary(ix_name: &proc_macro2::Ident, args: &[IxArg]) -> proc_macro2::TokenStream {
+    let strategies = args.iter().map(|arg| match &arg.strategy {
+        Some(strategy) => quote! { #strategy },
+        None => generate_strategy(&arg.raw_arg.ty),
+    });
+    let names = args.iter().map(|arg| &arg.name).collect::<Vec<_>>();
+
+    // Tuples of strategies are strategies, nested so that their length doesn't
+    // exceed the ones implementing `Strategy`.
+    let (strategy, pattern) = strategies.zip(&names).rev().fold(
+        (quote! { Just(()) }, quote! { () }),
+        |(strategy, pattern), (arg_strategy, name)| {
+            (
+                quote! { (#arg_strategy, #strategy) },
+                quote! { (#name, #pattern) },
+            )
+        },
+    );
+    let value = if names.is_empty() {
+        quote! { Self }
+    } else {
+        quote! { Self { #(#names),* } }
+    };
+
+    quote! {
+        impl anchor_lang::__private::proptest::arbitrary::Arbitrary for #ix_name {
+            type Parameters = ();
+            type Strategy = anchor_lang::__private::proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                use anchor_lang::__private::proptest::prelude::*;
+
+                #strategy.prop_map(|#pattern| #value).boxed()
+            }
+        }
+    }
+}
+
+// Generate the default strategy of a type. `Pubkey` doesn't implement `Arbitrary`,
+// so the strategies of the options, vectors, arrays and tuples are built from the
+// strategies of their elements, which may be `Pubkey`s.
+//
+// Other types are generated with their `Arbitrary` implementation, spanned to the
+// type so that a missing implementation, e.g. of a type of the program, is
+// reported on the argument that needs a `#[strategy(..)]`.
+fn generate_strategy(ty: &syn::Type) -> proc_macro2::TokenStream {
+    match ty {
+        syn::Type::Path(path) if path.qself.is_none() => {
+            if let Some(segment) = path.path.segments.last() {
+                if segment.ident == "Pubkey" {
+                    return quote! { any::<[u8; 32]>().prop_map(Pubkey::new_from_array) };
+                }
+                if let Some(elem) = generic_type_arg(segment) {
+                    let elem = generate_strategy(elem);
+                    if segment.ident == "Option" {
+                        return quote! { prop::option::of(#elem) };
+                    }
+                    if segment.ident == "Vec" {
+                        return quote! {
+                            prop::collection::vec(#elem, prop::collection::SizeRange::default())
+                        };
+                    }
+                    if segment.ident == "Box" {
+                        return quote! { (#elem).prop_map(Box::new) };
+                    }
+                }
+            }
+        }
+        syn::Type::Array(array) => {
+            let elem = generate_strategy(&array.elem);
+            let len = &array.len;
+            return quote! { prop::array::uniform::<_, { #len }>(#elem) };
+        }
+        syn::Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+            let elems = tuple.elems.iter().map(generate_strategy);
+            return quote! { (#(#elems,)*) };
+        }
+        syn::Type::Paren(paren) => return generate_strategy(&paren.elem),
+        _ => {}
+    }
+    quote_spanned! { ty.span()=> any::<#ty>() }
+}
+
+// Get the type of a path segment with a single generic type, e.g. `T` of `Vec<T>`.
+fn generic_type_arg(segment: &syn::PathSegment) -> Option<&syn::Type> {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(ty)) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
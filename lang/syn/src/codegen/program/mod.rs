@@ -9,6 +9,7 @@ mod entry;
 mod handlers;
 mod idl;
 mod instruction;
+mod security_txt;
 
 pub fn generate(program: &Program) -> proc_macro2::TokenStream {
     let mod_name = &program.name;
@@ -20,6 +21,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
     let instruction = instruction::generate(program);
     let cpi = cpi::generate(program);
     let accounts = accounts::generate(program);
+    let security_txt = security_txt::generate();
 
     #[allow(clippy::let_and_return)]
     let ret = {
@@ -34,6 +36,7 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
             #instruction
             #cpi
             #accounts
+            #security_txt
         }
     };
 
@@ -0,0 +1,69 @@
+use quote::quote;
+
+/// Fields of the security.txt, in the order of the `solana-security-txt`
+/// convention.
+const FIELDS: &[&str] = &[
+    "name",
+    "project_url",
+    "contacts",
+    "policy",
+    "preferred_languages",
+    "encryption",
+    "source_code",
+    "source_release",
+    "source_revision",
+    "auditors",
+    "acknowledgements",
+    "expiry",
+];
+
+/// Embeds the security.txt configured in the `[program.security]` of the
+/// Anchor.toml, which `anchor build` passes in `ANCHOR_SECURITY_TXT_<PACKAGE>_*`
+/// environment variables. The variables are keyed by package so that programs
+/// built as dependencies don't embed the security.txt of another program.
+///
+/// The security.txt is exported as the same `security_txt` symbol as the one
+/// of the `security_txt!` macro of `solana-security-txt`, so `anchor build`
+/// doesn't pass the variables to programs invoking the macro and fails
+/// instead.
+pub fn generate() -> proc_macro2::TokenStream {
+    let package = std::env::var("CARGO_PKG_NAME").unwrap_or_default();
+    let prefix = format!(
+        "ANCHOR_SECURITY_TXT_{}",
+        package.to_uppercase().replace('-', "_")
+    );
+    let vars = FIELDS
+        .iter()
+        .map(|field| format!("{prefix}_{}", field.to_uppercase()))
+        .collect::<Vec<_>>();
+    let len = vars.len();
+    // Reading the variables makes cargo rebuild the program when they change
+    let track = quote! {
+        const _: [Option<&str>; #len] = [#(option_env!(#vars)),*];
+    };
+
+    if std::env::var(format!("{prefix}_CONTACTS")).is_err() {
+        return track;
+    }
+
+    let mut security_txt = "=======BEGIN SECURITY.TXT V1=======\0".to_owned();
+    for (field, var) in FIELDS.iter().zip(&vars) {
+        let value = match (std::env::var(var), *field) {
+            (Ok(value), _) => value,
+            (Err(_), "name") => package.clone(),
+            _ => continue,
+        };
+        security_txt.push_str(&format!("{field}\0{value}\0"));
+    }
+    security_txt.push_str("=======END SECURITY.TXT V1=======\0");
+
+    quote! {
+        #track
+
+        #[cfg(not(feature = "no-entrypoint"))]
+        #[cfg_attr(target_os = "solana", link_section = ".security.txt")]
+        #[allow(dead_code)]
+        #[no_mangle]
+        pub static security_txt: &str = #security_txt;
+    }
+}
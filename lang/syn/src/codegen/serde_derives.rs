@@ -0,0 +1,63 @@
+use quote::quote;
+use syn::{parse_quote, Fields, GenericArgument, ItemStruct, PathArguments, Type};
+
+/// Returns the `serde` derives for the given struct and annotates its public
+/// key fields so that they're (de)serialized as base58 strings.
+///
+/// The generated code refers to `serde` through `anchor_lang`, so programs
+/// don't need to depend on it directly.
+pub fn gen_serde_derives(strct: &mut ItemStruct) -> proc_macro2::TokenStream {
+    let fields = match &mut strct.fields {
+        Fields::Named(fields) => fields.named.iter_mut().collect::<Vec<_>>(),
+        Fields::Unnamed(fields) => fields.unnamed.iter_mut().collect(),
+        Fields::Unit => vec![],
+    };
+    for field in fields {
+        if field.attrs.iter().any(|attr| attr.path.is_ident("serde")) {
+            continue;
+        }
+        let with = match pubkey_kind(&field.ty) {
+            Some(PubkeyKind::Pubkey) => "anchor_lang::__private::serde_pubkey",
+            Some(PubkeyKind::Option) => "anchor_lang::__private::serde_pubkey::option",
+            Some(PubkeyKind::Vec) => "anchor_lang::__private::serde_pubkey::vec",
+            None => continue,
+        };
+        field.attrs.push(parse_quote!(#[serde(with = #with)]));
+    }
+
+    quote! {
+        #[derive(anchor_lang::__private::serde::Serialize, anchor_lang::__private::serde::Deserialize)]
+        #[serde(crate = "anchor_lang::__private::serde")]
+    }
+}
+
+enum PubkeyKind {
+    Pubkey,
+    Option,
+    Vec,
+}
+
+fn pubkey_kind(ty: &Type) -> Option<PubkeyKind> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    match segment.ident.to_string().as_str() {
+        "Pubkey" => Some(PubkeyKind::Pubkey),
+        wrapper @ ("Option" | "Vec") => {
+            let inner = match &segment.arguments {
+                PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    GenericArgument::Type(inner) => inner,
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            match pubkey_kind(inner)? {
+                PubkeyKind::Pubkey if wrapper == "Option" => Some(PubkeyKind::Option),
+                PubkeyKind::Pubkey => Some(PubkeyKind::Vec),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -2,7 +2,10 @@ use anyhow::{anyhow, Result};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 
-use super::common::{get_idl_module_path, get_no_docs};
+use super::{
+    common::{get_idl_module_path, get_no_docs},
+    defined::gen_idl_generic_args,
+};
 use crate::{AccountField, AccountsStruct, Field, InitKind, Ty};
 
 /// Generate the IDL build impl for the Accounts struct.
@@ -16,6 +19,19 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
     let ident = &accounts.ident;
     let (impl_generics, ty_generics, where_clause) = accounts.generics.split_for_impl();
 
+    // Generic account types are stored with their type parameters, the arguments of the
+    // instantiation are stored in the account itself, e.g. `u64` of `Account<'info, Vault<u64>>`
+    let generic_params = accounts
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Type(param) => Some(param.ident.clone()),
+            syn::GenericParam::Const(param) => Some(param.ident.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
     let (accounts, defined) = accounts
         .fields
         .iter()
@@ -33,14 +49,20 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                     _ => quote! { vec![] },
                 };
 
-                let (address, pda, relations) = if resolution {
+                let (address, addresses, pda, relations) = if resolution {
                     (
                         get_address(acc),
+                        get_addresses(acc),
                         get_pda(acc, accounts),
                         get_relations(acc, accounts),
                     )
                 } else {
-                    (quote! { None }, quote! { None }, quote! { vec![] })
+                    (
+                        quote! { None },
+                        quote! { vec![] },
+                        quote! { None },
+                        quote! { vec![] },
+                    )
                 };
 
                 let acc_type_path = match &acc.ty {
@@ -60,6 +82,7 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                         Some(&ty.account_type_path)
                     }
                     Ty::AccountLoader(ty) => Some(&ty.account_type_path),
+                    Ty::AccountWithTail(ty) => Some(&ty.account_type_path),
                     Ty::InterfaceAccount(ty) => Some(&ty.account_type_path),
                     _ => None,
                 };
@@ -73,6 +96,7 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                             signer: #signer,
                             optional: #optional,
                             address: #address,
+                            addresses: #addresses,
                             pda: #pda,
                             relations: #relations,
                         })
@@ -117,6 +141,11 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
         .unzip::<_, _, Vec<_>, Vec<_>>();
     let defined = defined.into_iter().flatten().collect::<Vec<_>>();
 
+    let (generics, generic_defined) = defined
+        .iter()
+        .map(|ty| gen_idl_generic_args(ty, &generic_params).unwrap_or_default())
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+
     quote! {
         impl #impl_generics #ident #ty_generics #where_clause {
             pub fn __anchor_private_gen_idl_accounts(
@@ -128,10 +157,17 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                         let account = #idl::IdlAccount {
                             name: ty.name.clone(),
                             discriminator: <#defined as anchor_lang::Discriminator>::DISCRIMINATOR.into(),
+                            generics: vec![#(#generics),*],
                         };
                         accounts.insert(account.name.clone(), account);
                         types.insert(ty.name.clone(), ty);
                         <#defined>::insert_types(types);
+                        #(
+                            if let Some(ty) = <#generic_defined>::create_type() {
+                                types.insert(<#generic_defined>::get_full_path(), ty);
+                                <#generic_defined>::insert_types(types);
+                            }
+                        )*
                     }
                 );*
 
@@ -167,6 +203,21 @@ fn get_address(acc: &Field) -> TokenStream {
     }
 }
 
+fn get_addresses(acc: &Field) -> TokenStream {
+    match &acc.ty {
+        Ty::Interface(ty) => {
+            let ty = &ty.account_type_path;
+            quote! {
+                <#ty as anchor_lang::Ids>::ids()
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect()
+            }
+        }
+        _ => quote! { vec![] },
+    }
+}
+
 fn get_pda(acc: &Field, accounts: &AccountsStruct) -> TokenStream {
     let idl = get_idl_module_path();
     let parse_default = |expr: &syn::Expr| parse_seed(expr, accounts);
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Result};
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
 
 use super::common::{get_idl_module_path, get_no_docs};
 use crate::parser::docs;
@@ -73,7 +73,8 @@ fn impl_idl_build(
 pub fn gen_idl_type_def_struct(
     strct: &syn::ItemStruct,
 ) -> Result<(TokenStream, Vec<syn::TypePath>)> {
-    gen_idl_type_def(&strct.attrs, &strct.generics, |generic_params| {
+    let layout = gen_idl_layout(strct);
+    gen_idl_type_def(&strct.attrs, &strct.generics, layout, |generic_params| {
         let no_docs = get_no_docs();
         let idl = get_idl_module_path();
 
@@ -122,7 +123,8 @@ pub fn gen_idl_type_def_struct(
 }
 
 fn gen_idl_type_def_enum(enm: &syn::ItemEnum) -> Result<(TokenStream, Vec<syn::TypePath>)> {
-    gen_idl_type_def(&enm.attrs, &enm.generics, |generic_params| {
+    let layout = quote! { None };
+    gen_idl_type_def(&enm.attrs, &enm.generics, layout, |generic_params| {
         let no_docs = get_no_docs();
         let idl = get_idl_module_path();
 
@@ -186,9 +188,44 @@ fn gen_idl_type_def_enum(enm: &syn::ItemEnum) -> Result<(TokenStream, Vec<syn::T
     })
 }
 
+/// Generate the memory layout of `bytemuck` serialized structs with named fields.
+///
+/// The layout is left out for generic structs since it depends on the generic arguments.
+fn gen_idl_layout(strct: &syn::ItemStruct) -> TokenStream {
+    let idl = get_idl_module_path();
+    let is_bytemuck = get_attr_str("derive", &strct.attrs)
+        .map(|derive| derive.contains("bytemuck"))
+        .unwrap_or_default();
+    let fields = match &strct.fields {
+        syn::Fields::Named(fields) if is_bytemuck && strct.generics.params.is_empty() => fields,
+        _ => return quote! { None },
+    };
+
+    let offsets = fields
+        .named
+        .iter()
+        .filter_map(|f| f.ident.as_ref())
+        .map(|ident| {
+            let name = ident.to_string();
+            quote! {
+                #idl::IdlFieldOffset {
+                    name: #name.into(),
+                    offset: anchor_lang::__field_offset!(Self, #ident) as u64,
+                }
+            }
+        });
+    quote! {
+        Some(#idl::IdlLayout {
+            size: ::std::mem::size_of::<Self>() as u64,
+            offsets: vec![#(#offsets),*],
+        })
+    }
+}
+
 fn gen_idl_type_def<F>(
     attrs: &[syn::Attribute],
     generics: &syn::Generics,
+    layout: TokenStream,
     create_fields: F,
 ) -> Result<(TokenStream, Vec<syn::TypePath>)>
 where
@@ -202,17 +239,23 @@ where
         _ => quote! { vec![] },
     };
 
-    let serialization = get_attr_str("derive", attrs)
-        .and_then(|derive| {
-            if derive.contains("bytemuck") {
-                if derive.to_lowercase().contains("unsafe") {
-                    Some(quote! { #idl::IdlSerialization::BytemuckUnsafe })
+    let serialization = get_attr_str("serialization", attrs)
+        .map(|codec| {
+            let codec = codec.trim_matches(|c| matches!(c, '(' | ')' | '"' | ' '));
+            quote! { #idl::IdlSerialization::Custom(#codec.into()) }
+        })
+        .or_else(|| {
+            get_attr_str("derive", attrs).and_then(|derive| {
+                if derive.contains("bytemuck") {
+                    if derive.to_lowercase().contains("unsafe") {
+                        Some(quote! { #idl::IdlSerialization::BytemuckUnsafe })
+                    } else {
+                        Some(quote! { #idl::IdlSerialization::Bytemuck })
+                    }
                 } else {
-                    Some(quote! { #idl::IdlSerialization::Bytemuck })
+                    None
                 }
-            } else {
-                None
-            }
+            })
         })
         .unwrap_or_else(|| quote! { #idl::IdlSerialization::default() });
 
@@ -296,6 +339,7 @@ where
                 serialization: #serialization,
                 repr: #repr,
                 generics: vec![#(#generics.into()),*],
+                layout: #layout,
                 ty: #ty,
             }
         },
@@ -471,6 +515,17 @@ pub fn gen_idl_type(
                     }
                     _ => unreachable!("Array length can only be a generic parameter"),
                 }
+            } else if generic_params
+                .iter()
+                .any(|param| contains_ident(len.to_token_stream(), param))
+            {
+                // The length would otherwise be evaluated for the instantiation the IDL is
+                // built from, flattening the generic type definition
+                return Err(anyhow!(
+                    "Array length `{}` must either be a constant or a single const generic \
+                    parameter",
+                    len.to_token_stream()
+                ));
             } else {
                 quote! { #idl::IdlArrayLen::Value(#len) }
             };
@@ -494,7 +549,6 @@ pub fn gen_idl_type(
             {
                 use super::{common::find_path, external::get_external_type};
                 use crate::parser::context::CrateContext;
-                use quote::ToTokens;
 
                 let source_path = proc_macro2::Span::call_site().source_file().path();
                 let lib_path = find_path("lib.rs", &source_path).expect("lib.rs should exist");
@@ -577,26 +631,8 @@ pub fn gen_idl_type(
             }
 
             // Defined in crate
-            let mut generics = vec![];
-            let mut defined = vec![path.clone()];
-
-            if let Some(segment) = path.path.segments.last() {
-                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                    for arg in &args.args {
-                        match arg {
-                            syn::GenericArgument::Type(ty) => {
-                                let (ty, def) = gen_idl_type(ty, generic_params)?;
-                                generics.push(quote! { #idl::IdlGenericArg::Type { ty: #ty } });
-                                defined.extend(def);
-                            }
-                            syn::GenericArgument::Const(c) => generics.push(
-                                quote! { #idl::IdlGenericArg::Const { value: #c.to_string() } },
-                            ),
-                            _ => (),
-                        }
-                    }
-                }
-            }
+            let (generics, mut defined) = gen_idl_generic_args(path, generic_params)?;
+            defined.insert(0, path.clone());
 
             Ok((
                 quote! {
@@ -618,6 +654,47 @@ pub fn gen_idl_type(
     }
 }
 
+/// Generate the generic arguments of a defined type path, e.g. `u64` and `8` of `Foo<u64, 8>`.
+pub fn gen_idl_generic_args(
+    path: &syn::TypePath,
+    generic_params: &[syn::Ident],
+) -> Result<(Vec<TokenStream>, Vec<syn::TypePath>)> {
+    let idl = get_idl_module_path();
+
+    let mut generics = vec![];
+    let mut defined = vec![];
+
+    let args = match path.path.segments.last().map(|segment| &segment.arguments) {
+        Some(syn::PathArguments::AngleBracketed(args)) => &args.args,
+        _ => return Ok((generics, defined)),
+    };
+    for arg in args {
+        match arg {
+            syn::GenericArgument::Type(ty) => {
+                let (ty, def) = gen_idl_type(ty, generic_params)?;
+                generics.push(quote! { #idl::IdlGenericArg::Type { ty: #ty } });
+                defined.extend(def);
+            }
+            // Constants can't be told apart from types syntactically, so they must be literals
+            // or blocks, e.g. `Foo<8>` or `Foo<{ MAX_LEN }>`
+            syn::GenericArgument::Const(c) => {
+                generics.push(quote! { #idl::IdlGenericArg::Const { value: #c.to_string() } })
+            }
+            _ => (),
+        }
+    }
+
+    Ok((generics, defined))
+}
+
+fn contains_ident(tokens: TokenStream, ident: &syn::Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(i) => &i == ident,
+        proc_macro2::TokenTree::Group(group) => contains_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
 fn get_first_segment(type_path: &syn::TypePath) -> &syn::PathSegment {
     type_path.path.segments.first().unwrap()
 }
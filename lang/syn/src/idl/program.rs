@@ -31,7 +31,7 @@ pub fn gen_idl_print_fn_program(program: &Program) -> TokenStream {
         .flat_map(|ix| -> Result<_> {
             let name = ix.ident.to_string();
             let name_pascal = format_ident!("{}", name.to_camel_case());
-            let ctx_ident = &ix.anchor_ident;
+            let ctx_ty = ix.anchor_ty();
             let discriminator = quote! {
                 <crate::instruction::#name_pascal as anchor_lang::Discriminator>::DISCRIMINATOR
             };
@@ -81,7 +81,7 @@ pub fn gen_idl_print_fn_program(program: &Program) -> TokenStream {
                         name: #name.into(),
                         docs: #docs,
                         discriminator: #discriminator.into(),
-                        accounts: #ctx_ident::__anchor_private_gen_idl_accounts(
+                        accounts: <#ctx_ty>::__anchor_private_gen_idl_accounts(
                             &mut accounts,
                             &mut types,
                         ),
@@ -26,8 +26,8 @@ use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::Comma;
 use syn::{
-    Expr, Generics, Ident, ItemEnum, ItemFn, ItemMod, ItemStruct, LitInt, PatType, Token, Type,
-    TypePath,
+    Expr, GenericArgument, Generics, Ident, ItemEnum, ItemFn, ItemMod, ItemStruct, LitInt, PatType,
+    Token, Type, TypePath,
 };
 
 #[derive(Debug)]
@@ -37,6 +37,10 @@ pub struct Program {
     pub docs: Option<Vec<String>>,
     pub program_mod: ItemMod,
     pub fallback_fn: Option<FallbackFn>,
+    // The function marked with `#[before_instruction]`.
+    pub before_instruction: Option<Ident>,
+    // The function marked with `#[after_instruction]`.
+    pub after_instruction: Option<Ident>,
 }
 
 impl Parse for Program {
@@ -67,8 +71,26 @@ pub struct Ix {
     pub returns: IxReturn,
     // The ident for the struct deriving Accounts.
     pub anchor_ident: Ident,
+    // The type and const generic arguments of the struct deriving Accounts.
+    pub anchor_generics: Vec<GenericArgument>,
     // The discriminator based on the `#[interface]` attribute.
     pub interface_discriminator: Option<[u8; 8]>,
+    // Whether the instruction name logging is disabled with `#[no_log_ix_name]`.
+    pub no_log_ix_name: bool,
+}
+
+impl Ix {
+    /// The type of the struct deriving Accounts, including its generic
+    /// arguments (if any) so that it can be used in expressions.
+    pub fn anchor_ty(&self) -> TokenStream {
+        let anchor_ident = &self.anchor_ident;
+        if self.anchor_generics.is_empty() {
+            quote! { #anchor_ident }
+        } else {
+            let generics = &self.anchor_generics;
+            quote! { #anchor_ident<#(#generics),*> }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -76,6 +98,8 @@ pub struct IxArg {
     pub name: Ident,
     pub docs: Option<Vec<String>>,
     pub raw_arg: PatType,
+    // The proptest strategy of the argument set with `#[strategy(..)]`.
+    pub strategy: Option<Expr>,
 }
 
 #[derive(Debug)]
@@ -98,6 +122,10 @@ pub struct AccountsStruct {
     pub fields: Vec<AccountField>,
     // Instruction data api expression.
     instruction_api: Option<Punctuated<Expr, Comma>>,
+    // Maximum size of unboxed account types, set with `#[accounts(box_threshold = <bytes>)]`.
+    box_threshold: Option<LitInt>,
+    // Whether `<field>_signer_seeds` methods are generated, set with `#[accounts(signer_seeds)]`.
+    signer_seeds: bool,
 }
 
 impl Parse for AccountsStruct {
@@ -132,6 +160,8 @@ impl AccountsStruct {
             generics,
             fields,
             instruction_api,
+            box_threshold: None,
+            signer_seeds: false,
         }
     }
 
@@ -209,6 +239,9 @@ impl AccountField {
         let qualified_ty_name = match self {
             AccountField::Field(field) => match &field.ty {
                 Ty::Account(account) => Some(parser::tts_to_string(&account.account_type_path)),
+                Ty::AccountWithTail(account) => {
+                    Some(parser::tts_to_string(&account.account_type_path))
+                }
                 _ => None,
             },
             AccountField::CompositeField(field) => Some(field.symbol.clone()),
@@ -283,6 +316,8 @@ impl Field {
                     SysvarTy::StakeHistory => quote! {StakeHistory},
                     SysvarTy::Instructions => quote! {Instructions},
                     SysvarTy::Rewards => quote! {Rewards},
+                    SysvarTy::EpochRewards => quote! {EpochRewards},
+                    SysvarTy::LastRestartSlot => quote! {LastRestartSlot},
                 };
                 quote! {
                     Sysvar<#account>
@@ -352,6 +387,23 @@ impl Field {
                     stream
                 }
             }
+            Ty::AccountWithTail(_) => {
+                if checked {
+                    quote! {
+                        match #container_ty::try_from(&#field) {
+                            Ok(val) => val,
+                            Err(e) => return Err(e.with_account_name(#field_str))
+                        }
+                    }
+                } else {
+                    quote! {
+                        match #container_ty::try_from_unchecked(&#field) {
+                            Ok(val) => val,
+                            Err(e) => return Err(e.with_account_name(#field_str))
+                        }
+                    }
+                }
+            }
             Ty::AccountLoader(_) => {
                 if checked {
                     quote! {
@@ -397,6 +449,9 @@ impl Field {
             Ty::AccountLoader(_) => quote! {
                 anchor_lang::accounts::account_loader::AccountLoader
             },
+            Ty::AccountWithTail(_) => quote! {
+                anchor_lang::accounts::account_with_tail::AccountWithTail
+            },
             Ty::Sysvar(_) => quote! { anchor_lang::accounts::sysvar::Sysvar },
             Ty::Program(_) => quote! { anchor_lang::accounts::program::Program },
             Ty::Interface(_) => quote! { anchor_lang::accounts::interface::Interface },
@@ -447,6 +502,12 @@ impl Field {
                     #ident
                 }
             }
+            Ty::AccountWithTail(ty) => {
+                let ident = &ty.account_type_path;
+                quote! {
+                    #ident
+                }
+            }
             Ty::Sysvar(ty) => match ty {
                 SysvarTy::Clock => quote! {Clock},
                 SysvarTy::Rent => quote! {Rent},
@@ -458,6 +519,8 @@ impl Field {
                 SysvarTy::StakeHistory => quote! {StakeHistory},
                 SysvarTy::Instructions => quote! {Instructions},
                 SysvarTy::Rewards => quote! {Rewards},
+                SysvarTy::EpochRewards => quote! {EpochRewards},
+                SysvarTy::LastRestartSlot => quote! {LastRestartSlot},
             },
             Ty::Program(ty) => {
                 let program = &ty.account_type_path;
@@ -491,6 +554,7 @@ pub enum Ty {
     AccountInfo,
     UncheckedAccount,
     AccountLoader(AccountLoaderTy),
+    AccountWithTail(AccountWithTailTy),
     Sysvar(SysvarTy),
     Account(AccountTy),
     Program(ProgramTy),
@@ -513,6 +577,8 @@ pub enum SysvarTy {
     StakeHistory,
     Instructions,
     Rewards,
+    EpochRewards,
+    LastRestartSlot,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -521,6 +587,12 @@ pub struct AccountLoaderTy {
     pub account_type_path: TypePath,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct AccountWithTailTy {
+    // The struct type of the account header.
+    pub account_type_path: TypePath,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct AccountTy {
     // The struct type of the account.
@@ -598,7 +670,15 @@ pub struct ConstraintGroup {
     pub has_one: Vec<ConstraintHasOne>,
     pub raw: Vec<ConstraintRaw>,
     pub close: Option<ConstraintClose>,
+    pub reassign: Option<ConstraintReassign>,
+    pub not_before: Option<ConstraintClockBound>,
+    pub not_after: Option<ConstraintClockBound>,
+    pub not_before_slot: Option<ConstraintClockBound>,
+    pub not_after_slot: Option<ConstraintClockBound>,
+    pub authority_any_of: Option<ConstraintAuthorityAnyOf>,
+    pub extensions: Vec<ConstraintExtensionPresence>,
     pub address: Option<ConstraintAddress>,
+    pub with_seed: Option<ConstraintWithSeedGroup>,
     pub associated_token: Option<ConstraintAssociatedToken>,
     pub token_account: Option<ConstraintTokenAccountGroup>,
     pub mint: Option<ConstraintTokenMintGroup>,
@@ -621,6 +701,10 @@ impl ConstraintGroup {
     pub fn is_close(&self) -> bool {
         self.close.is_some()
     }
+
+    pub fn is_reassign(&self) -> bool {
+        self.reassign.is_some()
+    }
 }
 
 // A single account constraint *after* merging all tokens into a well formed
@@ -641,7 +725,12 @@ pub enum Constraint {
     AssociatedToken(ConstraintAssociatedToken),
     Executable(ConstraintExecutable),
     Close(ConstraintClose),
+    NotBefore(ConstraintClockBound),
+    NotAfter(ConstraintClockBound),
+    AuthorityAnyOf(ConstraintAuthorityAnyOf),
+    ExtensionPresence(ConstraintExtensionPresence),
     Address(ConstraintAddress),
+    WithSeed(ConstraintWithSeedGroup),
     TokenAccount(ConstraintTokenAccountGroup),
     Mint(ConstraintTokenMintGroup),
     Realloc(ConstraintReallocGroup),
@@ -662,12 +751,19 @@ pub enum ConstraintToken {
     Seeds(Context<ConstraintSeeds>),
     Executable(Context<ConstraintExecutable>),
     Close(Context<ConstraintClose>),
+    Reassign(Context<ConstraintReassign>),
+    NotBefore(Context<ConstraintClockBound>),
+    NotAfter(Context<ConstraintClockBound>),
+    AuthorityAnyOf(Context<ConstraintAuthorityAnyOf>),
     Payer(Context<ConstraintPayer>),
     Space(Context<ConstraintSpace>),
     Address(Context<ConstraintAddress>),
     TokenMint(Context<ConstraintTokenMint>),
     TokenAuthority(Context<ConstraintTokenAuthority>),
     TokenTokenProgram(Context<ConstraintTokenProgram>),
+    VaultMint(Context<ConstraintTokenMint>),
+    VaultAuthority(Context<ConstraintTokenAuthority>),
+    VaultTokenProgram(Context<ConstraintTokenProgram>),
     AssociatedTokenMint(Context<ConstraintTokenMint>),
     AssociatedTokenAuthority(Context<ConstraintTokenAuthority>),
     AssociatedTokenTokenProgram(Context<ConstraintTokenProgram>),
@@ -675,11 +771,16 @@ pub enum ConstraintToken {
     MintFreezeAuthority(Context<ConstraintMintFreezeAuthority>),
     MintDecimals(Context<ConstraintMintDecimals>),
     MintTokenProgram(Context<ConstraintTokenProgram>),
+    MintCloseAuthority(Context<ConstraintMintCloseAuthority>),
+    MintPermanentDelegate(Context<ConstraintMintPermanentDelegate>),
     Bump(Context<ConstraintTokenBump>),
     ProgramSeed(Context<ConstraintProgramSeed>),
     Realloc(Context<ConstraintRealloc>),
     ReallocPayer(Context<ConstraintReallocPayer>),
     ReallocZero(Context<ConstraintReallocZero>),
+    WithSeedBase(Context<ConstraintWithSeedBase>),
+    WithSeedSeed(Context<ConstraintWithSeedSeed>),
+    WithSeedOwner(Context<ConstraintWithSeedOwner>),
     // extensions
     ExtensionGroupPointerAuthority(Context<ConstraintExtensionAuthority>),
     ExtensionGroupPointerGroupAddress(Context<ConstraintExtensionGroupPointerGroupAddress>),
@@ -695,6 +796,8 @@ pub enum ConstraintToken {
     ExtensionTokenHookAuthority(Context<ConstraintExtensionAuthority>),
     ExtensionTokenHookProgramId(Context<ConstraintExtensionTokenHookProgramId>),
     ExtensionPermanentDelegate(Context<ConstraintExtensionPermanentDelegate>),
+    ExtensionGroupMemberGroup(Context<ConstraintExtensionGroupMemberGroup>),
+    ExtensionPresence(Context<ConstraintExtensionPresence>),
 }
 
 impl Parse for ConstraintToken {
@@ -706,6 +809,8 @@ impl Parse for ConstraintToken {
 #[derive(Debug, Clone)]
 pub struct ConstraintInit {
     pub if_needed: bool,
+    /// Created with `CreateIdempotent`, only for associated token accounts.
+    pub idempotent: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -741,6 +846,29 @@ pub struct ConstraintReallocZero {
     pub zero: Expr,
 }
 
+/// Address derived with `Pubkey::create_with_seed`.
+#[derive(Debug, Clone)]
+pub struct ConstraintWithSeedGroup {
+    pub base: Expr,
+    pub seed: Expr,
+    pub owner: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintWithSeedBase {
+    pub base: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintWithSeedSeed {
+    pub seed: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintWithSeedOwner {
+    pub owner: Expr,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintSigner {
     pub error: Option<Expr>,
@@ -774,12 +902,15 @@ pub struct ConstraintAddress {
 pub enum ConstraintRentExempt {
     Enforce,
     Skip,
+    TopUp { payer: Expr },
 }
 
 #[derive(Debug, Clone)]
 pub struct ConstraintInitGroup {
     pub if_needed: bool,
+    pub idempotent: bool,
     pub seeds: Option<ConstraintSeedsGroup>,
+    pub with_seed: Option<ConstraintWithSeedGroup>,
     pub payer: Expr,
     pub space: Option<Expr>,
     pub kind: InitKind,
@@ -842,6 +973,19 @@ pub struct ConstraintExtensionPermanentDelegate {
     pub permanent_delegate: Expr,
 }
 
+#[derive(Debug, Clone)]
+pub struct ConstraintExtensionGroupMemberGroup {
+    pub group: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintExtensionPresence {
+    /// `ExtensionType` variant of the extension.
+    pub extension: Ident,
+    /// Whether the extension must be present or absent.
+    pub present: bool,
+}
+
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum InitKind {
@@ -884,7 +1028,40 @@ pub enum InitKind {
 
 #[derive(Debug, Clone)]
 pub struct ConstraintClose {
-    pub sol_dest: Ident,
+    pub sol_dest: Expr,
+}
+
+impl ConstraintClose {
+    /// Returns the field receiving the lamports, or `None` if the receiver is
+    /// an expression evaluated at runtime.
+    pub fn sol_dest_field(&self) -> Option<&Ident> {
+        match &self.sol_dest {
+            Expr::Path(path) => path.path.get_ident(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintReassign {
+    pub new_owner: Expr,
+}
+
+/// A bound on the current time, compared against the `Clock` sysvar's unix
+/// timestamp, or its slot if `slot` is set.
+#[derive(Debug, Clone)]
+pub struct ConstraintClockBound {
+    pub bound: Expr,
+    pub slot: bool,
+    pub error: Option<Expr>,
+}
+
+/// A set of authorities of which the account must be one, and must have signed.
+/// Keys that are fields of the accounts struct are compared by their `key()`.
+#[derive(Debug, Clone)]
+pub struct ConstraintAuthorityAnyOf {
+    pub keys: Punctuated<Expr, Comma>,
+    pub error: Option<Expr>,
 }
 
 #[derive(Debug, Clone)]
@@ -929,7 +1106,8 @@ pub struct ConstraintMintGroupMemberPointerData {
 
 #[derive(Debug, Clone)]
 pub struct ConstraintMintCloseAuthority {
-    pub close_authority: Expr,
+    /// `None` if the mint must not have a close authority.
+    pub close_authority: Option<Expr>,
 }
 
 #[derive(Debug, Clone)]
@@ -957,6 +1135,12 @@ pub struct ConstraintMintDecimals {
     pub decimals: Expr,
 }
 
+#[derive(Debug, Clone)]
+pub struct ConstraintMintPermanentDelegate {
+    /// `None` if the mint must not have a permanent delegate.
+    pub permanent_delegate: Option<Expr>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintTokenBump {
     pub bump: Option<Expr>,
@@ -979,8 +1163,14 @@ pub struct ConstraintTokenAccountGroup {
     pub mint: Option<Expr>,
     pub authority: Option<Expr>,
     pub token_program: Option<Expr>,
+    /// Specified with the `vault` constraints, which additionally check that
+    /// only the authority can move the tokens.
+    pub vault: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct ConstraintVault {}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintTokenMintGroup {
     pub decimals: Option<Expr>,
@@ -997,6 +1187,9 @@ pub struct ConstraintTokenMintGroup {
     pub permanent_delegate: Option<Expr>,
     pub transfer_hook_authority: Option<Expr>,
     pub transfer_hook_program_id: Option<Expr>,
+    pub group_member_group: Option<Expr>,
+    pub mint_close_authority: Option<ConstraintMintCloseAuthority>,
+    pub mint_permanent_delegate: Option<ConstraintMintPermanentDelegate>,
 }
 
 // Syntaxt context object for preserving metadata about the inner item.
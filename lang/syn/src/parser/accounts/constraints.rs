@@ -1,4 +1,5 @@
 use crate::*;
+use heck::CamelCase;
 use syn::parse::{Error as ParseError, Result as ParseResult};
 use syn::{bracketed, Token};
 
@@ -28,11 +29,24 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
     let c = match kw.as_str() {
         "init" => ConstraintToken::Init(Context::new(
             ident.span(),
-            ConstraintInit { if_needed: false },
+            ConstraintInit {
+                if_needed: false,
+                idempotent: false,
+            },
         )),
         "init_if_needed" => ConstraintToken::Init(Context::new(
             ident.span(),
-            ConstraintInit { if_needed: true },
+            ConstraintInit {
+                if_needed: true,
+                idempotent: false,
+            },
+        )),
+        "init_idempotent" => ConstraintToken::Init(Context::new(
+            ident.span(),
+            ConstraintInit {
+                if_needed: true,
+                idempotent: true,
+            },
         )),
         "zero" => ConstraintToken::Zeroed(Context::new(ident.span(), ConstraintZeroed {})),
         "mut" => ConstraintToken::Mut(Context::new(
@@ -86,13 +100,55 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                         token_program: stream.parse()?,
                     },
                 )),
+                "close_authority" => ConstraintToken::MintCloseAuthority(Context::new(
+                    span,
+                    ConstraintMintCloseAuthority {
+                        close_authority: parse_key_or_none(&stream)?,
+                    },
+                )),
+                "permanent_delegate" => ConstraintToken::MintPermanentDelegate(Context::new(
+                    span,
+                    ConstraintMintPermanentDelegate {
+                        permanent_delegate: parse_key_or_none(&stream)?,
+                    },
+                )),
                 _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
             }
         }
         "extensions" => {
             stream.parse::<Token![:]>()?;
             stream.parse::<Token![:]>()?;
-            let kw = stream.call(Ident::parse_any)?.to_string();
+            let ext = stream.call(Ident::parse_any)?;
+            let kw = ext.to_string();
+
+            // `extensions::<extension>` and `extensions::<extension> = none`
+            // require or forbid the extension without checking its data.
+            if !stream.peek(Token![:]) {
+                let present = if stream.peek(Token![=]) {
+                    stream.parse::<Token![=]>()?;
+                    let none = stream.call(Ident::parse_any)?;
+                    if none != "none" {
+                        return Err(ParseError::new(
+                            none.span(),
+                            "expected `none` to forbid the extension",
+                        ));
+                    }
+                    false
+                } else {
+                    true
+                };
+                let span = ident
+                    .span()
+                    .join(stream.span())
+                    .unwrap_or_else(|| ident.span());
+                return Ok(ConstraintToken::ExtensionPresence(Context::new(
+                    span,
+                    ConstraintExtensionPresence {
+                        extension: extension_type(&ext),
+                        present,
+                    },
+                )));
+            }
 
             match kw.as_str() {
                 "group_pointer" => {
@@ -157,6 +213,27 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                         _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
                     }
                 }
+                "group_member" => {
+                    stream.parse::<Token![:]>()?;
+                    stream.parse::<Token![:]>()?;
+                    let kw = stream.call(Ident::parse_any)?.to_string();
+                    stream.parse::<Token![=]>()?;
+
+                    let span = ident
+                        .span()
+                        .join(stream.span())
+                        .unwrap_or_else(|| ident.span());
+
+                    match kw.as_str() {
+                        "group" => ConstraintToken::ExtensionGroupMemberGroup(Context::new(
+                            span,
+                            ConstraintExtensionGroupMemberGroup {
+                                group: stream.parse()?,
+                            },
+                        )),
+                        _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
+                    }
+                }
                 "metadata_pointer" => {
                     stream.parse::<Token![:]>()?;
                     stream.parse::<Token![:]>()?;
@@ -293,6 +370,72 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                 _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
             }
         }
+        "with_seed" => {
+            stream.parse::<Token![:]>()?;
+            stream.parse::<Token![:]>()?;
+            let kw = stream.call(Ident::parse_any)?.to_string();
+            stream.parse::<Token![=]>()?;
+
+            let span = ident
+                .span()
+                .join(stream.span())
+                .unwrap_or_else(|| ident.span());
+
+            match kw.as_str() {
+                "base" => ConstraintToken::WithSeedBase(Context::new(
+                    span,
+                    ConstraintWithSeedBase {
+                        base: stream.parse()?,
+                    },
+                )),
+                "seed" => ConstraintToken::WithSeedSeed(Context::new(
+                    span,
+                    ConstraintWithSeedSeed {
+                        seed: stream.parse()?,
+                    },
+                )),
+                "owner" => ConstraintToken::WithSeedOwner(Context::new(
+                    span,
+                    ConstraintWithSeedOwner {
+                        owner: stream.parse()?,
+                    },
+                )),
+                _ => return Err(ParseError::new(ident.span(), "Invalid attribute. with_seed::base, with_seed::seed and with_seed::owner are the only valid attributes")),
+            }
+        }
+        "vault" => {
+            stream.parse::<Token![:]>()?;
+            stream.parse::<Token![:]>()?;
+            let kw = stream.call(Ident::parse_any)?.to_string();
+            stream.parse::<Token![=]>()?;
+
+            let span = ident
+                .span()
+                .join(stream.span())
+                .unwrap_or_else(|| ident.span());
+
+            match kw.as_str() {
+                "mint" => ConstraintToken::VaultMint(Context::new(
+                    span,
+                    ConstraintTokenMint {
+                        mint: stream.parse()?,
+                    },
+                )),
+                "authority" => ConstraintToken::VaultAuthority(Context::new(
+                    span,
+                    ConstraintTokenAuthority {
+                        auth: stream.parse()?,
+                    },
+                )),
+                "token_program" => ConstraintToken::VaultTokenProgram(Context::new(
+                    span,
+                    ConstraintTokenProgram {
+                        token_program: stream.parse()?,
+                    },
+                )),
+                _ => return Err(ParseError::new(ident.span(), "Invalid attribute")),
+            }
+        }
         "associated_token" => {
             stream.parse::<Token![:]>()?;
             stream.parse::<Token![:]>()?;
@@ -441,10 +584,17 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                     match stream.parse::<Ident>()?.to_string().as_str() {
                         "skip" => ConstraintRentExempt::Skip,
                         "enforce" => ConstraintRentExempt::Enforce,
+                        "top_up" => {
+                            let payer;
+                            syn::parenthesized!(payer in stream);
+                            ConstraintRentExempt::TopUp {
+                                payer: payer.parse()?,
+                            }
+                        }
                         _ => {
                             return Err(ParseError::new(
                                 span,
-                                "rent_exempt must be either skip or enforce",
+                                "rent_exempt must be either skip, enforce or top_up(<payer>)",
                             ))
                         }
                     },
@@ -474,6 +624,39 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                         sol_dest: stream.parse()?,
                     },
                 )),
+                "not_before" | "not_before_slot" => ConstraintToken::NotBefore(Context::new(
+                    span,
+                    ConstraintClockBound {
+                        bound: stream.parse()?,
+                        slot: kw == "not_before_slot",
+                        error: parse_optional_custom_error(&stream)?,
+                    },
+                )),
+                "not_after" | "not_after_slot" => ConstraintToken::NotAfter(Context::new(
+                    span,
+                    ConstraintClockBound {
+                        bound: stream.parse()?,
+                        slot: kw == "not_after_slot",
+                        error: parse_optional_custom_error(&stream)?,
+                    },
+                )),
+                "authority_any_of" => {
+                    let keys;
+                    bracketed!(keys in stream);
+                    ConstraintToken::AuthorityAnyOf(Context::new(
+                        span,
+                        ConstraintAuthorityAnyOf {
+                            keys: keys.parse_terminated(Expr::parse)?,
+                            error: parse_optional_custom_error(&stream)?,
+                        },
+                    ))
+                }
+                "reassign" => ConstraintToken::Reassign(Context::new(
+                    span,
+                    ConstraintReassign {
+                        new_owner: stream.parse()?,
+                    },
+                )),
                 "address" => ConstraintToken::Address(Context::new(
                     span,
                     ConstraintAddress {
@@ -498,6 +681,30 @@ fn parse_optional_custom_error(stream: &ParseStream) -> ParseResult<Option<Expr>
     }
 }
 
+/// Parses either a key expression or `none`, returning `None` for the latter.
+fn parse_key_or_none(stream: &ParseStream) -> ParseResult<Option<Expr>> {
+    let expr: Expr = stream.parse()?;
+    match &expr {
+        Expr::Path(path) if path.path.is_ident("none") => Ok(None),
+        _ => Ok(Some(expr)),
+    }
+}
+
+/// Maps the snake case name used in `extensions::<extension>` to the
+/// corresponding `ExtensionType` variant.
+fn extension_type(ext: &Ident) -> Ident {
+    let name = match ext.to_string().as_str() {
+        "close_authority" => "MintCloseAuthority".to_owned(),
+        "transfer_fee" => "TransferFeeConfig".to_owned(),
+        "interest_bearing" => "InterestBearingConfig".to_owned(),
+        "metadata" => "TokenMetadata".to_owned(),
+        "group" => "TokenGroup".to_owned(),
+        "group_member" => "TokenGroupMember".to_owned(),
+        name => name.to_camel_case(),
+    };
+    Ident::new(&name, ext.span())
+}
+
 #[derive(Default)]
 pub struct ConstraintGroupBuilder<'ty> {
     pub f_ty: Option<&'ty Ty>,
@@ -514,10 +721,17 @@ pub struct ConstraintGroupBuilder<'ty> {
     pub payer: Option<Context<ConstraintPayer>>,
     pub space: Option<Context<ConstraintSpace>>,
     pub close: Option<Context<ConstraintClose>>,
+    pub reassign: Option<Context<ConstraintReassign>>,
+    pub not_before: Option<Context<ConstraintClockBound>>,
+    pub not_after: Option<Context<ConstraintClockBound>>,
+    pub not_before_slot: Option<Context<ConstraintClockBound>>,
+    pub not_after_slot: Option<Context<ConstraintClockBound>>,
+    pub authority_any_of: Option<Context<ConstraintAuthorityAnyOf>>,
     pub address: Option<Context<ConstraintAddress>>,
     pub token_mint: Option<Context<ConstraintTokenMint>>,
     pub token_authority: Option<Context<ConstraintTokenAuthority>>,
     pub token_token_program: Option<Context<ConstraintTokenProgram>>,
+    pub vault: Option<Context<ConstraintVault>>,
     pub associated_token_mint: Option<Context<ConstraintTokenMint>>,
     pub associated_token_authority: Option<Context<ConstraintTokenAuthority>>,
     pub associated_token_token_program: Option<Context<ConstraintTokenProgram>>,
@@ -525,6 +739,8 @@ pub struct ConstraintGroupBuilder<'ty> {
     pub mint_freeze_authority: Option<Context<ConstraintMintFreezeAuthority>>,
     pub mint_decimals: Option<Context<ConstraintMintDecimals>>,
     pub mint_token_program: Option<Context<ConstraintTokenProgram>>,
+    pub mint_close_authority: Option<Context<ConstraintMintCloseAuthority>>,
+    pub mint_permanent_delegate: Option<Context<ConstraintMintPermanentDelegate>>,
     pub extension_group_pointer_authority: Option<Context<ConstraintExtensionAuthority>>,
     pub extension_group_pointer_group_address:
         Option<Context<ConstraintExtensionGroupPointerGroupAddress>>,
@@ -538,11 +754,16 @@ pub struct ConstraintGroupBuilder<'ty> {
     pub extension_transfer_hook_authority: Option<Context<ConstraintExtensionAuthority>>,
     pub extension_transfer_hook_program_id: Option<Context<ConstraintExtensionTokenHookProgramId>>,
     pub extension_permanent_delegate: Option<Context<ConstraintExtensionPermanentDelegate>>,
+    pub extension_group_member_group: Option<Context<ConstraintExtensionGroupMemberGroup>>,
+    pub extensions: Vec<Context<ConstraintExtensionPresence>>,
     pub bump: Option<Context<ConstraintTokenBump>>,
     pub program_seed: Option<Context<ConstraintProgramSeed>>,
     pub realloc: Option<Context<ConstraintRealloc>>,
     pub realloc_payer: Option<Context<ConstraintReallocPayer>>,
     pub realloc_zero: Option<Context<ConstraintReallocZero>>,
+    pub with_seed_base: Option<Context<ConstraintWithSeedBase>>,
+    pub with_seed_seed: Option<Context<ConstraintWithSeedSeed>>,
+    pub with_seed_owner: Option<Context<ConstraintWithSeedOwner>>,
 }
 
 impl<'ty> ConstraintGroupBuilder<'ty> {
@@ -562,10 +783,17 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             payer: None,
             space: None,
             close: None,
+            reassign: None,
+            not_before: None,
+            not_after: None,
+            not_before_slot: None,
+            not_after_slot: None,
+            authority_any_of: None,
             address: None,
             token_mint: None,
             token_authority: None,
             token_token_program: None,
+            vault: None,
             associated_token_mint: None,
             associated_token_authority: None,
             associated_token_token_program: None,
@@ -573,6 +801,8 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             mint_freeze_authority: None,
             mint_decimals: None,
             mint_token_program: None,
+            mint_close_authority: None,
+            mint_permanent_delegate: None,
             extension_group_pointer_authority: None,
             extension_group_pointer_group_address: None,
             extension_group_member_pointer_authority: None,
@@ -583,18 +813,31 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             extension_transfer_hook_authority: None,
             extension_transfer_hook_program_id: None,
             extension_permanent_delegate: None,
+            extension_group_member_group: None,
+            extensions: Vec::new(),
             bump: None,
             program_seed: None,
             realloc: None,
             realloc_payer: None,
             realloc_zero: None,
+            with_seed_base: None,
+            with_seed_seed: None,
+            with_seed_owner: None,
         }
     }
 
     pub fn build(mut self) -> ParseResult<ConstraintGroup> {
         // Init.
         if let Some(i) = &self.init {
-            if cfg!(not(feature = "init-if-needed")) && i.if_needed {
+            // Associated token accounts can't be re-initialized, so creating
+            // them idempotently doesn't need to be gated behind the feature.
+            if i.idempotent && self.associated_token_mint.is_none() {
+                return Err(ParseError::new(
+                    i.span(),
+                    "init_idempotent can only be used with associated token accounts",
+                ));
+            }
+            if cfg!(not(feature = "init-if-needed")) && i.if_needed && !i.idempotent {
                 return Err(ParseError::new(
                     i.span(),
                     "init_if_needed requires that anchor-lang be imported \
@@ -627,10 +870,57 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
                     "payer must be provided when initializing an account",
                 ));
             }
+            // Members are added through the group's update authority, which
+            // isn't available when initializing the mint.
+            if let Some(g) = &self.extension_group_member_group {
+                return Err(ParseError::new(
+                    g.span(),
+                    "extensions::group_member::group cannot be used with init",
+                ));
+            }
+            // The extensions are configured with `extensions::close_authority`
+            // and `extensions::permanent_delegate` when initializing.
+            if let Some(c) = &self.mint_close_authority {
+                return Err(ParseError::new(
+                    c.span(),
+                    "mint::close_authority cannot be used with init, use extensions::close_authority::authority instead",
+                ));
+            }
+            if let Some(c) = &self.mint_permanent_delegate {
+                return Err(ParseError::new(
+                    c.span(),
+                    "mint::permanent_delegate cannot be used with init, use extensions::permanent_delegate::delegate instead",
+                ));
+            }
+            // Addresses derived with a seed are created by the base account,
+            // which can't be done by the token or associated token programs.
+            if let Some(b) = &self.with_seed_base {
+                if self.token_mint.is_some()
+                    || self.associated_token_mint.is_some()
+                    || self.mint_decimals.is_some()
+                {
+                    return Err(ParseError::new(
+                        b.span(),
+                        "with_seed cannot be used to initialize token accounts or mints",
+                    ));
+                }
+            }
+            // The address must be derived with the owner the account is
+            // created for, which is given with `owner`.
+            if let Some(o) = &self.with_seed_owner {
+                return Err(ParseError::new(
+                    o.span(),
+                    "with_seed::owner cannot be used with init, the address is derived with the owner given with owner, or the executing program",
+                ));
+            }
             // When initializing a non-PDA account, the account being
             // initialized must sign to invoke the system program's create
-            // account instruction.
-            if self.signer.is_none() && self.seeds.is_none() && self.associated_token_mint.is_none()
+            // account instruction. Accounts derived with a seed are signed for
+            // by their base instead.
+            if self.signer.is_none()
+                && self.seeds.is_none()
+                && self.associated_token_mint.is_none()
+                && self.with_seed_base.is_none()
             {
                 self.signer
                     .replace(Context::new(i.span(), ConstraintSigner { error: None }));
@@ -683,6 +973,16 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             }
         }
 
+        // Vault.
+        if let Some(v) = &self.vault {
+            if self.token_authority.is_none() {
+                return Err(ParseError::new(
+                    v.span(),
+                    "vault::authority must be provided to specify a vault",
+                ));
+            }
+        }
+
         // Realloc.
         if let Some(r) = &self.realloc {
             if self.realloc_payer.is_none() {
@@ -699,6 +999,38 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             }
         }
 
+        // With seed.
+        match (&self.with_seed_base, &self.with_seed_seed) {
+            (Some(_), Some(_)) => {
+                if let Some(s) = &self.seeds {
+                    return Err(ParseError::new(
+                        s.span(),
+                        "seeds cannot be used with the with_seed constraint",
+                    ));
+                }
+            }
+            (Some(b), None) => {
+                return Err(ParseError::new(
+                    b.span(),
+                    "with_seed::seed must be provided with with_seed::base",
+                ))
+            }
+            (None, Some(s)) => {
+                return Err(ParseError::new(
+                    s.span(),
+                    "with_seed::base must be provided with with_seed::seed",
+                ))
+            }
+            (None, None) => {
+                if let Some(o) = &self.with_seed_owner {
+                    return Err(ParseError::new(
+                        o.span(),
+                        "with_seed::base and with_seed::seed must be provided with with_seed::owner",
+                    ));
+                }
+            }
+        }
+
         // Zero.
         if let Some(z) = &self.zeroed {
             match self.mutable {
@@ -774,10 +1106,17 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             payer,
             space,
             close,
+            reassign,
+            not_before,
+            not_after,
+            not_before_slot,
+            not_after_slot,
+            authority_any_of,
             address,
             token_mint,
             token_authority,
             token_token_program,
+            vault,
             associated_token_mint,
             associated_token_authority,
             associated_token_token_program,
@@ -785,6 +1124,8 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             mint_freeze_authority,
             mint_decimals,
             mint_token_program,
+            mint_close_authority,
+            mint_permanent_delegate,
             extension_group_pointer_authority,
             extension_group_pointer_group_address,
             extension_group_member_pointer_authority,
@@ -795,11 +1136,16 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             extension_transfer_hook_authority,
             extension_transfer_hook_program_id,
             extension_permanent_delegate,
+            extension_group_member_group,
+            extensions,
             bump,
             program_seed,
             realloc,
             realloc_payer,
             realloc_zero,
+            with_seed_base,
+            with_seed_seed,
+            with_seed_owner,
         } = self;
 
         // Converts Option<Context<T>> -> Option<T>.
@@ -827,6 +1173,18 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
                 .expect("bump must be provided with seeds"),
             program_seed: into_inner!(program_seed).map(|id| id.program_seed),
         });
+        // The derived address defaults to the account's owner, which is the
+        // executing program if not given.
+        let with_seed = match (with_seed_base, with_seed_seed) {
+            (Some(base), Some(seed)) => Some(ConstraintWithSeedGroup {
+                base: base.into_inner().base,
+                seed: seed.into_inner().seed,
+                owner: into_inner!(with_seed_owner)
+                    .map(|o| o.owner)
+                    .or_else(|| owner.as_ref().map(|o| o.owner_address.clone())),
+            }),
+            _ => None,
+        };
         let associated_token = match (
             associated_token_mint,
             associated_token_authority,
@@ -876,6 +1234,7 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
                 token_program: token_token_program
                     .as_ref()
                     .map(|a| a.clone().into_inner().token_program),
+                vault: vault.is_some(),
             }),
         };
 
@@ -894,6 +1253,9 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             &extension_transfer_hook_authority,
             &extension_transfer_hook_program_id,
             &extension_permanent_delegate,
+            &extension_group_member_group,
+            &mint_close_authority,
+            &mint_permanent_delegate,
         ) {
             (
                 None,
@@ -910,6 +1272,9 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
                 None,
                 None,
                 None,
+                None,
+                None,
+                None,
             ) => None,
             _ => Some(ConstraintTokenMintGroup {
                 decimals: mint_decimals
@@ -955,13 +1320,24 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
                 transfer_hook_program_id: extension_transfer_hook_program_id
                     .as_ref()
                     .map(|a| a.clone().into_inner().program_id),
+                group_member_group: extension_group_member_group
+                    .as_ref()
+                    .map(|a| a.clone().into_inner().group),
+                mint_close_authority: mint_close_authority
+                    .as_ref()
+                    .map(|a| a.clone().into_inner()),
+                mint_permanent_delegate: mint_permanent_delegate
+                    .as_ref()
+                    .map(|a| a.clone().into_inner()),
             }),
         };
 
         Ok(ConstraintGroup {
             init: init.as_ref().map(|i| Ok(ConstraintInitGroup {
                 if_needed: i.if_needed,
+                idempotent: i.idempotent,
                 seeds: seeds.clone(),
+                with_seed: with_seed.clone(),
                 payer: into_inner!(payer.clone()).unwrap().target,
                 space: space.clone().map(|s| s.space.clone()),
                 kind: if let Some(tm) = &token_mint {
@@ -1026,7 +1402,15 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             rent_exempt: into_inner!(rent_exempt),
             executable: into_inner!(executable),
             close: into_inner!(close),
+            reassign: into_inner!(reassign),
+            not_before: into_inner!(not_before),
+            not_after: into_inner!(not_after),
+            not_before_slot: into_inner!(not_before_slot),
+            not_after_slot: into_inner!(not_after_slot),
+            authority_any_of: into_inner!(authority_any_of),
+            extensions: into_inner_vec!(extensions),
             address: into_inner!(address),
+            with_seed,
             associated_token: if !is_init { associated_token } else { None },
             seeds,
             token_account: if !is_init {token_account} else {None},
@@ -1049,10 +1433,38 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             ConstraintToken::Payer(c) => self.add_payer(c),
             ConstraintToken::Space(c) => self.add_space(c),
             ConstraintToken::Close(c) => self.add_close(c),
+            ConstraintToken::Reassign(c) => self.add_reassign(c),
+            ConstraintToken::NotBefore(c) => self.add_not_before(c),
+            ConstraintToken::NotAfter(c) => self.add_not_after(c),
+            ConstraintToken::AuthorityAnyOf(c) => self.add_authority_any_of(c),
             ConstraintToken::Address(c) => self.add_address(c),
-            ConstraintToken::TokenAuthority(c) => self.add_token_authority(c),
-            ConstraintToken::TokenMint(c) => self.add_token_mint(c),
-            ConstraintToken::TokenTokenProgram(c) => self.add_token_token_program(c),
+            ConstraintToken::TokenAuthority(c) => {
+                self.check_token_or_vault(c.span(), false)?;
+                self.add_token_authority(c)
+            }
+            ConstraintToken::TokenMint(c) => {
+                self.check_token_or_vault(c.span(), false)?;
+                self.add_token_mint(c)
+            }
+            ConstraintToken::TokenTokenProgram(c) => {
+                self.check_token_or_vault(c.span(), false)?;
+                self.add_token_token_program(c)
+            }
+            ConstraintToken::VaultMint(c) => {
+                self.check_token_or_vault(c.span(), true)?;
+                self.add_vault(c.span())?;
+                self.add_token_mint(c)
+            }
+            ConstraintToken::VaultAuthority(c) => {
+                self.check_token_or_vault(c.span(), true)?;
+                self.add_vault(c.span())?;
+                self.add_token_authority(c)
+            }
+            ConstraintToken::VaultTokenProgram(c) => {
+                self.check_token_or_vault(c.span(), true)?;
+                self.add_vault(c.span())?;
+                self.add_token_token_program(c)
+            }
             ConstraintToken::AssociatedTokenAuthority(c) => self.add_associated_token_authority(c),
             ConstraintToken::AssociatedTokenMint(c) => self.add_associated_token_mint(c),
             ConstraintToken::AssociatedTokenTokenProgram(c) => {
@@ -1062,11 +1474,16 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             ConstraintToken::MintFreezeAuthority(c) => self.add_mint_freeze_authority(c),
             ConstraintToken::MintDecimals(c) => self.add_mint_decimals(c),
             ConstraintToken::MintTokenProgram(c) => self.add_mint_token_program(c),
+            ConstraintToken::MintCloseAuthority(c) => self.add_mint_close_authority(c),
+            ConstraintToken::MintPermanentDelegate(c) => self.add_mint_permanent_delegate(c),
             ConstraintToken::Bump(c) => self.add_bump(c),
             ConstraintToken::ProgramSeed(c) => self.add_program_seed(c),
             ConstraintToken::Realloc(c) => self.add_realloc(c),
             ConstraintToken::ReallocPayer(c) => self.add_realloc_payer(c),
             ConstraintToken::ReallocZero(c) => self.add_realloc_zero(c),
+            ConstraintToken::WithSeedBase(c) => self.add_with_seed_base(c),
+            ConstraintToken::WithSeedSeed(c) => self.add_with_seed_seed(c),
+            ConstraintToken::WithSeedOwner(c) => self.add_with_seed_owner(c),
             ConstraintToken::ExtensionGroupPointerAuthority(c) => {
                 self.add_extension_group_pointer_authority(c)
             }
@@ -1093,6 +1510,10 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             ConstraintToken::ExtensionPermanentDelegate(c) => {
                 self.add_extension_permanent_delegate(c)
             }
+            ConstraintToken::ExtensionGroupMemberGroup(c) => {
+                self.add_extension_group_member_group(c)
+            }
+            ConstraintToken::ExtensionPresence(c) => self.add_extension_presence(c),
         }
     }
 
@@ -1181,10 +1602,11 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
     fn add_realloc(&mut self, c: Context<ConstraintRealloc>) -> ParseResult<()> {
         if !matches!(self.f_ty, Some(Ty::Account(_)))
             && !matches!(self.f_ty, Some(Ty::AccountLoader(_)))
+            && !matches!(self.f_ty, Some(Ty::AccountWithTail(_)))
         {
             return Err(ParseError::new(
                 c.span(),
-                "realloc must be on an Account or AccountLoader",
+                "realloc must be on an Account, AccountLoader or AccountWithTail",
             ));
         }
         if self.mutable.is_none() {
@@ -1228,13 +1650,47 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    fn add_with_seed_base(&mut self, c: Context<ConstraintWithSeedBase>) -> ParseResult<()> {
+        if self.with_seed_base.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "with_seed::base already provided",
+            ));
+        }
+        self.with_seed_base.replace(c);
+        Ok(())
+    }
+
+    fn add_with_seed_seed(&mut self, c: Context<ConstraintWithSeedSeed>) -> ParseResult<()> {
+        if self.with_seed_seed.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "with_seed::seed already provided",
+            ));
+        }
+        self.with_seed_seed.replace(c);
+        Ok(())
+    }
+
+    fn add_with_seed_owner(&mut self, c: Context<ConstraintWithSeedOwner>) -> ParseResult<()> {
+        if self.with_seed_owner.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "with_seed::owner already provided",
+            ));
+        }
+        self.with_seed_owner.replace(c);
+        Ok(())
+    }
+
     fn add_close(&mut self, c: Context<ConstraintClose>) -> ParseResult<()> {
         if !matches!(self.f_ty, Some(Ty::Account(_)))
             && !matches!(self.f_ty, Some(Ty::AccountLoader(_)))
+            && !matches!(self.f_ty, Some(Ty::AccountWithTail(_)))
         {
             return Err(ParseError::new(
                 c.span(),
-                "close must be on an Account, AccountLoader",
+                "close must be on an Account, AccountLoader, AccountWithTail",
             ));
         }
         if self.mutable.is_none() {
@@ -1243,6 +1699,12 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
                 "mut must be provided before close",
             ));
         }
+        if self.reassign.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "close cannot be used with reassign",
+            ));
+        }
         if self.close.is_some() {
             return Err(ParseError::new(c.span(), "close already provided"));
         }
@@ -1250,6 +1712,35 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    fn add_reassign(&mut self, c: Context<ConstraintReassign>) -> ParseResult<()> {
+        if !matches!(self.f_ty, Some(Ty::Account(_)))
+            && !matches!(self.f_ty, Some(Ty::AccountLoader(_)))
+            && !matches!(self.f_ty, Some(Ty::AccountWithTail(_)))
+        {
+            return Err(ParseError::new(
+                c.span(),
+                "reassign must be on an Account, AccountLoader, AccountWithTail",
+            ));
+        }
+        if self.mutable.is_none() {
+            return Err(ParseError::new(
+                c.span(),
+                "mut must be provided before reassign",
+            ));
+        }
+        if self.close.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "reassign cannot be used with close",
+            ));
+        }
+        if self.reassign.is_some() {
+            return Err(ParseError::new(c.span(), "reassign already provided"));
+        }
+        self.reassign.replace(c);
+        Ok(())
+    }
+
     fn add_address(&mut self, c: Context<ConstraintAddress>) -> ParseResult<()> {
         if self.address.is_some() {
             return Err(ParseError::new(c.span(), "address already provided"));
@@ -1258,6 +1749,30 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    // The `vault` constraints extend the `token` constraints, so both can't
+    // be set on the same account.
+    fn check_token_or_vault(&self, span: proc_macro2::Span, vault: bool) -> ParseResult<()> {
+        let has_token_constraints = self.token_mint.is_some()
+            || self.token_authority.is_some()
+            || self.token_token_program.is_some();
+        if has_token_constraints && self.vault.is_some() != vault {
+            return Err(ParseError::new(
+                span,
+                "vault and token constraints can't be used on the same account",
+            ));
+        }
+        Ok(())
+    }
+
+    // The `vault` constraints are the `token` constraints with additional
+    // checks, so this only marks the token account as a vault.
+    fn add_vault(&mut self, span: proc_macro2::Span) -> ParseResult<()> {
+        if self.vault.is_none() {
+            self.vault.replace(Context::new(span, ConstraintVault {}));
+        }
+        Ok(())
+    }
+
     fn add_token_mint(&mut self, c: Context<ConstraintTokenMint>) -> ParseResult<()> {
         if self.token_mint.is_some() {
             return Err(ParseError::new(c.span(), "token mint already provided"));
@@ -1424,6 +1939,34 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    fn add_mint_close_authority(
+        &mut self,
+        c: Context<ConstraintMintCloseAuthority>,
+    ) -> ParseResult<()> {
+        if self.mint_close_authority.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "mint close_authority already provided",
+            ));
+        }
+        self.mint_close_authority.replace(c);
+        Ok(())
+    }
+
+    fn add_mint_permanent_delegate(
+        &mut self,
+        c: Context<ConstraintMintPermanentDelegate>,
+    ) -> ParseResult<()> {
+        if self.mint_permanent_delegate.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "mint permanent_delegate already provided",
+            ));
+        }
+        self.mint_permanent_delegate.replace(c);
+        Ok(())
+    }
+
     fn add_mut(&mut self, c: Context<ConstraintMut>) -> ParseResult<()> {
         if self.mutable.is_some() {
             return Err(ParseError::new(c.span(), "mut already provided"));
@@ -1454,6 +1997,24 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    fn add_extension_presence(
+        &mut self,
+        c: Context<ConstraintExtensionPresence>,
+    ) -> ParseResult<()> {
+        if self
+            .extensions
+            .iter()
+            .any(|item| item.extension == c.extension)
+        {
+            return Err(ParseError::new(
+                c.span(),
+                "extension constraint already provided",
+            ));
+        }
+        self.extensions.push(c);
+        Ok(())
+    }
+
     fn add_raw(&mut self, c: Context<ConstraintRaw>) -> ParseResult<()> {
         self.raw.push(c);
         Ok(())
@@ -1471,6 +2032,12 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         if self.rent_exempt.is_some() {
             return Err(ParseError::new(c.span(), "rent already provided"));
         }
+        if matches!(c.inner, ConstraintRentExempt::TopUp { .. }) && self.mutable.is_none() {
+            return Err(ParseError::new(
+                c.span(),
+                "mut must be provided before rent_exempt = top_up",
+            ));
+        }
         self.rent_exempt.replace(c);
         Ok(())
     }
@@ -1491,6 +2058,55 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    fn add_not_before(&mut self, c: Context<ConstraintClockBound>) -> ParseResult<()> {
+        let (bound, name) = if c.slot {
+            (&mut self.not_before_slot, "not_before_slot")
+        } else {
+            (&mut self.not_before, "not_before")
+        };
+        if bound.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                format!("{name} already provided"),
+            ));
+        }
+        bound.replace(c);
+        Ok(())
+    }
+
+    fn add_not_after(&mut self, c: Context<ConstraintClockBound>) -> ParseResult<()> {
+        let (bound, name) = if c.slot {
+            (&mut self.not_after_slot, "not_after_slot")
+        } else {
+            (&mut self.not_after, "not_after")
+        };
+        if bound.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                format!("{name} already provided"),
+            ));
+        }
+        bound.replace(c);
+        Ok(())
+    }
+
+    fn add_authority_any_of(&mut self, c: Context<ConstraintAuthorityAnyOf>) -> ParseResult<()> {
+        if self.authority_any_of.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "authority_any_of already provided",
+            ));
+        }
+        if c.keys.is_empty() {
+            return Err(ParseError::new(
+                c.span(),
+                "authority_any_of requires at least one key",
+            ));
+        }
+        self.authority_any_of.replace(c);
+        Ok(())
+    }
+
     fn add_payer(&mut self, c: Context<ConstraintPayer>) -> ParseResult<()> {
         if self.init.is_none() {
             return Err(ParseError::new(
@@ -1661,4 +2277,18 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         self.extension_permanent_delegate.replace(c);
         Ok(())
     }
+
+    fn add_extension_group_member_group(
+        &mut self,
+        c: Context<ConstraintExtensionGroupMemberGroup>,
+    ) -> ParseResult<()> {
+        if self.extension_group_member_group.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "extension group member group already provided",
+            ));
+        }
+        self.extension_group_member_group.replace(c);
+        Ok(())
+    }
 }
@@ -19,6 +19,18 @@ pub fn parse(accounts_struct: &syn::ItemStruct) -> ParseResult<AccountsStruct> {
         .map(|ix_attr| ix_attr.parse_args_with(Punctuated::<Expr, Comma>::parse_terminated))
         .transpose()?;
 
+    let (box_threshold, signer_seeds) = accounts_struct
+        .attrs
+        .iter()
+        .find(|a| {
+            a.path
+                .get_ident()
+                .map_or(false, |ident| ident == "accounts")
+        })
+        .map(parse_accounts_attr)
+        .transpose()?
+        .unwrap_or_default();
+
     #[cfg(feature = "event-cpi")]
     let accounts_struct = {
         let is_event_cpi = accounts_struct
@@ -51,11 +63,48 @@ pub fn parse(accounts_struct: &syn::ItemStruct) -> ParseResult<AccountsStruct> {
 
     constraints_cross_checks(&fields)?;
 
-    Ok(AccountsStruct::new(
-        accounts_struct,
-        fields,
-        instruction_api,
-    ))
+    let mut accounts = AccountsStruct::new(accounts_struct, fields, instruction_api);
+    accounts.box_threshold = box_threshold;
+    accounts.signer_seeds = signer_seeds;
+    Ok(accounts)
+}
+
+/// Parses `#[accounts(box_threshold = <bytes>, signer_seeds)]`, both being
+/// optional.
+fn parse_accounts_attr(attr: &syn::Attribute) -> ParseResult<(Option<LitInt>, bool)> {
+    let mut box_threshold = None;
+    let mut signer_seeds = false;
+    let metas = attr.parse_args_with(Punctuated::<syn::NestedMeta, Comma>::parse_terminated)?;
+    for meta in metas {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(meta))
+                if meta.path.is_ident("box_threshold") =>
+            {
+                match meta.lit {
+                    syn::Lit::Int(lit) => {
+                        lit.base10_parse::<usize>()?;
+                        box_threshold = Some(lit);
+                    }
+                    lit => {
+                        return Err(ParseError::new_spanned(
+                            lit,
+                            "box_threshold must be an integer",
+                        ))
+                    }
+                }
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("signer_seeds") => {
+                signer_seeds = true;
+            }
+            meta => {
+                return Err(ParseError::new_spanned(
+                    meta,
+                    "expected `box_threshold = <bytes>` or `signer_seeds`",
+                ))
+            }
+        }
+    }
+    Ok((box_threshold, signer_seeds))
 }
 
 fn constraints_cross_checks(fields: &[AccountField]) -> ParseResult<()> {
@@ -287,9 +336,121 @@ fn constraints_cross_checks(fields: &[AccountField]) -> ParseResult<()> {
         }
     }
 
+    // RENT EXEMPT TOP UP
+    let mut required_top_up = false;
+    let top_up_fields: Vec<(&Field, &Expr)> = fields
+        .iter()
+        .filter_map(|f| match f {
+            AccountField::Field(field) => match &field.constraints.rent_exempt {
+                Some(ConstraintRentExempt::TopUp { payer }) => {
+                    if !field.is_optional {
+                        required_top_up = true
+                    }
+                    Some((field, payer))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    if !top_up_fields.is_empty() {
+        // top up needs system program.
+        if !fields
+            .iter()
+            .any(|f| f.ident() == "system_program" && !(required_top_up && f.is_optional()))
+        {
+            return Err(ParseError::new(
+                top_up_fields[0].0.ident.span(),
+                message("rent_exempt = top_up", "system_program", required_top_up),
+            ));
+        }
+
+        for (field, payer) in top_up_fields {
+            // Get payer for topped up account
+            let associated_payer_name = match payer {
+                // composite payer, check not supported
+                Expr::Field(_) => continue,
+                // method call, check not supported
+                Expr::MethodCall(_) => continue,
+                field_name => field_name.to_token_stream().to_string(),
+            };
+
+            // Check payer is mutable
+            let associated_payer_field = fields.iter().find_map(|f| match f {
+                AccountField::Field(field) if *f.ident() == associated_payer_name => Some(field),
+                _ => None,
+            });
+            match associated_payer_field {
+                Some(associated_payer_field) => {
+                    if !associated_payer_field.constraints.is_mutable() {
+                        return Err(ParseError::new(
+                            field.ident.span(),
+                            "the payer specified for a rent_exempt = top_up constraint must be mutable.",
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(ParseError::new(
+                        field.ident.span(),
+                        "the payer specified for a rent_exempt = top_up constraint does not exist.",
+                    ));
+                }
+            }
+        }
+    }
+
+    // VAULT
+    for field in fields.iter().filter_map(|f| match f {
+        AccountField::Field(field) => Some(field),
+        _ => None,
+    }) {
+        let authority = match &field.constraints.token_account {
+            Some(ConstraintTokenAccountGroup {
+                vault: true,
+                authority: Some(authority),
+                ..
+            }) => authority,
+            _ => continue,
+        };
+
+        // Check the authority is a PDA of the program, so that only the
+        // program can move the tokens. Authorities that aren't fields of the
+        // struct, e.g. a stored key, can't be checked here.
+        let authority_field = match vault_authority_field(authority) {
+            Some(ident) => ident,
+            None => continue,
+        };
+        let is_pda = fields.iter().any(|f| match f {
+            AccountField::Field(f) if f.ident == *authority_field => matches!(
+                &f.constraints.seeds,
+                Some(seeds) if seeds.program_seed.is_none()
+            ),
+            _ => false,
+        });
+        if !is_pda {
+            return Err(ParseError::new(
+                field.ident.span(),
+                "the authority specified for a vault constraint must be a field with seeds and bump, which makes it a PDA of the program.",
+            ));
+        }
+    }
+
     Ok(())
 }
 
+/// Returns the field given as the authority of a vault, either as `field` or
+/// `field.key()`.
+fn vault_authority_field(authority: &Expr) -> Option<&Ident> {
+    match authority {
+        Expr::Path(path) => path.path.get_ident(),
+        Expr::MethodCall(call) if call.method == "key" && call.args.is_empty() => {
+            vault_authority_field(&call.receiver)
+        }
+        _ => None,
+    }
+}
+
 pub fn parse_account_field(f: &syn::Field) -> ParseResult<AccountField> {
     let ident = f.ident.clone().unwrap();
     let docs = docs::parse(&f.attrs);
@@ -333,10 +494,12 @@ fn is_field_primitive(f: &syn::Field) -> ParseResult<bool> {
             | "AccountInfo"
             | "UncheckedAccount"
             | "AccountLoader"
+            | "AccountWithTail"
             | "Account"
             | "Program"
             | "Interface"
             | "InterfaceAccount"
+            | "Vault"
             | "Signer"
             | "SystemAccount"
             | "ProgramData"
@@ -351,10 +514,16 @@ fn parse_ty(f: &syn::Field) -> ParseResult<(Ty, bool)> {
         "AccountInfo" => Ty::AccountInfo,
         "UncheckedAccount" => Ty::UncheckedAccount,
         "AccountLoader" => Ty::AccountLoader(parse_program_account_loader(&path)?),
+        "AccountWithTail" => Ty::AccountWithTail(parse_account_with_tail_ty(&path)?),
         "Account" => Ty::Account(parse_account_ty(&path)?),
         "Program" => Ty::Program(parse_program_ty(&path)?),
         "Interface" => Ty::Interface(parse_interface_ty(&path)?),
         "InterfaceAccount" => Ty::InterfaceAccount(parse_interface_account_ty(&path)?),
+        // Alias of the `InterfaceAccount` of a token account in `anchor_spl::vault`.
+        "Vault" => Ty::InterfaceAccount(InterfaceAccountTy {
+            account_type_path: syn::parse_quote!(anchor_spl::token_interface::TokenAccount),
+            boxed: false,
+        }),
         "Signer" => Ty::Signer,
         "SystemAccount" => Ty::SystemAccount,
         "ProgramData" => Ty::ProgramData,
@@ -433,6 +602,11 @@ fn parse_program_account_loader(path: &syn::Path) -> ParseResult<AccountLoaderTy
     })
 }
 
+fn parse_account_with_tail_ty(path: &syn::Path) -> ParseResult<AccountWithTailTy> {
+    let account_type_path = parse_account(path)?;
+    Ok(AccountWithTailTy { account_type_path })
+}
+
 fn parse_account_ty(path: &syn::Path) -> ParseResult<AccountTy> {
     let account_type_path = parse_account(path)?;
     let boxed = parser::tts_to_string(path)
@@ -574,6 +748,8 @@ fn parse_sysvar(path: &syn::Path) -> ParseResult<SysvarTy> {
         "StakeHistory" => SysvarTy::StakeHistory,
         "Instructions" => SysvarTy::Instructions,
         "Rewards" => SysvarTy::Rewards,
+        "EpochRewards" => SysvarTy::EpochRewards,
+        "LastRestartSlot" => SysvarTy::LastRestartSlot,
         _ => {
             return Err(ParseError::new(
                 account_ident.span(),
@@ -0,0 +1,36 @@
+use syn::parse::{Error as ParseError, Result as ParseResult};
+use syn::spanned::Spanned;
+
+const HOOKS: [&str; 2] = ["before_instruction", "after_instruction"];
+
+/// Whether the function is marked with one of the instruction hook attributes.
+pub fn is_hook(item_fn: &syn::ItemFn) -> bool {
+    item_fn
+        .attrs
+        .iter()
+        .any(|attr| HOOKS.iter().any(|hook| attr.path.is_ident(hook)))
+}
+
+// Parse the function marked with the given hook attribute, if any.
+pub fn parse(program_mod: &syn::ItemMod, hook: &str) -> ParseResult<Option<syn::Ident>> {
+    let mod_content = match &program_mod.content {
+        Some((_, content)) => content,
+        None => return Ok(None),
+    };
+    let hook_fns = mod_content
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Fn(item_fn) if item_fn.attrs.iter().any(|a| a.path.is_ident(hook)) => {
+                Some(item_fn)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if hook_fns.len() > 1 {
+        return Err(ParseError::new(
+            hook_fns[1].span(),
+            format!("More than one `#[{hook}]` function found"),
+        ));
+    }
+    Ok(hook_fns.first().map(|item_fn| item_fn.sig.ident.clone()))
+}
@@ -1,5 +1,6 @@
 use crate::parser::docs;
-use crate::parser::program::ctx_accounts_ident;
+use crate::parser::program::hooks;
+use crate::parser::program::{ctx_accounts_generics, ctx_accounts_ident};
 use crate::parser::spl_interface;
 use crate::{FallbackFn, Ix, IxArg, IxReturn};
 use syn::parse::{Error as ParseError, Result as ParseResult};
@@ -16,7 +17,7 @@ pub fn parse(program_mod: &syn::ItemMod) -> ParseResult<(Vec<Ix>, Option<Fallbac
     let ixs = mod_content
         .iter()
         .filter_map(|item| match item {
-            syn::Item::Fn(item_fn) => {
+            syn::Item::Fn(item_fn) if !hooks::is_hook(item_fn) => {
                 let (ctx, _) = parse_args(item_fn).ok()?;
                 ctx_accounts_ident(&ctx.raw_arg).ok()?;
                 Some(item_fn)
@@ -29,14 +30,21 @@ pub fn parse(program_mod: &syn::ItemMod) -> ParseResult<(Vec<Ix>, Option<Fallbac
             let docs = docs::parse(&method.attrs);
             let returns = parse_return(method)?;
             let anchor_ident = ctx_accounts_ident(&ctx.raw_arg)?;
+            let anchor_generics = ctx_accounts_generics(&ctx.raw_arg)?;
+            let no_log_ix_name = method
+                .attrs
+                .iter()
+                .any(|attr| attr.path.is_ident("no_log_ix_name"));
             Ok(Ix {
                 raw_method: method.clone(),
                 ident: method.sig.ident.clone(),
                 docs,
                 args,
                 anchor_ident,
+                anchor_generics,
                 returns,
                 interface_discriminator,
+                no_log_ix_name,
             })
         })
         .collect::<ParseResult<Vec<Ix>>>()?;
@@ -45,7 +53,7 @@ pub fn parse(program_mod: &syn::ItemMod) -> ParseResult<(Vec<Ix>, Option<Fallbac
         let fallback_fns = mod_content
             .iter()
             .filter_map(|item| match item {
-                syn::Item::Fn(item_fn) => {
+                syn::Item::Fn(item_fn) if !hooks::is_hook(item_fn) => {
                     let (ctx, _args) = parse_args(item_fn).ok()?;
                     if ctx_accounts_ident(&ctx.raw_arg).is_ok() {
                         return None;
@@ -79,14 +87,18 @@ pub fn parse_args(method: &syn::ItemFn) -> ParseResult<(IxArg, Vec<IxArg>)> {
         .map(|arg: &syn::FnArg| match arg {
             syn::FnArg::Typed(arg) => {
                 let docs = docs::parse(&arg.attrs);
+                let strategy = parse_strategy(&arg.attrs)?;
                 let ident = match &*arg.pat {
                     syn::Pat::Ident(ident) => &ident.ident,
                     _ => return Err(ParseError::new(arg.pat.span(), "expected argument name")),
                 };
+                let mut raw_arg = arg.clone();
+                raw_arg.attrs.retain(|attr| !is_strategy(attr));
                 Ok(IxArg {
                     name: ident.clone(),
                     docs,
-                    raw_arg: arg.clone(),
+                    raw_arg,
+                    strategy,
                 })
             }
             syn::FnArg::Receiver(_) => Err(ParseError::new(
@@ -102,6 +114,38 @@ pub fn parse_args(method: &syn::ItemFn) -> ParseResult<(IxArg, Vec<IxArg>)> {
     Ok((ctx, args))
 }
 
+// Parse the `#[strategy(<expr>)]` attribute of an instruction argument, which
+// overrides the proptest strategy of the argument.
+fn parse_strategy(attrs: &[syn::Attribute]) -> ParseResult<Option<syn::Expr>> {
+    attrs
+        .iter()
+        .find(|attr| is_strategy(attr))
+        .map(|attr| attr.parse_args())
+        .transpose()
+}
+
+// Remove the `#[strategy(..)]` attributes of the instruction arguments, which
+// are not allowed on the parameters of the emitted handlers.
+pub fn remove_strategies(program_mod: &mut syn::ItemMod) {
+    let items = match &mut program_mod.content {
+        Some((_, items)) => items,
+        None => return,
+    };
+    for item in items {
+        if let syn::Item::Fn(item_fn) = item {
+            for arg in &mut item_fn.sig.inputs {
+                if let syn::FnArg::Typed(arg) = arg {
+                    arg.attrs.retain(|attr| !is_strategy(attr));
+                }
+            }
+        }
+    }
+}
+
+fn is_strategy(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("strategy")
+}
+
 pub fn parse_return(method: &syn::ItemFn) -> ParseResult<IxReturn> {
     match method.sig.output {
         syn::ReturnType::Type(_, ref ty) => {
@@ -3,21 +3,46 @@ use crate::Program;
 use syn::parse::{Error as ParseError, Result as ParseResult};
 use syn::spanned::Spanned;
 
+mod hooks;
 mod instructions;
 
-pub fn parse(program_mod: syn::ItemMod) -> ParseResult<Program> {
+pub fn parse(mut program_mod: syn::ItemMod) -> ParseResult<Program> {
     let docs = docs::parse(&program_mod.attrs);
     let (ixs, fallback_fn) = instructions::parse(&program_mod)?;
+    instructions::remove_strategies(&mut program_mod);
+    let before_instruction = hooks::parse(&program_mod, "before_instruction")?;
+    let after_instruction = hooks::parse(&program_mod, "after_instruction")?;
     Ok(Program {
         ixs,
         name: program_mod.ident.clone(),
         docs,
         program_mod,
         fallback_fn,
+        before_instruction,
+        after_instruction,
     })
 }
 
 fn ctx_accounts_ident(path_ty: &syn::PatType) -> ParseResult<proc_macro2::Ident> {
+    Ok(ctx_accounts_path(path_ty)?.segments[0].ident.clone())
+}
+
+/// Returns the type and const generic arguments of the accounts struct, e.g.
+/// `Token` for `Context<Transfer<Token>>`. Lifetimes are left to inference.
+fn ctx_accounts_generics(path_ty: &syn::PatType) -> ParseResult<Vec<syn::GenericArgument>> {
+    let generics = match &ctx_accounts_path(path_ty)?.segments[0].arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter(|arg| !matches!(arg, syn::GenericArgument::Lifetime(_)))
+            .cloned()
+            .collect(),
+        _ => vec![],
+    };
+    Ok(generics)
+}
+
+fn ctx_accounts_path(path_ty: &syn::PatType) -> ParseResult<&syn::Path> {
     let p = match &*path_ty.ty {
         syn::Type::Path(p) => &p.path,
         _ => return Err(ParseError::new(path_ty.ty.span(), "invalid type")),
@@ -50,5 +75,5 @@ fn ctx_accounts_ident(path_ty: &syn::PatType) -> ParseResult<proc_macro2::Ident>
             ))
         }
     };
-    Ok(path.segments[0].ident.clone())
+    Ok(path)
 }
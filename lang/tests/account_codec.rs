@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use std::io::Write;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+pub struct LittleEndian;
+
+impl AccountCodec<Config> for LittleEndian {
+    fn encode<W: Write>(value: &Config, writer: &mut W) -> Result<()> {
+        writer
+            .write_all(&value.fee_bps.to_le_bytes())
+            .map_err(|_| ErrorCode::AccountDidNotSerialize.into())
+    }
+
+    fn decode(data: &[u8]) -> Result<Config> {
+        let fee_bps = data.get(..2).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        Ok(Config {
+            fee_bps: u16::from_le_bytes(fee_bps.try_into().unwrap()),
+        })
+    }
+}
+
+#[account(serde = LittleEndian)]
+pub struct Config {
+    pub fee_bps: u16,
+}
+
+#[account(serde = borsh)]
+pub struct Data {
+    pub value: u64,
+}
+
+#[test]
+fn test_custom_codec() {
+    let mut data = vec![];
+    Config { fee_bps: 300 }.try_serialize(&mut data).unwrap();
+    assert_eq!(&data[..8], &Config::DISCRIMINATOR);
+    assert_eq!(&data[8..], &300u16.to_le_bytes());
+
+    let config = Config::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(config.fee_bps, 300);
+
+    let invalid = [&Config::DISCRIMINATOR[..], &[1]].concat();
+    assert!(Config::try_deserialize(&mut &invalid[..]).is_err());
+    assert!(Config::try_deserialize(&mut &[0u8; 10][..]).is_err());
+}
+
+#[test]
+fn test_borsh_codec() {
+    let mut data = vec![];
+    Data { value: 7 }.try_serialize(&mut data).unwrap();
+    assert_eq!(&data[8..], &7u64.to_le_bytes());
+    assert_eq!(Data::try_deserialize(&mut &data[..]).unwrap().value, 7);
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+#[derive(InitSpace)]
+pub struct Blob {
+    pub authority: Pubkey,
+    pub version: u8,
+}
+
+fn blob_data(blob: &Blob, tail_len: u32, tail: &[u8]) -> Vec<u8> {
+    let mut data = vec![];
+    blob.try_serialize(&mut data).unwrap();
+    data.extend_from_slice(&tail_len.to_le_bytes());
+    data.extend_from_slice(tail);
+    data
+}
+
+#[test]
+fn test_account_with_tail() {
+    let authority = Pubkey::new_unique();
+    let blob = Blob {
+        authority,
+        version: 1,
+    };
+    assert_eq!(AccountWithTail::<Blob>::space(3), 8 + 33 + 4 + 3);
+
+    let key = Pubkey::new_unique();
+    let mut lamports = 1;
+    let mut data = blob_data(&blob, 3, &[1, 2, 3]);
+    let info = AccountInfo::new(
+        &key,
+        false,
+        true,
+        &mut lamports,
+        &mut data,
+        &crate::ID,
+        false,
+        0,
+    );
+
+    let mut account = AccountWithTail::<Blob>::try_from(&info).unwrap();
+    assert_eq!(account.authority, authority);
+    assert_eq!(account.tail_len().unwrap(), 3);
+    assert_eq!(&*account.tail().unwrap(), &[1, 2, 3]);
+
+    account.tail_mut().unwrap()[0] = 4;
+    account.version = 2;
+    account.exit(&crate::ID).unwrap();
+
+    let account = AccountWithTail::<Blob>::try_from(&info).unwrap();
+    assert_eq!(account.version, 2);
+    assert_eq!(&*account.tail().unwrap(), &[4, 2, 3]);
+}
+
+#[test]
+fn test_account_with_tail_invalid_length() {
+    let blob = Blob {
+        authority: Pubkey::new_unique(),
+        version: 1,
+    };
+    let datas = [
+        // Tail length exceeds the account data
+        blob_data(&blob, 4, &[1, 2, 3]),
+        // Missing tail length prefix
+        blob_data(&blob, 0, &[])[..8 + 33].to_vec(),
+    ];
+    for mut data in datas {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1;
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+        assert!(AccountWithTail::<Blob>::try_from(&info).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const ADMIN: Pubkey = pubkey!("GfsHGXWFd5a2wqPhCzXkir7tPbadYHPcRh1kjkYV1cNP");
+const OPERATOR: Pubkey = pubkey!("Gc3wrGTGNXGFdsjDEN2o7X2b3GxCMpMfZoA9F5Gk6qPs");
+const GUARDIAN: Pubkey = pubkey!("8ZifZoJXmHa1vSCek8BAhnNKaXpB83zEwg8WtWdHpEA6");
+const EMERGENCY: Pubkey = pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin");
+
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub operator: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    pub config: Account<'info, Config>,
+    /// CHECK: Only used as an authority.
+    pub guardian: UncheckedAccount<'info>,
+    /// CHECK: Checked to be one of the authorities.
+    #[account(authority_any_of = [config.admin, config.operator, guardian, EMERGENCY])]
+    pub authority: UncheckedAccount<'info>,
+}
+
+fn infos(authority: AccountInfo<'static>) -> Vec<AccountInfo<'static>> {
+    let config = Config {
+        admin: ADMIN,
+        operator: OPERATOR,
+    };
+    vec![
+        program_account_info(Pubkey::new_unique(), &config),
+        system_info(GUARDIAN, 1),
+        authority,
+    ]
+}
+
+#[test]
+fn test_authority_any_of() {
+    for key in [ADMIN, OPERATOR, GUARDIAN, EMERGENCY] {
+        let authority = signer_info(key);
+        try_accounts::<Pause>(infos(authority)).unwrap();
+    }
+}
+
+#[test]
+fn test_authority_any_of_other_signer() {
+    let authority = signer_info(Pubkey::new_unique());
+    assert_eq!(
+        error_code(try_accounts::<Pause>(infos(authority))),
+        u32::from(ErrorCode::ConstraintAuthorityAnyOf)
+    );
+}
+
+#[test]
+fn test_authority_any_of_not_signer() {
+    let authority = system_info(EMERGENCY, 1);
+    assert_eq!(
+        error_code(try_accounts::<Pause>(infos(authority))),
+        u32::from(ErrorCode::ConstraintAuthorityAnyOf)
+    );
+}
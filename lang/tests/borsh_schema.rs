@@ -0,0 +1,97 @@
+#![cfg(feature = "borsh-schema")]
+// `#[program]` checks the features of the program, e.g. `cpi`, which this
+// crate doesn't have.
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::BorshSchema;
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub delegate: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum Status {
+    Open,
+    Closed { at: i64 },
+}
+
+#[event]
+pub struct Deposited {
+    pub vault: Pubkey,
+    pub status: Status,
+}
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn deposit(_ctx: Context<Empty>, amount: u64, memo: Vec<u8>) -> Result<()> {
+        let _ = (amount, memo);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+// Fields of the struct declared by the schema.
+fn named_fields(schema: &BorshSchemaContainer) -> Vec<(&str, &str)> {
+    match &schema.definitions[&schema.declaration] {
+        Definition::Struct {
+            fields: Fields::NamedFields(fields),
+        } => fields
+            .iter()
+            .map(|(name, ty)| (name.as_str(), ty.as_str()))
+            .collect(),
+        definition => panic!("Unexpected definition: {definition:?}"),
+    }
+}
+
+#[test]
+fn test_account_schema() {
+    let schema = Vault::schema_container();
+    assert_eq!(schema.declaration, "Vault");
+    assert_eq!(
+        named_fields(&schema),
+        [
+            ("authority", "Pubkey"),
+            ("delegate", "Option<Pubkey>"),
+            ("amount", "u64"),
+        ]
+    );
+}
+
+#[test]
+fn test_event_schema() {
+    let schema = Deposited::schema_container();
+    assert_eq!(
+        named_fields(&schema),
+        [("vault", "Pubkey"), ("status", "Status")]
+    );
+    assert_eq!(
+        schema.definitions["Status"],
+        Definition::Enum {
+            variants: vec![
+                ("Open".into(), "StatusOpen".into()),
+                ("Closed".into(), "StatusClosed".into()),
+            ],
+        }
+    );
+}
+
+#[test]
+fn test_instruction_schema() {
+    let schema = instruction::Deposit::schema_container();
+    assert_eq!(schema.declaration, "Deposit");
+    assert_eq!(
+        named_fields(&schema),
+        [("amount", "u64"), ("memo", "Vec<u8>")]
+    );
+}
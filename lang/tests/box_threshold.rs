@@ -0,0 +1,33 @@
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Small {
+    pub value: u64,
+}
+
+#[account]
+pub struct Large {
+    pub data: [u8; 2048],
+}
+
+#[derive(Accounts)]
+#[accounts(box_threshold = 1024)]
+pub struct Update<'info> {
+    pub small: Account<'info, Small>,
+    pub large: Box<Account<'info, Large>>,
+}
+
+#[test]
+fn test_box_threshold() {
+    assert!(std::mem::size_of::<Small>() <= 1024);
+    assert!(std::mem::size_of::<Large>() > 1024);
+}
+
+#[test]
+fn test_box_threshold_unboxed_large_account() {
+    trybuild::TestCases::new().compile_fail("tests/ui/box_threshold.rs");
+}
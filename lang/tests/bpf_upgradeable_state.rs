@@ -0,0 +1,106 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[derive(Accounts)]
+pub struct CheckBuffer<'info> {
+    #[account(constraint = buffer.is_authority(&admin.key()))]
+    pub buffer: Account<'info, Buffer>,
+    pub admin: Signer<'info>,
+}
+
+// Data of an account of the upgradeable loader in the given state, followed
+// by an ELF of 4 bytes.
+fn loader_data(state: &UpgradeableLoaderState) -> Vec<u8> {
+    let mut data = bincode::serialize(state).unwrap();
+    data.extend([1, 2, 3, 4]);
+    data
+}
+
+fn buffer_info(authority_address: Option<Pubkey>) -> AccountInfo<'static> {
+    let data = loader_data(&UpgradeableLoaderState::Buffer { authority_address });
+    account_info(Pubkey::new_unique(), bpf_loader_upgradeable::ID, 1, data)
+}
+
+#[test]
+fn test_buffer() {
+    let authority = Pubkey::new_unique();
+    let data = loader_data(&UpgradeableLoaderState::Buffer {
+        authority_address: Some(authority),
+    });
+    let buffer = Buffer::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(buffer.authority_address, Some(authority));
+    assert!(buffer.is_authority(&authority));
+    assert!(!buffer.is_authority(&Pubkey::new_unique()));
+    assert_eq!(data[Buffer::DATA_OFFSET..], [1, 2, 3, 4]);
+
+    let data = loader_data(&UpgradeableLoaderState::Buffer {
+        authority_address: None,
+    });
+    let buffer = Buffer::try_deserialize(&mut &data[..]).unwrap();
+    assert!(!buffer.is_authority(&authority));
+}
+
+#[test]
+fn test_buffer_wrong_state() {
+    let data = loader_data(&UpgradeableLoaderState::Program {
+        programdata_address: Pubkey::new_unique(),
+    });
+    assert_eq!(
+        error_code(Buffer::try_deserialize(&mut &data[..])),
+        u32::from(ErrorCode::AccountNotBuffer)
+    );
+}
+
+#[test]
+fn test_buffer_constraint() {
+    let admin = Pubkey::new_unique();
+    try_accounts::<CheckBuffer>(vec![buffer_info(Some(admin)), signer_info(admin)]).unwrap();
+
+    let infos = vec![buffer_info(Some(Pubkey::new_unique())), signer_info(admin)];
+    assert_eq!(
+        error_code(try_accounts::<CheckBuffer>(infos)),
+        u32::from(ErrorCode::ConstraintRaw)
+    );
+
+    // Buffers must be owned by the upgradeable loader
+    let data = loader_data(&UpgradeableLoaderState::Buffer {
+        authority_address: Some(admin),
+    });
+    let infos = vec![
+        account_info(Pubkey::new_unique(), crate::ID, 1, data),
+        signer_info(admin),
+    ];
+    assert_eq!(
+        error_code(try_accounts::<CheckBuffer>(infos)),
+        u32::from(ErrorCode::AccountOwnedByWrongProgram)
+    );
+}
+
+#[test]
+fn test_program_data_authority() {
+    let authority = Pubkey::new_unique();
+    let data = loader_data(&UpgradeableLoaderState::ProgramData {
+        slot: 10,
+        upgrade_authority_address: Some(authority),
+    });
+    let program_data = ProgramData::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(program_data.slot, 10);
+    assert!(program_data.is_upgradeable());
+    assert!(program_data.is_upgrade_authority(&authority));
+    assert!(!program_data.is_upgrade_authority(&Pubkey::new_unique()));
+    assert_eq!(data[ProgramData::DATA_OFFSET..], [1, 2, 3, 4]);
+
+    let data = loader_data(&UpgradeableLoaderState::ProgramData {
+        slot: 10,
+        upgrade_authority_address: None,
+    });
+    let program_data = ProgramData::try_deserialize(&mut &data[..]).unwrap();
+    assert!(!program_data.is_upgradeable());
+    assert!(!program_data.is_upgrade_authority(&authority));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const SLOT: u64 = 100;
+const UNIX_TIMESTAMP: i64 = 1_000;
+
+struct ClockStubs;
+
+impl SyscallStubs for ClockStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT,
+            unix_timestamp: UNIX_TIMESTAMP,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        anchor_lang::solana_program::entrypoint::SUCCESS
+    }
+}
+
+#[derive(Accounts)]
+pub struct Window<'info> {
+    /// CHECK: Only the clock is checked.
+    #[account(
+        not_before = UNIX_TIMESTAMP - 1,
+        not_after = UNIX_TIMESTAMP + 1,
+        not_before_slot = SLOT - 1,
+        not_after_slot = SLOT + 1,
+    )]
+    pub auction: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TooEarly<'info> {
+    /// CHECK: Only the clock is checked.
+    #[account(not_before = UNIX_TIMESTAMP + 1, not_before_slot = SLOT - 1)]
+    pub auction: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TooEarlySlot<'info> {
+    /// CHECK: Only the clock is checked.
+    #[account(not_before = UNIX_TIMESTAMP - 1, not_before_slot = SLOT + 1)]
+    pub auction: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TooLate<'info> {
+    /// CHECK: Only the clock is checked.
+    #[account(not_after = UNIX_TIMESTAMP - 1)]
+    pub auction: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TooLateSlot<'info> {
+    /// CHECK: Only the clock is checked.
+    #[account(not_after_slot = SLOT - 1)]
+    pub auction: UncheckedAccount<'info>,
+}
+
+fn infos() -> Vec<AccountInfo<'static>> {
+    set_syscall_stubs(Box::new(ClockStubs));
+    vec![system_info(Pubkey::new_unique(), 1)]
+}
+
+#[test]
+fn test_clock_window() {
+    try_accounts::<Window>(infos()).unwrap();
+}
+
+#[test]
+fn test_not_before() {
+    let not_before = u32::from(ErrorCode::ConstraintNotBefore);
+    assert_eq!(error_code(try_accounts::<TooEarly>(infos())), not_before);
+    assert_eq!(
+        error_code(try_accounts::<TooEarlySlot>(infos())),
+        not_before
+    );
+}
+
+#[test]
+fn test_not_after() {
+    let not_after = u32::from(ErrorCode::ConstraintNotAfter);
+    assert_eq!(error_code(try_accounts::<TooLate>(infos())), not_after);
+    assert_eq!(error_code(try_accounts::<TooLateSlot>(infos())), not_after);
+}
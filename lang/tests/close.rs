@@ -0,0 +1,104 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::CLOSED_ACCOUNT_DISCRIMINATOR;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Escrow {
+    pub expired: bool,
+}
+
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    #[account(
+        mut,
+        close = if escrow.expired { maker.to_account_info() } else { taker.to_account_info() }
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(mut)]
+    pub taker: SystemAccount<'info>,
+}
+
+fn settle_infos(expired: bool, maker: Pubkey) -> Vec<AccountInfo<'static>> {
+    vec![
+        program_account_info(Pubkey::new_unique(), &Escrow { expired }),
+        system_info(maker, 1),
+        system_info(Pubkey::new_unique(), 1),
+    ]
+}
+
+#[test]
+fn test_close_runtime_receiver() {
+    for expired in [true, false] {
+        let infos = settle_infos(expired, Pubkey::new_unique());
+        let accounts: Settle = try_accounts(infos.clone()).unwrap();
+        accounts.exit(&crate::ID).unwrap();
+
+        let (receiver, other) = if expired {
+            (&infos[1], &infos[2])
+        } else {
+            (&infos[2], &infos[1])
+        };
+        assert_eq!(receiver.lamports(), 1 + 1_000_000);
+        assert_eq!(other.lamports(), 1);
+        assert_eq!(infos[0].lamports(), 0);
+        assert!(infos[0].data_is_empty());
+        assert_eq!(infos[0].owner, &System::id());
+    }
+}
+
+#[test]
+fn test_close_runtime_receiver_to_itself() {
+    let escrow = Pubkey::new_unique();
+    let mut infos = settle_infos(true, escrow);
+    infos[0] = program_account_info(escrow, &Escrow { expired: true });
+    // The receiver is only evaluated on exit, where it's checked
+    let accounts: Settle = try_accounts(infos).unwrap();
+    assert_eq!(
+        error_code(accounts.exit(&crate::ID)),
+        u32::from(ErrorCode::ConstraintClose)
+    );
+}
+
+#[test]
+fn test_close_runtime_receiver_evaluated_on_exit() {
+    let infos = settle_infos(false, Pubkey::new_unique());
+    let mut accounts: Settle = try_accounts(infos.clone()).unwrap();
+    accounts.escrow.expired = true;
+    accounts.exit(&crate::ID).unwrap();
+
+    assert_eq!(infos[1].lamports(), 1 + 1_000_000);
+    assert_eq!(infos[2].lamports(), 1);
+}
+
+#[test]
+fn test_close_account() {
+    let infos = settle_infos(true, Pubkey::new_unique());
+    close_account(&infos[0], &infos[1]).unwrap();
+    assert_eq!(infos[1].lamports(), 1 + 1_000_000);
+    assert!(infos[0].data_is_empty());
+    assert_eq!(infos[0].owner, &System::id());
+}
+
+#[test]
+fn test_drained_account_is_marked_closed() {
+    // Leaked to be borrowed by the `Account`, as with the infos of a program
+    let infos: &'static [AccountInfo<'static>] =
+        Box::leak(settle_infos(false, Pubkey::new_unique()).into_boxed_slice());
+    let escrow = Account::<Escrow>::try_from(&infos[0]).unwrap();
+    // Drained without closing the account, e.g. via CPI
+    **infos[0].lamports.borrow_mut() = 0;
+    escrow.exit(&crate::ID).unwrap();
+
+    let data = infos[0].try_borrow_data().unwrap();
+    assert_eq!(data[..8], CLOSED_ACCOUNT_DISCRIMINATOR);
+    assert!(data[8..].iter().all(|byte| *byte == 0));
+    drop(data);
+    assert!(Account::<Escrow>::try_from(&infos[0]).is_err());
+}
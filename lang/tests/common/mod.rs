@@ -0,0 +1,36 @@
+//! Helpers building the accounts of the tests of account constraints.
+#![allow(dead_code, unused_imports)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::Bumps;
+
+pub use anchor_test_utils::account_info::{
+    account_info, error_code, program_info, signer_info, system_info,
+};
+
+/// Returns the account info of an account of the program.
+pub fn program_account_info<T: AccountSerialize>(key: Pubkey, account: &T) -> AccountInfo<'static> {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    account_info(key, crate::ID, 1_000_000, data)
+}
+
+/// Validates the accounts with the constraints of `T` without running an
+/// instruction.
+pub fn try_accounts<T>(infos: Vec<AccountInfo<'static>>) -> Result<T>
+where
+    T: Bumps + Accounts<'static, <T as Bumps>::Bumps>,
+    <T as Bumps>::Bumps: Default,
+{
+    try_accounts_with_data(infos, &[])
+}
+
+/// Validates the accounts with the constraints of `T` for the given
+/// instruction data, used by `#[instruction(..)]`.
+pub fn try_accounts_with_data<T>(infos: Vec<AccountInfo<'static>>, ix_data: &[u8]) -> Result<T>
+where
+    T: Bumps + Accounts<'static, <T as Bumps>::Bumps>,
+    <T as Bumps>::Bumps: Default,
+{
+    anchor_test_utils::account_info::try_accounts(&crate::ID, infos, ix_data)
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Market {
+    pub authority: Pubkey,
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(has_one = market.authority)]
+    pub vault: Account<'info, Vault>,
+    pub market: Account<'info, Market>,
+}
+
+fn withdraw_infos(vault_authority: Pubkey, market_authority: Pubkey) -> Vec<AccountInfo<'static>> {
+    vec![
+        program_account_info(
+            Pubkey::new_unique(),
+            &Vault {
+                authority: vault_authority,
+            },
+        ),
+        program_account_info(
+            Pubkey::new_unique(),
+            &Market {
+                authority: market_authority,
+            },
+        ),
+    ]
+}
+
+#[test]
+fn test_has_one_nested() {
+    let authority = Pubkey::new_unique();
+    try_accounts::<Withdraw>(withdraw_infos(authority, authority)).unwrap();
+}
+
+#[test]
+fn test_has_one_nested_mismatch() {
+    let infos = withdraw_infos(Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(
+        error_code(try_accounts::<Withdraw>(infos)),
+        u32::from(ErrorCode::ConstraintHasOne)
+    );
+}
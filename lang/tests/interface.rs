@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{CheckId, CheckOwner, Ids, Owners};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const ORACLE_A: Pubkey = pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin");
+const ORACLE_B: Pubkey = pubkey!("GfsHGXWFd5a2wqPhCzXkir7tPbadYHPcRh1kjkYV1cNP");
+
+declare_interface! {
+    /// Oracle programs sharing the price feed layout.
+    pub struct Oracle = [ORACLE_A, ORACLE_B];
+    accounts = [PriceFeed];
+}
+
+#[account]
+pub struct PriceFeed {
+    pub price: i64,
+}
+
+#[derive(Accounts)]
+pub struct ReadPrice<'info> {
+    pub oracle: Interface<'info, Oracle>,
+    #[account(owner = oracle.key())]
+    pub price_feed: InterfaceAccount<'info, PriceFeed>,
+}
+
+#[test]
+fn test_declare_interface() {
+    assert_eq!(<Oracle as Ids>::ids(), &[ORACLE_A, ORACLE_B]);
+    assert!(Oracle::check_id(&ORACLE_B).is_ok());
+    assert!(Oracle::check_id(&crate::ID).is_err());
+
+    assert_eq!(<PriceFeed as Owners>::owners(), Oracle::IDS);
+    assert!(PriceFeed::check_owner(&ORACLE_A).is_ok());
+    assert!(PriceFeed::check_owner(&crate::ID).is_err());
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account(zero_copy)]
+pub struct Pool {
+    pub authority: Pubkey,
+    pub total_deposits: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+const_assert_layout!(
+    Pool,
+    size = 48,
+    authority = 0,
+    total_deposits = 32,
+    bump = 40
+);
+
+#[zero_copy(unsafe)]
+pub struct Packed {
+    pub flag: u8,
+    pub value: u64,
+}
+
+const_assert_layout!(Packed, size = 9, flag = 0, value = 1);
+
+#[test]
+fn test_field_offset() {
+    assert_eq!(anchor_lang::__field_offset!(Pool, bump), 40);
+    assert_eq!(anchor_lang::__field_offset!(Packed, value), 1);
+}
@@ -0,0 +1,32 @@
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+#[test]
+fn test_checked_ops() {
+    assert_eq!((Checked(2u64) + 3).unwrap(), Checked(5));
+    assert_eq!((Checked(5u64) - Checked(3)).unwrap(), Checked(2));
+    assert_eq!((Checked(4u64) * 3 / Checked(2)).unwrap(), Checked(6));
+    assert_eq!((Checked(7i32) % 4).unwrap().get(), 3);
+    assert_eq!(Checked(3u8).pow(2).unwrap(), Checked(9));
+}
+
+#[test]
+fn test_checked_overflow() {
+    let overflow: Error = ErrorCode::MathOverflow.into();
+    assert_eq!((Checked(u64::MAX) + 1).unwrap_err(), overflow);
+    assert_eq!((Checked(0u64) - 1).unwrap_err(), overflow);
+    assert_eq!((Checked(1u64) / 0).unwrap_err(), overflow);
+    assert_eq!((Checked(u8::MAX) * 2 + Checked(1)).unwrap_err(), overflow);
+    assert_eq!(Checked(2u8).pow(8).unwrap_err(), overflow);
+}
+
+#[test]
+fn test_checked_mul_div() {
+    assert_eq!(
+        Checked(u64::MAX).mul_div(u64::MAX, u64::MAX).unwrap(),
+        Checked(u64::MAX)
+    );
+    assert_eq!(Checked(10u64).mul_div(3, 4).unwrap(), Checked(7));
+    assert!(Checked(u64::MAX).mul_div(2, 1).is_err());
+    assert!(Checked(1u64).mul_div(1, 0).is_err());
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "proptest")]
+// `#[program]` checks the features of the program, e.g. `cpi`, which this
+// crate doesn't have.
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, InstructionData};
+use proptest::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Config {
+    pub fee: u16,
+}
+
+#[program]
+pub mod vault {
+    use super::*;
+
+    pub fn deposit(
+        _ctx: Context<Empty>,
+        #[strategy(1..=1_000_000u64)] amount: u64,
+        delegate: Option<Pubkey>,
+        members: Vec<Pubkey>,
+        signers: [Pubkey; 3],
+        pair: (Pubkey, u8),
+        #[strategy(any::<u16>().prop_map(|fee| Config { fee }))] config: Config,
+    ) -> Result<()> {
+        let _ = (amount, delegate, members, signers, pair, config);
+        Ok(())
+    }
+
+    pub fn reset(_ctx: Context<Empty>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+proptest! {
+    #[test]
+    fn test_arbitrary_instruction(ix in any::<instruction::Deposit>()) {
+        prop_assert!((1..=1_000_000).contains(&ix.amount));
+        prop_assert!(ix.members.len() < 100);
+
+        let data = ix.data();
+        let decoded = instruction::Deposit::deserialize(&mut &data[8..]).unwrap();
+        prop_assert_eq!(format!("{decoded:?}"), format!("{ix:?}"));
+    }
+
+    #[test]
+    fn test_arbitrary_unit_instruction(ix in any::<instruction::Reset>()) {
+        prop_assert_eq!(ix.data(), instruction::Reset::DISCRIMINATOR.to_vec());
+    }
+}
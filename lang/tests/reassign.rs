@@ -0,0 +1,51 @@
+mod common;
+
+use anchor_lang::prelude::*;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const NEW_PROGRAM: Pubkey = pubkey!("11111111111111111111111111111112");
+
+#[account]
+pub struct State {
+    pub value: u64,
+}
+
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    #[account(mut, reassign = NEW_PROGRAM)]
+    pub state: Account<'info, State>,
+}
+
+#[test]
+fn test_reassign() {
+    let infos = vec![program_account_info(
+        Pubkey::new_unique(),
+        &State { value: 42 },
+    )];
+    let accounts: Migrate = try_accounts(infos.clone()).unwrap();
+    accounts.exit(&crate::ID).unwrap();
+
+    assert_eq!(infos[0].owner, &NEW_PROGRAM);
+    assert_eq!(infos[0].data_len(), 8 + 8);
+    assert!(infos[0].try_borrow_data().unwrap().iter().all(|b| *b == 0));
+    // Not an account of the program anymore
+    assert!(try_accounts::<Migrate>(infos).is_err());
+}
+
+#[test]
+fn test_reassign_account() {
+    // Leaked to be borrowed by the `Account`, as with the infos of a program
+    let info: &'static AccountInfo<'static> = Box::leak(Box::new(program_account_info(
+        Pubkey::new_unique(),
+        &State { value: 42 },
+    )));
+    reassign_account(info, &NEW_PROGRAM).unwrap();
+    assert_eq!(info.owner, &NEW_PROGRAM);
+    assert!(info.try_borrow_data().unwrap().iter().all(|b| *b == 0));
+
+    // Accounts are reassigned back with zeroed data, without discriminator
+    reassign_account(info, &crate::ID).unwrap();
+    assert!(Account::<State>::try_from(info).is_err());
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "serde")]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+#[derive(Debug, PartialEq)]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub delegate: Option<Pubkey>,
+    pub members: Vec<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+#[derive(Debug, PartialEq)]
+pub struct Deposited {
+    pub vault: Pubkey,
+    pub amount: u64,
+}
+
+#[test]
+fn test_serde_account() {
+    let vault = Vault {
+        authority: Pubkey::new_unique(),
+        delegate: None,
+        members: vec![Pubkey::new_unique()],
+        amount: 42,
+    };
+    let json = serde_json::to_value(&vault).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "authority": vault.authority.to_string(),
+            "delegate": null,
+            "members": [vault.members[0].to_string()],
+            "amount": 42,
+        })
+    );
+    assert_eq!(serde_json::from_value::<Vault>(json).unwrap(), vault);
+}
+
+#[test]
+fn test_serde_event() {
+    let event = Deposited {
+        vault: Pubkey::new_unique(),
+        amount: 1,
+    };
+    let json = serde_json::to_string(&event).unwrap();
+    assert_eq!(json, format!(r#"{{"vault":"{}","amount":1}}"#, event.vault));
+    assert_eq!(serde_json::from_str::<Deposited>(&json).unwrap(), event);
+}
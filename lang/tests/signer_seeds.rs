@@ -0,0 +1,108 @@
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[derive(Accounts)]
+#[accounts(signer_seeds)]
+pub struct Vault<'info> {
+    #[account(seeds = [b"vault", user.key().as_ref()], bump)]
+    pub vault: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[accounts(signer_seeds)]
+#[instruction(id: u64)]
+pub struct WithArgs<'info> {
+    #[account(seeds = [b"item", id.to_le_bytes().as_ref()], bump)]
+    pub item: UncheckedAccount<'info>,
+}
+
+#[account(zero_copy)]
+pub struct Pool {
+    pub id: u64,
+}
+
+// Seeds that can't be evaluated outside of `try_accounts`, skipped
+#[derive(Accounts)]
+#[accounts(signer_seeds)]
+pub struct NotPlain<'info> {
+    pub pool: AccountLoader<'info, Pool>,
+    #[account(seeds = [b"pool", pool.load()?.id.to_le_bytes().as_ref()], bump)]
+    pub pool_vault: UncheckedAccount<'info>,
+}
+
+impl<'info> NotPlain<'info> {
+    fn pool_vault_signer_seeds(&self) -> Result<SignerSeeds> {
+        Ok(SignerSeeds::new(
+            vec![
+                b"pool".to_vec(),
+                self.pool.load()?.id.to_le_bytes().to_vec(),
+            ],
+            0,
+        ))
+    }
+}
+
+// Not opted in, so user methods with the same name don't collide
+#[derive(Accounts)]
+pub struct NotOptedIn<'info> {
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: UncheckedAccount<'info>,
+}
+
+impl<'info> NotOptedIn<'info> {
+    fn vault_signer_seeds(&self) -> &'static [u8] {
+        b"vault"
+    }
+}
+
+#[test]
+fn test_signer_seeds() {
+    let user_key = Pubkey::new_unique();
+    let (vault_key, bump) =
+        Pubkey::find_program_address(&[b"vault", user_key.as_ref()], &crate::ID);
+
+    let (mut user_lamports, mut vault_lamports) = (0, 0);
+    let (mut user_data, mut vault_data) = (vec![], vec![]);
+    let owner = Pubkey::default();
+    let user_info = AccountInfo::new(
+        &user_key,
+        true,
+        false,
+        &mut user_lamports,
+        &mut user_data,
+        &owner,
+        false,
+        0,
+    );
+    let vault_info = AccountInfo::new(
+        &vault_key,
+        false,
+        false,
+        &mut vault_lamports,
+        &mut vault_data,
+        &owner,
+        false,
+        0,
+    );
+    let accounts = Vault {
+        vault: UncheckedAccount::try_from(&vault_info),
+        user: Signer::try_from(&user_info).unwrap(),
+    };
+
+    let seeds = accounts.vault_signer_seeds(bump);
+    assert_eq!(seeds.bump(), bump);
+    assert_eq!(
+        seeds.seeds(),
+        vec![&b"vault"[..], user_key.as_ref(), &[bump][..]]
+    );
+    assert_eq!(seeds.address(&crate::ID).unwrap(), vault_key);
+    seeds.with_signer(|signer| {
+        assert_eq!(signer.len(), 1);
+        assert_eq!(signer[0].len(), 3);
+    });
+    SignerSeeds::with_signers(&[&seeds, &seeds], |signer| assert_eq!(signer.len(), 2));
+}
@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::Hash,
+    stake_history::StakeHistoryEntry,
+    sysvar::{self, slot_hashes, stake_history},
+};
+
+fn with_account_info<F: FnOnce(&AccountInfo)>(key: Pubkey, mut data: Vec<u8>, f: F) {
+    let mut lamports = 0;
+    let owner = sysvar::ID;
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    f(&info)
+}
+
+#[test]
+fn test_slot_hashes_view() {
+    let entries: Vec<(u64, Hash)> = (0..10u64)
+        .rev()
+        .map(|slot| (slot * 2, Hash::new_from_array([slot as u8; 32])))
+        .collect();
+    let data = bincode::serialize(&entries).unwrap();
+
+    with_account_info(slot_hashes::ID, data, |info| {
+        let view = SlotHashesView::from_account_info(info).unwrap();
+        assert_eq!(view.len(), 10);
+        assert_eq!(view.most_recent(), Some(entries[0]));
+        assert_eq!(view.get(10), None);
+        for (slot, hash) in &entries {
+            assert_eq!(view.get_hash(*slot), Some(*hash));
+        }
+        assert_eq!(view.get_hash(3), None);
+        assert_eq!(view.get_hash(100), None);
+    });
+}
+
+#[test]
+fn test_slot_hashes_view_wrong_key() {
+    let data = bincode::serialize(&Vec::<(u64, Hash)>::new()).unwrap();
+    with_account_info(stake_history::ID, data, |info| {
+        assert!(SlotHashesView::from_account_info(info).is_err());
+    });
+}
+
+#[test]
+fn test_slot_hashes_view_truncated_data() {
+    let mut data = bincode::serialize(&vec![(1u64, Hash::default())]).unwrap();
+    data.pop();
+    with_account_info(slot_hashes::ID, data, |info| {
+        assert!(SlotHashesView::from_account_info(info).is_err());
+    });
+}
+
+#[test]
+fn test_stake_history_view() {
+    let entries: Vec<(u64, StakeHistoryEntry)> = (0..5u64)
+        .rev()
+        .map(|epoch| {
+            (
+                epoch,
+                StakeHistoryEntry {
+                    effective: epoch * 100,
+                    activating: epoch * 10,
+                    deactivating: epoch,
+                },
+            )
+        })
+        .collect();
+    let data = bincode::serialize(&entries).unwrap();
+
+    with_account_info(stake_history::ID, data, |info| {
+        let view = StakeHistoryView::from_account_info(info).unwrap();
+        assert_eq!(view.len(), 5);
+        for (epoch, entry) in &entries {
+            assert_eq!(view.get_entry(*epoch).as_ref(), Some(entry));
+        }
+        assert_eq!(view.get_entry(5), None);
+    });
+}
+
+#[test]
+fn test_introspection() {
+    use anchor_lang::solana_program::sysvar::instructions::{
+        self, BorrowedAccountMeta, BorrowedInstruction,
+    };
+
+    let program_a = Pubkey::new_unique();
+    let program_b = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    fn ix<'a>(
+        program_id: &'a Pubkey,
+        account: &'a Pubkey,
+        data: &'a [u8],
+    ) -> BorrowedInstruction<'a> {
+        BorrowedInstruction {
+            program_id,
+            accounts: vec![BorrowedAccountMeta {
+                pubkey: account,
+                is_signer: false,
+                is_writable: true,
+            }],
+            data,
+        }
+    }
+    let mut data = instructions::construct_instructions_data(&[
+        ix(&program_a, &account, &[1, 2]),
+        ix(&program_b, &account, &[3]),
+        ix(&program_a, &account, &[4]),
+    ]);
+    instructions::store_current_index(&mut data, 1);
+
+    with_account_info(instructions::ID, data, |info| {
+        let ixs = Introspection::from_account_info(info).unwrap();
+        assert_eq!(ixs.len(), 3);
+        assert_eq!(ixs.current_index(), 1);
+        assert_eq!(ixs.current().unwrap().program_id, program_b);
+        assert_eq!(ixs.get_relative(-1).unwrap().data, vec![1, 2]);
+        assert_eq!(ixs.get(2).unwrap().accounts[0].pubkey, account);
+        assert!(ixs.is_present_before(&program_a).unwrap());
+        assert!(!ixs.is_present_before(&program_b).unwrap());
+        assert!(ixs.is_present_after(&program_a).unwrap());
+        assert!(ixs.is_instruction_present_before(&program_a, &[1]).unwrap());
+        assert!(!ixs.is_instruction_present_before(&program_a, &[4]).unwrap());
+        assert!(ixs.is_only_invocation_of(&program_b).unwrap());
+        assert!(!ixs.is_only_invocation_of(&program_a).unwrap());
+        assert_eq!(ixs.before().count(), 1);
+        assert_eq!(ixs.after().count(), 1);
+    });
+}
+
+#[test]
+fn test_syscall_accessors() {
+    use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+    struct ClockStubs;
+    impl SyscallStubs for ClockStubs {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                slot: 42,
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            anchor_lang::solana_program::entrypoint::SUCCESS
+        }
+    }
+    set_syscall_stubs(Box::new(ClockStubs));
+
+    let clock = anchor_lang::sysvar::clock().unwrap();
+    assert_eq!((clock.slot, clock.unix_timestamp), (42, 1_700_000_000));
+    // Not stubbed, so unsupported off-chain
+    assert!(anchor_lang::sysvar::rent().is_err());
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    system_instruction::{SystemError, SystemInstruction},
+};
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// Stubs the rent sysvar and the transfers of the system program.
+struct SystemStubs;
+
+impl SyscallStubs for SystemStubs {
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        assert_eq!(instruction.program_id, System::id());
+        let lamports = match bincode::deserialize(&instruction.data).unwrap() {
+            SystemInstruction::Transfer { lamports } => lamports,
+            ix => panic!("Unexpected system instruction: {ix:?}"),
+        };
+        let find = |key: &Pubkey| account_infos.iter().find(|info| info.key == key).unwrap();
+        let from = find(&instruction.accounts[0].pubkey);
+        let to = find(&instruction.accounts[1].pubkey);
+        if !from.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if from.lamports() < lamports {
+            return Err(ProgramError::Custom(
+                SystemError::ResultWithNegativeLamports as u32,
+            ));
+        }
+        **from.try_borrow_mut_lamports()? -= lamports;
+        **to.try_borrow_mut_lamports()? += lamports;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Vault {
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct TopUp<'info> {
+    #[account(mut, rent_exempt = top_up(payer))]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+fn vault_info(lamports: u64) -> AccountInfo<'static> {
+    let mut data = vec![];
+    Vault { amount: 1 }.try_serialize(&mut data).unwrap();
+    account_info(Pubkey::new_unique(), crate::ID, lamports, data)
+}
+
+fn infos(vault: &AccountInfo<'static>, payer: &AccountInfo<'static>) -> Vec<AccountInfo<'static>> {
+    set_syscall_stubs(Box::new(SystemStubs));
+    vec![vault.clone(), payer.clone(), program_info(System::id())]
+}
+
+#[test]
+fn test_top_up() {
+    let minimum = Rent::default().minimum_balance(8 + 8);
+    let vault = vault_info(minimum - 100);
+    let payer = signer_info(Pubkey::new_unique());
+    let payer_lamports = payer.lamports();
+
+    try_accounts::<TopUp>(infos(&vault, &payer)).unwrap();
+    assert_eq!(vault.lamports(), minimum);
+    assert_eq!(payer.lamports(), payer_lamports - 100);
+}
+
+#[test]
+fn test_top_up_rent_exempt() {
+    let minimum = Rent::default().minimum_balance(8 + 8);
+    let vault = vault_info(minimum + 100);
+    let payer = signer_info(Pubkey::new_unique());
+    let payer_lamports = payer.lamports();
+
+    try_accounts::<TopUp>(infos(&vault, &payer)).unwrap();
+    assert_eq!(vault.lamports(), minimum + 100);
+    assert_eq!(payer.lamports(), payer_lamports);
+}
+
+#[test]
+fn test_top_up_insufficient_funds() {
+    let minimum = Rent::default().minimum_balance(8 + 8);
+    let vault = vault_info(minimum - 100);
+    let mut payer = system_info(Pubkey::new_unique(), 99);
+    payer.is_signer = true;
+
+    let result = try_accounts::<TopUp>(infos(&vault, &payer));
+    assert!(matches!(
+        result,
+        Err(Error::ProgramError(e))
+            if e.program_error == ProgramError::Custom(SystemError::ResultWithNegativeLamports as u32)
+    ));
+    assert_eq!(vault.lamports(), minimum - 100);
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Large {
+    pub data: [u8; 2048],
+}
+
+#[derive(Accounts)]
+#[accounts(box_threshold = 1024)]
+pub struct Update<'info> {
+    pub large: Account<'info, Large>,
+}
+
+fn main() {}
@@ -0,0 +1,194 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    system_instruction::{SystemError, SystemInstruction},
+};
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const OTHER_PROGRAM: Pubkey = pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin");
+
+// Stubs the rent sysvar and emulates the creation of accounts with a seed by
+// the system program, which derives the address with the new owner.
+struct SystemStubs;
+
+impl SyscallStubs for SystemStubs {
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        assert_eq!(instruction.program_id, System::id());
+        let (base, seed, lamports, space, owner) =
+            match bincode::deserialize(&instruction.data).unwrap() {
+                SystemInstruction::CreateAccountWithSeed {
+                    base,
+                    seed,
+                    lamports,
+                    space,
+                    owner,
+                } => (base, seed, lamports, space, owner),
+                ix => panic!("Unexpected system instruction: {ix:?}"),
+            };
+        let find = |key: &Pubkey| account_infos.iter().find(|info| info.key == key).unwrap();
+        let from = find(&instruction.accounts[0].pubkey);
+        let to = find(&instruction.accounts[1].pubkey);
+        if !from.is_signer || !find(&base).is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if Pubkey::create_with_seed(&base, &seed, &owner).unwrap() != *to.key {
+            return Err(ProgramError::Custom(
+                SystemError::AddressWithSeedMismatch as u32,
+            ));
+        }
+        **from.try_borrow_mut_lamports()? -= lamports;
+        **to.try_borrow_mut_lamports()? += lamports;
+        to.realloc(space as usize, false)?;
+        to.assign(&owner);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Vault {
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct InitVault<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 8,
+        with_seed::base = base,
+        with_seed::seed = "vault",
+    )]
+    pub vault: Account<'info, Vault>,
+    pub base: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitForeign<'info> {
+    /// CHECK: Created for another program.
+    #[account(
+        init,
+        payer = payer,
+        space = 8,
+        owner = OTHER_PROGRAM,
+        with_seed::base = base,
+        with_seed::seed = "vault",
+    )]
+    pub foreign: UncheckedAccount<'info>,
+    pub base: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckForeign<'info> {
+    /// CHECK: Only its address is checked.
+    #[account(
+        with_seed::base = base,
+        with_seed::seed = "vault",
+        with_seed::owner = OTHER_PROGRAM,
+    )]
+    pub foreign: UncheckedAccount<'info>,
+    /// CHECK: Only used to derive the address.
+    pub base: UncheckedAccount<'info>,
+}
+
+fn init_infos(
+    account: &AccountInfo<'static>,
+    base: &AccountInfo<'static>,
+) -> Vec<AccountInfo<'static>> {
+    set_syscall_stubs(Box::new(SystemStubs));
+    vec![
+        account.clone(),
+        base.clone(),
+        signer_info(Pubkey::new_unique()),
+        program_info(System::id()),
+    ]
+}
+
+#[test]
+fn test_init_with_seed() {
+    let base = signer_info(Pubkey::new_unique());
+    let key = Pubkey::create_with_seed(base.key, "vault", &crate::ID).unwrap();
+    let vault = system_info(key, 0);
+
+    try_accounts::<InitVault>(init_infos(&vault, &base)).unwrap();
+    assert_eq!(vault.owner, &crate::ID);
+    assert_eq!(vault.data_len(), 8 + 8);
+}
+
+#[test]
+fn test_init_with_seed_owner() {
+    let base = signer_info(Pubkey::new_unique());
+    let key = Pubkey::create_with_seed(base.key, "vault", &OTHER_PROGRAM).unwrap();
+    let foreign = system_info(key, 0);
+
+    // The address is derived with the owner the account is created for
+    try_accounts::<InitForeign>(init_infos(&foreign, &base)).unwrap();
+    assert_eq!(foreign.owner, &OTHER_PROGRAM);
+}
+
+#[test]
+fn test_init_with_seed_other_owner() {
+    let base = signer_info(Pubkey::new_unique());
+    let key = Pubkey::create_with_seed(base.key, "vault", &crate::ID).unwrap();
+    let foreign = system_info(key, 0);
+
+    let result = try_accounts::<InitForeign>(init_infos(&foreign, &base));
+    assert!(matches!(
+        result,
+        Err(Error::ProgramError(e))
+            if e.program_error == ProgramError::Custom(SystemError::AddressWithSeedMismatch as u32)
+    ));
+    assert_eq!(foreign.owner, &System::id());
+}
+
+#[test]
+fn test_init_with_seed_base_not_signer() {
+    let mut base = signer_info(Pubkey::new_unique());
+    base.is_signer = false;
+    let key = Pubkey::create_with_seed(base.key, "vault", &crate::ID).unwrap();
+    let vault = system_info(key, 0);
+
+    let result = try_accounts::<InitVault>(init_infos(&vault, &base));
+    assert_eq!(error_code(result), ErrorCode::AccountNotSigner as u32);
+}
+
+#[test]
+fn test_with_seed() {
+    let base = system_info(Pubkey::new_unique(), 0);
+    let key = Pubkey::create_with_seed(base.key, "vault", &OTHER_PROGRAM).unwrap();
+    let foreign = account_info(key, OTHER_PROGRAM, 1, vec![]);
+
+    try_accounts::<CheckForeign>(vec![foreign, base]).unwrap();
+}
+
+#[test]
+fn test_with_seed_other_owner() {
+    let base = system_info(Pubkey::new_unique(), 0);
+    let key = Pubkey::create_with_seed(base.key, "vault", &crate::ID).unwrap();
+    let foreign = account_info(key, OTHER_PROGRAM, 1, vec![]);
+
+    let result = try_accounts::<CheckForeign>(vec![foreign, base]);
+    assert_eq!(error_code(result), ErrorCode::ConstraintWithSeed as u32);
+}
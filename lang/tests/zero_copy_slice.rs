@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account(zero_copy)]
+pub struct OrderBook {
+    pub market: Pubkey,
+    pub len: u64,
+}
+
+#[zero_copy]
+pub struct Order {
+    pub price: u64,
+    pub amount: u64,
+}
+
+impl ZeroCopySlice<Order> for OrderBook {
+    fn slice_len(&self) -> usize {
+        self.len as usize
+    }
+}
+
+fn order_book_data(len: u64, capacity: usize) -> Vec<u8> {
+    let mut data = OrderBook::discriminator().to_vec();
+    data.extend_from_slice(Pubkey::default().as_ref());
+    data.extend_from_slice(&len.to_le_bytes());
+    data.resize(
+        AccountLoader::<OrderBook>::slice_space::<Order>(capacity),
+        0,
+    );
+    data
+}
+
+#[test]
+fn test_load_slice() {
+    assert_eq!(
+        AccountLoader::<OrderBook>::slice_space::<Order>(2),
+        8 + 40 + 2 * 16
+    );
+
+    let key = Pubkey::new_unique();
+    let mut lamports = 1;
+    let mut data = order_book_data(2, 3);
+    let info = AccountInfo::new(
+        &key,
+        false,
+        true,
+        &mut lamports,
+        &mut data,
+        &crate::ID,
+        false,
+        0,
+    );
+    let loader = AccountLoader::<OrderBook>::try_from(&info).unwrap();
+
+    {
+        let (header, mut orders) = loader.load_slice_mut::<Order>().unwrap();
+        assert_eq!(header.len, 2);
+        assert_eq!(orders.len(), 2);
+        orders[1].price = 10;
+    }
+
+    let (header, orders) = loader.load_slice::<Order>().unwrap();
+    assert_eq!(header.len, 2);
+    assert_eq!(orders[0].price, 0);
+    assert_eq!(orders[1].price, 10);
+}
+
+#[test]
+fn test_load_slice_exceeding_data() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 1;
+    let mut data = order_book_data(3, 2);
+    let info = AccountInfo::new(
+        &key,
+        false,
+        true,
+        &mut lamports,
+        &mut data,
+        &crate::ID,
+        false,
+        0,
+    );
+    let loader = AccountLoader::<OrderBook>::try_from(&info).unwrap();
+    assert!(loader.load_slice::<Order>().is_err());
+}
@@ -0,0 +1,298 @@
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{context::CpiContext, Accounts, AnchorSerialize, Result, ToAccountInfos};
+
+// spl-account-compression is itself an Anchor program depending on a
+// different version of anchor-lang, so its instructions are built here
+// instead of depending on its `cpi` feature.
+anchor_lang::declare_id!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+pub use noop::ID as NOOP_ID;
+mod noop {
+    use super::*;
+    anchor_lang::declare_id!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+}
+
+const INIT_EMPTY_MERKLE_TREE: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+const APPEND: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+const REPLACE_LEAF: [u8; 8] = [204, 165, 76, 100, 73, 147, 0, 128];
+const VERIFY_LEAF: [u8; 8] = [124, 220, 22, 223, 104, 10, 250, 224];
+
+/// Size of the header preceding the tree in a merkle tree account.
+pub const CONCURRENT_MERKLE_TREE_HEADER_SIZE: usize = 56;
+
+/// Returns the space to allocate for a merkle tree account before
+/// initializing it with [`init_empty_merkle_tree`].
+pub const fn merkle_tree_account_size(
+    max_depth: u32,
+    max_buffer_size: u32,
+    canopy_depth: u32,
+) -> usize {
+    let depth = max_depth as usize;
+    let change_log = 32 + 32 * depth + 8;
+    let rightmost_proof = 32 * depth + 32 + 8;
+    let tree = 24 + max_buffer_size as usize * change_log + rightmost_proof;
+    let canopy = if canopy_depth == 0 {
+        0
+    } else {
+        ((1 << (canopy_depth + 1)) - 2) * 32
+    };
+    CONCURRENT_MERKLE_TREE_HEADER_SIZE + tree + canopy
+}
+
+fn instruction(
+    discriminator: [u8; 8],
+    args: impl AnchorSerialize,
+    accounts: Vec<AccountMeta>,
+    proof: &[AccountInfo],
+) -> Result<Instruction> {
+    let mut data = discriminator.to_vec();
+    args.serialize(&mut data)
+        .map_err(|_| ErrorCode::InstructionDidNotSerialize)?;
+    let mut accounts = accounts;
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node.key, false)),
+    );
+    Ok(Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    })
+}
+
+fn modify_accounts(accounts: &Modify) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*accounts.merkle_tree.key, false),
+        AccountMeta::new_readonly(*accounts.authority.key, true),
+        AccountMeta::new_readonly(*accounts.noop.key, false),
+    ]
+}
+
+pub fn init_empty_merkle_tree<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Modify<'info>>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    let ix = instruction(
+        INIT_EMPTY_MERKLE_TREE,
+        (max_depth, max_buffer_size),
+        modify_accounts(&ctx.accounts),
+        &[],
+    )?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &ToAccountInfos::to_account_infos(&ctx),
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+pub fn append<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Modify<'info>>,
+    leaf: [u8; 32],
+) -> Result<()> {
+    let ix = instruction(APPEND, leaf, modify_accounts(&ctx.accounts), &[])?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &ToAccountInfos::to_account_infos(&ctx),
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Replaces the leaf at `index`, the proof nodes being passed as the remaining
+/// accounts of the context, ordered from the leaf to the root.
+pub fn replace_leaf<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Modify<'info>>,
+    root: [u8; 32],
+    previous_leaf: [u8; 32],
+    new_leaf: [u8; 32],
+    index: u32,
+) -> Result<()> {
+    let ix = instruction(
+        REPLACE_LEAF,
+        (root, previous_leaf, new_leaf, index),
+        modify_accounts(&ctx.accounts),
+        &ctx.remaining_accounts,
+    )?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &ToAccountInfos::to_account_infos(&ctx),
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Verifies that `leaf` is in the tree at `index`, the proof nodes being
+/// passed as the remaining accounts of the context.
+pub fn verify_leaf<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, VerifyLeaf<'info>>,
+    root: [u8; 32],
+    leaf: [u8; 32],
+    index: u32,
+) -> Result<()> {
+    let ix = instruction(
+        VERIFY_LEAF,
+        (root, leaf, index),
+        vec![AccountMeta::new_readonly(
+            *ctx.accounts.merkle_tree.key,
+            false,
+        )],
+        &ctx.remaining_accounts,
+    )?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &ToAccountInfos::to_account_infos(&ctx),
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Logs `data` by invoking the noop program, which makes it available to
+/// indexers in the transaction's inner instructions without being truncated
+/// like program logs can be.
+pub fn wrap_application_data<'info>(noop: &AccountInfo<'info>, data: Vec<u8>) -> Result<()> {
+    let ix = Instruction {
+        program_id: NOOP_ID,
+        accounts: vec![],
+        data,
+    };
+    anchor_lang::solana_program::program::invoke(&ix, &[noop.clone()]).map_err(Into::into)
+}
+
+/// Emits an event through a CPI to the noop program.
+///
+/// The noop program account must be passed as the first argument. The event
+/// is serialized like with `emit!`, its discriminator followed by its borsh
+/// encoding, and can be read back from the inner instructions of the
+/// transaction, e.g. with `anchor_client::parse_noop_events`.
+///
+/// # Example
+/// ```ignore
+/// emit_compressed!(
+///     ctx.accounts.noop,
+///     LeafUpdated {
+///         index,
+///         leaf,
+///     }
+/// );
+/// ```
+#[macro_export]
+macro_rules! emit_compressed {
+    ($noop:expr, $event:expr) => {
+        $crate::account_compression::wrap_application_data(
+            &anchor_lang::ToAccountInfo::to_account_info(&$noop),
+            anchor_lang::Event::data(&$event),
+        )?;
+    };
+}
+
+#[derive(Accounts)]
+pub struct Modify<'info> {
+    pub merkle_tree: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub noop: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyLeaf<'info> {
+    pub merkle_tree: AccountInfo<'info>,
+}
+
+/// Header and current root of a concurrent merkle tree account.
+///
+/// Only the header and the root are read since trees can be large, the
+/// change logs and canopy are left to the compression program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConcurrentMerkleTree {
+    pub max_buffer_size: u32,
+    pub max_depth: u32,
+    pub authority: Pubkey,
+    pub creation_slot: u64,
+    pub sequence_number: u64,
+    pub root: [u8; 32],
+}
+
+impl anchor_lang::AccountDeserialize for ConcurrentMerkleTree {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        // Account type discriminator of initialized concurrent merkle trees.
+        if buf.first() != Some(&1) {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let data: &[u8] = buf;
+
+        // Header: account type, header version, max buffer size, max depth,
+        // authority, creation slot and padding.
+        let max_buffer_size = u32::from_le_bytes(read(data, 2)?);
+        let max_depth = u32::from_le_bytes(read(data, 6)?);
+        let authority = Pubkey::new_from_array(read(data, 10)?);
+        let creation_slot = u64::from_le_bytes(read(data, 42)?);
+
+        // Tree: sequence number, active index, buffer size and change logs,
+        // each starting with the root it produced.
+        let tree = CONCURRENT_MERKLE_TREE_HEADER_SIZE;
+        let sequence_number = u64::from_le_bytes(read(data, tree)?);
+        let active_index = u64::from_le_bytes(read(data, tree + 8)?);
+        if active_index >= u64::from(max_buffer_size) {
+            return Err(ErrorCode::AccountDidNotDeserialize.into());
+        }
+        let root_offset = (max_depth as usize)
+            .checked_mul(32)
+            .and_then(|proof| proof.checked_add(32 + 8))
+            .and_then(|change_log| change_log.checked_mul(active_index as usize))
+            .and_then(|change_logs| change_logs.checked_add(tree + 24))
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        let root = read(data, root_offset)?;
+
+        Ok(Self {
+            max_buffer_size,
+            max_depth,
+            authority,
+            creation_slot,
+            sequence_number,
+            root,
+        })
+    }
+}
+
+fn read<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    offset
+        .checked_add(N)
+        .and_then(|end| data.get(offset..end))
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+impl anchor_lang::AccountSerialize for ConcurrentMerkleTree {}
+
+impl anchor_lang::Owner for ConcurrentMerkleTree {
+    fn owner() -> Pubkey {
+        ID
+    }
+}
+
+#[derive(Clone)]
+pub struct SplAccountCompression;
+
+impl anchor_lang::Id for SplAccountCompression {
+    fn id() -> Pubkey {
+        ID
+    }
+}
+
+#[derive(Clone)]
+pub struct Noop;
+
+impl anchor_lang::Id for Noop {
+    fn id() -> Pubkey {
+        NOOP_ID
+    }
+}
@@ -64,7 +64,7 @@ pub struct Create<'info> {
     pub token_program: AccountInfo<'info>,
 }
 
-type CreateIdempotent<'info> = Create<'info>;
+pub type CreateIdempotent<'info> = Create<'info>;
 
 #[derive(Clone)]
 pub struct AssociatedToken;
@@ -1,3 +1,401 @@
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::borsh;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{
+    context::CpiContext, Accounts, AnchorDeserialize, AnchorSerialize, Result, ToAccountInfos,
+};
+
+// Id of the main SPL Governance deployment. DAOs commonly run their own
+// deployment, so the CPI wrappers below invoke the program of the context
+// instead of this id.
+anchor_lang::declare_id!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+// Indexes of the instructions in spl-governance's `GovernanceInstruction`.
+const DEPOSIT_GOVERNING_TOKENS: u8 = 1;
+const CREATE_PROPOSAL: u8 = 6;
+const CAST_VOTE: u8 = 13;
+const EXECUTE_TRANSACTION: u8 = 16;
+
+/// Account type of `TokenOwnerRecordV2` in spl-governance's
+/// `GovernanceAccountType`.
+const TOKEN_OWNER_RECORD_V2: u8 = 17;
+
+const PROGRAM_AUTHORITY_SEED: &[u8] = b"governance";
+
+/// Returns the address of the token owner record of `governing_token_owner`.
+pub fn get_token_owner_record_address(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    governing_token_mint: &Pubkey,
+    governing_token_owner: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PROGRAM_AUTHORITY_SEED,
+            realm.as_ref(),
+            governing_token_mint.as_ref(),
+            governing_token_owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// Returns the address of the realm's token account holding the deposited
+/// governing tokens.
+pub fn get_governing_token_holding_address(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    governing_token_mint: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PROGRAM_AUTHORITY_SEED,
+            realm.as_ref(),
+            governing_token_mint.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// Returns the address of the realm's config account.
+pub fn get_realm_config_address(program_id: &Pubkey, realm: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"realm-config", realm.as_ref()], program_id).0
+}
+
+/// Returns the address of the proposal created with `proposal_seed`.
+pub fn get_proposal_address(
+    program_id: &Pubkey,
+    governance: &Pubkey,
+    governing_token_mint: &Pubkey,
+    proposal_seed: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PROGRAM_AUTHORITY_SEED,
+            governance.as_ref(),
+            governing_token_mint.as_ref(),
+            proposal_seed.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// Returns the address of the deposit paid by `payer` to create a proposal.
+pub fn get_proposal_deposit_address(
+    program_id: &Pubkey,
+    proposal: &Pubkey,
+    payer: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"proposal-deposit", proposal.as_ref(), payer.as_ref()],
+        program_id,
+    )
+    .0
+}
+
+/// Returns the address of the vote record of `token_owner_record` on a
+/// proposal.
+pub fn get_vote_record_address(
+    program_id: &Pubkey,
+    proposal: &Pubkey,
+    token_owner_record: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PROGRAM_AUTHORITY_SEED,
+            proposal.as_ref(),
+            token_owner_record.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultiChoiceType {
+    FullWeight,
+    Weighted,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoteType {
+    SingleChoice,
+    MultiChoice {
+        choice_type: MultiChoiceType,
+        min_voter_options: u8,
+        max_voter_options: u8,
+        max_winning_options: u8,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoteChoice {
+    pub rank: u8,
+    pub weight_percentage: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Vote {
+    Approve(Vec<VoteChoice>),
+    Deny,
+    Abstain,
+    Veto,
+}
+
+fn instruction(
+    program_id: &Pubkey,
+    index: u8,
+    args: impl AnchorSerialize,
+    accounts: Vec<AccountMeta>,
+) -> Result<Instruction> {
+    let mut data = vec![index];
+    args.serialize(&mut data)
+        .map_err(|_| ErrorCode::InstructionDidNotSerialize)?;
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+pub fn deposit_governing_tokens<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, DepositGoverningTokens<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let ix = instruction(
+        ctx.program.key,
+        DEPOSIT_GOVERNING_TOKENS,
+        amount,
+        vec![
+            AccountMeta::new_readonly(*ctx.accounts.realm.key, false),
+            AccountMeta::new(*ctx.accounts.governing_token_holding.key, false),
+            AccountMeta::new(*ctx.accounts.governing_token_source.key, false),
+            AccountMeta::new_readonly(*ctx.accounts.governing_token_owner.key, true),
+            AccountMeta::new_readonly(*ctx.accounts.governing_token_source_authority.key, true),
+            AccountMeta::new(*ctx.accounts.token_owner_record.key, false),
+            AccountMeta::new(*ctx.accounts.payer.key, true),
+            AccountMeta::new_readonly(*ctx.accounts.system_program.key, false),
+            AccountMeta::new_readonly(*ctx.accounts.token_program.key, false),
+            AccountMeta::new_readonly(*ctx.accounts.realm_config.key, false),
+        ],
+    )?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &ToAccountInfos::to_account_infos(&ctx),
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_proposal<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, CreateProposal<'info>>,
+    name: String,
+    description_link: String,
+    vote_type: VoteType,
+    options: Vec<String>,
+    use_deny_option: bool,
+    proposal_seed: Pubkey,
+    voter_weight_record: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*ctx.accounts.realm.key, false),
+        AccountMeta::new(*ctx.accounts.proposal.key, false),
+        AccountMeta::new(*ctx.accounts.governance.key, false),
+        AccountMeta::new(*ctx.accounts.proposal_owner_record.key, false),
+        AccountMeta::new_readonly(*ctx.accounts.governing_token_mint.key, false),
+        AccountMeta::new_readonly(*ctx.accounts.governance_authority.key, true),
+        AccountMeta::new(*ctx.accounts.payer.key, true),
+        AccountMeta::new_readonly(*ctx.accounts.system_program.key, false),
+        AccountMeta::new_readonly(*ctx.accounts.realm_config.key, false),
+    ];
+    if let Some(v) = &voter_weight_record {
+        accounts.push(AccountMeta::new_readonly(*v.key, false));
+    }
+    accounts.push(AccountMeta::new(*ctx.accounts.proposal_deposit.key, false));
+    let ix = instruction(
+        ctx.program.key,
+        CREATE_PROPOSAL,
+        (
+            name,
+            description_link,
+            vote_type,
+            options,
+            use_deny_option,
+            proposal_seed,
+        ),
+        accounts,
+    )?;
+    let mut account_infos = ToAccountInfos::to_account_infos(&ctx);
+    if let Some(v) = voter_weight_record {
+        account_infos.push(v);
+    }
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
+pub fn cast_vote<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, CastVote<'info>>,
+    vote: Vote,
+    voter_weight_record: Option<AccountInfo<'info>>,
+    max_voter_weight_record: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*ctx.accounts.realm.key, false),
+        AccountMeta::new(*ctx.accounts.governance.key, false),
+        AccountMeta::new(*ctx.accounts.proposal.key, false),
+        AccountMeta::new(*ctx.accounts.proposal_owner_record.key, false),
+        AccountMeta::new(*ctx.accounts.voter_token_owner_record.key, false),
+        AccountMeta::new_readonly(*ctx.accounts.governance_authority.key, true),
+        AccountMeta::new(*ctx.accounts.vote_record.key, false),
+        AccountMeta::new_readonly(*ctx.accounts.governing_token_mint.key, false),
+        AccountMeta::new(*ctx.accounts.payer.key, true),
+        AccountMeta::new_readonly(*ctx.accounts.system_program.key, false),
+        AccountMeta::new_readonly(*ctx.accounts.realm_config.key, false),
+    ];
+    let mut account_infos = ToAccountInfos::to_account_infos(&ctx);
+    for record in [voter_weight_record, max_voter_weight_record]
+        .into_iter()
+        .flatten()
+    {
+        accounts.push(AccountMeta::new_readonly(*record.key, false));
+        account_infos.push(record);
+    }
+    let ix = instruction(ctx.program.key, CAST_VOTE, vote, accounts)?;
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
+/// Executes a proposal transaction, the program and the accounts of its
+/// instructions being passed as the remaining accounts of the context.
+pub fn execute_transaction<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, ExecuteTransaction<'info>>,
+) -> Result<()> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*ctx.accounts.governance.key, false),
+        AccountMeta::new(*ctx.accounts.proposal.key, false),
+        AccountMeta::new(*ctx.accounts.proposal_transaction.key, false),
+    ];
+    // The governance PDA signs for the instructions itself, so it must not
+    // be required to sign here.
+    accounts.extend(ctx.remaining_accounts.iter().map(|account| {
+        let is_signer = account.is_signer && account.key != ctx.accounts.governance.key;
+        if account.is_writable {
+            AccountMeta::new(*account.key, is_signer)
+        } else {
+            AccountMeta::new_readonly(*account.key, is_signer)
+        }
+    }));
+    let ix = instruction(ctx.program.key, EXECUTE_TRANSACTION, (), accounts)?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &ToAccountInfos::to_account_infos(&ctx),
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct DepositGoverningTokens<'info> {
+    pub realm: AccountInfo<'info>,
+    pub governing_token_holding: AccountInfo<'info>,
+    pub governing_token_source: AccountInfo<'info>,
+    pub governing_token_owner: AccountInfo<'info>,
+    pub governing_token_source_authority: AccountInfo<'info>,
+    pub token_owner_record: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub realm_config: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    pub realm: AccountInfo<'info>,
+    pub proposal: AccountInfo<'info>,
+    pub governance: AccountInfo<'info>,
+    pub proposal_owner_record: AccountInfo<'info>,
+    pub governing_token_mint: AccountInfo<'info>,
+    pub governance_authority: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub realm_config: AccountInfo<'info>,
+    pub proposal_deposit: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    pub realm: AccountInfo<'info>,
+    pub governance: AccountInfo<'info>,
+    pub proposal: AccountInfo<'info>,
+    pub proposal_owner_record: AccountInfo<'info>,
+    pub voter_token_owner_record: AccountInfo<'info>,
+    pub governance_authority: AccountInfo<'info>,
+    pub vote_record: AccountInfo<'info>,
+    pub governing_token_mint: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub realm_config: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTransaction<'info> {
+    pub governance: AccountInfo<'info>,
+    pub proposal: AccountInfo<'info>,
+    pub proposal_transaction: AccountInfo<'info>,
+}
+
+/// Leading fields of a `TokenOwnerRecordV2`, the deposit of a governing token
+/// owner in a realm.
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TokenOwnerRecord {
+    pub account_type: u8,
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub governing_token_deposit_amount: u64,
+    pub unrelinquished_votes_count: u64,
+    pub outstanding_proposal_count: u8,
+    pub version: u8,
+    pub reserved: [u8; 6],
+    pub governance_delegate: Option<Pubkey>,
+}
+
+impl anchor_lang::AccountDeserialize for TokenOwnerRecord {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        if buf.first() != Some(&TOKEN_OWNER_RECORD_V2) {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        AnchorDeserialize::deserialize(buf).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+impl anchor_lang::AccountSerialize for TokenOwnerRecord {}
+
+impl anchor_lang::Owner for TokenOwnerRecord {
+    fn owner() -> Pubkey {
+        ID
+    }
+}
+
+#[derive(Clone)]
+pub struct Governance;
+
+impl anchor_lang::Id for Governance {
+    fn id() -> Pubkey {
+        ID
+    }
+}
+
 /// A macro is exposed so that we can embed the program ID.
 #[macro_export]
 macro_rules! vote_weight_record {
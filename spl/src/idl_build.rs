@@ -15,6 +15,12 @@ macro_rules! impl_idl_build {
     };
 }
 
+#[cfg(feature = "account_compression")]
+impl_idl_build!(crate::account_compression::ConcurrentMerkleTree);
+
+#[cfg(feature = "governance")]
+impl_idl_build!(crate::governance::TokenOwnerRecord);
+
 #[cfg(feature = "metadata")]
 impl_idl_build!(crate::metadata::MetadataAccount);
 #[cfg(feature = "metadata")]
@@ -2,6 +2,9 @@
 
 //! Anchor CPI wrappers for popular programs in the Solana ecosystem.
 
+#[cfg(feature = "account_compression")]
+pub mod account_compression;
+
 #[cfg(feature = "associated_token")]
 pub mod associated_token;
 
@@ -20,6 +23,9 @@ pub mod token_2022_extensions;
 #[cfg(feature = "token_2022")]
 pub mod token_interface;
 
+#[cfg(feature = "token_2022")]
+pub mod vault;
+
 #[cfg(feature = "dex")]
 pub mod dex;
 
@@ -1,6 +1,8 @@
+use anchor_lang::solana_program::account_info::AccountInfo;
 use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::sysvar::Introspection;
 use anchor_lang::Result;
-use anchor_lang::{context::CpiContext, Accounts};
+use anchor_lang::{context::CpiContext, error_code, Accounts};
 
 pub use spl_memo;
 pub use spl_memo::ID;
@@ -24,6 +26,47 @@ pub fn build_memo<'info>(ctx: CpiContext<'_, '_, '_, 'info, BuildMemo>, memo: &[
 #[derive(Accounts)]
 pub struct BuildMemo {}
 
+/// Errors of the memo helpers, offset to not collide with the errors of the
+/// calling program.
+#[error_code(offset = 10_000)]
+pub enum MemoError {
+    /// 10000 - The transaction does not contain the required memo
+    #[msg("The transaction does not contain the required memo")]
+    MemoMissing,
+}
+
+/// Requires the transaction to contain a top-level memo instruction whose
+/// content is exactly `memo`, using the given `Instructions` sysvar account.
+///
+/// Both the current and the legacy (v1) memo programs are accepted. The memo
+/// can be placed anywhere in the transaction. Fails with
+/// [`MemoError::MemoMissing`] otherwise.
+///
+/// # Example
+/// ```ignore
+/// #[derive(Accounts)]
+/// pub struct Withdraw<'info> {
+///     /// CHECK: Validated by `require_memo`.
+///     #[account(address = sysvar::instructions::ID)]
+///     pub instructions: UncheckedAccount<'info>,
+/// }
+///
+/// pub fn withdraw(ctx: Context<Withdraw>, reference: String) -> Result<()> {
+///     memo::require_memo(&ctx.accounts.instructions, reference.as_bytes())?;
+///     Ok(())
+/// }
+/// ```
+pub fn require_memo(instructions: &AccountInfo, memo: &[u8]) -> Result<()> {
+    let ixs = Introspection::from_account_info(instructions)?;
+    for ix in ixs.iter() {
+        let (_, ix) = ix?;
+        if (ix.program_id == ID || ix.program_id == spl_memo::v1::ID) && ix.data == memo {
+            return Ok(());
+        }
+    }
+    Err(MemoError::MemoMissing.into())
+}
+
 #[derive(Clone)]
 pub struct Memo;
 
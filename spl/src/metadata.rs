@@ -546,6 +546,181 @@ pub fn unverify_sized_collection_item<'info>(
     .map_err(Into::into)
 }
 
+/// Accounts of the token auth rules program and of the rule set enforced on a
+/// programmable NFT, required by the pNFT instructions when the NFT has a rule
+/// set.
+pub struct AuthorizationRules<'info> {
+    pub program: AccountInfo<'info>,
+    pub rule_set: AccountInfo<'info>,
+}
+
+fn invoke_with_authorization_rules<
+    'info,
+    T: ToAccountInfos<'info> + anchor_lang::ToAccountMetas,
+>(
+    ix: &anchor_lang::solana_program::instruction::Instruction,
+    ctx: &CpiContext<'_, '_, '_, 'info, T>,
+    authorization_rules: Option<AuthorizationRules<'info>>,
+) -> Result<()> {
+    let mut account_infos = ToAccountInfos::to_account_infos(ctx);
+    if let Some(rules) = authorization_rules {
+        account_infos.push(rules.program);
+        account_infos.push(rules.rule_set);
+    }
+    anchor_lang::solana_program::program::invoke_signed(ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
+/// Transfers a programmable NFT, creating the destination token account and
+/// token record if needed.
+pub fn transfer_v1<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferV1<'info>>,
+    amount: u64,
+    authorization_rules: Option<AuthorizationRules<'info>>,
+) -> Result<()> {
+    let ix = mpl_token_metadata::instructions::TransferV1 {
+        token: *ctx.accounts.token.key,
+        token_owner: *ctx.accounts.token_owner.key,
+        destination_token: *ctx.accounts.destination_token.key,
+        destination_owner: *ctx.accounts.destination_owner.key,
+        mint: *ctx.accounts.mint.key,
+        metadata: *ctx.accounts.metadata.key,
+        edition: Some(*ctx.accounts.edition.key),
+        token_record: Some(*ctx.accounts.token_record.key),
+        destination_token_record: Some(*ctx.accounts.destination_token_record.key),
+        authority: *ctx.accounts.authority.key,
+        payer: *ctx.accounts.payer.key,
+        system_program: system_program::ID,
+        sysvar_instructions: sysvar::instructions::ID,
+        spl_token_program: *ctx.accounts.spl_token.key,
+        spl_ata_program: *ctx.accounts.spl_ata.key,
+        authorization_rules_program: authorization_rules.as_ref().map(|r| *r.program.key),
+        authorization_rules: authorization_rules.as_ref().map(|r| *r.rule_set.key),
+    }
+    .instruction(
+        mpl_token_metadata::instructions::TransferV1InstructionArgs {
+            amount,
+            authorization_data: None,
+        },
+    );
+    invoke_with_authorization_rules(&ix, &ctx, authorization_rules)
+}
+
+/// Approves a transfer delegate on a programmable NFT.
+pub fn delegate_transfer_v1<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, DelegateV1<'info>>,
+    amount: u64,
+    authorization_rules: Option<AuthorizationRules<'info>>,
+) -> Result<()> {
+    let ix = mpl_token_metadata::instructions::DelegateTransferV1 {
+        delegate_record: None,
+        delegate: *ctx.accounts.delegate.key,
+        metadata: *ctx.accounts.metadata.key,
+        master_edition: Some(*ctx.accounts.edition.key),
+        token_record: Some(*ctx.accounts.token_record.key),
+        mint: *ctx.accounts.mint.key,
+        token: *ctx.accounts.token.key,
+        authority: *ctx.accounts.authority.key,
+        payer: *ctx.accounts.payer.key,
+        system_program: system_program::ID,
+        sysvar_instructions: sysvar::instructions::ID,
+        spl_token_program: Some(*ctx.accounts.spl_token.key),
+        authorization_rules_program: authorization_rules.as_ref().map(|r| *r.program.key),
+        authorization_rules: authorization_rules.as_ref().map(|r| *r.rule_set.key),
+    }
+    .instruction(
+        mpl_token_metadata::instructions::DelegateTransferV1InstructionArgs {
+            amount,
+            authorization_data: None,
+        },
+    );
+    invoke_with_authorization_rules(&ix, &ctx, authorization_rules)
+}
+
+/// Approves a utility delegate on a programmable NFT, which can lock, unlock
+/// and burn it.
+pub fn delegate_utility_v1<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, DelegateV1<'info>>,
+    amount: u64,
+    authorization_rules: Option<AuthorizationRules<'info>>,
+) -> Result<()> {
+    let ix = mpl_token_metadata::instructions::DelegateUtilityV1 {
+        delegate_record: None,
+        delegate: *ctx.accounts.delegate.key,
+        metadata: *ctx.accounts.metadata.key,
+        master_edition: Some(*ctx.accounts.edition.key),
+        token_record: Some(*ctx.accounts.token_record.key),
+        mint: *ctx.accounts.mint.key,
+        token: *ctx.accounts.token.key,
+        authority: *ctx.accounts.authority.key,
+        payer: *ctx.accounts.payer.key,
+        system_program: system_program::ID,
+        sysvar_instructions: sysvar::instructions::ID,
+        spl_token_program: Some(*ctx.accounts.spl_token.key),
+        authorization_rules_program: authorization_rules.as_ref().map(|r| *r.program.key),
+        authorization_rules: authorization_rules.as_ref().map(|r| *r.rule_set.key),
+    }
+    .instruction(
+        mpl_token_metadata::instructions::DelegateUtilityV1InstructionArgs {
+            amount,
+            authorization_data: None,
+        },
+    );
+    invoke_with_authorization_rules(&ix, &ctx, authorization_rules)
+}
+
+/// Locks a programmable NFT, the authority being its utility delegate.
+pub fn lock_v1<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, LockV1<'info>>,
+    authorization_rules: Option<AuthorizationRules<'info>>,
+) -> Result<()> {
+    let ix = mpl_token_metadata::instructions::LockV1 {
+        authority: *ctx.accounts.authority.key,
+        token_owner: Some(*ctx.accounts.token_owner.key),
+        token: *ctx.accounts.token.key,
+        mint: *ctx.accounts.mint.key,
+        metadata: *ctx.accounts.metadata.key,
+        edition: Some(*ctx.accounts.edition.key),
+        token_record: Some(*ctx.accounts.token_record.key),
+        payer: *ctx.accounts.payer.key,
+        system_program: system_program::ID,
+        sysvar_instructions: sysvar::instructions::ID,
+        spl_token_program: Some(*ctx.accounts.spl_token.key),
+        authorization_rules_program: authorization_rules.as_ref().map(|r| *r.program.key),
+        authorization_rules: authorization_rules.as_ref().map(|r| *r.rule_set.key),
+    }
+    .instruction(mpl_token_metadata::instructions::LockV1InstructionArgs {
+        authorization_data: None,
+    });
+    invoke_with_authorization_rules(&ix, &ctx, authorization_rules)
+}
+
+/// Unlocks a programmable NFT locked with [`lock_v1`].
+pub fn unlock_v1<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, UnlockV1<'info>>,
+    authorization_rules: Option<AuthorizationRules<'info>>,
+) -> Result<()> {
+    let ix = mpl_token_metadata::instructions::UnlockV1 {
+        authority: *ctx.accounts.authority.key,
+        token_owner: Some(*ctx.accounts.token_owner.key),
+        token: *ctx.accounts.token.key,
+        mint: *ctx.accounts.mint.key,
+        metadata: *ctx.accounts.metadata.key,
+        edition: Some(*ctx.accounts.edition.key),
+        token_record: Some(*ctx.accounts.token_record.key),
+        payer: *ctx.accounts.payer.key,
+        system_program: system_program::ID,
+        sysvar_instructions: sysvar::instructions::ID,
+        spl_token_program: Some(*ctx.accounts.spl_token.key),
+        authorization_rules_program: authorization_rules.as_ref().map(|r| *r.program.key),
+        authorization_rules: authorization_rules.as_ref().map(|r| *r.rule_set.key),
+    }
+    .instruction(mpl_token_metadata::instructions::UnlockV1InstructionArgs {
+        authorization_data: None,
+    });
+    invoke_with_authorization_rules(&ix, &ctx, authorization_rules)
+}
+
 #[derive(Accounts)]
 pub struct ApproveCollectionAuthority<'info> {
     pub collection_authority_record: AccountInfo<'info>,
@@ -777,6 +952,57 @@ pub struct UnverifySizedCollectionItem<'info> {
     pub collection_master_edition_account: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct TransferV1<'info> {
+    pub token: AccountInfo<'info>,
+    pub token_owner: AccountInfo<'info>,
+    pub destination_token: AccountInfo<'info>,
+    pub destination_owner: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub edition: AccountInfo<'info>,
+    pub token_record: AccountInfo<'info>,
+    pub destination_token_record: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub spl_token: AccountInfo<'info>,
+    pub spl_ata: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateV1<'info> {
+    pub delegate: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub edition: AccountInfo<'info>,
+    pub token_record: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub token: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub spl_token: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LockV1<'info> {
+    pub authority: AccountInfo<'info>,
+    pub token_owner: AccountInfo<'info>,
+    pub token: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub edition: AccountInfo<'info>,
+    pub token_record: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub spl_token: AccountInfo<'info>,
+}
+
+pub type UnlockV1<'info> = LockV1<'info>;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct MetadataAccount(mpl_token_metadata::accounts::Metadata);
 
@@ -848,6 +1074,11 @@ pub struct TokenRecordAccount(mpl_token_metadata::accounts::TokenRecord);
 
 impl TokenRecordAccount {
     pub const LEN: usize = mpl_token_metadata::accounts::TokenRecord::LEN;
+
+    /// Whether the programmable NFT is locked, e.g. by [`lock_v1`].
+    pub fn is_locked(&self) -> bool {
+        self.state == mpl_token_metadata::types::TokenState::Locked
+    }
 }
 impl anchor_lang::AccountDeserialize for TokenRecordAccount {
     fn try_deserialize(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
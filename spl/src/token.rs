@@ -28,6 +28,26 @@ pub fn transfer<'info>(
     .map_err(Into::into)
 }
 
+/// Same as [`transfer`] for a multisig authority, signed by `signers`.
+pub fn transfer_multisig<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Transfer<'info>>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let ix = spl_token::instruction::transfer(
+        &spl_token::ID,
+        ctx.accounts.from.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &signers.iter().map(|signer| signer.key).collect::<Vec<_>>(),
+        amount,
+    )?;
+    let mut account_infos = vec![ctx.accounts.from, ctx.accounts.to, ctx.accounts.authority];
+    account_infos.extend_from_slice(signers);
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
 pub fn transfer_checked<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
     amount: u64,
@@ -56,6 +76,44 @@ pub fn transfer_checked<'info>(
     .map_err(Into::into)
 }
 
+/// Same as [`transfer_checked`] for a multisig authority, signed by `signers`.
+pub fn transfer_checked_multisig<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let ix = spl_token::instruction::transfer_checked(
+        &spl_token::ID,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &signers.iter().map(|signer| signer.key).collect::<Vec<_>>(),
+        amount,
+        decimals,
+    )?;
+    let mut account_infos = vec![
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+    ];
+    account_infos.extend_from_slice(signers);
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
+/// Same as [`transfer_checked`], reading the decimals from the mint account
+/// of the context.
+pub fn transfer_checked_auto<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let decimals = spl_token::state::Mint::unpack(&ctx.accounts.mint.try_borrow_data()?)?.decimals;
+    transfer_checked(ctx, amount, decimals)
+}
+
 pub fn mint_to<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, MintTo<'info>>,
     amount: u64,
@@ -76,6 +134,26 @@ pub fn mint_to<'info>(
     .map_err(Into::into)
 }
 
+/// Same as [`mint_to`] for a multisig authority, signed by `signers`.
+pub fn mint_to_multisig<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, MintTo<'info>>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let ix = spl_token::instruction::mint_to(
+        &spl_token::ID,
+        ctx.accounts.mint.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &signers.iter().map(|signer| signer.key).collect::<Vec<_>>(),
+        amount,
+    )?;
+    let mut account_infos = vec![ctx.accounts.to, ctx.accounts.mint, ctx.accounts.authority];
+    account_infos.extend_from_slice(signers);
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
 pub fn burn<'info>(ctx: CpiContext<'_, '_, '_, 'info, Burn<'info>>, amount: u64) -> Result<()> {
     let ix = spl_token::instruction::burn(
         &spl_token::ID,
@@ -93,6 +171,26 @@ pub fn burn<'info>(ctx: CpiContext<'_, '_, '_, 'info, Burn<'info>>, amount: u64)
     .map_err(Into::into)
 }
 
+/// Same as [`burn`] for a multisig authority, signed by `signers`.
+pub fn burn_multisig<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Burn<'info>>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let ix = spl_token::instruction::burn(
+        &spl_token::ID,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.authority.key,
+        &signers.iter().map(|signer| signer.key).collect::<Vec<_>>(),
+        amount,
+    )?;
+    let mut account_infos = vec![ctx.accounts.from, ctx.accounts.mint, ctx.accounts.authority];
+    account_infos.extend_from_slice(signers);
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
 pub fn approve<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, Approve<'info>>,
     amount: u64,
@@ -59,6 +59,51 @@ pub fn transfer_checked<'info>(
     .map_err(Into::into)
 }
 
+/// Same as [`transfer_checked`] for a multisig authority, signed by `signers`.
+pub fn transfer_checked_multisig<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let ix = spl_token_2022::instruction::transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &signers.iter().map(|signer| signer.key).collect::<Vec<_>>(),
+        amount,
+        decimals,
+    )?;
+    let mut account_infos = vec![
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+    ];
+    account_infos.extend_from_slice(signers);
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
+/// Same as [`transfer_checked`], reading the decimals from the mint account
+/// of the context, which can have extensions.
+pub fn transfer_checked_auto<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let decimals = {
+        let data = ctx.accounts.mint.try_borrow_data()?;
+        spl_token_2022::extension::StateWithExtensions::<spl_token_2022::state::Mint>::unpack(
+            &data,
+        )?
+        .base
+        .decimals
+    };
+    transfer_checked(ctx, amount, decimals)
+}
+
 pub fn mint_to<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, MintTo<'info>>,
     amount: u64,
@@ -79,6 +124,26 @@ pub fn mint_to<'info>(
     .map_err(Into::into)
 }
 
+/// Same as [`mint_to`] for a multisig authority, signed by `signers`.
+pub fn mint_to_multisig<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, MintTo<'info>>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let ix = spl_token_2022::instruction::mint_to(
+        ctx.program.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &signers.iter().map(|signer| signer.key).collect::<Vec<_>>(),
+        amount,
+    )?;
+    let mut account_infos = vec![ctx.accounts.to, ctx.accounts.mint, ctx.accounts.authority];
+    account_infos.extend_from_slice(signers);
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
 pub fn burn<'info>(ctx: CpiContext<'_, '_, '_, 'info, Burn<'info>>, amount: u64) -> Result<()> {
     let ix = spl_token_2022::instruction::burn(
         ctx.program.key,
@@ -96,6 +161,26 @@ pub fn burn<'info>(ctx: CpiContext<'_, '_, '_, 'info, Burn<'info>>, amount: u64)
     .map_err(Into::into)
 }
 
+/// Same as [`burn`] for a multisig authority, signed by `signers`.
+pub fn burn_multisig<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Burn<'info>>,
+    signers: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let ix = spl_token_2022::instruction::burn(
+        ctx.program.key,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.authority.key,
+        &signers.iter().map(|signer| signer.key).collect::<Vec<_>>(),
+        amount,
+    )?;
+    let mut account_infos = vec![ctx.accounts.from, ctx.accounts.mint, ctx.accounts.authority];
+    account_infos.extend_from_slice(signers);
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
 pub fn approve<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, Approve<'info>>,
     amount: u64,
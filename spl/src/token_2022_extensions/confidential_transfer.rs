@@ -1 +1,181 @@
-// waiting for labs to merge
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::Result;
+use anchor_lang::{context::CpiContext, Accounts};
+use spl_token_2022::extension::confidential_transfer::instruction;
+use spl_token_2022::proof::ProofLocation;
+use spl_token_2022::solana_zk_token_sdk::encryption::auth_encryption::AeCiphertext;
+
+// Proofs can't be verified in the same instruction when invoked through CPI,
+// so the wrappers below expect them to be pre-verified into context state
+// accounts passed as the first remaining account of the `CpiContext`.
+fn proof_context_account<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<AccountInfo<'info>> {
+    remaining_accounts
+        .first()
+        .cloned()
+        .ok_or_else(|| ErrorCode::AccountNotEnoughKeys.into())
+}
+
+fn invoke_all<'info>(
+    ixs: Vec<Instruction>,
+    account_infos: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    for ix in ixs {
+        anchor_lang::solana_program::program::invoke_signed(&ix, account_infos, signer_seeds)?;
+    }
+    Ok(())
+}
+
+pub fn confidential_transfer_configure_account<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, ConfidentialTransferConfigureAccount<'info>>,
+    decryptable_zero_balance: AeCiphertext,
+    maximum_pending_balance_credit_counter: u64,
+) -> Result<()> {
+    let proof_context = proof_context_account(&ctx.remaining_accounts)?;
+    let ixs = instruction::configure_account(
+        ctx.accounts.token_program_id.key,
+        ctx.accounts.token_account.key,
+        ctx.accounts.mint.key,
+        decryptable_zero_balance,
+        maximum_pending_balance_credit_counter,
+        ctx.accounts.authority.key,
+        &[],
+        ProofLocation::ContextStateAccount(proof_context.key),
+    )?;
+    invoke_all(
+        ixs,
+        &[
+            ctx.accounts.token_program_id,
+            ctx.accounts.token_account,
+            ctx.accounts.mint,
+            proof_context,
+            ctx.accounts.authority,
+        ],
+        ctx.signer_seeds,
+    )
+}
+
+#[derive(Accounts)]
+pub struct ConfidentialTransferConfigureAccount<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+pub fn confidential_transfer_deposit<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, ConfidentialTransferDeposit<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let ix = instruction::deposit(
+        ctx.accounts.token_program_id.key,
+        ctx.accounts.token_account.key,
+        ctx.accounts.mint.key,
+        amount,
+        decimals,
+        ctx.accounts.authority.key,
+        &[],
+    )?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.token_program_id,
+            ctx.accounts.token_account,
+            ctx.accounts.mint,
+            ctx.accounts.authority,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct ConfidentialTransferDeposit<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+pub fn confidential_transfer_withdraw<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, ConfidentialTransferWithdraw<'info>>,
+    amount: u64,
+    decimals: u8,
+    new_decryptable_available_balance: AeCiphertext,
+) -> Result<()> {
+    let proof_context = proof_context_account(&ctx.remaining_accounts)?;
+    let ixs = instruction::withdraw(
+        ctx.accounts.token_program_id.key,
+        ctx.accounts.token_account.key,
+        ctx.accounts.mint.key,
+        amount,
+        decimals,
+        new_decryptable_available_balance,
+        ctx.accounts.authority.key,
+        &[],
+        ProofLocation::ContextStateAccount(proof_context.key),
+    )?;
+    invoke_all(
+        ixs,
+        &[
+            ctx.accounts.token_program_id,
+            ctx.accounts.token_account,
+            ctx.accounts.mint,
+            proof_context,
+            ctx.accounts.authority,
+        ],
+        ctx.signer_seeds,
+    )
+}
+
+#[derive(Accounts)]
+pub struct ConfidentialTransferWithdraw<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+pub fn confidential_transfer_transfer<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, ConfidentialTransferTransfer<'info>>,
+    new_source_decryptable_available_balance: AeCiphertext,
+) -> Result<()> {
+    let proof_context = proof_context_account(&ctx.remaining_accounts)?;
+    let ixs = instruction::transfer(
+        ctx.accounts.token_program_id.key,
+        ctx.accounts.source.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.destination.key,
+        new_source_decryptable_available_balance,
+        ctx.accounts.authority.key,
+        &[],
+        ProofLocation::ContextStateAccount(proof_context.key),
+    )?;
+    invoke_all(
+        ixs,
+        &[
+            ctx.accounts.token_program_id,
+            ctx.accounts.source,
+            ctx.accounts.mint,
+            ctx.accounts.destination,
+            proof_context,
+            ctx.accounts.authority,
+        ],
+        ctx.signer_seeds,
+    )
+}
+
+#[derive(Accounts)]
+pub struct ConfidentialTransferTransfer<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub source: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
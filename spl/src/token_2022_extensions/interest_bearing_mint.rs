@@ -1,7 +1,11 @@
+use anchor_lang::error::ErrorCode;
 use anchor_lang::solana_program::account_info::AccountInfo;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang::Result;
 use anchor_lang::{context::CpiContext, Accounts};
+use spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, BaseStateWithExtensions, StateWithExtensions,
+};
 
 pub fn interest_bearing_mint_initialize<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, InterestBearingMintInitialize<'info>>,
@@ -57,3 +61,45 @@ pub struct InterestBearingMintUpdateRate<'info> {
     pub mint: AccountInfo<'info>,
     pub rate_authority: AccountInfo<'info>,
 }
+
+/// Converts a raw amount of an interest-bearing mint to its UI amount, i.e.
+/// with the interest accrued up to `unix_timestamp` applied and scaled by the
+/// mint decimals.
+///
+/// Mints without the extension are converted without any interest.
+pub fn interest_bearing_amount_to_ui_amount(
+    mint: &AccountInfo,
+    amount: u64,
+    unix_timestamp: i64,
+) -> Result<String> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let decimals = mint.base.decimals;
+    match mint.get_extension::<InterestBearingConfig>() {
+        Ok(config) => config
+            .amount_to_ui_amount(amount, decimals, unix_timestamp)
+            .ok_or_else(|| ErrorCode::MathOverflow.into()),
+        Err(_) => Ok(spl_token_2022::amount_to_ui_amount_string_trimmed(
+            amount, decimals,
+        )),
+    }
+}
+
+/// Converts a UI amount of an interest-bearing mint back to the raw amount,
+/// the inverse of [`interest_bearing_amount_to_ui_amount`].
+pub fn interest_bearing_ui_amount_to_amount(
+    mint: &AccountInfo,
+    ui_amount: &str,
+    unix_timestamp: i64,
+) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let decimals = mint.base.decimals;
+    match mint.get_extension::<InterestBearingConfig>() {
+        Ok(config) => Ok(config.try_ui_amount_into_amount(ui_amount, decimals, unix_timestamp)?),
+        Err(_) => Ok(spl_token_2022::try_ui_amount_into_amount(
+            ui_amount.to_string(),
+            decimals,
+        )?),
+    }
+}
@@ -11,11 +11,13 @@ pub mod metadata_pointer;
 pub mod mint_close_authority;
 pub mod non_transferable;
 pub mod permanent_delegate;
+pub mod state;
 pub mod token_group;
 pub mod token_metadata;
 pub mod transfer_fee;
 pub mod transfer_hook;
 
+pub use confidential_transfer::*;
 pub use cpi_guard::*;
 pub use default_account_state::*;
 pub use group_member_pointer::*;
@@ -27,10 +29,13 @@ pub use metadata_pointer::*;
 pub use mint_close_authority::*;
 pub use non_transferable::*;
 pub use permanent_delegate::*;
+pub use state::*;
 pub use token_group::*;
 pub use token_metadata::*;
 pub use transfer_fee::*;
 pub use transfer_hook::*;
 
 pub use spl_pod;
+pub use spl_token_group_interface;
 pub use spl_token_metadata_interface;
+pub use spl_transfer_hook_interface;
@@ -0,0 +1,91 @@
+#[cfg(feature = "token_2022")]
+use anchor_lang::accounts::interface_account::InterfaceAccount;
+#[cfg(feature = "token_2022")]
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::Result;
+use spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, metadata_pointer::MetadataPointer,
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, Extension, StateWithExtensions,
+};
+use spl_token_2022::solana_zk_token_sdk::instruction::Pod;
+use spl_token_group_interface::state::{TokenGroup, TokenGroupMember};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+#[cfg(feature = "token_2022")]
+use crate::token_interface::Mint;
+
+/// Typed accessors for the extension state of a Token-2022 mint.
+///
+/// Each accessor returns `None` if the extension isn't enabled on the mint,
+/// which is always the case for mints owned by the legacy token program. It's
+/// implemented for the `InterfaceAccount` mints of the `token_2022` feature.
+///
+/// # Example
+/// ```ignore
+/// use anchor_spl::token_interface::{Mint, MintExtensions};
+///
+/// pub fn quote(ctx: Context<Quote>, amount: u64) -> Result<u64> {
+///     let fee = match ctx.accounts.mint.transfer_fee_config()? {
+///         Some(config) => config
+///             .calculate_epoch_fee(Clock::get()?.epoch, amount)
+///             .ok_or(ErrorCode::Overflow)?,
+///         None => 0,
+///     };
+///     Ok(amount - fee)
+/// }
+/// ```
+pub trait MintExtensions {
+    /// Returns the data of a fixed size extension.
+    fn extension<T: Extension + Pod>(&self) -> Result<Option<T>>;
+
+    /// Returns the `TransferFeeConfig` extension.
+    fn transfer_fee_config(&self) -> Result<Option<TransferFeeConfig>> {
+        self.extension()
+    }
+
+    /// Returns the `InterestBearingConfig` extension.
+    fn interest_bearing_config(&self) -> Result<Option<InterestBearingConfig>> {
+        self.extension()
+    }
+
+    /// Returns the `MetadataPointer` extension.
+    fn metadata_pointer(&self) -> Result<Option<MetadataPointer>> {
+        self.extension()
+    }
+
+    /// Returns the `TokenGroup` stored in the mint itself.
+    fn token_group(&self) -> Result<Option<TokenGroup>> {
+        self.extension()
+    }
+
+    /// Returns the `TokenGroupMember` stored in the mint itself.
+    fn token_group_member(&self) -> Result<Option<TokenGroupMember>> {
+        self.extension()
+    }
+
+    /// Returns the `TokenMetadata` stored in the mint itself.
+    fn token_metadata(&self) -> Result<Option<TokenMetadata>>;
+}
+
+#[cfg(feature = "token_2022")]
+impl<'info> MintExtensions for InterfaceAccount<'info, Mint> {
+    fn extension<T: Extension + Pod>(&self) -> Result<Option<T>> {
+        let info: &AccountInfo = self.as_ref();
+        let data = info.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        if !mint.get_extension_types()?.contains(&T::TYPE) {
+            return Ok(None);
+        }
+        Ok(Some(*mint.get_extension::<T>()?))
+    }
+
+    fn token_metadata(&self) -> Result<Option<TokenMetadata>> {
+        let info: &AccountInfo = self.as_ref();
+        let data = info.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        if !mint.get_extension_types()?.contains(&TokenMetadata::TYPE) {
+            return Ok(None);
+        }
+        Ok(Some(mint.get_variable_len_extension::<TokenMetadata>()?))
+    }
+}
@@ -72,3 +72,61 @@ pub struct TokenMemberInitialize<'info> {
     pub group: AccountInfo<'info>,
     pub group_update_authority: AccountInfo<'info>,
 }
+
+pub fn token_group_update_max_size<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TokenGroupUpdateMaxSize<'info>>,
+    max_size: u32,
+) -> Result<()> {
+    let ix = spl_token_group_interface::instruction::update_group_max_size(
+        ctx.accounts.token_program_id.key,
+        ctx.accounts.group.key,
+        ctx.accounts.update_authority.key,
+        max_size,
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.token_program_id,
+            ctx.accounts.group,
+            ctx.accounts.update_authority,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct TokenGroupUpdateMaxSize<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub group: AccountInfo<'info>,
+    pub update_authority: AccountInfo<'info>,
+}
+
+pub fn token_group_update_authority<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TokenGroupUpdateAuthority<'info>>,
+    new_authority: Option<Pubkey>,
+) -> Result<()> {
+    let ix = spl_token_group_interface::instruction::update_group_authority(
+        ctx.accounts.token_program_id.key,
+        ctx.accounts.group.key,
+        ctx.accounts.current_authority.key,
+        new_authority,
+    );
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.token_program_id,
+            ctx.accounts.group,
+            ctx.accounts.current_authority,
+        ],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct TokenGroupUpdateAuthority<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub group: AccountInfo<'info>,
+    pub current_authority: AccountInfo<'info>,
+}
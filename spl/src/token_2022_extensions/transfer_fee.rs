@@ -2,6 +2,8 @@ use anchor_lang::solana_program::account_info::AccountInfo;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang::Result;
 use anchor_lang::{context::CpiContext, Accounts};
+use spl_token_2022::extension::transfer_fee::TransferFeeAmount;
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
 
 pub fn transfer_fee_initialize<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, TransferFeeInitialize<'info>>,
@@ -158,3 +160,80 @@ pub struct WithdrawWithheldTokensFromMint<'info> {
     pub destination: AccountInfo<'info>,
     pub authority: AccountInfo<'info>,
 }
+
+pub fn withdraw_withheld_tokens_from_accounts<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, WithdrawWithheldTokensFromAccounts<'info>>,
+    sources: Vec<AccountInfo<'info>>,
+) -> Result<()> {
+    let ix =
+        spl_token_2022::extension::transfer_fee::instruction::withdraw_withheld_tokens_from_accounts(
+            ctx.accounts.token_program_id.key,
+            ctx.accounts.mint.key,
+            ctx.accounts.destination.key,
+            ctx.accounts.authority.key,
+            &[],
+            sources.iter().map(|a| a.key).collect::<Vec<_>>().as_slice(),
+        )?;
+
+    let mut account_infos = vec![
+        ctx.accounts.token_program_id,
+        ctx.accounts.mint,
+        ctx.accounts.destination,
+        ctx.accounts.authority,
+    ];
+    account_infos.extend_from_slice(&sources);
+
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+        .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct WithdrawWithheldTokensFromAccounts<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+/// Returns the amount of transfer fees withheld in a token account, `0` if
+/// it doesn't have the `TransferFeeAmount` extension.
+pub fn get_withheld_amount(account_data: &[u8]) -> Result<u64> {
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(account_data)?;
+    match account.get_extension::<TransferFeeAmount>() {
+        Ok(fee_amount) => Ok(fee_amount.withheld_amount.into()),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Filters the given token accounts down to the ones holding withheld
+/// transfer fees, along with the withheld amount.
+///
+/// This is meant to be used by clients to find the sources to pass to
+/// [`harvest_withheld_tokens_to_mint`] or
+/// [`withdraw_withheld_tokens_from_accounts`].
+///
+/// # Example
+/// ```ignore
+/// let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref()))];
+/// let accounts = rpc.get_program_accounts_with_config(
+///     &spl_token_2022::ID,
+///     RpcProgramAccountsConfig {
+///         filters: Some(filters),
+///         ..Default::default()
+///     },
+/// )?;
+/// let sources = get_accounts_with_withheld_fees(
+///     accounts.iter().map(|(key, account)| (*key, account.data.as_slice())),
+/// );
+/// ```
+pub fn get_accounts_with_withheld_fees<'a>(
+    accounts: impl IntoIterator<Item = (Pubkey, &'a [u8])>,
+) -> Vec<(Pubkey, u64)> {
+    accounts
+        .into_iter()
+        .filter_map(|(key, data)| match get_withheld_amount(data) {
+            Ok(amount) if amount > 0 => Some((key, amount)),
+            _ => None,
+        })
+        .collect()
+}
@@ -1,7 +1,9 @@
 use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::instruction::Instruction;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang::Result;
 use anchor_lang::{context::CpiContext, Accounts};
+use spl_token_2022::extension::StateWithExtensions;
 
 pub fn transfer_hook_initialize<'info>(
     ctx: CpiContext<'_, '_, '_, 'info, TransferHookInitialize<'info>>,
@@ -57,3 +59,90 @@ pub struct TransferHookUpdate<'info> {
     pub mint: AccountInfo<'info>,
     pub authority: AccountInfo<'info>,
 }
+
+/// Transfers tokens of a mint with the transfer hook extension.
+///
+/// The extra accounts required by the hook must be passed as the remaining
+/// accounts of the context: the hook program, its extra account metas
+/// validation account (see [`get_extra_account_metas_address`]) and every
+/// account listed in it. They are resolved from the validation account and
+/// appended to the `transfer_checked` instruction, so they can be given in
+/// any order.
+pub fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferCheckedWithHook<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        ctx.accounts.token_program_id.key,
+        ctx.accounts.source,
+        ctx.accounts.mint,
+        ctx.accounts.destination,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[derive(Accounts)]
+pub struct TransferCheckedWithHook<'info> {
+    pub token_program_id: AccountInfo<'info>,
+    pub source: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+/// Returns the address of the account storing the extra account metas the
+/// transfer hook program requires for the given mint.
+pub fn get_extra_account_metas_address(mint: &Pubkey, hook_program_id: &Pubkey) -> Pubkey {
+    spl_transfer_hook_interface::get_extra_account_metas_address(mint, hook_program_id)
+}
+
+/// Returns the transfer hook program of the mint, if any.
+pub fn get_transfer_hook_program_id(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(spl_token_2022::extension::transfer_hook::get_program_id(
+        &mint,
+    ))
+}
+
+/// Resolves the extra accounts required by the transfer hook of `mint` from
+/// its validation account and appends them to a `transfer_checked`
+/// instruction built manually, along with their account infos.
+///
+/// `additional_accounts` must contain the hook program, the validation
+/// account and the accounts it lists. Instructions of mints without a
+/// transfer hook are left untouched.
+#[allow(clippy::too_many_arguments)]
+pub fn add_transfer_hook_accounts<'info>(
+    instruction: &mut Instruction,
+    account_infos: &mut Vec<AccountInfo<'info>>,
+    source: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    amount: u64,
+    additional_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let hook_program_id = match get_transfer_hook_program_id(&mint)? {
+        Some(hook_program_id) => hook_program_id,
+        None => return Ok(()),
+    };
+    spl_transfer_hook_interface::onchain::add_extra_accounts_for_execute_cpi(
+        instruction,
+        account_infos,
+        &hook_program_id,
+        source,
+        mint,
+        destination,
+        authority,
+        amount,
+        additional_accounts,
+    )
+    .map_err(Into::into)
+}
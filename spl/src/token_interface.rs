@@ -1,3 +1,4 @@
+use anchor_lang::context::CpiContext;
 use anchor_lang::solana_program::program_pack::Pack;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use spl_token_2022::extension::ExtensionType;
@@ -78,6 +79,99 @@ impl anchor_lang::Ids for TokenInterface {
     }
 }
 
+/// A token program flavor, i.e. either spl-token or spl-token-2022.
+///
+/// Accounts structs and handlers can be made generic over the flavor with a
+/// `Program<'info, T>` field instead of duplicating every instruction for both
+/// token programs. Unlike [`TokenInterface`], the token program is known at
+/// compile time, so instructions can be exposed once per flavor (each with its
+/// own program address in the IDL) while sharing a single implementation.
+///
+/// # Example
+/// ```ignore
+/// #[program]
+/// pub mod my_program {
+///     use super::*;
+///
+///     pub fn pay(ctx: Context<Pay<Token>>, amount: u64) -> Result<()> {
+///         pay_handler(ctx, amount)
+///     }
+///
+///     pub fn pay_2022(ctx: Context<Pay<Token2022>>, amount: u64) -> Result<()> {
+///         pay_handler(ctx, amount)
+///     }
+/// }
+///
+/// fn pay_handler<T: TokenFlavor>(ctx: Context<Pay<T>>, amount: u64) -> Result<()> {
+///     let accounts = TransferChecked {
+///         from: ctx.accounts.from.to_account_info(),
+///         mint: ctx.accounts.mint.to_account_info(),
+///         to: ctx.accounts.to.to_account_info(),
+///         authority: ctx.accounts.authority.to_account_info(),
+///     };
+///     let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), accounts);
+///     T::transfer(cpi_ctx, amount, ctx.accounts.mint.decimals)
+/// }
+///
+/// #[derive(Accounts)]
+/// pub struct Pay<'info, T: TokenFlavor> {
+///     #[account(mut, token::mint = mint, token::token_program = token_program)]
+///     pub from: InterfaceAccount<'info, TokenAccount>,
+///     #[account(mut, token::mint = mint, token::token_program = token_program)]
+///     pub to: InterfaceAccount<'info, TokenAccount>,
+///     #[account(mint::token_program = token_program)]
+///     pub mint: InterfaceAccount<'info, Mint>,
+///     pub authority: Signer<'info>,
+///     pub token_program: Program<'info, T>,
+/// }
+/// ```
+pub trait TokenFlavor: anchor_lang::Id + Clone {
+    /// Whether the token program supports token extensions.
+    const EXTENSIONS: bool;
+
+    /// Transfers tokens using the instruction best suited to the token
+    /// program, which is `transfer` for spl-token as it is cheaper and
+    /// `transfer_checked` for spl-token-2022 as it is required by mints with
+    /// transfer fees or transfer hooks.
+    fn transfer<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+        amount: u64,
+        decimals: u8,
+    ) -> anchor_lang::Result<()>;
+}
+
+#[cfg(feature = "token")]
+impl TokenFlavor for crate::token::Token {
+    const EXTENSIONS: bool = false;
+
+    fn transfer<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+        amount: u64,
+        _decimals: u8,
+    ) -> anchor_lang::Result<()> {
+        let accounts = crate::token::Transfer {
+            from: ctx.accounts.from,
+            to: ctx.accounts.to,
+            authority: ctx.accounts.authority,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.program, accounts, ctx.signer_seeds)
+            .with_remaining_accounts(ctx.remaining_accounts);
+        crate::token::transfer(cpi_ctx, amount)
+    }
+}
+
+impl TokenFlavor for Token2022 {
+    const EXTENSIONS: bool = true;
+
+    fn transfer<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+        amount: u64,
+        decimals: u8,
+    ) -> anchor_lang::Result<()> {
+        transfer_checked(ctx, amount, decimals)
+    }
+}
+
 pub type ExtensionsVec = Vec<ExtensionType>;
 
 pub fn find_mint_account_size(extensions: Option<&ExtensionsVec>) -> anchor_lang::Result<usize> {
@@ -99,3 +193,18 @@ pub fn get_mint_extension_data<T: Extension + Pod>(
     let extension_data = *mint_with_extension.get_extension::<T>()?;
     Ok(extension_data)
 }
+
+/// Returns the extensions enabled on a Token-2022 mint or token account.
+///
+/// Accounts owned by the legacy token program have no extensions, so an
+/// empty vec is returned for them.
+pub fn get_extension_types(
+    account: &anchor_lang::solana_program::account_info::AccountInfo,
+) -> anchor_lang::Result<ExtensionsVec> {
+    let data = account.data.borrow();
+    if let Ok(mint) = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data) {
+        return Ok(mint.get_extension_types()?);
+    }
+    let token_account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(token_account.get_extension_types()?)
+}
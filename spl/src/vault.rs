@@ -0,0 +1,157 @@
+//! Token vaults owned by a program derived address.
+//!
+//! A vault is a token account whose authority is a PDA of the program, so
+//! that tokens can only leave it through the program. Vaults are declared with
+//! the `vault` constraints, which are the `token` constraints with additional
+//! checks that no delegate or close authority can move the tokens, and can't
+//! be combined with the `token` constraints. An authority given as a field of
+//! the struct, `field` or `field.key()`, must have `seeds` and `bump`, so that
+//! its address is checked to be a PDA of the program.
+//!
+//! # Example
+//! ```ignore
+//! use anchor_spl::vault::{self, Vault};
+//!
+//! #[derive(Accounts)]
+//! pub struct Withdraw<'info> {
+//!     #[account(seeds = [b"pool"], bump)]
+//!     pub pool: Account<'info, Pool>,
+//!     #[account(mut, vault::mint = mint, vault::authority = pool)]
+//!     pub vault: Vault<'info>,
+//!     ...
+//! }
+//!
+//! pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+//!     let bump = [ctx.bumps.pool];
+//!     let signer_seeds: &[&[&[u8]]] = &[&[b"pool", &bump]];
+//!     vault::withdraw(
+//!         CpiContext::new_with_signer(
+//!             ctx.accounts.token_program.to_account_info(),
+//!             vault::Withdraw {
+//!                 vault: ctx.accounts.vault.to_account_info(),
+//!                 mint: ctx.accounts.mint.to_account_info(),
+//!                 to: ctx.accounts.user_token_account.to_account_info(),
+//!                 authority: ctx.accounts.pool.to_account_info(),
+//!             },
+//!             signer_seeds,
+//!         ),
+//!         amount,
+//!     )
+//! }
+//! ```
+
+use anchor_lang::accounts::interface_account::InterfaceAccount;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::Result;
+use anchor_lang::{context::CpiContext, Accounts};
+use spl_token_2022::extension::{transfer_hook, StateWithExtensions};
+
+use crate::token_interface::TokenAccount;
+
+/// Token account of either token program used as a vault.
+pub type Vault<'info> = InterfaceAccount<'info, TokenAccount>;
+
+/// Transfers `amount` tokens from a user's token account to the vault.
+///
+/// The extra accounts of the transfer hook of the mint, if any, must be given
+/// as the remaining accounts of the context: the hook program, its extra
+/// account metas validation account and the accounts it lists.
+pub fn deposit<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Deposit<'info>>,
+    amount: u64,
+) -> Result<()> {
+    transfer_checked(
+        ctx.program,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.vault,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        ctx.signer_seeds,
+    )
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    pub from: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub vault: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+/// Transfers `amount` tokens out of the vault, the context being signed with
+/// the seeds of the vault's authority.
+///
+/// The extra accounts of the transfer hook of the mint are given as in
+/// [`deposit`].
+pub fn withdraw<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Withdraw<'info>>,
+    amount: u64,
+) -> Result<()> {
+    transfer_checked(
+        ctx.program,
+        ctx.accounts.vault,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        ctx.signer_seeds,
+    )
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub vault: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub to: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+// Invokes `transfer_checked` with the decimals of the mint, appending the
+// extra accounts of its transfer hook resolved from `additional_accounts`.
+#[allow(clippy::too_many_arguments)]
+fn transfer_checked<'info>(
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    additional_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let (decimals, hook_program_id) = {
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        (state.base.decimals, transfer_hook::get_program_id(&state))
+    };
+    let mut ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        from.key,
+        mint.key,
+        to.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    let mut account_infos = vec![from.clone(), mint.clone(), to.clone(), authority.clone()];
+    if let Some(hook_program_id) = hook_program_id {
+        spl_transfer_hook_interface::onchain::add_extra_accounts_for_execute_cpi(
+            &mut ix,
+            &mut account_infos,
+            &hook_program_id,
+            from,
+            mint,
+            to,
+            authority,
+            amount,
+            additional_accounts,
+        )?;
+    }
+    anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, signer_seeds)
+        .map_err(Into::into)
+}
@@ -0,0 +1,230 @@
+#![cfg(feature = "account_compression")]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use anchor_lang::{Discriminator, Event};
+use anchor_spl::account_compression::{
+    self, merkle_tree_account_size, ConcurrentMerkleTree, CONCURRENT_MERKLE_TREE_HEADER_SIZE,
+    NOOP_ID,
+};
+use common::*;
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the compression CPIs instead of executing them.
+struct CompressionStubs;
+
+impl SyscallStubs for CompressionStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+fn take_invoked() -> Instruction {
+    let mut invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked.len(), 1);
+    invoked.remove(0)
+}
+
+fn modify() -> account_compression::Modify<'static> {
+    set_syscall_stubs(Box::new(CompressionStubs));
+    account_compression::Modify {
+        merkle_tree: account_info(Pubkey::new_unique(), account_compression::ID, 1, vec![]),
+        authority: signer_info(Pubkey::new_unique()),
+        noop: program_info(NOOP_ID),
+    }
+}
+
+fn modify_metas(accounts: &account_compression::Modify) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*accounts.merkle_tree.key, false),
+        AccountMeta::new_readonly(*accounts.authority.key, true),
+        AccountMeta::new_readonly(NOOP_ID, false),
+    ]
+}
+
+// Data of an Anchor instruction of the compression program.
+fn ix_data(name: &str, args: impl AnchorSerialize) -> Vec<u8> {
+    let mut data = hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec();
+    args.serialize(&mut data).unwrap();
+    data
+}
+
+fn compression_program() -> AccountInfo<'static> {
+    program_info(account_compression::ID)
+}
+
+#[test]
+fn test_merkle_tree_account_size() {
+    // Size given by `getConcurrentMerkleTreeAccountSize` of the JS SDK
+    assert_eq!(merkle_tree_account_size(14, 64, 0), 31_800);
+    assert_eq!(merkle_tree_account_size(14, 64, 2), 31_800 + 6 * 32);
+}
+
+#[test]
+fn test_init_empty_merkle_tree_and_append() {
+    let accounts = modify();
+    let metas = modify_metas(&accounts);
+    let ctx = CpiContext::new(compression_program(), accounts);
+    account_compression::init_empty_merkle_tree(ctx, 14, 64).unwrap();
+    let ix = take_invoked();
+    assert_eq!(ix.program_id, account_compression::ID);
+    assert_eq!(ix.data, ix_data("init_empty_merkle_tree", (14u32, 64u32)));
+    assert_eq!(ix.accounts, metas);
+
+    let accounts = modify();
+    let metas = modify_metas(&accounts);
+    let ctx = CpiContext::new(compression_program(), accounts);
+    account_compression::append(ctx, [1; 32]).unwrap();
+    let ix = take_invoked();
+    assert_eq!(ix.data, ix_data("append", [1u8; 32]));
+    assert_eq!(ix.accounts, metas);
+}
+
+#[test]
+fn test_replace_and_verify_leaf() {
+    let proof = (0..3)
+        .map(|_| account_info(Pubkey::new_unique(), Pubkey::default(), 0, vec![]))
+        .collect::<Vec<_>>();
+    let proof_metas = proof
+        .iter()
+        .map(|node| AccountMeta::new_readonly(*node.key, false))
+        .collect::<Vec<_>>();
+
+    let accounts = modify();
+    let mut metas = modify_metas(&accounts);
+    metas.extend(proof_metas.clone());
+    let ctx =
+        CpiContext::new(compression_program(), accounts).with_remaining_accounts(proof.clone());
+    account_compression::replace_leaf(ctx, [1; 32], [2; 32], [3; 32], 4).unwrap();
+    let ix = take_invoked();
+    assert_eq!(
+        ix.data,
+        ix_data("replace_leaf", ([1u8; 32], [2u8; 32], [3u8; 32], 4u32))
+    );
+    assert_eq!(ix.accounts, metas);
+
+    let merkle_tree = modify().merkle_tree;
+    let mut metas = vec![AccountMeta::new_readonly(*merkle_tree.key, false)];
+    metas.extend(proof_metas);
+    let ctx = CpiContext::new(
+        compression_program(),
+        account_compression::VerifyLeaf { merkle_tree },
+    )
+    .with_remaining_accounts(proof);
+    account_compression::verify_leaf(ctx, [1; 32], [2; 32], 4).unwrap();
+    let ix = take_invoked();
+    assert_eq!(
+        ix.data,
+        ix_data("verify_leaf", ([1u8; 32], [2u8; 32], 4u32))
+    );
+    assert_eq!(ix.accounts, metas);
+}
+
+// Data of a tree of depth 3 and buffer size 8 whose change log at
+// `active_index` has the given root.
+fn merkle_tree_data(active_index: u64, root: [u8; 32]) -> Vec<u8> {
+    let mut data = vec![0; merkle_tree_account_size(3, 8, 0)];
+    data[0] = 1;
+    data[2..6].copy_from_slice(&8u32.to_le_bytes());
+    data[6..10].copy_from_slice(&3u32.to_le_bytes());
+    data[10..42].copy_from_slice(&[7; 32]);
+    data[42..50].copy_from_slice(&100u64.to_le_bytes());
+    let tree = CONCURRENT_MERKLE_TREE_HEADER_SIZE;
+    data[tree..tree + 8].copy_from_slice(&5u64.to_le_bytes());
+    data[tree + 8..tree + 16].copy_from_slice(&active_index.to_le_bytes());
+    let change_log = 32 + 32 * 3 + 8;
+    let root_offset = tree + 24 + active_index as usize * change_log;
+    data[root_offset..root_offset + 32].copy_from_slice(&root);
+    data
+}
+
+#[test]
+fn test_concurrent_merkle_tree() {
+    let data = merkle_tree_data(7, [9; 32]);
+    let tree = ConcurrentMerkleTree::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(
+        tree,
+        ConcurrentMerkleTree {
+            max_buffer_size: 8,
+            max_depth: 3,
+            authority: Pubkey::new_from_array([7; 32]),
+            creation_slot: 100,
+            sequence_number: 5,
+            root: [9; 32],
+        }
+    );
+
+    let mut uninitialized = data.clone();
+    uninitialized[0] = 0;
+    assert!(ConcurrentMerkleTree::try_deserialize(&mut &uninitialized[..]).is_err());
+    assert!(ConcurrentMerkleTree::try_deserialize(&mut &data[..60]).is_err());
+}
+
+#[test]
+fn test_concurrent_merkle_tree_invalid_active_index() {
+    let mut data = merkle_tree_data(0, [9; 32]);
+    let tree = CONCURRENT_MERKLE_TREE_HEADER_SIZE;
+    for active_index in [8, u64::MAX] {
+        data[tree + 8..tree + 16].copy_from_slice(&active_index.to_le_bytes());
+        assert!(ConcurrentMerkleTree::try_deserialize(&mut &data[..]).is_err());
+    }
+
+    // A max depth putting the root past the end of the account
+    let mut data = merkle_tree_data(1, [9; 32]);
+    data[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(ConcurrentMerkleTree::try_deserialize(&mut &data[..]).is_err());
+}
+
+#[event]
+pub struct LeafUpdated {
+    pub index: u32,
+    pub leaf: [u8; 32],
+}
+
+fn emit_leaf_updated(noop: AccountInfo<'static>) -> Result<()> {
+    anchor_spl::emit_compressed!(
+        noop,
+        LeafUpdated {
+            index: 1,
+            leaf: [2; 32],
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_emit_compressed() {
+    let noop = modify().noop;
+    emit_leaf_updated(noop).unwrap();
+
+    let ix = take_invoked();
+    assert_eq!(ix.program_id, NOOP_ID);
+    assert!(ix.accounts.is_empty());
+    let event = LeafUpdated {
+        index: 1,
+        leaf: [2; 32],
+    };
+    assert_eq!(ix.data, event.data());
+    assert_eq!(ix.data[..8], LeafUpdated::DISCRIMINATOR);
+    assert_eq!(ix.data.len(), 8 + 4 + 32);
+}
@@ -0,0 +1,127 @@
+//! Helpers building the accounts of the tests of token constraints and CPIs.
+#![allow(dead_code, unused_imports)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+#[cfg(feature = "token")]
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::Bumps;
+#[cfg(feature = "token")]
+use anchor_spl::token::spl_token;
+#[cfg(feature = "token_2022")]
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut},
+};
+
+pub use anchor_test_utils::account_info::{
+    account_info, error_code, program_info, signer_info, system_info,
+};
+
+/// Returns the data of an initialized Token-2022 mint with the given
+/// extensions, which are set up by `init`.
+#[cfg(feature = "token_2022")]
+pub fn mint_data(
+    extension_types: &[ExtensionType],
+    init: impl FnOnce(&mut StateWithExtensionsMut<spl_token_2022::state::Mint>),
+) -> Vec<u8> {
+    let len =
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extension_types)
+            .unwrap();
+    let mut data = vec![0; len];
+    let mut state =
+        StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data)
+            .unwrap();
+    init(&mut state);
+    state.base = spl_token_2022::state::Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Default::default()
+    };
+    state.pack_base();
+    if !extension_types.is_empty() {
+        state.init_account_type().unwrap();
+    }
+    data
+}
+
+/// Returns the account info of a Token-2022 mint.
+#[cfg(feature = "token_2022")]
+pub fn mint_info(key: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+    account_info(key, spl_token_2022::ID, 1_000_000, data)
+}
+
+/// Returns the account info of an initialized mint of the token program.
+#[cfg(feature = "token")]
+pub fn token_mint_info() -> AccountInfo<'static> {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    account_info(Pubkey::new_unique(), spl_token::ID, 1_000_000, data)
+}
+
+/// Returns the data of an initialized token account of the token program.
+#[cfg(feature = "token")]
+pub fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+    token_account_data_with(mint, owner, amount, |_| {})
+}
+
+/// Same as [`token_account_data`], the account being modified by `edit`
+/// before it's packed.
+#[cfg(feature = "token")]
+pub fn token_account_data_with(
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+    edit: impl FnOnce(&mut spl_token::state::Account),
+) -> Vec<u8> {
+    let mut account = spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    edit(&mut account);
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    account.pack_into_slice(&mut data);
+    data
+}
+
+/// Returns the error of the runtime if an instruction invoked through CPI
+/// requires a signature that's neither given to the caller nor derived from
+/// the signer seeds.
+pub fn check_signers(
+    instruction: &Instruction,
+    account_infos: &[AccountInfo],
+    signers_seeds: &[&[&[u8]]],
+) -> std::result::Result<(), ProgramError> {
+    let signers = signers_seeds
+        .iter()
+        .map(|seeds| Pubkey::create_program_address(seeds, &crate::ID).unwrap())
+        .collect::<Vec<_>>();
+    for meta in instruction.accounts.iter().filter(|meta| meta.is_signer) {
+        let info = account_infos
+            .iter()
+            .find(|info| info.key == &meta.pubkey)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !info.is_signer && !signers.contains(info.key) {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    }
+    Ok(())
+}
+
+/// Validates the accounts with the constraints of `T` without running an
+/// instruction.
+pub fn try_accounts<T>(infos: Vec<AccountInfo<'static>>) -> Result<T>
+where
+    T: Bumps + Accounts<'static, <T as Bumps>::Bumps>,
+    <T as Bumps>::Bumps: Default,
+{
+    anchor_test_utils::account_info::try_accounts(&crate::ID, infos, &[])
+}
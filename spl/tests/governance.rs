@@ -0,0 +1,238 @@
+#![cfg(feature = "governance")]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    system_program,
+};
+use anchor_spl::governance::{self, TokenOwnerRecord, Vote, VoteChoice};
+use common::*;
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the governance CPIs instead of executing them.
+struct GovernanceStubs;
+
+impl SyscallStubs for GovernanceStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+fn take_invoked() -> Instruction {
+    let mut invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked.len(), 1);
+    invoked.remove(0)
+}
+
+fn info() -> AccountInfo<'static> {
+    set_syscall_stubs(Box::new(GovernanceStubs));
+    account_info(Pubkey::new_unique(), governance::ID, 1, vec![])
+}
+
+// Metas of the accounts, given with whether they're writable and signers.
+fn metas(accounts: &[(&AccountInfo, bool, bool)]) -> Vec<AccountMeta> {
+    accounts
+        .iter()
+        .map(|&(info, writable, signer)| match writable {
+            true => AccountMeta::new(*info.key, signer),
+            false => AccountMeta::new_readonly(*info.key, signer),
+        })
+        .collect()
+}
+
+#[test]
+fn test_deposit_governing_tokens() {
+    let accounts = governance::DepositGoverningTokens {
+        realm: info(),
+        governing_token_holding: info(),
+        governing_token_source: info(),
+        governing_token_owner: signer_info(Pubkey::new_unique()),
+        governing_token_source_authority: signer_info(Pubkey::new_unique()),
+        token_owner_record: info(),
+        payer: signer_info(Pubkey::new_unique()),
+        system_program: program_info(system_program::ID),
+        token_program: program_info(Pubkey::new_unique()),
+        realm_config: info(),
+    };
+    let expected = metas(&[
+        (&accounts.realm, false, false),
+        (&accounts.governing_token_holding, true, false),
+        (&accounts.governing_token_source, true, false),
+        (&accounts.governing_token_owner, false, true),
+        (&accounts.governing_token_source_authority, false, true),
+        (&accounts.token_owner_record, true, false),
+        (&accounts.payer, true, true),
+        (&accounts.system_program, false, false),
+        (&accounts.token_program, false, false),
+        (&accounts.realm_config, false, false),
+    ]);
+    let program = program_info(Pubkey::new_unique());
+    let ctx = CpiContext::new(program.clone(), accounts);
+    governance::deposit_governing_tokens(ctx, 1_000).unwrap();
+
+    let ix = take_invoked();
+    // DAOs commonly run their own deployment of the program
+    assert_eq!(ix.program_id, *program.key);
+    assert_eq!(ix.data, [&[1][..], &1_000u64.to_le_bytes()].concat());
+    assert_eq!(ix.accounts, expected);
+}
+
+#[test]
+fn test_create_proposal() {
+    let accounts = governance::CreateProposal {
+        realm: info(),
+        proposal: info(),
+        governance: info(),
+        proposal_owner_record: info(),
+        governing_token_mint: info(),
+        governance_authority: signer_info(Pubkey::new_unique()),
+        payer: signer_info(Pubkey::new_unique()),
+        system_program: program_info(system_program::ID),
+        realm_config: info(),
+        proposal_deposit: info(),
+    };
+    let voter_weight_record = info();
+    let mut expected = metas(&[
+        (&accounts.realm, false, false),
+        (&accounts.proposal, true, false),
+        (&accounts.governance, true, false),
+        (&accounts.proposal_owner_record, true, false),
+        (&accounts.governing_token_mint, false, false),
+        (&accounts.governance_authority, false, true),
+        (&accounts.payer, true, true),
+        (&accounts.system_program, false, false),
+        (&accounts.realm_config, false, false),
+        (&voter_weight_record, false, false),
+    ]);
+    // The deposit comes after the optional voter weight record
+    expected.push(AccountMeta::new(*accounts.proposal_deposit.key, false));
+    let ctx = CpiContext::new(program_info(governance::ID), accounts);
+    let seed = Pubkey::new_unique();
+    governance::create_proposal(
+        ctx,
+        "Upgrade".into(),
+        "https://example.com".into(),
+        governance::VoteType::SingleChoice,
+        vec!["Approve".into()],
+        true,
+        seed,
+        Some(voter_weight_record),
+    )
+    .unwrap();
+
+    let ix = take_invoked();
+    let mut data = vec![6];
+    (
+        "Upgrade".to_owned(),
+        "https://example.com".to_owned(),
+        0u8,
+        vec!["Approve".to_owned()],
+        true,
+        seed,
+    )
+        .serialize(&mut data)
+        .unwrap();
+    assert_eq!(ix.data, data);
+    assert_eq!(ix.accounts, expected);
+}
+
+#[test]
+fn test_cast_vote() {
+    let accounts = governance::CastVote {
+        realm: info(),
+        governance: info(),
+        proposal: info(),
+        proposal_owner_record: info(),
+        voter_token_owner_record: info(),
+        governance_authority: signer_info(Pubkey::new_unique()),
+        vote_record: info(),
+        governing_token_mint: info(),
+        payer: signer_info(Pubkey::new_unique()),
+        system_program: program_info(system_program::ID),
+        realm_config: info(),
+    };
+    let max_voter_weight_record = info();
+    let expected = metas(&[
+        (&accounts.realm, false, false),
+        (&accounts.governance, true, false),
+        (&accounts.proposal, true, false),
+        (&accounts.proposal_owner_record, true, false),
+        (&accounts.voter_token_owner_record, true, false),
+        (&accounts.governance_authority, false, true),
+        (&accounts.vote_record, true, false),
+        (&accounts.governing_token_mint, false, false),
+        (&accounts.payer, true, true),
+        (&accounts.system_program, false, false),
+        (&accounts.realm_config, false, false),
+        (&max_voter_weight_record, false, false),
+    ]);
+    let ctx = CpiContext::new(program_info(governance::ID), accounts);
+    let vote = Vote::Approve(vec![VoteChoice {
+        rank: 0,
+        weight_percentage: 100,
+    }]);
+    governance::cast_vote(ctx, vote, None, Some(max_voter_weight_record)).unwrap();
+
+    let ix = take_invoked();
+    assert_eq!(ix.data, [13, 0, 1, 0, 0, 0, 0, 100]);
+    assert_eq!(ix.accounts, expected);
+}
+
+#[test]
+fn test_execute_transaction() {
+    let governance_pda = info();
+    let accounts = governance::ExecuteTransaction {
+        governance: governance_pda.clone(),
+        proposal: info(),
+        proposal_transaction: info(),
+    };
+    let target_program = program_info(Pubkey::new_unique());
+    let mut signing_governance = governance_pda.clone();
+    signing_governance.is_signer = true;
+    let expected = metas(&[
+        (&accounts.governance, false, false),
+        (&accounts.proposal, true, false),
+        (&accounts.proposal_transaction, true, false),
+        (&target_program, false, false),
+        (&signing_governance, true, false),
+    ]);
+    let ctx = CpiContext::new(program_info(governance::ID), accounts)
+        .with_remaining_accounts(vec![target_program, signing_governance]);
+    governance::execute_transaction(ctx).unwrap();
+
+    let ix = take_invoked();
+    assert_eq!(ix.data, [16]);
+    assert_eq!(ix.accounts, expected);
+}
+
+#[test]
+fn test_token_owner_record() {
+    let mut data = vec![17];
+    data.extend_from_slice(&[1; 32 * 3]);
+    data.extend_from_slice(&100u64.to_le_bytes());
+    data.extend_from_slice(&[0; 8 + 1 + 1 + 6 + 1]);
+
+    let record = TokenOwnerRecord::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(record.governing_token_deposit_amount, 100);
+    assert_eq!(record.governance_delegate, None);
+
+    data[0] = 1;
+    assert!(TokenOwnerRecord::try_deserialize(&mut &data[..]).is_err());
+}
@@ -0,0 +1,151 @@
+#![cfg(all(feature = "associated_token", feature = "token"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{spl_token, Mint, Token, TokenAccount};
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// Stubs the rent sysvar and emulates `CreateIdempotent` of the associated
+// token program.
+struct AssociatedTokenStubs;
+
+impl SyscallStubs for AssociatedTokenStubs {
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        assert_eq!(instruction.program_id, AssociatedToken::id());
+        assert_eq!(instruction.data, [1], "Expected CreateIdempotent");
+        check_signers(instruction, account_infos, signers_seeds)?;
+        let find = |key: &Pubkey| {
+            account_infos
+                .iter()
+                .find(|info| info.key == key)
+                .ok_or(ProgramError::NotEnoughAccountKeys)
+        };
+        let associated_token = find(&instruction.accounts[1].pubkey)?;
+        let wallet = find(&instruction.accounts[2].pubkey)?;
+        let mint = find(&instruction.accounts[3].pubkey)?;
+
+        // Existing accounts are left as they are
+        if associated_token.owner == &System::id() {
+            **associated_token.try_borrow_mut_lamports()? = token_account_rent();
+            associated_token.realloc(spl_token::state::Account::LEN, false)?;
+            associated_token
+                .try_borrow_mut_data()?
+                .copy_from_slice(&token_account_data(*mint.key, *wallet.key, 0));
+            associated_token.assign(&spl_token::ID);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init_idempotent,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = payer,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+fn token_account_rent() -> u64 {
+    Rent::default().minimum_balance(spl_token::state::Account::LEN)
+}
+
+fn infos(
+    payer: &AccountInfo<'static>,
+    mint: &AccountInfo<'static>,
+    vault: &AccountInfo<'static>,
+) -> Vec<AccountInfo<'static>> {
+    set_syscall_stubs(Box::new(AssociatedTokenStubs));
+    vec![
+        payer.clone(),
+        mint.clone(),
+        vault.clone(),
+        program_info(spl_token::ID),
+        program_info(AssociatedToken::id()),
+        program_info(System::id()),
+    ]
+}
+
+#[test]
+fn test_init_idempotent_create() {
+    let payer = signer_info(Pubkey::new_unique());
+    let mint = token_mint_info();
+    let vault = system_info(get_associated_token_address(payer.key, mint.key), 0);
+
+    let accounts = try_accounts::<CreateVault>(infos(&payer, &mint, &vault)).unwrap();
+    assert_eq!(accounts.vault.mint, *mint.key);
+    assert_eq!(accounts.vault.owner, *payer.key);
+    assert_eq!(vault.owner, &spl_token::ID);
+}
+
+#[test]
+fn test_init_idempotent_existing() {
+    let payer = signer_info(Pubkey::new_unique());
+    let mint = token_mint_info();
+    let vault = account_info(
+        get_associated_token_address(payer.key, mint.key),
+        spl_token::ID,
+        token_account_rent(),
+        token_account_data(*mint.key, *payer.key, 42),
+    );
+
+    let accounts = try_accounts::<CreateVault>(infos(&payer, &mint, &vault)).unwrap();
+    assert_eq!(accounts.vault.amount, 42);
+}
+
+#[test]
+fn test_init_idempotent_existing_other_mint() {
+    let payer = signer_info(Pubkey::new_unique());
+    let mint = token_mint_info();
+    let vault = account_info(
+        get_associated_token_address(payer.key, mint.key),
+        spl_token::ID,
+        token_account_rent(),
+        token_account_data(Pubkey::new_unique(), *payer.key, 42),
+    );
+
+    let result = try_accounts::<CreateVault>(infos(&payer, &mint, &vault));
+    assert_eq!(error_code(result), ErrorCode::ConstraintTokenMint as u32);
+}
+
+#[test]
+fn test_init_idempotent_not_associated() {
+    let payer = signer_info(Pubkey::new_unique());
+    let mint = token_mint_info();
+    let vault = system_info(Pubkey::new_unique(), 0);
+
+    let result = try_accounts::<CreateVault>(infos(&payer, &mint, &vault));
+    assert!(matches!(
+        result,
+        Err(Error::ProgramError(e)) if e.program_error == ProgramError::NotEnoughAccountKeys
+    ));
+    assert_eq!(vault.owner, &System::id());
+}
@@ -0,0 +1,64 @@
+#![cfg(all(feature = "token_2022", feature = "token_2022_extensions"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, BaseStateWithExtensionsMut, ExtensionType,
+};
+use anchor_spl::token_2022_extensions::{
+    interest_bearing_amount_to_ui_amount, interest_bearing_ui_amount_to_amount,
+};
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// Length of a year used by Token-2022 to accrue interest.
+const SECONDS_PER_YEAR: i64 = 60 * 60 * 24 * 36524 / 100;
+
+// Mint of 6 decimals accruing interest at `rate` basis points since 0.
+fn interest_bearing_mint(rate: i16) -> AccountInfo<'static> {
+    let data = mint_data(&[ExtensionType::InterestBearingConfig], |state| {
+        let config = state.init_extension::<InterestBearingConfig>(true).unwrap();
+        config.pre_update_average_rate = rate.into();
+        config.current_rate = rate.into();
+    });
+    mint_info(Pubkey::new_unique(), data)
+}
+
+#[test]
+fn test_amount_to_ui_amount_without_interest() {
+    let mint = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+    let ui_amount = interest_bearing_amount_to_ui_amount(&mint, 1_500_000, 1_000).unwrap();
+    assert_eq!(ui_amount, "1.5");
+    assert_eq!(
+        interest_bearing_ui_amount_to_amount(&mint, &ui_amount, 1_000).unwrap(),
+        1_500_000
+    );
+
+    let mint = interest_bearing_mint(0);
+    let ui_amount = interest_bearing_amount_to_ui_amount(&mint, 1_500_000, 1_000).unwrap();
+    assert_eq!(ui_amount.parse::<f64>().unwrap(), 1.5);
+}
+
+#[test]
+fn test_amount_to_ui_amount_with_interest() {
+    // 5% compounded continuously over a year
+    let mint = interest_bearing_mint(500);
+    let ui_amount =
+        interest_bearing_amount_to_ui_amount(&mint, 1_000_000, SECONDS_PER_YEAR).unwrap();
+    assert!(ui_amount.starts_with("1.05127"), "{ui_amount}");
+    assert_eq!(
+        interest_bearing_amount_to_ui_amount(&mint, 1_000_000, 0).unwrap(),
+        "1"
+    );
+
+    let amount = interest_bearing_ui_amount_to_amount(&mint, &ui_amount, SECONDS_PER_YEAR).unwrap();
+    assert!(amount.abs_diff(1_000_000) <= 1, "{amount}");
+}
+
+#[test]
+fn test_ui_amount_to_amount_invalid() {
+    let mint = interest_bearing_mint(500);
+    assert!(interest_bearing_ui_amount_to_amount(&mint, "one", 0).is_err());
+}
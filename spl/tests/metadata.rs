@@ -0,0 +1,220 @@
+#![cfg(feature = "metadata")]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    system_program, sysvar,
+};
+use anchor_spl::metadata::{self, mpl_token_metadata, AuthorizationRules, TokenRecordAccount};
+use common::*;
+use mpl_token_metadata::accounts::TokenRecord;
+use mpl_token_metadata::types::{Key, TokenState};
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the metadata CPIs instead of executing them.
+struct MetadataStubs;
+
+impl SyscallStubs for MetadataStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        // Every account of the instruction must be given to the runtime
+        for meta in &instruction.accounts {
+            if !account_infos.iter().any(|info| info.key == &meta.pubkey) {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            }
+        }
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+fn take_invoked() -> Instruction {
+    let mut invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked.len(), 1);
+    invoked.remove(0)
+}
+
+fn info() -> AccountInfo<'static> {
+    set_syscall_stubs(Box::new(MetadataStubs));
+    account_info(Pubkey::new_unique(), mpl_token_metadata::ID, 1, vec![])
+}
+
+fn metadata_program() -> AccountInfo<'static> {
+    program_info(mpl_token_metadata::ID)
+}
+
+fn authorization_rules() -> AuthorizationRules<'static> {
+    AuthorizationRules {
+        program: program_info(Pubkey::new_unique()),
+        rule_set: info(),
+    }
+}
+
+fn transfer_accounts() -> metadata::TransferV1<'static> {
+    metadata::TransferV1 {
+        token: info(),
+        token_owner: info(),
+        destination_token: info(),
+        destination_owner: info(),
+        mint: info(),
+        metadata: info(),
+        edition: info(),
+        token_record: info(),
+        destination_token_record: info(),
+        authority: signer_info(Pubkey::new_unique()),
+        payer: signer_info(Pubkey::new_unique()),
+        system_program: program_info(system_program::ID),
+        sysvar_instructions: account_info(sysvar::instructions::ID, sysvar::ID, 1, vec![]),
+        spl_token: program_info(Pubkey::new_unique()),
+        spl_ata: program_info(Pubkey::new_unique()),
+    }
+}
+
+fn lock_accounts() -> metadata::LockV1<'static> {
+    metadata::LockV1 {
+        authority: signer_info(Pubkey::new_unique()),
+        token_owner: info(),
+        token: info(),
+        mint: info(),
+        metadata: info(),
+        edition: info(),
+        token_record: info(),
+        payer: signer_info(Pubkey::new_unique()),
+        system_program: program_info(system_program::ID),
+        sysvar_instructions: account_info(sysvar::instructions::ID, sysvar::ID, 1, vec![]),
+        spl_token: program_info(Pubkey::new_unique()),
+    }
+}
+
+#[test]
+fn test_transfer_v1() {
+    let accounts = transfer_accounts();
+    let keys = [
+        &accounts.token,
+        &accounts.token_owner,
+        &accounts.destination_token,
+        &accounts.destination_owner,
+        &accounts.mint,
+        &accounts.metadata,
+        &accounts.edition,
+        &accounts.token_record,
+        &accounts.destination_token_record,
+        &accounts.authority,
+        &accounts.payer,
+    ]
+    .map(|info| *info.key);
+    let rules = authorization_rules();
+    let rule_keys = [*rules.program.key, *rules.rule_set.key];
+    let ctx = CpiContext::new(metadata_program(), accounts);
+
+    metadata::transfer_v1(ctx, 1, Some(rules)).unwrap();
+    let ix = take_invoked();
+    assert_eq!(ix.program_id, mpl_token_metadata::ID);
+    // `Transfer`, `TransferArgs::V1`, the amount and no authorization data
+    assert_eq!(ix.data, [&[49, 0][..], &1u64.to_le_bytes(), &[0]].concat());
+    let ix_keys = ix
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect::<Vec<_>>();
+    assert_eq!(ix_keys[..11], keys);
+    assert_eq!(ix_keys[15..], rule_keys);
+    assert!(ix.accounts[9].is_signer && ix.accounts[10].is_signer);
+}
+
+#[test]
+fn test_transfer_v1_without_rule_set() {
+    let ctx = CpiContext::new(metadata_program(), transfer_accounts());
+
+    metadata::transfer_v1(ctx, 1, None).unwrap();
+    let ix = take_invoked();
+    // Missing optional accounts are replaced by the metadata program
+    assert_eq!(ix.accounts[15].pubkey, mpl_token_metadata::ID);
+    assert_eq!(ix.accounts[16].pubkey, mpl_token_metadata::ID);
+}
+
+#[test]
+fn test_delegate_v1() {
+    let delegate_accounts = || metadata::DelegateV1 {
+        delegate: info(),
+        metadata: info(),
+        edition: info(),
+        token_record: info(),
+        mint: info(),
+        token: info(),
+        authority: signer_info(Pubkey::new_unique()),
+        payer: signer_info(Pubkey::new_unique()),
+        system_program: program_info(system_program::ID),
+        sysvar_instructions: account_info(sysvar::instructions::ID, sysvar::ID, 1, vec![]),
+        spl_token: program_info(Pubkey::new_unique()),
+    };
+
+    let accounts = delegate_accounts();
+    let delegate = *accounts.delegate.key;
+    let ctx = CpiContext::new(metadata_program(), accounts);
+    metadata::delegate_transfer_v1(ctx, 1, None).unwrap();
+    let ix = take_invoked();
+    // `Delegate`, `DelegateArgs::TransferV1`, the amount and no authorization
+    // data
+    assert_eq!(ix.data, [&[44, 2][..], &1u64.to_le_bytes(), &[0]].concat());
+    assert_eq!(ix.accounts[1].pubkey, delegate);
+
+    let ctx = CpiContext::new(metadata_program(), delegate_accounts());
+    metadata::delegate_utility_v1(ctx, 1, Some(authorization_rules())).unwrap();
+    let ix = take_invoked();
+    assert_eq!(ix.data, [&[44, 4][..], &1u64.to_le_bytes(), &[0]].concat());
+}
+
+#[test]
+fn test_lock_and_unlock_v1() {
+    let accounts = lock_accounts();
+    let authority = *accounts.authority.key;
+    let ctx = CpiContext::new(metadata_program(), accounts);
+    metadata::lock_v1(ctx, None).unwrap();
+    let ix = take_invoked();
+    // `Lock`, `LockArgs::V1` and no authorization data
+    assert_eq!(ix.data, [46, 0, 0]);
+    assert_eq!(ix.accounts[0].pubkey, authority);
+    assert!(ix.accounts[0].is_signer);
+
+    let ctx = CpiContext::new(metadata_program(), lock_accounts());
+    metadata::unlock_v1(ctx, Some(authorization_rules())).unwrap();
+    assert_eq!(take_invoked().data, [47, 0, 0]);
+}
+
+#[test]
+fn test_token_record_is_locked() {
+    let record = |state| {
+        let mut data = TokenRecord {
+            key: Key::TokenRecord,
+            bump: 255,
+            state,
+            rule_set_revision: None,
+            delegate: None,
+            delegate_role: None,
+            locked_transfer: None,
+        }
+        .try_to_vec()
+        .unwrap();
+        data.resize(TokenRecordAccount::LEN, 0);
+        TokenRecordAccount::try_deserialize(&mut &data[..]).unwrap()
+    };
+
+    assert!(record(TokenState::Locked).is_locked());
+    assert!(!record(TokenState::Unlocked).is_locked());
+}
@@ -0,0 +1,125 @@
+#![cfg(all(feature = "token_2022", feature = "token_2022_extensions"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    mint_close_authority::MintCloseAuthority, permanent_delegate::PermanentDelegate,
+    BaseStateWithExtensionsMut, ExtensionType,
+};
+use anchor_spl::token_2022_extensions::spl_pod::optional_keys::OptionalNonZeroPubkey;
+use anchor_spl::token_interface::Mint;
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[derive(Accounts)]
+pub struct CheckAuthorities<'info> {
+    #[account(mint::close_authority = authority, mint::permanent_delegate = authority)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Only compared to the authorities of the mint.
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CheckNoAuthorities<'info> {
+    #[account(mint::close_authority = none, mint::permanent_delegate = none)]
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+fn authorities_mint_info(
+    close_authority: Option<Pubkey>,
+    permanent_delegate: Option<Pubkey>,
+) -> AccountInfo<'static> {
+    let extension_types = [
+        ExtensionType::MintCloseAuthority,
+        ExtensionType::PermanentDelegate,
+    ];
+    let data = mint_data(&extension_types, |state| {
+        state
+            .init_extension::<MintCloseAuthority>(true)
+            .unwrap()
+            .close_authority = OptionalNonZeroPubkey::try_from(close_authority).unwrap();
+        state
+            .init_extension::<PermanentDelegate>(true)
+            .unwrap()
+            .delegate = OptionalNonZeroPubkey::try_from(permanent_delegate).unwrap();
+    });
+    mint_info(Pubkey::new_unique(), data)
+}
+
+#[test]
+fn test_mint_authorities() {
+    let authority = system_info(Pubkey::new_unique(), 0);
+    let mint = authorities_mint_info(Some(*authority.key), Some(*authority.key));
+
+    try_accounts::<CheckAuthorities>(vec![mint, authority]).unwrap();
+}
+
+#[test]
+fn test_mint_close_authority_mismatch() {
+    let authority = system_info(Pubkey::new_unique(), 0);
+    let mint = authorities_mint_info(Some(Pubkey::new_unique()), Some(*authority.key));
+
+    let result = try_accounts::<CheckAuthorities>(vec![mint, authority]);
+    assert_eq!(
+        error_code(result),
+        ErrorCode::ConstraintMintCloseAuthority as u32
+    );
+}
+
+#[test]
+fn test_mint_permanent_delegate_mismatch() {
+    let authority = system_info(Pubkey::new_unique(), 0);
+    let mint = authorities_mint_info(Some(*authority.key), Some(Pubkey::new_unique()));
+
+    let result = try_accounts::<CheckAuthorities>(vec![mint, authority]);
+    assert_eq!(
+        error_code(result),
+        ErrorCode::ConstraintMintPermanentDelegate as u32
+    );
+}
+
+#[test]
+fn test_mint_authorities_missing_extensions() {
+    let authority = system_info(Pubkey::new_unique(), 0);
+    let mint = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+
+    let result = try_accounts::<CheckAuthorities>(vec![mint, authority]);
+    assert_eq!(
+        error_code(result),
+        ErrorCode::ConstraintMintCloseAuthority as u32
+    );
+}
+
+#[test]
+fn test_mint_no_authorities() {
+    let mint = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+    try_accounts::<CheckNoAuthorities>(vec![mint]).unwrap();
+
+    // Extensions without an authority are equivalent to missing extensions
+    let mint = authorities_mint_info(None, None);
+    try_accounts::<CheckNoAuthorities>(vec![mint]).unwrap();
+}
+
+#[test]
+fn test_mint_no_authorities_with_close_authority() {
+    let mint = authorities_mint_info(Some(Pubkey::new_unique()), None);
+
+    let result = try_accounts::<CheckNoAuthorities>(vec![mint]);
+    assert_eq!(
+        error_code(result),
+        ErrorCode::ConstraintMintCloseAuthority as u32
+    );
+}
+
+#[test]
+fn test_mint_no_authorities_with_permanent_delegate() {
+    let mint = authorities_mint_info(None, Some(Pubkey::new_unique()));
+
+    let result = try_accounts::<CheckNoAuthorities>(vec![mint]);
+    assert_eq!(
+        error_code(result),
+        ErrorCode::ConstraintMintPermanentDelegate as u32
+    );
+}
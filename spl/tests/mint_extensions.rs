@@ -0,0 +1,144 @@
+#![cfg(all(feature = "token_2022", feature = "token_2022_extensions"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, metadata_pointer::MetadataPointer,
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, ExtensionType,
+    StateWithExtensionsMut,
+};
+use anchor_spl::token_2022_extensions::spl_pod::optional_keys::OptionalNonZeroPubkey;
+use anchor_spl::token_2022_extensions::spl_token_group_interface::state::{
+    TokenGroup, TokenGroupMember,
+};
+use anchor_spl::token_2022_extensions::spl_token_metadata_interface::state::TokenMetadata;
+use anchor_spl::token_interface::{spl_token_2022, Mint, MintExtensions};
+use common::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// Leaked to be borrowed by the `InterfaceAccount`, as with the infos of a
+// program.
+fn mint_account(data: Vec<u8>) -> InterfaceAccount<'static, Mint> {
+    let info = Box::leak(Box::new(mint_info(Pubkey::new_unique(), data)));
+    InterfaceAccount::try_from(&*info).unwrap()
+}
+
+#[test]
+fn test_mint_without_extensions() {
+    let mint = mint_account(mint_data(&[], |_| {}));
+
+    assert!(mint.transfer_fee_config().unwrap().is_none());
+    assert!(mint.interest_bearing_config().unwrap().is_none());
+    assert!(mint.metadata_pointer().unwrap().is_none());
+    assert!(mint.token_group().unwrap().is_none());
+    assert!(mint.token_group_member().unwrap().is_none());
+    assert!(mint.token_metadata().unwrap().is_none());
+}
+
+#[cfg(feature = "token")]
+#[test]
+fn test_legacy_mint() {
+    let info = Box::leak(Box::new(token_mint_info()));
+    let mint = InterfaceAccount::<Mint>::try_from(&*info).unwrap();
+
+    assert!(mint.transfer_fee_config().unwrap().is_none());
+    assert!(mint.token_metadata().unwrap().is_none());
+}
+
+#[test]
+fn test_transfer_fee_config() {
+    let mint = mint_account(mint_data(&[ExtensionType::TransferFeeConfig], |state| {
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.newer_transfer_fee.transfer_fee_basis_points = 100.into();
+        config.newer_transfer_fee.maximum_fee = 1_000.into();
+    }));
+
+    let config = mint.transfer_fee_config().unwrap().unwrap();
+    assert_eq!(config.calculate_epoch_fee(0, 10_000), Some(100));
+    assert!(mint.interest_bearing_config().unwrap().is_none());
+}
+
+#[test]
+fn test_interest_bearing_config() {
+    let mint = mint_account(mint_data(
+        &[ExtensionType::InterestBearingConfig],
+        |state| {
+            let config = state.init_extension::<InterestBearingConfig>(true).unwrap();
+            config.current_rate = 250.into();
+        },
+    ));
+
+    let config = mint.interest_bearing_config().unwrap().unwrap();
+    assert_eq!(i16::from(config.current_rate), 250);
+}
+
+#[test]
+fn test_metadata_pointer() {
+    let metadata_address = Pubkey::new_unique();
+    let mint = mint_account(mint_data(&[ExtensionType::MetadataPointer], |state| {
+        state
+            .init_extension::<MetadataPointer>(true)
+            .unwrap()
+            .metadata_address = OptionalNonZeroPubkey::try_from(Some(metadata_address)).unwrap();
+    }));
+
+    let pointer = mint.metadata_pointer().unwrap().unwrap();
+    assert_eq!(
+        Option::<Pubkey>::from(pointer.metadata_address),
+        Some(metadata_address)
+    );
+}
+
+#[test]
+fn test_token_group() {
+    let mint = mint_account(mint_data(&[ExtensionType::TokenGroup], |state| {
+        let group = state.init_extension::<TokenGroup>(true).unwrap();
+        group.size = 2.into();
+        group.max_size = 10.into();
+    }));
+
+    let group = mint.token_group().unwrap().unwrap();
+    assert_eq!(u32::from(group.size), 2);
+    assert_eq!(u32::from(group.max_size), 10);
+    assert!(mint.token_group_member().unwrap().is_none());
+}
+
+#[test]
+fn test_token_group_member() {
+    let group = Pubkey::new_unique();
+    let mint = mint_account(mint_data(&[ExtensionType::TokenGroupMember], |state| {
+        let member = state.init_extension::<TokenGroupMember>(true).unwrap();
+        member.group = group;
+        member.member_number = 3.into();
+    }));
+
+    let member = mint.token_group_member().unwrap().unwrap();
+    assert_eq!(member.group, group);
+    assert_eq!(u32::from(member.member_number), 3);
+}
+
+#[test]
+fn test_token_metadata() {
+    let metadata = TokenMetadata {
+        name: "Token".to_owned(),
+        symbol: "TKN".to_owned(),
+        uri: "https://example.com/token.json".to_owned(),
+        additional_metadata: vec![("key".to_owned(), "value".to_owned())],
+        ..Default::default()
+    };
+    let mut data = mint_data(&[ExtensionType::MetadataPointer], |state| {
+        state.init_extension::<MetadataPointer>(true).unwrap();
+    });
+    // Room for the variable length metadata after the fixed size extensions
+    data.resize(data.len() + 256, 0);
+    StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack(&mut data)
+        .unwrap()
+        .init_variable_len_extension(&metadata, false)
+        .unwrap();
+    let mint = mint_account(data);
+
+    assert_eq!(mint.token_metadata().unwrap(), Some(metadata));
+    assert!(mint.metadata_pointer().unwrap().is_some());
+}
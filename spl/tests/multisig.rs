@@ -0,0 +1,173 @@
+#![cfg(all(feature = "token", feature = "token_2022"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use anchor_spl::token::{self, spl_token};
+use anchor_spl::token_2022::{self, spl_token_2022};
+use common::*;
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the token CPIs instead of executing them.
+struct TokenStubs;
+
+impl SyscallStubs for TokenStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+struct TokenAccounts {
+    from: AccountInfo<'static>,
+    mint: AccountInfo<'static>,
+    to: AccountInfo<'static>,
+    multisig: AccountInfo<'static>,
+    signers: Vec<AccountInfo<'static>>,
+}
+
+fn accounts(token_program: Pubkey) -> TokenAccounts {
+    set_syscall_stubs(Box::new(TokenStubs));
+    let token_info = || account_info(Pubkey::new_unique(), token_program, 1, vec![]);
+    TokenAccounts {
+        from: token_info(),
+        mint: token_info(),
+        to: token_info(),
+        multisig: token_info(),
+        signers: (0..2).map(|_| signer_info(Pubkey::new_unique())).collect(),
+    }
+}
+
+fn take_invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.take())
+}
+
+// Metas of the authority and its signers, which end every instruction.
+fn multisig_metas(accounts: &TokenAccounts) -> Vec<AccountMeta> {
+    let mut metas = vec![AccountMeta::new_readonly(*accounts.multisig.key, false)];
+    metas.extend(
+        accounts
+            .signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer.key, true)),
+    );
+    metas
+}
+
+#[test]
+fn test_transfer_multisig() {
+    let accounts = accounts(spl_token::ID);
+    let cpi_accounts = token::Transfer {
+        from: accounts.from.clone(),
+        to: accounts.to.clone(),
+        authority: accounts.multisig.clone(),
+    };
+    let ctx = CpiContext::new(program_info(spl_token::ID), cpi_accounts);
+
+    token::transfer_multisig(ctx, &accounts.signers, 10).unwrap();
+    let invoked = take_invoked();
+    assert_eq!(invoked.len(), 1);
+    assert_eq!(invoked[0].accounts[2..], multisig_metas(&accounts));
+}
+
+#[test]
+fn test_transfer_checked_multisig() {
+    let accounts = accounts(spl_token_2022::ID);
+    let cpi_accounts = token_2022::TransferChecked {
+        from: accounts.from.clone(),
+        mint: accounts.mint.clone(),
+        to: accounts.to.clone(),
+        authority: accounts.multisig.clone(),
+    };
+    let ctx = CpiContext::new(program_info(spl_token_2022::ID), cpi_accounts);
+
+    token_2022::transfer_checked_multisig(ctx, &accounts.signers, 10, 6).unwrap();
+    let invoked = take_invoked();
+    assert_eq!(invoked.len(), 1);
+    assert_eq!(invoked[0].program_id, spl_token_2022::ID);
+    assert_eq!(invoked[0].accounts[3..], multisig_metas(&accounts));
+}
+
+#[test]
+fn test_mint_to_and_burn_multisig() {
+    let accounts = accounts(spl_token::ID);
+    let cpi_accounts = token::MintTo {
+        mint: accounts.mint.clone(),
+        to: accounts.to.clone(),
+        authority: accounts.multisig.clone(),
+    };
+    let ctx = CpiContext::new(program_info(spl_token::ID), cpi_accounts);
+    token::mint_to_multisig(ctx, &accounts.signers, 10).unwrap();
+
+    let cpi_accounts = token::Burn {
+        mint: accounts.mint.clone(),
+        from: accounts.to.clone(),
+        authority: accounts.multisig.clone(),
+    };
+    let ctx = CpiContext::new(program_info(spl_token::ID), cpi_accounts);
+    token::burn_multisig(ctx, &accounts.signers, 10).unwrap();
+
+    let invoked = take_invoked();
+    assert_eq!(invoked.len(), 2);
+    assert_eq!(invoked[0].accounts[2..], multisig_metas(&accounts));
+    assert_eq!(invoked[1].accounts[2..], multisig_metas(&accounts));
+}
+
+#[test]
+fn test_transfer_multisig_missing_signature() {
+    let mut accounts = accounts(spl_token::ID);
+    accounts.signers[1].is_signer = false;
+    let cpi_accounts = token::Transfer {
+        from: accounts.from.clone(),
+        to: accounts.to.clone(),
+        authority: accounts.multisig.clone(),
+    };
+    let ctx = CpiContext::new(program_info(spl_token::ID), cpi_accounts);
+
+    let result = token::transfer_multisig(ctx, &accounts.signers, 10);
+    assert!(matches!(
+        result,
+        Err(Error::ProgramError(e)) if e.program_error == ProgramError::MissingRequiredSignature
+    ));
+    assert!(take_invoked().is_empty());
+}
+
+#[test]
+fn test_transfer_checked_remaining_accounts() {
+    let accounts = accounts(spl_token_2022::ID);
+    let authority = signer_info(Pubkey::new_unique());
+    let cpi_accounts = token_2022::TransferChecked {
+        from: accounts.from.clone(),
+        mint: accounts.mint.clone(),
+        to: accounts.to.clone(),
+        authority: authority.clone(),
+    };
+    // Remaining accounts, e.g. the accounts of a transfer hook, aren't signers
+    let ctx = CpiContext::new(program_info(spl_token_2022::ID), cpi_accounts)
+        .with_remaining_accounts(accounts.signers.clone());
+
+    token_2022::transfer_checked(ctx, 10, 6).unwrap();
+    let invoked = take_invoked();
+    assert_eq!(invoked.len(), 1);
+    assert_eq!(
+        invoked[0].accounts[3..],
+        [AccountMeta::new_readonly(*authority.key, true)]
+    );
+}
@@ -0,0 +1,178 @@
+#![cfg(all(feature = "token_2022", feature = "token_2022_extensions"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use anchor_lang::solana_program::{entrypoint::ProgramResult, instruction::Instruction};
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensionsMut, ExtensionType,
+};
+use anchor_spl::token_2022_extensions::spl_token_group_interface::instruction::{
+    TokenGroupInstruction, UpdateGroupAuthority, UpdateGroupMaxSize,
+};
+use anchor_spl::token_2022_extensions::spl_token_group_interface::state::TokenGroupMember;
+use anchor_spl::token_2022_extensions::{
+    token_group_update_authority, token_group_update_max_size, TokenGroupUpdateAuthority,
+    TokenGroupUpdateMaxSize,
+};
+use anchor_spl::token_interface::{spl_token_2022, Mint};
+use common::*;
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the group CPIs instead of executing them.
+struct GroupStubs;
+
+impl SyscallStubs for GroupStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CheckMember<'info> {
+    #[account(extensions::group_member::group = collection)]
+    pub nft: InterfaceAccount<'info, Mint>,
+    pub collection: InterfaceAccount<'info, Mint>,
+}
+
+fn member_info(group: Pubkey) -> AccountInfo<'static> {
+    let key = Pubkey::new_unique();
+    let data = mint_data(&[ExtensionType::TokenGroupMember], |state| {
+        let member = state.init_extension::<TokenGroupMember>(true).unwrap();
+        member.mint = key;
+        member.group = group;
+        member.member_number = 1.into();
+    });
+    mint_info(key, data)
+}
+
+fn group_infos() -> (
+    AccountInfo<'static>,
+    AccountInfo<'static>,
+    AccountInfo<'static>,
+) {
+    set_syscall_stubs(Box::new(GroupStubs));
+    (
+        program_info(spl_token_2022::ID),
+        mint_info(Pubkey::new_unique(), mint_data(&[], |_| {})),
+        signer_info(Pubkey::new_unique()),
+    )
+}
+
+fn take_invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.take())
+}
+
+#[test]
+fn test_group_member() {
+    let collection = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+    let nft = member_info(*collection.key);
+
+    try_accounts::<CheckMember>(vec![nft, collection]).unwrap();
+}
+
+#[test]
+fn test_group_member_other_group() {
+    let collection = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+    let nft = member_info(Pubkey::new_unique());
+
+    let result = try_accounts::<CheckMember>(vec![nft, collection]);
+    assert_eq!(
+        error_code(result),
+        ErrorCode::ConstraintMintTokenGroupMemberExtensionGroup as u32
+    );
+}
+
+#[test]
+fn test_group_member_not_a_member() {
+    let collection = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+    let nft = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+
+    let result = try_accounts::<CheckMember>(vec![nft, collection]);
+    assert_eq!(
+        error_code(result),
+        ErrorCode::ConstraintMintTokenGroupMemberExtension as u32
+    );
+}
+
+#[test]
+fn test_group_update_max_size() {
+    let (token_program, group, update_authority) = group_infos();
+    let accounts = TokenGroupUpdateMaxSize {
+        token_program_id: token_program.clone(),
+        group: group.clone(),
+        update_authority: update_authority.clone(),
+    };
+
+    token_group_update_max_size(CpiContext::new(token_program, accounts), 10).unwrap();
+    let invoked = take_invoked();
+    assert_eq!(invoked.len(), 1);
+    assert_eq!(invoked[0].program_id, spl_token_2022::ID);
+    assert_eq!(invoked[0].accounts[0].pubkey, *group.key);
+    assert_eq!(invoked[0].accounts[1].pubkey, *update_authority.key);
+    assert_eq!(
+        TokenGroupInstruction::unpack(&invoked[0].data).unwrap(),
+        TokenGroupInstruction::UpdateGroupMaxSize(UpdateGroupMaxSize {
+            max_size: 10.into()
+        })
+    );
+}
+
+#[test]
+fn test_group_update_authority() {
+    let (token_program, group, current_authority) = group_infos();
+    let new_authority = Pubkey::new_unique();
+    let accounts = TokenGroupUpdateAuthority {
+        token_program_id: token_program.clone(),
+        group: group.clone(),
+        current_authority: current_authority.clone(),
+    };
+
+    token_group_update_authority(
+        CpiContext::new(token_program, accounts),
+        Some(new_authority),
+    )
+    .unwrap();
+    let invoked = take_invoked();
+    assert_eq!(invoked.len(), 1);
+    assert_eq!(invoked[0].accounts[0].pubkey, *group.key);
+    assert_eq!(invoked[0].accounts[1].pubkey, *current_authority.key);
+    assert_eq!(
+        TokenGroupInstruction::unpack(&invoked[0].data).unwrap(),
+        TokenGroupInstruction::UpdateGroupAuthority(UpdateGroupAuthority {
+            new_authority: Some(new_authority).try_into().unwrap()
+        })
+    );
+}
+
+#[test]
+fn test_group_update_without_authority_signature() {
+    let (token_program, group, mut update_authority) = group_infos();
+    update_authority.is_signer = false;
+    let accounts = TokenGroupUpdateMaxSize {
+        token_program_id: token_program.clone(),
+        group,
+        update_authority,
+    };
+
+    let result = token_group_update_max_size(CpiContext::new(token_program, accounts), 10);
+    assert!(matches!(
+        result,
+        Err(Error::ProgramError(e)) if e.program_error == ProgramError::MissingRequiredSignature
+    ));
+    assert!(take_invoked().is_empty());
+}
@@ -0,0 +1,115 @@
+#![cfg(all(feature = "token", feature = "token_2022"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use anchor_spl::token::{self, spl_token};
+use anchor_spl::token_2022::{self, spl_token_2022};
+use common::*;
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, ExtensionType,
+};
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the token CPIs instead of executing them.
+struct TokenStubs;
+
+impl SyscallStubs for TokenStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+// Source and destination token accounts, and the authority of the source.
+fn transfer_accounts(token_program: Pubkey) -> [AccountInfo<'static>; 3] {
+    set_syscall_stubs(Box::new(TokenStubs));
+    let token_info = || account_info(Pubkey::new_unique(), token_program, 1, vec![]);
+    [
+        token_info(),
+        token_info(),
+        signer_info(Pubkey::new_unique()),
+    ]
+}
+
+fn take_invoked() -> Instruction {
+    let mut invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked.len(), 1);
+    invoked.remove(0)
+}
+
+// Data of a `TransferChecked` instruction.
+fn transfer_checked_data(amount: u64, decimals: u8) -> Vec<u8> {
+    [&[12][..], &amount.to_le_bytes(), &[decimals]].concat()
+}
+
+#[test]
+fn test_transfer_checked_auto() {
+    let [from, to, authority] = transfer_accounts(spl_token::ID);
+    let mint = token_mint_info();
+    let cpi_accounts = token::TransferChecked {
+        from,
+        mint: mint.clone(),
+        to,
+        authority,
+    };
+    let ctx = CpiContext::new(program_info(spl_token::ID), cpi_accounts);
+
+    token::transfer_checked_auto(ctx, 10).unwrap();
+    let ix = take_invoked();
+    assert_eq!(ix.program_id, spl_token::ID);
+    assert_eq!(ix.data, transfer_checked_data(10, 6));
+    assert_eq!(ix.accounts[1].pubkey, *mint.key);
+}
+
+#[test]
+fn test_transfer_checked_auto_with_extensions() {
+    let data = mint_data(&[ExtensionType::TransferFeeConfig], |state| {
+        state.init_extension::<TransferFeeConfig>(true).unwrap();
+    });
+    let [from, to, authority] = transfer_accounts(spl_token_2022::ID);
+    let cpi_accounts = token_2022::TransferChecked {
+        from,
+        mint: mint_info(Pubkey::new_unique(), data),
+        to,
+        authority,
+    };
+    let ctx = CpiContext::new(program_info(spl_token_2022::ID), cpi_accounts);
+
+    token_2022::transfer_checked_auto(ctx, 10).unwrap();
+    let ix = take_invoked();
+    assert_eq!(ix.program_id, spl_token_2022::ID);
+    assert_eq!(ix.data, transfer_checked_data(10, 6));
+}
+
+#[test]
+fn test_transfer_checked_auto_uninitialized_mint() {
+    let [from, to, authority] = transfer_accounts(spl_token::ID);
+    let cpi_accounts = token::TransferChecked {
+        from,
+        mint: account_info(Pubkey::new_unique(), spl_token::ID, 1, vec![0; 82]),
+        to,
+        authority,
+    };
+    let ctx = CpiContext::new(program_info(spl_token::ID), cpi_accounts);
+
+    assert!(token::transfer_checked_auto(ctx, 10).is_err());
+    assert!(INVOKED.with(|invoked| invoked.borrow().is_empty()));
+}
@@ -0,0 +1,204 @@
+#![cfg(all(feature = "token_2022", feature = "token_2022_extensions"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_hook::TransferHook, BaseStateWithExtensionsMut, ExtensionType},
+};
+use anchor_spl::token_2022_extensions::spl_pod::optional_keys::OptionalNonZeroPubkey;
+use anchor_spl::token_2022_extensions::spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use anchor_spl::token_2022_extensions::{
+    add_transfer_hook_accounts, get_extra_account_metas_address, get_transfer_hook_program_id,
+    transfer_checked_with_hook, TransferCheckedWithHook,
+};
+use common::*;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the token CPIs instead of executing them.
+struct TokenStubs;
+
+impl SyscallStubs for TokenStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+// Mint whose hook requires a single writable extra account, along with the
+// accounts the hook needs.
+struct HookAccounts {
+    mint: AccountInfo<'static>,
+    hook_program: AccountInfo<'static>,
+    validation: AccountInfo<'static>,
+    extra: AccountInfo<'static>,
+}
+
+fn hook_accounts() -> HookAccounts {
+    set_syscall_stubs(Box::new(TokenStubs));
+    let hook_program = program_info(Pubkey::new_unique());
+    let data = mint_data(&[ExtensionType::TransferHook], |state| {
+        state
+            .init_extension::<TransferHook>(true)
+            .unwrap()
+            .program_id = OptionalNonZeroPubkey::try_from(Some(*hook_program.key)).unwrap();
+    });
+    let mint = mint_info(Pubkey::new_unique(), data);
+    let extra = system_info(Pubkey::new_unique(), 1);
+    let mut validation_data = vec![0; ExtraAccountMetaList::size_of(1).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut validation_data,
+        &[ExtraAccountMeta::new_with_pubkey(extra.key, false, true).unwrap()],
+    )
+    .unwrap();
+    let validation = account_info(
+        get_extra_account_metas_address(mint.key, hook_program.key),
+        *hook_program.key,
+        1,
+        validation_data,
+    );
+    HookAccounts {
+        mint,
+        hook_program,
+        validation,
+        extra,
+    }
+}
+
+fn token_account() -> AccountInfo<'static> {
+    account_info(Pubkey::new_unique(), spl_token_2022::ID, 1, vec![])
+}
+
+// Metas the hook appends to `transfer_checked`.
+fn hook_metas(accounts: &HookAccounts) -> [AccountMeta; 3] {
+    [
+        AccountMeta::new(*accounts.extra.key, false),
+        AccountMeta::new_readonly(*accounts.hook_program.key, false),
+        AccountMeta::new_readonly(*accounts.validation.key, false),
+    ]
+}
+
+#[test]
+fn test_get_transfer_hook_program_id() {
+    let accounts = hook_accounts();
+    assert_eq!(
+        get_transfer_hook_program_id(&accounts.mint).unwrap(),
+        Some(*accounts.hook_program.key)
+    );
+
+    let mint = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+    assert_eq!(get_transfer_hook_program_id(&mint).unwrap(), None);
+}
+
+#[test]
+fn test_get_extra_account_metas_address() {
+    let mint = Pubkey::new_unique();
+    let hook_program = Pubkey::new_unique();
+    assert_eq!(
+        get_extra_account_metas_address(&mint, &hook_program),
+        Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], &hook_program).0
+    );
+}
+
+#[test]
+fn test_transfer_checked_with_hook() {
+    let accounts = hook_accounts();
+    let cpi_accounts = TransferCheckedWithHook {
+        token_program_id: program_info(spl_token_2022::ID),
+        source: token_account(),
+        mint: accounts.mint.clone(),
+        destination: token_account(),
+        authority: signer_info(Pubkey::new_unique()),
+    };
+    let expected = [
+        AccountMeta::new(*cpi_accounts.source.key, false),
+        AccountMeta::new_readonly(*cpi_accounts.mint.key, false),
+        AccountMeta::new(*cpi_accounts.destination.key, false),
+        AccountMeta::new_readonly(*cpi_accounts.authority.key, true),
+    ];
+    // The extra accounts can be given in any order
+    let ctx = CpiContext::new(program_info(spl_token_2022::ID), cpi_accounts)
+        .with_remaining_accounts(vec![
+            accounts.extra.clone(),
+            accounts.validation.clone(),
+            accounts.hook_program.clone(),
+        ]);
+
+    transfer_checked_with_hook(ctx, 10, 6).unwrap();
+    let invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked.len(), 1);
+    assert_eq!(
+        invoked[0].data,
+        [&[12][..], &10u64.to_le_bytes(), &[6]].concat()
+    );
+    assert_eq!(invoked[0].accounts[..4], expected);
+    assert_eq!(invoked[0].accounts[4..], hook_metas(&accounts));
+}
+
+#[test]
+fn test_add_transfer_hook_accounts() {
+    let accounts = hook_accounts();
+    let (source, destination, authority) = (token_account(), token_account(), token_account());
+    let ix = || {
+        spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::ID,
+            source.key,
+            accounts.mint.key,
+            destination.key,
+            authority.key,
+            &[],
+            10,
+            6,
+        )
+        .unwrap()
+    };
+    let add = |ix: &mut Instruction, infos: &mut Vec<AccountInfo<'static>>, mint| {
+        add_transfer_hook_accounts(
+            ix,
+            infos,
+            source.clone(),
+            mint,
+            destination.clone(),
+            authority.clone(),
+            10,
+            &[
+                accounts.hook_program.clone(),
+                accounts.validation.clone(),
+                accounts.extra.clone(),
+            ],
+        )
+    };
+
+    let mut hooked = ix();
+    let mut infos = vec![];
+    add(&mut hooked, &mut infos, accounts.mint.clone()).unwrap();
+    assert_eq!(hooked.accounts[4..], hook_metas(&accounts));
+    assert_eq!(infos.len(), 3);
+
+    // Mints without a hook are left untouched
+    let mut unhooked = ix();
+    let mut infos = vec![];
+    let mint = mint_info(Pubkey::new_unique(), mint_data(&[], |_| {}));
+    add(&mut unhooked, &mut infos, mint).unwrap();
+    assert_eq!(unhooked, ix());
+    assert!(infos.is_empty());
+}
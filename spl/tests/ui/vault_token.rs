@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::vault::Vault;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Pool {}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(seeds = [b"pool"], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(vault::authority = pool, token::mint = mint)]
+    pub vault: Vault<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+fn main() {}
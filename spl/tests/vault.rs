@@ -0,0 +1,246 @@
+#![cfg(all(feature = "token", feature = "token_2022"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_option::COption,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::vault::{self, Vault};
+use common::*;
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Pool {}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(seeds = [b"pool"], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(vault::mint = mint, vault::authority = pool)]
+    pub vault: Vault<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawByKey<'info> {
+    #[account(seeds = [b"pool"], bump)]
+    pub pool: Account<'info, Pool>,
+    #[account(vault::mint = mint, vault::authority = pool.key())]
+    pub vault: Vault<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the token CPIs instead of executing them.
+struct TokenStubs;
+
+impl SyscallStubs for TokenStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+fn pool_info(key: Pubkey) -> AccountInfo<'static> {
+    let mut data = vec![];
+    Pool {}.try_serialize(&mut data).unwrap();
+    account_info(key, crate::ID, 1_000_000, data)
+}
+
+fn pool_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"pool"], &crate::ID).0
+}
+
+fn vault_info(
+    mint: &AccountInfo<'static>,
+    authority: Pubkey,
+    edit: impl FnOnce(&mut spl_token::state::Account),
+) -> AccountInfo<'static> {
+    let data = token_account_data_with(*mint.key, authority, 100, edit);
+    account_info(Pubkey::new_unique(), spl_token::ID, 1_000_000, data)
+}
+
+#[test]
+fn test_vault() {
+    let mint = token_mint_info();
+    let vault = vault_info(&mint, pool_address(), |_| {});
+
+    try_accounts::<Withdraw>(vec![pool_info(pool_address()), vault, mint]).unwrap();
+}
+
+#[test]
+fn test_vault_authority_key() {
+    let mint = token_mint_info();
+    let vault = vault_info(&mint, pool_address(), |_| {});
+
+    try_accounts::<WithdrawByKey>(vec![pool_info(pool_address()), vault, mint]).unwrap();
+}
+
+#[test]
+fn test_vault_authority_not_pda() {
+    // The authority of the vault isn't the PDA of the program
+    let authority = Pubkey::new_unique();
+    let mint = token_mint_info();
+    let vault = vault_info(&mint, authority, |_| {});
+
+    let result = try_accounts::<Withdraw>(vec![pool_info(authority), vault, mint]);
+    assert_eq!(error_code(result), ErrorCode::ConstraintSeeds as u32);
+}
+
+#[test]
+fn test_vault_other_authority() {
+    let mint = token_mint_info();
+    let vault = vault_info(&mint, Pubkey::new_unique(), |_| {});
+
+    let result = try_accounts::<Withdraw>(vec![pool_info(pool_address()), vault, mint]);
+    assert_eq!(error_code(result), ErrorCode::ConstraintTokenOwner as u32);
+}
+
+#[test]
+fn test_vault_other_mint() {
+    let mint = token_mint_info();
+    let vault = vault_info(&token_mint_info(), pool_address(), |_| {});
+
+    let result = try_accounts::<Withdraw>(vec![pool_info(pool_address()), vault, mint]);
+    assert_eq!(error_code(result), ErrorCode::ConstraintTokenMint as u32);
+}
+
+#[test]
+fn test_vault_delegate() {
+    let mint = token_mint_info();
+    let vault = vault_info(&mint, pool_address(), |vault| {
+        vault.delegate = COption::Some(Pubkey::new_unique());
+        vault.delegated_amount = 100;
+    });
+
+    let result = try_accounts::<Withdraw>(vec![pool_info(pool_address()), vault, mint]);
+    assert_eq!(error_code(result), ErrorCode::ConstraintTokenVault as u32);
+}
+
+#[test]
+fn test_vault_close_authority() {
+    let mint = token_mint_info();
+    let vault = vault_info(&mint, pool_address(), |vault| {
+        vault.close_authority = COption::Some(Pubkey::new_unique());
+    });
+
+    let result = try_accounts::<Withdraw>(vec![pool_info(pool_address()), vault, mint]);
+    assert_eq!(error_code(result), ErrorCode::ConstraintTokenVault as u32);
+}
+
+#[test]
+fn test_vault_with_token_constraints() {
+    trybuild::TestCases::new().compile_fail("tests/ui/vault_token.rs");
+}
+
+#[test]
+fn test_deposit() {
+    set_syscall_stubs(Box::new(TokenStubs));
+    let mint = token_mint_info();
+    let from = account_info(Pubkey::new_unique(), spl_token::ID, 1, vec![]);
+    let vault = account_info(Pubkey::new_unique(), spl_token::ID, 1, vec![]);
+    let authority = signer_info(Pubkey::new_unique());
+    let cpi_accounts = vault::Deposit {
+        from: from.clone(),
+        mint: mint.clone(),
+        vault: vault.clone(),
+        authority: authority.clone(),
+    };
+
+    vault::deposit(
+        CpiContext::new(program_info(spl_token::ID), cpi_accounts),
+        10,
+    )
+    .unwrap();
+    let invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked[0].program_id, spl_token::ID);
+    assert_eq!(
+        invoked[0].accounts,
+        [
+            AccountMeta::new(*from.key, false),
+            AccountMeta::new_readonly(*mint.key, false),
+            AccountMeta::new(*vault.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ]
+    );
+}
+
+#[cfg(feature = "token_2022_extensions")]
+#[test]
+fn test_deposit_transfer_hook() {
+    use anchor_spl::token_2022::spl_token_2022::{
+        self,
+        extension::{transfer_hook::TransferHook, BaseStateWithExtensionsMut, ExtensionType},
+    };
+    use anchor_spl::token_2022_extensions::spl_pod::optional_keys::OptionalNonZeroPubkey;
+    use anchor_spl::token_2022_extensions::spl_transfer_hook_interface::{
+        get_extra_account_metas_address, instruction::ExecuteInstruction,
+    };
+    use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+
+    set_syscall_stubs(Box::new(TokenStubs));
+    let hook_program = Pubkey::new_unique();
+    let data = mint_data(&[ExtensionType::TransferHook], |state| {
+        state
+            .init_extension::<TransferHook>(true)
+            .unwrap()
+            .program_id = OptionalNonZeroPubkey::try_from(Some(hook_program)).unwrap();
+    });
+    let mint = mint_info(Pubkey::new_unique(), data);
+    // The hook lists a single extra account, which is writable
+    let extra = system_info(Pubkey::new_unique(), 1);
+    let mut validation_data = vec![0; ExtraAccountMetaList::size_of(1).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut validation_data,
+        &[ExtraAccountMeta::new_with_pubkey(extra.key, false, true).unwrap()],
+    )
+    .unwrap();
+    let validation = account_info(
+        get_extra_account_metas_address(mint.key, &hook_program),
+        hook_program,
+        1,
+        validation_data,
+    );
+    let token_account = || account_info(Pubkey::new_unique(), spl_token_2022::ID, 1, vec![]);
+    let cpi_accounts = vault::Deposit {
+        from: token_account(),
+        mint: mint.clone(),
+        vault: token_account(),
+        authority: signer_info(Pubkey::new_unique()),
+    };
+
+    let ctx = CpiContext::new(program_info(spl_token_2022::ID), cpi_accounts)
+        .with_remaining_accounts(vec![
+            program_info(hook_program),
+            validation.clone(),
+            extra.clone(),
+        ]);
+    vault::deposit(ctx, 10).unwrap();
+    let invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked[0].program_id, spl_token_2022::ID);
+    assert_eq!(
+        invoked[0].accounts[4..],
+        [
+            AccountMeta::new(*extra.key, false),
+            AccountMeta::new_readonly(hook_program, false),
+            AccountMeta::new_readonly(*validation.key, false),
+        ]
+    );
+}
@@ -0,0 +1,132 @@
+#![cfg(all(feature = "token_2022", feature = "token_2022_extensions"))]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::TransferFeeAmount, BaseStateWithExtensionsMut, ExtensionType,
+        StateWithExtensionsMut,
+    },
+    state::{Account, AccountState},
+};
+use anchor_spl::token_2022_extensions::{
+    get_accounts_with_withheld_fees, get_withheld_amount, withdraw_withheld_tokens_from_accounts,
+    WithdrawWithheldTokensFromAccounts,
+};
+use common::*;
+use std::cell::RefCell;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(vec![]) };
+}
+
+// Records the instructions of the token CPIs instead of executing them.
+struct TokenStubs;
+
+impl SyscallStubs for TokenStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        check_signers(instruction, account_infos, signers_seeds)?;
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        Ok(())
+    }
+}
+
+// Data of a Token-2022 account, with fees withheld if `withheld` is given.
+fn token_account_data(withheld: Option<u64>) -> Vec<u8> {
+    let extension_types = match withheld {
+        Some(_) => vec![ExtensionType::TransferFeeAmount],
+        None => vec![],
+    };
+    let len = ExtensionType::try_calculate_account_len::<Account>(&extension_types).unwrap();
+    let mut data = vec![0; len];
+    let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+    if let Some(withheld) = withheld {
+        state
+            .init_extension::<TransferFeeAmount>(true)
+            .unwrap()
+            .withheld_amount = withheld.into();
+    }
+    state.base = Account {
+        mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        state: AccountState::Initialized,
+        ..Default::default()
+    };
+    state.pack_base();
+    if withheld.is_some() {
+        state.init_account_type().unwrap();
+    }
+    data
+}
+
+#[test]
+fn test_get_withheld_amount() {
+    assert_eq!(
+        get_withheld_amount(&token_account_data(Some(42))).unwrap(),
+        42
+    );
+    assert_eq!(get_withheld_amount(&token_account_data(None)).unwrap(), 0);
+    assert!(get_withheld_amount(&[0; 10]).is_err());
+}
+
+#[test]
+fn test_get_accounts_with_withheld_fees() {
+    let keys = [(); 4].map(|_| Pubkey::new_unique());
+    let data = [
+        token_account_data(Some(42)),
+        token_account_data(Some(0)),
+        token_account_data(None),
+        vec![0; 10],
+    ];
+
+    let accounts = get_accounts_with_withheld_fees(
+        keys.iter()
+            .zip(&data)
+            .map(|(key, data)| (*key, data.as_slice())),
+    );
+    assert_eq!(accounts, [(keys[0], 42)]);
+}
+
+#[test]
+fn test_withdraw_withheld_tokens_from_accounts() {
+    set_syscall_stubs(Box::new(TokenStubs));
+    let token_info = || account_info(Pubkey::new_unique(), spl_token_2022::ID, 1, vec![]);
+    let sources = vec![token_info(), token_info()];
+    let accounts = WithdrawWithheldTokensFromAccounts {
+        token_program_id: program_info(spl_token_2022::ID),
+        mint: token_info(),
+        destination: token_info(),
+        authority: signer_info(Pubkey::new_unique()),
+    };
+    let expected = vec![
+        AccountMeta::new_readonly(*accounts.mint.key, false),
+        AccountMeta::new(*accounts.destination.key, false),
+        AccountMeta::new_readonly(*accounts.authority.key, true),
+        AccountMeta::new(*sources[0].key, false),
+        AccountMeta::new(*sources[1].key, false),
+    ];
+    let ctx = CpiContext::new(program_info(spl_token_2022::ID), accounts);
+
+    withdraw_withheld_tokens_from_accounts(ctx, sources).unwrap();
+    let invoked = INVOKED.with(|invoked| invoked.take());
+    assert_eq!(invoked.len(), 1);
+    assert_eq!(invoked[0].program_id, spl_token_2022::ID);
+    // `TransferFeeExtension`, `WithdrawWithheldTokensFromAccounts` and the
+    // number of sources
+    assert_eq!(invoked[0].data, [26, 3, 2]);
+    assert_eq!(invoked[0].accounts, expected);
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::error::Error;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::{bpf_loader_upgradeable, pubkey::Pubkey, system_program};
+use anchor_lang::{Accounts, Bumps, Result};
+
+/// Returns a writable account info, leaked for the duration of the test.
+///
+/// The account is laid out as in the input of programs, so that it can be
+/// resized with `realloc`.
+pub fn account_info(
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+) -> AccountInfo<'static> {
+    AccountInfo::new(
+        leak_key(key, data.len()),
+        false,
+        true,
+        Box::leak(Box::new(lamports)),
+        leak_data(data),
+        Box::leak(Box::new(owner)),
+        false,
+        0,
+    )
+}
+
+// `realloc` reads the original length of the data before the key.
+fn leak_key(key: Pubkey, data_len: usize) -> &'static Pubkey {
+    let buf = Box::leak(vec![0u8; 4 + 32].into_boxed_slice());
+    buf[..4].copy_from_slice(&(data_len as u32).to_le_bytes());
+    buf[4..].copy_from_slice(key.as_ref());
+    // `Pubkey` is a byte array, so it's always aligned
+    unsafe { &*(buf[4..].as_ptr() as *const Pubkey) }
+}
+
+// The data is preceded by its length, written by `realloc`, and followed by
+// the space data can grow into.
+fn leak_data(data: Vec<u8>) -> &'static mut [u8] {
+    let len = data.len();
+    let mut buf = vec![0u8; 8 + len + MAX_PERMITTED_DATA_INCREASE];
+    buf[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    buf[8..8 + len].copy_from_slice(&data);
+    &mut Box::leak(buf.into_boxed_slice())[8..8 + len]
+}
+
+/// Returns the account info of a signer owned by the system program.
+pub fn signer_info(key: Pubkey) -> AccountInfo<'static> {
+    let mut info = system_info(key, 1_000_000_000);
+    info.is_signer = true;
+    info
+}
+
+/// Returns the account info of a wallet owned by the system program.
+pub fn system_info(key: Pubkey, lamports: u64) -> AccountInfo<'static> {
+    account_info(key, system_program::ID, lamports, vec![])
+}
+
+/// Returns the account info of a program.
+pub fn program_info(key: Pubkey) -> AccountInfo<'static> {
+    let mut info = account_info(key, bpf_loader_upgradeable::ID, 1, vec![]);
+    info.executable = true;
+    info.is_writable = false;
+    info
+}
+
+/// Validates the accounts with the constraints of `T` for `program_id`
+/// without running an instruction, `ix_data` being the instruction data used
+/// by `#[instruction(..)]`.
+pub fn try_accounts<T>(
+    program_id: &Pubkey,
+    infos: Vec<AccountInfo<'static>>,
+    ix_data: &[u8],
+) -> Result<T>
+where
+    T: Bumps + Accounts<'static, <T as Bumps>::Bumps>,
+    <T as Bumps>::Bumps: Default,
+{
+    let mut infos: &'static [AccountInfo<'static>] = Box::leak(infos.into_boxed_slice());
+    T::try_accounts(
+        program_id,
+        &mut infos,
+        ix_data,
+        &mut Default::default(),
+        &mut BTreeSet::new(),
+    )
+}
+
+/// Returns the error code of a failed validation.
+///
+/// # Panics
+///
+/// Panics if the validation succeeded or failed with a program error.
+pub fn error_code<T>(result: Result<T>) -> u32 {
+    match result {
+        Ok(_) => panic!("Expected the accounts to be invalid"),
+        Err(Error::AnchorError(e)) => e.error_code_number,
+        Err(Error::ProgramError(e)) => panic!("Unexpected program error: {e:?}"),
+    }
+}
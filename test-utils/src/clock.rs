@@ -0,0 +1,88 @@
+#[cfg(any(feature = "litesvm", feature = "program-test"))]
+use solana_sdk::clock::Clock;
+#[cfg(feature = "litesvm")]
+use solana_sdk::epoch_schedule::EpochSchedule;
+
+/// Moves the clock of a test environment, so that vesting or expiry logic can
+/// be tested deterministically.
+pub trait TimeTravel {
+    /// Sets the slot of the clock, leaving its timestamp as it is.
+    ///
+    /// `LiteSVM` has an inherent `warp_to_slot` doing the same, which method
+    /// calls resolve to.
+    fn warp_to_slot(&mut self, slot: u64);
+
+    /// Sets the unix timestamp of the clock, leaving its slot and epoch as
+    /// they are.
+    fn set_unix_timestamp(&mut self, unix_timestamp: i64);
+
+    /// Moves the clock to the first slot of the next epoch.
+    fn advance_epoch(&mut self);
+}
+
+#[cfg(feature = "litesvm")]
+impl TimeTravel for litesvm::LiteSVM {
+    fn warp_to_slot(&mut self, slot: u64) {
+        let mut clock = self.get_sysvar::<Clock>();
+        clock.slot = slot;
+        self.set_sysvar(&clock);
+    }
+
+    fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        let mut clock = self.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        self.set_sysvar(&clock);
+    }
+
+    fn advance_epoch(&mut self) {
+        let schedule = self.get_sysvar::<EpochSchedule>();
+        let mut clock = self.get_sysvar::<Clock>();
+        clock.epoch += 1;
+        clock.slot = schedule.get_first_slot_in_epoch(clock.epoch);
+        clock.leader_schedule_epoch = schedule.get_leader_schedule_epoch(clock.slot);
+        clock.epoch_start_timestamp = clock.unix_timestamp;
+        self.set_sysvar(&clock);
+    }
+}
+
+/// [`TimeTravel`] for `solana-program-test`, whose clock is read
+/// asynchronously.
+///
+/// `ProgramTestContext::warp_to_slot` already warps the bank to a slot.
+#[cfg(feature = "program-test")]
+#[allow(async_fn_in_trait)]
+pub trait ProgramTestTimeTravel {
+    /// Sets the unix timestamp of the clock, leaving its slot and epoch as
+    /// they are.
+    async fn set_unix_timestamp(&mut self, unix_timestamp: i64);
+
+    /// Warps the bank to the first slot of the next epoch.
+    async fn advance_epoch(&mut self);
+}
+
+#[cfg(feature = "program-test")]
+impl ProgramTestTimeTravel for solana_program_test::ProgramTestContext {
+    async fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        let mut clock = self
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .expect("Failed to get the clock");
+        clock.unix_timestamp = unix_timestamp;
+        self.set_sysvar(&clock);
+    }
+
+    async fn advance_epoch(&mut self) {
+        let clock = self
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .expect("Failed to get the clock");
+        let slot = self
+            .genesis_config()
+            .epoch_schedule
+            .get_first_slot_in_epoch(clock.epoch + 1);
+        self.warp_to_slot(slot)
+            .expect("Failed to warp to the next epoch");
+    }
+}
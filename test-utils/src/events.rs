@@ -0,0 +1,229 @@
+use std::fmt::Debug;
+
+use anchor_lang::{event::EVENT_IX_TAG_LE, Event};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_client::{
+    client_error::ClientError, rpc_client::RpcClient, rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{bs58, commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, UiInstruction, UiTransactionEncoding,
+};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// Recorder of the events emitted by the transactions of a test, to assert
+/// them by type instead of searching the logs.
+///
+/// Events are extracted from the `Program data:` logs of `emit!`, and from the
+/// self-CPIs of `emit_cpi!`, in the order in which the transactions were
+/// recorded.
+///
+/// ```ignore
+/// let mut events = EventRecorder::new();
+/// events.record_litesvm(&svm.send_transaction(tx).unwrap());
+///
+/// let deposit = events.assert_emitted::<Deposit>(|e| e.amount == 100);
+/// let withdraw = events.assert_emitted::<Withdraw>(|_| true);
+/// assert!(deposit < withdraw);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EventRecorder {
+    // Discriminators followed by the serialized events.
+    events: Vec<Vec<u8>>,
+}
+
+impl EventRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the events of the logs of a transaction.
+    pub fn record_logs<S: AsRef<str>>(&mut self, logs: &[S]) {
+        for log in logs {
+            if let Some(data) = log.as_ref().strip_prefix(PROGRAM_DATA) {
+                if let Ok(data) = STANDARD.decode(data) {
+                    self.record(data);
+                }
+            }
+        }
+    }
+
+    /// Records the event of the data of an inner instruction, if it's the
+    /// self-CPI of `emit_cpi!`.
+    pub fn record_inner_instruction(&mut self, data: &[u8]) {
+        if let Some(data) = data.strip_prefix(&EVENT_IX_TAG_LE[..]) {
+            self.record(data.to_vec());
+        }
+    }
+
+    /// Records the events of a transaction executed by LiteSVM.
+    #[cfg(feature = "litesvm")]
+    pub fn record_litesvm(&mut self, meta: &litesvm::types::TransactionMetadata) {
+        self.record_logs(&meta.logs);
+        for ix in meta.inner_instructions.iter().flatten() {
+            self.record_inner_instruction(&ix.instruction.data);
+        }
+    }
+
+    /// Records the events of a confirmed transaction, fetched from the RPC.
+    pub fn record_signature(
+        &mut self,
+        rpc: &RpcClient,
+        signature: &Signature,
+    ) -> Result<(), ClientError> {
+        let tx = rpc.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        if let Some(meta) = tx.transaction.meta {
+            if let OptionSerializer::Some(logs) = &meta.log_messages {
+                self.record_logs(logs);
+            }
+            if let OptionSerializer::Some(inner_ixs) = &meta.inner_instructions {
+                for ix in inner_ixs.iter().flat_map(|ixs| &ixs.instructions) {
+                    if let UiInstruction::Compiled(ix) = ix {
+                        if let Ok(data) = bs58::decode(&ix.data).into_vec() {
+                            self.record_inner_instruction(&data);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of recorded events, of any type.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Forgets the recorded events, e.g. between the steps of a test.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Returns the recorded events of type `T`, in order.
+    pub fn emitted<T: Event>(&self) -> Vec<T> {
+        self.events_of::<T>().map(|(_, event)| event).collect()
+    }
+
+    /// Asserts that an event of type `T` matching `predicate` was recorded,
+    /// returning its index among the events of all types, e.g. to assert the
+    /// order of events of different types.
+    #[track_caller]
+    pub fn assert_emitted<T: Event + Debug>(&self, predicate: impl Fn(&T) -> bool) -> usize {
+        match self.events_of::<T>().find(|(_, event)| predicate(event)) {
+            Some((index, _)) => index,
+            None => panic!(
+                "No matching {} event was emitted, emitted ones: {:#?}",
+                std::any::type_name::<T>(),
+                self.emitted::<T>()
+            ),
+        }
+    }
+
+    /// Asserts that no event of type `T` matching `predicate` was recorded.
+    #[track_caller]
+    pub fn assert_not_emitted<T: Event + Debug>(&self, predicate: impl Fn(&T) -> bool) {
+        if let Some((_, event)) = self.events_of::<T>().find(|(_, event)| predicate(event)) {
+            panic!("Unexpected event emitted: {event:#?}");
+        }
+    }
+
+    /// Asserts that events with the given discriminators were recorded in
+    /// this order, other events being allowed between them.
+    ///
+    /// ```ignore
+    /// events.assert_order(&[Deposit::DISCRIMINATOR, Withdraw::DISCRIMINATOR]);
+    /// ```
+    #[track_caller]
+    pub fn assert_order(&self, discriminators: &[[u8; 8]]) {
+        let mut events = self.events.iter();
+        for (i, discriminator) in discriminators.iter().enumerate() {
+            if !events.any(|event| event.starts_with(discriminator)) {
+                panic!(
+                    "Event {i} of the order, with discriminator {discriminator:?}, wasn't \
+                    emitted after the previous ones"
+                );
+            }
+        }
+    }
+
+    fn record(&mut self, data: Vec<u8>) {
+        if data.len() >= 8 {
+            self.events.push(data);
+        }
+    }
+
+    // Iterates over the events of type `T` with their index among all events.
+    fn events_of<T: Event>(&self) -> impl Iterator<Item = (usize, T)> + '_ {
+        self.events.iter().enumerate().filter_map(|(index, data)| {
+            let data = data.strip_prefix(&T::DISCRIMINATOR[..])?;
+            T::deserialize(&mut &data[..])
+                .ok()
+                .map(|event| (index, event))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+
+    use super::*;
+
+    #[event]
+    #[derive(Debug)]
+    pub struct Deposit {
+        pub amount: u64,
+    }
+
+    #[event]
+    #[derive(Debug)]
+    pub struct Withdraw {
+        pub amount: u64,
+    }
+
+    #[test]
+    fn test_event_recorder() {
+        let mut events = EventRecorder::new();
+        let log = |data: Vec<u8>| format!("{PROGRAM_DATA}{}", STANDARD.encode(data));
+        events.record_logs(&[
+            "Program log: Instruction: Deposit".to_owned(),
+            log(Deposit { amount: 1 }.data()),
+        ]);
+        let mut cpi_data = EVENT_IX_TAG_LE.to_vec();
+        cpi_data.extend(Withdraw { amount: 2 }.data());
+        events.record_inner_instruction(&cpi_data);
+        events.record_logs(&[log(Deposit { amount: 3 }.data())]);
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events.emitted::<Deposit>().len(), 2);
+        assert_eq!(events.assert_emitted::<Deposit>(|e| e.amount == 3), 2);
+        assert_eq!(events.assert_emitted::<Withdraw>(|e| e.amount == 2), 1);
+        events.assert_not_emitted::<Withdraw>(|e| e.amount == 1);
+        events.assert_order(&[Deposit::DISCRIMINATOR, Withdraw::DISCRIMINATOR]);
+        events.assert_order(&[Withdraw::DISCRIMINATOR, Deposit::DISCRIMINATOR]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_event_recorder_order() {
+        let mut events = EventRecorder::new();
+        events.record_logs(&[format!(
+            "{PROGRAM_DATA}{}",
+            STANDARD.encode(Withdraw { amount: 2 }.data())
+        )]);
+        events.assert_order(&[Deposit::DISCRIMINATOR, Withdraw::DISCRIMINATOR]);
+    }
+}
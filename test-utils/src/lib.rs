@@ -0,0 +1,139 @@
+//! Utilities to set up the state of the tests of Anchor programs.
+//!
+//! [`AccountBuilder`] builds the accounts of a program from their state, so
+//! that tests can add them to the genesis of `solana-program-test` or
+//! LiteSVM instead of sending the instructions that initialize them.
+//!
+//! ```ignore
+//! use anchor_test_utils::AccountBuilder;
+//!
+//! let vault = AccountBuilder::new(&Vault {
+//!     authority,
+//!     amount: 100,
+//! })
+//! .space(8 + Vault::INIT_SPACE)
+//! .build();
+//!
+//! let mut program_test = ProgramTest::new("vault", vault::ID, None);
+//! program_test.add_account(vault_address, vault.into());
+//! ```
+//!
+//! [`TimeTravel`] moves the clock of LiteSVM, with the `litesvm` feature, and
+//! [`ProgramTestTimeTravel`] the clock of `solana-program-test`, with the
+//! `program-test` feature.
+//!
+//! [`assert_account_snapshot!`] compares the state of an account with a
+//! snapshot committed with the tests, and [`EventRecorder`] asserts the events
+//! emitted by the transactions of a test.
+//!
+//! The functions of [`account_info`] build leaked account infos, e.g. to
+//! validate accounts structs with [`account_info::try_accounts`] without
+//! running a program.
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{AccountSerialize, Owner, ZeroCopy};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::rent::Rent;
+
+#[cfg(feature = "program-test")]
+pub use clock::ProgramTestTimeTravel;
+pub use clock::TimeTravel;
+pub use events::EventRecorder;
+#[doc(hidden)]
+pub use snapshot::__fn_path;
+pub use snapshot::{check_account_snapshot, AccountSource, UPDATE_SNAPSHOTS_ENV};
+
+pub mod account_info;
+mod clock;
+mod events;
+mod snapshot;
+
+/// Builder of the account of a program, holding the discriminator and the
+/// serialized fields of an `#[account]` type.
+#[derive(Debug, Clone)]
+pub struct AccountBuilder {
+    data: Vec<u8>,
+    owner: Pubkey,
+    lamports: Option<u64>,
+    space: Option<usize>,
+}
+
+impl AccountBuilder {
+    /// Creates the builder of a borsh account, owned by the program of its
+    /// type.
+    pub fn new<T: AccountSerialize + Owner>(state: &T) -> Self {
+        let mut data = Vec::new();
+        state
+            .try_serialize(&mut data)
+            .expect("Failed to serialize the account");
+        Self::from_data(data, T::owner())
+    }
+
+    /// Creates the builder of a `zero_copy` account, owned by the program of
+    /// its type.
+    pub fn zero_copy<T: ZeroCopy + Owner>(state: &T) -> Self {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(state));
+        Self::from_data(data, T::owner())
+    }
+
+    /// Creates the builder of an account with the given data, discriminator
+    /// included.
+    pub fn from_data(data: Vec<u8>, owner: Pubkey) -> Self {
+        Self {
+            data,
+            owner,
+            lamports: None,
+            space: None,
+        }
+    }
+
+    /// Sets the owner of the account, e.g. for types of another program.
+    pub fn owner(mut self, owner: Pubkey) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    /// Sets the balance of the account, which is rent exempt by default.
+    pub fn lamports(mut self, lamports: u64) -> Self {
+        self.lamports = Some(lamports);
+        self
+    }
+
+    /// Pads the data of the account with zeros up to `space` bytes, e.g. to
+    /// the `space` of the `init` constraint of the account.
+    ///
+    /// # Panics
+    ///
+    /// Panics on [`build`](Self::build) if the data takes more than `space`
+    /// bytes.
+    pub fn space(mut self, space: usize) -> Self {
+        self.space = Some(space);
+        self
+    }
+
+    /// Builds the account.
+    pub fn build(self) -> AccountSharedData {
+        let mut data = self.data;
+        if let Some(space) = self.space {
+            assert!(
+                data.len() <= space,
+                "The account takes {} bytes, more than its space of {space} bytes",
+                data.len()
+            );
+            data.resize(space, 0);
+        }
+        let lamports = self
+            .lamports
+            .unwrap_or_else(|| Rent::default().minimum_balance(data.len()));
+
+        Account {
+            lamports,
+            data,
+            owner: self.owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into()
+    }
+}
@@ -0,0 +1,238 @@
+use std::{fmt::Debug, fmt::Write, fs, path::Path};
+
+use anchor_lang::{solana_program::pubkey::Pubkey, AccountDeserialize};
+
+/// Environment variable which, when set, makes the snapshot assertions write
+/// the snapshots instead of comparing the accounts against them.
+///
+/// It's ignored when `CI` is set, so that a missing or outdated snapshot
+/// always fails the tests of CI.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "ANCHOR_UPDATE_SNAPSHOTS";
+
+/// Test environment from which snapshot assertions read the accounts.
+pub trait AccountSource {
+    /// Returns the data of the account, if it exists.
+    fn account_data(&self, address: &Pubkey) -> Option<Vec<u8>>;
+}
+
+impl<T: AccountSource + ?Sized> AccountSource for &T {
+    fn account_data(&self, address: &Pubkey) -> Option<Vec<u8>> {
+        (**self).account_data(address)
+    }
+}
+
+impl AccountSource for solana_client::rpc_client::RpcClient {
+    fn account_data(&self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.get_account(address).ok().map(|account| account.data)
+    }
+}
+
+#[cfg(feature = "litesvm")]
+impl AccountSource for litesvm::LiteSVM {
+    fn account_data(&self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.get_account(address).map(|account| account.data)
+    }
+}
+
+/// Asserts that the account at `address`, deserialized as `T` and formatted
+/// with `{:#?}`, matches the snapshot `name` of the test `test` in `dir`.
+///
+/// Missing snapshots fail the assertion like mismatching ones, and are
+/// written along with the mismatching ones when [`UPDATE_SNAPSHOTS_ENV`] is
+/// set. Prefer [`assert_account_snapshot!`],
+/// which keeps the snapshots in the `snapshots` directory of the crate.
+///
+/// [`assert_account_snapshot!`]: crate::assert_account_snapshot
+#[track_caller]
+pub fn check_account_snapshot<T: AccountDeserialize + Debug>(
+    source: &impl AccountSource,
+    address: &Pubkey,
+    dir: &Path,
+    test: &str,
+    name: &str,
+) {
+    let data = source
+        .account_data(address)
+        .unwrap_or_else(|| panic!("Account {address} not found"));
+    let account = T::try_deserialize(&mut data.as_slice())
+        .unwrap_or_else(|e| panic!("Failed to deserialize account {address}: {e}"));
+
+    let path = dir.join(format!("{}__{}.snap", sanitize(test), sanitize(name)));
+    let update =
+        std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() && std::env::var_os("CI").is_none();
+    if let Err(e) = check_snapshot(&format!("{account:#?}\n"), &path, update) {
+        panic!("{e}");
+    }
+}
+
+/// Asserts that a typed account matches its committed snapshot.
+///
+/// The account at the address is read from the test environment, e.g.
+/// LiteSVM or an `RpcClient`, deserialized as the given type, which must
+/// implement `Debug`, and compared against
+/// `snapshots/<test>__<name>.snap` in the crate of the test, where `<test>`
+/// is the module path of the function invoking the macro. The name defaults
+/// to the type.
+///
+/// Snapshots are written by running the tests with
+/// `ANCHOR_UPDATE_SNAPSHOTS=1`, which also rewrites the snapshots that don't
+/// match. Otherwise, and always on CI, a missing snapshot fails the test.
+///
+/// ```ignore
+/// assert_account_snapshot!(svm, vault_address, Vault);
+/// assert_account_snapshot!(svm, vault_address, Vault, "after_withdraw");
+/// ```
+#[macro_export]
+macro_rules! assert_account_snapshot {
+    ($source:expr, $address:expr, $ty:ty $(,)?) => {
+        $crate::assert_account_snapshot!($source, $address, $ty, stringify!($ty))
+    };
+    ($source:expr, $address:expr, $ty:ty, $name:expr $(,)?) => {
+        $crate::check_account_snapshot::<$ty>(
+            &$source,
+            &$address,
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots"),
+            &{
+                fn f() {}
+                $crate::__fn_path(f)
+            },
+            $name,
+        )
+    };
+}
+
+/// Returns the path of the function enclosing `f`, a function item defined in
+/// it, without the crate. Used by [`assert_account_snapshot!`] to name the
+/// snapshots after their test, since the names of the test threads aren't
+/// reliable, e.g. with `--test-threads=1`.
+#[doc(hidden)]
+pub fn __fn_path<F>(_: F) -> String {
+    let path = std::any::type_name::<F>();
+    let mut path = path.strip_suffix("::f").unwrap_or(path);
+    // Async tests invoke the macro in a closure
+    while let Some(stripped) = path.strip_suffix("::{{closure}}") {
+        path = stripped;
+    }
+    path.split_once("::")
+        .map_or(path, |(_, path)| path)
+        .to_owned()
+}
+
+// Compares the formatted account with its snapshot, writing the snapshot
+// instead if `update` is set.
+fn check_snapshot(actual: &str, path: &Path, update: bool) -> Result<(), String> {
+    let write = || {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, actual).map_err(|e| format!("Unable to write {}: {e}", path.display()))
+    };
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(_) if update => {
+            println!("Writing new snapshot {}", path.display());
+            return write();
+        }
+        Err(_) => {
+            return Err(format!(
+                "Snapshot {} is missing, run the test with {UPDATE_SNAPSHOTS_ENV}=1 outside of CI \
+                to write it",
+                path.display()
+            ))
+        }
+    };
+    if expected == actual {
+        return Ok(());
+    }
+    if update {
+        println!("Updating snapshot {}", path.display());
+        return write();
+    }
+
+    let mut diff = String::new();
+    let (expected_lines, actual_lines) = (
+        expected.lines().collect::<Vec<_>>(),
+        actual.lines().collect::<Vec<_>>(),
+    );
+    for i in 0..expected_lines.len().max(actual_lines.len()) {
+        match (expected_lines.get(i), actual_lines.get(i)) {
+            (Some(expected), Some(actual)) if expected == actual => {
+                let _ = writeln!(diff, "  {expected}");
+            }
+            (expected, actual) => {
+                if let Some(expected) = expected {
+                    let _ = writeln!(diff, "- {expected}");
+                }
+                if let Some(actual) = actual {
+                    let _ = writeln!(diff, "+ {actual}");
+                }
+            }
+        }
+    }
+    Err(format!(
+        "The account doesn't match snapshot {}, rerun with {UPDATE_SNAPSHOTS_ENV}=1 to update \
+        it:\n{diff}",
+        path.display()
+    ))
+}
+
+fn sanitize(name: &str) -> String {
+    name.replace(' ', "")
+        .replace("::", "__")
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("anchor-test-utils-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_check_snapshot() {
+        let path = snapshot_path("vault.snap");
+
+        check_snapshot("Vault {\n    amount: 1,\n}\n", &path, true).unwrap();
+        check_snapshot("Vault {\n    amount: 1,\n}\n", &path, false).unwrap();
+        let err = check_snapshot("Vault {\n    amount: 2,\n}\n", &path, false).unwrap_err();
+        assert!(err.contains("-     amount: 1,\n+     amount: 2,"));
+
+        check_snapshot("Vault {\n    amount: 2,\n}\n", &path, true).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Vault {\n    amount: 2,\n}\n"
+        );
+        assert_eq!(sanitize("tests::deposit"), "tests__deposit");
+        assert_eq!(sanitize("vault :: Vault"), "vault__Vault");
+    }
+
+    #[test]
+    fn test_fn_path() {
+        fn f() {}
+        assert_eq!(__fn_path(f), "snapshot::tests::test_fn_path");
+        assert_eq!(sanitize(&__fn_path(f)), "snapshot__tests__test_fn_path");
+    }
+
+    #[test]
+    fn test_check_snapshot_missing() {
+        let path = snapshot_path("missing.snap");
+
+        let err = check_snapshot("Vault {\n    amount: 1,\n}\n", &path, false).unwrap_err();
+        assert!(err.contains("is missing"));
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_test_utils::AccountBuilder;
+use solana_sdk::account::ReadableAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[account(zero_copy)]
+pub struct Counter {
+    pub count: u64,
+    pub bump: u8,
+    pub padding: [u8; 7],
+}
+
+fn vault() -> Vault {
+    Vault {
+        authority: Pubkey::new_unique(),
+        amount: 100,
+    }
+}
+
+#[test]
+fn test_account_builder() {
+    let vault = vault();
+    let account = AccountBuilder::new(&vault).build();
+
+    assert_eq!(account.owner(), &crate::ID);
+    assert_eq!(account.data().len(), 8 + 32 + 8);
+    assert_eq!(&account.data()[..8], Vault::DISCRIMINATOR);
+    assert_eq!(&account.data()[8..40], vault.authority.as_ref());
+    assert_eq!(&account.data()[40..], 100u64.to_le_bytes());
+
+    let deserialized = Vault::try_deserialize(&mut account.data()).unwrap();
+    assert_eq!(deserialized.authority, vault.authority);
+    assert_eq!(deserialized.amount, vault.amount);
+}
+
+#[test]
+fn test_account_builder_space() {
+    let account = AccountBuilder::new(&vault()).space(8 + 32 + 8 + 16).build();
+
+    assert_eq!(account.data().len(), 8 + 32 + 8 + 16);
+    assert!(account.data()[8 + 32 + 8..].iter().all(|byte| *byte == 0));
+    assert_eq!(
+        account.lamports(),
+        Rent::default().minimum_balance(8 + 32 + 8 + 16)
+    );
+}
+
+#[test]
+#[should_panic(expected = "more than its space")]
+fn test_account_builder_space_too_small() {
+    AccountBuilder::new(&vault()).space(8 + 32).build();
+}
+
+#[test]
+fn test_account_builder_lamports() {
+    let account = AccountBuilder::new(&vault()).build();
+    assert_eq!(
+        account.lamports(),
+        Rent::default().minimum_balance(8 + 32 + 8)
+    );
+
+    let account = AccountBuilder::new(&vault()).lamports(1).build();
+    assert_eq!(account.lamports(), 1);
+}
+
+#[test]
+fn test_account_builder_owner() {
+    let owner = Pubkey::new_unique();
+    let account = AccountBuilder::new(&vault()).owner(owner).build();
+    assert_eq!(account.owner(), &owner);
+}
+
+#[test]
+fn test_account_builder_zero_copy() {
+    let counter = Counter {
+        count: 42,
+        bump: 254,
+        padding: [0; 7],
+    };
+    let account = AccountBuilder::zero_copy(&counter)
+        .space(8 + 16 + 8)
+        .build();
+
+    assert_eq!(account.owner(), &crate::ID);
+    assert_eq!(account.data().len(), 8 + 16 + 8);
+    assert_eq!(&account.data()[..8], Counter::DISCRIMINATOR);
+    assert_eq!(&account.data()[8..16], 42u64.to_le_bytes());
+    assert_eq!(account.data()[16], 254);
+    assert!(account.data()[17..].iter().all(|byte| *byte == 0));
+}
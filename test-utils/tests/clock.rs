@@ -0,0 +1,29 @@
+#![cfg(feature = "litesvm")]
+
+use anchor_test_utils::TimeTravel;
+use litesvm::LiteSVM;
+use solana_sdk::clock::Clock;
+use solana_sdk::epoch_schedule::EpochSchedule;
+
+#[test]
+fn test_warp_to_slot() {
+    let mut svm = LiteSVM::new();
+    svm.set_unix_timestamp(1_000);
+    TimeTravel::warp_to_slot(&mut svm, 500);
+
+    let clock = svm.get_sysvar::<Clock>();
+    assert_eq!(clock.slot, 500);
+    assert_eq!(clock.unix_timestamp, 1_000);
+}
+
+#[test]
+fn test_advance_epoch() {
+    let mut svm = LiteSVM::new();
+    let epoch = svm.get_sysvar::<Clock>().epoch;
+    svm.advance_epoch();
+
+    let clock = svm.get_sysvar::<Clock>();
+    let schedule = svm.get_sysvar::<EpochSchedule>();
+    assert_eq!(clock.epoch, epoch + 1);
+    assert_eq!(clock.slot, schedule.get_first_slot_in_epoch(epoch + 1));
+}
@@ -442,6 +442,7 @@ mod wrapped {
                 generics: Default::default(),
                 serialization: Default::default(),
                 repr: Default::default(),
+                layout: Default::default(),
             })
         }
     }
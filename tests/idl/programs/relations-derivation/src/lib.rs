@@ -2,6 +2,13 @@ use anchor_lang::prelude::*;
 
 declare_id!("Re1ationsDerivation111111111111111111111111");
 
+declare_interface! {
+    pub struct Oracle = [
+        pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"),
+        pubkey!("GfsHGXWFd5a2wqPhCzXkir7tPbadYHPcRh1kjkYV1cNP"),
+    ];
+}
+
 #[program]
 pub mod relations_derivation {
     use super::*;
@@ -15,6 +22,10 @@ pub mod relations_derivation {
     pub fn test_relation(_ctx: Context<TestRelation>) -> Result<()> {
         Ok(())
     }
+
+    pub fn test_interface(_ctx: Context<TestInterface>) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -58,6 +69,11 @@ pub struct TestRelation<'info> {
     nested: Nested<'info>,
 }
 
+#[derive(Accounts)]
+pub struct TestInterface<'info> {
+    oracle: Interface<'info, Oracle>,
+}
+
 #[account]
 pub struct MyAccount {
     pub my_account: Pubkey,
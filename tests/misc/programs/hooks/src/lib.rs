@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Hooks11111111111111111111111111111111111111");
+
+#[program]
+pub mod hooks {
+    use super::*;
+
+    #[before_instruction]
+    pub fn before(_program_id: &Pubkey, ix_name: &str, _accounts: &[AccountInfo]) -> Result<()> {
+        msg!("Before {}", ix_name);
+        require_neq!(ix_name, "rejected", HooksError::Rejected);
+        Ok(())
+    }
+
+    #[after_instruction]
+    pub fn after(_program_id: &Pubkey, ix_name: &str, accounts: &[AccountInfo]) -> Result<()> {
+        if ix_name == "increment" {
+            // The accounts have been persisted when the hook runs
+            let counter = Counter::try_deserialize(&mut &accounts[0].try_borrow_data()?[..])?;
+            msg!("After {}: {}", ix_name, counter.count);
+        } else {
+            msg!("After {}", ix_name);
+        }
+        Ok(())
+    }
+
+    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn increment(ctx: Context<Increment>) -> Result<()> {
+        ctx.accounts.counter.count += 1;
+        Ok(())
+    }
+
+    #[no_log_ix_name]
+    pub fn quiet(_ctx: Context<Empty>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn rejected(_ctx: Context<Empty>) -> Result<()> {
+        msg!("Handler rejected");
+        Ok(())
+    }
+
+    pub fn failed(_ctx: Context<Empty>) -> Result<()> {
+        err!(HooksError::Failed)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + Counter::INIT_SPACE)]
+    pub counter: Account<'info, Counter>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Increment<'info> {
+    #[account(mut)]
+    pub counter: Account<'info, Counter>,
+}
+
+#[derive(Accounts)]
+pub struct Empty {}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Counter {
+    pub count: u64,
+}
+
+#[error_code]
+pub enum HooksError {
+    #[msg("Rejected by the before instruction hook")]
+    Rejected,
+    #[msg("Failed in the instruction handler")]
+    Failed,
+}
@@ -0,0 +1,75 @@
+import * as anchor from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { Hooks } from "../../target/types/hooks";
+
+describe("hooks", () => {
+  // Configure the client to use the local cluster
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Hooks as anchor.Program<Hooks>;
+  const counter = anchor.web3.Keypair.generate();
+
+  const getLogs = async (signature: string) => {
+    const tx = await program.provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return tx.meta.logMessages;
+  };
+
+  it("Runs the hooks around the instruction", async () => {
+    await program.methods
+      .initialize()
+      .accounts({ counter: counter.publicKey })
+      .signers([counter])
+      .rpc();
+
+    const signature = await program.methods
+      .increment()
+      .accounts({ counter: counter.publicKey })
+      .rpc({ commitment: "confirmed" });
+
+    const logs = await getLogs(signature);
+    const before = logs.indexOf("Program log: Before increment");
+    const after = logs.indexOf("Program log: After increment: 1");
+    assert.isAbove(before, logs.indexOf("Program log: Instruction: Increment"));
+    assert.isAbove(after, before);
+  });
+
+  it("Does not log the name of instructions without the log", async () => {
+    const signature = await program.methods
+      .quiet()
+      .rpc({ commitment: "confirmed" });
+
+    const logs = await getLogs(signature);
+    assert.notInclude(logs, "Program log: Instruction: Quiet");
+    assert.include(logs, "Program log: Before quiet");
+  });
+
+  it("Aborts the instruction when the before hook fails", async () => {
+    try {
+      await program.methods.rejected().rpc();
+      assert.isTrue(false);
+    } catch (_err) {
+      assert.isTrue(_err instanceof anchor.AnchorError);
+      const err: anchor.AnchorError = _err;
+      assert.strictEqual(err.error.errorCode.code, "Rejected");
+      assert.include(err.logs, "Program log: Before rejected");
+      assert.notInclude(err.logs, "Program log: Handler rejected");
+    }
+  });
+
+  it("Does not run the after hook when the instruction fails", async () => {
+    try {
+      await program.methods.failed().rpc();
+      assert.isTrue(false);
+    } catch (_err) {
+      assert.isTrue(_err instanceof anchor.AnchorError);
+      const err: anchor.AnchorError = _err;
+      assert.strictEqual(err.error.errorCode.code, "Failed");
+      assert.include(err.logs, "Program log: Before failed");
+      assert.notInclude(err.logs, "Program log: After failed");
+    }
+  });
+});
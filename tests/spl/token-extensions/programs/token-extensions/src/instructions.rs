@@ -178,3 +178,36 @@ pub struct CheckMintExtensionConstraints<'info> {
     )]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
 }
+
+#[derive(Accounts)]
+pub struct CheckExtensionPresence<'info> {
+    #[account(
+        extensions::metadata_pointer,
+        extensions::metadata,
+        extensions::group_member_pointer,
+        extensions::transfer_hook,
+        extensions::close_authority,
+        extensions::permanent_delegate,
+        extensions::transfer_fee = none,
+        extensions::non_transferable = none,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        extensions::immutable_owner,
+        extensions::transfer_hook_account,
+        extensions::memo_transfer = none,
+    )]
+    pub mint_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct CheckExtensionMissing<'info> {
+    #[account(extensions::metadata_pointer, extensions::transfer_fee)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct CheckExtensionForbidden<'info> {
+    #[account(extensions::metadata_pointer, extensions::permanent_delegate = none)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
@@ -29,4 +29,16 @@ pub mod token_extensions {
     ) -> Result<()> {
         Ok(())
     }
+
+    pub fn check_extension_presence(_ctx: Context<CheckExtensionPresence>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn check_extension_missing(_ctx: Context<CheckExtensionMissing>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn check_extension_forbidden(_ctx: Context<CheckExtensionForbidden>) -> Result<()> {
+        Ok(())
+    }
 }
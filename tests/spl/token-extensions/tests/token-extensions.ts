@@ -1,9 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { AnchorError, Program } from "@coral-xyz/anchor";
 import { PublicKey, Keypair } from "@solana/web3.js";
 import { TokenExtensions } from "../target/types/token_extensions";
 import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
 import { it } from "node:test";
+import { assert } from "chai";
 
 const TOKEN_2022_PROGRAM_ID = new anchor.web3.PublicKey(
   "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
@@ -81,4 +82,51 @@ describe("token extensions", () => {
       .signers([payer])
       .rpc();
   });
+
+  it("extension presence constraints test passes", async () => {
+    await program.methods
+      .checkExtensionPresence()
+      .accountsStrict({
+        mint: mint.publicKey,
+        mintTokenAccount: associatedAddress({
+          mint: mint.publicKey,
+          owner: payer.publicKey,
+        }),
+      })
+      .rpc();
+  });
+
+  it("extension presence constraint fails for a missing extension", async () => {
+    try {
+      await program.methods
+        .checkExtensionMissing()
+        .accountsStrict({ mint: mint.publicKey })
+        .rpc();
+      assert.isTrue(false);
+    } catch (_err) {
+      assert.isTrue(_err instanceof AnchorError);
+      const err: AnchorError = _err;
+      assert.strictEqual(
+        err.error.errorCode.code,
+        "ConstraintExtensionMissing"
+      );
+    }
+  });
+
+  it("extension presence constraint fails for a forbidden extension", async () => {
+    try {
+      await program.methods
+        .checkExtensionForbidden()
+        .accountsStrict({ mint: mint.publicKey })
+        .rpc();
+      assert.isTrue(false);
+    } catch (_err) {
+      assert.isTrue(_err instanceof AnchorError);
+      const err: AnchorError = _err;
+      assert.strictEqual(
+        err.error.errorCode.code,
+        "ConstraintExtensionForbidden"
+      );
+    }
+  });
 });
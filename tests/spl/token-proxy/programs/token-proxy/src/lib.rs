@@ -3,10 +3,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
+    token::Token,
     token_2022::spl_token_2022,
     token_interface::{
-        self, Burn, Mint, MintTo, SetAuthority, TokenAccount, TokenInterface, Transfer,
-        TransferChecked,
+        self, Burn, Mint, MintTo, SetAuthority, Token2022, TokenAccount, TokenFlavor,
+        TokenInterface, Transfer, TransferChecked,
     },
 };
 
@@ -49,6 +50,20 @@ mod token_proxy {
         }
     }
 
+    pub fn proxy_flavor_transfer(
+        ctx: Context<ProxyFlavorTransfer<Token>>,
+        amount: u64,
+    ) -> Result<()> {
+        flavor_transfer(ctx, amount)
+    }
+
+    pub fn proxy_flavor_transfer_2022(
+        ctx: Context<ProxyFlavorTransfer<Token2022>>,
+        amount: u64,
+    ) -> Result<()> {
+        flavor_transfer(ctx, amount)
+    }
+
     pub fn proxy_mint_to(ctx: Context<ProxyMintTo>, amount: u64) -> Result<()> {
         token_interface::mint_to(ctx.accounts.into(), amount)
     }
@@ -80,6 +95,21 @@ mod token_proxy {
     }
 }
 
+fn flavor_transfer<T: TokenFlavor>(
+    ctx: Context<ProxyFlavorTransfer<T>>,
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.from.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.to.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+    T::transfer(cpi_context, amount, ctx.accounts.mint.decimals)
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub enum AuthorityType {
     /// Authority to mint new tokens
@@ -117,6 +147,18 @@ pub struct ProxyOptionalTransfer<'info> {
     pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+#[derive(Accounts)]
+pub struct ProxyFlavorTransfer<'info, T: TokenFlavor> {
+    pub authority: Signer<'info>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Program<'info, T>,
+}
+
 #[derive(Accounts)]
 pub struct ProxyMintTo<'info> {
     #[account(signer)]
@@ -188,6 +188,61 @@ describe("program", () => {
         );
       });
 
+      it("Transfers a token with a generic token program", async () => {
+        const preFromAccount = await getTokenAccount(provider, from);
+        const preToAccount = await getTokenAccount(provider, to);
+
+        const transferAmount = new anchor.BN(10);
+        const accounts = {
+          authority: provider.wallet.publicKey,
+          from,
+          to,
+          mint,
+          tokenProgram: tokenProgram.programId,
+        };
+        if (name === "token") {
+          await program.rpc.proxyFlavorTransfer(transferAmount, { accounts });
+        } else {
+          await program.rpc.proxyFlavorTransfer2022(transferAmount, {
+            accounts,
+          });
+        }
+
+        const postFromAccount = await getTokenAccount(provider, from);
+        const postToAccount = await getTokenAccount(provider, to);
+
+        assert.isTrue(
+          postFromAccount.amount.eq(preFromAccount.amount.sub(transferAmount))
+        );
+        assert.isTrue(
+          postToAccount.amount.eq(preToAccount.amount.add(transferAmount))
+        );
+      });
+
+      it("Does not transfer a token with the other flavor", async () => {
+        const accounts = {
+          authority: provider.wallet.publicKey,
+          from,
+          to,
+          mint,
+          tokenProgram: tokenProgram.programId,
+        };
+        try {
+          if (name === "token") {
+            await program.rpc.proxyFlavorTransfer2022(new anchor.BN(10), {
+              accounts,
+            });
+          } else {
+            await program.rpc.proxyFlavorTransfer(new anchor.BN(10), {
+              accounts,
+            });
+          }
+          assert.fail("Expected the transfer to fail");
+        } catch (err) {
+          assert.strictEqual(err.error.errorCode.code, "InvalidProgramId");
+        }
+      });
+
       it("Does not transfer a token without optional accounts", async () => {
         const preFromAccount = await getTokenAccount(provider, from);
         const preToAccount = await getTokenAccount(provider, to);
@@ -105,6 +105,28 @@ export const ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION = 2037;
 export const ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION_AUTHORITY = 2038;
 /** A transfer hook extension transfer hook program id constraint was violated. */
 export const ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION_PROGRAM_ID = 2039;
+/** A not_before constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_NOT_BEFORE = 2040;
+/** A not_after constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_NOT_AFTER = 2041;
+/** An authority_any_of constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_AUTHORITY_ANY_OF = 2042;
+/** A required token extension is missing. */
+export const ANCHOR_ERROR__CONSTRAINT_EXTENSION_MISSING = 2043;
+/** A forbidden token extension is present. */
+export const ANCHOR_ERROR__CONSTRAINT_EXTENSION_FORBIDDEN = 2044;
+/** A token group member extension constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_MINT_TOKEN_GROUP_MEMBER_EXTENSION = 2045;
+/** A token group member extension group constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_MINT_TOKEN_GROUP_MEMBER_EXTENSION_GROUP = 2046;
+/** A mint close authority constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_MINT_CLOSE_AUTHORITY = 2047;
+/** A mint permanent delegate constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_MINT_PERMANENT_DELEGATE = 2048;
+/** A with_seed constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_WITH_SEED = 2049;
+/** A vault constraint was violated. */
+export const ANCHOR_ERROR__CONSTRAINT_TOKEN_VAULT = 2050;
 
 // Require errors.
 
@@ -161,6 +183,8 @@ export const ANCHOR_ERROR__ACCOUNT_SYSVAR_MISMATCH = 3015;
 export const ANCHOR_ERROR__ACCOUNT_REALLOC_EXCEEDS_LIMIT = 3016;
 /** The account was duplicated for more than one reallocation. */
 export const ANCHOR_ERROR__ACCOUNT_DUPLICATE_REALLOCS = 3017;
+/** The given account is not a buffer account. */
+export const ANCHOR_ERROR__ACCOUNT_NOT_BUFFER = 3018;
 
 // Miscellaneous errors.
 
@@ -170,6 +194,8 @@ export const ANCHOR_ERROR__DECLARED_PROGRAM_ID_MISMATCH = 4100;
 export const ANCHOR_ERROR__TRYING_TO_INIT_PAYER_AS_PROGRAM_ACCOUNT = 4101;
 /** The program could not perform the numeric conversion, out of range integral type conversion attempted. */
 export const ANCHOR_ERROR__INVALID_NUMERIC_CONVERSION = 4102;
+/** Checked arithmetic operation overflowed. */
+export const ANCHOR_ERROR__MATH_OVERFLOW = 4103;
 
 // Deprecated errors.
 
@@ -226,6 +252,17 @@ export type AnchorErrorCode =
   | typeof ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION
   | typeof ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION_AUTHORITY
   | typeof ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION_PROGRAM_ID
+  | typeof ANCHOR_ERROR__CONSTRAINT_NOT_BEFORE
+  | typeof ANCHOR_ERROR__CONSTRAINT_NOT_AFTER
+  | typeof ANCHOR_ERROR__CONSTRAINT_AUTHORITY_ANY_OF
+  | typeof ANCHOR_ERROR__CONSTRAINT_EXTENSION_MISSING
+  | typeof ANCHOR_ERROR__CONSTRAINT_EXTENSION_FORBIDDEN
+  | typeof ANCHOR_ERROR__CONSTRAINT_MINT_TOKEN_GROUP_MEMBER_EXTENSION
+  | typeof ANCHOR_ERROR__CONSTRAINT_MINT_TOKEN_GROUP_MEMBER_EXTENSION_GROUP
+  | typeof ANCHOR_ERROR__CONSTRAINT_MINT_CLOSE_AUTHORITY
+  | typeof ANCHOR_ERROR__CONSTRAINT_MINT_PERMANENT_DELEGATE
+  | typeof ANCHOR_ERROR__CONSTRAINT_WITH_SEED
+  | typeof ANCHOR_ERROR__CONSTRAINT_TOKEN_VAULT
   | typeof ANCHOR_ERROR__REQUIRE_VIOLATED
   | typeof ANCHOR_ERROR__REQUIRE_EQ_VIOLATED
   | typeof ANCHOR_ERROR__REQUIRE_KEYS_EQ_VIOLATED
@@ -251,7 +288,9 @@ export type AnchorErrorCode =
   | typeof ANCHOR_ERROR__ACCOUNT_SYSVAR_MISMATCH
   | typeof ANCHOR_ERROR__ACCOUNT_REALLOC_EXCEEDS_LIMIT
   | typeof ANCHOR_ERROR__ACCOUNT_DUPLICATE_REALLOCS
+  | typeof ANCHOR_ERROR__ACCOUNT_NOT_BUFFER
   | typeof ANCHOR_ERROR__DECLARED_PROGRAM_ID_MISMATCH
   | typeof ANCHOR_ERROR__TRYING_TO_INIT_PAYER_AS_PROGRAM_ACCOUNT
   | typeof ANCHOR_ERROR__INVALID_NUMERIC_CONVERSION
+  | typeof ANCHOR_ERROR__MATH_OVERFLOW
   | typeof ANCHOR_ERROR__DEPRECATED;
@@ -33,7 +33,10 @@ export class BorshAccountsCoder<A extends string = string>
       if (!typeDef) {
         throw new Error(`Account not found: ${acc.name}`);
       }
-      return [acc.name as A, IdlCoder.typeDefLayout({ typeDef, types })];
+      return [
+        acc.name as A,
+        IdlCoder.typeDefLayout({ typeDef, types, genericArgs: acc.generics }),
+      ];
     });
 
     this.accountLayouts = new Map(layouts);
@@ -93,9 +96,12 @@ export class BorshAccountsCoder<A extends string = string>
   }
 
   public size(accountName: A): number {
+    const generics = this.idl.accounts?.find(
+      (acc) => acc.name === accountName
+    )?.generics;
     return (
       DISCRIMINATOR_SIZE +
-      IdlCoder.typeSize({ defined: { name: accountName } }, this.idl)
+      IdlCoder.typeSize({ defined: { name: accountName, generics } }, this.idl)
     );
   }
 
@@ -387,6 +387,22 @@ export const LangErrorCode = {
     errors.ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION_AUTHORITY,
   ConstraintMintTransferHookExtensionProgramId:
     errors.ANCHOR_ERROR__CONSTRAINT_MINT_TRANSFER_HOOK_EXTENSION_PROGRAM_ID,
+  ConstraintNotBefore: errors.ANCHOR_ERROR__CONSTRAINT_NOT_BEFORE,
+  ConstraintNotAfter: errors.ANCHOR_ERROR__CONSTRAINT_NOT_AFTER,
+  ConstraintAuthorityAnyOf: errors.ANCHOR_ERROR__CONSTRAINT_AUTHORITY_ANY_OF,
+  ConstraintExtensionMissing: errors.ANCHOR_ERROR__CONSTRAINT_EXTENSION_MISSING,
+  ConstraintExtensionForbidden:
+    errors.ANCHOR_ERROR__CONSTRAINT_EXTENSION_FORBIDDEN,
+  ConstraintMintTokenGroupMemberExtension:
+    errors.ANCHOR_ERROR__CONSTRAINT_MINT_TOKEN_GROUP_MEMBER_EXTENSION,
+  ConstraintMintTokenGroupMemberExtensionGroup:
+    errors.ANCHOR_ERROR__CONSTRAINT_MINT_TOKEN_GROUP_MEMBER_EXTENSION_GROUP,
+  ConstraintMintCloseAuthority:
+    errors.ANCHOR_ERROR__CONSTRAINT_MINT_CLOSE_AUTHORITY,
+  ConstraintMintPermanentDelegate:
+    errors.ANCHOR_ERROR__CONSTRAINT_MINT_PERMANENT_DELEGATE,
+  ConstraintWithSeed: errors.ANCHOR_ERROR__CONSTRAINT_WITH_SEED,
+  ConstraintTokenVault: errors.ANCHOR_ERROR__CONSTRAINT_TOKEN_VAULT,
 
   // Require.
   RequireViolated: errors.ANCHOR_ERROR__REQUIRE_VIOLATED,
@@ -422,12 +438,14 @@ export const LangErrorCode = {
   AccountReallocExceedsLimit:
     errors.ANCHOR_ERROR__ACCOUNT_REALLOC_EXCEEDS_LIMIT,
   AccountDuplicateReallocs: errors.ANCHOR_ERROR__ACCOUNT_DUPLICATE_REALLOCS,
+  AccountNotBuffer: errors.ANCHOR_ERROR__ACCOUNT_NOT_BUFFER,
 
   // Miscellaneous
   DeclaredProgramIdMismatch: errors.ANCHOR_ERROR__DECLARED_PROGRAM_ID_MISMATCH,
   TryingToInitPayerAsProgramAccount:
     errors.ANCHOR_ERROR__TRYING_TO_INIT_PAYER_AS_PROGRAM_ACCOUNT,
   InvalidNumericConversion: errors.ANCHOR_ERROR__INVALID_NUMERIC_CONVERSION,
+  MathOverflow: errors.ANCHOR_ERROR__MATH_OVERFLOW,
 
   // Used for APIs that shouldn't be used anymore.
   Deprecated: errors.ANCHOR_ERROR__DEPRECATED,
@@ -591,6 +609,38 @@ export const LangErrorMessage = new Map<number, string>([
     LangErrorCode.ConstraintMintTransferHookExtensionProgramId,
     "A transfer hook extension transfer hook program id constraint was violated",
   ],
+  [LangErrorCode.ConstraintNotBefore, "A not_before constraint was violated"],
+  [LangErrorCode.ConstraintNotAfter, "A not_after constraint was violated"],
+  [
+    LangErrorCode.ConstraintAuthorityAnyOf,
+    "An authority_any_of constraint was violated",
+  ],
+  [
+    LangErrorCode.ConstraintExtensionMissing,
+    "A required token extension is missing",
+  ],
+  [
+    LangErrorCode.ConstraintExtensionForbidden,
+    "A forbidden token extension is present",
+  ],
+  [
+    LangErrorCode.ConstraintMintTokenGroupMemberExtension,
+    "A token group member extension constraint was violated",
+  ],
+  [
+    LangErrorCode.ConstraintMintTokenGroupMemberExtensionGroup,
+    "A token group member extension group constraint was violated",
+  ],
+  [
+    LangErrorCode.ConstraintMintCloseAuthority,
+    "A mint close authority constraint was violated",
+  ],
+  [
+    LangErrorCode.ConstraintMintPermanentDelegate,
+    "A mint permanent delegate constraint was violated",
+  ],
+  [LangErrorCode.ConstraintWithSeed, "A with_seed constraint was violated"],
+  [LangErrorCode.ConstraintTokenVault, "A vault constraint was violated"],
 
   // Require.
   [LangErrorCode.RequireViolated, "A require expression was violated"],
@@ -662,6 +712,10 @@ export const LangErrorMessage = new Map<number, string>([
     LangErrorCode.AccountDuplicateReallocs,
     "The account was duplicated for more than one reallocation",
   ],
+  [
+    LangErrorCode.AccountNotBuffer,
+    "The given account is not a buffer account",
+  ],
 
   // Miscellaneous
   [
@@ -676,6 +730,7 @@ export const LangErrorMessage = new Map<number, string>([
     LangErrorCode.InvalidNumericConversion,
     "The program could not perform the numeric conversion, out of range integral type conversion attempted",
   ],
+  [LangErrorCode.MathOverflow, "Checked arithmetic operation overflowed"],
 
   // Deprecated
   [
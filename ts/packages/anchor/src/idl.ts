@@ -58,6 +58,7 @@ export type IdlInstructionAccount = {
   signer?: boolean;
   optional?: boolean;
   address?: string;
+  addresses?: string[];
   pda?: IdlPda;
   relations?: string[];
 };
@@ -93,6 +94,7 @@ export type IdlSeedAccount = {
 export type IdlAccount = {
   name: string;
   discriminator: IdlDiscriminator;
+  generics?: IdlGenericArg[];
 };
 
 export type IdlEvent = {
@@ -124,6 +126,7 @@ export type IdlTypeDef = {
   serialization?: IdlSerialization;
   repr?: IdlRepr;
   generics?: IdlTypeDefGeneric[];
+  layout?: IdlLayout;
   type: IdlTypeDefTy;
 };
 
@@ -152,6 +155,16 @@ export type IdlReprModifier = {
   align?: number;
 };
 
+export type IdlLayout = {
+  size: number;
+  offsets: IdlFieldOffset[];
+};
+
+export type IdlFieldOffset = {
+  name: string;
+  offset: number;
+};
+
 export type IdlTypeDefGeneric = IdlTypeDefGenericType | IdlTypeDefGenericConst;
 
 export type IdlTypeDefGenericType = {